@@ -110,6 +110,14 @@ impl Database {
     }
 
     async fn delete_user(&self, _id: i64) {}
+
+    async fn send_welcome_email(&self, _user_id: i64) {}
+}
+
+async fn log_created(db: &Database, user: &User) {
+    if db.get_user(user.id).await.is_some() {
+        println!("created user {}", user.id);
+    }
 }
 
 /// Get a user by ID
@@ -128,7 +136,13 @@ async fn list_users(query: Query<Pagination>, db: State<Database>) -> Vec<User>
 /// Create a new user
 #[post("/")]
 async fn create_user(body: CreateUser, db: Dep<Database>) -> User {
-    db.create_user(&body).await
+    let user = db.create_user(&body).await;
+    // `&db` is a `&Database`; a clone of the `Dep` is a cheap handle the task can own
+    log_created(&db, &user).await;
+    let mailer = db.clone();
+    let id = user.id;
+    tokio::spawn(async move { mailer.send_welcome_email(id).await });
+    user
 }
 
 /// Delete a user by ID
@@ -0,0 +1,31 @@
+use hayai::prelude::*;
+
+/// A todo item; without the `openapi` feature it derives no `JsonSchema`
+#[api_model]
+#[derive(Debug, Clone)]
+pub struct Todo {
+    pub id: i64,
+    /// Without the `validation` feature the rule is accepted but not enforced
+    #[validate(min_length = 1)]
+    pub title: String,
+}
+
+#[api_model]
+pub struct TodoFilter {
+    pub limit: Option<usize>,
+}
+
+#[get("/todos")]
+async fn list_todos(filter: Query<TodoFilter>) -> Vec<Todo> {
+    let todos = vec![Todo { id: 1, title: "Ship it".to_string() }, Todo { id: 2, title: "Slim it".to_string() }];
+    todos.into_iter().take(filter.limit.unwrap_or(usize::MAX)).collect()
+}
+
+#[post("/todos")]
+async fn create_todo(body: Todo) -> Todo {
+    body
+}
+
+pub fn app() -> HayaiApp {
+    HayaiApp::new().include(HayaiRouter::new("").route(__HAYAI_ROUTE_LIST_TODOS).route(__HAYAI_ROUTE_CREATE_TODO))
+}
@@ -0,0 +1,4 @@
+#[tokio::main]
+async fn main() {
+    hayai_slim::app().serve("0.0.0.0:8000").await;
+}
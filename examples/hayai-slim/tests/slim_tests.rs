@@ -0,0 +1,48 @@
+use hayai::axum;
+use serde_json::{json, Value};
+use std::process::Command;
+
+async fn spawn() -> String {
+    let app = hayai_slim::app().into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_serves_routes_without_default_features() {
+    let base = spawn().await;
+    let res = reqwest::get(format!("{base}/todos?limit=1")).await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.json::<Value>().await.unwrap(), json!([{ "id": 1, "title": "Ship it" }]));
+
+    // `#[validate]` rules aren't enforced without the `validation` feature
+    let res = reqwest::Client::new().post(format!("{base}/todos")).json(&json!({ "id": 3, "title": "" })).send().await.unwrap();
+    assert_eq!(res.status(), 201);
+
+    // Malformed bodies are still rejected
+    let res = reqwest::Client::new().post(format!("{base}/todos")).json(&json!({ "id": "three" })).send().await.unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_no_docs_routes() {
+    let base = spawn().await;
+    for path in ["/docs", "/openapi.json"] {
+        assert_eq!(reqwest::get(format!("{base}{path}")).await.unwrap().status(), 404, "{path}");
+    }
+}
+
+#[test]
+fn test_no_schemars_in_dependency_tree() {
+    let output = Command::new(env!("CARGO"))
+        .args(["tree", "--offline", "-e", "normal"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let tree = String::from_utf8(output.stdout).unwrap();
+    assert!(tree.contains("hayai v"), "{tree}");
+    assert!(!tree.contains("schemars"), "{tree}");
+}
@@ -2,6 +2,14 @@ use proc_macro::TokenStream;
 use quote::{quote, format_ident};
 use syn::{parse_macro_input, ItemFn, ItemStruct, ItemEnum, FnArg, PatType, Type, PathSegment, LitStr, LitInt};
 
+/// hayai's `openapi` feature, forwarded: without it models derive no `JsonSchema` and
+/// submit no `SchemaInfo`, and routes document no query parameters
+const OPENAPI: bool = cfg!(feature = "openapi");
+
+/// hayai's `validation` feature, forwarded: without it models get no `Validate` impl and
+/// routes don't validate their bodies
+const VALIDATION: bool = cfg!(feature = "validation");
+
 fn extract_inner_type(seg: &PathSegment) -> Option<&Type> {
     if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
         if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
@@ -20,6 +28,16 @@ fn is_dep_type(ty: &Type) -> bool {
     false
 }
 
+/// `TenantScoped<T>`, the argument of `Dep<TenantScoped<T>>`
+fn is_tenant_scoped_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "TenantScoped";
+        }
+    }
+    false
+}
+
 fn is_state_type(ty: &Type) -> bool {
     if let Type::Path(tp) = ty {
         if let Some(seg) = tp.path.segments.last() {
@@ -29,6 +47,87 @@ fn is_state_type(ty: &Type) -> bool {
     false
 }
 
+/// Request metadata extracted from the request parts: `ClientIp`, `ConnectionInfo`,
+/// `Deadline`, `Draining`, `Locale`, `Parent<T>`, `RequestParts`, `Tenant`, `Tx`
+fn is_request_info_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return ["ClientIp", "ConnectionInfo", "Deadline", "Draining", "Locale", "Parent", "Precondition", "Subject", "RequestParts", "Tenant", "Tx"].iter().any(|name| seg.ident == name);
+        }
+    }
+    false
+}
+
+/// `Scoped<T>`, with `T` when it is `Scoped<Option<T>>`
+fn get_scoped_type(ty: &Type) -> Option<Option<&Type>> {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            if seg.ident == "Scoped" {
+                return Some(extract_inner_type(seg).and_then(get_option_inner_type));
+            }
+        }
+    }
+    None
+}
+
+/// An error when `ty` is one of the wrapper types but lacks the type inside it, e.g. a bare `Dep`
+fn missing_type_argument(ty: &Type) -> Option<syn::Error> {
+    let Type::Path(tp) = ty else { return None };
+    let seg = tp.path.segments.last()?;
+    let example = match seg.ident.to_string().as_str() {
+        "Dep" => "Dep<Database>",
+        "State" => "State<Config>",
+        "Query" => "Query<Filters>",
+        "Auth" => "Auth<Claims>",
+        "Scoped" => "Scoped<Tenant>",
+        _ => return None,
+    };
+    match extract_inner_type(seg) {
+        None => Some(syn::Error::new_spanned(ty, format!("`{}` needs the type it wraps, e.g. `{}`", seg.ident, example))),
+        Some(Type::Reference(r)) if seg.ident == "Dep" || seg.ident == "State" => Some(syn::Error::new_spanned(
+            r,
+            format!("`{}` takes the registered type itself, not a reference to it, e.g. `{}`", seg.ident, example),
+        )),
+        Some(_) => None,
+    }
+}
+
+fn is_auth_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "Auth";
+        }
+    }
+    false
+}
+
+fn is_precondition_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "Precondition";
+        }
+    }
+    false
+}
+
+fn is_locale_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "Locale";
+        }
+    }
+    false
+}
+
+fn is_deadline_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "Deadline";
+        }
+    }
+    false
+}
+
 fn is_query_type(ty: &Type) -> bool {
     if let Type::Path(tp) = ty {
         if let Some(seg) = tp.path.segments.last() {
@@ -38,6 +137,560 @@ fn is_query_type(ty: &Type) -> bool {
     false
 }
 
+/// `rename_all` rules supported by serde
+const RENAME_RULES: &[&str] = &[
+    "lowercase", "UPPERCASE", "PascalCase", "camelCase", "snake_case",
+    "SCREAMING_SNAKE_CASE", "kebab-case", "SCREAMING-KEBAB-CASE",
+];
+
+/// Apply a serde `rename_all` rule to a snake_case field name
+fn rename_field(name: &str, rule: &str) -> String {
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.replace('_', "-").to_ascii_uppercase(),
+        "PascalCase" | "camelCase" => {
+            let mut out = String::new();
+            let mut capitalize = rule == "PascalCase";
+            for ch in name.chars() {
+                if ch == '_' {
+                    capitalize = true;
+                } else if capitalize {
+                    out.push(ch.to_ascii_uppercase());
+                    capitalize = false;
+                } else {
+                    out.push(ch);
+                }
+            }
+            out
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Apply a serde `rename_all` rule to a PascalCase variant name
+fn rename_variant(name: &str, rule: &str) -> String {
+    match rule {
+        "lowercase" => name.to_ascii_lowercase(),
+        "UPPERCASE" => name.to_ascii_uppercase(),
+        "camelCase" => name[..1].to_ascii_lowercase() + &name[1..],
+        "PascalCase" => name.to_string(),
+        _ => {
+            let mut snake = String::new();
+            for (i, ch) in name.char_indices() {
+                if i > 0 && ch.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(ch.to_ascii_lowercase());
+            }
+            rename_field(&snake, rule)
+        }
+    }
+}
+
+/// Read a `#[serde(key = "value")]` string from a list of attributes
+fn serde_str_attr(attrs: &[syn::Attribute], key: &str) -> Option<String> {
+    attr_entry(attrs, "serde", key).flatten()
+}
+
+/// Find `key` in `#[attr_name(..)]` attributes: `Some(Some(value))` for `key = "value"`,
+/// `Some(None)` for a bare `key` or a value that isn't a string
+fn attr_entry(attrs: &[syn::Attribute], attr_name: &str, key: &str) -> Option<Option<String>> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident(attr_name)) {
+        let _ = attr.parse_nested_meta(|meta| {
+            let is_key = meta.path.is_ident(key);
+            if meta.input.peek(syn::Token![=]) {
+                let value: syn::Expr = meta.value()?.parse()?;
+                if is_key {
+                    found = Some(match value {
+                        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Some(lit.value()),
+                        _ => None,
+                    });
+                }
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|_| Ok(()))?;
+            } else if is_key {
+                found = Some(None);
+            }
+            Ok(())
+        });
+    }
+    found
+}
+
+/// The name a field has on the wire (and in the schema): its `#[serde(rename)]`, or the
+/// struct's `rename_all` rule applied to the Rust name
+fn wire_field_name(field: &syn::Field, rename_all: Option<&str>) -> String {
+    let ident = field.ident.as_ref().unwrap().to_string();
+    let ident = ident.strip_prefix("r#").unwrap_or(&ident).to_string();
+    serde_str_attr(&field.attrs, "rename")
+        .unwrap_or_else(|| rename_all.map(|rule| rename_field(&ident, rule)).unwrap_or(ident))
+}
+
+/// The expression passing a wrapper local on to the handler (`mut x` binds as `x`)
+fn call_arg(pat: &syn::Pat) -> proc_macro2::TokenStream {
+    match pat {
+        syn::Pat::Ident(pi) => {
+            let ident = &pi.ident;
+            quote!(#ident)
+        }
+        other => quote!(#other),
+    }
+}
+
+fn is_form_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "Form";
+        }
+    }
+    false
+}
+
+fn is_multipart_type(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            return seg.ident == "Multipart";
+        }
+    }
+    false
+}
+
+/// `SignedPayload<T, S>`: the payload `T` and the signature scheme `S`
+fn get_signed_payload_types(ty: &Type) -> Option<(&Type, &Type)> {
+    let Type::Path(tp) = ty else { return None };
+    let seg = tp.path.segments.last().filter(|seg| seg.ident == "SignedPayload")?;
+    let syn::PathArguments::AngleBracketed(args) = &seg.arguments else { return None };
+    let mut types = args.args.iter().filter_map(|a| match a {
+        syn::GenericArgument::Type(t) => Some(t),
+        _ => None,
+    });
+    Some((types.next()?, types.next()?))
+}
+
+/// Parse `#[multipart_schema(file: binary, caption: string)]` into (field, type) pairs
+fn parse_multipart_schema(attr: &syn::Attribute) -> syn::Result<Vec<(String, String)>> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let mut fields = Vec::new();
+        while !input.is_empty() {
+            let name: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![:]>()?;
+            let kind: syn::Ident = input.parse()?;
+            fields.push((name.to_string(), kind.to_string()));
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
+        }
+        Ok(fields)
+    })
+}
+
+/// Parse `#[responses(401 = "UnauthorizedError", ..)]` into (status, component name) pairs
+fn parse_responses(attr: &syn::Attribute) -> syn::Result<Vec<(u16, String)>> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let mut responses = Vec::new();
+        while !input.is_empty() {
+            let status: LitInt = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            let name: LitStr = input.parse()?;
+            responses.push((status.base10_parse()?, name.value()));
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
+        }
+        Ok(responses)
+    })
+}
+
+/// Parse `#[status(201)]`, rejecting codes outside 100..=599
+fn parse_status(attr: &syn::Attribute) -> syn::Result<u16> {
+    let lit: LitInt = attr.parse_args()
+        .map_err(|e| syn::Error::new(e.span(), "expected a status code: #[status(201)]"))?;
+    match lit.base10_parse::<u16>() {
+        Ok(status) if (100..=599).contains(&status) => Ok(status),
+        _ => Err(syn::Error::new_spanned(&lit, "HTTP status codes are between 100 and 599")),
+    }
+}
+
+/// Parse `#[version(2)]` into the API version it serves
+fn parse_version(attr: &syn::Attribute) -> syn::Result<u32> {
+    let lit: LitInt = attr.parse_args()
+        .map_err(|e| syn::Error::new(e.span(), "expected an API version: #[version(2)]"))?;
+    match lit.base10_parse::<u32>() {
+        Ok(version) if version > 0 => Ok(version),
+        _ => Err(syn::Error::new_spanned(&lit, "API versions are positive integers")),
+    }
+}
+
+/// The schema `#[response(schema = ..)]` documents the response with
+enum ResponseSchema {
+    /// `"SomeModel"`: a registered component, the element of a list or page
+    Named(String),
+    /// `inline({ "type": "object", .. })`: the whole response body, as `json!` tokens
+    Inline(proc_macro2::TokenStream),
+}
+
+/// Parse `#[response(schema = "SomeModel")]`, `#[response(schema = inline({..}))]` or
+/// `#[response(tuple)]`
+fn parse_response(attr: &syn::Attribute) -> syn::Result<(Option<ResponseSchema>, bool)> {
+    let mut schema = None;
+    let mut tuple = false;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("schema") {
+            let value = meta.value()?;
+            if value.peek(LitStr) {
+                schema = Some(ResponseSchema::Named(value.parse::<LitStr>()?.value()));
+            } else {
+                let ident: syn::Ident = value.parse()?;
+                if ident != "inline" {
+                    return Err(syn::Error::new_spanned(ident, "expected `\"Model\"` or `inline({..})`"));
+                }
+                let content;
+                syn::parenthesized!(content in value);
+                schema = Some(ResponseSchema::Inline(content.parse()?));
+            }
+            Ok(())
+        } else if meta.path.is_ident("tuple") {
+            tuple = true;
+            Ok(())
+        } else {
+            Err(meta.error("expected `schema = \"..\"` or `tuple`"))
+        }
+    })?;
+    if schema.is_none() && !tuple {
+        return Err(syn::Error::new_spanned(attr, "expected `schema = \"..\"` or `tuple`"));
+    }
+    Ok((schema, tuple))
+}
+
+/// Parse `#[response_header("ETag", "string", "Version of the returned item")]`
+fn parse_response_header(attr: &syn::Attribute) -> syn::Result<(String, String, String)> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let name: LitStr = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let kind: LitStr = input.parse()?;
+        if !matches!(kind.value().as_str(), "string" | "integer" | "number" | "boolean") {
+            return Err(syn::Error::new_spanned(&kind, "expected one of \"string\", \"integer\", \"number\", \"boolean\""));
+        }
+        input.parse::<syn::Token![,]>()?;
+        let description: LitStr = input.parse()?;
+        let _ = input.parse::<Option<syn::Token![,]>>()?;
+        Ok((name.value(), kind.value(), description.value()))
+    })
+}
+
+/// `#[link(name = "GetUserById", operation = get_user, parameters(id = "$response.body#/id"))]`
+struct LinkAttr {
+    name: LitStr,
+    /// The target's `operationId`
+    operation_id: String,
+    /// The target handler when given as a path, referenced so a typo fails to compile
+    handler: Option<syn::Path>,
+    parameters: Vec<(String, LitStr)>,
+}
+
+/// Parse `#[link(..)]`; `operation` is a handler path or an `operationId` string
+fn parse_link(attr: &syn::Attribute) -> syn::Result<LinkAttr> {
+    let mut name = None;
+    let mut operation = None;
+    let mut parameters = Vec::new();
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?);
+        } else if meta.path.is_ident("operation") {
+            operation = Some(match meta.value()?.parse::<syn::Expr>()? {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => (lit.value(), None),
+                syn::Expr::Path(p) => match p.path.segments.last() {
+                    Some(last) => (last.ident.to_string(), Some(p.path.clone())),
+                    None => return Err(meta.error("expected a handler")),
+                },
+                other => return Err(syn::Error::new_spanned(other, "expected a handler path or an operationId string")),
+            });
+        } else if meta.path.is_ident("parameters") {
+            meta.parse_nested_meta(|param| {
+                let key = param.path.get_ident()
+                    .ok_or_else(|| param.error("expected a parameter name"))?
+                    .to_string();
+                parameters.push((key, param.value()?.parse::<LitStr>()?));
+                Ok(())
+            })?;
+        } else {
+            return Err(meta.error("unknown link option, expected `name`, `operation` or `parameters`"));
+        }
+        Ok(())
+    })?;
+    let name = name.ok_or_else(|| syn::Error::new_spanned(attr, "missing `name = \"..\"`"))?;
+    let (operation_id, handler) = operation.ok_or_else(|| syn::Error::new_spanned(attr, "missing `operation = ..`"))?;
+    Ok(LinkAttr { name, operation_id, handler, parameters })
+}
+
+/// Parse `#[concurrency_limit(4)]`, `#[concurrency_limit(4, queue_timeout_ms = 500)]` or
+/// `#[concurrency_limit(4, fail_fast)]` into a `ConcurrencyLimit` expression
+fn parse_concurrency_limit(attr: &syn::Attribute) -> syn::Result<proc_macro2::TokenStream> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let max: syn::LitInt = input.parse()?;
+        if max.base10_parse::<usize>()? == 0 {
+            return Err(syn::Error::new_spanned(&max, "the limit must be at least 1"));
+        }
+        let mut limit = quote! { hayai::concurrency::ConcurrencyLimit::queue(#max) };
+        while input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key == "fail_fast" {
+                limit = quote! { hayai::concurrency::ConcurrencyLimit::fail_fast(#max) };
+            } else if key == "queue_timeout_ms" {
+                input.parse::<syn::Token![=]>()?;
+                let ms: syn::LitInt = input.parse()?;
+                ms.base10_parse::<u64>()?;
+                limit = quote! { #limit.queue_timeout(std::time::Duration::from_millis(#ms)) };
+            } else {
+                return Err(syn::Error::new_spanned(key, "expected `fail_fast` or `queue_timeout_ms = ..`"));
+            }
+        }
+        Ok(limit)
+    })
+}
+
+/// Parse a duration such as `"500ms"`, `"30s"`, `"5m"` or `"1h"` into milliseconds
+fn parse_duration_ms(lit: &LitStr) -> syn::Result<u64> {
+    let value = lit.value();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let factor = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return Err(syn::Error::new_spanned(lit, "expected a duration such as \"500ms\", \"30s\", \"5m\" or \"1h\"")),
+    };
+    number.parse::<u64>().ok()
+        .filter(|n| *n > 0)
+        .map(|n| n * factor)
+        .ok_or_else(|| syn::Error::new_spanned(lit, "expected a positive duration such as \"30s\""))
+}
+
+/// `#[cache(ttl = "30s", vary = ["query", "header:Accept-Language"], allow_authenticated)]`
+struct CacheAttr {
+    ttl_ms: u64,
+    vary: Vec<proc_macro2::TokenStream>,
+    allow_authenticated: bool,
+}
+
+/// Parse `#[cache(..)]`; `vary` defaults to the whole query string
+fn parse_cache(attr: &syn::Attribute) -> syn::Result<CacheAttr> {
+    let mut ttl_ms = None;
+    let mut vary = None;
+    let mut allow_authenticated = false;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("ttl") {
+            ttl_ms = Some(parse_duration_ms(&meta.value()?.parse::<LitStr>()?)?);
+        } else if meta.path.is_ident("vary") {
+            vary = Some(parse_vary(&meta)?);
+        } else if meta.path.is_ident("allow_authenticated") {
+            allow_authenticated = true;
+        } else {
+            return Err(meta.error("unknown cache option, expected `ttl`, `vary` or `allow_authenticated`"));
+        }
+        Ok(())
+    })?;
+    let ttl_ms = ttl_ms.ok_or_else(|| syn::Error::new_spanned(attr, "missing `ttl = \"..\"`"))?;
+    let vary = vary.unwrap_or_else(|| vec![quote! { hayai::cache::Vary::Query }]);
+    Ok(CacheAttr { ttl_ms, vary, allow_authenticated })
+}
+
+/// Parse `vary = ["query", "query:page", "header:Accept-Language"]` into `Vary` expressions
+fn parse_vary(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let list: syn::ExprArray = meta.value()?.parse()?;
+    let mut entries = Vec::new();
+    for elem in &list.elems {
+        let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) = elem else {
+            return Err(syn::Error::new_spanned(elem, "expected a string"));
+        };
+        let value = lit.value();
+        entries.push(match value.split_once(':') {
+            None if value == "query" => quote! { hayai::cache::Vary::Query },
+            Some(("query", name)) if !name.is_empty() => quote! { hayai::cache::Vary::QueryParam(#name) },
+            Some(("header", name)) if !name.is_empty() => {
+                let name = name.to_ascii_lowercase();
+                quote! { hayai::cache::Vary::Header(#name) }
+            }
+            _ => return Err(syn::Error::new_spanned(lit, "expected \"query\", \"query:<name>\" or \"header:<Name>\"")),
+        });
+    }
+    Ok(entries)
+}
+
+/// Parse `#[coalesce]` or `#[coalesce(vary = [..])]` into the `Vary` expressions of the key;
+/// like `#[cache]`, `vary` defaults to the whole query string
+fn parse_coalesce(attr: &syn::Attribute) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let mut vary = None;
+    if !matches!(attr.meta, syn::Meta::Path(_)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("vary") {
+                vary = Some(parse_vary(&meta)?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown coalesce option, expected `vary`"))
+            }
+        })?;
+    }
+    Ok(vary.unwrap_or_else(|| vec![quote! { hayai::cache::Vary::Query }]))
+}
+
+/// Parse `#[emit_event("user.created")]` or `#[emit_event("user.created", include_body)]`
+/// into an `EmitEvent` expression
+fn parse_emit_event(attr: &syn::Attribute) -> syn::Result<proc_macro2::TokenStream> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let name: LitStr = input.parse()?;
+        if name.value().is_empty() {
+            return Err(syn::Error::new_spanned(&name, "the event name must not be empty"));
+        }
+        let mut include_body = false;
+        while input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key != "include_body" {
+                return Err(syn::Error::new_spanned(key, "expected `include_body`"));
+            }
+            include_body = true;
+        }
+        Ok(quote! { hayai::events::EmitEvent { name: #name, include_body: #include_body } })
+    })
+}
+
+/// Parse `#[audited(resource = "user")]` into the resource name
+fn parse_audited(attr: &syn::Attribute) -> syn::Result<LitStr> {
+    let mut resource: Option<LitStr> = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("resource") {
+            let lit: LitStr = meta.value()?.parse()?;
+            if lit.value().is_empty() {
+                return Err(syn::Error::new_spanned(&lit, "the resource name must not be empty"));
+            }
+            resource = Some(lit);
+            Ok(())
+        } else {
+            Err(meta.error("expected `resource = \"..\"`"))
+        }
+    })?;
+    resource.ok_or_else(|| syn::Error::new_spanned(attr, "missing `resource = \"..\"`"))
+}
+
+/// Parse `#[request_body(name = "CreateUserBody", description = "..")]`, both optional
+fn parse_request_body(attr: &syn::Attribute) -> syn::Result<(Option<LitStr>, Option<LitStr>)> {
+    let mut name: Option<LitStr> = None;
+    let mut description: Option<LitStr> = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("name") {
+            let lit: LitStr = meta.value()?.parse()?;
+            // Component keys are limited to these characters by the OpenAPI spec
+            let valid = !lit.value().is_empty()
+                && lit.value().chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+            if !valid {
+                return Err(syn::Error::new_spanned(&lit, "a request body name may only contain letters, digits, `.`, `-` and `_`"));
+            }
+            name = Some(lit);
+            Ok(())
+        } else if meta.path.is_ident("description") {
+            description = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `name = \"..\"` or `description = \"..\"`"))
+        }
+    })?;
+    Ok((name, description))
+}
+
+/// `application/xml`, `text/xml` and `application/*+xml`
+fn is_xml_media_type(media_type: &str) -> bool {
+    let media_type = media_type.trim().to_ascii_lowercase();
+    media_type == "application/xml" || media_type == "text/xml" || (media_type.starts_with("application/") && media_type.ends_with("+xml"))
+}
+
+/// Parse `#[produces("application/xml")]` or `#[produces("application/xml", root = "user")]`
+fn parse_produces(attr: &syn::Attribute) -> syn::Result<(String, Option<String>)> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let media_type: LitStr = input.parse()?;
+        if !is_xml_media_type(&media_type.value()) {
+            return Err(syn::Error::new_spanned(&media_type, "#[produces] supports XML media types; responses are JSON without it"));
+        }
+        let mut root = None;
+        while input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key != "root" {
+                return Err(syn::Error::new_spanned(key, "expected `root = \"..\"`"));
+            }
+            input.parse::<syn::Token![=]>()?;
+            root = Some(input.parse::<LitStr>()?.value());
+        }
+        Ok((media_type.value(), root))
+    })
+}
+
+/// Parse `#[openapi_extension("x-internal-owner", "payments-team")]`; the value is any
+/// `serde_json::json!` input: a string, number or object literal
+fn parse_openapi_extension(attr: &syn::Attribute) -> syn::Result<(LitStr, proc_macro2::TokenStream)> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let key: LitStr = input.parse()?;
+        if !key.value().starts_with("x-") {
+            return Err(syn::Error::new_spanned(&key, "OpenAPI extension names must start with \"x-\""));
+        }
+        input.parse::<syn::Token![,]>()?;
+        let value: proc_macro2::TokenStream = input.parse()?;
+        if value.is_empty() {
+            return Err(input.error("expected a value"));
+        }
+        Ok((key, value))
+    })
+}
+
+/// `#[param(id, minimum = 1)]`: constraints on a path parameter, checked after extraction
+struct ParamConstraints {
+    name: syn::Ident,
+    minimum: Option<i64>,
+    maximum: Option<i64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    pattern: Option<String>,
+}
+
+/// Parse `#[param(name, minimum = 1, maximum = 10)]` or `#[param(slug, pattern = "..", max_length = 40)]`
+fn parse_param_constraints(attr: &syn::Attribute) -> syn::Result<ParamConstraints> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let name: syn::Ident = input.parse()?;
+        let mut constraints = ParamConstraints {
+            name,
+            minimum: None,
+            maximum: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+        };
+        while input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            let signed = |input: syn::parse::ParseStream| -> syn::Result<i64> {
+                let negative = input.parse::<Option<syn::Token![-]>>()?.is_some();
+                let value: i64 = input.parse::<LitInt>()?.base10_parse()?;
+                Ok(if negative { -value } else { value })
+            };
+            match key.to_string().as_str() {
+                "minimum" => constraints.minimum = Some(signed(input)?),
+                "maximum" => constraints.maximum = Some(signed(input)?),
+                "min_length" => constraints.min_length = Some(input.parse::<LitInt>()?.base10_parse()?),
+                "max_length" => constraints.max_length = Some(input.parse::<LitInt>()?.base10_parse()?),
+                "pattern" => constraints.pattern = Some(input.parse::<LitStr>()?.value()),
+                _ => return Err(syn::Error::new_spanned(
+                    &key,
+                    "unknown param constraint, expected one of minimum, maximum, min_length, max_length, pattern",
+                )),
+            }
+        }
+        Ok(constraints)
+    })
+}
+
 fn get_type_name(ty: &Type) -> String {
     if let Type::Path(tp) = ty {
         if let Some(seg) = tp.path.segments.last() {
@@ -63,20 +716,286 @@ fn get_result_ok_type(ty: &Type) -> Option<&Type> {
     None
 }
 
+/// Check if the type is Vec<T> and return T
+fn get_vec_inner_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            if seg.ident == "Vec" {
+                return extract_inner_type(seg);
+            }
+        }
+    }
+    None
+}
+
+/// Check if the type is Page<T> or CursorPage<T> and return T with the envelope kind
+fn get_page_inner_type(ty: &Type) -> Option<(&Type, proc_macro2::TokenStream)> {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            let kind = match seg.ident.to_string().as_str() {
+                "Page" => quote! { hayai::pagination::PageKind::Page },
+                "CursorPage" => quote! { hayai::pagination::PageKind::Cursor },
+                _ => return None,
+            };
+            return extract_inner_type(seg).map(|inner| (inner, kind));
+        }
+    }
+    None
+}
+
+/// Check if the type is BulkResult<T> and return T
+fn get_bulk_inner_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            if seg.ident == "BulkResult" {
+                return extract_inner_type(seg);
+            }
+        }
+    }
+    None
+}
+
+/// Check if a body is `Vec<T>` or a `HashMap`/`BTreeMap` keyed by `String`, and return `T`
+/// with the collection kind
+fn get_body_collection(ty: &Type) -> Option<(&Type, proc_macro2::TokenStream)> {
+    if let Some(inner) = get_vec_inner_type(ty) {
+        return Some((inner, quote! { hayai::openapi::BodyCollection::List }));
+    }
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            if seg.ident == "HashMap" || seg.ident == "BTreeMap" {
+                if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                    let mut types = args.args.iter().filter_map(|a| match a {
+                        syn::GenericArgument::Type(t) => Some(t),
+                        _ => None,
+                    });
+                    if let (Some(key), Some(value)) = (types.next(), types.next()) {
+                        if get_type_name(key) == "String" {
+                            return Some((value, quote! { hayai::openapi::BodyCollection::Map }));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// `V` of a `HashMap<K, V>` or `BTreeMap<K, V>`
+fn get_map_value_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            if seg.ident == "HashMap" || seg.ident == "BTreeMap" {
+                if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                    return args.args.iter()
+                        .filter_map(|a| match a {
+                            syn::GenericArgument::Type(t) => Some(t),
+                            _ => None,
+                        })
+                        .nth(1);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A response its type names can't document: a map, a tuple, or a `Vec` of `Option`s,
+/// maps, tuples or `Vec`s
+fn needs_response_schema_fn(ty: &Type) -> bool {
+    let is_shape = |t: &Type| {
+        matches!(t, Type::Tuple(tuple) if !tuple.elems.is_empty())
+            || (get_map_value_type(t).is_some() && !is_free_form_type(t))
+    };
+    is_shape(ty) || get_vec_inner_type(ty).is_some_and(|inner| {
+        is_shape(inner) || get_option_inner_type(inner).is_some() || get_vec_inner_type(inner).is_some()
+    })
+}
+
+/// An `impl Trait` response, possibly inside `Vec`, `Option` or a map
+fn contains_opaque(ty: &Type) -> bool {
+    match ty {
+        Type::ImplTrait(_) => true,
+        _ => [get_option_inner_type(ty), get_vec_inner_type(ty), get_map_value_type(ty)].into_iter()
+            .flatten()
+            .any(contains_opaque),
+    }
+}
+
+fn contains_tuple(ty: &Type) -> bool {
+    match ty {
+        Type::Tuple(tuple) => !tuple.elems.is_empty(),
+        _ => [get_option_inner_type(ty), get_vec_inner_type(ty), get_map_value_type(ty)].into_iter()
+            .flatten()
+            .any(contains_tuple),
+    }
+}
+
+/// Expression building the response schema of `ty`; tuples are positional arrays with
+/// `#[response(tuple)]`, and an error otherwise
+fn response_schema_expr(ty: &Type, tuples: bool) -> syn::Result<proc_macro2::TokenStream> {
+    if let Type::Tuple(tuple) = ty {
+        if tuple.elems.is_empty() {
+            return Ok(quote! { hayai::serde_json::json!({ "type": "null" }) });
+        }
+        if !tuples {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "tuple responses have no schema; return a named #[api_model] struct, or add #[response(tuple)] to document a positional array",
+            ));
+        }
+        let items = tuple.elems.iter().map(|t| response_schema_expr(t, tuples)).collect::<syn::Result<Vec<_>>>()?;
+        let len = items.len();
+        return Ok(quote! {{
+            let items: Vec<hayai::serde_json::Value> = vec![#(#items),*];
+            hayai::serde_json::json!({ "type": "array", "prefixItems": items, "minItems": #len, "maxItems": #len })
+        }});
+    }
+    if is_free_form_type(ty) {
+        return Ok(quote! { hayai::openapi::type_schema("Value", None) });
+    }
+    if let Some(inner) = get_option_inner_type(ty) {
+        let inner = response_schema_expr(inner, tuples)?;
+        return Ok(quote! { hayai::openapi::nullable(#inner) });
+    }
+    if let Some(inner) = get_vec_inner_type(ty) {
+        let inner = response_schema_expr(inner, tuples)?;
+        return Ok(quote! { hayai::openapi::BodyCollection::List.schema(#inner) });
+    }
+    if let Some(value) = get_map_value_type(ty) {
+        let value = response_schema_expr(value, tuples)?;
+        return Ok(quote! { hayai::openapi::BodyCollection::Map.schema(#value) });
+    }
+    let type_name = get_type_name(ty);
+    Ok(quote! {{
+        use hayai::__component::{ViaModel as _, ViaTypeName as _};
+        hayai::openapi::type_schema(#type_name, (&&hayai::__component::Probe::<#ty>(#type_name, std::marker::PhantomData)).model_component())
+    }})
+}
+
 /// Check if the type is Vec<T> and return the inner type name
 fn get_vec_inner_type_name(ty: &Type) -> Option<String> {
+    get_vec_inner_type(ty).map(response_type_name)
+}
+
+/// Untyped JSON: `serde_json::Value` or a `HashMap`/`BTreeMap` of them
+fn is_free_form_type(ty: &Type) -> bool {
     if let Type::Path(tp) = ty {
         if let Some(seg) = tp.path.segments.last() {
-            if seg.ident == "Vec" {
-                if let Some(inner) = extract_inner_type(seg) {
-                    return Some(get_type_name(inner));
+            if seg.ident == "Value" {
+                return true;
+            }
+            if seg.ident == "HashMap" || seg.ident == "BTreeMap" {
+                if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                    return matches!(args.args.iter().nth(1), Some(syn::GenericArgument::Type(value)) if get_type_name(value) == "Value");
                 }
             }
         }
     }
+    false
+}
+
+/// The name a response type is documented under; free-form JSON is `Value`, which the
+/// spec renders as an open object instead of a `$ref`
+fn response_type_name(ty: &Type) -> String {
+    if is_free_form_type(ty) { "Value".to_string() } else { get_type_name(ty) }
+}
+
+/// Runtime expression for the component name of `ty`: its `ComponentName` for models,
+/// otherwise the type name
+fn component_name_expr(ty: &Type) -> proc_macro2::TokenStream {
+    let type_name = get_type_name(ty);
+    quote! {{
+        use hayai::__component::{ViaModel as _, ViaTypeName as _};
+        (&&hayai::__component::Probe::<#ty>(#type_name, std::marker::PhantomData)).component_name()
+    }}
+}
+
+/// Check if the type is Option<T> and return T
+fn get_option_inner_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(tp) = ty {
+        if let Some(seg) = tp.path.segments.last() {
+            if seg.ident == "Option" {
+                return extract_inner_type(seg);
+            }
+        }
+    }
     None
 }
 
+/// `Decimal` fields of the `decimal` feature
+fn is_decimal_type(ty: &Type) -> bool {
+    get_type_name(ty) == "Decimal"
+}
+
+/// A decimal bound from `#[validate(minimum = "0.5")]`, parsed at compile time into the
+/// `Decimal` expression, its text and its value for the schema
+fn parse_decimal_bound(input: syn::parse::ParseStream) -> syn::Result<(proc_macro2::TokenStream, String, f64)> {
+    let (text, span) = match input.parse::<syn::Lit>()? {
+        syn::Lit::Str(lit) => (lit.value(), lit.span()),
+        syn::Lit::Int(lit) => (lit.base10_digits().to_string(), lit.span()),
+        other => return Err(syn::Error::new_spanned(other, "write decimal bounds as strings: minimum = \"0.01\"")),
+    };
+    let invalid = || syn::Error::new(span, format!("`{}` is not a decimal like \"19.99\"", text));
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.as_str()),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if int.is_empty() || !all_digits(int) || !all_digits(frac) || (digits.contains('.') && frac.is_empty()) {
+        return Err(invalid());
+    }
+    let scale = frac.len() as u32;
+    let mantissa: i128 = format!("{}{}", int, frac).parse().map_err(|_| invalid())?;
+    // Decimal holds a 96-bit mantissa and at most 28 decimal places
+    if scale > 28 || mantissa >= 1i128 << 96 {
+        return Err(syn::Error::new(span, format!("`{}` is out of range for a Decimal", text)));
+    }
+    let mantissa = if negative { -mantissa } else { mantissa };
+    let value: f64 = text.parse().map_err(|_| invalid())?;
+    Ok((quote! { hayai::decimal::Decimal::from_i128_with_scale(#mantissa, #scale) }, text, value))
+}
+
+/// `hayai::types` geo fields, which a model validates along with its own rules
+fn is_geo_type(ty: &Type) -> bool {
+    matches!(get_type_name(ty).as_str(), "LatLng" | "GeoJsonGeometry")
+}
+
+/// Types `#[consumes("...")]` reads as the raw request body
+fn is_raw_body_type(ty: &Type) -> bool {
+    matches!(get_type_name(ty).as_str(), "String" | "Bytes")
+}
+
+/// Whether `ty` has a lifetime argument anywhere, e.g. `Ingest<'_>` or `Vec<&'a str>`
+fn has_lifetime(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(_) => true,
+        Type::Path(tp) => tp.path.segments.iter().any(|seg| match &seg.arguments {
+            syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                syn::GenericArgument::Lifetime(_) => true,
+                syn::GenericArgument::Type(inner) => has_lifetime(inner),
+                _ => false,
+            }),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+fn is_unit_type(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(t) if t.elems.is_empty())
+}
+
+/// `()` and `NoContent`, answered without a body
+fn is_empty_response_type(ty: &Type) -> bool {
+    is_unit_type(ty) || get_type_name(ty) == "NoContent"
+}
+
+fn is_integer_type(ty: &Type) -> bool {
+    matches!(get_type_name(ty).as_str(), "i8"|"i16"|"i32"|"i64"|"i128"|"u8"|"u16"|"u32"|"u64"|"u128"|"isize"|"usize")
+}
+
 fn is_primitive_type(ty: &Type) -> bool {
     let name = get_type_name(ty);
     matches!(name.as_str(), "i8"|"i16"|"i32"|"i64"|"i128"|"u8"|"u16"|"u32"|"u64"|"u128"|"f32"|"f64"|"String"|"bool")
@@ -97,6 +1016,19 @@ fn extract_doc_comment(attrs: &[syn::Attribute]) -> String {
     lines.join("\n").trim().to_string()
 }
 
+/// With `HAYAI_MACRO_DEBUG=1`, write the expansion for `name` to a temp file and print
+/// where it went
+fn debug_expansion(name: &str, output: &proc_macro2::TokenStream) {
+    if std::env::var("HAYAI_MACRO_DEBUG").as_deref() != Ok("1") {
+        return;
+    }
+    let path = std::env::temp_dir().join(format!("hayai-expand-{}.rs", name));
+    match std::fs::write(&path, output.to_string()) {
+        Ok(()) => eprintln!("note: hayai expansion of `{}` written to {}", name, path.display()),
+        Err(e) => eprintln!("note: could not write hayai expansion of `{}`: {}", name, e),
+    }
+}
+
 fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> TokenStream {
     let path = parse_macro_input!(attr as LitStr).value();
     let input_fn = parse_macro_input!(item as ItemFn);
@@ -105,50 +1037,250 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
     let fn_sig = &input_fn.sig;
     let fn_block = &input_fn.block;
 
-    // Parse custom attributes: #[status(N)], #[tag("x")], #[security("x")], doc comments
+    // Parse custom attributes: #[status(N)], #[tag("x")], #[security("x")], #[allow_body],
+    // #[zero_copy], #[responses(N = "Schema")], #[response(schema = "Model" | inline({..}))], #[response(tuple)], #[response_header("Name", "type", "description")],
+    // #[param(id, minimum = 1)], #[response_options(exclude_none, sparse_fields)],
+    // #[link(name = "GetUserById", operation = get_user, parameters(id = "$response.body#/id"))],
+    // #[transactional], #[transactional(skip)], #[concurrency_limit(4, fail_fast)],
+    // #[cache(ttl = "30s", vary = ["query"])], #[coalesce], #[emit_event("user.created", include_body)], #[audited(resource = "user")],
+    // #[timeout("5s")], #[openapi_extension("x-internal-owner", "payments-team")], #[none_as(204)], #[operation_id("getUser")],
+    // #[consumes("application/xml")], #[produces("application/xml", root = "user")], #[version(2)], #[feature_flag("new_billing")],
+    // #[request_body(name = "CreateUserBody", description = "..")], doc comments
     let mut status_code: Option<u16> = None;
+    let mut allow_body = false;
+    let mut zero_copy = false;
+    let mut consumes: Option<String> = None;
+    let mut produces: Option<(&syn::Attribute, String, Option<String>)> = None;
+    let mut multipart_fields: Vec<(String, String)> = Vec::new();
+    let mut error_responses: Vec<(u16, String)> = Vec::new();
+    let mut response_headers: Vec<(String, String, String)> = Vec::new();
+    let mut links: Vec<LinkAttr> = Vec::new();
+    let mut transactional: Option<bool> = None;
+    let mut concurrency_limit: Option<proc_macro2::TokenStream> = None;
+    let mut cache: Option<(&syn::Attribute, CacheAttr)> = None;
+    let mut coalesce: Option<(&syn::Attribute, Vec<proc_macro2::TokenStream>)> = None;
+    let mut emit_event: Option<proc_macro2::TokenStream> = None;
+    let mut audited: Option<LitStr> = None;
+    let mut request_body: Option<(&syn::Attribute, Option<LitStr>, Option<LitStr>)> = None;
+    let mut operation_id: Option<LitStr> = None;
+    let mut timeout_ms: Option<u64> = None;
+    let mut feature_flag: Option<LitStr> = None;
+    let mut version: Option<u32> = None;
+    let mut none_as: Option<(&syn::Attribute, u16)> = None;
+    let mut openapi_extensions: Vec<(LitStr, proc_macro2::TokenStream)> = Vec::new();
+    let mut response_schema: Option<ResponseSchema> = None;
+    let mut response_tuple = false;
+    let mut param_constraints: Vec<ParamConstraints> = Vec::new();
+    let mut exclude_none = false;
+    let mut sparse_fields = false;
     let mut tags: Vec<String> = Vec::new();
-    let mut security_schemes: Vec<String> = Vec::new();
+    let mut security_schemes: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut replaces_tags = false;
+    let mut clears_security = false;
+    let mut no_envelope = false;
     let description = extract_doc_comment(&input_fn.attrs);
 
     let mut clean_attrs: Vec<&syn::Attribute> = Vec::new();
     for attr in &input_fn.attrs {
         if attr.path().is_ident("status") {
-            if let syn::Meta::List(list) = &attr.meta {
-                let tokens = list.tokens.clone();
-                if let Ok(lit) = syn::parse2::<LitInt>(tokens) {
-                    status_code = Some(lit.base10_parse().unwrap());
-                }
+            match parse_status(attr) {
+                Ok(status) => status_code = Some(status),
+                Err(e) => return e.to_compile_error().into(),
             }
         } else if attr.path().is_ident("tag") {
-            if let syn::Meta::List(list) = &attr.meta {
-                let tokens = list.tokens.clone();
-                if let Ok(lit) = syn::parse2::<LitStr>(tokens) {
+            // `#[tag("users")]` adds to the router's tags, `#[tag(replace = "admin")]` replaces them
+            let parsed = attr.parse_args_with(|input: syn::parse::ParseStream| {
+                if input.peek(LitStr) {
+                    return Ok((false, input.parse::<LitStr>()?));
+                }
+                let key: syn::Ident = input.parse()?;
+                if key != "replace" {
+                    return Err(syn::Error::new_spanned(key, "expected `replace`"));
+                }
+                input.parse::<syn::Token![=]>()?;
+                Ok((true, input.parse::<LitStr>()?))
+            });
+            match parsed {
+                Ok((replace, lit)) => {
+                    replaces_tags |= replace;
                     tags.push(lit.value());
                 }
+                Err(e) => return syn::Error::new(e.span(), "expected a tag name: #[tag(\"users\")] or #[tag(replace = \"admin\")]").to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("security") {
+            // A literal name, or a constant such as `hayai::security::BEARER`
+            match attr.parse_args::<syn::Expr>() {
+                Ok(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. })) => security_schemes.push(quote! { #lit }),
+                // `#[security(none)]` drops the schemes inherited from routers
+                Ok(syn::Expr::Path(path)) if path.path.is_ident("none") => clears_security = true,
+                Ok(syn::Expr::Path(path)) => security_schemes.push(quote! { #path }),
+                Ok(other) => return syn::Error::new_spanned(other, "expected a security scheme name: #[security(\"bearer\")] or #[security(hayai::security::BEARER)]").to_compile_error().into(),
+                Err(e) => return syn::Error::new(e.span(), "expected a security scheme name: #[security(\"bearer\")] or #[security(hayai::security::BEARER)]").to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("allow_body") {
+            allow_body = true;
+        } else if attr.path().is_ident("zero_copy") {
+            zero_copy = true;
+        } else if attr.path().is_ident("no_envelope") {
+            no_envelope = true;
+        } else if attr.path().is_ident("multipart_schema") {
+            match parse_multipart_schema(attr) {
+                Ok(fields) => multipart_fields = fields,
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("responses") {
+            match parse_responses(attr) {
+                Ok(responses) => error_responses.extend(responses),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("response") {
+            match parse_response(attr) {
+                Ok((schema, tuple)) => {
+                    response_schema = schema.or(response_schema);
+                    response_tuple |= tuple;
+                }
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("response_header") {
+            match parse_response_header(attr) {
+                Ok(header) => response_headers.push(header),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("link") {
+            match parse_link(attr) {
+                Ok(link) if links.iter().any(|l| l.name.value() == link.name.value()) => {
+                    return syn::Error::new_spanned(&link.name, "duplicate link name").to_compile_error().into();
+                }
+                Ok(link) => links.push(link),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("transactional") {
+            // `#[transactional]` opts in, `#[transactional(skip)]` opts out
+            match &attr.meta {
+                syn::Meta::Path(_) => transactional = Some(true),
+                _ => match attr.parse_args::<syn::Ident>() {
+                    Ok(ident) if ident == "skip" => transactional = Some(false),
+                    _ => return syn::Error::new_spanned(attr, "expected #[transactional] or #[transactional(skip)]").to_compile_error().into(),
+                },
+            }
+        } else if attr.path().is_ident("concurrency_limit") {
+            match parse_concurrency_limit(attr) {
+                Ok(limit) => concurrency_limit = Some(limit),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("cache") {
+            if method != "get" {
+                return syn::Error::new_spanned(attr, "#[cache] is only supported on GET routes").to_compile_error().into();
+            }
+            match parse_cache(attr) {
+                Ok(parsed) => cache = Some((attr, parsed)),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("coalesce") {
+            if method != "get" {
+                return syn::Error::new_spanned(attr, "#[coalesce] is only supported on GET routes").to_compile_error().into();
+            }
+            match parse_coalesce(attr) {
+                Ok(vary) => coalesce = Some((attr, vary)),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("emit_event") {
+            match parse_emit_event(attr) {
+                Ok(event) => emit_event = Some(event),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("audited") {
+            if !matches!(method, "post" | "put" | "patch" | "delete") {
+                return syn::Error::new_spanned(attr, "#[audited] records mutating operations; it is only supported on POST, PUT, PATCH and DELETE routes").to_compile_error().into();
+            }
+            match parse_audited(attr) {
+                Ok(resource) => audited = Some(resource),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("request_body") {
+            match parse_request_body(attr) {
+                Ok((name, description)) => request_body = Some((attr, name, description)),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("operation_id") {
+            match attr.parse_args::<LitStr>() {
+                Ok(lit) if lit.value().is_empty() || lit.value().contains(char::is_whitespace) => {
+                    return syn::Error::new_spanned(&lit, "an operationId is a non-empty name without whitespace").to_compile_error().into();
+                }
+                Ok(lit) => operation_id = Some(lit),
+                Err(e) => return syn::Error::new(e.span(), "expected an operationId: #[operation_id(\"getUser\")]").to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("feature_flag") {
+            match attr.parse_args::<LitStr>() {
+                Ok(lit) if lit.value().is_empty() => {
+                    return syn::Error::new_spanned(&lit, "a feature flag needs a name").to_compile_error().into();
+                }
+                Ok(lit) => feature_flag = Some(lit),
+                Err(e) => return syn::Error::new(e.span(), "expected a flag name: #[feature_flag(\"new_billing\")]").to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("timeout") {
+            match attr.parse_args::<LitStr>().and_then(|lit| parse_duration_ms(&lit)) {
+                Ok(ms) => timeout_ms = Some(ms),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("version") {
+            match parse_version(attr) {
+                Ok(v) => version = Some(v),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("none_as") {
+            match parse_status(attr) {
+                Ok(204) => none_as = Some((attr, 204)),
+                Ok(_) => return syn::Error::new_spanned(attr, "expected #[none_as(204)]; `None` answers 404 without it").to_compile_error().into(),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("openapi_extension") {
+            match parse_openapi_extension(attr) {
+                Ok((key, _)) if openapi_extensions.iter().any(|(k, _)| k.value() == key.value()) => {
+                    return syn::Error::new_spanned(&key, "duplicate OpenAPI extension").to_compile_error().into();
+                }
+                Ok(extension) => openapi_extensions.push(extension),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("param") {
+            match parse_param_constraints(attr) {
+                Ok(constraints) => param_constraints.push(constraints),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("response_options") {
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("exclude_none") {
+                    exclude_none = true;
+                    Ok(())
+                } else if meta.path.is_ident("sparse_fields") {
+                    if !OPENAPI {
+                        // The known fields are read from the response model's schema
+                        return Err(meta.error("`sparse_fields` requires hayai's `openapi` feature"));
+                    }
+                    sparse_fields = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown response option, expected `exclude_none` or `sparse_fields`"))
+                }
+            });
+            if let Err(e) = parsed {
+                return e.to_compile_error().into();
             }
-        } else if attr.path().is_ident("security") {
-            if let syn::Meta::List(list) = &attr.meta {
-                let tokens = list.tokens.clone();
-                if let Ok(lit) = syn::parse2::<LitStr>(tokens) {
-                    security_schemes.push(lit.value());
-                }
+        } else if attr.path().is_ident("produces") {
+            match parse_produces(attr) {
+                Ok((media_type, root)) => produces = Some((attr, media_type, root)),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("consumes") {
+            match attr.parse_args::<LitStr>() {
+                Ok(lit) => consumes = Some(lit.value()),
+                Err(e) => return e.to_compile_error().into(),
             }
-        } else if !attr.path().is_ident("doc") {
-            clean_attrs.push(attr);
         } else {
             clean_attrs.push(attr);
         }
     }
 
-    // Default status codes
-    let default_status: u16 = match method {
-        "post" => 201,
-        "delete" => 204,
-        _ => 200,
-    };
-    let success_status = status_code.unwrap_or(default_status);
-
     let path_params: Vec<String> = path.split('/')
         .filter(|s| s.starts_with('{') && s.ends_with('}'))
         .map(|s| s[1..s.len()-1].to_string())
@@ -168,18 +1300,47 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
     let mut path_param_types: Vec<(&syn::Ident, &Type)> = Vec::new();
     let mut query_type: Option<&Type> = None;
     let mut query_extraction = quote!{};
+    let mut takes_precondition = false;
+    let mut takes_deadline = false;
+    let mut takes_locale = false;
+    let mut auth_schemes = Vec::new();
+    let mut errors = Vec::new();
+
+    if input_fn.sig.asyncness.is_none() {
+        errors.push(syn::Error::new_spanned(input_fn.sig.fn_token, "route handlers must be `async fn`"));
+    }
 
     for arg in &input_fn.sig.inputs {
-        if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
-            let param_name = quote!(#pat).to_string();
+        let FnArg::Typed(PatType { pat, ty, .. }) = arg else {
+            errors.push(syn::Error::new_spanned(
+                arg,
+                "route handlers must be free functions; `self` receivers are not supported — call the method from a free `async fn` instead",
+            ));
+            continue;
+        };
+        let param_name = match pat.as_ref() {
+            syn::Pat::Ident(pi) => pi.ident.to_string(),
+            other => quote!(#other).to_string(),
+        };
+        if let Some(error) = missing_type_argument(ty) {
+            errors.push(error);
+            continue;
+        }
+        {
             if is_dep_type(ty) {
                 if let Type::Path(tp) = ty.as_ref() {
                     if let Some(seg) = tp.path.segments.last() {
                         if let Some(inner) = extract_inner_type(seg) {
-                            dep_extractions.push(quote! {
-                                let #pat: hayai::Dep<#inner> = hayai::Dep::from_app_state(&state)?;
+                            dep_extractions.push(if is_tenant_scoped_type(inner) {
+                                quote! {
+                                    let #pat: hayai::Dep<#inner> = hayai::tenant::__tenant_dep(&state, &parts).await?;
+                                }
+                            } else {
+                                quote! {
+                                    let #pat: #ty = <#ty as hayai::axum::extract::FromRequestParts<hayai::AppState>>::from_request_parts(&mut parts, &state).await?;
+                                }
                             });
-                            call_args.push(quote!(#pat));
+                            call_args.push(call_arg(pat));
                         }
                     }
                 }
@@ -190,39 +1351,165 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
                             dep_extractions.push(quote! {
                                 let #pat: hayai::State<#inner> = hayai::State::from_app_state(&state)?;
                             });
-                            call_args.push(quote!(#pat));
+                            call_args.push(call_arg(pat));
                         }
                     }
                 }
+            } else if let Some(optional) = get_scoped_type(ty) {
+                dep_extractions.push(match optional {
+                    Some(inner) => quote! {
+                        let #pat: #ty = hayai::scope::__scoped_optional::<#inner>(&parts);
+                    },
+                    None => quote! {
+                        let #pat: #ty = <#ty as hayai::axum::extract::FromRequestParts<hayai::AppState>>::from_request_parts(&mut parts, &state).await?;
+                    },
+                });
+                call_args.push(call_arg(pat));
+            } else if is_auth_type(ty) {
+                if let Type::Path(tp) = ty.as_ref() {
+                    if let Some(validator) = tp.path.segments.last().and_then(extract_inner_type) {
+                        auth_schemes.push(quote! { <#validator as hayai::auth::SecurityValidator>::SCHEME });
+                    }
+                }
+                dep_extractions.push(quote! {
+                    let #pat: #ty = <#ty as hayai::axum::extract::FromRequestParts<hayai::AppState>>::from_request_parts(&mut parts, &state).await?;
+                });
+                call_args.push(call_arg(pat));
+            } else if is_request_info_type(ty) {
+                takes_precondition |= is_precondition_type(ty);
+                takes_deadline |= is_deadline_type(ty);
+                takes_locale |= is_locale_type(ty);
+                dep_extractions.push(quote! {
+                    let #pat: #ty = <#ty as hayai::axum::extract::FromRequestParts<hayai::AppState>>::from_request_parts(&mut parts, &state).await?;
+                });
+                call_args.push(call_arg(pat));
             } else if is_query_type(ty) {
                 if let Type::Path(tp) = ty.as_ref() {
                     if let Some(seg) = tp.path.segments.last() {
                         if let Some(inner) = extract_inner_type(seg) {
                             query_type = Some(inner);
+                            // Which fields take repeated keys is read from the schema
+                            let array_fields = if OPENAPI {
+                                quote! {
+                                    static __QUERY_ARRAY_FIELDS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+                                    let __array_fields = __QUERY_ARRAY_FIELDS.get_or_init(|| {
+                                        hayai::openapi::array_fields_from_schema(&hayai::schemars::schema_for!(#inner))
+                                    });
+                                }
+                            } else {
+                                quote! { let __array_fields: &[String] = &[]; }
+                            };
                             query_extraction = quote! {
-                                let #pat: hayai::axum::extract::Query<#inner> =
-                                    hayai::axum::extract::Query::from_request_parts(&mut parts, &state).await
-                                    .map_err(|e| hayai::ApiError::bad_request(format!("Invalid query parameters: {}", e)))?;
+                                #array_fields
+                                let #pat: hayai::axum::extract::Query<#inner> = hayai::__extract_query(&parts, __array_fields)?;
                             };
-                            call_args.push(quote!(#pat));
+                            call_args.push(call_arg(pat));
                         }
                     }
                 }
             } else if path_params.contains(&param_name) {
                 if let syn::Pat::Ident(pi) = pat.as_ref() {
                     path_param_types.push((&pi.ident, ty));
-                    call_args.push(quote!(#pat));
+                    call_args.push(call_arg(pat));
+                }
+            } else if !is_primitive_type(ty) || (consumes.is_some() && !has_body && is_raw_body_type(ty)) {
+                if let Some(first) = input_fn.sig.inputs.iter().find_map(|a| match a {
+                    FnArg::Typed(pt) if body_type.is_some_and(|b| std::ptr::eq(b, pt.ty.as_ref())) => Some(&pt.pat),
+                    _ => None,
+                }) {
+                    errors.push(syn::Error::new_spanned(
+                        arg,
+                        format!(
+                            "handlers may declare at most one body parameter; `{}` and `{}` both look like bodies — take query parameters as Query<..> or name path parameters in the route path",
+                            quote!(#first), param_name,
+                        ),
+                    ));
+                    continue;
                 }
-            } else if !is_primitive_type(ty) {
                 has_body = true;
                 body_type = Some(ty);
-                call_args.push(quote!(#pat));
+                call_args.push(call_arg(pat));
             } else {
-                call_args.push(quote!(#pat));
+                errors.push(syn::Error::new_spanned(
+                    arg,
+                    format!(
+                        "`{}` is not a parameter of {:?}; add `{{{}}}` to the path, or take it as a field of Query<..>",
+                        param_name, path, param_name,
+                    ),
+                ));
             }
         }
     }
 
+    for name in &path_params {
+        if !path_param_types.iter().any(|(ident, _)| *ident == name) {
+            errors.push(syn::Error::new_spanned(
+                &input_fn.sig,
+                format!("path parameter `{{{}}}` has no matching argument; add `{}: <type>` to the handler", name, name),
+            ));
+        }
+    }
+    for constraints in &param_constraints {
+        let name = &constraints.name;
+        let Some((_, ty)) = path_param_types.iter().find(|(ident, _)| *ident == name) else {
+            errors.push(syn::Error::new_spanned(name, format!("`{}` is not a parameter of {:?}", name, path)));
+            continue;
+        };
+        let numeric = constraints.minimum.is_some() || constraints.maximum.is_some();
+        let textual = constraints.min_length.is_some() || constraints.max_length.is_some() || constraints.pattern.is_some();
+        if numeric && !is_integer_type(ty) && !matches!(get_type_name(ty).as_str(), "f32" | "f64") {
+            errors.push(syn::Error::new_spanned(name, "minimum and maximum apply to numeric path parameters"));
+        }
+        if textual && get_type_name(ty) != "String" {
+            errors.push(syn::Error::new_spanned(name, "min_length, max_length and pattern apply to String path parameters"));
+        }
+    }
+    if let Some(error) = errors.into_iter().reduce(|mut all, e| { all.combine(e); all }) {
+        return error.to_compile_error().into();
+    }
+
+    // An XML `#[consumes]` type reads a model body the way JSON bodies are read
+    let xml_body = consumes.as_deref().is_some_and(is_xml_media_type)
+        && body_type.is_some_and(|t| !is_raw_body_type(t) && !is_form_type(t) && !is_multipart_type(t));
+    if consumes.is_some() && !xml_body && !body_type.is_some_and(is_raw_body_type) {
+        return syn::Error::new_spanned(
+            &input_fn.sig,
+            "#[consumes] needs a String or Bytes argument to receive the raw request body",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // Borrowing from the body changes the wrapper's lifetimes, so it has to be asked for
+    let json_body = body_type.filter(|t| consumes.is_none() && !is_form_type(t) && !is_multipart_type(t));
+    if zero_copy && json_body.is_none() {
+        return syn::Error::new_spanned(
+            &input_fn.sig,
+            "#[zero_copy] needs a JSON body parameter to borrow from",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if let Some(bty) = json_body.filter(|t| !zero_copy && has_lifetime(t)) {
+        return syn::Error::new_spanned(
+            bty,
+            "bodies that borrow from the request need #[zero_copy] on the handler",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // A body on GET is unusual enough that it has to be asked for explicitly
+    if has_body && method == "get" && !allow_body {
+        let bty = body_type.unwrap();
+        return syn::Error::new_spanned(
+            bty,
+            "GET handlers don't take a request body; add #[allow_body] if this is deliberate",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     let return_type = match &input_fn.sig.output {
         syn::ReturnType::Type(_, ty) => Some(ty.as_ref()),
         _ => None,
@@ -231,58 +1518,365 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
     // Detect Result<T, ApiError> return type
     let is_result_return = return_type.map(|t| get_result_ok_type(t).is_some()).unwrap_or(false);
     let effective_return_type = return_type.and_then(|t| get_result_ok_type(t)).or(return_type);
+    // `Option<T>`, also inside `Result`: `None` answers 404, or 204 with `#[none_as(204)]`
+    let option_inner = effective_return_type.and_then(get_option_inner_type);
+    // Wrapping an empty response leaves nothing to tell the cases apart by
+    let degenerate = option_inner.map(|t| (t, "Option"))
+        .or_else(|| effective_return_type.and_then(get_vec_inner_type).map(|t| (t, "Vec")));
+    if let Some((inner, wrapper)) = degenerate.filter(|(inner, _)| is_empty_response_type(inner)) {
+        let message = match wrapper {
+            "Option" => "`Option<()>` has no body to answer `Some` with; return `NoContent` for 204 and `Err(ApiError::not_found(..))` when there is nothing",
+            _ => "`Vec<()>` has no body to answer with; return `NoContent` for 204",
+        };
+        return syn::Error::new_spanned(inner, message).to_compile_error().into();
+    }
+    let effective_return_type = option_inner.or(effective_return_type);
+    let none_status = match (option_inner, none_as) {
+        (Some(_), Some((_, status))) => Some(status),
+        (Some(_), None) => Some(404),
+        (None, Some((attr, _))) => {
+            return syn::Error::new_spanned(attr, "#[none_as] needs an `Option<T>` return type").to_compile_error().into();
+        }
+        (None, None) => None,
+    };
+    if let Some((attr, ..)) = request_body.as_ref().filter(|_| !has_body) {
+        return syn::Error::new_spanned(attr, "#[request_body] needs a request body parameter").to_compile_error().into();
+    }
+
+    // Default status codes: handlers returning `()` or `NoContent` answer 204 whatever the
+    // method, and are sent without a body under any `#[status]`
+    let empty_response = effective_return_type.map(is_empty_response_type).unwrap_or(true);
+    let default_status: u16 = match method {
+        _ if empty_response => 204,
+        "post" => 201,
+        _ => 200,
+    };
+    let success_status = status_code.unwrap_or(default_status);
 
-    let return_type_name = effective_return_type.map(|t| get_type_name(t)).unwrap_or_else(|| "()".to_string());
+    let return_type_name = match effective_return_type {
+        Some(t) if !empty_response => response_type_name(t),
+        _ => "()".to_string(),
+    };
+
+    // `RangedFile` streams the slice a `Range` header asks for
+    let returns_ranged_file = return_type_name == "RangedFile";
+    if returns_ranged_file {
+        let conflict = if method != "get" {
+            Some(syn::Error::new_spanned(&input_fn.sig.output, "RangedFile answers Range requests, which only GET routes get"))
+        } else if let Some((attr, _)) = &cache {
+            Some(syn::Error::new_spanned(attr, "#[cache] keeps whole responses; a RangedFile answers each Range differently"))
+        } else if let Some((attr, _)) = &coalesce {
+            Some(syn::Error::new_spanned(attr, "#[coalesce] shares whole responses; a RangedFile answers each Range differently"))
+        } else if let Some((attr, _, _)) = &produces {
+            Some(syn::Error::new_spanned(attr, "a RangedFile sets its own content type; #[produces] is for models"))
+        } else if exclude_none || sparse_fields {
+            Some(syn::Error::new_spanned(&input_fn.sig.output, "#[response_options] is for model responses, not RangedFile"))
+        } else {
+            None
+        };
+        if let Some(e) = conflict {
+            return e.to_compile_error().into();
+        }
+    }
 
     // Detect Vec<T> return type for array schema (check effective type, i.e. inside Result if applicable)
     let is_vec_response = effective_return_type.map(|t| get_vec_inner_type_name(t).is_some()).unwrap_or(false);
-    let vec_inner_type_name = effective_return_type.and_then(|t| get_vec_inner_type_name(t)).unwrap_or_default();
+    let page_inner = effective_return_type.and_then(get_page_inner_type);
+    let bulk_inner = effective_return_type.and_then(get_bulk_inner_type);
+    if bulk_inner.is_some() && (exclude_none || sparse_fields) {
+        return syn::Error::new_spanned(
+            &input_fn.sig.output,
+            "#[response_options] is not supported on BulkResult responses",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if page_inner.is_some() && (exclude_none || sparse_fields) {
+        return syn::Error::new_spanned(
+            &input_fn.sig.output,
+            "#[response_options] is not supported on paginated responses",
+        )
+        .to_compile_error()
+        .into();
+    }
+    #[allow(clippy::redundant_closure)]
+    let vec_inner_type_name = match page_inner.as_ref().map(|(inner, _)| *inner).or(bulk_inner) {
+        Some(inner) => response_type_name(inner),
+        None => effective_return_type.and_then(|t| get_vec_inner_type_name(t)).unwrap_or_default(),
+    };
+    // `impl SerializeResponse` has no type to document the response by
+    if let Some(t) = effective_return_type.filter(|t| contains_opaque(t)) {
+        if response_schema.is_none() {
+            return syn::Error::new_spanned(
+                t,
+                "an `impl Trait` response has no type to document; declare its schema with #[response(schema = \"Model\")] or #[response(schema = inline({..}))]",
+            )
+            .to_compile_error()
+            .into();
+        }
+        if sparse_fields {
+            return syn::Error::new_spanned(t, "#[response_options(sparse_fields)] needs a concrete model response, not `impl Trait`")
+                .to_compile_error()
+                .into();
+        }
+    }
+    // `#[response(schema = "..")]` names the documented model, the element of a list or page
+    let (return_type_name, vec_inner_type_name) = match &response_schema {
+        Some(ResponseSchema::Named(schema)) if is_vec_response || page_inner.is_some() || bulk_inner.is_some() => (return_type_name, schema.clone()),
+        Some(ResponseSchema::Named(schema)) => (schema.clone(), vec_inner_type_name),
+        _ => (return_type_name, vec_inner_type_name),
+    };
+    // Maps, tuples and nullable or nested elements are documented by a generated schema, and
+    // `#[response(schema = inline(..))]` by the declared one
+    let response_schema_fn = match (&response_schema, effective_return_type) {
+        (Some(ResponseSchema::Inline(schema)), _) => Some(quote! { hayai::serde_json::json!(#schema) }),
+        (None, Some(t)) if page_inner.is_none() && bulk_inner.is_none() && needs_response_schema_fn(t) => match response_schema_expr(t, response_tuple) {
+            Ok(schema) => Some(schema),
+            Err(e) => return e.to_compile_error().into(),
+        },
+        _ => None,
+    };
+    if response_tuple && !effective_return_type.is_some_and(contains_tuple) {
+        return syn::Error::new_spanned(&input_fn.sig.output, "#[response(tuple)] needs a response with a tuple in it")
+            .to_compile_error()
+            .into();
+    }
+    let response_schema_fn_expr = match &response_schema_fn {
+        Some(schema) => quote! { Some(|| #schema) },
+        None => quote! { None },
+    };
+    let is_bulk = bulk_inner.is_some();
+    let pagination_expr = match &page_inner {
+        Some((_, kind)) => quote! { Some(#kind) },
+        None => quote! { None },
+    };
 
     let path_extraction = if !path_param_types.is_empty() {
         let names: Vec<_> = path_param_types.iter().map(|(n,_)| *n).collect();
+        let name_strs: Vec<String> = names.iter().map(|n| n.to_string()).collect();
         let types: Vec<_> = path_param_types.iter().map(|(_,t)| *t).collect();
         if path_param_types.len() == 1 {
             let n = names[0]; let t = types[0];
             quote! {
                 let hayai::axum::extract::Path(#n): hayai::axum::extract::Path<#t> =
                     hayai::axum::extract::Path::from_request_parts(&mut parts, &state).await
-                    .map_err(|e| hayai::ApiError::bad_request(format!("Invalid path param: {}", e)))?;
+                    .map_err(|e| hayai::__path_rejection(e, &[#(#name_strs),*]))?;
             }
         } else {
             quote! {
                 let hayai::axum::extract::Path((#(#names),*)): hayai::axum::extract::Path<(#(#types),*)> =
                     hayai::axum::extract::Path::from_request_parts(&mut parts, &state).await
-                    .map_err(|e| hayai::ApiError::bad_request(format!("Invalid path params: {}", e)))?;
+                    .map_err(|e| hayai::__path_rejection(e, &[#(#name_strs),*]))?;
             }
         }
     } else {
         quote!{}
     };
 
+    // `#[param(..)]` constraints are all checked so the 400 lists every violation
+    let param_checks = if param_constraints.is_empty() {
+        quote! {}
+    } else {
+        let checks = param_constraints.iter().map(|c| {
+            let ident = &c.name;
+            let name = ident.to_string();
+            let mut checks = Vec::new();
+            if let Some(min) = c.minimum {
+                let min_f64 = min as f64;
+                checks.push(quote! {
+                    if (#ident as f64) < #min_f64 {
+                        __param_errors.push(format!("{}: must be at least {}", #name, #min));
+                    }
+                });
+            }
+            if let Some(max) = c.maximum {
+                let max_f64 = max as f64;
+                checks.push(quote! {
+                    if (#ident as f64) > #max_f64 {
+                        __param_errors.push(format!("{}: must be at most {}", #name, #max));
+                    }
+                });
+            }
+            if let Some(min) = c.min_length {
+                checks.push(quote! {
+                    if #ident.len() < #min {
+                        __param_errors.push(format!("{}: must be at least {} characters", #name, #min));
+                    }
+                });
+            }
+            if let Some(max) = c.max_length {
+                checks.push(quote! {
+                    if #ident.len() > #max {
+                        __param_errors.push(format!("{}: must be at most {} characters", #name, #max));
+                    }
+                });
+            }
+            if let Some(pat) = &c.pattern {
+                checks.push(quote! {
+                    {
+                        static RE: std::sync::OnceLock<hayai::regex::Regex> = std::sync::OnceLock::new();
+                        let re = RE.get_or_init(|| hayai::regex::Regex::new(#pat).expect("Invalid regex"));
+                        if !re.is_match(&#ident) {
+                            __param_errors.push(format!("{}: must match pattern {}", #name, #pat));
+                        }
+                    }
+                });
+            }
+            quote! { #(#checks)* }
+        });
+        quote! {
+            let mut __param_errors: Vec<String> = Vec::new();
+            #(#checks)*
+            if !__param_errors.is_empty() {
+                return Err(hayai::ApiError::invalid_path_params(__param_errors));
+            }
+        }
+    };
+
+    // A `BulkResult` handler's `Vec<_>` JSON body
+    let bulk_elements = VALIDATION && bulk_inner.is_some() && !zero_copy && !xml_body && consumes.is_none()
+        && body_type.is_some_and(|t| get_vec_inner_type(t).is_some());
     let body_extraction = if has_body {
         let bty = body_type.unwrap();
+        // Strict bodies check each element of a list against the element's schema
+        let body_component_name = component_name_expr(get_vec_inner_type(bty).unwrap_or(bty));
         let bpat = input_fn.sig.inputs.iter().find_map(|arg| {
             if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
-                if !is_dep_type(ty) && !is_primitive_type(ty) && !is_query_type(ty) {
-                    let n = quote!(#pat).to_string();
-                    if !path_params.contains(&n) { return Some(pat.clone()); }
+                if std::ptr::eq(ty.as_ref(), bty) {
+                    return Some(pat.clone());
                 }
             }
             None
         }).unwrap();
-        quote! {
-            let hayai::axum::Json(#bpat): hayai::axum::Json<#bty> =
-                hayai::axum::Json::from_request(req, &state).await
-                .map_err(|e| hayai::ApiError::bad_request(format!("Invalid body: {}", e)))?;
-            #bpat.validate().map_err(|e| hayai::ApiError::validation_error(e))?;
+        let bident = call_arg(&bpat);
+        let validate = |body: proc_macro2::TokenStream| if VALIDATION {
+            quote! {
+                hayai::__validate(&state, #body)?;
+                hayai::__validate_async(&state, #body).await?;
+            }
+        } else {
+            quote! {}
+        };
+        let validate_body = validate(quote! { &#bident });
+        if xml_body {
+            quote! {
+                let #bident: #bty = hayai::xml::__extract_xml_body(req, &state).await?;
+                #validate_body
+            }
+        } else if let Some(ct) = &consumes {
+            // Raw bodies skip JSON parsing and validation
+            quote! {
+                let #bident: #bty = hayai::__extract_raw_body(req, &state, #ct).await?;
+            }
+        } else if is_form_type(bty) {
+            let validate_form = validate(quote! { &#bident.0 });
+            quote! {
+                let #bident: #bty = hayai::__extract_form(req, &state).await?;
+                #validate_form
+            }
+        } else if is_multipart_type(bty) {
+            quote! {
+                let #bident: #bty = hayai::__extract_multipart(req, &state).await?;
+            }
+        } else if let Some((payload, scheme)) = get_signed_payload_types(bty) {
+            // The signature is checked on the raw bytes before the payload is parsed
+            let payload_component_name = component_name_expr(get_vec_inner_type(payload).unwrap_or(payload));
+            let validate_signed = validate(quote! { &*#bident });
+            quote! {
+                let #bident: #bty = hayai::webhook::__extract_signed::<#payload, #scheme>(req, &state, &#payload_component_name).await?;
+                #validate_signed
+            }
+        } else if zero_copy {
+            // The bytes outlive the handler call, so the body can borrow from them
+            quote! {
+                let __body_bytes = hayai::__read_json_bytes(req, &state).await?;
+                let #bident: #bty = hayai::__parse_json_body(&__body_bytes, &state, &#body_component_name)?;
+                #validate_body
+            }
+        } else if bulk_elements {
+            // Each element of a batch is validated on its own, the invalid ones answered per item
+            quote! {
+                let #bident: #bty = hayai::__extract_json_body(req, &state, &#body_component_name).await?;
+                let (#bident, __bulk_rejections) = hayai::bulk::__validate_elements(&state, #bident).await;
+            }
+        } else {
+            quote! {
+                let #bident: #bty = hayai::__extract_json_body(req, &state, &#body_component_name).await?;
+                #validate_body
+            }
         }
     } else {
         quote! { let _ = req; }
     };
+    let body_extraction = if bulk_inner.is_some() && !bulk_elements {
+        quote! {
+            #body_extraction
+            let __bulk_rejections = hayai::bulk::Rejections::default();
+        }
+    } else {
+        body_extraction
+    };
+
+    // `?fields=` is checked before the handler runs, against the response model's properties
+    let fields_extraction = if sparse_fields {
+        let model = effective_return_type.map(|t| get_vec_inner_type(t).unwrap_or(t));
+        quote! {
+            static __RESPONSE_FIELDS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+            let __known_fields = __RESPONSE_FIELDS.get_or_init(|| {
+                hayai::openapi::property_names_from_schema(&hayai::schemars::schema_for!(#model))
+            });
+            let __fields = hayai::response_options::__requested_fields(&parts, __known_fields)?;
+        }
+    } else {
+        quote! {}
+    };
 
     // Generate response based on status code
     let status_lit = proc_macro2::Literal::u16_unsuffixed(success_status);
-    let response_expr = if success_status == 204 {
+    // Paginated responses also carry a Link header built from the request
+    let respond = if returns_ranged_file {
+        quote! { hayai::range::__ranged_response(&parts, result) }
+    } else if let Some((attr, _, root)) = &produces {
+        if page_inner.is_some() || bulk_inner.is_some() || is_vec_response || exclude_none || sparse_fields {
+            return syn::Error::new_spanned(attr, "#[produces] needs a single model response, without pagination or #[response_options]")
+                .to_compile_error()
+                .into();
+        }
+        let root = root.clone().unwrap_or_else(|| return_type_name.clone());
+        quote! { hayai::xml::__xml_response(hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(), &result, #root) }
+    } else if page_inner.is_some() {
+        quote! { hayai::pagination::__page_response(&state, &parts, hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(), &result) }
+    } else if bulk_inner.is_some() {
+        quote! { hayai::bulk::__bulk_response(&state, hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(), result, __bulk_rejections) }
+    } else if exclude_none || sparse_fields {
+        let fields = if sparse_fields { quote! { __fields.as_deref() } } else { quote! { None } };
+        quote! { hayai::response_options::__json_response(&state, hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(), &result, #exclude_none, #fields) }
+    } else {
+        quote! { hayai::__json_response(&state, hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(), &result) }
+    };
+    // Unwrap `Option<T>` before responding
+    let unwrap_option = match none_status {
+        Some(204) => quote! {
+            let Some(result) = result else {
+                return Ok((hayai::axum::http::StatusCode::NO_CONTENT,).into_response());
+            };
+        },
+        Some(_) => {
+            let message = match effective_return_type {
+                Some(t) if !is_primitive_type(t) && !is_free_form_type(t) && !contains_opaque(t) && get_vec_inner_type(t).is_none() => {
+                    format!("{} not found", response_type_name(t))
+                }
+                _ => "Resource not found".to_string(),
+            };
+            quote! {
+                let Some(result) = result else {
+                    return Err(hayai::ApiError::not_found(#message.to_string()));
+                };
+            }
+        }
+        None => quote! {},
+    };
+    let response_expr = if success_status == 204 || empty_response {
         if is_result_return {
             quote! {
                 let _ = #fn_name(#(#call_args),*).await?;
@@ -297,26 +1891,22 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
     } else if is_result_return {
         quote! {
             let result = #fn_name(#(#call_args),*).await?;
-            let value = hayai::serde_json::to_value(&result)
-                .map_err(|e| hayai::ApiError::internal(format!("Response serialization failed: {}", e)))?;
-            Ok((
-                hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(),
-                hayai::axum::Json(value),
-            ).into_response())
+            #unwrap_option
+            #respond
         }
     } else {
         quote! {
             let result = #fn_name(#(#call_args),*).await;
-            let value = hayai::serde_json::to_value(&result)
-                .map_err(|e| hayai::ApiError::internal(format!("Response serialization failed: {}", e)))?;
-            Ok((
-                hayai::axum::http::StatusCode::from_u16(#status_lit).unwrap(),
-                hayai::axum::Json(value),
-            ).into_response())
+            #unwrap_option
+            #respond
         }
     };
+    let none_status_expr = match none_status {
+        Some(status) => quote! { Some(#status) },
+        None => quote! { None },
+    };
 
-    let path_param_schemas: Vec<_> = path_params.iter().map(|p| {
+    let mut path_param_schemas: Vec<_> = path_params.iter().map(|p| {
         // Find the type of this path param
         let openapi_type = path_param_types.iter()
             .find(|(name, _)| name.to_string() == *p)
@@ -331,24 +1921,305 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
                 }
             })
             .unwrap_or("string");
+        let schema = match param_constraints.iter().find(|c| c.name == p) {
+            Some(c) => {
+                let some_f64 = |v: Option<i64>| match v {
+                    Some(v) => { let v = v as f64; quote! { Some(#v) } }
+                    None => quote! { None },
+                };
+                let some_usize = |v: Option<usize>| match v {
+                    Some(v) => quote! { Some(#v) },
+                    None => quote! { None },
+                };
+                let (minimum, maximum) = (some_f64(c.minimum), some_f64(c.maximum));
+                let (min_length, max_length) = (some_usize(c.min_length), some_usize(c.max_length));
+                let pattern = match &c.pattern {
+                    Some(pat) => quote! { Some(#pat) },
+                    None => quote! { None },
+                };
+                quote! {
+                    hayai::openapi::SchemaObject {
+                        minimum: #minimum,
+                        maximum: #maximum,
+                        min_length: #min_length,
+                        max_length: #max_length,
+                        pattern: #pattern,
+                        ..hayai::openapi::SchemaObject::new_type(#openapi_type)
+                    }
+                }
+            }
+            None => quote! { hayai::openapi::SchemaObject::new_type(#openapi_type) },
+        };
         quote! {
             hayai::openapi::Parameter {
                 name: #p,
                 location: "path",
                 required: true,
-                schema: hayai::openapi::SchemaObject::new_type(#openapi_type),
+                schema: #schema,
                 description: None,
             }
         }
     }).collect();
 
-    let body_type_name = body_type.map(|t| get_type_name(t)).unwrap_or_default();
+    if sparse_fields {
+        path_param_schemas.push(quote! {
+            hayai::openapi::Parameter {
+                name: "fields",
+                location: "query",
+                required: false,
+                schema: hayai::openapi::SchemaObject::new_type("string"),
+                description: Some(hayai::response_options::FIELDS_DESCRIPTION),
+            }
+        });
+    }
+
+    // Conditional request headers read by `Precondition`
+    if takes_precondition {
+        let headers = [
+            ("If-Match", "Entity tags the resource must match"),
+            ("If-None-Match", "Entity tags the resource must not match"),
+            ("If-Unmodified-Since", "HTTP-date; the request fails with 412 if the resource changed after it"),
+        ];
+        path_param_schemas.extend(headers.iter().map(|(name, description)| quote! {
+            hayai::openapi::Parameter {
+                name: #name,
+                location: "header",
+                required: false,
+                schema: hayai::openapi::SchemaObject::new_type("string"),
+                description: Some(#description),
+            }
+        }));
+    }
+
+    // The client's time budget read by `Deadline`
+    if takes_deadline {
+        path_param_schemas.push(quote! {
+            hayai::openapi::Parameter {
+                name: "X-Request-Timeout",
+                location: "header",
+                required: false,
+                schema: hayai::openapi::SchemaObject::new_type("string"),
+                description: Some("How long the client will wait, in milliseconds or as a duration such as `25s`"),
+            }
+        });
+    }
+
+    // Read by `Locale`; the app adds its locale query parameter and cookie
+    if takes_locale {
+        path_param_schemas.push(quote! {
+            hayai::openapi::Parameter {
+                name: "Accept-Language",
+                location: "header",
+                required: false,
+                schema: hayai::openapi::SchemaObject::new_type("string"),
+                description: Some("Preferred languages with q-values, such as `fr-CH, fr;q=0.9, en;q=0.8`"),
+            }
+        });
+    }
+
+    // Seeking within a `RangedFile`
+    if returns_ranged_file {
+        path_param_schemas.push(quote! {
+            hayai::openapi::Parameter {
+                name: "Range",
+                location: "header",
+                required: false,
+                schema: hayai::openapi::SchemaObject::new_type("string"),
+                description: Some("One byte range of the file, such as `bytes=0-1023`; answered with 206"),
+            }
+        });
+    }
+
+    // The signature a `SignedPayload` is checked against; a bad one answers 401
+    if let Some((_, scheme)) = body_type.and_then(get_signed_payload_types) {
+        path_param_schemas.push(quote! {
+            hayai::openapi::Parameter {
+                name: <#scheme as hayai::webhook::SignatureScheme>::HEADER,
+                location: "header",
+                required: true,
+                schema: hayai::openapi::SchemaObject::new_type("string"),
+                description: Some("Signature of the raw request body"),
+            }
+        });
+        if !error_responses.iter().any(|(status, _)| *status == 401) {
+            error_responses.push((401, "ApiError".to_string()));
+        }
+    }
+
+    // Form<T> and SignedPayload<T, _> are documented with T's component schema, collections
+    // with their element's
+    let body_collection = body_type.filter(|_| consumes.is_none()).and_then(get_body_collection);
+    let body_collection_expr = match &body_collection {
+        Some((_, kind)) => quote! { Some(#kind) },
+        None => quote! { None },
+    };
+    let body_type_name = body_type
+        .map(|t| get_signed_payload_types(t).map_or(t, |(payload, _)| payload))
+        .map(|t| match t {
+            Type::Path(tp) if is_form_type(t) => tp.path.segments.last()
+                .and_then(extract_inner_type)
+                .map(get_type_name)
+                .unwrap_or_default(),
+            _ => match &body_collection {
+                Some((inner, _)) => response_type_name(inner),
+                None => get_type_name(t),
+            },
+        })
+        .unwrap_or_default();
+    let produces_type = match &produces {
+        Some((_, media_type, _)) => media_type.as_str(),
+        None if returns_ranged_file => "application/octet-stream",
+        None => "application/json",
+    };
+    let body_content_type = match (&consumes, body_type) {
+        (Some(ct), _) => ct.as_str(),
+        (None, Some(t)) if is_form_type(t) => "application/x-www-form-urlencoded",
+        (None, Some(t)) if is_multipart_type(t) => "multipart/form-data",
+        _ => "application/json",
+    };
+    let multipart_field_entries: Vec<_> = multipart_fields.iter()
+        .map(|(name, kind)| quote! { (#name, #kind) })
+        .collect();
+    let response_header_entries: Vec<_> = response_headers.iter()
+        .map(|(name, kind, description)| quote! { (#name, #kind, #description) })
+        .collect();
+    let link_entries: Vec<_> = links.iter()
+        .map(|link| {
+            let name = &link.name;
+            let operation_id = &link.operation_id;
+            let params = link.parameters.iter().map(|(key, expr)| quote! { (#key, #expr) });
+            quote! { hayai::openapi::Link { name: #name, operation_id: #operation_id, parameters: &[#(#params),*] } }
+        })
+        .collect();
+    let transactional_expr = match transactional {
+        Some(flag) => quote! { Some(#flag) },
+        None => quote! { None },
+    };
+    let concurrency_limit_expr = match &concurrency_limit {
+        Some(limit) => quote! { Some(#limit) },
+        None => quote! { None },
+    };
+    let emit_event_expr = match &emit_event {
+        Some(event) => quote! { Some(#event) },
+        None => quote! { None },
+    };
+    let timeout_expr = match timeout_ms {
+        Some(ms) => quote! { Some(std::time::Duration::from_millis(#ms)) },
+        None => quote! { None },
+    };
+    let feature_flag_expr = match &feature_flag {
+        Some(flag) => quote! { Some(#flag) },
+        None => quote! { None },
+    };
+    // Checked before anything is read from the request
+    let flag_check = feature_flag.as_ref().map(|_| quote! {
+        if let Some(disabled) = hayai::feature_flags::__check(&state, &#route_info_name) {
+            return Ok(disabled);
+        }
+    });
+    let audited_expr = match &audited {
+        Some(resource) => quote! { Some(#resource) },
+        None => quote! { None },
+    };
+    let (request_body_name_expr, request_body_description_expr) = match &request_body {
+        Some((_, name, description)) => (
+            name.as_ref().map_or_else(|| quote! { None }, |name| quote! { Some(#name) }),
+            description.as_ref().map_or_else(|| quote! { None }, |description| quote! { Some(#description) }),
+        ),
+        None => (quote! { None }, quote! { None }),
+    };
+    let operation_id_expr = match &operation_id {
+        Some(id) => quote! { Some(#id) },
+        None => quote! { None },
+    };
+    let version_expr = match version {
+        Some(v) => quote! { Some(#v) },
+        None => quote! { None },
+    };
+    let openapi_extension_entries = openapi_extensions.iter().map(|(key, value)| {
+        quote! { (#key, (|| hayai::serde_json::json!(#value)) as fn() -> hayai::serde_json::Value) }
+    });
+    // A shared entry would hand one caller's response to another
+    if let Some((attr, c)) = &cache {
+        if !c.allow_authenticated && (!security_schemes.is_empty() || !auth_schemes.is_empty()) {
+            return syn::Error::new_spanned(attr, "caching an authenticated route needs `allow_authenticated`; entries are then kept per subject").to_compile_error().into();
+        }
+    }
+    let cache_expr = match &cache {
+        Some((_, c)) => {
+            let CacheAttr { ttl_ms, vary, allow_authenticated } = c;
+            quote! { Some(hayai::cache::CachePolicy {
+                ttl: std::time::Duration::from_millis(#ttl_ms),
+                vary: &[#(#vary),*],
+                allow_authenticated: #allow_authenticated,
+            }) }
+        }
+        None => quote! { None },
+    };
+    let coalesce_expr = match &coalesce {
+        Some((_, vary)) => quote! { Some(hayai::coalesce::CoalescePolicy { vary: &[#(#vary),*] }) },
+        None => quote! { None },
+    };
+    // On a hit the handler isn't called; a successful miss is stored on the way out
+    let response_expr = if cache.is_some() {
+        quote! {
+            let __response: Result<hayai::axum::response::Response, hayai::ApiError> = async { #response_expr }.await;
+            hayai::cache::__store(&state, &#route_info_name, __cache_key, __response?).await
+        }
+    } else {
+        response_expr
+    };
+    // Identical requests arriving while one runs wait for its response instead of running
+    // the handler (and storing a miss) themselves
+    let response_expr = if coalesce.is_some() {
+        quote! {
+            hayai::coalesce::__run(&state, &#route_info_name, &parts, async { #response_expr }).await
+        }
+    } else {
+        response_expr
+    };
+    let response_expr = if cache.is_some() {
+        quote! {
+            let __cache_key = match hayai::cache::__lookup(&state, &#route_info_name, &parts).await {
+                Ok(key) => key,
+                Err(hit) => return Ok(hit),
+            };
+            #response_expr
+        }
+    } else {
+        response_expr
+    };
+    let link_targets = links.iter().filter_map(|link| link.handler.as_ref());
+    let error_response_entries: Vec<_> = error_responses.iter()
+        .map(|(status, name)| quote! { (#status, #name) })
+        .collect();
     let fn_name_str = fn_name.to_string();
 
-    let query_params_fn_expr = if let Some(qt) = query_type {
+    // Model types documented by $ref: the response (or its Vec element) and the body
+    let response_model = effective_return_type
+        .filter(|_| response_schema.is_none() && response_schema_fn.is_none())
+        .map(|t| get_page_inner_type(t).map(|(inner, _)| inner).or_else(|| get_bulk_inner_type(t)).or_else(|| get_vec_inner_type(t)).unwrap_or(t));
+    let body_model = body_type
+        .map(|t| get_signed_payload_types(t).map_or(t, |(payload, _)| payload))
+        .and_then(|t| match t {
+            Type::Path(tp) if is_form_type(t) => tp.path.segments.last().and_then(extract_inner_type),
+            _ => Some(body_collection.as_ref().map_or(t, |(inner, _)| *inner)),
+        });
+    let component_name_entries: Vec<_> = [response_model, body_model].into_iter()
+        .flatten()
+        .filter(|t| !is_empty_response_type(t) && !is_primitive_type(t) && !is_free_form_type(t))
+        .map(|t| {
+            let type_name = get_type_name(t);
+            let expr = component_name_expr(t);
+            quote! { (#type_name, #expr) }
+        })
+        .collect();
+
+    let query_params_fn_expr = if let Some(qt) = query_type.filter(|_| OPENAPI) {
+        let query_component_name = component_name_expr(qt);
         quote! { Some(|| {
             let root = hayai::schemars::schema_for!(#qt);
-            hayai::openapi::query_params_from_schema(&root)
+            hayai::__query_params(&root, &#query_component_name)
         }) }
     } else {
         quote! { None }
@@ -369,7 +2240,15 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
             use hayai::axum::response::IntoResponse;
             use hayai::Validate;
 
+            #flag_check
+            // Held until the response is built
+            let _permit = match hayai::concurrency::__acquire(&state, &#route_info_name).await {
+                Ok(permit) => permit,
+                Err(rejected) => return Ok(rejected),
+            };
             #path_extraction
+            #param_checks
+            #fields_extraction
             #query_extraction
             #(#dep_extractions)*
             #body_extraction
@@ -384,17 +2263,44 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
             axum_path: #axum_path,
             method: #method_upper,
             handler_name: #fn_name_str,
+            operation_id: #operation_id_expr,
             response_type_name: #return_type_name,
             is_result_return: #is_result_return,
+            none_status: #none_status_expr,
             is_vec_response: #is_vec_response,
             vec_inner_type_name: #vec_inner_type_name,
+            response_schema_fn: #response_schema_fn_expr,
+            pagination: #pagination_expr,
+            bulk: #is_bulk,
             parameters: &[#(#path_param_schemas),*],
             has_body: #has_body,
             body_type_name: #body_type_name,
+            body_collection: #body_collection_expr,
+            body_content_type: #body_content_type,
+            request_body_name: #request_body_name_expr,
+            request_body_description: #request_body_description_expr,
+            produces: #produces_type,
+            multipart_fields: &[#(#multipart_field_entries),*],
+            error_responses: &[#(#error_response_entries),*],
+            response_headers: &[#(#response_header_entries),*],
+            links: &[#(#link_entries),*],
+            transactional: #transactional_expr,
+            concurrency_limit: #concurrency_limit_expr,
+            cache: #cache_expr,
+            emit_event: #emit_event_expr,
+            audited: #audited_expr,
+            timeout: #timeout_expr,
+            feature_flag: #feature_flag_expr,
+            coalesce: #coalesce_expr,
+            no_envelope: #no_envelope,
+            version: #version_expr,
+            openapi_extensions: &[#(#openapi_extension_entries),*],
             success_status: #status_lit,
             description: #description,
             tags: &[#(#tags),*],
-            security: &[#(#security_schemes),*],
+            replaces_tags: #replaces_tags,
+            security: &[#(#security_schemes,)* #(#auth_schemes),*],
+            clears_security: #clears_security,
             query_params_fn: #query_params_fn_expr,
             register_fn: |app: hayai::axum::Router<hayai::AppState>| {
                 app.route(#axum_path, hayai::axum::routing::#method_ident(#wrapper_name))
@@ -402,14 +2308,19 @@ fn route_macro_impl(method: &str, attr: TokenStream, item: TokenStream) -> Token
             method_router_fn: || {
                 hayai::axum::routing::#method_ident(#wrapper_name)
             },
+            component_names_fn: || vec![#(#component_name_entries),*],
         };
 
+        // Link targets given as handler paths must exist
+        #(const _: () = { let _ = #link_targets; };)*
+
         #[doc(hidden)]
         pub static #route_ref_name: &hayai::RouteInfo = &#route_info_name;
 
         hayai::inventory::submit! { &#route_info_name }
     };
 
+    debug_expansion(&fn_name.to_string(), &output);
     output.into()
 }
 
@@ -434,129 +2345,463 @@ pub fn delete(attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn api_model(attr: TokenStream, item: TokenStream) -> TokenStream {
-    // Parse the attribute to look for validate(custom = "fn_name")
-    let mut custom_validation_fn: Option<proc_macro2::TokenStream> = None;
-    
-    // Parse attribute if provided (e.g., #[api_model(validate(custom = "fn_name"))])
-    let attr_str = attr.to_string();
-    if attr_str.contains("validate") {
-        // Simple string parsing to find validate(custom = "fn_name")
-        if let Some(start) = attr_str.find("custom") {
-            let rest = &attr_str[start..];
-            if let Some(eq_pos) = rest.find('=') {
-                let after_eq = &rest[eq_pos+1..];
-                // Find the quoted string
-                if let Some(quote_start) = after_eq.find('"') {
-                    if let Some(quote_end) = after_eq[quote_start+1..].find('"') {
-                        let fn_name = &after_eq[quote_start+1..quote_start+1+quote_end];
-                        if !fn_name.is_empty() {
-                            custom_validation_fn = Some(quote!(#fn_name));
-                        }
+pub fn patch(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route_macro_impl("patch", attr, item)
+}
+
+/// An explicit HEAD handler, answering instead of the GET handler on the same path, e.g.
+/// to report a file's size without reading it
+#[proc_macro_attribute]
+pub fn head(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route_macro_impl("head", attr, item)
+}
+
+/// Model-level options parsed from `#[api_model(...)]`
+#[derive(Default)]
+struct ApiModelOptions {
+    custom_validation_fn: Option<syn::Path>,
+    async_validation_fn: Option<syn::Ident>,
+    title: Option<String>,
+    description: Option<String>,
+    deny_unknown_fields: bool,
+    strict_bodies: bool,
+    generate_patch: bool,
+    rename_all: Option<String>,
+    schema_name: Option<String>,
+    module_prefix: bool,
+    enum_case_insensitive: bool,
+}
+
+impl ApiModelOptions {
+    fn parse(attr: TokenStream) -> syn::Result<Self> {
+        let mut opts = ApiModelOptions::default();
+        let parser = syn::meta::parser(|meta| {
+            if meta.path.is_ident("validate") {
+                meta.parse_nested_meta(|nested| {
+                    if nested.path.is_ident("custom") {
+                        let lit: LitStr = nested.value()?.parse()?;
+                        opts.custom_validation_fn = Some(lit.parse()?);
+                        Ok(())
+                    } else {
+                        Err(nested.error("unsupported validate option, expected `custom`"))
                     }
+                })
+            } else if meta.path.is_ident("async_validate") {
+                let lit: LitStr = meta.value()?.parse()?;
+                opts.async_validation_fn = Some(lit.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("title") {
+                let lit: LitStr = meta.value()?.parse()?;
+                opts.title = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("description") {
+                let lit: LitStr = meta.value()?.parse()?;
+                opts.description = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("deny_unknown_fields") {
+                opts.deny_unknown_fields = true;
+                Ok(())
+            } else if meta.path.is_ident("strict_bodies") {
+                opts.strict_bodies = true;
+                Ok(())
+            } else if meta.path.is_ident("generate_patch") {
+                opts.generate_patch = true;
+                Ok(())
+            } else if meta.path.is_ident("rename_all") {
+                let lit: LitStr = meta.value()?.parse()?;
+                if !RENAME_RULES.contains(&lit.value().as_str()) {
+                    return Err(syn::Error::new_spanned(
+                        &lit,
+                        format!("unknown rename_all rule, expected one of {}", RENAME_RULES.join(", ")),
+                    ));
                 }
+                opts.rename_all = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("schema_name") {
+                let lit: LitStr = meta.value()?.parse()?;
+                opts.schema_name = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("module_prefix") {
+                opts.module_prefix = true;
+                Ok(())
+            } else if meta.path.is_ident("enum_case_insensitive") {
+                opts.enum_case_insensitive = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported api_model option"))
             }
+        });
+        syn::parse::Parser::parse(parser, attr)?;
+        if opts.module_prefix && opts.schema_name.is_some() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`schema_name` and `module_prefix` are mutually exclusive",
+            ));
+        }
+        Ok(opts)
+    }
+
+    /// Component name before any module prefix: `schema_name`, or the type name
+    fn component_base(&self, name: &syn::Ident) -> String {
+        self.schema_name.clone().unwrap_or_else(|| name.to_string())
+    }
+
+    /// The effective `rename_all` rule: `#[api_model(rename_all = ..)]` or a `#[serde(rename_all = ..)]`
+    /// already on the item
+    fn effective_rename_all(&self, attrs: &[syn::Attribute]) -> Option<String> {
+        self.rename_all.clone().or_else(|| serde_str_attr(attrs, "rename_all"))
+    }
+
+    /// Title and description for the component schema: explicit overrides win,
+    /// otherwise the first doc line becomes the title and the full doc text the description.
+    fn title_and_description(&self, attrs: &[syn::Attribute]) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        let doc = extract_doc_comment(attrs);
+        let title = self.title.clone()
+            .or_else(|| doc.lines().next().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()));
+        let description = self.description.clone()
+            .or((!doc.is_empty()).then_some(doc));
+        let title_expr = match title {
+            Some(t) => quote! { Some(#t.to_string()) },
+            None => quote! { None },
+        };
+        let desc_expr = match description {
+            Some(d) => quote! { Some(#d.to_string()) },
+            None => quote! { None },
+        };
+        (title_expr, desc_expr)
+    }
+}
+
+#[proc_macro_attribute]
+pub fn api_model(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let opts = match ApiModelOptions::parse(attr) {
+        Ok(opts) => opts,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    match syn::parse::<syn::Item>(item) {
+        Ok(syn::Item::Struct(input)) if opts.enum_case_insensitive => syn::Error::new_spanned(
+            &input.ident,
+            "enum_case_insensitive applies to enums",
+        )
+            .to_compile_error()
+            .into(),
+        Ok(syn::Item::Struct(input)) => api_model_struct(input, opts),
+        Ok(syn::Item::Enum(input)) => api_model_enum(input, opts),
+        Ok(other) => syn::Error::new_spanned(other, "api_model only supports structs and enums")
+            .to_compile_error()
+            .into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// `#[derive(ApiModel)]`: what `#[api_model]` adds to a struct, for structs that derive
+/// `Serialize`, `Deserialize` and `JsonSchema` themselves.
+///
+/// The item is left exactly as written, so rust-analyzer resolves its fields and other
+/// attribute macros see it unchanged. The derive reads the same `#[validate(..)]` and
+/// `#[schema(..)]` field attributes and generates the same `Validate` impl and schema
+/// registration. What `#[api_model]` would write onto the item has to be written by hand:
+/// `#[serde(rename_all)]`/`#[serde(deny_unknown_fields)]` instead of those options,
+/// `#[schemars(rename = "..")]` instead of `schema_name`, and the `serde(with)` and
+/// `schemars(with)` attributes of `string_encoded` and `Decimal` fields, which fail to
+/// compile without them. Other options go in `#[api_model_options(..)]`:
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, JsonSchema, ApiModel)]
+/// #[serde(crate = "hayai::serde")]
+/// #[schemars(crate = "hayai::schemars")]
+/// #[api_model_options(validate(custom = "check_dates"), generate_patch)]
+/// struct Booking {
+///     #[validate(min_length = 1)]
+///     guest: String,
+/// }
+/// ```
+#[proc_macro_derive(ApiModel, attributes(validate, schema, api_model_options))]
+pub fn derive_api_model(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<syn::DeriveInput>(item) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    match derive_api_model_impl(&input) {
+        Ok(output) => {
+            debug_expansion(&input.ident.to_string(), &output);
+            output.into()
+        }
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn derive_api_model_impl(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let attrs = &input.attrs;
+    let generics = &input.generics;
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(fields), .. }) => &fields.named,
+        syn::Data::Struct(_) => return Err(syn::Error::new_spanned(name, "#[derive(ApiModel)] only supports structs with named fields")),
+        _ => return Err(syn::Error::new_spanned(name, "#[derive(ApiModel)] only supports structs; use #[api_model] on enums")),
+    };
+
+    let mut opts = ApiModelOptions::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("api_model_options")) {
+        let tokens: proc_macro2::TokenStream = attr.parse_args()?;
+        opts = ApiModelOptions::parse(tokens.into())?;
+        let written_by_hand = if opts.rename_all.is_some() {
+            Some("`rename_all`: write #[serde(rename_all = \"..\")] on the struct")
+        } else if opts.deny_unknown_fields {
+            Some("`deny_unknown_fields`: write #[serde(deny_unknown_fields)] on the struct")
+        } else if opts.schema_name.is_some() {
+            Some("`schema_name`: write #[schemars(rename = \"..\")] on the struct")
+        } else if opts.module_prefix {
+            Some("`module_prefix`: it needs #[api_model], which derives the schema itself")
+        } else if opts.enum_case_insensitive {
+            Some("`enum_case_insensitive`: it applies to enums")
+        } else {
+            None
+        };
+        if let Some(message) = written_by_hand {
+            return Err(syn::Error::new_spanned(attr, format!("#[derive(ApiModel)] doesn't take {}", message)));
         }
     }
-    
-    let item_clone = item.clone();
-    if let Ok(input) = syn::parse::<ItemStruct>(item) {
-        api_model_struct(input, custom_validation_fn)
-    } else if let Ok(input) = syn::parse::<ItemEnum>(item_clone) {
-        api_model_enum(input)
-    } else {
-        syn::Error::new(proc_macro2::Span::call_site(), "api_model only supports structs and enums")
-            .to_compile_error()
-            .into()
+    if opts.generate_patch && generics.lifetimes().next().is_some() {
+        return Err(syn::Error::new_spanned(generics, "generate_patch is not supported on models that borrow"));
+    }
+    // Read back what `#[api_model]` would have written, so the component and patch model match
+    opts.schema_name = attr_entry(attrs, "schemars", "rename").flatten();
+    opts.deny_unknown_fields = attr_entry(attrs, "serde", "deny_unknown_fields").is_some();
+
+    let rename_all = serde_str_attr(attrs, "rename_all");
+    let mut parts = model_fields(fields, rename_all.as_deref());
+    let ModelFields { clean_fields, serde_with, .. } = &mut parts;
+    // The patch model adds its own `with` attributes to these fields
+    let has_with = |attr: &syn::Attribute| {
+        ["serde", "schemars"].iter().any(|name| attr_entry(std::slice::from_ref(attr), name, "with").is_some())
+    };
+    for field in clean_fields.iter_mut().filter(|f| serde_with.iter().any(|(with_field, _)| with_field.ident == f.ident)) {
+        field.attrs.retain(|attr| !has_with(attr));
+    }
+    for (field, with) in serde_with.iter() {
+        if attr_entry(&field.attrs, "serde", "with").is_none() {
+            let ty = &field.ty;
+            let default = if get_option_inner_type(ty).is_some() { "default, " } else { "" };
+            return Err(syn::Error::new_spanned(field, format!(
+                "#[derive(ApiModel)] can't add attributes to this field; add #[serde({}with = \"{}\")] and #[schemars(with = \"{}\")]",
+                default, with, quote!(#ty),
+            )));
+        }
     }
+
+    let name_str = opts.component_base(name);
+    let naming = schema_naming(&opts, name, generics, &name_str, attrs, quote! {});
+    let naming_items = &naming.items;
+    let model_impls = model_impls(name, generics, &opts, &name_str, false, attrs, &parts);
+    let patch_model = if opts.generate_patch {
+        patch_model_tokens(name, &input.vis, &parts.clean_fields, &parts.encoded_fields, &parts.patch_validation_checks, &opts, rename_all.as_deref())
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        #naming_items
+
+        #model_impls
+
+        #patch_model
+    })
 }
 
-fn api_model_enum(input: ItemEnum) -> TokenStream {
+fn api_model_enum(input: ItemEnum, opts: ApiModelOptions) -> TokenStream {
     let name = &input.ident;
     let vis = &input.vis;
     let attrs = &input.attrs;
     let variants = &input.variants;
-    let description = extract_doc_comment(attrs);
+    let (title_expr, desc_expr) = opts.title_and_description(attrs);
 
+    let rename_all = opts.effective_rename_all(attrs);
     let variant_names: Vec<String> = variants.iter()
-        .map(|v| v.ident.to_string())
+        .map(|v| serde_str_attr(&v.attrs, "rename").unwrap_or_else(|| {
+            let name = v.ident.to_string();
+            match &rename_all {
+                Some(rule) => rename_variant(&name, rule),
+                None => name,
+            }
+        }))
         .collect();
+    let serde_rename_all = match &opts.rename_all {
+        Some(rule) => quote! { #[serde(rename_all = #rule)] },
+        None => quote! {},
+    };
+
+    // Variants are matched ignoring ASCII case; anything else fails as serde's derive would,
+    // so the error still lists the variants
+    let (deserialize_derive, case_insensitive_impl) = if opts.enum_case_insensitive {
+        if let Some(v) = variants.iter().find(|v| !matches!(v.fields, syn::Fields::Unit)) {
+            return syn::Error::new_spanned(v, "enum_case_insensitive applies to enums of unit variants")
+                .to_compile_error()
+                .into();
+        }
+        let idents = variants.iter().map(|v| &v.ident);
+        let names = &variant_names;
+        (quote! {}, quote! {
+            impl<'de> hayai::serde::Deserialize<'de> for #name {
+                fn deserialize<D: hayai::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    const VARIANTS: &[&str] = &[#(#names),*];
+                    let value = <String as hayai::serde::Deserialize>::deserialize(deserializer)?;
+                    #(
+                        if value.eq_ignore_ascii_case(#names) {
+                            return Ok(#name::#idents);
+                        }
+                    )*
+                    Err(<D::Error as hayai::serde::de::Error>::unknown_variant(&value, VARIANTS))
+                }
+            }
+        })
+    } else {
+        (quote! { , hayai::serde::Deserialize }, quote! {})
+    };
 
-    let name_str = name.to_string();
+    let name_str = opts.component_base(name);
+    let shadow = format_ident!("__HayaiSchema{}", name);
+    let naming = schema_naming(&opts, name, &syn::Generics::default(), &name_str, attrs, quote! {
+        #serde_rename_all
+        enum #shadow {
+            #variants
+        }
+    });
+    let schema_derive = &naming.derive;
+    let naming_items = &naming.items;
+    let module_prefix = naming.module_prefix;
+    let strict_bodies = opts.strict_bodies;
 
-    let desc_expr = if description.is_empty() {
-        quote! { None }
+    let validate_impl = if VALIDATION {
+        quote! {
+            impl hayai::Validate for #name {
+                fn validate(&self) -> Result<(), Vec<String>> { Ok(()) }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let schema_info = if OPENAPI {
+        quote! {
+            hayai::inventory::submit! {
+                hayai::SchemaInfo {
+                    name: #name_str,
+                    module_path: module_path!(),
+                    module_prefix: #module_prefix,
+                    strict_bodies: #strict_bodies,
+                    schema_fn: || {
+                        static CACHE: std::sync::OnceLock<hayai::openapi::Schema> = std::sync::OnceLock::new();
+                        CACHE.get_or_init(|| {
+                            hayai::openapi::Schema {
+                                type_name: "string".to_string(),
+                                properties: std::collections::HashMap::new(),
+                                required: vec![],
+                                title: #title_expr,
+                                description: #desc_expr,
+                                enum_values: Some(vec![#(#variant_names.to_string()),*]),
+                                example: None,
+                                additional_properties: None,
+                                one_of: Vec::new(),
+                                discriminator: None,
+                            }
+                        }).clone()
+                    },
+                    nested_fn: || {
+                        static CACHE: std::sync::OnceLock<std::collections::HashMap<String, hayai::openapi::Schema>> = std::sync::OnceLock::new();
+                        CACHE.get_or_init(|| std::collections::HashMap::new()).clone()
+                    },
+                    patches_fn: std::collections::HashMap::new,
+                }
+            }
+        }
     } else {
-        quote! { Some(#description.to_string()) }
+        quote! {}
     };
 
     let output = quote! {
         #(#attrs)*
-        #[derive(hayai::serde::Serialize, hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+        #[derive(hayai::serde::Serialize #deserialize_derive)]
+        #schema_derive
         #[serde(crate = "hayai::serde")]
-        #[schemars(crate = "hayai::schemars")]
+        #serde_rename_all
         #vis enum #name {
             #variants
         }
 
-        impl hayai::Validate for #name {
-            fn validate(&self) -> Result<(), Vec<String>> { Ok(()) }
-        }
+        #naming_items
 
-        hayai::inventory::submit! {
-            hayai::SchemaInfo {
-                name: #name_str,
-                schema_fn: || {
-                    static CACHE: std::sync::OnceLock<hayai::openapi::Schema> = std::sync::OnceLock::new();
-                    CACHE.get_or_init(|| {
-                        hayai::openapi::Schema {
-                            type_name: "string".to_string(),
-                            properties: std::collections::HashMap::new(),
-                            required: vec![],
-                            description: #desc_expr,
-                            enum_values: Some(vec![#(#variant_names.to_string()),*]),
-                            example: None,
-                        }
-                    }).clone()
-                },
-                nested_fn: || {
-                    static CACHE: std::sync::OnceLock<std::collections::HashMap<String, hayai::openapi::Schema>> = std::sync::OnceLock::new();
-                    CACHE.get_or_init(|| std::collections::HashMap::new()).clone()
-                },
-            }
-        }
+        #case_insensitive_impl
+
+        #validate_impl
+
+        #schema_info
     };
 
+    debug_expansion(&name.to_string(), &output);
     output.into()
 }
 
-fn api_model_struct(input: ItemStruct, custom_validation_fn: Option<proc_macro2::TokenStream>) -> TokenStream {
-    let name = &input.ident;
-    let vis = &input.vis;
-    let attrs = &input.attrs;
-    let generics = &input.generics;
-    let struct_description = extract_doc_comment(attrs);
+/// What a struct model's fields contribute, shared by `#[api_model]` and `#[derive(ApiModel)]`
+struct ModelFields<'a> {
+    /// `Validate` checks, one block per field with rules
+    validation_checks: Vec<proc_macro2::TokenStream>,
+    /// The same checks for the `Option`-wrapped fields of a generated patch model
+    patch_validation_checks: Vec<proc_macro2::TokenStream>,
+    /// `HasSchemaPatches` statements editing `props`
+    schema_patches: Vec<proc_macro2::TokenStream>,
+    /// The fields without hayai's helper attributes
+    clean_fields: Vec<syn::Field>,
+    /// The clean fields plus the serde and schemars attributes `#[api_model]` adds
+    model_fields: Vec<syn::Field>,
+    /// `#[schema(string_encoded)]` fields
+    encoded_fields: Vec<&'a syn::Field>,
+    /// Fields (de)serialized through a `hayai` module, with its path
+    serde_with: Vec<(&'a syn::Field, String)>,
+}
 
-    let fields = match &input.fields {
-        syn::Fields::Named(fields) => &fields.named,
-        _ => return syn::Error::new_spanned(&input, "api_model only supports structs with named fields")
-            .to_compile_error()
-            .into(),
-    };
+/// `#[serde(with = ..)]` for a field, with `default` for optional ones
+fn serde_with_attr(ty: &Type, with: &str) -> syn::Attribute {
+    if get_option_inner_type(ty).is_some() {
+        syn::parse_quote! { #[serde(default, with = #with)] }
+    } else {
+        syn::parse_quote! { #[serde(with = #with)] }
+    }
+}
 
+fn model_fields<'a>(fields: &'a syn::punctuated::Punctuated<syn::Field, syn::Token![,]>, rename_all: Option<&str>) -> ModelFields<'a> {
     let mut validation_checks = Vec::new();
+    let mut patch_validation_checks = Vec::new();
     let mut schema_patches = Vec::new();
     let mut clean_fields = Vec::new();
+    let mut model_fields = Vec::new();
+    let mut encoded_fields = Vec::new();
+    let mut serde_with = Vec::new();
 
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
-        let field_name_str = field_name.to_string();
+        // Schema properties and validation messages use the serialized name
+        let field_name_str = wire_field_name(field, rename_all);
+        // Checks are written against `__field: &FieldType` so the Patch variant can reuse them
+        let mut field_checks = Vec::new();
+        let mut string_encoded = false;
+        let decimal = is_decimal_type(get_option_inner_type(&field.ty).unwrap_or(&field.ty));
+        let mut decimal_as_number = false;
+        if decimal {
+            schema_patches.push(quote! {
+                {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                    prop.format = Some("decimal".to_string());
+                }
+            });
+        }
 
         // Extract doc comment for field description
         let field_desc = extract_doc_comment(&field.attrs);
         if !field_desc.is_empty() {
             schema_patches.push(quote! {
-                if let Some(prop) = props.get_mut(#field_name_str) {
+                {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                     prop.description = Some(#field_desc.to_string());
                 }
             });
@@ -564,18 +2809,19 @@ fn api_model_struct(input: ItemStruct, custom_validation_fn: Option<proc_macro2:
 
         for attr in &field.attrs {
             if attr.path().is_ident("validate") {
-                let _ = attr.parse_nested_meta(|meta| {
+                let parsed = attr.parse_nested_meta(|meta| {
                     if meta.path.is_ident("min_length") {
                         let value = meta.value()?;
                         let lit: syn::LitInt = value.parse()?;
                         let min: usize = lit.base10_parse()?;
-                        validation_checks.push(quote! {
-                            if self.#field_name.len() < #min {
-                                errors.push(format!("{}: must be at least {} characters", #field_name_str, #min));
+                        field_checks.push(quote! {
+                            if __field.len() < #min {
+                                errors.push(hayai::ValidationError::new(#field_name_str, "min_length", format!("must be at least {} characters", #min)).with_param("min", #min));
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.min_length = Some(#min);
                             }
                         });
@@ -583,55 +2829,116 @@ fn api_model_struct(input: ItemStruct, custom_validation_fn: Option<proc_macro2:
                         let value = meta.value()?;
                         let lit: syn::LitInt = value.parse()?;
                         let max: usize = lit.base10_parse()?;
-                        validation_checks.push(quote! {
-                            if self.#field_name.len() > #max {
-                                errors.push(format!("{}: must be at most {} characters", #field_name_str, #max));
+                        field_checks.push(quote! {
+                            if __field.len() > #max {
+                                errors.push(hayai::ValidationError::new(#field_name_str, "max_length", format!("must be at most {} characters", #max)).with_param("max", #max));
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.max_length = Some(#max);
                             }
                         });
                     } else if meta.path.is_ident("email") {
-                        validation_checks.push(quote! {
-                            {
-                                let email = &self.#field_name;
-                                let at_count = email.chars().filter(|&c| c == '@').count();
-                                let valid = at_count == 1
-                                    && !email.starts_with('@')
-                                    && !email.ends_with('@')
-                                    && {
-                                        if let Some(at_pos) = email.find('@') {
-                                            let domain = &email[at_pos + 1..];
-                                            !domain.is_empty() && domain.contains('.')
-                                                && !domain.starts_with('.') && !domain.ends_with('.')
-                                        } else {
-                                            false
-                                        }
-                                    };
-                                if !valid {
-                                    errors.push(format!("{}: must be a valid email address", #field_name_str));
+                        // `email`, or `email(strict, allow_no_tld)`
+                        let mut strict = false;
+                        let mut allow_no_tld = false;
+                        if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|option| {
+                                if option.path.is_ident("strict") {
+                                    strict = true;
+                                } else if option.path.is_ident("allow_no_tld") {
+                                    allow_no_tld = true;
+                                } else {
+                                    field_checks.push(syn::Error::new_spanned(
+                                        &option.path,
+                                        "unknown email option, expected `strict` or `allow_no_tld`",
+                                    ).to_compile_error());
                                 }
+                                Ok(())
+                            })?;
+                        }
+                        let check = if strict {
+                            quote! { hayai::email::is_valid_strict }
+                        } else {
+                            quote! { hayai::email::is_valid }
+                        };
+                        field_checks.push(quote! {
+                            if !#check(__field, hayai::email::EmailOptions { allow_no_tld: #allow_no_tld }) {
+                                errors.push(hayai::ValidationError::new(#field_name_str, "email", "must be a valid email address"));
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.format = Some("email".to_string());
                             }
                         });
+                    } else if meta.path.is_ident("minimum") && decimal {
+                        let (min, text, min_f64) = parse_decimal_bound(meta.value()?)?;
+                        field_checks.push(quote! {
+                            if let Some(__value) = hayai::decimal::DecimalField::decimal(__field) {
+                                if *__value < #min {
+                                    errors.push(hayai::ValidationError::new(#field_name_str, "minimum", format!("must be at least {}", #text)).with_param("min", #text));
+                                }
+                            }
+                        });
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.minimum = Some(#min_f64);
+                            }
+                        });
+                    } else if meta.path.is_ident("maximum") && decimal {
+                        let (max, text, max_f64) = parse_decimal_bound(meta.value()?)?;
+                        field_checks.push(quote! {
+                            if let Some(__value) = hayai::decimal::DecimalField::decimal(__field) {
+                                if *__value > #max {
+                                    errors.push(hayai::ValidationError::new(#field_name_str, "maximum", format!("must be at most {}", #text)).with_param("max", #text));
+                                }
+                            }
+                        });
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.maximum = Some(#max_f64);
+                            }
+                        });
+                    } else if meta.path.is_ident("max_scale") {
+                        if !decimal {
+                            return Err(meta.error("max_scale applies to Decimal fields"));
+                        }
+                        let lit: syn::LitInt = meta.value()?.parse()?;
+                        let max: u32 = lit.base10_parse()?;
+                        // Trailing zeros don't count: "19.990" has two decimal places
+                        let pattern = format!("^-?[0-9]+(\\.[0-9]{{0,{}}}0*)?$", max);
+                        field_checks.push(quote! {
+                            if let Some(__value) = hayai::decimal::DecimalField::decimal(__field) {
+                                if hayai::decimal::__scale(__value) > #max {
+                                    errors.push(hayai::ValidationError::new(#field_name_str, "max_scale", format!("must have at most {} decimal places", #max)).with_param("max", #max));
+                                }
+                            }
+                        });
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.pattern = Some(#pattern.to_string());
+                            }
+                        });
                     } else if meta.path.is_ident("minimum") {
                         let value = meta.value()?;
                         let lit: syn::LitInt = value.parse()?;
                         let min: i64 = lit.base10_parse()?;
                         let min_f64 = min as f64;
-                        validation_checks.push(quote! {
-                            if (self.#field_name as f64) < #min_f64 {
-                                errors.push(format!("{}: must be at least {}", #field_name_str, #min));
+                        field_checks.push(quote! {
+                            if (*__field as f64) < #min_f64 {
+                                errors.push(hayai::ValidationError::new(#field_name_str, "minimum", format!("must be at least {}", #min)).with_param("min", #min));
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.minimum = Some(#min_f64);
                             }
                         });
@@ -640,13 +2947,14 @@ fn api_model_struct(input: ItemStruct, custom_validation_fn: Option<proc_macro2:
                         let lit: syn::LitInt = value.parse()?;
                         let max: i64 = lit.base10_parse()?;
                         let max_f64 = max as f64;
-                        validation_checks.push(quote! {
-                            if (self.#field_name as f64) > #max_f64 {
-                                errors.push(format!("{}: must be at most {}", #field_name_str, #max));
+                        field_checks.push(quote! {
+                            if (*__field as f64) > #max_f64 {
+                                errors.push(hayai::ValidationError::new(#field_name_str, "maximum", format!("must be at most {}", #max)).with_param("max", #max));
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.maximum = Some(#max_f64);
                             }
                         });
@@ -654,17 +2962,18 @@ fn api_model_struct(input: ItemStruct, custom_validation_fn: Option<proc_macro2:
                         let value = meta.value()?;
                         let lit: syn::LitStr = value.parse()?;
                         let pat = lit.value();
-                        validation_checks.push(quote! {
+                        field_checks.push(quote! {
                             {
                                 static RE: std::sync::OnceLock<hayai::regex::Regex> = std::sync::OnceLock::new();
                                 let re = RE.get_or_init(|| hayai::regex::Regex::new(#pat).expect("Invalid regex"));
-                                if !re.is_match(&self.#field_name) {
-                                    errors.push(format!("{}: must match pattern {}", #field_name_str, #pat));
+                                if !re.is_match(__field) {
+                                    errors.push(hayai::ValidationError::new(#field_name_str, "pattern", format!("must match pattern {}", #pat)).with_param("pattern", #pat));
                                 }
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.pattern = Some(#pat.to_string());
                             }
                         });
@@ -672,114 +2981,601 @@ fn api_model_struct(input: ItemStruct, custom_validation_fn: Option<proc_macro2:
                         let value = meta.value()?;
                         let lit: syn::LitInt = value.parse()?;
                         let min: usize = lit.base10_parse()?;
-                        validation_checks.push(quote! {
-                            if self.#field_name.len() < #min {
-                                errors.push(format!("{}: must have at least {} items", #field_name_str, #min));
+                        field_checks.push(quote! {
+                            if __field.len() < #min {
+                                errors.push(hayai::ValidationError::new(#field_name_str, "min_items", format!("must have at least {} items", #min)).with_param("min", #min));
                             }
                         });
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.min_items = Some(#min);
                             }
                         });
+                    } else {
+                        return Err(meta.error(
+                            "unknown validate rule, expected one of min_length, max_length, email, minimum, maximum, pattern, min_items, max_scale",
+                        ));
                     }
                     Ok(())
                 });
+                if let Err(e) = parsed {
+                    field_checks.push(e.to_compile_error());
+                }
             } else if attr.path().is_ident("schema") {
-                let _ = attr.parse_nested_meta(|meta| {
+                let parsed = attr.parse_nested_meta(|meta| {
                     if meta.path.is_ident("example") {
                         let value = meta.value()?;
                         let lit: syn::LitStr = value.parse()?;
                         let example_val = lit.value();
                         schema_patches.push(quote! {
-                            if let Some(prop) = props.get_mut(#field_name_str) {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
                                 prop.example = Some(#example_val.to_string());
                             }
                         });
+                    } else if meta.path.is_ident("required") {
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.required = true;
+                            }
+                        });
+                    } else if meta.path.is_ident("non_nullable") {
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.non_nullable = true;
+                            }
+                        });
+                    } else if meta.path.is_ident("string_encoded") {
+                        let inner = get_option_inner_type(&field.ty).unwrap_or(&field.ty);
+                        if !is_integer_type(inner) {
+                            return Err(meta.error("string_encoded applies to integer fields"));
+                        }
+                        string_encoded = true;
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.string_encoded = true;
+                            }
+                        });
+                    } else if meta.path.is_ident("as_number") {
+                        if !decimal {
+                            return Err(meta.error("as_number applies to Decimal fields"));
+                        }
+                        decimal_as_number = true;
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.decimal_as_number = true;
+                            }
+                        });
+                    } else if meta.path.is_ident("sensitive") {
+                        schema_patches.push(quote! {
+                            {
+                                let prop = props.entry(#field_name_str.to_string()).or_default();
+                                prop.sensitive = true;
+                            }
+                        });
+                    } else {
+                        return Err(meta.error("unknown schema option, expected one of example, required, non_nullable, string_encoded, sensitive, as_number"));
                     }
                     Ok(())
                 });
+                if let Err(e) = parsed {
+                    field_checks.push(e.to_compile_error());
+                }
+            }
+        }
+
+        // Geo fields check their own coordinates; errors are named after the field
+        let nested_check = quote! {
+            if let Err(nested) = hayai::Validate::validate_fields(__geo) {
+                errors.extend(nested.into_iter().map(|e| hayai::__nested_validation_error(#field_name_str, e)));
             }
+        };
+        let holds_geo = |ty: &Type| is_geo_type(ty) || get_vec_inner_type(ty).is_some_and(is_geo_type);
+        if holds_geo(&field.ty) {
+            field_checks.push(quote! { { let __geo = __field; #nested_check } });
+        } else if get_option_inner_type(&field.ty).is_some_and(holds_geo) {
+            field_checks.push(quote! { if let Some(__geo) = __field { #nested_check } });
+        }
+
+        if !field_checks.is_empty() {
+            validation_checks.push(quote! {
+                {
+                    let __field = &self.#field_name;
+                    #(#field_checks)*
+                }
+            });
+            patch_validation_checks.push(quote! {
+                if let Some(__field) = &self.#field_name {
+                    #(#field_checks)*
+                }
+            });
         }
 
         let mut clean_field = field.clone();
         clean_field.attrs.retain(|a| !a.path().is_ident("validate") && !a.path().is_ident("schema"));
+        // The model serializes the integer as a string; its schema still comes from the
+        // integer type and is flipped to `type: string` by the `string_encoded` patch
+        let mut model_field = clean_field.clone();
+        if string_encoded {
+            let ty = &field.ty;
+            let ty_str = quote!(#ty).to_string();
+            if OPENAPI {
+                model_field.attrs.push(syn::parse_quote! { #[schemars(with = #ty_str)] });
+            }
+            let with = if get_option_inner_type(ty).is_some() { "hayai::string_encoded::option" } else { "hayai::string_encoded" };
+            model_field.attrs.push(serde_with_attr(ty, with));
+            serde_with.push((field, with.to_string()));
+            encoded_fields.push(field);
+        }
+        if decimal {
+            // As with string_encoded, the schema still comes from the field's own type
+            let ty = &field.ty;
+            let ty_str = quote!(#ty).to_string();
+            if OPENAPI {
+                model_field.attrs.push(syn::parse_quote! { #[schemars(with = #ty_str)] });
+            }
+            let module = if decimal_as_number { "hayai::decimal::number" } else { "hayai::decimal" };
+            let with = if get_option_inner_type(ty).is_some() { format!("{}::option", module) } else { module.to_string() };
+            model_field.attrs.push(serde_with_attr(ty, &with));
+            serde_with.push((field, with));
+        }
+        model_fields.push(model_field);
         clean_fields.push(clean_field);
     }
 
-    let name_str = name.to_string();
+    ModelFields { validation_checks, patch_validation_checks, schema_patches, clean_fields, model_fields, encoded_fields, serde_with }
+}
 
-    let desc_expr = if struct_description.is_empty() {
-        quote! { None }
+fn api_model_struct(input: ItemStruct, opts: ApiModelOptions) -> TokenStream {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let attrs = &input.attrs;
+    let generics = &input.generics;
+    if opts.generate_patch && generics.lifetimes().next().is_some() {
+        return syn::Error::new_spanned(generics, "generate_patch is not supported on models that borrow")
+            .to_compile_error()
+            .into();
+    }
+
+    let fields = match &input.fields {
+        syn::Fields::Named(fields) => &fields.named,
+        _ => return syn::Error::new_spanned(&input, "api_model only supports structs with named fields")
+            .to_compile_error()
+            .into(),
+    };
+
+    let rename_all = opts.effective_rename_all(attrs);
+    let parts = model_fields(fields, rename_all.as_deref());
+    let ModelFields { patch_validation_checks, clean_fields, model_fields, encoded_fields, .. } = &parts;
+
+    let name_str = opts.component_base(name);
+
+    let serde_container = serde_container_tokens(&opts);
+    let shadow = format_ident!("__HayaiSchema{}", name);
+    let naming = schema_naming(&opts, name, generics, &name_str, attrs, quote! {
+        #serde_container
+        struct #shadow #generics {
+            #(#clean_fields),*
+        }
+    });
+    let schema_derive = &naming.derive;
+    let naming_items = &naming.items;
+    let module_prefix = naming.module_prefix;
+
+    let patch_model = if opts.generate_patch {
+        patch_model_tokens(name, vis, clean_fields, encoded_fields, patch_validation_checks, &opts, rename_all.as_deref())
     } else {
-        quote! { Some(#struct_description.to_string()) }
+        quote! {}
     };
 
+    let model_impls = model_impls(name, generics, &opts, &name_str, module_prefix, attrs, &parts);
+
     let output = quote! {
         #(#attrs)*
-        #[derive(hayai::serde::Serialize, hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+        #[derive(hayai::serde::Serialize, hayai::serde::Deserialize)]
+        #schema_derive
         #[serde(crate = "hayai::serde")]
-        #[schemars(crate = "hayai::schemars")]
+        #serde_container
         #vis struct #name #generics {
-            #(#clean_fields),*
+            #(#model_fields),*
         }
 
-        impl hayai::Validate for #name {
-            fn validate(&self) -> Result<(), Vec<String>> {
-                let mut errors = Vec::new();
-                #(#validation_checks)*
-                if errors.is_empty() { Ok(()) } else { Err(errors) }
+        #naming_items
+
+        #model_impls
+
+        #patch_model
+    };
+
+    debug_expansion(&name.to_string(), &output);
+    output.into()
+}
+
+/// The `Validate` and `HasSchemaPatches` impls and `SchemaInfo` registration of a struct model
+fn model_impls(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    opts: &ApiModelOptions,
+    name_str: &str,
+    module_prefix: bool,
+    attrs: &[syn::Attribute],
+    fields: &ModelFields,
+) -> proc_macro2::TokenStream {
+    let ModelFields { validation_checks, schema_patches, .. } = fields;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // Borrowed models are documented through their `'static` instance
+    let static_lifetimes: Vec<_> = generics.lifetimes().map(|_| quote! { 'static }).collect();
+    let static_ty = if static_lifetimes.is_empty() { quote! { #name } } else { quote! { #name<#(#static_lifetimes),*> } };
+    let (title_expr, desc_expr) = opts.title_and_description(attrs);
+    let strict_bodies = opts.strict_bodies;
+
+    let async_validation = match &opts.async_validation_fn {
+        Some(method) => quote! {
+            fn validate_async(&self, deps: &hayai::DepResolver<'_>) -> impl std::future::Future<Output = Result<(), hayai::AsyncValidationError>> + Send {
+                async move { Self::#method(self, deps).await.map_err(hayai::AsyncValidationError::from) }
             }
-        }
+        },
+        None => quote! {},
+    };
+
+    // `validate(custom = "..")` runs after the field checks, its messages coded `custom`
+    let custom_validation = match &opts.custom_validation_fn {
+        Some(path) => quote! {
+            if let Err(custom_errors) = #path(self) {
+                errors.extend(custom_errors.iter().map(|e| hayai::ValidationError::from_message(e, "custom")));
+            }
+        },
+        None => quote! {},
+    };
 
-        impl hayai::HasSchemaPatches for #name {
-            fn patch_schema(props: &mut std::collections::HashMap<String, hayai::openapi::PropertyPatch>) {
-                #(#schema_patches)*
+    let validate_impl = if VALIDATION {
+        quote! {
+            impl #impl_generics hayai::Validate for #name #ty_generics #where_clause {
+                fn validate_fields(&self) -> Result<(), Vec<hayai::ValidationError>> {
+                    let mut errors = Vec::new();
+                    #(#validation_checks)*
+                    #custom_validation
+                    if errors.is_empty() { Ok(()) } else { Err(errors) }
+                }
+                #async_validation
             }
         }
+    } else {
+        quote! {}
+    };
+    let schema_info = if OPENAPI {
+        quote! {
+            impl #impl_generics hayai::HasSchemaPatches for #name #ty_generics #where_clause {
+                fn patch_schema(props: &mut std::collections::HashMap<String, hayai::openapi::PropertyPatch>) {
+                    #(#schema_patches)*
+                }
+            }
 
-        hayai::inventory::submit! {
-            hayai::SchemaInfo {
-                name: #name_str,
-                schema_fn: || {
-                    static CACHE: std::sync::OnceLock<hayai::openapi::Schema> = std::sync::OnceLock::new();
-                    CACHE.get_or_init(|| {
-                        let base = hayai::schemars::schema_for!(#name);
-                        let result = hayai::openapi::schema_from_schemars_full(#name_str, &base);
-                        let mut schema = result.schema;
-                        schema.description = #desc_expr;
+            hayai::inventory::submit! {
+                hayai::SchemaInfo {
+                    name: #name_str,
+                    module_path: module_path!(),
+                    module_prefix: #module_prefix,
+                    strict_bodies: #strict_bodies,
+                    schema_fn: || {
+                        static CACHE: std::sync::OnceLock<hayai::openapi::Schema> = std::sync::OnceLock::new();
+                        CACHE.get_or_init(|| {
+                            let base = hayai::schemars::schema_for!(#static_ty);
+                            let result = hayai::openapi::schema_from_schemars_full(#name_str, &base);
+                            let mut schema = result.schema;
+                            schema.title = #title_expr;
+                            schema.description = #desc_expr;
+                            let mut patches = std::collections::HashMap::new();
+                            for (name, _) in &schema.properties {
+                                patches.insert(name.clone(), hayai::openapi::PropertyPatch::default());
+                            }
+                            <#static_ty as hayai::HasSchemaPatches>::patch_schema(&mut patches);
+                            hayai::openapi::apply_property_patches(&mut schema, patches);
+                            schema
+                        }).clone()
+                    },
+                    nested_fn: || {
+                        static CACHE: std::sync::OnceLock<std::collections::HashMap<String, hayai::openapi::Schema>> = std::sync::OnceLock::new();
+                        CACHE.get_or_init(|| {
+                            let base = hayai::schemars::schema_for!(#static_ty);
+                            let result = hayai::openapi::schema_from_schemars_full(#name_str, &base);
+                            result.nested
+                        }).clone()
+                    },
+                    patches_fn: || {
                         let mut patches = std::collections::HashMap::new();
-                        for (name, _) in &schema.properties {
-                            patches.insert(name.clone(), hayai::openapi::PropertyPatch::default());
-                        }
-                        <#name as hayai::HasSchemaPatches>::patch_schema(&mut patches);
-                        for (name, patch) in patches {
-                            if let Some(prop) = schema.properties.get_mut(&name) {
-                                if patch.min_length.is_some() { prop.min_length = patch.min_length; }
-                                if patch.max_length.is_some() { prop.max_length = patch.max_length; }
-                                if patch.format.is_some() { prop.format = patch.format; }
-                                if patch.minimum.is_some() { prop.minimum = patch.minimum; }
-                                if patch.maximum.is_some() { prop.maximum = patch.maximum; }
-                                if patch.pattern.is_some() { prop.pattern = patch.pattern.clone(); }
-                                if patch.min_items.is_some() { prop.min_items = patch.min_items; }
-                                if patch.description.is_some() { prop.description = patch.description.clone(); }
-                                if patch.example.is_some() { prop.example = patch.example.clone(); }
+                        <#static_ty as hayai::HasSchemaPatches>::patch_schema(&mut patches);
+                        patches
+                    },
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        #validate_impl
+
+        #schema_info
+    }
+}
+
+/// The `JsonSchema` impl, component name and `ComponentName` impl for a model.
+///
+/// Normally this is the schemars derive, renamed when `component` differs from the type
+/// name. With `module_prefix` the name is only known at runtime (`module_path!()`), so the
+/// derive goes on a hidden shadow item of the same shape and the model's impl delegates to
+/// it under the prefixed name.
+struct SchemaNaming {
+    /// Attributes deriving `JsonSchema` on the model itself (empty with `module_prefix`)
+    derive: proc_macro2::TokenStream,
+    /// Extra items: the shadow and manual impl, plus the `ComponentName` impl
+    items: proc_macro2::TokenStream,
+    /// `SchemaInfo::module_prefix`
+    module_prefix: bool,
+}
+
+fn schema_naming(
+    opts: &ApiModelOptions,
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    component: &str,
+    attrs: &[syn::Attribute],
+    shadow_body: proc_macro2::TokenStream,
+) -> SchemaNaming {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let component_name = if opts.module_prefix {
+        quote! { hayai::openapi::module_schema_name(module_path!(), #component) }
+    } else {
+        quote! { #component.to_string() }
+    };
+    let component_name_impl = quote! {
+        impl #impl_generics hayai::ComponentName for #name #ty_generics #where_clause {
+            fn component_name() -> String {
+                #component_name
+            }
+        }
+    };
+    if !OPENAPI {
+        // Nothing derives `JsonSchema`; the name still labels bodies in error messages
+        return SchemaNaming { derive: quote! {}, items: component_name_impl, module_prefix: opts.module_prefix };
+    }
+    if !opts.module_prefix {
+        let rename = (*name != component)
+            .then(|| quote! { #[schemars(rename = #component)] });
+        return SchemaNaming {
+            derive: quote! {
+                #[derive(hayai::schemars::JsonSchema)]
+                #[schemars(crate = "hayai::schemars")]
+                #rename
+            },
+            items: component_name_impl,
+            module_prefix: false,
+        };
+    }
+
+    let shadow = format_ident!("__HayaiSchema{}", name);
+    let shadow_attrs = attrs.iter()
+        .filter(|a| a.path().is_ident("doc") || a.path().is_ident("serde") || a.path().is_ident("schemars"));
+    let derives_default = attrs.iter()
+        .filter(|a| a.path().is_ident("derive"))
+        .filter_map(|a| a.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated).ok())
+        .any(|paths| paths.iter().any(|p| p.is_ident("Default")));
+    let default_derive = derives_default.then(|| quote! { #[derive(Default)] });
+    SchemaNaming {
+        derive: quote! {},
+        items: quote! {
+            #[doc(hidden)]
+            #[allow(dead_code, non_camel_case_types)]
+            #[derive(hayai::schemars::JsonSchema)]
+            #default_derive
+            #[schemars(crate = "hayai::schemars")]
+            #(#shadow_attrs)*
+            #shadow_body
+
+            impl #impl_generics hayai::schemars::JsonSchema for #name #ty_generics #where_clause {
+                fn schema_name() -> String {
+                    <Self as hayai::ComponentName>::component_name()
+                }
+
+                fn schema_id() -> std::borrow::Cow<'static, str> {
+                    std::borrow::Cow::Borrowed(concat!(module_path!(), "::", #component))
+                }
+
+                fn json_schema(gen: &mut hayai::schemars::gen::SchemaGenerator) -> hayai::schemars::schema::Schema {
+                    <#shadow #ty_generics as hayai::schemars::JsonSchema>::json_schema(gen)
+                }
+            }
+
+            #component_name_impl
+        },
+        module_prefix: true,
+    }
+}
+
+/// Container-level serde attributes implied by `#[api_model(...)]` options
+fn serde_container_tokens(opts: &ApiModelOptions) -> proc_macro2::TokenStream {
+    let deny_unknown = if opts.deny_unknown_fields {
+        quote! { #[serde(deny_unknown_fields)] }
+    } else {
+        quote! {}
+    };
+    let rename_all = match &opts.rename_all {
+        Some(rule) => quote! { #[serde(rename_all = #rule)] },
+        None => quote! {},
+    };
+    quote! { #deny_unknown #rename_all }
+}
+
+/// `#[api_model(generate_patch)]`: emit `{Name}Patch` with every field optional.
+///
+/// Fields that are already `Option<T>` become `Option<Option<T>>` so an explicit `null`
+/// ("clear it") is distinguishable from an absent key ("leave unchanged").
+fn patch_model_tokens(
+    name: &syn::Ident,
+    vis: &syn::Visibility,
+    fields: &[syn::Field],
+    encoded_fields: &[&syn::Field],
+    patch_validation_checks: &[proc_macro2::TokenStream],
+    opts: &ApiModelOptions,
+    rename_all: Option<&str>,
+) -> proc_macro2::TokenStream {
+    let patch_name = format_ident!("{}Patch", name);
+    let patch_name_str = format!("{}Patch", opts.component_base(name));
+    let doc = format!("Partial update for [`{}`]: only the fields present are applied.", name);
+
+    // Carry over a rename_all that was written as a plain serde attribute on the model
+    let serde_container = match (&opts.rename_all, rename_all) {
+        (None, Some(rule)) => {
+            let base = serde_container_tokens(opts);
+            quote! { #base #[serde(rename_all = #rule)] }
+        }
+        _ => serde_container_tokens(opts),
+    };
+
+    let mut patch_fields = Vec::new();
+    let mut apply_stmts = Vec::new();
+    // Required fields of the original model: omitted means "unchanged", null isn't part of the contract
+    let mut non_nullable_names = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let field_attrs = &field.attrs;
+        let encoded = encoded_fields.iter().any(|f| f.ident.as_ref() == Some(ident));
+        if encoded && get_option_inner_type(ty).is_some() {
+            return syn::Error::new_spanned(field, "string_encoded Option fields are not supported with generate_patch")
+                .to_compile_error();
+        }
+        if encoded {
+            let patch_ty_str = quote!(Option<#ty>).to_string();
+            let schema_with = OPENAPI.then(|| quote! { #[schemars(with = #patch_ty_str)] });
+            non_nullable_names.push(wire_field_name(field, rename_all));
+            patch_fields.push(quote! {
+                #(#field_attrs)*
+                #[serde(default, skip_serializing_if = "Option::is_none", with = "hayai::string_encoded::option")]
+                #schema_with
+                pub #ident: Option<#ty>
+            });
+        } else if get_option_inner_type(ty).is_some() {
+            patch_fields.push(quote! {
+                #(#field_attrs)*
+                #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "hayai::patch::double_option")]
+                pub #ident: Option<#ty>
+            });
+        } else {
+            non_nullable_names.push(wire_field_name(field, rename_all));
+            patch_fields.push(quote! {
+                #(#field_attrs)*
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub #ident: Option<#ty>
+            });
+        }
+        apply_stmts.push(quote! {
+            if let Some(value) = self.#ident {
+                target.#ident = value;
+            }
+        });
+    }
+
+    let shadow = format_ident!("__HayaiSchema{}", patch_name);
+    let doc_attr: syn::Attribute = syn::parse_quote! { #[doc = #doc] };
+    let naming = schema_naming(opts, &patch_name, &syn::Generics::default(), &patch_name_str, &[doc_attr], quote! {
+        #[derive(Default)]
+        #serde_container
+        struct #shadow {
+            #(#patch_fields),*
+        }
+    });
+    let schema_derive = &naming.derive;
+    let naming_items = &naming.items;
+    let module_prefix = naming.module_prefix;
+    let strict_bodies = opts.strict_bodies;
+
+    let validate_impl = if VALIDATION {
+        quote! {
+            impl hayai::Validate for #patch_name {
+                fn validate_fields(&self) -> Result<(), Vec<hayai::ValidationError>> {
+                    let mut errors = Vec::new();
+                    #(#patch_validation_checks)*
+                    if errors.is_empty() { Ok(()) } else { Err(errors) }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let schema_info = if OPENAPI {
+        quote! {
+            hayai::inventory::submit! {
+                hayai::SchemaInfo {
+                    name: #patch_name_str,
+                    module_path: module_path!(),
+                    module_prefix: #module_prefix,
+                    strict_bodies: #strict_bodies,
+                    schema_fn: || {
+                        static CACHE: std::sync::OnceLock<hayai::openapi::Schema> = std::sync::OnceLock::new();
+                        CACHE.get_or_init(|| {
+                            let base = hayai::schemars::schema_for!(#patch_name);
+                            let mut schema = hayai::openapi::schema_from_schemars_full(#patch_name_str, &base).schema;
+                            schema.description = Some(#doc.to_string());
+                            let mut patches = std::collections::HashMap::new();
+                            for (name, _) in &schema.properties {
+                                patches.insert(name.clone(), hayai::openapi::PropertyPatch::default());
                             }
-                        }
-                        schema
-                    }).clone()
-                },
-                nested_fn: || {
-                    static CACHE: std::sync::OnceLock<std::collections::HashMap<String, hayai::openapi::Schema>> = std::sync::OnceLock::new();
-                    CACHE.get_or_init(|| {
-                        let base = hayai::schemars::schema_for!(#name);
-                        let result = hayai::openapi::schema_from_schemars_full(#name_str, &base);
-                        result.nested
-                    }).clone()
-                },
+                            <#name as hayai::HasSchemaPatches>::patch_schema(&mut patches);
+                            hayai::openapi::apply_property_patches(&mut schema, patches);
+                            schema.required.clear();
+                            for name in [#(#non_nullable_names),*] {
+                                if let Some(prop) = schema.properties.get_mut(name) {
+                                    prop.nullable = false;
+                                }
+                            }
+                            schema
+                        }).clone()
+                    },
+                    nested_fn: || {
+                        static CACHE: std::sync::OnceLock<std::collections::HashMap<String, hayai::openapi::Schema>> = std::sync::OnceLock::new();
+                        CACHE.get_or_init(|| {
+                            let base = hayai::schemars::schema_for!(#patch_name);
+                            hayai::openapi::schema_from_schemars_full(#patch_name_str, &base).nested
+                        }).clone()
+                    },
+                    // Checked on the model itself
+                    patches_fn: std::collections::HashMap::new,
+                }
             }
         }
+    } else {
+        quote! {}
     };
 
-    output.into()
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Default, hayai::serde::Serialize, hayai::serde::Deserialize)]
+        #schema_derive
+        #[serde(crate = "hayai::serde")]
+        #serde_container
+        #vis struct #patch_name {
+            #(#patch_fields),*
+        }
+
+        #naming_items
+
+        impl #patch_name {
+            /// Apply every present field onto `target`, leaving the rest untouched
+            pub fn apply_to(self, target: &mut #name) {
+                #(#apply_stmts)*
+            }
+        }
+
+        #validate_impl
+
+        #schema_info
+    }
 }
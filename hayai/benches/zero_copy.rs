@@ -0,0 +1,69 @@
+//! Owned vs borrowed deserialization of a body with several large string fields, the
+//! trade-off behind `#[zero_copy]`. Run with `cargo bench --bench zero_copy`.
+
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct OwnedEvent {
+    source: String,
+    message: String,
+    payload: String,
+    trace: String,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct BorrowedEvent<'a> {
+    source: &'a str,
+    #[serde(borrow)]
+    message: Cow<'a, str>,
+    #[serde(borrow)]
+    payload: Cow<'a, str>,
+    #[serde(borrow)]
+    trace: Cow<'a, str>,
+}
+
+const ITERATIONS: u32 = 20_000;
+
+fn body(escaped: bool) -> Vec<u8> {
+    let text = |n: usize| if escaped { "a \\\"quoted\\\" line\\n".repeat(n) } else { "lorem ipsum dolor ".repeat(n) };
+    format!(
+        r#"{{"source":"sensor-7","message":"{}","payload":"{}","trace":"{}"}}"#,
+        text(64), text(512), text(128),
+    ).into_bytes()
+}
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    for escaped in [false, true] {
+        let bytes = body(escaped);
+        let owned = time(|| {
+            black_box(serde_json::from_slice::<OwnedEvent>(black_box(&bytes)).unwrap());
+        });
+        let borrowed = time(|| {
+            black_box(serde_json::from_slice::<BorrowedEvent>(black_box(&bytes)).unwrap());
+        });
+        println!(
+            "{} bytes{}: owned {:?}, borrowed {:?} ({:.2}x)",
+            bytes.len(),
+            if escaped { ", escaped" } else { "" },
+            owned,
+            borrowed,
+            owned.as_secs_f64() / borrowed.as_secs_f64(),
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! One log line per request, see [`HayaiApp::access_log`](crate::HayaiApp::access_log).
+//!
+//! Lines go to the `hayai::access` tracing target at info level, or to the writer given
+//! to [`HayaiApp::access_log_writer`](crate::HayaiApp::access_log_writer). The route
+//! template and operation id come from the route table; the subject is the
+//! [`AuthSubject`] of requests to `Auth<V>` routes.
+//!
+//! With [`HayaiApp::log_bodies`](crate::HayaiApp::log_bodies), the JSON body of a request
+//! answered with 4xx or 5xx is part of its line. Fields marked `#[schema(sensitive)]` on
+//! any model are replaced with `"***"` wherever their name appears in the body.
+
+use crate::auth::AuthSubject;
+use crate::proxy::ConnectionInfo;
+use axum::body::Bytes;
+use axum::extract::{MatchedPath, Request};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// How each request is rendered
+#[derive(Debug, Clone, Copy)]
+pub enum AccessLogFormat {
+    /// One JSON object per line with the fields of [`AccessLogRecord`]
+    Json,
+    /// NCSA Common Log Format: `ip - subject [time] "GET /path HTTP/1.1" status bytes`
+    Common,
+    Custom(fn(&AccessLogRecord) -> String),
+}
+
+/// Which request bodies are logged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogBodies {
+    #[default]
+    Never,
+    /// Bodies of requests answered with 4xx or 5xx
+    OnError,
+}
+
+/// Bodies longer than this are left out of the log
+pub const DEFAULT_LOGGED_BODY_LIMIT: usize = 16 * 1024;
+
+/// Placed in the request extensions when bodies are logged, filled by the body extractor
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct BodySlot {
+    limit: usize,
+    body: Arc<Mutex<Option<Bytes>>>,
+}
+
+impl BodySlot {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self { limit, body: Arc::default() }
+    }
+
+    /// The kept body, left in place for other readers
+    pub(crate) fn peek(&self) -> Option<Bytes> {
+        self.body.lock().unwrap().clone()
+    }
+
+    pub(crate) fn keep(&self, body: &Bytes) {
+        if body.len() <= self.limit {
+            *self.body.lock().unwrap() = Some(body.clone());
+        }
+    }
+}
+
+/// Everything known about a finished request
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessLogRecord {
+    /// RFC 3339, UTC
+    pub timestamp: String,
+    pub method: String,
+    pub path: String,
+    /// The matched route, e.g. `/users/{id}`; `None` when nothing matched
+    pub route: Option<String>,
+    pub operation_id: Option<&'static str>,
+    pub status: u16,
+    pub latency_ms: f64,
+    /// The incoming `X-Request-Id`, or a generated one
+    pub request_id: String,
+    /// The authenticated caller, see [`SecurityValidator::subject`](crate::auth::SecurityValidator::subject)
+    pub subject: Option<String>,
+    pub client_ip: Option<IpAddr>,
+    /// Response `Content-Length`, when known
+    pub bytes: Option<u64>,
+    /// The redacted JSON request body, see [`LogBodies`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    #[serde(skip)]
+    pub version: axum::http::Version,
+    #[serde(skip)]
+    pub started: SystemTime,
+}
+
+impl AccessLogRecord {
+    pub fn format(&self, format: AccessLogFormat) -> String {
+        match format {
+            AccessLogFormat::Json => serde_json::to_string(self).unwrap_or_default(),
+            AccessLogFormat::Common => format!(
+                "{} - {} [{}] \"{} {} {:?}\" {} {}",
+                self.client_ip.map_or("-".to_string(), |ip| ip.to_string()),
+                self.subject.as_deref().unwrap_or("-"),
+                common_time(self.started),
+                self.method,
+                self.path,
+                self.version,
+                self.status,
+                self.bytes.map_or("-".to_string(), |b| b.to_string()),
+            ),
+            AccessLogFormat::Custom(render) => render(self),
+        }
+    }
+}
+
+type Writer = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Access log settings, assembled by [`HayaiApp`](crate::HayaiApp)
+#[derive(Clone)]
+pub(crate) struct AccessLog {
+    pub(crate) format: AccessLogFormat,
+    pub(crate) writer: Option<Writer>,
+    pub(crate) excluded: HashSet<String>,
+    /// `(METHOD, axum path)` → handler name
+    pub(crate) operations: Arc<HashMap<(String, String), &'static str>>,
+    pub(crate) bodies: LogBodies,
+    pub(crate) body_limit: usize,
+    /// Field names redacted from logged bodies
+    pub(crate) sensitive: Arc<HashSet<String>>,
+}
+
+impl AccessLog {
+    pub(crate) fn new(format: AccessLogFormat) -> Self {
+        Self {
+            format,
+            writer: None,
+            excluded: HashSet::new(),
+            operations: Arc::default(),
+            bodies: LogBodies::Never,
+            body_limit: DEFAULT_LOGGED_BODY_LIMIT,
+            sensitive: Arc::default(),
+        }
+    }
+
+    pub(crate) fn set_writer(&mut self, writer: impl Write + Send + 'static) {
+        self.writer = Some(Arc::new(Mutex::new(Box::new(writer))));
+    }
+
+    fn emit(&self, record: &AccessLogRecord) {
+        let line = record.format(self.format);
+        match &self.writer {
+            Some(writer) => {
+                let mut writer = writer.lock().unwrap();
+                let _ = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+            }
+            None => tracing::info!(target: "hayai::access", "{}", line),
+        }
+    }
+}
+
+pub(crate) async fn log_request(log: AccessLog, mut req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let route = req.extensions().get::<MatchedPath>().map(|m| m.as_str().to_string());
+    if log.excluded.contains(&path) || route.as_ref().is_some_and(|r| log.excluded.contains(r)) {
+        return next.run(req).await;
+    }
+
+    let started = SystemTime::now();
+    let timer = Instant::now();
+    let method = req.method().to_string();
+    let version = req.version();
+    let client_ip = req.extensions().get::<ConnectionInfo>().and_then(|c| c.client_ip);
+    let request_id = req.headers().get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map_or_else(generate_request_id, str::to_string);
+    // Generated ids are handed on, so the response envelope and outgoing calls carry the same one
+    if let Ok(value) = header::HeaderValue::from_str(&request_id) {
+        req.headers_mut().insert("x-request-id", value);
+    }
+    let slot = (log.bodies == LogBodies::OnError).then(|| {
+        let slot = BodySlot::new(log.body_limit);
+        req.extensions_mut().insert(slot.clone());
+        slot
+    });
+
+    let response = next.run(req).await;
+
+    let failed = response.status().is_client_error() || response.status().is_server_error();
+    let body = slot.filter(|_| failed)
+        .and_then(|slot| slot.body.lock().unwrap().take())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .map(|mut body| {
+            redact(&mut body, &log.sensitive);
+            body
+        });
+
+    let operation_id = route.as_ref()
+        .and_then(|r| log.operations.get(&(method.clone(), r.clone())).copied());
+    let record = AccessLogRecord {
+        timestamp: rfc3339(started),
+        path,
+        operation_id,
+        route,
+        method,
+        status: response.status().as_u16(),
+        latency_ms: timer.elapsed().as_secs_f64() * 1000.0,
+        request_id,
+        subject: response.extensions().get::<AuthSubject>().map(|s| s.subject.clone()),
+        client_ip,
+        bytes: response.headers().get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok()),
+        body,
+        version,
+        started,
+    };
+    log.emit(&record);
+    response
+}
+
+/// Names of the `#[schema(sensitive)]` fields of every registered model
+pub(crate) fn sensitive_fields() -> HashSet<String> {
+    inventory::iter::<crate::SchemaInfo>.into_iter()
+        .flat_map(|info| (info.schema_fn)().properties.into_iter())
+        .filter(|(_, prop)| prop.write_only)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Replace the value of every field named in `sensitive`, at any depth, with `"***"`
+pub(crate) fn redact(value: &mut serde_json::Value, sensitive: &HashSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if sensitive.contains(key) {
+                    *field = serde_json::Value::String("***".to_string());
+                } else {
+                    redact(field, sensitive);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| redact(item, sensitive)),
+        _ => {}
+    }
+}
+
+pub(crate) fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64;
+    format!("{:016x}{:08x}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed) as u32)
+}
+
+/// Calendar date and time of day (UTC) for a point in time
+fn civil(time: SystemTime) -> (i64, u32, u32, u64, u32) {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = crate::precondition::civil_from_days((secs / 86_400) as i64);
+    (year, month, day, secs % 86_400, since_epoch.subsec_millis())
+}
+
+/// `2024-05-01T12:30:00.123Z`
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let (year, month, day, secs, millis) = civil(time);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z", year, month, day, secs / 3600, secs / 60 % 60, secs % 60, millis)
+}
+
+/// `01/May/2024:12:30:00 +0000`
+fn common_time(time: SystemTime) -> String {
+    let (year, month, day, secs, _) = civil(time);
+    format!("{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000", day, crate::precondition::MONTHS[month as usize - 1], year, secs / 3600, secs / 60 % 60, secs % 60)
+}
@@ -0,0 +1,247 @@
+//! An audit trail of mutating operations, see [`HayaiApp::audit_sink`](crate::HayaiApp::audit_sink).
+//!
+//! Every response of a route marked `#[audited(resource = "user")]` becomes one
+//! [`AuditEvent`]: who called it, which operation on which path parameters, the JSON
+//! request body with `#[schema(sensitive)]` fields replaced by `"***"`, the status, and
+//! when it was received and answered. Events are queued for the app's [`AuditSink`] and
+//! recorded by a background task, so a slow sink never holds up a response; when the
+//! queue is full the event is dropped and an error logged to the `hayai::audit` target.
+//!
+//! ```ignore
+//! #[put("/users/{id}")]
+//! #[audited(resource = "user")]
+//! async fn update_user(id: i64, auth: Auth<Claims>, body: UserUpdate) -> User { ... }
+//!
+//! HayaiApp::new().audit_sink(FileAuditSink::open("audit.jsonl")?)
+//! ```
+
+use crate::access_log::{self, BodySlot};
+use crate::auth::SubjectSlot;
+use crate::lifespan::BoxError;
+use crate::RouteInfo;
+use axum::extract::{FromRequestParts, MatchedPath, RawPathParams, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// Events waiting for the sink before new ones are dropped
+pub const DEFAULT_AUDIT_BUFFER: usize = 1024;
+
+/// One call of an `#[audited]` route
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    /// The `resource` given to `#[audited]`
+    pub resource: &'static str,
+    pub operation_id: &'static str,
+    pub method: String,
+    /// The matched route, e.g. `/users/{id}`
+    pub route: String,
+    pub path_params: BTreeMap<String, String>,
+    /// The authenticated caller, see [`SecurityValidator::subject`](crate::auth::SecurityValidator::subject)
+    pub subject: Option<String>,
+    /// The JSON request body, redacted; absent for other bodies and those over
+    /// [`DEFAULT_LOGGED_BODY_LIMIT`](access_log::DEFAULT_LOGGED_BODY_LIMIT)
+    pub body: Option<serde_json::Value>,
+    pub status: u16,
+    #[serde(serialize_with = "rfc3339")]
+    pub received_at: SystemTime,
+    #[serde(serialize_with = "rfc3339")]
+    pub completed_at: SystemTime,
+}
+
+fn rfc3339<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&access_log::rfc3339(*time))
+}
+
+/// Where audit events are kept, e.g. [`FileAuditSink`] or a compliance store
+pub trait AuditSink: Send + Sync + 'static {
+    fn record(&self, event: AuditEvent) -> impl Future<Output = Result<(), BoxError>> + Send;
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// [`AuditSink`] behind a pointer, as kept by the app
+pub(crate) trait DynAuditSink: Send + Sync {
+    fn record(&self, event: AuditEvent) -> BoxFuture<'_, Result<(), BoxError>>;
+}
+
+impl<A: AuditSink> DynAuditSink for A {
+    fn record(&self, event: AuditEvent) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(AuditSink::record(self, event))
+    }
+}
+
+/// Keeps events in memory, for tests
+#[derive(Clone, Default)]
+pub struct MemoryAuditSink {
+    events: Arc<Mutex<Vec<AuditEvent>>>,
+}
+
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The events recorded so far, oldest first
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    async fn record(&self, event: AuditEvent) -> Result<(), BoxError> {
+        self.events.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+/// Appends each event to a file as one line of JSON
+pub struct FileAuditSink {
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl FileAuditSink {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: tokio::sync::Mutex::new(tokio::fs::File::from_std(file)) })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    async fn record(&self, event: AuditEvent) -> Result<(), BoxError> {
+        let mut line = serde_json::to_vec(&event)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// The receiving end of the queue and the sink it feeds, until the delivery task takes them
+type Worker = (mpsc::Receiver<AuditEvent>, Arc<dyn DynAuditSink>);
+
+/// The queue in front of the sink; its task starts with the first event, inside the runtime
+struct Queue {
+    sender: mpsc::Sender<AuditEvent>,
+    worker: Mutex<Option<Worker>>,
+}
+
+/// Middleware state: the sink's queue, and the audited routes by `(METHOD, axum path)`
+#[derive(Clone)]
+pub(crate) struct AuditTrail {
+    queue: Arc<Queue>,
+    routes: Arc<HashMap<(String, String), &'static RouteInfo>>,
+    /// Every route's `operationId` by `(METHOD, axum path)`
+    operations: Arc<HashMap<(String, String), &'static str>>,
+    sensitive: Arc<HashSet<String>>,
+}
+
+impl AuditTrail {
+    pub(crate) fn new(
+        sink: Arc<dyn DynAuditSink>,
+        buffer: usize,
+        routes: HashMap<(String, String), &'static RouteInfo>,
+        operations: HashMap<(String, String), &'static str>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        Self {
+            queue: Arc::new(Queue { sender, worker: Mutex::new(Some((receiver, sink))) }),
+            routes: Arc::new(routes),
+            operations: Arc::new(operations),
+            sensitive: Arc::new(access_log::sensitive_fields()),
+        }
+    }
+
+    fn send(&self, event: AuditEvent) {
+        if let Some((receiver, sink)) = self.queue.worker.lock().unwrap().take() {
+            tokio::spawn(deliver(receiver, sink));
+        }
+        if let Err(e) = self.queue.sender.try_send(event) {
+            let (reason, event) = match e {
+                mpsc::error::TrySendError::Full(event) => ("audit queue full", event),
+                mpsc::error::TrySendError::Closed(event) => ("audit sink stopped", event),
+            };
+            tracing::error!(
+                target: "hayai::audit",
+                resource = event.resource,
+                operation_id = event.operation_id,
+                status = event.status,
+                "{}, event dropped", reason,
+            );
+        }
+    }
+}
+
+/// Hand queued events to the sink one at a time, in order
+async fn deliver(mut receiver: mpsc::Receiver<AuditEvent>, sink: Arc<dyn DynAuditSink>) {
+    while let Some(event) = receiver.recv().await {
+        let operation_id = event.operation_id;
+        let sink = sink.clone();
+        match tokio::spawn(async move { sink.record(event).await }).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!(target: "hayai::audit", operation_id, error = %e, "audit sink failed"),
+            Err(e) => tracing::error!(target: "hayai::audit", operation_id, error = %e, "audit sink panicked"),
+        }
+    }
+}
+
+pub(crate) async fn audit(trail: AuditTrail, req: Request, next: Next) -> Response {
+    let route_path = req.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string());
+    let key = route_path.as_ref().map(|path| (req.method().to_string(), path.clone()));
+    let route = key.as_ref().and_then(|key| trail.routes.get(key).copied());
+    let (Some(route_path), Some((route, resource))) = (route_path, route.and_then(|r| Some((r, r.audited?)))) else {
+        return next.run(req).await;
+    };
+    let operation_id = key.and_then(|key| trail.operations.get(&key).copied()).unwrap_or(route.handler_name);
+
+    let received_at = SystemTime::now();
+    let method = req.method().to_string();
+    let (mut parts, body) = req.into_parts();
+    let path_params = match RawPathParams::from_request_parts(&mut parts, &()).await {
+        Ok(params) => params.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+        Err(_) => BTreeMap::new(),
+    };
+    let mut req = Request::from_parts(parts, body);
+    // The access log's slot when it keeps bodies too
+    let body_slot = match req.extensions().get::<BodySlot>() {
+        Some(slot) => slot.clone(),
+        None => {
+            let slot = BodySlot::new(access_log::DEFAULT_LOGGED_BODY_LIMIT);
+            req.extensions_mut().insert(slot.clone());
+            slot
+        }
+    };
+    let subject_slot = req.extensions().get::<SubjectSlot>().cloned();
+
+    let response = next.run(req).await;
+
+    let body = body_slot.peek()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .map(|mut body| {
+            access_log::redact(&mut body, &trail.sensitive);
+            body
+        });
+    trail.send(AuditEvent {
+        resource,
+        operation_id,
+        method,
+        route: route_path,
+        path_params,
+        subject: subject_slot.and_then(|slot| slot.peek()).map(|s| s.subject),
+        body,
+        status: response.status().as_u16(),
+        received_at,
+        completed_at: SystemTime::now(),
+    });
+    response
+}
@@ -0,0 +1,171 @@
+//! Authenticated routes.
+//!
+//! A handler taking [`Auth<V>`] runs only after `V` validated the request, and is
+//! documented with `V`'s security scheme and a 401 response. Who the caller is becomes an
+//! [`AuthSubject`]: the [`Subject`] extractor reads it inside the handler, and it is attached
+//! to the response's extensions for middleware such as the access log.
+//!
+//! Everything but [`AuthSubject`] needs the `auth` feature. Under the `testing` feature,
+//! [`TestClient::authenticated_as`](crate::testing::TestClient::authenticated_as) hands
+//! `Auth<V>` its claims directly, skipping `V::validate`.
+
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "auth")]
+use {
+    crate::ApiError,
+    axum::extract::{FromRequestParts, Request},
+    axum::http::request::Parts,
+    axum::middleware::Next,
+    axum::response::Response,
+    std::future::Future,
+    std::ops::Deref,
+};
+
+/// Checks a request's credentials and produces the caller's claims
+///
+/// ```ignore
+/// impl SecurityValidator for Claims {
+///     async fn validate(parts: &http::request::Parts) -> Result<Self, ApiError> {
+///         let token = bearer_token(parts)?;
+///         decode(token).map_err(|_| ApiError::unauthorized("Invalid token"))
+///     }
+///
+///     fn subject(&self) -> String {
+///         self.user_id.to_string()
+///     }
+/// }
+/// ```
+#[cfg(feature = "auth")]
+pub trait SecurityValidator: Sized + Send + Sync + 'static {
+    /// Security scheme the route is documented with; `bearer` maps to `bearerAuth`
+    const SCHEME: &'static str = "bearer";
+
+    fn validate(parts: &Parts) -> impl Future<Output = Result<Self, ApiError>> + Send;
+
+    /// Opaque identifier of the caller for logs and downstream middleware
+    fn subject(&self) -> String {
+        DEFAULT_SUBJECT.to_string()
+    }
+}
+
+/// Claims of a validated request; dereferences to `V`
+#[derive(Debug, Clone)]
+#[cfg(feature = "auth")]
+pub struct Auth<V>(pub V);
+
+#[cfg(feature = "auth")]
+impl<V> Deref for Auth<V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        &self.0
+    }
+}
+
+#[cfg(feature = "auth")]
+impl<V: SecurityValidator, S: Send + Sync> FromRequestParts<S> for Auth<V> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        #[cfg(feature = "testing")]
+        let injected = InjectedClaims::take::<V>(parts)?;
+        #[cfg(not(feature = "testing"))]
+        let injected = None;
+        let claims = match injected {
+            Some(claims) => claims,
+            None => V::validate(parts).await?,
+        };
+        let subject = AuthSubject { scheme: V::SCHEME.to_string(), subject: claims.subject() };
+        tracing::debug!(scheme = %subject.scheme, subject = %subject.subject, "request authenticated");
+        if let Some(slot) = parts.extensions.get::<SubjectSlot>() {
+            *slot.0.lock().unwrap() = Some(subject.clone());
+        }
+        parts.extensions.insert(subject);
+        Ok(Auth(claims))
+    }
+}
+
+/// Claims put on a request by [`TestClient::authenticated_as`](crate::testing::TestClient::authenticated_as);
+/// only in-process requests can carry them
+#[derive(Clone)]
+#[cfg(all(feature = "auth", feature = "testing"))]
+pub(crate) struct InjectedClaims {
+    type_name: &'static str,
+    claims: Arc<Mutex<Option<Box<dyn std::any::Any + Send>>>>,
+}
+
+#[cfg(all(feature = "auth", feature = "testing"))]
+impl InjectedClaims {
+    pub(crate) fn new<V: Send + 'static>(claims: V) -> Self {
+        Self { type_name: std::any::type_name::<V>(), claims: Arc::new(Mutex::new(Some(Box::new(claims)))) }
+    }
+
+    /// The injected claims, once per request; an error when they aren't a `V`
+    fn take<V: 'static>(parts: &Parts) -> Result<Option<V>, ApiError> {
+        let Some(injected) = parts.extensions.get::<InjectedClaims>() else {
+            return Ok(None);
+        };
+        let mut claims = injected.claims.lock().unwrap();
+        match claims.take().map(|claims| claims.downcast::<V>()) {
+            None => Ok(None),
+            Some(Ok(claims)) => Ok(Some(*claims)),
+            Some(Err(_)) => Err(ApiError::internal(format!(
+                "authenticated_as was given {} claims, but the route authenticates with {}",
+                injected.type_name,
+                std::any::type_name::<V>(),
+            ))),
+        }
+    }
+}
+
+/// The subject of validators that don't override [`SecurityValidator::subject`], which
+/// can't tell callers apart
+pub const DEFAULT_SUBJECT: &str = "<authenticated>";
+
+/// Who made an authenticated request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthSubject {
+    pub scheme: String,
+    pub subject: String,
+}
+
+/// The caller's [`AuthSubject::subject`], for handlers that don't need the full claims.
+/// Must come after the `Auth<V>` argument; rejects with 401 when the request wasn't
+/// authenticated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg(feature = "auth")]
+pub struct Subject(pub String);
+
+#[cfg(feature = "auth")]
+impl<S: Send + Sync> FromRequestParts<S> for Subject {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts.extensions.get::<AuthSubject>()
+            .map(|s| Subject(s.subject.clone()))
+            .ok_or_else(|| ApiError::unauthorized("Not authenticated"))
+    }
+}
+
+/// Carries the subject out of the handler, which only sees a copy of the request's extensions
+#[derive(Clone, Default)]
+pub(crate) struct SubjectSlot(Arc<Mutex<Option<AuthSubject>>>);
+
+impl SubjectSlot {
+    /// The subject recorded so far, left in place for [`propagate_subject`]
+    pub(crate) fn peek(&self) -> Option<AuthSubject> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Attach the [`AuthSubject`] recorded while handling the request to the response
+#[cfg(feature = "auth")]
+pub(crate) async fn propagate_subject(mut req: Request, next: Next) -> Response {
+    let slot = SubjectSlot::default();
+    req.extensions_mut().insert(slot.clone());
+    let mut response = next.run(req).await;
+    if let Some(subject) = slot.0.lock().unwrap().take() {
+        response.extensions_mut().insert(subject);
+    }
+    response
+}
@@ -0,0 +1,80 @@
+//! Blocking work in async handlers.
+//!
+//! A handler that calls `std::thread::sleep`, synchronous I/O or a CPU-heavy loop holds a
+//! runtime worker for the whole call, stalling every other request scheduled on it. Move
+//! such work to the blocking pool with [`blocking`]; during development,
+//! [`HayaiApp::detect_blocking`](crate::HayaiApp::detect_blocking) logs the handlers whose
+//! single polls take too long.
+//!
+//! ```ignore
+//! #[post("/thumbnails")]
+//! async fn create_thumbnail(body: Bytes) -> Result<Thumbnail, ApiError> {
+//!     let image = hayai::blocking(move || resize(&body)).await?;
+//!     ...
+//! }
+//! ```
+
+use crate::ApiError;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Run `work` on the blocking thread pool and wait for it without holding up the runtime.
+///
+/// A panic in `work` becomes a 500.
+pub async fn blocking<F, T>(work: F) -> Result<T, ApiError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work).await
+        .map_err(|e| ApiError::internal(format!("Blocking task failed: {}", e)))
+}
+
+/// Middleware state: the threshold, and handler names by `(METHOD, axum path)`
+#[derive(Clone)]
+pub(crate) struct BlockingDetector {
+    pub(crate) threshold: Duration,
+    pub(crate) operations: Arc<HashMap<(String, String), &'static str>>,
+}
+
+/// A future whose polls are timed, warning about each one longer than `threshold`
+struct Watched<F> {
+    inner: Pin<Box<F>>,
+    handler: &'static str,
+    threshold: Duration,
+}
+
+impl<F: Future> Future for Watched<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let started = Instant::now();
+        let result = self.inner.as_mut().poll(cx);
+        let elapsed = started.elapsed();
+        if elapsed > self.threshold {
+            tracing::warn!(
+                target: "hayai::blocking",
+                handler = self.handler,
+                poll_ms = elapsed.as_secs_f64() * 1000.0,
+                "handler blocked the runtime; move blocking work to hayai::blocking",
+            );
+        }
+        result
+    }
+}
+
+pub(crate) async fn detect(config: BlockingDetector, req: Request, next: Next) -> Response {
+    let handler = req.extensions().get::<MatchedPath>()
+        .and_then(|path| config.operations.get(&(req.method().to_string(), path.as_str().to_string())).copied());
+    match handler {
+        Some(handler) => Watched { inner: Box::pin(next.run(req)), handler, threshold: config.threshold }.await,
+        None => next.run(req).await,
+    }
+}
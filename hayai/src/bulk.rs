@@ -0,0 +1,176 @@
+//! Batch endpoints answering with a result per item.
+//!
+//! A handler returning [`BulkResult<T>`] responds with one [`BulkItem`] per element of its
+//! `Vec<_>` body, in body order: `{index, status, data}` for the elements that succeeded and
+//! `{index, status, error}` for those that failed, `error` being the body the failure would
+//! have answered on its own. Elements are validated one by one instead of the whole body
+//! failing with 422: the handler receives the valid ones and returns a result for each, and
+//! the rejected ones are put back at their index with their validation error. With
+//! `#[status(207)]` the batch reports partial success in a single response.
+//!
+//! ```ignore
+//! #[post("/users/batch")]
+//! #[status(207)]
+//! async fn create_users(users: Vec<CreateUser>, db: Dep<Db>) -> BulkResult<User> {
+//!     BulkResult::from_results(users.into_iter().map(|user| db.insert(user)))
+//!         .success_status(StatusCode::CREATED)
+//! }
+//! ```
+//!
+//! The items are documented as a `<Model>BulkItem` component.
+
+use crate::openapi::{Property, Schema};
+use crate::{ApiError, AppState, Validate};
+use axum::http::StatusCode;
+use axum::response::Response;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The outcome of one element of a batch
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkItem<T> {
+    /// Position of the element in the request body
+    pub index: usize,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    /// The error's response body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
+}
+
+/// Per-item results of a batch, serialized as an array of [`BulkItem`]s
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct BulkResult<T> {
+    items: Vec<BulkItem<T>>,
+}
+
+impl<T> BulkResult<T> {
+    /// One item per result, in order; successes answer 200, errors their own status
+    pub fn from_results(results: impl IntoIterator<Item = Result<T, ApiError>>) -> Self {
+        let items = results.into_iter().enumerate().map(|(index, result)| match result {
+            Ok(data) => BulkItem { index, status: 200, data: Some(data), error: None },
+            Err(error) => BulkItem { index, status: error.status.as_u16(), data: None, error: Some(error.body_value()) },
+        });
+        Self { items: items.collect() }
+    }
+
+    /// Status of the items that succeeded (default 200), e.g. 201 for a batch create
+    pub fn success_status(mut self, status: StatusCode) -> Self {
+        for item in self.items.iter_mut().filter(|item| item.data.is_some()) {
+            item.status = status.as_u16();
+        }
+        self
+    }
+
+    pub fn items(&self) -> &[BulkItem<T>] {
+        &self.items
+    }
+
+    /// Whether every item succeeded
+    pub fn is_complete(&self) -> bool {
+        self.items.iter().all(|item| item.error.is_none())
+    }
+}
+
+impl<T> FromIterator<Result<T, ApiError>> for BulkResult<T> {
+    fn from_iter<I: IntoIterator<Item = Result<T, ApiError>>>(results: I) -> Self {
+        Self::from_results(results)
+    }
+}
+
+/// The elements of a batch body that failed validation
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct Rejections {
+    /// Elements in the body, `None` for routes without a `Vec<_>` body
+    received: Option<usize>,
+    errors: Vec<(usize, ApiError)>,
+}
+
+/// Validate a batch body element by element: the valid elements, and the index and error
+/// of each rejected one
+#[doc(hidden)]
+pub async fn __validate_elements<T: Validate>(state: &AppState, elements: Vec<T>) -> (Vec<T>, Rejections) {
+    let received = elements.len();
+    let mut valid = Vec::with_capacity(received);
+    let mut errors = Vec::new();
+    for (index, element) in elements.into_iter().enumerate() {
+        let checked = match crate::__validate(state, &element) {
+            Ok(()) => crate::__validate_async(state, &element).await,
+            Err(e) => Err(e),
+        };
+        match checked {
+            Ok(()) => valid.push(element),
+            Err(e) => errors.push((index, e)),
+        }
+    }
+    (valid, Rejections { received: Some(received), errors })
+}
+
+/// Respond with the handler's items moved to the indices of the elements it received, and
+/// the rejected elements' errors at theirs
+#[doc(hidden)]
+pub fn __bulk_response<T: Serialize>(state: &AppState, status: StatusCode, result: BulkResult<T>, rejections: Rejections) -> Result<Response, ApiError> {
+    let received = rejections.received.unwrap_or(result.items.len());
+    if result.items.len() + rejections.errors.len() != received {
+        return Err(ApiError::internal(format!(
+            "BulkResult has {} items for the {} valid elements received",
+            result.items.len(),
+            received - rejections.errors.len(),
+        )));
+    }
+    let mut rejected = rejections.errors.into_iter().peekable();
+    let mut handled = result.items.into_iter();
+    let items: Vec<BulkItem<T>> = (0..received)
+        .filter_map(|index| match rejected.next_if(|(i, _)| *i == index) {
+            Some((_, error)) => Some(BulkItem { index, status: error.status.as_u16(), data: None, error: Some(error.body_value()) }),
+            None => handled.next().map(|item| BulkItem { index, ..item }),
+        })
+        .collect();
+    crate::__json_response(state, status, &items)
+}
+
+/// The response schema of a batch whose data is documented by `data`: an array of a
+/// `<Model>BulkItem` component, returned with it, or of inline objects for data without a
+/// component
+pub(crate) fn schema(data: serde_json::Value) -> (serde_json::Value, Option<(String, Schema)>) {
+    let integer = |description: &str| Property {
+        type_name: "integer".to_string(),
+        description: Some(description.to_string()),
+        ..Default::default()
+    };
+    let error = Property {
+        ref_path: Some("#/components/schemas/ApiError".to_string()),
+        ..Default::default()
+    };
+    let required = vec!["index".to_string(), "status".to_string()];
+    let Some(reference) = data.get("$ref").and_then(|r| r.as_str()) else {
+        let inline = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "index": integer("Position of the element in the request body").to_json_value(),
+                "status": integer("HTTP status of the element").to_json_value(),
+                "data": data,
+                "error": error.to_json_value(),
+            },
+            "required": required,
+        });
+        return (serde_json::json!({ "type": "array", "items": inline }), None);
+    };
+    let name = format!("{}BulkItem", reference.trim_start_matches("#/components/schemas/"));
+    let component = Schema {
+        type_name: "object".to_string(),
+        properties: HashMap::from([
+            ("index".to_string(), integer("Position of the element in the request body")),
+            ("status".to_string(), integer("HTTP status of the element")),
+            ("data".to_string(), Property { ref_path: Some(reference.to_string()), ..Default::default() }),
+            ("error".to_string(), error),
+        ]),
+        required,
+        ..Default::default()
+    };
+    let items = serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) });
+    (serde_json::json!({ "type": "array", "items": items }), Some((name, component)))
+}
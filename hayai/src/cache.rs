@@ -0,0 +1,276 @@
+//! Cached responses for GET routes marked `#[cache(..)]`.
+//!
+//! The key is the route (its method, path template and `#[version]`), the request path,
+//! and whatever `vary` lists: `"query"` for every query parameter, `"query:page"` for one
+//! of them, `"header:Accept-Language"` for a header. Without `vary` the whole query string
+//! counts. Successful responses are stored with their status, headers and body; a hit is
+//! answered without calling the handler and carries `X-Cache: HIT`, a stored miss
+//! `X-Cache: MISS`.
+//!
+//! Routes needing authentication are refused unless they say `allow_authenticated`; their
+//! entries are then kept per subject and per hash of the `Authorization` header. Requests
+//! whose validator leaves [`subject`](crate::auth::SecurityValidator::subject) at its
+//! default and that carry no `Authorization` header can't be told apart, and aren't
+//! cached. Under a [tenant resolver](crate::tenant), entries are kept per tenant.
+//!
+//! Entries live in a [`MemoryCache`] unless the app installs another [`ResponseCache`] with
+//! [`HayaiApp::response_cache`](crate::HayaiApp::response_cache).
+//!
+//! ```ignore
+//! #[get("/articles")]
+//! #[cache(ttl = "30s", vary = ["query:page", "header:Accept-Language"])]
+//! async fn list_articles(query: Query<ArticleQuery>) -> Vec<Article> { ... }
+//! ```
+
+use crate::auth::{AuthSubject, DEFAULT_SUBJECT};
+use crate::tenant::Tenant;
+use crate::{ApiError, AppState, RouteInfo};
+use axum::body::{Body, Bytes};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderName, HeaderValue, StatusCode};
+use axum::response::Response;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Entries kept by the default [`MemoryCache`]
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Header telling whether the response came from the cache
+pub const X_CACHE: &str = "x-cache";
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// `#[cache(ttl = "30s", vary = [..], allow_authenticated)]` of one route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub ttl: Duration,
+    pub vary: &'static [Vary],
+    /// The route may be cached although it needs authentication
+    pub allow_authenticated: bool,
+}
+
+/// A part of the request that selects the cache entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vary {
+    /// The whole query string, independent of parameter order
+    Query,
+    /// One query parameter
+    QueryParam(&'static str),
+    /// One request header, given in lower case
+    Header(&'static str),
+}
+
+/// A stored response
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, Vec<u8>)>,
+    pub body: Bytes,
+}
+
+/// Storage for cached responses, e.g. [`MemoryCache`] or a shared store such as Redis
+pub trait ResponseCache: Send + Sync + 'static {
+    fn get(&self, key: &str) -> impl Future<Output = Option<CachedResponse>> + Send;
+    fn put(&self, key: &str, response: CachedResponse, ttl: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// [`ResponseCache`] behind a pointer, as kept in the app's settings
+pub(crate) trait DynResponseCache: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>>;
+    fn put<'a>(&'a self, key: &'a str, response: CachedResponse, ttl: Duration) -> BoxFuture<'a, ()>;
+}
+
+impl<C: ResponseCache> DynResponseCache for C {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>> {
+        Box::pin(ResponseCache::get(self, key))
+    }
+
+    fn put<'a>(&'a self, key: &'a str, response: CachedResponse, ttl: Duration) -> BoxFuture<'a, ()> {
+        Box::pin(ResponseCache::put(self, key, response, ttl))
+    }
+}
+
+struct Entry {
+    response: CachedResponse,
+    expires: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    /// Keys by `last_used`, least recent first
+    order: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+impl Entries {
+    /// Mark `key`'s entry as just used
+    fn touch(&mut self, key: &str) {
+        self.clock += 1;
+        let now = self.clock;
+        if let Some(entry) = self.map.get_mut(key) {
+            let key = self.order.remove(&entry.last_used).unwrap_or_else(|| key.to_string());
+            entry.last_used = now;
+            self.order.insert(now, key);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.map.remove(key) {
+            self.order.remove(&entry.last_used);
+        }
+    }
+}
+
+/// In-process cache dropping the least recently used entry when full
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), entries: Mutex::new(Entries::default()) }
+    }
+
+    /// Entries stored, including expired ones not looked up since
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.map.get(key)?.expires <= Instant::now() {
+            entries.remove(key);
+            return None;
+        }
+        entries.touch(key);
+        entries.map.get(key).map(|entry| entry.response.clone())
+    }
+
+    async fn put(&self, key: &str, response: CachedResponse, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(key);
+        entries.map.insert(key.to_string(), Entry { response, expires: Instant::now() + ttl, last_used: 0 });
+        entries.touch(key);
+        if entries.map.len() > self.capacity {
+            if let Some((_, oldest)) = entries.order.pop_first() {
+                entries.map.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// The entry for this request as selected by `vary`, kept per caller when `per_subject`:
+/// `None` when the caller can't be told apart from others
+pub(crate) fn request_key(route: &RouteInfo, vary: &[Vary], per_subject: bool, parts: &Parts) -> Option<String> {
+    // Handlers may share a name across modules, and a path across versions
+    let mut key = format!("{} {} v{} {}", route.method, route.path, route.version.unwrap_or(0), parts.uri.path());
+    if let Some(tenant) = parts.extensions.get::<Tenant>() {
+        key.push_str(&format!(" t:{}", tenant.id));
+    }
+    let query: Vec<(String, String)> = form_urlencoded::parse(parts.uri.query().unwrap_or("").as_bytes())
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    for vary in vary {
+        match vary {
+            Vary::Query => {
+                let mut sorted = query.clone();
+                sorted.sort();
+                key.push_str(&format!(" q{:?}", sorted));
+            }
+            Vary::QueryParam(name) => {
+                let values: Vec<&str> = query.iter().filter(|(k, _)| k == name).map(|(_, v)| v.as_str()).collect();
+                key.push_str(&format!(" q:{}{:?}", name, values));
+            }
+            Vary::Header(name) => {
+                let values: Vec<&[u8]> = parts.headers.get_all(*name).iter().map(HeaderValue::as_bytes).collect();
+                key.push_str(&format!(" h:{}{:?}", name, values));
+            }
+        }
+    }
+    if per_subject {
+        // The subject alone is shared by every caller of a validator keeping the default
+        let authorization = parts.headers.get(header::AUTHORIZATION).map(HeaderValue::as_bytes);
+        let subject = parts.extensions.get::<AuthSubject>();
+        if authorization.is_none() && subject.is_some_and(|s| s.subject == DEFAULT_SUBJECT) {
+            return None;
+        }
+        if let Some(subject) = subject {
+            key.push_str(&format!(" s:{}:{}", subject.scheme, subject.subject));
+        }
+        // A fixed digest, so replicas sharing a store agree on keys whatever their toolchain
+        let digest: String = match authorization {
+            Some(value) => Sha256::digest(value).iter().map(|b| format!("{:02x}", b)).collect(),
+            None => "-".to_string(),
+        };
+        key.push_str(&format!(" a:{}", digest));
+    }
+    Some(key)
+}
+
+fn to_response(cached: CachedResponse) -> Response {
+    let mut response = Response::new(Body::from(cached.body));
+    *response.status_mut() = StatusCode::from_u16(cached.status).unwrap_or(StatusCode::OK);
+    for (name, value) in cached.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::from_bytes(&value)) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response.headers_mut().insert(X_CACHE, HeaderValue::from_static("HIT"));
+    response
+}
+
+/// The key to store the response under; `Err` is the cached response to send instead
+#[doc(hidden)]
+pub async fn __lookup(state: &AppState, route: &'static RouteInfo, parts: &Parts) -> Result<Option<String>, Response> {
+    let (Some(policy), Some(cache)) = (&route.cache, &state.settings.response_cache) else {
+        return Ok(None);
+    };
+    let Some(key) = request_key(route, policy.vary, policy.allow_authenticated, parts) else {
+        return Ok(None);
+    };
+    match cache.get(&key).await {
+        Some(cached) => Err(to_response(cached)),
+        None => Ok(Some(key)),
+    }
+}
+
+/// Store a successful response under `key` and pass it on
+#[doc(hidden)]
+pub async fn __store(state: &AppState, route: &'static RouteInfo, key: Option<String>, response: Response) -> Result<Response, ApiError> {
+    let (Some(key), Some(policy), Some(cache)) = (key, &route.cache, &state.settings.response_cache) else {
+        return Ok(response);
+    };
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+    let (mut head, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await
+        .map_err(|e| ApiError::internal(format!("Failed to read response for caching: {}", e)))?;
+    let headers = head.headers.iter()
+        .filter(|(name, _)| *name != header::SET_COOKIE)
+        .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+        .collect();
+    let cached = CachedResponse { status: head.status.as_u16(), headers, body: body.clone() };
+    cache.put(&key, cached, policy.ttl).await;
+    head.headers.insert(X_CACHE, HeaderValue::from_static("MISS"));
+    Ok(Response::from_parts(head, Body::from(body)))
+}
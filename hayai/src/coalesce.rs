@@ -0,0 +1,135 @@
+//! Shared handler runs for identical GET requests in flight, marked `#[coalesce]`.
+//!
+//! Requests with the same key (the route and its version, the request path and whatever
+//! `vary` lists, with the same rules and default as [`#[cache]`](crate::cache)) that arrive
+//! while one of them is being handled don't call the handler: they wait for that run and
+//! get a copy of its response, error responses included, carrying `X-Coalesced: true`. Keys are always kept
+//! per subject and per hash of the `Authorization` header, so callers only ever share with
+//! themselves on secured routes; requests that can't be told apart (the default subject and
+//! no `Authorization` header) always run the handler.
+//!
+//! Nothing is kept once the run finishes; pair it with `#[cache]` to also answer later
+//! requests. If the running request is dropped, say because its client went away, one of
+//! the waiting requests runs the handler instead.
+//!
+//! ```ignore
+//! #[get("/leaderboard")]
+//! #[coalesce(vary = ["query:season"])]
+//! async fn leaderboard(query: Query<SeasonQuery>, db: Dep<Database>) -> Vec<Entry> { ... }
+//! ```
+
+use crate::cache::{request_key, Vary};
+use crate::i18n::Localizable;
+use crate::{ApiError, AppState, RouteInfo};
+use axum::body::{Body, Bytes};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// Header marking a response copied from another request's run
+pub const X_COALESCED: &str = "x-coalesced";
+
+/// `#[coalesce(vary = [..])]` of one route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoalescePolicy {
+    pub vary: &'static [Vary],
+}
+
+/// A finished run's response, copied to every waiting request
+#[derive(Clone)]
+struct SharedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    localizable: Option<Localizable>,
+}
+
+impl SharedResponse {
+    async fn read(response: Response) -> Result<Self, ApiError> {
+        let (mut head, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await
+            .map_err(|e| ApiError::internal(format!("Failed to read response for coalescing: {}", e)))?;
+        let localizable = head.extensions.remove::<Localizable>();
+        Ok(Self { status: head.status, headers: head.headers, body, localizable })
+    }
+
+    fn to_response(&self, coalesced: bool) -> Response {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        if let Some(localizable) = &self.localizable {
+            response.extensions_mut().insert(localizable.clone());
+        }
+        if coalesced {
+            // Cookies belong to the request that ran
+            response.headers_mut().remove(header::SET_COOKIE);
+            response.headers_mut().insert(X_COALESCED, HeaderValue::from_static("true"));
+        }
+        response
+    }
+}
+
+/// Runs in flight by key; the receiver yields the response once the run finishes
+#[derive(Default)]
+pub(crate) struct InFlight(Mutex<HashMap<String, watch::Receiver<Option<SharedResponse>>>>);
+
+/// Removes the running request's entry when it finishes or is dropped
+struct Running<'a> {
+    in_flight: &'a InFlight,
+    key: &'a str,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.in_flight.0.lock().unwrap().remove(self.key);
+    }
+}
+
+/// Run the handler, or wait for the identical request already running it
+#[doc(hidden)]
+pub async fn __run<F>(state: &AppState, route: &'static RouteInfo, parts: &Parts, run: F) -> Result<Response, ApiError>
+where
+    F: Future<Output = Result<Response, ApiError>>,
+{
+    let Some(policy) = &route.coalesce else {
+        return run.await;
+    };
+    let Some(key) = request_key(route, policy.vary, true, parts) else {
+        return run.await;
+    };
+    let in_flight = &state.settings.in_flight;
+    loop {
+        let waiting = {
+            let mut runs = in_flight.0.lock().unwrap();
+            match runs.get(&key) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    runs.insert(key.clone(), receiver);
+                    Ok(sender)
+                }
+            }
+        };
+        match waiting {
+            Ok(sender) => {
+                let _running = Running { in_flight, key: &key };
+                let response = run.await.unwrap_or_else(IntoResponse::into_response);
+                let shared = SharedResponse::read(response).await?;
+                sender.send_replace(Some(shared.clone()));
+                return Ok(shared.to_response(false));
+            }
+            Err(mut receiver) => {
+                // An error means the running request was dropped; take over
+                if let Ok(done) = receiver.wait_for(Option::is_some).await {
+                    if let Some(shared) = done.as_ref() {
+                        return Ok(shared.to_response(true));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Per-route limits on requests handled at once.
+//!
+//! `#[concurrency_limit(4)]` lets four requests into the handler; further ones queue until
+//! a slot frees up, or until `queue_timeout_ms` passes. With `fail_fast` they get 503 with
+//! `Retry-After` straight away. [`HayaiApp::default_concurrency_limit`](crate::HayaiApp::default_concurrency_limit)
+//! sets a limit for every route without the attribute; each route still counts separately.
+//!
+//! ```ignore
+//! #[get("/reports/{id}")]
+//! #[concurrency_limit(2, queue_timeout_ms = 5000)]
+//! async fn build_report(id: i64) -> Report { ... }
+//! ```
+
+use crate::{ApiError, AppState, RouteInfo};
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Seconds clients are asked to wait before retrying a rejected request
+pub const RETRY_AFTER_SECS: u64 = 1;
+
+/// How many requests a route handles at once, and what happens to the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimit {
+    pub max: usize,
+    pub when_full: WhenFull,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhenFull {
+    /// Wait for a slot, giving up with 503 after `timeout_ms` if set
+    Queue { timeout_ms: Option<u64> },
+    /// Answer 503 immediately
+    Reject,
+}
+
+impl ConcurrencyLimit {
+    /// Queue excess requests without a time limit
+    pub const fn queue(max: usize) -> Self {
+        Self { max, when_full: WhenFull::Queue { timeout_ms: None } }
+    }
+
+    /// Reject excess requests with 503
+    pub const fn fail_fast(max: usize) -> Self {
+        Self { max, when_full: WhenFull::Reject }
+    }
+
+    /// Give up on queued requests after `timeout`
+    pub const fn queue_timeout(self, timeout: Duration) -> Self {
+        Self { max: self.max, when_full: WhenFull::Queue { timeout_ms: Some(timeout.as_millis() as u64) } }
+    }
+}
+
+/// The semaphore of one route
+pub(crate) struct RouteLimiter {
+    limit: ConcurrencyLimit,
+    slots: Arc<Semaphore>,
+}
+
+/// Identity of a route's static `RouteInfo`, the same under every router prefix
+fn route_key(route: &RouteInfo) -> usize {
+    route as *const RouteInfo as usize
+}
+
+/// Limiters for the mounted routes that have a limit of their own or get `default`
+pub(crate) fn limiters(routes: &[&'static RouteInfo], default: Option<ConcurrencyLimit>) -> HashMap<usize, RouteLimiter> {
+    routes.iter()
+        .filter_map(|route| {
+            let limit = route.concurrency_limit.or(default)?;
+            let slots = Arc::new(Semaphore::new(limit.max));
+            Some((route_key(route), RouteLimiter { limit, slots }))
+        })
+        .collect()
+}
+
+/// A slot in the route's limit, held until the wrapper returns; `Err` is the 503 to send
+#[doc(hidden)]
+pub async fn __acquire(state: &AppState, route: &'static RouteInfo) -> Result<Option<OwnedSemaphorePermit>, Response> {
+    let Some(limiter) = state.settings.concurrency.get(&route_key(route)) else {
+        return Ok(None);
+    };
+    let slots = limiter.slots.clone();
+    let permit = match limiter.limit.when_full {
+        WhenFull::Reject => slots.try_acquire_owned().ok(),
+        WhenFull::Queue { timeout_ms: None } => slots.acquire_owned().await.ok(),
+        WhenFull::Queue { timeout_ms: Some(ms) } => {
+            tokio::time::timeout(Duration::from_millis(ms), slots.acquire_owned()).await.ok().and_then(Result::ok)
+        }
+    };
+    match permit {
+        Some(permit) => Ok(Some(permit)),
+        None => {
+            let mut response = ApiError::service_unavailable(format!("Too many concurrent requests to {}", route.handler_name)).into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+            Err(response)
+        }
+    }
+}
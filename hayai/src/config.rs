@@ -0,0 +1,150 @@
+//! Typed configuration from environment variables and an optional TOML/JSON file,
+//! see [`HayaiApp::config_from_env`](crate::HayaiApp::config_from_env).
+//!
+//! `HAYAI_DATABASE_URL=postgres://..` fills a `database_url` field: the prefix is stripped
+//! and the rest lower-cased. Environment variables win over keys in the file. Scalars are
+//! parsed from their string form; repeated file array items become lists.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Where configuration is read from
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    prefix: String,
+    pub(crate) file: Option<PathBuf>,
+}
+
+impl Default for ConfigSource {
+    fn default() -> Self {
+        Self::env("HAYAI_")
+    }
+}
+
+impl ConfigSource {
+    /// Environment variables starting with `prefix`, e.g. `"APP_"`
+    pub fn env(prefix: &str) -> Self {
+        Self { prefix: prefix.to_string(), file: None }
+    }
+
+    /// Also read a `.toml` or `.json` file; environment variables override its keys
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Deserialize `T` from this source
+    pub fn load<T: DeserializeOwned>(&self) -> Result<T, ConfigError> {
+        let entries = self.entries()?;
+        let values = entries.iter().map(|e| (e.key.clone(), e.values.clone())).collect();
+        crate::query::from_entries(values).map_err(|e| self.describe(&entries, &e.to_string()))
+    }
+
+    fn entries(&self) -> Result<Vec<Entry>, ConfigError> {
+        let mut entries = match &self.file {
+            Some(path) => file_entries(path)?,
+            None => Vec::new(),
+        };
+        for (name, value) in std::env::vars() {
+            let Some(key) = name.strip_prefix(&self.prefix) else { continue };
+            let key = key.to_lowercase();
+            entries.retain(|e| e.key != key);
+            entries.push(Entry { key, values: vec![value], origin: name });
+        }
+        Ok(entries)
+    }
+
+    /// Name the variable (or file key) behind a deserialization error
+    fn describe(&self, entries: &[Entry], msg: &str) -> ConfigError {
+        if let Some(field) = msg.strip_prefix("missing field `").and_then(|m| m.strip_suffix('`')) {
+            return ConfigError {
+                variable: Some(format!("{}{}", self.prefix, field.to_uppercase())),
+                message: "is required but not set".to_string(),
+            };
+        }
+        if let Some((key, reason)) = msg.split_once(": ") {
+            if let Some(entry) = entries.iter().find(|e| e.key == key) {
+                return ConfigError { variable: Some(entry.origin.clone()), message: reason.to_string() };
+            }
+        }
+        ConfigError { variable: None, message: msg.to_string() }
+    }
+}
+
+/// One configuration key and where it came from
+struct Entry {
+    key: String,
+    values: Vec<String>,
+    /// Environment variable name, or `path:key` for file keys
+    origin: String,
+}
+
+fn file_entries(path: &Path) -> Result<Vec<Entry>, ConfigError> {
+    let file_error = |message: String| ConfigError { variable: Some(path.display().to_string()), message };
+    let text = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+    let table: serde_json::Value = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| file_error(e.to_string()))?,
+        Some("json") => serde_json::from_str(&text).map_err(|e| file_error(e.to_string()))?,
+        _ => return Err(file_error("expected a .toml or .json file".to_string())),
+    };
+    let serde_json::Value::Object(table) = table else {
+        return Err(file_error("expected a table of keys".to_string()));
+    };
+
+    let mut entries = Vec::new();
+    for (key, value) in table {
+        let items = match value {
+            serde_json::Value::Array(items) => items,
+            scalar => vec![scalar],
+        };
+        let values = items.into_iter()
+            .map(|item| match item {
+                serde_json::Value::String(s) => Ok(s),
+                serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                    Err(file_error(format!("`{}`: nested tables are not supported", key)))
+                }
+                scalar => Ok(scalar.to_string()),
+            })
+            .collect::<Result<_, _>>()?;
+        entries.push(Entry { origin: format!("{}:{}", path.display(), key), key, values });
+    }
+    Ok(entries)
+}
+
+/// Invalid or missing configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// The environment variable or `file:key` at fault, when known
+    pub variable: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.variable {
+            Some(variable) => write!(f, "invalid configuration: {}: {}", variable, self.message),
+            None => write!(f, "invalid configuration: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Well-known keys that configure the app itself
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HayaiConfig {
+    /// Address for [`serve_from_config`](crate::HayaiApp::serve_from_config), `BIND`
+    pub bind: String,
+    /// Maximum request body size in bytes, `BODY_LIMIT`
+    pub body_limit: Option<usize>,
+    /// Serve `/docs` and `/openapi.json`, `DOCS`
+    pub docs: bool,
+}
+
+impl Default for HayaiConfig {
+    fn default() -> Self {
+        Self { bind: "0.0.0.0:8000".to_string(), body_limit: None, docs: true }
+    }
+}
@@ -0,0 +1,461 @@
+//! Contract tests of a running service against its OpenAPI document, behind the `contract`
+//! feature.
+//!
+//! [`verify`] sends one request to each operation of the spec, with the documented
+//! examples or values synthesized from the schemas for its path, required query and header
+//! parameters and JSON body. It then checks that the answer's status is documented and
+//! that a JSON body matches the response schema: types, `required` properties, `enum`
+//! values, closed objects (`additionalProperties: false`), arrays item by item,
+//! `$ref`/`allOf`/`anyOf`/`oneOf` and nullable values. Each mismatch is reported with a
+//! JSON pointer into the body.
+//!
+//! ```ignore
+//! let spec: Value = reqwest::get("https://billing.internal/openapi.json").await?.json().await?;
+//! let report = contract::verify(spec, "https://billing.internal", Expectations::new()
+//!     .bearer(&token)
+//!     .skip_destructive()
+//!     .path_param("invoice_id", "inv_test_1")).await;
+//! report.assert_ok();
+//! ```
+
+use axum::http::Method;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Methods [`Expectations::skip_destructive`] leaves out
+pub const DESTRUCTIVE_METHODS: [&str; 3] = ["DELETE", "PUT", "PATCH"];
+
+/// How deep synthesized values nest before recursive schemas are cut off
+const MAX_DEPTH: usize = 8;
+
+const METHODS: [&str; 7] = ["get", "put", "post", "delete", "patch", "head", "options"];
+
+/// Which operations to call, and what to send them besides the spec's examples
+#[derive(Debug, Clone, Default)]
+pub struct Expectations {
+    operations: Option<Vec<String>>,
+    skip_methods: Vec<Method>,
+    headers: Vec<(String, String)>,
+    path_params: HashMap<String, String>,
+}
+
+impl Expectations {
+    /// Every operation, without credentials
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only the operations with these operationIds
+    pub fn operations<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.operations = Some(ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Leave out operations with `method`
+    pub fn skip_method(mut self, method: Method) -> Self {
+        self.skip_methods.push(method);
+        self
+    }
+
+    /// Leave out the [`DESTRUCTIVE_METHODS`], for services holding data that matters
+    pub fn skip_destructive(self) -> Self {
+        DESTRUCTIVE_METHODS.iter().fold(self, |expectations, method| {
+            expectations.skip_method(Method::from_bytes(method.as_bytes()).expect("a valid method"))
+        })
+    }
+
+    /// Send `Authorization: Bearer <token>` with every request
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("Authorization", &format!("Bearer {}", token))
+    }
+
+    /// Send `name: value` with every request, e.g. an API key
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Fill the `{name}` path parameter with `value` instead of the synthesized one, e.g.
+    /// the id of a record known to exist
+    pub fn path_param(mut self, name: &str, value: &str) -> Self {
+        self.path_params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    fn selects(&self, method: &str, operation: &Value) -> bool {
+        let id = operation["operationId"].as_str().unwrap_or_default();
+        self.operations.as_ref().is_none_or(|ids| ids.iter().any(|selected| selected == id))
+            && !self.skip_methods.iter().any(|skipped| skipped.as_str().eq_ignore_ascii_case(method))
+    }
+}
+
+/// A way the service departs from its spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// `GET /users/{id}`
+    pub operation: String,
+    /// The response status, when there was a response
+    pub status: Option<u16>,
+    /// Where in the response body, e.g. `#/items/0/id`; `None` for the response as a whole
+    pub pointer: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.operation)?;
+        if let Some(status) = self.status {
+            write!(f, " ({})", status)?;
+        }
+        if let Some(pointer) = &self.pointer {
+            write!(f, " at {}", pointer)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// What [`verify`] called and found
+#[derive(Debug, Clone, Default)]
+pub struct ContractReport {
+    /// Operations called, as `GET /users/{id}`
+    pub checked: Vec<String>,
+    /// Operations left out by the [`Expectations`]
+    pub skipped: Vec<String>,
+    pub violations: Vec<Violation>,
+}
+
+impl ContractReport {
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Panic listing the violations, if there are any
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "{}", self);
+    }
+}
+
+impl std::fmt::Display for ContractReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} operations checked, {} violations", self.checked.len(), self.violations.len())?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Call each operation of `spec` selected by `expectations` on the service at `base_url`,
+/// and report where its answers depart from the spec
+pub async fn verify(spec: Value, base_url: &str, expectations: Expectations) -> ContractReport {
+    let client = reqwest::Client::new();
+    let base_url = base_url.trim_end_matches('/');
+    let mut report = ContractReport::default();
+
+    let mut operations: Vec<(&String, &str, &Value)> = spec["paths"].as_object().into_iter()
+        .flat_map(|paths| paths.iter())
+        .flat_map(|(path, item)| METHODS.iter().filter_map(move |method| Some((path, *method, item.get(*method)?))))
+        .collect();
+    operations.sort_by_key(|(path, method, _)| (*path, METHODS.iter().position(|m| m == method)));
+
+    for (path, method, operation) in operations {
+        let name = format!("{} {}", method.to_uppercase(), path);
+        if !expectations.selects(method, operation) {
+            report.skipped.push(name);
+            continue;
+        }
+        let violation = |status: Option<u16>, pointer: Option<String>, message: String| Violation {
+            operation: name.clone(),
+            status,
+            pointer,
+            message,
+        };
+
+        let request = Contract { spec: &spec }.request(path, operation, &spec["paths"][path], &expectations);
+        let mut builder = client.request(Method::from_bytes(method.to_uppercase().as_bytes()).expect("a valid method"), format!("{}{}", base_url, request.path_and_query));
+        for (header, value) in expectations.headers.iter().chain(&request.headers) {
+            builder = builder.header(header, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.header(reqwest::header::CONTENT_TYPE, "application/json").body(body.to_string());
+        }
+        let response = match builder.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                report.violations.push(violation(None, None, format!("request failed: {}", e)));
+                report.checked.push(name);
+                continue;
+            }
+        };
+        let status = response.status().as_u16();
+        let json = response.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        let body = response.bytes().await.unwrap_or_default();
+
+        let contract = Contract { spec: &spec };
+        let Some(documented) = contract.documented_response(operation, status) else {
+            report.violations.push(violation(Some(status), None, "status is not documented".to_string()));
+            report.checked.push(name);
+            continue;
+        };
+        let schema = documented.pointer("/content/application~1json/schema");
+        match schema {
+            Some(schema) if !body.is_empty() => match serde_json::from_slice::<Value>(&body).ok().filter(|_| json) {
+                Some(value) => {
+                    let mut mismatches = Vec::new();
+                    contract.check(&value, schema, "#", &mut mismatches);
+                    report.violations.extend(mismatches.into_iter()
+                        .map(|(pointer, message)| violation(Some(status), Some(pointer), message)));
+                }
+                None => report.violations.push(violation(Some(status), None, "body is not the documented JSON".to_string())),
+            },
+            _ => {}
+        }
+        report.checked.push(name);
+    }
+    report
+}
+
+/// A request built from the spec
+struct ContractRequest {
+    path_and_query: String,
+    headers: Vec<(String, String)>,
+    body: Option<Value>,
+}
+
+struct Contract<'a> {
+    spec: &'a Value,
+}
+
+impl<'a> Contract<'a> {
+    /// `value`, or what its `$ref` points to in the spec
+    fn resolve(&self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..MAX_DEPTH {
+            match value.get("$ref").and_then(|r| r.as_str()).and_then(|r| r.strip_prefix('#')) {
+                Some(pointer) => value = self.spec.pointer(pointer).unwrap_or(&Value::Null),
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn request(&self, path: &str, operation: &'a Value, path_item: &'a Value, expectations: &Expectations) -> ContractRequest {
+        let parameters = path_item["parameters"].as_array().into_iter().flatten()
+            .chain(operation["parameters"].as_array().into_iter().flatten())
+            .map(|p| self.resolve(p));
+        let mut path = path.to_string();
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        let mut headers = Vec::new();
+        for parameter in parameters {
+            let name = parameter["name"].as_str().unwrap_or_default();
+            let location = parameter["in"].as_str().unwrap_or_default();
+            let required = parameter["required"].as_bool().unwrap_or(false);
+            let value = || {
+                let example = parameter.get("example").cloned().unwrap_or_else(|| self.example(&parameter["schema"], 0));
+                match example {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                }
+            };
+            match location {
+                "path" => {
+                    let value = expectations.path_params.get(name).cloned().unwrap_or_else(value);
+                    path = path.replace(&format!("{{{}}}", name), &value);
+                }
+                "query" if required => {
+                    query.append_pair(name, &value());
+                }
+                "header" if required && !expectations.headers.iter().any(|(h, _)| h.eq_ignore_ascii_case(name)) => {
+                    headers.push((name.to_string(), value()));
+                }
+                _ => {}
+            }
+        }
+        let query = query.finish();
+        let body = self.resolve(&operation["requestBody"]).pointer("/content/application~1json")
+            .map(|media| media.get("example").cloned().unwrap_or_else(|| self.example(&media["schema"], 0)));
+        ContractRequest {
+            path_and_query: if query.is_empty() { path } else { format!("{}?{}", path, query) },
+            headers,
+            body,
+        }
+    }
+
+    /// The response documented for `status`: the exact code, its range (`4XX`), or `default`
+    fn documented_response(&self, operation: &'a Value, status: u16) -> Option<&'a Value> {
+        let responses = operation.get("responses")?;
+        let range = format!("{}XX", status / 100);
+        [status.to_string(), range, "default".to_string()].iter()
+            .find_map(|key| responses.get(key))
+            .map(|response| self.resolve(response))
+    }
+
+    /// A value satisfying `schema` as far as its types and bounds go
+    fn example(&self, schema: &'a Value, depth: usize) -> Value {
+        let schema = self.resolve(schema);
+        if let Some(example) = schema.get("example").or_else(|| schema.get("default")) {
+            return example.clone();
+        }
+        if let Some(first) = schema["enum"].as_array().and_then(|values| values.first()) {
+            return first.clone();
+        }
+        if let Some(parts) = schema["allOf"].as_array() {
+            let mut merged = serde_json::Map::new();
+            for part in parts {
+                match self.example(part, depth + 1) {
+                    Value::Object(fields) => merged.extend(fields),
+                    other => return other,
+                }
+            }
+            return Value::Object(merged);
+        }
+        if let Some(variant) = schema["anyOf"].as_array().or_else(|| schema["oneOf"].as_array())
+            .and_then(|variants| variants.iter().find(|v| v["type"] != "null"))
+        {
+            return self.example(variant, depth + 1);
+        }
+        match json_type(schema).as_deref() {
+            Some("string") => {
+                let example = match schema["format"].as_str() {
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("date") => "2024-01-01",
+                    Some("email") => "user@example.com",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000001",
+                    Some("uri") => "https://example.com",
+                    _ => "example",
+                };
+                let min = schema["minLength"].as_u64().unwrap_or(0) as usize;
+                let max = schema["maxLength"].as_u64().map_or(usize::MAX, |max| max as usize);
+                let mut example = example.to_string();
+                if example.len() < min {
+                    example.push_str(&"x".repeat(min - example.len()));
+                }
+                example.truncate(max.max(min));
+                Value::String(example)
+            }
+            Some("integer") => {
+                let min = schema["minimum"].as_f64().map_or(1, |min| min.ceil() as i64);
+                let max = schema["maximum"].as_f64().map_or(i64::MAX, |max| max.floor() as i64);
+                Value::from(min.min(max))
+            }
+            Some("number") => {
+                let min = schema["minimum"].as_f64().unwrap_or(1.0);
+                Value::from(schema["maximum"].as_f64().map_or(min, |max| min.min(max)))
+            }
+            Some("boolean") => Value::Bool(true),
+            Some("array") if depth < MAX_DEPTH => Value::Array(vec![self.example(&schema["items"], depth + 1)]),
+            Some("array") => Value::Array(Vec::new()),
+            _ if depth < MAX_DEPTH => {
+                let fields = schema["properties"].as_object().into_iter().flatten()
+                    .filter(|(_, property)| property["readOnly"] != true)
+                    .map(|(name, property)| (name.clone(), self.example(property, depth + 1)))
+                    .collect();
+                Value::Object(fields)
+            }
+            _ => Value::Object(serde_json::Map::new()),
+        }
+    }
+
+    /// Push `(pointer, message)` for each place `value` departs from `schema`
+    fn check(&self, value: &Value, schema: &'a Value, pointer: &str, out: &mut Vec<(String, String)>) {
+        let schema = self.resolve(schema);
+        for part in schema["allOf"].as_array().into_iter().flatten() {
+            self.check(value, part, pointer, out);
+        }
+        if let Some(variants) = schema["anyOf"].as_array().or_else(|| schema["oneOf"].as_array()) {
+            let results: Vec<Vec<(String, String)>> = variants.iter()
+                .map(|variant| {
+                    let mut found = Vec::new();
+                    self.check(value, variant, pointer, &mut found);
+                    found
+                })
+                .collect();
+            if !results.iter().any(Vec::is_empty) {
+                // With one variant besides `null`, its mismatches say more than "none matched"
+                let non_null: Vec<usize> = (0..variants.len()).filter(|i| self.resolve(&variants[*i])["type"] != "null").collect();
+                match non_null.as_slice() {
+                    [only] if !value.is_null() => out.extend(results[*only].iter().cloned()),
+                    _ => out.push((pointer.to_string(), format!("matches none of the {} alternatives", variants.len()))),
+                }
+            }
+        }
+
+        if value.is_null() && (schema["nullable"] == true || types(schema).iter().any(|t| t == "null")) {
+            return;
+        }
+        let expected = types(schema);
+        let actual = value_type(value);
+        let fits = |t: &String| *t == actual || (t == "number" && actual == "integer");
+        if !expected.is_empty() && !expected.iter().any(fits) {
+            out.push((pointer.to_string(), format!("expected {}, got {}", expected.join(" or "), actual)));
+            return;
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                out.push((pointer.to_string(), format!("{} is not one of the documented values", value)));
+            }
+        }
+
+        match value {
+            Value::Object(fields) => {
+                for required in schema["required"].as_array().into_iter().flatten().filter_map(|r| r.as_str()) {
+                    if !fields.contains_key(required) {
+                        out.push((format!("{}/{}", pointer, escape(required)), "required property is missing".to_string()));
+                    }
+                }
+                let properties = schema["properties"].as_object();
+                for (name, field) in fields {
+                    let field_pointer = format!("{}/{}", pointer, escape(name));
+                    match properties.and_then(|p| p.get(name)) {
+                        Some(property) => self.check(field, property, &field_pointer, out),
+                        None => match &schema["additionalProperties"] {
+                            Value::Bool(false) => out.push((field_pointer, "property is not documented".to_string())),
+                            additional @ Value::Object(_) => self.check(field, additional, &field_pointer, out),
+                            _ => {}
+                        },
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check(item, item_schema, &format!("{}/{}", pointer, i), out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The schema's `type`, as a list; empty when it doesn't say
+fn types(schema: &Value) -> Vec<String> {
+    match &schema["type"] {
+        Value::String(t) => vec![t.clone()],
+        Value::Array(ts) => ts.iter().filter_map(|t| t.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The schema's single non-null `type`
+fn json_type(schema: &Value) -> Option<String> {
+    types(schema).into_iter().find(|t| t != "null")
+}
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// A property name as a JSON pointer segment
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,138 @@
+//! How long a handler may keep a request open, for long polling.
+//!
+//! A [`Deadline`] is the earliest of the app's [`default_deadline`](crate::HayaiApp::default_deadline),
+//! the route's timeout and the client's `X-Request-Timeout` header, counted from when the
+//! request arrived. Its waits also end as soon as graceful shutdown begins, so long polls
+//! answer promptly instead of holding the server open until the shutdown gives up on them.
+//!
+//! ```ignore
+//! #[get("/notifications")]
+//! async fn notifications(deadline: Deadline, bus: Dep<Bus>) -> Vec<Notification> {
+//!     deadline.sleep_until_or_event(bus.next_batch()).await.unwrap_or_default()
+//! }
+//! ```
+
+use crate::{ApiError, AppState};
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// The request header a client sends with how long it will wait
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout";
+
+/// The budget when neither the app, the route nor the client sets a shorter one
+pub(crate) const DEFAULT_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Set once graceful shutdown begins
+#[derive(Default)]
+pub(crate) struct ShutdownSignal {
+    begun: AtomicBool,
+    notify: Notify,
+}
+
+impl ShutdownSignal {
+    pub(crate) fn begin(&self) {
+        self.begun.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    fn has_begun(&self) -> bool {
+        self.begun.load(Ordering::SeqCst)
+    }
+
+    pub(crate) async fn wait(&self) {
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        if !self.has_begun() {
+            notified.await;
+        }
+    }
+}
+
+/// The time a handler has left to answer
+#[derive(Clone)]
+pub struct Deadline {
+    at: Instant,
+    shutdown: Arc<ShutdownSignal>,
+}
+
+impl Deadline {
+    /// When the budget runs out
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+
+    /// Time left, zero once the deadline has passed or shutdown has begun
+    pub fn remaining(&self) -> Duration {
+        if self.is_shutting_down() {
+            return Duration::ZERO;
+        }
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.has_begun()
+    }
+
+    /// Resolves at the deadline, or earlier when shutdown begins
+    pub async fn expired(&self) {
+        tokio::select! {
+            _ = tokio::time::sleep_until(self.at) => {}
+            _ = self.shutdown.wait() => {}
+        }
+    }
+
+    /// `event`'s output if it resolves before the deadline and before shutdown begins
+    pub async fn sleep_until_or_event<F: Future>(&self, event: F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            _ = self.shutdown.wait() => None,
+            output = event => Some(output),
+            _ = tokio::time::sleep_until(self.at) => None,
+        }
+    }
+}
+
+/// When the route's timeout ends the request, set by the timeout middleware
+#[derive(Clone, Copy)]
+pub(crate) struct RouteExpiry(pub(crate) Instant);
+
+impl FromRequestParts<AppState> for Deadline {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        let mut at = Instant::now() + state.settings.deadline;
+        if let Some(RouteExpiry(expiry)) = parts.extensions.get::<RouteExpiry>() {
+            at = at.min(*expiry);
+        }
+        if let Some(value) = parts.headers.get(REQUEST_TIMEOUT_HEADER) {
+            let budget = value.to_str().ok().and_then(parse_budget).ok_or_else(|| {
+                ApiError::bad_request("Invalid X-Request-Timeout header: expected milliseconds or a duration such as \"25s\"".to_string())
+            })?;
+            if let Some(client_at) = Instant::now().checked_add(budget) {
+                at = at.min(client_at);
+            }
+        }
+        Ok(Self { at, shutdown: state.settings.shutdown.clone() })
+    }
+}
+
+/// `"2500"` (milliseconds), `"500ms"`, `"25s"` or `"1m"`
+fn parse_budget(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        "" | "ms" => Some(Duration::from_millis(number)),
+        "s" => Some(Duration::from_secs(number)),
+        "m" => Some(Duration::from_secs(number.checked_mul(60)?)),
+        _ => None,
+    }
+}
@@ -0,0 +1,114 @@
+//! Endpoints for seeing what the server received, see
+//! [`HayaiApp::debug_endpoints`](crate::HayaiApp::debug_endpoints).
+//!
+//! `/_debug/echo` answers any method with the request as the app saw it: method, path,
+//! headers, query parameters and body. Credentials are replaced with `"***"`: the
+//! `Authorization`, `Proxy-Authorization` and `Cookie` headers, the headers of API key
+//! security schemes, and body fields marked `#[schema(sensitive)]`. `/_debug/routes`
+//! answers with the [route manifest](crate::manifest). Neither is documented in the spec.
+//!
+//! Release builds refuse to serve them unless [`ALLOW_ENV_VAR`] is set or the app opts
+//! in with [`HayaiApp::allow_debug_in_release`](crate::HayaiApp::allow_debug_in_release).
+//!
+//! ```ignore
+//! HayaiApp::new().debug_endpoints(cfg!(debug_assertions) || settings.debug)
+//! ```
+
+use crate::access_log;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// Setting this environment variable to `1` or `true` allows the debug endpoints in a
+/// release build
+pub const ALLOW_ENV_VAR: &str = "HAYAI_ALLOW_DEBUG_ENDPOINTS";
+
+/// Path of the echo endpoint
+pub const ECHO_PATH: &str = "/_debug/echo";
+
+/// Path of the route table endpoint
+pub const ROUTES_PATH: &str = "/_debug/routes";
+
+/// Whether a build may serve the debug endpoints: always unless `is_release`, otherwise
+/// only when allowed explicitly or through [`ALLOW_ENV_VAR`]. The app passes
+/// `!cfg!(debug_assertions)`.
+pub fn allowed(is_release: bool, allow_in_release: bool) -> bool {
+    !is_release
+        || allow_in_release
+        || std::env::var(ALLOW_ENV_VAR).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// What the echo endpoint redacts and how much body it reads
+#[derive(Clone)]
+pub(crate) struct Echo {
+    /// Lower-cased header names
+    pub(crate) sensitive_headers: Arc<HashSet<String>>,
+    /// Body field names
+    pub(crate) sensitive_fields: Arc<HashSet<String>>,
+    pub(crate) body_limit: usize,
+}
+
+impl Echo {
+    /// `api_key_headers` are the headers API key security schemes read
+    pub(crate) fn new(api_key_headers: impl IntoIterator<Item = String>, body_limit: usize) -> Self {
+        let mut sensitive_headers: HashSet<String> = ["authorization", "proxy-authorization", "cookie"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        sensitive_headers.extend(api_key_headers.into_iter().map(|h| h.to_ascii_lowercase()));
+        Self {
+            sensitive_headers: Arc::new(sensitive_headers),
+            sensitive_fields: Arc::new(access_log::sensitive_fields()),
+            body_limit,
+        }
+    }
+}
+
+pub(crate) async fn echo(echo: Echo, req: Request) -> Response {
+    let (parts, body) = req.into_parts();
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    for (name, value) in &parts.headers {
+        let value = match echo.sensitive_headers.contains(name.as_str()) {
+            true => "***".to_string(),
+            false => String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        };
+        headers.entry(name.to_string())
+            .and_modify(|existing| {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            })
+            .or_insert(value);
+    }
+    let mut query: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (key, value) in form_urlencoded::parse(parts.uri.query().unwrap_or("").as_bytes()) {
+        query.entry(key.into_owned()).or_default().push(value.into_owned());
+    }
+    let bytes = match axum::body::to_bytes(Body::new(body), echo.body_limit).await {
+        Ok(bytes) => bytes,
+        Err(_) => return crate::ApiError::payload_too_large("Request body too large").into_response(),
+    };
+    // JSON bodies are echoed as JSON, other text as a string
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(mut json) => {
+            access_log::redact(&mut json, &echo.sensitive_fields);
+            json
+        }
+        Err(_) if bytes.is_empty() => serde_json::Value::Null,
+        Err(_) => match std::str::from_utf8(&bytes) {
+            Ok(text) => serde_json::Value::String(text.to_string()),
+            Err(_) => serde_json::Value::Null,
+        },
+    };
+    axum::Json(serde_json::json!({
+        "method": parts.method.as_str(),
+        "path": parts.uri.path(),
+        "version": format!("{:?}", parts.version),
+        "headers": headers,
+        "query": query,
+        "body": body,
+        "body_bytes": bytes.len(),
+    }))
+    .into_response()
+}
@@ -0,0 +1,146 @@
+//! Exact decimal fields, behind the `decimal` feature.
+//!
+//! `Decimal` fields of an `#[api_model]` are sent as JSON strings (`"19.99"`), or as
+//! numbers with `#[schema(as_number)]`, and read from either; a number is taken as its
+//! shortest decimal form, so `19.99` reads as exactly `19.99`. They are documented as
+//! `format: decimal`. `#[validate(minimum = "0", maximum = "1000", max_scale = 2)]` bound
+//! them, with the bounds checked as decimals at compile time.
+//!
+//! ```ignore
+//! #[api_model]
+//! struct Price {
+//!     #[validate(minimum = "0", max_scale = 2)]
+//!     amount: Decimal,
+//! }
+//! ```
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Error as _;
+use serde::Serializer;
+use std::fmt;
+use std::str::FromStr;
+
+pub use rust_decimal::Decimal;
+
+pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    deserializer.deserialize_any(DecimalVisitor)
+}
+
+pub mod option {
+    use super::{Decimal, DecimalVisitor};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+        Option::<Read>::deserialize(deserializer).map(|read| read.map(|r| r.0))
+    }
+
+    struct Read(Decimal);
+
+    impl<'de> Deserialize<'de> for Read {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(DecimalVisitor).map(Read)
+        }
+    }
+}
+
+/// `#[schema(as_number)]`: written as a JSON number, read from a number or a string
+pub mod number {
+    use super::Decimal;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(crate::decimal::to_f64::<S>(value)?)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        super::deserialize(deserializer)
+    }
+
+    pub mod option {
+        use super::Decimal;
+        use serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(value: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => serializer.serialize_f64(crate::decimal::to_f64::<S>(value)?),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+            crate::decimal::option::deserialize(deserializer)
+        }
+    }
+}
+
+fn to_f64<S: Serializer>(value: &Decimal) -> Result<f64, S::Error> {
+    value.to_string().parse().map_err(|_| S::Error::custom(format!("decimal {} is not a number", value)))
+}
+
+struct DecimalVisitor;
+
+impl DecimalVisitor {
+    fn parse<E: de::Error>(text: &str) -> Result<Decimal, E> {
+        Decimal::from_str_exact(text).map_err(|e| E::custom(format!("invalid decimal {:?}: {}", text, e)))
+    }
+}
+
+impl Visitor<'_> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal number or a string containing one")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+        // The shortest representation that reads back as `v`: 19.99, not 19.989999...
+        Decimal::from_str(&v.to_string()).map_err(|e| E::custom(format!("invalid decimal {}: {}", v, e)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Decimal, E> {
+        Self::parse(v)
+    }
+}
+
+/// A `Decimal` or `Option<Decimal>` field, as the generated validation reads it
+#[doc(hidden)]
+pub trait DecimalField {
+    fn decimal(&self) -> Option<&Decimal>;
+}
+
+impl DecimalField for Decimal {
+    fn decimal(&self) -> Option<&Decimal> {
+        Some(self)
+    }
+}
+
+impl DecimalField for Option<Decimal> {
+    fn decimal(&self) -> Option<&Decimal> {
+        self.as_ref()
+    }
+}
+
+/// Decimal places of `value`, ignoring trailing zeros
+#[doc(hidden)]
+pub fn __scale(value: &Decimal) -> u32 {
+    value.normalize().scale()
+}
@@ -0,0 +1,289 @@
+//! The HTML served at `/docs`, see [`HayaiApp::docs_customization`](crate::HayaiApp::docs_customization)
+//! and [`HayaiApp::docs_html`](crate::HayaiApp::docs_html).
+//!
+//! With [`DocsAssets::Embedded`] (the `embedded-docs` feature, on by default) the page
+//! loads the Scalar API reference from `/docs/assets/`, compiled into the binary, so docs
+//! work without internet access. The script is stored brotli-compressed and sent as is to
+//! clients accepting `br`. `assets/scalar.min.js.br` is `assets/scalar.min.js` compressed
+//! at quality 11.
+//!
+//! ```ignore
+//! HayaiApp::new().docs_customization(DocsCustomization {
+//!     title: Some("Acme API".into()),
+//!     logo_url: Some("/static/logo.svg".into()),
+//!     custom_css: Some("/static/docs.css".into()),
+//!     head_html: Some(COOKIE_CONSENT_SNIPPET.into()),
+//!     ..Default::default()
+//! })
+//! ```
+
+#[cfg(feature = "embedded-docs")]
+use {
+    axum::extract::Path,
+    axum::http::{header, HeaderMap, HeaderValue, StatusCode},
+    axum::response::{IntoResponse, Response},
+};
+
+/// Swagger UI on jsDelivr, the CDN used without the `embedded-docs` feature
+pub const DEFAULT_SWAGGER_CDN: &str = "https://cdn.jsdelivr.net/npm/swagger-ui-dist@5";
+
+/// Where the embedded assets are served, below the docs prefix
+#[cfg(feature = "embedded-docs")]
+pub(crate) const ASSETS_PATH: &str = "/docs/assets";
+
+/// Assets are addressed with the hayai version, so browsers may keep them for good
+#[cfg(feature = "embedded-docs")]
+const ASSET_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Where the generated docs page loads its UI from, see [`HayaiApp::docs_assets`](crate::HayaiApp::docs_assets)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocsAssets {
+    /// Swagger UI from a CDN: the base URL of `swagger-ui.css` and `swagger-ui-bundle.js`
+    Cdn(String),
+    /// The Scalar API reference compiled into the binary, served from `/docs/assets/`
+    #[cfg(feature = "embedded-docs")]
+    Embedded,
+}
+
+impl Default for DocsAssets {
+    #[cfg(feature = "embedded-docs")]
+    fn default() -> Self {
+        DocsAssets::Embedded
+    }
+
+    #[cfg(not(feature = "embedded-docs"))]
+    fn default() -> Self {
+        DocsAssets::Cdn(DEFAULT_SWAGGER_CDN.to_string())
+    }
+}
+
+/// Placeholder in a [`docs_html`](crate::HayaiApp::docs_html) template, replaced with the
+/// URL of the OpenAPI document
+pub const SPEC_URL_PLACEHOLDER: &str = "{{SPEC_URL}}";
+
+/// Branding added to the generated docs page, whichever UI is selected.
+///
+/// Every value is HTML-escaped except `head_html`, which is inserted as is.
+#[derive(Debug, Clone, Default)]
+pub struct DocsCustomization {
+    /// Page title, instead of one derived from the app title
+    pub title: Option<String>,
+    pub favicon_url: Option<String>,
+    /// Image shown above the UI
+    pub logo_url: Option<String>,
+    /// Stylesheet URL, loaded after the UI's own styles
+    pub custom_css: Option<String>,
+    /// Script URL, loaded after the UI
+    pub custom_js: Option<String>,
+    /// Raw HTML appended to `<head>`, e.g. a cookie-consent snippet
+    pub head_html: Option<String>,
+}
+
+/// Escape `&`, `<`, `>` and quotes for use in text and attribute values
+pub fn escape_html(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render a user-supplied template, substituting [`SPEC_URL_PLACEHOLDER`]
+pub(crate) fn render_template(template: &str, spec_url: &str) -> String {
+    template.replace(SPEC_URL_PLACEHOLDER, spec_url)
+}
+
+/// The docs page for `assets`, loading the spec from `spec_url` and embedded assets from
+/// `assets_url`
+#[cfg_attr(not(feature = "embedded-docs"), allow(unused_variables))]
+pub(crate) fn render(assets: &DocsAssets, app_title: &str, spec_url: &str, assets_url: &str, custom: &DocsCustomization) -> String {
+    let default_title = match assets {
+        DocsAssets::Cdn(_) => format!("{} - Swagger UI", app_title),
+        #[cfg(feature = "embedded-docs")]
+        DocsAssets::Embedded => format!("{} - API Reference", app_title),
+    };
+    let title = escape_html(custom.title.as_deref().unwrap_or(&default_title));
+    // A JSON string is a valid JS literal; `</` must not end the inline script early
+    let spec_url_js = serde_json::to_string(spec_url).unwrap_or_default().replace("</", "<\\/");
+    let spec_url = escape_html(spec_url);
+
+    let mut head = String::new();
+    if let Some(url) = &custom.favicon_url {
+        head.push_str(&format!("\n    <link rel=\"icon\" href=\"{}\" />", escape_html(url)));
+    }
+    if let Some(url) = &custom.custom_css {
+        head.push_str(&format!("\n    <link rel=\"stylesheet\" type=\"text/css\" href=\"{}\" />", escape_html(url)));
+    }
+    if let Some(html) = &custom.head_html {
+        head.push_str("\n    ");
+        head.push_str(html);
+    }
+    let logo = match &custom.logo_url {
+        Some(url) => format!("\n    <header class=\"hayai-docs-header\"><img src=\"{}\" alt=\"{}\" /></header>", escape_html(url), title),
+        None => String::new(),
+    };
+    let script = match &custom.custom_js {
+        Some(url) => format!("\n    <script src=\"{}\"></script>", escape_html(url)),
+        None => String::new(),
+    };
+
+    match assets {
+        DocsAssets::Cdn(cdn_base) => {
+            format!(
+                r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{title}</title>
+    <link rel="stylesheet" type="text/css" href="{cdn}/swagger-ui.css" >{head}
+</head>
+<body>{logo}
+    <div id="swagger-ui"></div>
+    <script src="{cdn}/swagger-ui-bundle.js"> </script>
+    <script>
+    SwaggerUIBundle({{
+        url: {spec_url_js},
+        dom_id: '#swagger-ui',
+        presets: [SwaggerUIBundle.presets.apis, SwaggerUIBundle.SwaggerUIStandalonePreset],
+        layout: "BaseLayout"
+    }})
+    </script>{script}
+</body>
+</html>"#,
+                cdn = cdn_base,
+            )
+        }
+        #[cfg(feature = "embedded-docs")]
+        DocsAssets::Embedded => {
+            format!(
+                r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{title}</title>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <link rel="stylesheet" type="text/css" href="{assets}/scalar.min.css?v={version}" />{head}
+</head>
+<body>{logo}
+    <script id="api-reference" data-url="{spec_url}"></script>
+    <script src="{assets}/scalar.min.js?v={version}"></script>{script}
+</body>
+</html>"#,
+                assets = escape_html(assets_url),
+                version = env!("CARGO_PKG_VERSION"),
+            )
+        }
+    }
+}
+
+/// The Content-Security-Policy the page [`render`] builds for `assets` needs: its assets,
+/// and inline scripts and styles where the UI uses them
+pub(crate) fn content_security_policy(assets: &DocsAssets) -> String {
+    match assets {
+        DocsAssets::Cdn(base) => {
+            // The CDN's origin, `https://cdn.jsdelivr.net` of `https://cdn.jsdelivr.net/npm/..`
+            let origin = base.find("://")
+                .map(|scheme_end| match base[scheme_end + 3..].find('/') {
+                    Some(path) => &base[..scheme_end + 3 + path],
+                    None => base.as_str(),
+                })
+                .unwrap_or("");
+            format!(
+                "default-src 'self'; script-src 'self' 'unsafe-inline' {origin}; style-src 'self' 'unsafe-inline' {origin}; \
+                 img-src 'self' data: https:; font-src 'self' data: {origin}; connect-src 'self'"
+            )
+        }
+        #[cfg(feature = "embedded-docs")]
+        DocsAssets::Embedded => "default-src 'self'; script-src 'self'; style-src 'self' 'unsafe-inline'; \
+            img-src 'self' data: https:; font-src 'self' data: https:; connect-src 'self'"
+            .to_string(),
+    }
+}
+
+/// A file served from [`ASSETS_PATH`]
+#[cfg(feature = "embedded-docs")]
+struct EmbeddedAsset {
+    name: &'static str,
+    content_type: &'static str,
+    bytes: &'static [u8],
+    /// `bytes` are brotli-compressed
+    brotli: bool,
+}
+
+#[cfg(feature = "embedded-docs")]
+static EMBEDDED_ASSETS: &[EmbeddedAsset] = &[
+    EmbeddedAsset {
+        name: "scalar.min.css",
+        content_type: "text/css; charset=utf-8",
+        bytes: include_bytes!("../assets/scalar.min.css"),
+        brotli: false,
+    },
+    EmbeddedAsset {
+        name: "scalar.min.js",
+        content_type: "text/javascript; charset=utf-8",
+        bytes: include_bytes!("../assets/scalar.min.js.br"),
+        brotli: true,
+    },
+];
+
+/// Serve an embedded asset, compressed when the client accepts `br`
+#[cfg(feature = "embedded-docs")]
+pub(crate) async fn serve_asset(Path(name): Path<String>, headers: HeaderMap) -> Response {
+    let Some(asset) = EMBEDDED_ASSETS.iter().find(|asset| asset.name == name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let send_compressed = asset.brotli && accepts_brotli(&headers);
+    let mut response = if asset.brotli && !send_compressed {
+        decompressed(asset).into_response()
+    } else {
+        asset.bytes.into_response()
+    };
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(asset.content_type));
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(ASSET_CACHE_CONTROL));
+    if asset.brotli {
+        response_headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+    if send_compressed {
+        response_headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("br"));
+    }
+    response
+}
+
+/// The asset's bytes for clients without brotli support, decompressed once
+#[cfg(feature = "embedded-docs")]
+fn decompressed(asset: &'static EmbeddedAsset) -> axum::body::Bytes {
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::sync::{Mutex, OnceLock};
+
+    static DECOMPRESSED: OnceLock<Mutex<HashMap<&'static str, axum::body::Bytes>>> = OnceLock::new();
+    let mut cache = DECOMPRESSED.get_or_init(Mutex::default).lock().unwrap();
+    cache.entry(asset.name).or_insert_with(|| {
+        let mut out = Vec::new();
+        brotli_decompressor::Decompressor::new(asset.bytes, 4096)
+            .read_to_end(&mut out)
+            .expect("embedded docs assets are valid brotli");
+        out.into()
+    }).clone()
+}
+
+/// Whether `Accept-Encoding` lists `br` with a non-zero quality
+#[cfg(feature = "embedded-docs")]
+fn accepts_brotli(headers: &HeaderMap) -> bool {
+    headers.get_all(header::ACCEPT_ENCODING).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut parts = coding.split(';');
+            let name = parts.next().unwrap_or_default().trim();
+            let quality = parts.find_map(|p| p.trim().strip_prefix("q=")).map_or(1.0, |q| q.trim().parse().unwrap_or(0.0));
+            name.eq_ignore_ascii_case("br") && quality > 0.0
+        })
+}
@@ -0,0 +1,121 @@
+//! Draining ahead of a deploy, see [`HayaiApp::drain_endpoint`](crate::HayaiApp::drain_endpoint).
+//!
+//! A POST to the drain endpoint with `Authorization: Bearer <token>` turns draining on:
+//! `/readyz` answers 503 so load balancers stop routing to the instance, while requests
+//! keep being served. A second POST, or the drain timeout, turns it off again. Long-poll
+//! handlers can take a [`Draining`] argument and return early once draining starts.
+//!
+//! ```ignore
+//! #[get("/events")]
+//! async fn events(draining: Draining, bus: Dep<EventBus>) -> Vec<Event> {
+//!     tokio::select! {
+//!         events = bus.next_batch() => events,
+//!         _ = draining.started() => Vec::new(),
+//!     }
+//! }
+//! ```
+
+use crate::{ApiError, AppState};
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// How long draining lasts when nothing turns it off
+pub(crate) const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Whether the app is draining, shared by the endpoint, `/readyz` and handlers
+#[derive(Default)]
+pub(crate) struct DrainState {
+    /// When draining ends by itself, while it is on
+    until: Mutex<Option<Instant>>,
+    started: Notify,
+}
+
+impl DrainState {
+    pub(crate) fn is_draining(&self) -> bool {
+        self.until.lock().unwrap().is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Turn draining on for `timeout`, or off if it is on; whether it is now on
+    fn toggle(&self, timeout: Duration) -> bool {
+        let draining = {
+            let mut until = self.until.lock().unwrap();
+            let draining = !until.is_some_and(|until| Instant::now() < until);
+            *until = draining.then(|| Instant::now() + timeout);
+            draining
+        };
+        if draining {
+            self.started.notify_waiters();
+        }
+        draining
+    }
+}
+
+/// The drain endpoint's configuration
+#[derive(Clone)]
+pub(crate) struct DrainEndpoint {
+    pub(crate) token: String,
+    pub(crate) timeout: Duration,
+}
+
+/// Whether the app is draining, for handlers that hold requests open
+#[derive(Clone)]
+pub struct Draining {
+    state: Arc<DrainState>,
+}
+
+impl Draining {
+    pub fn is_draining(&self) -> bool {
+        self.state.is_draining()
+    }
+
+    /// Resolves once draining is on, immediately if it already is
+    pub async fn started(&self) {
+        loop {
+            let notified = self.state.started.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.state.is_draining() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for Draining {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        Ok(Self { state: state.settings.drain.clone() })
+    }
+}
+
+/// Toggle draining for a request carrying the endpoint's token
+pub(crate) async fn toggle(endpoint: DrainEndpoint, state: Arc<DrainState>, headers: HeaderMap) -> Response {
+    let presented = headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !presented.is_some_and(|token| same_token(token, &endpoint.token)) {
+        return ApiError::unauthorized("Invalid drain token").into_response();
+    }
+    let draining = state.toggle(endpoint.timeout);
+    if draining {
+        tracing::warn!(timeout_secs = endpoint.timeout.as_secs(), "draining: readiness reports unavailable");
+    } else {
+        tracing::info!("draining stopped");
+    }
+    axum::Json(serde_json::json!({ "draining": draining })).into_response()
+}
+
+/// Compare fixed-size digests without stopping at the first differing byte, so the time
+/// taken reveals neither the token nor its length
+fn same_token(presented: &str, expected: &str) -> bool {
+    let (presented, expected) = (Sha256::digest(presented), Sha256::digest(expected));
+    presented.iter().zip(expected.iter()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
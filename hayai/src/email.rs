@@ -0,0 +1,91 @@
+//! Email address checks behind `#[validate(email)]`.
+//!
+//! The default check is a practical subset of RFC 5321/5322: a dot-atom or quoted local
+//! part, and a domain of dot-separated labels with at least two labels unless
+//! `email(allow_no_tld)` is given. `email(strict)` uses a full RFC 5322 parser instead,
+//! which also takes domain literals like `user@[192.168.0.1]`; it needs the
+//! `strict-email` feature.
+//!
+//! Internationalized addresses (RFC 6531) are accepted as sent: domains may be Unicode
+//! or A-labels (`xn--...`) and local parts may contain non-ASCII characters. No IDNA
+//! mapping or punycode conversion is done, so `bücher.example` and
+//! `xn--bcher-kva.example` are both valid but not considered equal.
+
+/// Options from `#[validate(email(...))]`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmailOptions {
+    /// Accept single-label domains such as `localhost`
+    pub allow_no_tld: bool,
+}
+
+const MAX_LENGTH: usize = 254;
+const MAX_LOCAL_LENGTH: usize = 64;
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// The default check
+pub fn is_valid(address: &str, options: EmailOptions) -> bool {
+    if address.len() > MAX_LENGTH {
+        return false;
+    }
+    // Quoted local parts may contain '@', the domain never does
+    let Some((local, domain)) = address.rsplit_once('@') else { return false };
+    is_valid_local(local) && is_valid_domain(domain, options)
+}
+
+/// The `strict` check, an RFC 5322 parse of the bare address (no display name)
+#[cfg(feature = "strict-email")]
+pub fn is_valid_strict(address: &str, options: EmailOptions) -> bool {
+    use email_address::{EmailAddress, Options};
+    let parser = Options::default().without_display_text();
+    let parser = if options.allow_no_tld { parser.with_no_minimum_sub_domains() } else { parser.with_required_tld() };
+    EmailAddress::parse_with_options(address, parser).is_ok()
+}
+
+fn is_valid_local(local: &str) -> bool {
+    if local.is_empty() || local.len() > MAX_LOCAL_LENGTH {
+        return false;
+    }
+    if let Some(quoted) = local.strip_prefix('"').and_then(|l| l.strip_suffix('"')) {
+        return is_valid_quoted(quoted);
+    }
+    local.split('.').all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// Contents of a quoted local part: printable characters, with `"` and `\` escaped
+fn is_valid_quoted(quoted: &str) -> bool {
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) if !escaped.is_control() => {}
+                _ => return false,
+            },
+            '"' => return false,
+            c if c.is_control() => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || (!c.is_ascii() && !c.is_control() && !c.is_whitespace())
+}
+
+fn is_valid_domain(domain: &str, options: EmailOptions) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 && !options.allow_no_tld {
+        return false;
+    }
+    labels.iter().all(|label| is_valid_label(label))
+        // An all-numeric TLD would make this an IP address
+        && !labels.last().is_some_and(|tld| labels.len() > 1 && tld.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= MAX_LABEL_LENGTH
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c == '-' || c.is_alphanumeric())
+}
@@ -0,0 +1,171 @@
+//! Success responses wrapped in an envelope, see [`HayaiApp::response_envelope`](crate::HayaiApp::response_envelope).
+//!
+//! Every 2xx JSON response of a route is sent as `{"data": <body>, "meta": {...}}`, its
+//! `meta` holding the request id (`X-Request-Id`, or the one generated for the access log)
+//! and whatever [`EnvelopeConfig::meta_fn`] adds. Error responses and routes marked
+//! `#[no_envelope]` are sent as they are. The spec documents the wrapped contract: a
+//! response of `User` refers to a generated `UserEnvelope` component, `Vec<User>` to
+//! `UserListEnvelope`, and other shapes are wrapped inline; `meta` refers to `EnvelopeMeta`.
+//!
+//! ```ignore
+//! HayaiApp::new().response_envelope(EnvelopeConfig {
+//!     meta_fn: |ctx| json!({ "took_ms": ctx.elapsed.as_millis() as u64 }),
+//!     ..Default::default()
+//! })
+//!
+//! #[get("/healthcheck")]
+//! #[no_envelope]
+//! async fn healthcheck() -> Value { json!({ "ok": true }) }
+//! ```
+
+use crate::openapi::{Operation, Property, Schema};
+use crate::ApiError;
+use axum::body::Body;
+use axum::extract::{MatchedPath, Request};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Component documenting `meta`
+pub const META_COMPONENT: &str = "EnvelopeMeta";
+
+/// Extra `meta` fields for a response; the fields of the object returned are added after
+/// `request_id`
+pub type EnvelopeMetaFn = fn(&EnvelopeContext) -> serde_json::Value;
+
+/// How success responses are wrapped
+#[derive(Clone)]
+pub struct EnvelopeConfig {
+    /// Key the response body goes under, `data` by default
+    pub data_key: String,
+    pub meta_fn: EnvelopeMetaFn,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self { data_key: "data".to_string(), meta_fn: |_| serde_json::json!({}) }
+    }
+}
+
+/// What [`EnvelopeConfig::meta_fn`] knows about the request
+#[derive(Debug, Clone)]
+pub struct EnvelopeContext {
+    pub request_id: String,
+    pub method: String,
+    /// The route's path template, e.g. `/users/{id}`
+    pub route: String,
+    pub status: u16,
+    /// From receiving the request to the handler's response
+    pub elapsed: Duration,
+}
+
+/// Middleware state: the config, and the `(METHOD, axum path)` of the wrapped routes
+#[derive(Clone)]
+pub(crate) struct Enveloping {
+    pub(crate) config: Arc<EnvelopeConfig>,
+    pub(crate) routes: Arc<HashSet<(String, String)>>,
+}
+
+pub(crate) async fn wrap(enveloping: Enveloping, req: Request, next: Next) -> Response {
+    let route = req.extensions().get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .filter(|path| enveloping.routes.contains(&(req.method().to_string(), path.clone())));
+    let Some(route) = route else {
+        return next.run(req).await;
+    };
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let request_id = req.headers().get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map_or_else(crate::access_log::generate_request_id, str::to_string);
+
+    let response = next.run(req).await;
+    let json = response.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !response.status().is_success() || !json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => return ApiError::internal(format!("Failed to read response body: {}", e)).into_response(),
+    };
+    let Ok(data) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let context = EnvelopeContext { request_id, method, route, status: parts.status.as_u16(), elapsed: started.elapsed() };
+    let mut meta = serde_json::Map::new();
+    meta.insert("request_id".to_string(), serde_json::Value::String(context.request_id.clone()));
+    if let serde_json::Value::Object(extra) = (enveloping.config.meta_fn)(&context) {
+        meta.extend(extra);
+    }
+    let mut envelope = serde_json::Map::new();
+    envelope.insert(enveloping.config.data_key.clone(), data);
+    envelope.insert("meta".to_string(), serde_json::Value::Object(meta));
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(serde_json::Value::Object(envelope).to_string()))
+}
+
+/// The schema of `meta`: the request id, and the fields `meta_fn` adds
+pub(crate) fn meta_schema() -> Schema {
+    Schema {
+        type_name: "object".to_string(),
+        properties: HashMap::from([("request_id".to_string(), Property { type_name: "string".to_string(), ..Default::default() })]),
+        required: vec!["request_id".to_string()],
+        ..Default::default()
+    }
+}
+
+/// The envelope around a response schema: a `$ref` to a generated component, returned
+/// with it, or an inline object for shapes without a component
+fn wrap_schema(config: &EnvelopeConfig, data: &serde_json::Value) -> (serde_json::Value, Option<(String, Schema)>) {
+    let meta_ref = format!("#/components/schemas/{}", META_COMPONENT);
+    let model = |schema: &serde_json::Value| schema.get("$ref").and_then(|r| r.as_str()).map(str::to_string);
+    let (name, data_property) = if let Some(reference) = model(data) {
+        let name = format!("{}Envelope", reference.trim_start_matches("#/components/schemas/"));
+        (name, Property { ref_path: Some(reference), ..Default::default() })
+    } else if let Some(reference) = data.get("items").filter(|_| data["type"] == "array").and_then(model) {
+        let name = format!("{}ListEnvelope", reference.trim_start_matches("#/components/schemas/"));
+        let items = Property { ref_path: Some(reference), ..Default::default() };
+        (name, Property { type_name: "array".to_string(), items: Some(Box::new(items)), ..Default::default() })
+    } else {
+        let inline = serde_json::json!({
+            "type": "object",
+            "properties": { config.data_key.as_str(): data, "meta": { "$ref": meta_ref } },
+            "required": [config.data_key, "meta"],
+        });
+        return (inline, None);
+    };
+    let component = Schema {
+        type_name: "object".to_string(),
+        properties: HashMap::from([
+            (config.data_key.clone(), data_property),
+            ("meta".to_string(), Property { ref_path: Some(meta_ref), ..Default::default() }),
+        ]),
+        required: vec![config.data_key.clone(), "meta".to_string()],
+        ..Default::default()
+    };
+    (serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) }), Some((name, component)))
+}
+
+/// Wrap the documented 2xx JSON responses of `operation`, returning the components used
+pub(crate) fn wrap_operation(config: &EnvelopeConfig, operation: &mut Operation) -> Vec<(String, Schema)> {
+    if operation.produces.is_some() {
+        return Vec::new();
+    }
+    let successes = operation.responses.iter_mut().filter(|(status, _)| status.starts_with('2'));
+    let mut components = Vec::new();
+    for (_, response) in successes {
+        let Some(schema) = &mut response.schema_ref else { continue };
+        let (wrapped, component) = wrap_schema(config, schema);
+        *schema = wrapped;
+        components.extend(component);
+    }
+    components
+}
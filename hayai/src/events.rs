@@ -0,0 +1,97 @@
+//! Domain events published after successful responses, see
+//! [`HayaiApp::on_response`](crate::HayaiApp::on_response).
+//!
+//! A route marked `#[emit_event("user.created")]` hands every 2xx response to the app's
+//! hooks. They run in a spawned task, so the client never waits for the broker; an error
+//! or panic in a hook is logged and nothing else. Add `include_body` to pass the
+//! serialized response along, which buffers it first.
+//!
+//! ```ignore
+//! #[post("/users")]
+//! #[emit_event("user.created", include_body)]
+//! async fn create_user(body: NewUser) -> User { ... }
+//!
+//! HayaiApp::new().on_response(|meta, state| {
+//!     let broker = state.get::<Broker>();
+//!     let (event, body) = (meta.event, meta.body.clone());
+//!     async move { broker.expect("connected at startup").publish(event, body).await }
+//! })
+//! ```
+
+use crate::lifespan::{BoxError, LifespanSharedState};
+use crate::RouteInfo;
+use axum::body::{Body, Bytes};
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// `#[emit_event("name")]` or `#[emit_event("name", include_body)]` of one route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitEvent {
+    pub name: &'static str,
+    pub include_body: bool,
+}
+
+/// What a hook learns about a successful response
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// The name given to `#[emit_event]`
+    pub event: &'static str,
+    pub status: u16,
+    pub operation_id: &'static str,
+    /// The response body, with `include_body`
+    pub body: Option<Bytes>,
+}
+
+pub(crate) type ResponseHook = Arc<dyn Fn(&ResponseMeta, &LifespanSharedState) -> Pin<Box<dyn Future<Output = Result<(), BoxError>> + Send>> + Send + Sync>;
+
+/// Middleware state: the hooks, and the annotated routes by `(METHOD, axum path)`
+#[derive(Clone)]
+pub(crate) struct EventHooks {
+    pub(crate) hooks: Arc<Vec<ResponseHook>>,
+    pub(crate) routes: Arc<HashMap<(String, String), &'static RouteInfo>>,
+    /// Every route's `operationId` by `(METHOD, axum path)`
+    pub(crate) operations: Arc<HashMap<(String, String), &'static str>>,
+    pub(crate) shared: LifespanSharedState,
+}
+
+pub(crate) async fn emit_events(config: EventHooks, req: Request, next: Next) -> Response {
+    let key = req.extensions().get::<MatchedPath>().map(|path| (req.method().to_string(), path.as_str().to_string()));
+    let route = key.as_ref().and_then(|key| config.routes.get(key).copied());
+    let response = next.run(req).await;
+    let Some((route, event)) = route.and_then(|r| Some((r, r.emit_event?))) else {
+        return response;
+    };
+    let operation_id = key.and_then(|key| config.operations.get(&key).copied()).unwrap_or(route.handler_name);
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (response, body) = if event.include_body {
+        let (parts, body) = response.into_parts();
+        match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => (Response::from_parts(parts, Body::from(bytes.clone())), Some(bytes)),
+            Err(e) => return crate::ApiError::internal(format!("Failed to read response body: {}", e)).into_response(),
+        }
+    } else {
+        (response, None)
+    };
+    let meta = ResponseMeta { event: event.name, status: response.status().as_u16(), operation_id, body };
+
+    for hook in config.hooks.iter() {
+        let (hook, meta, shared) = (hook.clone(), meta.clone(), config.shared.clone());
+        let task = tokio::spawn(async move { hook(&meta, &shared).await });
+        tokio::spawn(async move {
+            match task.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::warn!(event = event.name, error = %e, "response hook failed"),
+                Err(e) => tracing::error!(event = event.name, error = %e, "response hook panicked"),
+            }
+        });
+    }
+    response
+}
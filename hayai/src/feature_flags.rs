@@ -0,0 +1,113 @@
+//! Routes switched on and off at runtime, see [`HayaiApp::feature_flags`](crate::HayaiApp::feature_flags).
+//!
+//! A route marked `#[feature_flag("name")]` asks the app's [`FlagProvider`] before anything
+//! is extracted from the request; while the flag is off it answers 404, or the
+//! [`DisabledRoute`] configured with [`HayaiApp::disabled_route`](crate::HayaiApp::disabled_route).
+//! Handlers check flags themselves through `Dep<FeatureFlags>`.
+//!
+//! ```ignore
+//! let flags = InMemoryFlags::new().with("new_billing", true);
+//! HayaiApp::new().feature_flags(flags.clone())
+//!
+//! #[post("/invoices")]
+//! #[feature_flag("new_billing")]
+//! async fn create_invoice(body: NewInvoice, flags: Dep<FeatureFlags>) -> Invoice {
+//!     if flags.is_enabled("invoice_pdf") { ... }
+//! }
+//!
+//! // During an incident
+//! flags.set("new_billing", false);
+//! ```
+
+use crate::{ApiError, AppState, RouteInfo};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Answers whether a flag is on; implement it over a flag service for remote toggles
+pub trait FlagProvider: Send + Sync + 'static {
+    fn is_enabled(&self, name: &str) -> bool;
+}
+
+/// Flags held in memory and flipped with [`InMemoryFlags::set`]; clones share the flags.
+/// Flags never set are off unless [`InMemoryFlags::enabled_by_default`].
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryFlags {
+    flags: Arc<RwLock<HashMap<String, bool>>>,
+    default: bool,
+}
+
+impl InMemoryFlags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treat flags never set as on, for flags used as kill switches
+    pub fn enabled_by_default(mut self) -> Self {
+        self.default = true;
+        self
+    }
+
+    /// Start with `name` on or off
+    pub fn with(self, name: &str, enabled: bool) -> Self {
+        self.set(name, enabled);
+        self
+    }
+
+    /// Turn `name` on or off, taking effect for the next request
+    pub fn set(&self, name: &str, enabled: bool) {
+        self.flags.write().unwrap().insert(name.to_string(), enabled);
+    }
+}
+
+impl FlagProvider for InMemoryFlags {
+    fn is_enabled(&self, name: &str) -> bool {
+        self.flags.read().unwrap().get(name).copied().unwrap_or(self.default)
+    }
+}
+
+/// The app's [`FlagProvider`], registered as a dependency for handlers
+#[derive(Clone)]
+pub struct FeatureFlags(pub(crate) Arc<dyn FlagProvider>);
+
+impl FeatureFlags {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.0.is_enabled(name)
+    }
+}
+
+/// What a route answers while its flag is off
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DisabledRoute {
+    /// An empty 404, as if the route didn't exist
+    #[default]
+    NotFound,
+    /// 503 with the message
+    Unavailable(String),
+}
+
+/// The rejection for a flagged route whose flag is off, checked before extraction
+#[doc(hidden)]
+pub fn __check(state: &AppState, route: &'static RouteInfo) -> Option<Response> {
+    let flag = route.feature_flag?;
+    let flags = state.settings.feature_flags.as_ref()?;
+    if flags.is_enabled(flag) {
+        return None;
+    }
+    tracing::debug!(flag, handler = route.handler_name, "route disabled by feature flag");
+    Some(match &state.settings.disabled_route {
+        DisabledRoute::NotFound => StatusCode::NOT_FOUND.into_response(),
+        DisabledRoute::Unavailable(message) => ApiError::service_unavailable(message.clone()).into_response(),
+    })
+}
+
+/// Flagged routes as `flag (handler)`, which need a provider to be mounted
+pub(crate) fn flagged_routes(routes: &[&'static RouteInfo]) -> Vec<String> {
+    let mut flagged: Vec<String> = routes.iter()
+        .filter_map(|route| route.feature_flag.map(|flag| format!("{} ({})", flag, route.handler_name)))
+        .collect();
+    flagged.sort();
+    flagged.dedup();
+    flagged
+}
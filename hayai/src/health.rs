@@ -0,0 +1,108 @@
+//! Liveness and readiness endpoints, see [`HayaiApp::health_endpoints`](crate::HayaiApp::health_endpoints).
+//!
+//! `/healthz` only reports that the process is serving. `/readyz` runs the checks of
+//! every dependency registered with [`HayaiApp::dep_with_health`](crate::HayaiApp::dep_with_health)
+//! concurrently and answers 503 if any fails or times out, or while the app is draining
+//! (see [`HayaiApp::drain_endpoint`](crate::HayaiApp::drain_endpoint)).
+
+use crate::drain::DrainState;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A dependency that can report whether it is usable, e.g. a database pool
+///
+/// ```ignore
+/// impl HealthCheck for Database {
+///     async fn health(&self) -> Result<(), String> {
+///         self.ping().await.map_err(|e| e.to_string())
+///     }
+/// }
+/// ```
+pub trait HealthCheck: Send + Sync + 'static {
+    fn health(&self) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+type CheckFn = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync>;
+
+/// A registered dependency check, named after the dependency's type
+#[derive(Clone)]
+pub(crate) struct RegisteredCheck {
+    name: String,
+    check: CheckFn,
+}
+
+impl RegisteredCheck {
+    pub(crate) fn new<T: HealthCheck>(dep: Arc<T>) -> Self {
+        Self {
+            name: short_type_name(std::any::type_name::<T>()),
+            check: Arc::new(move || {
+                let dep = dep.clone();
+                Box::pin(async move { dep.health().await })
+            }),
+        }
+    }
+}
+
+/// `my_app::db::Database` → `Database`
+fn short_type_name(full: &str) -> String {
+    let base = full.split('<').next().unwrap_or(full);
+    let short = base.rsplit("::").next().unwrap_or(base);
+    format!("{}{}", short, &full[base.len()..])
+}
+
+pub(crate) async fn liveness() -> Response {
+    axum::Json(serde_json::json!({ "status": "ok" })).into_response()
+}
+
+/// Run every check concurrently, each bounded by `timeout`
+pub(crate) async fn readiness(checks: Arc<Vec<RegisteredCheck>>, timeout: Duration, drain: Arc<DrainState>) -> Response {
+    if drain.is_draining() {
+        return (StatusCode::SERVICE_UNAVAILABLE, axum::Json(serde_json::json!({ "status": "draining" }))).into_response();
+    }
+    let mut tasks = tokio::task::JoinSet::new();
+    for (i, registered) in checks.iter().enumerate() {
+        let check = (registered.check)();
+        tasks.spawn(async move {
+            let result = match tokio::time::timeout(timeout, check).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+            };
+            (i, result)
+        });
+    }
+
+    let mut results: Vec<Option<Result<(), String>>> = vec![None; checks.len()];
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((i, result)) = joined {
+            results[i] = Some(result);
+        }
+    }
+
+    let mut statuses = serde_json::Map::new();
+    let mut failures = Vec::new();
+    for (registered, result) in checks.iter().zip(results) {
+        let status = match result {
+            Some(Ok(())) => "ok".to_string(),
+            Some(Err(e)) => {
+                failures.push(registered.name.clone());
+                e
+            }
+            None => {
+                failures.push(registered.name.clone());
+                "check panicked".to_string()
+            }
+        };
+        statuses.insert(registered.name.clone(), status.into());
+    }
+
+    if failures.is_empty() {
+        (StatusCode::OK, axum::Json(serde_json::json!({ "status": "ready", "checks": statuses }))).into_response()
+    } else {
+        let body = serde_json::json!({ "status": "unavailable", "checks": statuses, "failures": failures });
+        (StatusCode::SERVICE_UNAVAILABLE, axum::Json(body)).into_response()
+    }
+}
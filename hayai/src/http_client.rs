@@ -0,0 +1,133 @@
+//! Outgoing HTTP clients shared as dependencies, see
+//! [`HayaiApp::http_client`](crate::HayaiApp::http_client). Requires the `http-client`
+//! feature.
+//!
+//! Calls made while a request is being handled carry its `X-Request-Id`, `traceparent`
+//! and `tracestate` headers, so the downstream service logs the same ids. Tasks spawned
+//! from the handler no longer belong to the request and send none.
+//!
+//! ```ignore
+//! HayaiApp::new()
+//!     .http_client(HttpClientConfig { base_url: Some("http://users.internal/api".into()), ..Default::default() })
+//!     .http_client_named("billing", HttpClientConfig { timeout: Some(Duration::from_secs(2)), ..Default::default() });
+//!
+//! #[get("/invoices/{id}")]
+//! async fn get_invoice(id: i64, users: Dep<HttpClient>, clients: Dep<HttpClients>) -> Result<Invoice, ApiError> {
+//!     let user = users.get(&format!("/users/{id}")).send().await?;
+//!     let billing = clients.get("billing").expect("registered at startup");
+//!     ...
+//! }
+//! ```
+
+use axum::extract::Request;
+use axum::http::{HeaderMap, HeaderName, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Incoming headers copied onto outgoing calls
+pub const PROPAGATED_HEADERS: [&str; 3] = ["x-request-id", "traceparent", "tracestate"];
+
+tokio::task_local! {
+    static PROPAGATED: HeaderMap;
+}
+
+/// Settings of one client
+#[derive(Debug, Clone, Default)]
+pub struct HttpClientConfig {
+    /// Prefix of relative request paths, e.g. `http://users.internal/api`
+    pub base_url: Option<String>,
+    /// Limit on each whole request, connecting through reading the body
+    pub timeout: Option<Duration>,
+    /// Sent with every request
+    pub default_headers: HeaderMap,
+}
+
+/// A `reqwest` client with a base URL and request context propagation
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    base_url: Option<String>,
+}
+
+impl HttpClient {
+    pub fn new(config: HttpClientConfig) -> Result<Self, reqwest::Error> {
+        let mut builder = reqwest::Client::builder().default_headers(config.default_headers);
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        Ok(Self { client: builder.build()?, base_url: config.base_url })
+    }
+
+    /// The URL requested for `path`: absolute URLs are used as they are, anything else is
+    /// appended to the base URL with exactly one slash between them
+    pub fn url(&self, path: &str) -> String {
+        match &self.base_url {
+            Some(base) if !path.contains("://") => crate::registry::join_path(base, path),
+            _ => path.to_string(),
+        }
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.request(method, self.url(path));
+        if let Ok(headers) = PROPAGATED.try_with(HeaderMap::clone) {
+            request = request.headers(headers);
+        }
+        request
+    }
+
+    pub fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.request(Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        self.request(Method::POST, path)
+    }
+
+    pub fn put(&self, path: &str) -> reqwest::RequestBuilder {
+        self.request(Method::PUT, path)
+    }
+
+    pub fn patch(&self, path: &str) -> reqwest::RequestBuilder {
+        self.request(Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> reqwest::RequestBuilder {
+        self.request(Method::DELETE, path)
+    }
+
+    /// The underlying client, for requests that bypass the base URL and propagation
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
+
+/// Clients registered with [`HayaiApp::http_client_named`](crate::HayaiApp::http_client_named),
+/// resolved as `Dep<HttpClients>`
+#[derive(Debug, Clone, Default)]
+pub struct HttpClients {
+    clients: HashMap<String, HttpClient>,
+}
+
+impl HttpClients {
+    pub fn get(&self, name: &str) -> Option<&HttpClient> {
+        self.clients.get(name)
+    }
+
+    pub(crate) fn insert(&mut self, name: &str, client: HttpClient) {
+        self.clients.insert(name.to_string(), client);
+    }
+}
+
+/// Middleware making the request's propagated headers available to [`HttpClient`] calls
+/// made while it is handled
+pub(crate) async fn propagate_context(req: Request, next: Next) -> Response {
+    let mut headers = HeaderMap::new();
+    for name in PROPAGATED_HEADERS {
+        if let Some(value) = req.headers().get(name) {
+            headers.insert(HeaderName::from_static(name), value.clone());
+        }
+    }
+    PROPAGATED.scope(headers, next.run(req)).await
+}
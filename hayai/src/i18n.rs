@@ -0,0 +1,161 @@
+//! Error messages in the client's language, see [`HayaiApp::message_catalog`](crate::HayaiApp::message_catalog).
+//!
+//! Errors carry a code and the values their message was built from: a validation rule's
+//! code (`min_length`) with its arguments (`{min}`) and the `{field}` it failed on, or the
+//! code given with [`ApiError::with_code`]. A [`MessageCatalog`] maps `(locale, code)` to a
+//! template; each error response is rendered in the locale that best matches the request's
+//! `Accept-Language`, or the catalog's default. A code the locale has no template for falls
+//! back to the English template, then to the built-in English message.
+//!
+//! ```ignore
+//! let catalog = MessageCatalog::new()
+//!     .message("fr", "validation_failed", "La validation a échoué")
+//!     .message("fr", "min_length", "doit contenir au moins {min} caractères")
+//!     .message("fr", "user_not_found", "Utilisateur {id} introuvable");
+//! HayaiApp::new().message_catalog(catalog)
+//!
+//! Err(ApiError::not_found(format!("User {} not found", id)).with_code("user_not_found").with_param("id", id))
+//! ```
+
+use crate::{ApiError, ValidationError, ValidationShaper};
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Localized message templates by locale and error code
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    default_locale: String,
+    templates: HashMap<(String, String), String>,
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageCatalog {
+    /// An empty catalog answering in English when `Accept-Language` names nothing it has
+    pub fn new() -> Self {
+        Self { default_locale: "en".to_string(), templates: HashMap::new() }
+    }
+
+    /// The locale for requests without an `Accept-Language` the catalog can serve
+    pub fn default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.default_locale = locale.into().to_ascii_lowercase();
+        self
+    }
+
+    /// The template for `code` in `locale`; `{name}` placeholders are replaced by the
+    /// error's params, and `{field}` by the field a validation rule failed on
+    pub fn message(mut self, locale: impl Into<String>, code: impl Into<String>, template: impl Into<String>) -> Self {
+        self.templates.insert((locale.into().to_ascii_lowercase(), code.into()), template.into());
+        self
+    }
+
+    /// The locale to answer in: the first of the `Accept-Language` ranges, by quality, that
+    /// the catalog has templates for, matching `fr-CA` to `fr` when only that is known
+    pub fn negotiate(&self, accept_language: Option<&str>) -> &str {
+        crate::locale::best_match(accept_language.unwrap_or(""), self.locales()).unwrap_or(&self.default_locale)
+    }
+
+    fn locales(&self) -> impl Iterator<Item = &str> + Clone {
+        std::iter::once(self.default_locale.as_str()).chain(self.templates.keys().map(|(locale, _)| locale.as_str()))
+    }
+
+    /// `code` rendered in `locale`, or in English when the locale lacks it; `None` when
+    /// neither has a template
+    pub fn render(&self, locale: &str, code: &str, params: &[(String, String)]) -> Option<String> {
+        let template = self.templates.get(&(locale.to_string(), code.to_string()))
+            .or_else(|| self.templates.get(&("en".to_string(), code.to_string())))?;
+        Some(params.iter().fold(template.clone(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value)))
+    }
+
+    fn localize_validation(&self, locale: &str, error: &ValidationError) -> ValidationError {
+        let mut params = error.params.clone();
+        params.push(("field".to_string(), error.field.clone()));
+        match self.render(locale, &error.code, &params) {
+            Some(message) => ValidationError { message, ..error.clone() },
+            None => error.clone(),
+        }
+    }
+}
+
+/// What localizes an error, see [`ApiError::with_code`]
+#[derive(Debug, Clone, Default)]
+pub struct MessageKey {
+    /// The catalog entry for the message
+    pub code: Option<String>,
+    /// Values for the `{placeholders}` of the message
+    pub params: Vec<(String, String)>,
+    /// The failed rules behind a validation error's `details`, localized with them
+    pub validation: Vec<ValidationError>,
+}
+
+/// What an error response was rendered from, kept on the response for [`localize`]
+#[derive(Debug, Clone)]
+pub(crate) struct Localizable {
+    error: String,
+    details: Vec<String>,
+    key: MessageKey,
+    /// The body came from a [`ValidationShaper`] and is shaped again once localized
+    shaped: bool,
+}
+
+impl Localizable {
+    /// `None` for errors without codes, and for bodies set by hand that can't be rewritten
+    pub(crate) fn of(error: &ApiError) -> Option<Self> {
+        let key = error.message_key.as_deref()?;
+        if key.validation.is_empty() && (key.code.is_none() || error.body.is_some()) {
+            return None;
+        }
+        Some(Self {
+            error: error.error.clone(),
+            details: error.details.clone(),
+            key: key.clone(),
+            shaped: error.body.is_some(),
+        })
+    }
+
+    fn render(self, catalog: &MessageCatalog, locale: &str, shaper: Option<ValidationShaper>) -> ApiError {
+        let message = self.key.code.as_deref()
+            .and_then(|code| catalog.render(locale, code, &self.key.params))
+            .unwrap_or(self.error);
+        let validation: Vec<ValidationError> = self.key.validation.iter().map(|e| catalog.localize_validation(locale, e)).collect();
+        let mut error = ApiError::new(StatusCode::OK, message);
+        error.details = if validation.is_empty() { self.details } else { validation.iter().map(ValidationError::to_string).collect() };
+        error.body = shaper.filter(|_| self.shaped).map(|shaper| shaper(validation));
+        error
+    }
+}
+
+/// The catalog and what it needs to re-render error bodies
+#[derive(Clone)]
+pub(crate) struct Localizer {
+    pub(crate) catalog: Arc<MessageCatalog>,
+    pub(crate) shaper: Option<ValidationShaper>,
+}
+
+/// Re-render error responses in the request's [`Locale`](crate::locale::Locale), or the
+/// locale its `Accept-Language` asks for
+pub(crate) async fn localize(localizer: Localizer, req: Request, next: Next) -> Response {
+    let accept_language = match req.extensions().get::<crate::locale::Locale>() {
+        Some(locale) => Some(locale.tag.clone()),
+        None => req.headers().get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()).map(str::to_string),
+    };
+    let response = next.run(req).await;
+    let Some(localizable) = response.extensions().get::<Localizable>().cloned() else {
+        return response;
+    };
+    let locale = localizer.catalog.negotiate(accept_language.as_deref());
+    let body = localizable.render(&localizer.catalog, locale, localizer.shaper).body_json();
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
@@ -0,0 +1,119 @@
+//! Replayed responses for requests carrying an idempotency key.
+//!
+//! Before running a request that must not happen twice, a handler
+//! [claims](IdempotencyStore::claim) its key (typically the `Idempotency-Key` header,
+//! scoped to the caller). The first claim runs the request and
+//! [completes](IdempotencyStore::complete) the key with its response; later claims get that
+//! response back to replay, or learn that the first is still running. A request that fails
+//! in a way worth retrying [releases](IdempotencyStore::release) its key instead.
+//!
+//! Keys live in a [`MemoryIdempotencyStore`] per process; behind the `redis` feature,
+//! [`RedisIdempotencyStore`](crate::redis::RedisIdempotencyStore) shares them between
+//! replicas, so a retry landing on another one is still recognized.
+//!
+//! ```ignore
+//! match store.claim(&key, Duration::from_secs(86400)).await? {
+//!     Claim::New => {}
+//!     Claim::InProgress => return Err(ApiError::conflict("A request with this key is in progress")),
+//!     Claim::Completed(response) => return Ok(replay(response)),
+//! }
+//! ```
+
+use crate::cache::CachedResponse;
+use crate::ApiError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Keys a [`MemoryIdempotencyStore`] holds before dropping the expired ones
+const PRUNE_AT: usize = 4096;
+
+/// What a [`claim`](IdempotencyStore::claim) found under the key
+#[derive(Debug, Clone)]
+pub enum Claim {
+    /// First use of the key: the caller runs the request
+    New,
+    /// Claimed by a request that hasn't completed yet
+    InProgress,
+    /// The response of the request that claimed the key
+    Completed(CachedResponse),
+}
+
+/// The store couldn't be reached or answered something unreadable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyError {
+    pub message: String,
+}
+
+impl std::fmt::Display for IdempotencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "idempotency store unavailable: {}", self.message)
+    }
+}
+
+impl std::error::Error for IdempotencyError {}
+
+/// 503: without the store, the request can't be told apart from an earlier attempt
+impl From<IdempotencyError> for ApiError {
+    fn from(_: IdempotencyError) -> Self {
+        ApiError::service_unavailable("Idempotency keys can't be checked right now")
+    }
+}
+
+/// Idempotency keys and their responses, e.g. [`MemoryIdempotencyStore`] or a shared store
+/// such as Redis
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Claim `key` for `ttl` unless an earlier request did
+    fn claim(&self, key: &str, ttl: Duration) -> impl Future<Output = Result<Claim, IdempotencyError>> + Send;
+    /// Keep `response` under the claimed `key` for `ttl`, replayed to later claims
+    fn complete(&self, key: &str, response: CachedResponse, ttl: Duration) -> impl Future<Output = Result<(), IdempotencyError>> + Send;
+    /// Drop the claim on `key`, letting the next request run
+    fn release(&self, key: &str) -> impl Future<Output = Result<(), IdempotencyError>> + Send;
+}
+
+struct Entry {
+    /// `None` while the claiming request runs
+    response: Option<CachedResponse>,
+    expires: Instant,
+}
+
+/// In-process [`IdempotencyStore`]
+#[derive(Default)]
+pub struct MemoryIdempotencyStore {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn claim(&self, key: &str, ttl: Duration) -> Result<Claim, IdempotencyError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PRUNE_AT {
+            entries.retain(|_, entry| entry.expires > now);
+        }
+        match entries.get(key) {
+            Some(entry) if entry.expires > now => Ok(entry.response.clone().map_or(Claim::InProgress, Claim::Completed)),
+            _ => {
+                entries.insert(key.to_string(), Entry { response: None, expires: now + ttl });
+                Ok(Claim::New)
+            }
+        }
+    }
+
+    async fn complete(&self, key: &str, response: CachedResponse, ttl: Duration) -> Result<(), IdempotencyError> {
+        let entry = Entry { response: Some(response), expires: Instant::now() + ttl };
+        self.entries.lock().unwrap().insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
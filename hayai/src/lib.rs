@@ -1,33 +1,339 @@
+pub mod access_log;
+pub mod audit;
+pub mod auth;
+pub mod blocking;
+pub mod bulk;
+pub mod cache;
+pub mod coalesce;
+pub mod concurrency;
+pub mod config;
+#[cfg(feature = "contract")]
+pub mod contract;
+#[cfg(feature = "decimal")]
+pub mod decimal;
+pub mod deadline;
+pub mod debug;
+pub mod decompression;
+pub mod docs;
+pub mod drain;
+pub mod email;
+pub mod envelope;
+pub mod events;
+pub mod feature_flags;
+pub mod health;
+#[cfg(feature = "http-client")]
+pub mod http_client;
+pub mod i18n;
+pub mod idempotency;
+pub mod lifespan;
+pub mod lint;
+pub mod locale;
+pub mod maintenance;
+pub mod manifest;
 pub mod openapi;
+pub mod operation_id;
+pub mod pagination;
+pub mod parent;
+pub mod patch;
+pub mod precondition;
+pub mod proxy;
+pub mod query;
+pub mod range;
+pub mod rate_limit;
+pub mod reload;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod registry;
+pub mod response_options;
+pub mod runtime;
+pub mod scope;
+pub mod security;
+pub mod security_headers;
+pub mod server;
+pub mod string_encoded;
+pub mod tenant;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod timeout;
+pub mod transaction;
+#[cfg(feature = "geo")]
+pub mod types;
+pub mod versioning;
+pub mod warmup;
+pub mod well_known;
+#[cfg(feature = "webhooks")]
+pub mod webhook;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 use axum::Router;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
 // Re-exports
-pub use hayai_macros::{get, post, put, delete, api_model};
+pub use hayai_macros::{get, post, put, patch, delete, head, api_model, ApiModel};
+pub use blocking::blocking;
+#[cfg(feature = "http-client")]
+pub use http_client::{HttpClient, HttpClientConfig, HttpClients};
 pub use serde;
 pub use serde_json;
+#[cfg(feature = "openapi")]
 pub use schemars;
+#[doc(hidden)]
 pub use inventory;
 pub use axum;
 pub use regex;
 
 pub mod prelude {
-    pub use crate::{get, post, put, delete, api_model};
-    pub use crate::{HayaiApp, HayaiRouter, Dep, State, ApiError, Validate};
-    pub use crate::axum::extract::Query;
+    pub use crate::{get, post, put, patch, delete, head, api_model, ApiModel};
+    pub use crate::{HayaiApp, HayaiRouter, Dep, DepResolver, State, RequestParts, ApiError, NoContent, SerializeResponse, Validate, ValidationError};
+    pub use crate::lifespan::{Lifespan, LifespanSharedState};
+    pub use crate::proxy::{ClientIp, ConnectionInfo};
+    pub use crate::auth::AuthSubject;
+    #[cfg(feature = "auth")]
+    pub use crate::auth::{Auth, SecurityValidator, Subject};
+    #[cfg(feature = "decimal")]
+    pub use crate::decimal::Decimal;
+    pub use crate::axum::http;
+    pub use crate::bulk::{BulkItem, BulkResult};
+    pub use crate::deadline::Deadline;
+    pub use crate::drain::Draining;
+    pub use crate::feature_flags::{FeatureFlags, FlagProvider, InMemoryFlags};
+    pub use crate::envelope::EnvelopeConfig;
+    pub use crate::i18n::MessageCatalog;
+    pub use crate::locale::Locale;
+    pub use crate::maintenance::MaintenanceConfig;
+    pub use crate::pagination::{CursorPage, Page};
+    pub use crate::parent::Parent;
+    pub use crate::precondition::Precondition;
+    pub use crate::range::RangedFile;
+    pub use crate::reload::Reloadable;
+    pub use crate::runtime::RuntimeConfig;
+    pub use crate::scope::{RequestScope, Scoped};
+    pub use crate::security_headers::{FrameOptions, SecurityHeaders};
+    pub use crate::server::HttpConfig;
+    pub use crate::tenant::{Tenant, TenantResolver, TenantScoped, TenantSource};
+    pub use crate::transaction::{RequestTransaction, Tx, TxHandle};
+    #[cfg(feature = "geo")]
+    pub use crate::types::{GeoJsonGeometry, LatLng};
+    pub use crate::versioning::HeaderVersioning;
+    pub use crate::well_known::{RobotsTxt, WellKnown};
+    #[cfg(feature = "webhooks")]
+    pub use crate::webhook::SignedPayload;
+    pub use crate::axum::extract::{Form, Query};
+    #[cfg(feature = "multipart")]
+    pub use crate::axum::extract::Multipart;
 }
 
-/// Validation trait generated by api_model attribute
+/// A response body known only as serializable, e.g. a builder's output or a third-party
+/// type that can't carry `#[api_model]`.
+///
+/// Handlers returning `impl SerializeResponse` declare what the spec documents:
+///
+/// ```ignore
+/// #[get("/rates")]
+/// #[response(schema = inline({ "type": "object", "additionalProperties": { "type": "number" } }))]
+/// async fn rates(feed: Dep<RateFeed>) -> impl SerializeResponse {
+///     feed.snapshot()
+/// }
+/// ```
+pub trait SerializeResponse: Serialize {}
+
+impl<T: Serialize + ?Sized> SerializeResponse for T {}
+
+/// A response without a body, answering 204 unless the route sets another `#[status]`.
+///
+/// Handlers returning `()` answer the same way; `NoContent` names it in signatures such as
+/// `Result<NoContent, ApiError>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoContent;
+
+/// Validation trait generated by api_model attribute.
+///
+/// Implement either method; each defaults to the other.
 pub trait Validate {
-    fn validate(&self) -> Result<(), Vec<String>>;
+    /// Validation errors as `"field: message"` strings
+    fn validate(&self) -> Result<(), Vec<String>> {
+        self.validate_fields()
+            .map_err(|errors| errors.iter().map(ValidationError::to_string).collect())
+    }
+
+    /// Structured validation errors, as passed to [`HayaiApp::validation_response`]
+    fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+        self.validate()
+            .map_err(|errors| errors.iter().map(|e| ValidationError::from_message(e, "invalid")).collect())
+    }
+
+    /// Checks that need app dependencies, such as uniqueness against a database; run
+    /// after the synchronous rules pass. Generated from `#[api_model(async_validate = "..")]`.
+    fn validate_async(&self, _deps: &DepResolver<'_>) -> impl std::future::Future<Output = Result<(), AsyncValidationError>> + Send {
+        std::future::ready(Ok(()))
+    }
+}
+
+/// Collection bodies validate every element, naming it in each error: `[1].email`
+impl<T: Validate + Sync> Validate for Vec<T> {
+    fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+        validate_elements(self.iter().enumerate().map(|(i, item)| (i.to_string(), item)))
+    }
+
+    fn validate_async(&self, deps: &DepResolver<'_>) -> impl std::future::Future<Output = Result<(), AsyncValidationError>> + Send {
+        validate_elements_async(self.iter().enumerate().map(|(i, item)| (i.to_string(), item)).collect(), deps)
+    }
+}
+
+impl<T: Validate + Sync, H: Sync> Validate for HashMap<String, T, H> {
+    fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+        validate_elements(self.iter().map(|(key, item)| (key.clone(), item)))
+    }
+
+    fn validate_async(&self, deps: &DepResolver<'_>) -> impl std::future::Future<Output = Result<(), AsyncValidationError>> + Send {
+        validate_elements_async(self.iter().map(|(key, item)| (key.clone(), item)).collect(), deps)
+    }
+}
+
+impl<T: Validate + Sync> Validate for std::collections::BTreeMap<String, T> {
+    fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+        validate_elements(self.iter().map(|(key, item)| (key.clone(), item)))
+    }
+
+    fn validate_async(&self, deps: &DepResolver<'_>) -> impl std::future::Future<Output = Result<(), AsyncValidationError>> + Send {
+        validate_elements_async(self.iter().map(|(key, item)| (key.clone(), item)).collect(), deps)
+    }
+}
+
+/// Elements without rules of their own, e.g. `Vec<String>` bodies
+macro_rules! impl_validate_noop {
+    ($($t:ty),*) => {$(
+        impl Validate for $t {
+            fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_validate_noop!(String, bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, serde_json::Value);
+
+/// `field` of the element at `index` becomes `[index].field`
+fn element_field(index: &str, field: &str) -> String {
+    if field.is_empty() {
+        format!("[{}]", index)
+    } else {
+        format!("[{}].{}", index, field)
+    }
+}
+
+/// Name an error of a model field's own validation after the field: `location.lat`,
+/// `stops[1].lat`
+#[doc(hidden)]
+pub fn __nested_validation_error(field: &str, error: ValidationError) -> ValidationError {
+    let nested = if error.field.is_empty() {
+        field.to_string()
+    } else if error.field.starts_with('[') {
+        format!("{}{}", field, error.field)
+    } else {
+        format!("{}.{}", field, error.field)
+    };
+    ValidationError { field: nested, ..error }
+}
+
+fn validate_elements<'a, T: Validate + 'a>(items: impl Iterator<Item = (String, &'a T)>) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    for (index, item) in items {
+        if let Err(item_errors) = item.validate_fields() {
+            errors.extend(item_errors.into_iter().map(|e| ValidationError { field: element_field(&index, &e.field), ..e }));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+async fn validate_elements_async<T: Validate + Sync>(items: Vec<(String, &T)>, deps: &DepResolver<'_>) -> Result<(), AsyncValidationError> {
+    let mut errors = Vec::new();
+    for (index, item) in items {
+        match item.validate_async(deps).await {
+            Ok(()) => {}
+            Err(AsyncValidationError::Fields(item_errors)) => errors.extend(item_errors.iter().map(|e| {
+                let e = ValidationError::from_message(e, "async");
+                format!("{}: {}", element_field(&index, &e.field), e.message)
+            })),
+            Err(AsyncValidationError::Error(error)) => return Err(AsyncValidationError::Error(error)),
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(AsyncValidationError::Fields(errors)) }
+}
+
+/// Failure of an `#[api_model(async_validate = "..")]` validator
+#[derive(Debug)]
+pub enum AsyncValidationError {
+    /// `"field: message"` strings, answered like synchronous validation errors
+    Fields(Vec<String>),
+    /// Returned as is, e.g. a 409 Conflict
+    Error(ApiError),
+}
+
+impl From<Vec<String>> for AsyncValidationError {
+    fn from(errors: Vec<String>) -> Self {
+        Self::Fields(errors)
+    }
+}
+
+impl From<ApiError> for AsyncValidationError {
+    fn from(error: ApiError) -> Self {
+        Self::Error(error)
+    }
+}
+
+/// A single failed validation rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// Serialized field name, empty for model-level (cross-field) errors
+    pub field: String,
+    /// The rule that failed: `min_length`, `email`, `custom`, `unknown_field`, ...
+    pub code: String,
+    pub message: String,
+    /// The rule's arguments, `min` for `min_length`, substituted into localized messages
+    #[serde(skip)]
+    pub params: Vec<(String, String)>,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), code: code.into(), message: message.into(), params: Vec::new() }
+    }
+
+    /// A value for the `{name}` placeholder of the localized message
+    pub fn with_param(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.params.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Parse a `"field: message"` string; anything else is a model-level error
+    pub fn from_message(msg: &str, code: &str) -> Self {
+        match msg.split_once(": ") {
+            Some((field, message)) if !field.contains(' ') => Self::new(field, code, message),
+            _ => Self::new("", code, msg),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
 }
 
+/// Builds the body of 422 responses, see [`HayaiApp::validation_response`]
+pub type ValidationShaper = fn(Vec<ValidationError>) -> serde_json::Value;
+
 impl Validate for () {
     fn validate(&self) -> Result<(), Vec<String>> {
         Ok(())
@@ -40,15 +346,101 @@ pub trait HasSchemaPatches {
     fn patch_schema(props: &mut HashMap<String, openapi::PropertyPatch>);
 }
 
+/// App-wide runtime settings consulted by generated handler wrappers
+#[derive(Default)]
+pub(crate) struct AppSettings {
+    /// Under [`HayaiApp::strict_bodies`], the properties of each opted-in model by component name
+    strict_bodies: HashMap<String, HashSet<String>>,
+    json: JsonConfig,
+    validation_response: Option<(StatusCode, ValidationShaper)>,
+    concurrency: HashMap<usize, concurrency::RouteLimiter>,
+    response_cache: Option<Arc<dyn cache::DynResponseCache>>,
+    in_flight: coalesce::InFlight,
+    tenant_deps: HashMap<TypeId, tenant::TenantDep>,
+    parent_loaders: HashMap<TypeId, parent::ParentLoader>,
+    drain: Arc<drain::DrainState>,
+    deadline: std::time::Duration,
+    shutdown: Arc<deadline::ShutdownSignal>,
+    feature_flags: Option<feature_flags::FeatureFlags>,
+    disabled_route: feature_flags::DisabledRoute,
+    /// Held only to stop the `reloadable_config` watchers when the router goes away
+    _reload_watchers: reload::Watchers,
+}
+
+impl AppSettings {
+    /// The error response for failed validation, shaped by the app's shaper if one is installed
+    fn validation_error(&self, errors: Vec<ValidationError>) -> ApiError {
+        let mut err = ApiError::from_validation_errors(errors.clone());
+        if let Some((status, shaper)) = self.validation_response {
+            err.status = status;
+            err.body = Some(shaper(errors));
+        }
+        err
+    }
+}
+
+/// Validate a request body for a generated handler wrapper
+#[doc(hidden)]
+pub fn __validate<T: Validate>(state: &AppState, value: &T) -> Result<(), ApiError> {
+    value.validate_fields().map_err(|errors| state.settings.validation_error(errors))
+}
+
+/// Run a request body's async validator for a generated handler wrapper
+#[doc(hidden)]
+pub async fn __validate_async<T: Validate>(state: &AppState, value: &T) -> Result<(), ApiError> {
+    match value.validate_async(&DepResolver { state: std::borrow::Cow::Borrowed(state) }).await {
+        Ok(()) => Ok(()),
+        Err(AsyncValidationError::Fields(errors)) => Err(state.settings.validation_error(
+            errors.iter().map(|e| ValidationError::from_message(e, "async")).collect(),
+        )),
+        Err(AsyncValidationError::Error(error)) => Err(error),
+    }
+}
+
+/// Formatting of JSON responses, see [`HayaiApp::json_config`]
+#[derive(Debug, Clone, Default)]
+pub struct JsonConfig {
+    /// Pretty-print response bodies (handy in development)
+    pub pretty: bool,
+}
+
+/// Property naming convention checked by [`HayaiApp::enforce_casing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    /// `camelCase`
+    Camel,
+    /// `snake_case`
+    Snake,
+    /// `PascalCase`
+    Pascal,
+    /// `kebab-case`
+    Kebab,
+}
+
+impl Casing {
+    pub fn matches(self, name: &str) -> bool {
+        let Some(first) = name.chars().next() else { return true };
+        let alnum = |sep: Option<char>| name.chars().all(|c| c.is_ascii_alphanumeric() || Some(c) == sep);
+        match self {
+            Casing::Camel => first.is_ascii_lowercase() && alnum(None),
+            Casing::Pascal => first.is_ascii_uppercase() && alnum(None),
+            Casing::Snake => !name.chars().any(|c| c.is_ascii_uppercase()) && alnum(Some('_')),
+            Casing::Kebab => !name.chars().any(|c| c.is_ascii_uppercase()) && alnum(Some('-')),
+        }
+    }
+}
+
 /// Application state holding dependency injection container
 #[derive(Clone)]
 pub struct AppState {
     deps: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    settings: Arc<AppSettings>,
 }
 
 impl AppState {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self { deps: Arc::new(HashMap::new()) }
+        Self { deps: Arc::new(HashMap::new()), settings: Arc::new(AppSettings::default()) }
     }
 
     pub fn get<T: 'static + Send + Sync>(&self) -> Option<Arc<T>> {
@@ -59,14 +451,48 @@ impl AppState {
 }
 
 /// Dependency injection extractor
+///
+/// A shared handle on the instance registered with [`HayaiApp::dep`]: it derefs to `T`, so
+/// `&db` passes where a `&Database` is expected, and cloning it only bumps a reference
+/// count. Clone it, or take [`inner_arc`](Dep::inner_arc), to move it into a spawned task.
 pub struct Dep<T: 'static + Send + Sync>(Arc<T>);
 
 impl<T: 'static + Send + Sync> Dep<T> {
+    /// A dependency outside of a request, e.g. to call a handler from a unit test
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
     pub fn from_app_state(state: &AppState) -> Result<Self, ApiError> {
         state.get::<T>()
             .map(Dep)
             .ok_or_else(|| ApiError::internal(format!("Dependency not registered: {}", std::any::type_name::<T>())))
     }
+
+    /// The shared instance, for code that keeps an `Arc<T>`
+    pub fn inner_arc(&self) -> Arc<T> {
+        self.0.clone()
+    }
+}
+
+impl<T: 'static + Send + Sync> axum::extract::FromRequestParts<AppState> for Dep<T> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(_parts: &mut axum::http::request::Parts, state: &AppState) -> Result<Self, ApiError> {
+        Self::from_app_state(state)
+    }
+}
+
+impl<T: 'static + Send + Sync> From<Arc<T>> for Dep<T> {
+    fn from(shared: Arc<T>) -> Self {
+        Self(shared)
+    }
+}
+
+impl<T: 'static + Send + Sync> Clone for Dep<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
 }
 
 impl<T: 'static + Send + Sync> std::ops::Deref for Dep<T> {
@@ -76,6 +502,40 @@ impl<T: 'static + Send + Sync> std::ops::Deref for Dep<T> {
     }
 }
 
+impl<T: 'static + Send + Sync> AsRef<T> for Dep<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: 'static + Send + Sync> std::borrow::Borrow<T> for Dep<T> {
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: 'static + Send + Sync + std::fmt::Debug> std::fmt::Debug for Dep<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Dep").field(&self.0).finish()
+    }
+}
+
+/// Dependencies available to async validators, see [`Validate::validate_async`]
+pub struct DepResolver<'a> {
+    state: std::borrow::Cow<'a, AppState>,
+}
+
+impl DepResolver<'_> {
+    /// A resolver that outlives the request, for futures that must be `'static`
+    pub(crate) fn owned(state: AppState) -> DepResolver<'static> {
+        DepResolver { state: std::borrow::Cow::Owned(state) }
+    }
+
+    pub fn get<T: 'static + Send + Sync>(&self) -> Result<Dep<T>, ApiError> {
+        Dep::from_app_state(&self.state)
+    }
+}
+
 /// Axum-style state extractor — alternative to Dep<T>
 /// Both work identically; choose based on your preferred style.
 pub struct State<T: 'static + Send + Sync>(Arc<T>);
@@ -95,6 +555,32 @@ impl<T: 'static + Send + Sync> std::ops::Deref for State<T> {
     }
 }
 
+/// A snapshot of the request line, headers and extensions, for handlers that need more
+/// than the typed arguments (signature checks, forwarding upstream). Taken before the
+/// body is read, so it can be combined with a body argument.
+#[derive(Debug, Clone)]
+pub struct RequestParts {
+    pub method: axum::http::Method,
+    pub uri: axum::http::Uri,
+    pub version: axum::http::Version,
+    pub headers: axum::http::HeaderMap,
+    pub extensions: axum::http::Extensions,
+}
+
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for RequestParts {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut axum::http::request::Parts, _state: &S) -> Result<Self, ApiError> {
+        Ok(Self {
+            method: parts.method.clone(),
+            uri: parts.uri.clone(),
+            version: parts.version,
+            headers: parts.headers.clone(),
+            extensions: parts.extensions.clone(),
+        })
+    }
+}
+
 /// API Error type
 #[derive(Debug, Serialize)]
 pub struct ApiError {
@@ -103,107 +589,660 @@ pub struct ApiError {
     pub error: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub details: Vec<String>,
+    /// Response body replacing the default `{error, details}` shape, e.g. from a
+    /// [`HayaiApp::validation_response`] shaper
+    #[serde(skip)]
+    pub body: Option<serde_json::Value>,
+    /// What a [`i18n::MessageCatalog`] localizes the message and details by
+    #[serde(skip)]
+    pub message_key: Option<Box<i18n::MessageKey>>,
 }
 
 impl ApiError {
+    pub fn new(status: StatusCode, msg: impl Into<String>) -> Self {
+        Self { status, error: msg.into(), details: vec![], body: None, message_key: None }
+    }
+
+    /// Name the catalog entry that localizes this error's message
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.message_key.get_or_insert_with(Default::default).code = Some(code.into());
+        self
+    }
+
+    /// A value for the `{name}` placeholder of the localized message
+    pub fn with_param(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.message_key.get_or_insert_with(Default::default).params.push((name.into(), value.to_string()));
+        self
+    }
+
     pub fn unauthorized(msg: impl Into<String>) -> Self {
-        Self { status: StatusCode::UNAUTHORIZED, error: msg.into(), details: vec![] }
+        Self::new(StatusCode::UNAUTHORIZED, msg)
     }
 
     pub fn bad_request(msg: String) -> Self {
-        Self { status: StatusCode::BAD_REQUEST, error: msg, details: vec![] }
+        Self::new(StatusCode::BAD_REQUEST, msg)
     }
 
     pub fn not_found(msg: String) -> Self {
-        Self { status: StatusCode::NOT_FOUND, error: msg, details: vec![] }
+        Self::new(StatusCode::NOT_FOUND, msg)
+    }
+
+    pub fn conflict(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, msg)
     }
 
     pub fn internal(msg: String) -> Self {
-        Self { status: StatusCode::INTERNAL_SERVER_ERROR, error: msg, details: vec![] }
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+
+    pub fn unsupported_media_type(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, msg)
+    }
+
+    pub fn payload_too_large(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::PAYLOAD_TOO_LARGE, msg)
+    }
+
+    pub fn uri_too_long(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::URI_TOO_LONG, msg)
+    }
+
+    pub fn service_unavailable(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, msg)
+    }
+
+    pub fn gateway_timeout(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::GATEWAY_TIMEOUT, msg)
+    }
+
+    pub fn not_acceptable(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_ACCEPTABLE, msg)
+    }
+
+    pub fn precondition_failed(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::PRECONDITION_FAILED, msg)
+    }
+
+    pub fn range_not_satisfiable(msg: impl Into<String>) -> Self {
+        Self::new(StatusCode::RANGE_NOT_SATISFIABLE, msg)
+    }
+
+    /// 400 listing the path parameters that fail their `#[param(..)]` constraints
+    pub fn invalid_path_params(errors: Vec<String>) -> Self {
+        let error = format!("Invalid path params: {}", errors.join(", "));
+        Self { details: errors, ..Self::new(StatusCode::BAD_REQUEST, error) }
     }
 
     pub fn validation_error(errors: Vec<String>) -> Self {
-        Self {
-            status: StatusCode::UNPROCESSABLE_ENTITY,
-            error: "Validation failed".into(),
-            details: errors,
+        Self { details: errors, ..Self::new(StatusCode::UNPROCESSABLE_ENTITY, "Validation failed").with_code("validation_failed") }
+    }
+}
+
+impl ApiError {
+    /// Map a JSON body rejection to an ApiError.
+    ///
+    /// Unknown fields rejected by `deny_unknown_fields` become a 422 naming the field;
+    /// everything else stays a 400.
+    pub fn from_json_rejection(rejection: axum::extract::rejection::JsonRejection) -> Self {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            return Self::payload_too_large("Request body too large");
+        }
+        let msg = rejection.body_text();
+        if let Some(error) = rejected_enum_value(&msg) {
+            return Self::from_validation_errors(vec![error]);
+        }
+        if let Some(field) = unknown_field_name(&msg) {
+            return Self::validation_error(vec![format!("{}: unknown field", field)]);
+        }
+        Self::bad_request(format!("Invalid body: {}", msg))
+    }
+
+    /// 422 listing the failed rules; a [`i18n::MessageCatalog`] can localize each of them
+    pub fn from_validation_errors(errors: Vec<ValidationError>) -> Self {
+        let mut err = Self::validation_error(errors.iter().map(ValidationError::to_string).collect());
+        err.message_key.get_or_insert_with(Default::default).validation = errors;
+        err
+    }
+}
+
+/// [`ApiError::from_json_rejection`], shaping invalid enum values and unknown fields like
+/// other failed rules
+fn json_rejection(rejection: axum::extract::rejection::JsonRejection, state: &AppState) -> ApiError {
+    let msg = rejection.body_text();
+    if let Some(error) = rejected_enum_value(&msg) {
+        return state.settings.validation_error(vec![error]);
+    }
+    if let Some(field) = unknown_field_name(&msg) {
+        return state.settings.validation_error(vec![ValidationError::new(field, "unknown_field", "unknown field")]);
+    }
+    ApiError::from_json_rejection(rejection)
+}
+
+/// [`invalid_enum_value`] of axum's "Failed to deserialize the JSON body" rejection text
+fn rejected_enum_value(msg: &str) -> Option<ValidationError> {
+    invalid_enum_value(msg.strip_prefix("Failed to deserialize the JSON body into the target type: ")?)
+}
+
+/// A value that is none of an enum's variants, from serde's
+/// "path: unknown variant `x`, expected `A` or `B`" message
+fn invalid_enum_value(msg: &str) -> Option<ValidationError> {
+    let start = msg.find("unknown variant `")?;
+    let field = msg[..start].strip_suffix(": ").unwrap_or_default();
+    let (value, expected) = msg[start + "unknown variant `".len()..].split_once("`, expected ")?;
+    // "`A`", "`A` or `B`", or "one of `A`, `B`, `C`", then the position
+    let allowed: Vec<&str> = expected.split('`').skip(1).step_by(2).collect();
+    let allowed = allowed.join(", ");
+    Some(ValidationError::new(field, "invalid_enum_value", format!("must be one of {}", allowed))
+        .with_param("value", value)
+        .with_param("allowed", allowed))
+}
+
+/// Extract the field name from serde's "unknown field `x`" message
+fn unknown_field_name(msg: &str) -> Option<&str> {
+    let start = msg.find("unknown field `")? + "unknown field `".len();
+    let len = msg[start..].find('`')?;
+    Some(&msg[start..start + len])
+}
+
+/// Extract and deserialize a JSON request body for a generated handler wrapper.
+///
+/// With [`HayaiApp::strict_bodies`] enabled, top-level keys that are not properties of a
+/// `#[api_model(strict_bodies)]` model are rejected with a 422.
+#[doc(hidden)]
+pub async fn __extract_json_body<T: serde::de::DeserializeOwned>(
+    req: axum::http::Request<axum::body::Body>,
+    state: &AppState,
+    type_name: &str,
+) -> Result<T, ApiError> {
+    use axum::extract::FromRequest;
+
+    let req = keep_body_for_log(req, state).await?;
+    let Some(known) = state.settings.strict_bodies.get(type_name) else {
+        let axum::Json(value) = axum::Json::<T>::from_request(req, state).await
+            .map_err(|rejection| json_rejection(rejection, state))?;
+        return Ok(value);
+    };
+
+    let axum::Json(raw) = axum::Json::<serde_json::Value>::from_request(req, state).await
+        .map_err(ApiError::from_json_rejection)?;
+    reject_unknown_keys(&raw, known, state)?;
+    serde_path_to_error::deserialize(raw).map_err(|e| body_error(e, state))
+}
+
+/// The [`HayaiApp::strict_bodies`] check: top-level keys must be `known` properties of the
+/// body model, or of each element's for a list body
+fn reject_unknown_keys(raw: &serde_json::Value, known: &HashSet<String>, state: &AppState) -> Result<(), ApiError> {
+    let objects: Vec<(String, &serde_json::Map<String, serde_json::Value>)> = match raw {
+        serde_json::Value::Object(obj) => vec![(String::new(), obj)],
+        serde_json::Value::Array(items) => items.iter().enumerate()
+            .filter_map(|(i, item)| item.as_object().map(|obj| (format!("[{}].", i), obj)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let unknown: Vec<ValidationError> = objects.iter()
+        .flat_map(|(prefix, obj)| obj.keys().map(move |k| (prefix, k)))
+        .filter(|(_, k)| !known.contains(*k))
+        .map(|(prefix, k)| ValidationError::new(format!("{}{}", prefix, k), "unknown_field", "unknown field"))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(state.settings.validation_error(unknown));
+    }
+    Ok(())
+}
+
+fn body_error(e: serde_path_to_error::Error<serde_json::Error>, state: &AppState) -> ApiError {
+    if let Some(error) = invalid_enum_value(&e.to_string()) {
+        return state.settings.validation_error(vec![error]);
+    }
+    let msg = e.into_inner().to_string();
+    match unknown_field_name(&msg) {
+        Some(field) => state.settings.validation_error(vec![ValidationError::new(field, "unknown_field", "unknown field")]),
+        None => ApiError::bad_request(format!("Invalid body: {}", msg)),
+    }
+}
+
+/// Read a JSON request body for a `#[zero_copy]` handler wrapper, which keeps the bytes
+/// alive while the handler borrows from them. Same Content-Type check and body limit as
+/// [`__extract_json_body`].
+#[doc(hidden)]
+pub async fn __read_json_bytes(
+    req: axum::http::Request<axum::body::Body>,
+    state: &AppState,
+) -> Result<axum::body::Bytes, ApiError> {
+    use axum::extract::FromRequest;
+
+    let is_json = req.headers().get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|essence| {
+            let essence = essence.trim().to_ascii_lowercase();
+            essence == "application/json" || (essence.starts_with("application/") && essence.ends_with("+json"))
+        })
+        .unwrap_or(false);
+    if !is_json {
+        return Err(ApiError::bad_request("Invalid body: Expected request with `Content-Type: application/json`".to_string()));
+    }
+    let slot = req.extensions().get::<access_log::BodySlot>().cloned();
+    let bytes = axum::body::Bytes::from_request(req, state).await.map_err(body_read_error)?;
+    if let Some(slot) = &slot {
+        slot.keep(&bytes);
+    }
+    Ok(bytes)
+}
+
+fn body_read_error(rejection: axum::extract::rejection::BytesRejection) -> ApiError {
+    if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::payload_too_large("Request body too large")
+    } else {
+        ApiError::bad_request(format!("Failed to read body: {}", rejection.body_text()))
+    }
+}
+
+/// With [`HayaiApp::log_bodies`] or on an `#[audited]` route, read the body ahead of the
+/// extractor and hand a copy to the access log and audit trail; the extractor then gets a
+/// request with the same bytes
+async fn keep_body_for_log(
+    req: axum::http::Request<axum::body::Body>,
+    state: &AppState,
+) -> Result<axum::http::Request<axum::body::Body>, ApiError> {
+    use axum::extract::FromRequest;
+
+    let Some(slot) = req.extensions().get::<access_log::BodySlot>().cloned() else {
+        return Ok(req);
+    };
+    let (parts, body) = req.into_parts();
+    let bytes = axum::body::Bytes::from_request(axum::http::Request::from_parts(parts.clone(), body), state).await
+        .map_err(body_read_error)?;
+    slot.keep(&bytes);
+    Ok(axum::http::Request::from_parts(parts, axum::body::Body::from(bytes)))
+}
+
+/// Deserialize a `#[zero_copy]` body, borrowing string fields from `bytes` where serde can
+/// (strings containing escapes are still copied into a `Cow`)
+#[doc(hidden)]
+pub fn __parse_json_body<'de, T: serde::Deserialize<'de>>(
+    bytes: &'de [u8],
+    state: &AppState,
+    type_name: &str,
+) -> Result<T, ApiError> {
+    if let Some(known) = state.settings.strict_bodies.get(type_name) {
+        let raw: serde_json::Value = serde_json::from_slice(bytes)
+            .map_err(|e| ApiError::bad_request(format!("Invalid body: {}", e)))?;
+        reject_unknown_keys(&raw, known, state)?;
+    }
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| body_error(e, state))?;
+    deserializer.end().map_err(|e| ApiError::bad_request(format!("Invalid body: {}", e)))?;
+    Ok(value)
+}
+
+/// Serialize a handler's return value for a generated wrapper, honouring [`JsonConfig`]
+#[doc(hidden)]
+pub fn __json_response<T: Serialize>(state: &AppState, status: StatusCode, value: &T) -> Result<Response, ApiError> {
+    let body = if state.settings.json.pretty {
+        serde_json::to_vec_pretty(value)
+    } else {
+        serde_json::to_vec(value)
+    }
+    .map_err(|e| ApiError::internal(format!("Response serialization failed: {}", e)))?;
+    Ok((status, [(axum::http::header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+/// Types a `#[consumes("...")]` handler can take as its raw request body
+#[doc(hidden)]
+pub trait RawBody: Sized {
+    fn from_bytes(bytes: axum::body::Bytes) -> Result<Self, ApiError>;
+}
+
+impl RawBody for String {
+    fn from_bytes(bytes: axum::body::Bytes) -> Result<Self, ApiError> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| ApiError::bad_request("Invalid body: not valid UTF-8".to_string()))
+    }
+}
+
+impl RawBody for axum::body::Bytes {
+    fn from_bytes(bytes: axum::body::Bytes) -> Result<Self, ApiError> {
+        Ok(bytes)
+    }
+}
+
+/// Read the raw request body for a `#[consumes("...")]` handler, answering 415 when the
+/// request's `Content-Type` isn't the declared media type. Same body limit as
+/// [`__extract_json_body`].
+#[doc(hidden)]
+pub async fn __extract_raw_body<T: RawBody>(
+    req: axum::http::Request<axum::body::Body>,
+    state: &AppState,
+    content_type: &str,
+) -> Result<T, ApiError> {
+    use axum::extract::FromRequest;
+
+    let received = req.headers().get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let essence = received.split(';').next().unwrap_or("").trim();
+    if !essence.eq_ignore_ascii_case(content_type) {
+        return Err(ApiError::unsupported_media_type(format!("Expected Content-Type: {}", content_type)));
+    }
+    let bytes = axum::body::Bytes::from_request(req, state).await.map_err(body_read_error)?;
+    T::from_bytes(bytes)
+}
+
+/// Extract a `Form<T>` body for a generated handler wrapper
+#[doc(hidden)]
+pub async fn __extract_form<T: serde::de::DeserializeOwned>(
+    req: axum::http::Request<axum::body::Body>,
+    state: &AppState,
+) -> Result<axum::extract::Form<T>, ApiError> {
+    use axum::extract::FromRequest;
+    axum::extract::Form::<T>::from_request(req, state).await
+        .map_err(|e| ApiError::new(e.status(), format!("Invalid form body: {}", e.body_text())))
+}
+
+/// Extract a `Multipart` body for a generated handler wrapper
+#[doc(hidden)]
+#[cfg(feature = "multipart")]
+pub async fn __extract_multipart(
+    req: axum::http::Request<axum::body::Body>,
+    state: &AppState,
+) -> Result<axum::extract::Multipart, ApiError> {
+    use axum::extract::FromRequest;
+    axum::extract::Multipart::from_request(req, state).await
+        .map_err(|e| ApiError::new(e.status(), format!("Invalid multipart body: {}", e.body_text())))
+}
+
+/// Extract a `Query<T>` for a generated handler wrapper; see [`query::from_query_str`].
+#[doc(hidden)]
+pub fn __extract_query<T: serde::de::DeserializeOwned>(
+    parts: &axum::http::request::Parts,
+    array_fields: &[String],
+) -> Result<axum::extract::Query<T>, ApiError> {
+    let query = parts.uri.query().unwrap_or("");
+    // A panicking `Deserialize` impl must not take the connection down with it
+    std::panic::catch_unwind(|| query::from_query_str(query, array_fields))
+        .map_err(|_| ApiError::bad_request("Invalid query parameters".to_string()))?
+        .map(axum::extract::Query)
+        .map_err(|e| ApiError::bad_request(format!("Invalid query parameters: {}", e)))
+}
+
+/// Map a `Path` rejection to a 400 naming the offending parameter; `names` are the
+/// handler's path parameters in order.
+#[doc(hidden)]
+pub fn __path_rejection(rejection: axum::extract::rejection::PathRejection, names: &[&str]) -> ApiError {
+    use axum::extract::path::ErrorKind;
+    use axum::extract::rejection::PathRejection;
+    let PathRejection::FailedToDeserializePathParams(e) = rejection else {
+        return ApiError::internal(rejection.body_text());
+    };
+    let named = |name: &str, reason: String| ApiError::bad_request(format!("Invalid path param `{}`: {}", name, reason));
+    match e.into_kind() {
+        ErrorKind::InvalidUtf8InPathParam { key } => named(&key, "not valid UTF-8".to_string()),
+        ErrorKind::ParseErrorAtKey { key, value, expected_type } => {
+            named(&key, format!("cannot parse {:?} as {}", value, expected_type))
+        }
+        ErrorKind::ParseErrorAtIndex { index, value, expected_type } if index < names.len() => {
+            named(names[index], format!("cannot parse {:?} as {}", value, expected_type))
+        }
+        ErrorKind::ParseError { value, expected_type } if names.len() == 1 => {
+            named(names[0], format!("cannot parse {:?} as {}", value, expected_type))
+        }
+        ErrorKind::DeserializeError { key, message, .. } => named(&key, message),
+        kind => ApiError::bad_request(format!("Invalid path params: {}", kind)),
+    }
+}
+
+/// Expand a `Query<T>` struct into OpenAPI query parameters for a generated route.
+///
+/// When `T` is also an `#[api_model]`, its registered schema supplies the
+/// `#[validate(...)]` constraints and doc comments schemars doesn't see.
+#[doc(hidden)]
+#[cfg(feature = "openapi")]
+pub fn __query_params(root: &schemars::schema::RootSchema, type_name: &str) -> Vec<openapi::DynParameter> {
+    let mut params = openapi::query_params_from_schema(root);
+    if let Some(info) = inventory::iter::<SchemaInfo>.into_iter().find(|s| s.component_name() == type_name) {
+        let schema = (info.schema_fn)();
+        for param in &mut params {
+            if let Some(prop) = schema.properties.get(&param.name) {
+                param.merge_property(prop);
+            }
+        }
+    }
+    params
+}
+
+impl ApiError {
+    /// The response body as JSON, e.g. for a failed [`bulk::BulkItem`]
+    pub(crate) fn body_value(&self) -> serde_json::Value {
+        match &self.body {
+            Some(custom) => custom.clone(),
+            None => serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({ "error": "Internal server error" })),
+        }
+    }
+
+    /// The response body: the custom body if one is set, `{error, details}` otherwise
+    pub(crate) fn body_json(&self) -> String {
+        match &self.body {
+            Some(custom) => serde_json::to_string(custom),
+            None => serde_json::to_string(self),
         }
+            .unwrap_or_else(|_| r#"{"error":"Internal server error"}"#.to_string())
     }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let body = serde_json::to_string(&self)
-            .unwrap_or_else(|_| r#"{"error":"Internal server error"}"#.to_string());
-        (self.status, [("content-type", "application/json")], body).into_response()
+        let body = self.body_json();
+        let localizable = i18n::Localizable::of(&self);
+        let mut response = (self.status, [("content-type", "application/json")], body).into_response();
+        if let Some(localizable) = localizable {
+            response.extensions_mut().insert(localizable);
+        }
+        response
     }
 }
 
-/// Route information collected by proc macros
+/// Route information collected by proc macros; see [`registry`] for listing and accessors
 pub struct RouteInfo {
     pub path: &'static str,
     pub axum_path: &'static str,
     pub method: &'static str,
     pub handler_name: &'static str,
+    /// `#[operation_id("..")]`, used instead of the id [`HayaiApp::operation_id_style`] derives
+    pub operation_id: Option<&'static str>,
+    /// Type name of the response model; `Value` for untyped JSON, `()` for responses without
+    /// a body, or the component named by `#[response(schema = "..")]`
     pub response_type_name: &'static str,
     pub is_result_return: bool,
+    /// Status answered when an `Option<T>` handler returns `None`: 404, or 204 with
+    /// `#[none_as(204)]`
+    pub none_status: Option<u16>,
     pub is_vec_response: bool,
+    /// Element type of a `Vec<T>` response, or `T` of a paginated one
     pub vec_inner_type_name: &'static str,
+    /// Schema of a response its type names can't describe: maps, `#[response(tuple)]`
+    /// tuples, collections of `Option<T>` or of other collections, and
+    /// `#[response(schema = inline(..))]`
+    pub response_schema_fn: Option<fn() -> serde_json::Value>,
+    /// Set for handlers returning `Page<T>` or `CursorPage<T>`
+    pub pagination: Option<pagination::PageKind>,
+    /// Set for handlers returning `BulkResult<T>`
+    pub bulk: bool,
     pub parameters: &'static [openapi::Parameter],
     pub has_body: bool,
+    /// Element type name when the body is a `Vec<T>` or string-keyed map of `T`
     pub body_type_name: &'static str,
+    pub body_collection: Option<openapi::BodyCollection>,
+    /// Media type of the request body: `application/json`, form encodings for `Form<T>` and
+    /// `Multipart`, or the `#[consumes]` type
+    pub body_content_type: &'static str,
+    /// `#[request_body(name = "..")]`: documented as `#/components/requestBodies/{name}`
+    pub request_body_name: Option<&'static str>,
+    /// `#[request_body(description = "..")]`
+    pub request_body_description: Option<&'static str>,
+    /// Media type of the success response: `application/json`, or the `#[produces]` type
+    pub produces: &'static str,
+    /// Fields declared with `#[multipart_schema(name: type, ..)]` for a `Multipart` body
+    pub multipart_fields: &'static [(&'static str, &'static str)],
+    /// Error component overrides from `#[responses(401 = "UnauthorizedError", ..)]`
+    pub error_responses: &'static [(u16, &'static str)],
+    /// Success response headers from `#[response_header("ETag", "string", "..")]` as
+    /// `(name, type, description)`
+    pub response_headers: &'static [(&'static str, &'static str, &'static str)],
+    /// Links on the success response from `#[link(..)]`
+    pub links: &'static [openapi::Link],
+    /// `#[transactional]` (`Some(true)`) or `#[transactional(skip)]` (`Some(false)`)
+    pub transactional: Option<bool>,
+    /// `#[concurrency_limit(..)]`
+    pub concurrency_limit: Option<concurrency::ConcurrencyLimit>,
+    /// `#[cache(..)]`
+    pub cache: Option<cache::CachePolicy>,
+    /// `#[emit_event(..)]`
+    pub emit_event: Option<events::EmitEvent>,
+    /// `#[audited(resource = "..")]`: the resource name, see [`audit`]
+    pub audited: Option<&'static str>,
+    /// `#[timeout("5s")]`
+    pub timeout: Option<std::time::Duration>,
+    /// `#[feature_flag("..")]`: the flag that must be on for the route to answer, see
+    /// [`feature_flags`]
+    pub feature_flag: Option<&'static str>,
+    /// `#[coalesce(..)]`: identical requests in flight share one handler run, see [`coalesce`]
+    pub coalesce: Option<coalesce::CoalescePolicy>,
+    /// `#[no_envelope]`: success bodies are sent as they are under
+    /// [`HayaiApp::response_envelope`], see [`envelope`]
+    pub no_envelope: bool,
+    /// `#[version(2)]`: the API version served, see [`versioning`]
+    pub version: Option<u32>,
+    /// Vendor extensions from `#[openapi_extension("x-..", value)]`, added to the operation
+    pub openapi_extensions: &'static [openapi::Extension],
     pub success_status: u16,
     pub description: &'static str,
     pub tags: &'static [&'static str],
+    /// `#[tag(replace = "..")]`: `tags` replace the routers' tags instead of adding to them
+    pub replaces_tags: bool,
     pub security: &'static [&'static str],
+    /// `#[security(none)]`: the routers' security doesn't apply, only `security`
+    pub clears_security: bool,
     pub query_params_fn: Option<fn() -> Vec<openapi::DynParameter>>,
     pub register_fn: fn(Router<AppState>) -> Router<AppState>,
     pub method_router_fn: fn() -> axum::routing::MethodRouter<AppState>,
+    /// Component names of the model types above, keyed by type name; they differ for
+    /// models renamed with `schema_name` or `module_prefix`
+    pub component_names_fn: fn() -> Vec<(&'static str, String)>,
 }
 
 inventory::collect!(&'static RouteInfo);
 
-/// Schema information collected by api_model attribute
+impl RouteInfo {
+    /// The component schema name for one of this route's type names
+    pub fn component_name(&self, type_name: &str) -> String {
+        (self.component_names_fn)().into_iter()
+            .find(|(name, _)| *name == type_name)
+            .map(|(_, component)| component)
+            .unwrap_or_else(|| type_name.to_string())
+    }
+}
+
+/// Schema information collected by api_model attribute; see [`registry`] for listing and accessors
 pub struct SchemaInfo {
+    /// Component name, before any module prefix
     pub name: &'static str,
+    /// Module the model is defined in
+    pub module_path: &'static str,
+    /// `#[api_model(module_prefix)]`: the component name is prefixed with `module_path`
+    pub module_prefix: bool,
+    /// `#[api_model(strict_bodies)]`: unknown keys are rejected under [`HayaiApp::strict_bodies`]
+    pub strict_bodies: bool,
     pub schema_fn: fn() -> openapi::Schema,
     pub nested_fn: fn() -> std::collections::HashMap<String, openapi::Schema>,
+    /// What `#[validate]` and `#[schema]` record per property, as `schema_fn` applies it;
+    /// checked by [`lint`]
+    pub patches_fn: fn() -> std::collections::HashMap<String, openapi::PropertyPatch>,
 }
 
 inventory::collect!(SchemaInfo);
 
-/// A resolved route with runtime prefix and merged tags/security
-pub struct ResolvedRoute {
+impl SchemaInfo {
+    /// The name this schema is registered under in `components/schemas`
+    pub fn component_name(&self) -> String {
+        if self.module_prefix {
+            openapi::module_schema_name(self.module_path, self.name)
+        } else {
+            self.name.to_string()
+        }
+    }
+}
+
+/// Component schema name of a model, implemented by api_model.
+///
+/// The type name, unless renamed with `#[api_model(schema_name = "...")]` or
+/// `#[api_model(module_prefix)]`.
+pub trait ComponentName {
+    fn component_name() -> String;
+}
+
+/// Resolves a type in a route signature to its component name: the [`ComponentName`]
+/// impl for models, the plain type name for anything else (autoref specialization).
+#[doc(hidden)]
+pub mod __component {
+    pub struct Probe<T>(pub &'static str, pub std::marker::PhantomData<T>);
+
+    pub trait ViaModel {
+        fn component_name(&self) -> String;
+        /// The component, for types that have one
+        fn model_component(&self) -> Option<String>;
+    }
+
+    impl<T: super::ComponentName> ViaModel for &Probe<T> {
+        fn component_name(&self) -> String {
+            T::component_name()
+        }
+
+        fn model_component(&self) -> Option<String> {
+            Some(T::component_name())
+        }
+    }
+
+    pub trait ViaTypeName {
+        fn component_name(&self) -> String;
+        fn model_component(&self) -> Option<String>;
+    }
+
+    impl<T> ViaTypeName for Probe<T> {
+        fn component_name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn model_component(&self) -> Option<String> {
+            None
+        }
+    }
+}
+
+/// A resolved route with runtime prefix and merged tags/security
+pub struct ResolvedRoute {
     pub route_info: &'static RouteInfo,
     pub prefix: String,
     pub extra_tags: Vec<String>,
     pub extra_security: Vec<String>,
+    /// Error responses documented by the routers, as `(status, description)`
+    pub extra_responses: Vec<(u16, String)>,
 }
 
 impl ResolvedRoute {
     /// Full path = prefix + route's original path
     pub fn full_path(&self) -> String {
-        let base = self.route_info.path;
-        if self.prefix.is_empty() {
-            base.to_string()
-        } else {
-            format!("{}{}", self.prefix, base)
-        }
+        self.route_info.full_path(&self.prefix)
     }
 
     /// Full axum path = prefix + route's original axum_path
     pub fn full_axum_path(&self) -> String {
-        let base = self.route_info.axum_path;
-        if self.prefix.is_empty() {
-            base.to_string()
-        } else {
-            format!("{}{}", self.prefix, base)
-        }
+        registry::join_path(&self.prefix, self.route_info.axum_path)
     }
 
-    /// Merged tags: router-level + route-level
+    /// Merged tags: router-level + route-level, or only the route's with `#[tag(replace = "..")]`
     pub fn merged_tags(&self) -> Vec<String> {
-        let mut tags: Vec<String> = self.extra_tags.clone();
+        let mut tags: Vec<String> = if self.route_info.replaces_tags { Vec::new() } else { self.extra_tags.clone() };
         for t in self.route_info.tags {
             if !tags.contains(&t.to_string()) {
                 tags.push(t.to_string());
@@ -212,9 +1251,13 @@ impl ResolvedRoute {
         tags
     }
 
-    /// Merged security: router-level + route-level
+    /// Merged security: router-level + route-level, or only the route's with `#[security(none)]`
     pub fn merged_security(&self) -> Vec<&str> {
-        let mut sec: Vec<&str> = self.extra_security.iter().map(|s| s.as_str()).collect();
+        let mut sec: Vec<&str> = if self.route_info.clears_security {
+            Vec::new()
+        } else {
+            self.extra_security.iter().map(|s| s.as_str()).collect()
+        };
         for s in self.route_info.security {
             if !sec.contains(s) {
                 sec.push(s);
@@ -224,23 +1267,37 @@ impl ResolvedRoute {
     }
 }
 
-/// A FastAPI-style router with prefix, shared tags, security, deps, and nested routers
+/// A FastAPI-style router with prefix, shared tags, security, deps, and nested routers.
+///
+/// A route's tags and security add to those of every router above it, unless the handler
+/// opts out: `#[tag(replace = "..")]` drops the inherited tags and `#[security(none)]` the
+/// inherited security. The handler's own `#[security]` schemes and `Auth<V>` arguments always
+/// apply, and only `Auth<V>` is enforced at runtime.
 pub struct HayaiRouter {
     prefix: String,
     routes: Vec<&'static RouteInfo>,
     tags: Vec<String>,
     security: Vec<String>,
+    default_responses: Vec<(u16, String)>,
     deps: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
     children: Vec<HayaiRouter>,
 }
 
 impl HayaiRouter {
+    /// Leading and trailing slashes are optional and `""` or `"/"` mounts at the root.
+    ///
+    /// Panics if the prefix has an empty segment, such as `/api//v1`.
     pub fn new(prefix: &str) -> Self {
+        let trimmed = prefix.trim_matches('/');
+        if !trimmed.is_empty() && trimmed.split('/').any(str::is_empty) {
+            panic!("Router prefix {:?} has an empty segment", prefix);
+        }
         Self {
-            prefix: prefix.to_string(),
+            prefix: if trimmed.is_empty() { String::new() } else { format!("/{}", trimmed) },
             routes: Vec::new(),
             tags: Vec::new(),
             security: Vec::new(),
+            default_responses: Vec::new(),
             deps: HashMap::new(),
             children: Vec::new(),
         }
@@ -261,6 +1318,22 @@ impl HayaiRouter {
         self
     }
 
+    /// Document error responses on every route of this router and the routers it includes,
+    /// as `(status, description)` with the `ApiError` schema.
+    ///
+    /// A nested router's entry wins over its parent's for the same status, and
+    /// `#[responses(..)]` on a route wins over both.
+    ///
+    /// ```ignore
+    /// HayaiRouter::new("/admin").default_responses([(401, "Unauthorized"), (403, "Forbidden"), (429, "Too Many Requests")])
+    /// ```
+    pub fn default_responses<'a>(mut self, responses: impl IntoIterator<Item = (u16, &'a str)>) -> Self {
+        for (status, description) in responses {
+            merge_response(&mut self.default_responses, status, description);
+        }
+        self
+    }
+
     pub fn dep<T: 'static + Send + Sync>(mut self, dep: T) -> Self {
         self.deps.insert(TypeId::of::<T>(), Arc::new(dep));
         self
@@ -278,7 +1351,17 @@ impl HayaiRouter {
         parent_tags: &[String],
         parent_security: &[String],
     ) -> Vec<ResolvedRoute> {
-        let full_prefix = format!("{}{}", parent_prefix, self.prefix);
+        self.resolve_with_responses(parent_prefix, parent_tags, parent_security, &[])
+    }
+
+    fn resolve_with_responses(
+        &self,
+        parent_prefix: &str,
+        parent_tags: &[String],
+        parent_security: &[String],
+        parent_responses: &[(u16, String)],
+    ) -> Vec<ResolvedRoute> {
+        let full_prefix = registry::join_path(parent_prefix, &self.prefix);
         let mut merged_tags: Vec<String> = parent_tags.to_vec();
         for t in &self.tags {
             if !merged_tags.contains(t) {
@@ -291,6 +1374,10 @@ impl HayaiRouter {
                 merged_security.push(s.clone());
             }
         }
+        let mut merged_responses = parent_responses.to_vec();
+        for (status, description) in &self.default_responses {
+            merge_response(&mut merged_responses, *status, description);
+        }
 
         let mut resolved = Vec::new();
         for route in &self.routes {
@@ -299,10 +1386,11 @@ impl HayaiRouter {
                 prefix: full_prefix.clone(),
                 extra_tags: merged_tags.clone(),
                 extra_security: merged_security.clone(),
+                extra_responses: merged_responses.clone(),
             });
         }
         for child in &self.children {
-            resolved.extend(child.resolve(&full_prefix, &merged_tags, &merged_security));
+            resolved.extend(child.resolve_with_responses(&full_prefix, &merged_tags, &merged_security, &merged_responses));
         }
         resolved
     }
@@ -317,15 +1405,50 @@ impl HayaiRouter {
     }
 }
 
-/// Swagger UI serving mode
-#[derive(Debug, Clone)]
-pub enum SwaggerMode {
-    /// Load Swagger UI assets from a CDN URL
-    Cdn(String),
-    /// Use embedded Scalar API reference (works offline)
-    Embedded,
+/// Set the description documented for `status`, replacing an earlier one
+fn merge_response(responses: &mut Vec<(u16, String)>, status: u16, description: &str) {
+    responses.retain(|(s, _)| *s != status);
+    responses.push((status, description.to_string()));
+}
+
+/// Middleware for [`HayaiApp::auto_head`]`(false)`: axum answers HEAD on every GET route,
+/// so HEAD requests are refused unless the matched path has a `#[head]` handler or returns
+/// a [`RangedFile`](range::RangedFile). The 405 lists the path's other methods in `Allow`;
+/// routes hayai mounts itself only answer GET.
+async fn reject_head(route_methods: Arc<HashMap<String, BTreeSet<&'static str>>>, req: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    if req.method() != axum::http::Method::HEAD {
+        return next.run(req).await;
+    }
+    let Some(path) = req.extensions().get::<axum::extract::MatchedPath>() else {
+        return next.run(req).await;
+    };
+    let allow = match route_methods.get(path.as_str()) {
+        Some(methods) if methods.contains("HEAD") => return next.run(req).await,
+        Some(methods) => methods.iter().copied().collect::<Vec<_>>().join(", "),
+        None => "GET".to_string(),
+    };
+    let mut response = StatusCode::METHOD_NOT_ALLOWED.into_response();
+    if let Ok(allow) = axum::http::HeaderValue::from_str(&allow) {
+        response.headers_mut().insert(axum::http::header::ALLOW, allow);
+    }
+    response
+}
+
+/// Default for [`HayaiApp::max_query_length`]
+pub const DEFAULT_MAX_QUERY_LENGTH: usize = 16 * 1024;
+
+/// Middleware for [`HayaiApp::max_query_length`]: refuse query strings longer than `max`
+/// bytes with 414 before any extractor parses them.
+async fn limit_query_length(max: usize, req: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    if req.uri().query().is_some_and(|q| q.len() > max) {
+        return ApiError::uri_too_long(format!("Query string longer than {} bytes", max)).into_response();
+    }
+    next.run(req).await
 }
 
+/// The former name of [`docs::DocsAssets`]
+pub type SwaggerMode = docs::DocsAssets;
+
 /// The main application struct
 pub struct HayaiApp {
     deps: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
@@ -336,13 +1459,82 @@ pub struct HayaiApp {
     description: Option<String>,
     contact: Option<openapi::Contact>,
     license: Option<openapi::License>,
-    swagger_mode: SwaggerMode,
+    docs_assets: docs::DocsAssets,
     servers: Vec<openapi::Server>,
     security_schemes: HashMap<String, openapi::SecurityScheme>,
     routers: Vec<HayaiRouter>,
+    strict_bodies: bool,
+    auto_head: bool,
+    document_auto_head: bool,
+    minimal: bool,
+    json_config: JsonConfig,
+    enforce_casing: Option<Casing>,
+    encode_int64_as_string: bool,
+    strict_paths: bool,
+    strict_spec: bool,
+    strict_models: bool,
+    operation_id_style: operation_id::OperationIdStyle,
+    number_duplicate_operation_ids: bool,
+    validation_response: Option<(StatusCode, ValidationShaper)>,
+    message_catalog: Option<Arc<i18n::MessageCatalog>>,
+    locales: Option<locale::Locales>,
+    error_schemas: Vec<(u16, String)>,
+    error_components: HashMap<String, openapi::SchemaResult>,
+    startup: Vec<lifespan::Lifespan>,
+    shutdown: Vec<lifespan::Lifespan>,
+    /// Shared by the lifespan callbacks and the response hooks
+    shared: lifespan::LifespanSharedState,
+    response_hooks: Vec<events::ResponseHook>,
+    response_envelope: Option<envelope::EnvelopeConfig>,
+    audit_sink: Option<Arc<dyn audit::DynAuditSink>>,
+    audit_buffer: usize,
+    health_endpoints: bool,
+    health_checks: Vec<health::RegisteredCheck>,
+    health_timeout: std::time::Duration,
+    drain_endpoint: Option<(String, String)>,
+    drain_timeout: std::time::Duration,
+    maintenance: Option<maintenance::MaintenanceConfig>,
+    http_config: server::HttpConfig,
+    security_headers: Option<security_headers::SecurityHeaders>,
+    well_known: Option<well_known::WellKnown>,
+    reload_tasks: Vec<reload::WatchTask>,
+    debug_endpoints: bool,
+    allow_debug_in_release: bool,
+    docs: bool,
+    docs_prefix: String,
+    docs_customization: docs::DocsCustomization,
+    docs_html: Option<String>,
+    body_limit: Option<usize>,
+    max_query_length: usize,
+    bind_addr: Option<String>,
+    proxy: Option<proxy::ProxyConfig>,
+    accepted_encodings: Vec<decompression::Encoding>,
+    access_log: Option<access_log::AccessLog>,
+    transaction: Option<transaction::TransactionSetup>,
+    default_concurrency_limit: Option<concurrency::ConcurrencyLimit>,
+    response_cache: Option<Arc<dyn cache::DynResponseCache>>,
+    default_timeout: Option<std::time::Duration>,
+    default_deadline: std::time::Duration,
+    feature_flags: Option<feature_flags::FeatureFlags>,
+    disabled_route: feature_flags::DisabledRoute,
+    /// Begun when [`serve_with_shutdown`](HayaiApp::serve_with_shutdown)'s signal completes
+    shutdown_signal: Arc<deadline::ShutdownSignal>,
+    detect_blocking: Option<std::time::Duration>,
+    tenant_resolver: Option<Arc<dyn tenant::DynTenantResolver>>,
+    tenant_deps: HashMap<TypeId, tenant::TenantDep>,
+    warmup: Option<warmup::WarmupPlan>,
+    worker_start: Vec<runtime::WorkerStartHook>,
+    runtime: runtime::RuntimeConfig,
+    parent_loaders: HashMap<TypeId, parent::ParentLoader>,
+    header_versioning: Option<versioning::HeaderVersioning>,
+    example_placeholder: openapi::ExamplePlaceholder,
+    reuse_request_bodies: bool,
+    global_response_headers: Vec<(String, openapi::ResponseHeader)>,
+    global_responses: Vec<(u16, String)>,
 }
 
 impl HayaiApp {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
             deps: HashMap::new(),
@@ -352,13 +1544,88 @@ impl HayaiApp {
             description: None,
             contact: None,
             license: None,
-            swagger_mode: SwaggerMode::Embedded,
+            docs_assets: docs::DocsAssets::default(),
             servers: Vec::new(),
             security_schemes: HashMap::new(),
             routers: Vec::new(),
+            strict_bodies: false,
+            auto_head: true,
+            document_auto_head: false,
+            minimal: false,
+            json_config: JsonConfig::default(),
+            enforce_casing: None,
+            encode_int64_as_string: false,
+            strict_paths: false,
+            strict_spec: false,
+            strict_models: false,
+            operation_id_style: operation_id::OperationIdStyle::default(),
+            number_duplicate_operation_ids: false,
+            validation_response: None,
+            message_catalog: None,
+            locales: None,
+            error_schemas: Vec::new(),
+            error_components: HashMap::new(),
+            startup: Vec::new(),
+            shutdown: Vec::new(),
+            shared: lifespan::LifespanSharedState::default(),
+            response_hooks: Vec::new(),
+            response_envelope: None,
+            audit_sink: None,
+            audit_buffer: audit::DEFAULT_AUDIT_BUFFER,
+            health_endpoints: false,
+            health_checks: Vec::new(),
+            health_timeout: std::time::Duration::from_secs(2),
+            drain_endpoint: None,
+            drain_timeout: drain::DEFAULT_DRAIN_TIMEOUT,
+            maintenance: None,
+            http_config: server::HttpConfig::default(),
+            security_headers: None,
+            well_known: None,
+            reload_tasks: Vec::new(),
+            debug_endpoints: false,
+            allow_debug_in_release: false,
+            docs: true,
+            docs_prefix: String::new(),
+            docs_customization: docs::DocsCustomization::default(),
+            docs_html: None,
+            body_limit: None,
+            max_query_length: DEFAULT_MAX_QUERY_LENGTH,
+            bind_addr: None,
+            proxy: None,
+            accepted_encodings: Vec::new(),
+            access_log: None,
+            transaction: None,
+            default_concurrency_limit: None,
+            response_cache: None,
+            default_timeout: None,
+            default_deadline: deadline::DEFAULT_DEADLINE,
+            feature_flags: None,
+            disabled_route: feature_flags::DisabledRoute::NotFound,
+            shutdown_signal: Arc::default(),
+            detect_blocking: None,
+            tenant_resolver: None,
+            tenant_deps: HashMap::new(),
+            warmup: None,
+            worker_start: Vec::new(),
+            runtime: runtime::RuntimeConfig::default(),
+            parent_loaders: HashMap::new(),
+            header_versioning: None,
+            example_placeholder: openapi::default_placeholder,
+            reuse_request_bodies: false,
+            global_response_headers: Vec::new(),
+            global_responses: vec![(500, openapi::status_description(500).to_string())],
         }
     }
 
+    /// An app without `/docs`, `/openapi.json` or OpenAPI spec assembly.
+    ///
+    /// Routing, validation, deps and auth work as usual; no schema is ever generated.
+    /// Combine with [`route_explicit`](HayaiApp::route_explicit) to avoid relying on
+    /// inventory route collection.
+    pub fn minimal() -> Self {
+        Self { minimal: true, ..Self::new() }
+    }
+
     pub fn title(mut self, title: &str) -> Self {
         self.title = title.to_string();
         self
@@ -391,14 +1658,19 @@ impl HayaiApp {
         self
     }
 
-    pub fn swagger_mode(mut self, mode: SwaggerMode) -> Self {
-        self.swagger_mode = mode;
+    /// Load the docs UI from the binary or a CDN (default embedded, with the `embedded-docs`
+    /// feature); see [`docs`]
+    pub fn docs_assets(mut self, assets: docs::DocsAssets) -> Self {
+        self.docs_assets = assets;
         self
     }
 
-    pub fn swagger_cdn(mut self, url: &str) -> Self {
-        self.swagger_mode = SwaggerMode::Cdn(url.to_string());
-        self
+    pub fn swagger_mode(self, mode: SwaggerMode) -> Self {
+        self.docs_assets(mode)
+    }
+
+    pub fn swagger_cdn(self, url: &str) -> Self {
+        self.docs_assets(docs::DocsAssets::Cdn(url.to_string()))
     }
 
     pub fn dep<T: 'static + Send + Sync>(mut self, dep: T) -> Self {
@@ -406,6 +1678,402 @@ impl HayaiApp {
         self
     }
 
+    /// Limit concurrent requests to every route without its own `#[concurrency_limit]`,
+    /// counted per route; see [`concurrency`]
+    pub fn default_concurrency_limit(mut self, limit: concurrency::ConcurrencyLimit) -> Self {
+        self.default_concurrency_limit = Some(limit);
+        self
+    }
+
+    /// Answer requests to routes without `#[timeout]` with 504 once `timeout` passes; see
+    /// [`timeout`]
+    pub fn default_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// The longest budget a [`Deadline`](deadline::Deadline) reports, before the route's
+    /// timeout and the client's `X-Request-Timeout` shorten it (default 30s)
+    pub fn default_deadline(mut self, budget: std::time::Duration) -> Self {
+        self.default_deadline = budget;
+        self
+    }
+
+    /// Log a warning from the `hayai::blocking` target whenever a single poll of a handler
+    /// takes longer than `threshold`, the sign of blocking calls in async code; see
+    /// [`blocking`](mod@blocking). Meant for development, as every poll is timed.
+    pub fn detect_blocking(mut self, threshold: std::time::Duration) -> Self {
+        self.detect_blocking = Some(threshold);
+        self
+    }
+
+    /// Resolve the [`Tenant`](tenant::Tenant) of every request with `resolver` before it
+    /// reaches a route, answering with the resolver's error when it fails; see [`tenant`].
+    /// A header-based resolver's header is documented on every operation.
+    pub fn tenant_resolver(mut self, resolver: impl tenant::TenantResolver) -> Self {
+        self.tenant_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Negotiate the API version of `#[version(N)]` routes from the `Accept` header; see
+    /// [`versioning`]
+    pub fn header_versioning(mut self, versioning: versioning::HeaderVersioning) -> Self {
+        self.header_versioning = Some(versioning);
+        self
+    }
+
+    /// Build `Dep<TenantScoped<T>>` with `factory`, once per tenant; the instances are kept
+    /// for the life of the app
+    pub fn tenant_scoped<T, F, Fut>(mut self, factory: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(tenant::Tenant) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<T, ApiError>> + Send + 'static,
+    {
+        self.tenant_deps.insert(TypeId::of::<T>(), tenant::TenantDep::new(factory));
+        self
+    }
+
+    /// Load `Parent<T>` arguments from the `param` path parameter with `loader`; its error
+    /// is the response when the parent can't be loaded. See [`parent`].
+    pub fn parent_loader<T, K, F, Fut>(mut self, param: &str, loader: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        K: std::str::FromStr + Send + 'static,
+        F: Fn(K, DepResolver<'static>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<T, ApiError>> + Send + 'static,
+    {
+        self.parent_loaders.insert(TypeId::of::<T>(), parent::ParentLoader::new(param, loader));
+        self
+    }
+
+    /// Store `#[cache]`d responses in `cache` instead of a [`cache::MemoryCache`] of
+    /// [`cache::DEFAULT_CAPACITY`] entries
+    pub fn response_cache(mut self, cache: impl cache::ResponseCache) -> Self {
+        self.response_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Run every route in a transaction begun by the `D` dependency, except those marked
+    /// `#[transactional(skip)]`; see [`transaction`]. `D` must be registered with
+    /// [`dep`](HayaiApp::dep).
+    pub fn transactional<D: transaction::RequestTransaction>(mut self) -> Self {
+        self.transaction = Some(transaction::TransactionSetup::new::<D>(transaction::TransactionScope::AllRoutes));
+        self
+    }
+
+    /// Like [`transactional`](HayaiApp::transactional), but only for routes marked
+    /// `#[transactional]`
+    pub fn transactional_annotated<D: transaction::RequestTransaction>(mut self) -> Self {
+        self.transaction = Some(transaction::TransactionSetup::new::<D>(transaction::TransactionScope::Annotated));
+        self
+    }
+
+    /// Register an outgoing [`HttpClient`] as `Dep<HttpClient>`, see [`http_client`].
+    ///
+    /// Panics if the client can't be built.
+    #[cfg(feature = "http-client")]
+    pub fn http_client(self, config: HttpClientConfig) -> Self {
+        let client = HttpClient::new(config).unwrap_or_else(|e| panic!("Failed to build HTTP client: {}", e));
+        self.dep(client)
+    }
+
+    /// Register an outgoing client under `name`, resolved with `Dep<HttpClients>` and
+    /// [`HttpClients::get`]
+    ///
+    /// Panics if the client can't be built.
+    #[cfg(feature = "http-client")]
+    pub fn http_client_named(self, name: &str, config: HttpClientConfig) -> Self {
+        let client = HttpClient::new(config).unwrap_or_else(|e| panic!("Failed to build HTTP client {:?}: {}", name, e));
+        let mut clients = self.deps.get(&TypeId::of::<HttpClients>())
+            .and_then(|dep| dep.downcast_ref::<HttpClients>())
+            .cloned()
+            .unwrap_or_default();
+        clients.insert(name, client);
+        self.dep(clients)
+    }
+
+    /// Load `T` from `HAYAI_*` environment variables and register it as a `Dep<T>`.
+    ///
+    /// The well-known [`HayaiConfig`](config::HayaiConfig) keys (`HAYAI_BIND`,
+    /// `HAYAI_BODY_LIMIT`, `HAYAI_DOCS`) configure the app itself. The error names the
+    /// variable that is missing or failed to parse.
+    ///
+    /// ```ignore
+    /// let app = HayaiApp::new().config_from_env::<AppConfig>()?;
+    /// app.serve_from_config().await;
+    /// ```
+    pub fn config_from_env<T>(self) -> Result<Self, config::ConfigError>
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        self.config::<T>(config::ConfigSource::default())
+    }
+
+    /// Like [`config_from_env`](HayaiApp::config_from_env) with a custom prefix and/or file
+    pub fn config<T>(mut self, source: config::ConfigSource) -> Result<Self, config::ConfigError>
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let settings: config::HayaiConfig = source.load()?;
+        let value: T = source.load()?;
+        self.docs = settings.docs;
+        self.body_limit = settings.body_limit.or(self.body_limit);
+        self.bind_addr = Some(settings.bind);
+        Ok(self.dep(value))
+    }
+
+    /// Register `initial` as a `Dep<Reloadable<T>>` replaced by each value `watcher`
+    /// produces while the server runs; values that fail to load or, with
+    /// [`ConfigWatcher::validated`](reload::ConfigWatcher::validated), to validate are
+    /// logged and skipped. The watcher starts in `into_router`, which must run inside a
+    /// Tokio runtime, and stops once shutdown begins or the router is dropped. See
+    /// [`reload`].
+    pub fn reloadable_config<T>(mut self, initial: T, watcher: impl reload::ConfigWatcher<T>) -> Self
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let config = reload::Reloadable::new(initial);
+        self.deps.insert(TypeId::of::<reload::Reloadable<T>>(), Arc::new(config.clone()));
+        self.reload_tasks.push(Box::pin(reload::watch(config, watcher)));
+        self
+    }
+
+    /// Take the scheme, host and client address from the `X-Forwarded-*` headers set by
+    /// trusted reverse proxies, see [`proxy`].
+    pub fn behind_proxy(mut self, config: proxy::ProxyConfig) -> Self {
+        self.proxy = Some(config);
+        self
+    }
+
+    /// Serve `/docs` and `/openapi.json` (default `true`); never served without the
+    /// `openapi` feature
+    pub fn docs(mut self, enabled: bool) -> Self {
+        self.docs = enabled;
+        self
+    }
+
+    /// Serve `/docs` and `/openapi.json` below `prefix`, e.g. `/api` for `/api/docs`
+    pub fn docs_prefix(mut self, prefix: &str) -> Self {
+        let trimmed = prefix.trim_matches('/');
+        self.docs_prefix = if trimmed.is_empty() { String::new() } else { format!("/{}", trimmed) };
+        self
+    }
+
+    /// Add a title, logo, stylesheet, scripts or raw `<head>` HTML to the generated docs
+    /// page, see [`docs`]
+    pub fn docs_customization(mut self, customization: docs::DocsCustomization) -> Self {
+        self.docs_customization = customization;
+        self
+    }
+
+    /// Serve `template` at `/docs` instead of the generated page; `{{SPEC_URL}}` is
+    /// replaced with the URL of the OpenAPI document
+    pub fn docs_html(mut self, template: impl Into<String>) -> Self {
+        self.docs_html = Some(template.into());
+        self
+    }
+
+    /// Reject request bodies larger than `bytes` with 413 (axum's default is 2 MB)
+    pub fn body_limit(mut self, bytes: usize) -> Self {
+        self.body_limit = Some(bytes);
+        self
+    }
+
+    /// Reject query strings longer than `bytes` with 414 (default
+    /// [`DEFAULT_MAX_QUERY_LENGTH`])
+    pub fn max_query_length(mut self, bytes: usize) -> Self {
+        self.max_query_length = bytes;
+        self
+    }
+
+    /// Inflate request bodies sent with one of these `Content-Encoding`s before extraction,
+    /// see [`decompression`]. Other encodings get 415; a body that inflates past the body
+    /// limit gets 413.
+    pub fn accept_compressed_bodies(mut self, encodings: &[decompression::Encoding]) -> Self {
+        self.accepted_encodings = encodings.to_vec();
+        self
+    }
+
+    /// Document a header sent with every success response, e.g. one added by a gateway.
+    /// `schema_type` is `string`, `integer`, `number` or `boolean`; routes document their
+    /// own headers with `#[response_header(..)]`.
+    pub fn global_response_header(mut self, name: &str, schema_type: &str, description: &str) -> Self {
+        self.global_response_headers.push((name.to_string(), openapi::ResponseHeader::new(schema_type, description)));
+        self
+    }
+
+    /// Error responses documented on every operation, as `(status, description)` with the
+    /// `ApiError` schema. Replaces the default, which documents only 500.
+    ///
+    /// [`HayaiRouter::default_responses`] and `#[responses(..)]` win for the same status.
+    pub fn global_responses<'a>(mut self, responses: impl IntoIterator<Item = (u16, &'a str)>) -> Self {
+        self.global_responses.clear();
+        for (status, description) in responses {
+            merge_response(&mut self.global_responses, status, description);
+        }
+        self
+    }
+
+    /// Values for request body example fields that have no example or default (the
+    /// default placeholders satisfy the field's format and length constraints)
+    ///
+    /// ```ignore
+    /// app.example_placeholders(|prop| match prop.type_name.as_str() {
+    ///     "string" => json!("lorem ipsum"),
+    ///     _ => openapi::default_placeholder(prop),
+    /// })
+    /// ```
+    pub fn example_placeholders(mut self, placeholder: openapi::ExamplePlaceholder) -> Self {
+        self.example_placeholder = placeholder;
+        self
+    }
+
+    /// Document each model request body once under `components.requestBodies`, named after
+    /// its schema (`#/components/requestBodies/CreateUser`), instead of inline in every
+    /// operation. Bodies of other types stay inline unless the route names them with
+    /// `#[request_body(name = "..")]`.
+    ///
+    /// The same name must always describe the same body; a model sent with two media types
+    /// fails at spec build and needs a `#[request_body(name = "..")]` for one of them.
+    pub fn reuse_request_bodies(mut self, enabled: bool) -> Self {
+        self.reuse_request_bodies = enabled;
+        self
+    }
+
+    /// Log one line per request in `format`, see [`access_log`]
+    pub fn access_log(mut self, format: access_log::AccessLogFormat) -> Self {
+        match &mut self.access_log {
+            Some(log) => log.format = format,
+            None => self.access_log = Some(access_log::AccessLog::new(format)),
+        }
+        self
+    }
+
+    /// Write access log lines to `writer` instead of the `hayai::access` tracing target.
+    /// Enables JSON access logging if no format was chosen.
+    pub fn access_log_writer(mut self, writer: impl std::io::Write + Send + 'static) -> Self {
+        self.access_log.get_or_insert_with(|| access_log::AccessLog::new(access_log::AccessLogFormat::Json))
+            .set_writer(writer);
+        self
+    }
+
+    /// Include JSON request bodies in access log lines, with `#[schema(sensitive)]` fields
+    /// redacted; see [`access_log`]. Enables JSON access logging if no format was chosen.
+    pub fn log_bodies(mut self, bodies: access_log::LogBodies) -> Self {
+        self.access_log.get_or_insert_with(|| access_log::AccessLog::new(access_log::AccessLogFormat::Json))
+            .bodies = bodies;
+        self
+    }
+
+    /// Leave bodies longer than `bytes` out of the access log instead of
+    /// [`access_log::DEFAULT_LOGGED_BODY_LIMIT`]
+    pub fn log_bodies_limit(mut self, bytes: usize) -> Self {
+        self.access_log.get_or_insert_with(|| access_log::AccessLog::new(access_log::AccessLogFormat::Json))
+            .body_limit = bytes;
+        self
+    }
+
+    /// Don't log requests to these paths or route templates, e.g. `&["/healthz", "/readyz"]`
+    pub fn access_log_exclude(mut self, paths: &[&str]) -> Self {
+        self.access_log.get_or_insert_with(|| access_log::AccessLog::new(access_log::AccessLogFormat::Json))
+            .excluded.extend(paths.iter().map(|p| p.to_string()));
+        self
+    }
+
+    /// Register a dependency whose [`HealthCheck`](health::HealthCheck) feeds `/readyz`
+    pub fn dep_with_health<T: health::HealthCheck>(mut self, dep: T) -> Self {
+        let dep = Arc::new(dep);
+        self.deps.insert(TypeId::of::<T>(), dep.clone());
+        self.health_checks.push(health::RegisteredCheck::new(dep));
+        self
+    }
+
+    /// Serve `/healthz` (liveness: always 200 while the server runs) and `/readyz`
+    /// (readiness: 200, or 503 naming each dependency whose health check failed)
+    pub fn health_endpoints(mut self) -> Self {
+        self.health_endpoints = true;
+        self
+    }
+
+    /// How long each readiness check may take before it counts as failed (default 2s)
+    pub fn health_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.health_timeout = timeout;
+        self
+    }
+
+    /// Serve a drain endpoint at `path`: a POST with `Authorization: Bearer <token>` makes
+    /// `/readyz` answer 503 while requests keep being served, and a second POST undoes it.
+    /// Handlers can watch for it with the [`Draining`](drain::Draining) extractor.
+    pub fn drain_endpoint(mut self, path: &str, token: impl Into<String>) -> Self {
+        self.drain_endpoint = Some((path.to_string(), token.into()));
+        self
+    }
+
+    /// Switch `#[feature_flag("..")]` routes on and off with `provider`, which handlers can
+    /// also consult through `Dep<FeatureFlags>`; see [`feature_flags`]
+    pub fn feature_flags(mut self, provider: impl feature_flags::FlagProvider) -> Self {
+        let flags = feature_flags::FeatureFlags(Arc::new(provider));
+        self.deps.insert(TypeId::of::<feature_flags::FeatureFlags>(), Arc::new(flags.clone()));
+        self.feature_flags = Some(flags);
+        self
+    }
+
+    /// What routes answer while their feature flag is off (default an empty 404)
+    pub fn disabled_route(mut self, response: feature_flags::DisabledRoute) -> Self {
+        self.disabled_route = response;
+        self
+    }
+
+    /// Serve `/robots.txt`, `/.well-known/security.txt` and other discovery documents,
+    /// kept out of the spec and the access log; see [`well_known`]
+    pub fn well_known(mut self, well_known: well_known::WellKnown) -> Self {
+        self.well_known = Some(well_known);
+        self
+    }
+
+    /// Serve `/_debug/echo`, answering with the request as received (credentials redacted),
+    /// and `/_debug/routes`, answering with the route table; kept out of the spec. Release
+    /// builds panic in `into_router` unless allowed, see [`debug`].
+    pub fn debug_endpoints(mut self, enabled: bool) -> Self {
+        self.debug_endpoints = enabled;
+        self
+    }
+
+    /// Let [`debug_endpoints`](HayaiApp::debug_endpoints) be served by a release build,
+    /// e.g. a staging deployment, without setting [`debug::ALLOW_ENV_VAR`]
+    pub fn allow_debug_in_release(mut self) -> Self {
+        self.allow_debug_in_release = true;
+        self
+    }
+
+    /// How long draining lasts unless turned off by a second POST (default 5 minutes)
+    pub fn drain_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Answer 503 with `Retry-After` while `config.enabled_flag` is set, except on the
+    /// health and drain endpoints and `config.allow_paths`; see [`maintenance`].
+    pub fn maintenance(mut self, config: maintenance::MaintenanceConfig) -> Self {
+        self.maintenance = Some(config);
+        self
+    }
+
+    /// Connection timeouts and the connection cap applied by [`serve`](HayaiApp::serve);
+    /// see [`server`] for the defaults
+    pub fn http_config(mut self, config: server::HttpConfig) -> Self {
+        self.http_config = config;
+        self
+    }
+
+    /// Send `config`'s security headers on every response, with HSTS only over HTTPS and
+    /// the Content-Security-Policy only on HTML; see [`security_headers`].
+    pub fn security_headers(mut self, config: security_headers::SecurityHeaders) -> Self {
+        self.security_headers = Some(config);
+        self
+    }
+
     /// Override a dependency for testing.
     ///
     /// This allows replacing registered dependencies with mock values during testing.
@@ -463,19 +2131,482 @@ impl HayaiApp {
         self
     }
 
-    pub fn bearer_auth(self) -> Self {
-        self.security_scheme("bearerAuth", openapi::SecurityScheme {
-            scheme_type: "http".to_string(),
-            scheme: Some("bearer".to_string()),
-            bearer_format: None,
-            name: None,
-            location: None,
-        })
+    pub fn bearer_auth(self) -> Self {
+        self.security_scheme("bearerAuth", openapi::SecurityScheme {
+            scheme_type: "http".to_string(),
+            scheme: Some("bearer".to_string()),
+            bearer_format: None,
+            name: None,
+            location: None,
+        })
+    }
+
+    /// API keys sent in the `header` request header, referenced as
+    /// [`security::API_KEY`](crate::security::API_KEY)
+    pub fn api_key_auth(self, header: &str) -> Self {
+        self.security_scheme("apiKeyAuth", openapi::SecurityScheme {
+            scheme_type: "apiKey".to_string(),
+            scheme: None,
+            bearer_format: None,
+            name: Some(header.to_string()),
+            location: Some("header".to_string()),
+        })
+    }
+
+    pub fn include(mut self, router: HayaiRouter) -> Self {
+        self.routers.push(router);
+        self
+    }
+
+    /// Register a single route at the root, e.g. `route_explicit(__HAYAI_ROUTE_GET_USER)`.
+    ///
+    /// Like [`include`](HayaiApp::include), this switches the app from collecting every
+    /// `#[get]`/`#[post]`/... route in the binary to serving only the registered ones.
+    pub fn route_explicit(self, route: &'static RouteInfo) -> Self {
+        self.include(HayaiRouter::new("").route(route))
+    }
+
+    /// Reject unknown top-level fields with a 422 in JSON request bodies of models marked
+    /// `#[api_model(strict_bodies)]`, whose component schemas are documented with
+    /// `additionalProperties: false` as well.
+    ///
+    /// Unlike `#[api_model(deny_unknown_fields)]`, the models only opt in: the app decides,
+    /// e.g. to be strict in staging before it is in production.
+    pub fn strict_bodies(mut self, strict: bool) -> Self {
+        self.strict_bodies = strict;
+        self
+    }
+
+    /// Answer HEAD requests on GET routes; on by default.
+    ///
+    /// axum already does this for every GET route: the GET handler runs and its headers,
+    /// including `Content-Length`, are returned with an empty body. So only
+    /// `auto_head(false)` changes anything: HEAD requests then get a 405 with an `Allow`
+    /// header unless the path has a `#[head]` handler, which takes precedence over the GET
+    /// one either way, or returns a [`RangedFile`](range::RangedFile), which always answers
+    /// HEAD.
+    pub fn auto_head(mut self, enabled: bool) -> Self {
+        self.auto_head = enabled;
+        self
+    }
+
+    /// Add a `head` operation next to every GET operation in the OpenAPI spec.
+    pub fn document_auto_head(mut self, enabled: bool) -> Self {
+        self.document_auto_head = enabled;
+        self
+    }
+
+    /// Replace the body (and optionally the status) of validation failure responses.
+    ///
+    /// `shaper` receives the structured errors; the documented response for that status
+    /// becomes a generic object instead of `ApiError`.
+    ///
+    /// ```ignore
+    /// app.validation_response(StatusCode::UNPROCESSABLE_ENTITY, |errors| {
+    ///     let mut by_field = serde_json::Map::new();
+    ///     for e in errors {
+    ///         by_field.entry(e.field).or_insert_with(|| json!([])).as_array_mut().unwrap().push(e.message.into());
+    ///     }
+    ///     json!({ "errors": by_field })
+    /// })
+    /// ```
+    pub fn validation_response(mut self, status: StatusCode, shaper: ValidationShaper) -> Self {
+        self.validation_response = Some((status, shaper));
+        self
+    }
+
+    /// Localize error messages by the request's `Accept-Language`, see [`i18n`].
+    ///
+    /// ```ignore
+    /// app.message_catalog(MessageCatalog::new()
+    ///     .message("fr", "validation_failed", "La validation a échoué")
+    ///     .message("fr", "min_length", "doit contenir au moins {min} caractères"))
+    /// ```
+    pub fn message_catalog(mut self, catalog: i18n::MessageCatalog) -> Self {
+        self.message_catalog = Some(Arc::new(catalog));
+        self
+    }
+
+    /// Serve each request in one of `supported`, the best match for its `Accept-Language`
+    /// or `default`; handlers read it as a [`Locale`](locale::Locale), see [`locale`].
+    ///
+    /// ```ignore
+    /// app.locales(&["en", "fr", "ja"], "en")
+    /// ```
+    pub fn locales(mut self, supported: &[&str], default: &str) -> Self {
+        let mut supported: Vec<String> = supported.iter().map(|tag| tag.to_string()).collect();
+        if !supported.iter().any(|tag| tag.eq_ignore_ascii_case(default)) {
+            supported.push(default.to_string());
+        }
+        let (query, cookie) = self.locales.take().map_or((None, None), |l| (l.query, l.cookie));
+        self.locales = Some(locale::Locales { supported, default: default.to_string(), query, cookie });
+        self
+    }
+
+    /// Let the `name` query parameter choose the locale over `Accept-Language`, e.g.
+    /// `?lang=fr`; needs [`locales`](HayaiApp::locales)
+    pub fn locale_query(mut self, name: &str) -> Self {
+        self.locales.as_mut().expect("locale_query needs HayaiApp::locales first").query = Some(name.to_string());
+        self
+    }
+
+    /// Let the `name` cookie choose the locale over `Accept-Language`, after
+    /// [`locale_query`](HayaiApp::locale_query); needs [`locales`](HayaiApp::locales)
+    pub fn locale_cookie(mut self, name: &str) -> Self {
+        self.locales.as_mut().expect("locale_cookie needs HayaiApp::locales first").cookie = Some(name.to_string());
+        self
+    }
+
+    /// Document `status` error responses with a named component instead of `ApiError`.
+    ///
+    /// Applies to every operation that documents that status; `#[responses(..)]` on a
+    /// route takes precedence.
+    ///
+    /// ```ignore
+    /// app.error_schema(401, "UnauthorizedError", openapi::schema_of::<AuthError>())
+    /// ```
+    pub fn error_schema(mut self, status: u16, name: impl Into<String>, schema: impl Into<openapi::SchemaResult>) -> Self {
+        let name = name.into();
+        self.error_schemas.retain(|(s, _)| *s != status);
+        self.error_schemas.push((status, name.clone()));
+        self.error_components.insert(name, schema.into());
+        self
+    }
+
+    /// Run a callback before the server accepts connections.
+    ///
+    /// Startup callbacks run sequentially in registration order and share one
+    /// [`LifespanSharedState`](lifespan::LifespanSharedState); values inserted into it are
+    /// also registered as `Dep<T>` unless a dependency of that type already exists. The
+    /// first failure aborts [`serve_with_shutdown`](HayaiApp::serve_with_shutdown).
+    /// Use [`Lifespan::named`](lifespan::Lifespan::named) to name a callback in logs and errors.
+    pub fn on_startup(mut self, callback: impl Into<lifespan::Lifespan>) -> Self {
+        self.startup.push(callback.into());
+        self
+    }
+
+    /// Send `plan`'s requests through the app after the startup callbacks and before
+    /// listening; the first unexpected status aborts
+    /// [`serve_with_shutdown`](HayaiApp::serve_with_shutdown). See [`warmup`].
+    pub fn warmup(mut self, plan: warmup::WarmupPlan) -> Self {
+        self.warmup = Some(plan);
+        self
+    }
+
+    /// Run `hook` once on the serving runtime, after the startup callbacks and warmup and
+    /// right before connections are accepted; hooks run in registration order.
+    ///
+    /// Unlike [`on_startup`](HayaiApp::on_startup) callbacks it gets the runtime itself, for
+    /// what has to live there: runtime metrics collectors, background tasks that run for
+    /// as long as the server does. See [`runtime`].
+    pub fn on_worker_start(mut self, hook: impl FnOnce(&tokio::runtime::Handle) + Send + 'static) -> Self {
+        self.worker_start.push(Box::new(hook));
+        self
+    }
+
+    /// The worker threads, blocking pool and thread names of the runtime
+    /// [`serve_blocking`](HayaiApp::serve_blocking) builds; see [`runtime`]
+    pub fn runtime(mut self, config: runtime::RuntimeConfig) -> Self {
+        self.runtime = config;
+        self
+    }
+
+    /// Run a callback after the server has stopped.
+    ///
+    /// Shutdown callbacks run in reverse registration order with the same shared state
+    /// as startup; a failing callback doesn't stop the ones after it.
+    pub fn on_shutdown(mut self, callback: impl Into<lifespan::Lifespan>) -> Self {
+        self.shutdown.push(callback.into());
+        self
+    }
+
+    /// Wrap the 2xx JSON responses of every route not marked `#[no_envelope]` as
+    /// `{"data": .., "meta": {"request_id": ..}}`, and document them wrapped; see [`envelope`].
+    ///
+    /// ```ignore
+    /// app.response_envelope(EnvelopeConfig::default())
+    /// ```
+    pub fn response_envelope(mut self, config: envelope::EnvelopeConfig) -> Self {
+        self.response_envelope = Some(config);
+        self
+    }
+
+    /// Run `hook` after each 2xx response of a route marked `#[emit_event(..)]`, see [`events`].
+    ///
+    /// The hook gets the lifespan shared state, so it can reach a broker connected at
+    /// startup. It runs in a spawned task; errors and panics are only logged.
+    pub fn on_response<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&events::ResponseMeta, &lifespan::LifespanSharedState) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<(), lifespan::BoxError>> + Send + 'static,
+    {
+        self.response_hooks.push(Arc::new(move |meta, shared| Box::pin(hook(meta, shared))));
+        self
+    }
+
+    /// Record every response of a route marked `#[audited(..)]` with `sink`, off the
+    /// request path; see [`audit`]
+    pub fn audit_sink(mut self, sink: impl audit::AuditSink) -> Self {
+        self.audit_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// How many audit events may wait for the sink before new ones are dropped
+    /// (default [`audit::DEFAULT_AUDIT_BUFFER`])
+    pub fn audit_buffer(mut self, capacity: usize) -> Self {
+        self.audit_buffer = capacity;
+        self
+    }
+
+    /// Configure how JSON responses are formatted.
+    pub fn json_config(mut self, config: JsonConfig) -> Self {
+        self.json_config = config;
+        self
+    }
+
+    /// Require every registered component schema's property names to follow `casing`.
+    ///
+    /// Checked in [`into_router`](HayaiApp::into_router), which panics listing each
+    /// offending `Schema.property`. Use `#[api_model(rename_all = "...")]` to fix a model.
+    pub fn enforce_casing(mut self, casing: Casing) -> Self {
+        self.enforce_casing = Some(casing);
+        self
+    }
+
+    /// Require 64-bit integer properties to be sent as strings, since JavaScript clients
+    /// lose precision above 2^53.
+    ///
+    /// Checked when the spec is built, which panics listing each `Schema.property` still
+    /// documented as a raw `int64`/`uint64`. Mark those fields `#[schema(string_encoded)]`.
+    pub fn encode_int64_as_string(mut self, enabled: bool) -> Self {
+        self.encode_int64_as_string = enabled;
+        self
+    }
+
+    /// Fail instead of warn when a composed route path repeats a segment, which usually
+    /// means a prefix was given both to a router and to the router including it.
+    ///
+    /// Checked in [`into_router`](HayaiApp::into_router); see
+    /// [`repeated_segments`](HayaiApp::repeated_segments).
+    pub fn strict_paths(mut self, enabled: bool) -> Self {
+        self.strict_paths = enabled;
+        self
+    }
+
+    /// Fail instead of warn when the generated OpenAPI document has structural problems,
+    /// such as a `$ref` to a model that isn't registered.
+    ///
+    /// Checked in [`into_router`](HayaiApp::into_router); see [`openapi::validate_spec`].
+    pub fn strict_spec(mut self, enabled: bool) -> Self {
+        self.strict_spec = enabled;
+        self
+    }
+
+    /// Fail instead of warn when a model's `#[validate]` or `#[schema]` attribute doesn't
+    /// apply to its field, such as `min_items` on a `String`.
+    ///
+    /// Checked in [`into_router`](HayaiApp::into_router), also for apps without docs; see
+    /// [`lint::check_models`].
+    pub fn strict_models(mut self, enabled: bool) -> Self {
+        self.strict_models = enabled;
+        self
+    }
+
+    /// Derive the `operationId` of routes without `#[operation_id]` in `style`; see
+    /// [`operation_id`].
+    pub fn operation_id_style(mut self, style: operation_id::OperationIdStyle) -> Self {
+        self.operation_id_style = style;
+        self
+    }
+
+    /// Number handlers that would share an `operationId` in path order, `getUsers2`,
+    /// instead of failing; renaming one with `#[operation_id]` is usually better.
+    pub fn number_duplicate_operation_ids(mut self, enabled: bool) -> Self {
+        self.number_duplicate_operation_ids = enabled;
+        self
+    }
+
+    /// Routes of included routers whose full path has the same literal segment twice in a
+    /// row, such as `GET /api/api/users`
+    pub fn repeated_segments(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        for r in self.resolve_routes() {
+            let path = r.full_path();
+            let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            if segments.windows(2).any(|w| w[0] == w[1] && !w[0].starts_with('{')) {
+                found.push(format!("{} {}", r.route_info.method, path));
+            }
+        }
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// `Schema.property` names of registered component schemas documented as a JSON
+    /// number with `int64` or `uint64` format, including array items and map values
+    pub fn raw_int64_properties() -> Vec<String> {
+        fn is_raw_int64(prop: &openapi::Property) -> bool {
+            (prop.type_name == "integer" && matches!(prop.format.as_deref(), Some("int64" | "uint64")))
+                || prop.items.as_deref().is_some_and(is_raw_int64)
+                || prop.additional_properties.as_deref().is_some_and(is_raw_int64)
+        }
+        let mut found = Vec::new();
+        for info in inventory::iter::<SchemaInfo> {
+            let mut schemas = vec![(info.component_name(), (info.schema_fn)())];
+            schemas.extend((info.nested_fn)());
+            for (schema_name, schema) in schemas {
+                for (prop_name, prop) in &schema.properties {
+                    if is_raw_int64(prop) {
+                        found.push(format!("{}.{}", schema_name, prop_name));
+                    }
+                }
+            }
+        }
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// Component names registered by more than one model with differing schemas, each
+    /// described with both definitions' modules and property sets.
+    ///
+    /// Checked when the spec is built, which panics on any. Rename one model with
+    /// `#[api_model(schema_name = "...")]` or `#[api_model(module_prefix)]`.
+    pub fn schema_collisions() -> Vec<String> {
+        let mut seen: HashMap<String, (&SchemaInfo, openapi::Schema)> = HashMap::new();
+        let mut collisions = Vec::new();
+        for info in inventory::iter::<SchemaInfo> {
+            let name = info.component_name();
+            let schema = (info.schema_fn)();
+            match seen.get(&name) {
+                Some((first, first_schema)) if first_schema.to_json_value() != schema.to_json_value() => {
+                    let props = |s: &openapi::Schema| {
+                        let mut names: Vec<&str> = s.properties.keys().map(String::as_str).collect();
+                        names.sort_unstable();
+                        names.join(", ")
+                    };
+                    collisions.push(format!(
+                        "`{}` is defined in `{}` with {{{}}} and in `{}` with {{{}}}",
+                        name, first.module_path, props(first_schema), info.module_path, props(&schema),
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(name, (info, schema));
+                }
+            }
+        }
+        collisions.sort();
+        collisions
+    }
+
+    /// `Schema.property` names of registered component schemas that don't follow `casing`
+    pub fn casing_violations(casing: Casing) -> Vec<String> {
+        let mut violations = Vec::new();
+        for info in inventory::iter::<SchemaInfo> {
+            let mut schemas = vec![(info.component_name(), (info.schema_fn)())];
+            schemas.extend((info.nested_fn)());
+            for (schema_name, schema) in schemas {
+                for prop in schema.properties.keys() {
+                    if !casing.matches(prop) {
+                        violations.push(format!("{}.{}", schema_name, prop));
+                    }
+                }
+            }
+        }
+        violations.sort();
+        violations.dedup();
+        violations
+    }
+
+    /// Security references of the mounted routes that match no registered scheme, as
+    /// `handler: name`.
+    ///
+    /// Checked when the spec is built, which panics on any and lists the known schemes.
+    /// Apps serving every registered route, without [`include`](HayaiApp::include), only
+    /// log a warning unless [`strict_spec`](HayaiApp::strict_spec) is on.
+    pub fn unknown_security_schemes(&self) -> Vec<String> {
+        let references: Vec<(&str, Vec<String>)> = if self.has_explicit_routes() {
+            self.resolve_routes().iter()
+                .map(|r| (r.route_info.handler_name, r.merged_security().into_iter().map(String::from).collect()))
+                .collect()
+        } else {
+            inventory::iter::<&RouteInfo>.into_iter()
+                .map(|r| (r.handler_name, r.security.iter().map(|s| s.to_string()).collect()))
+                .collect()
+        };
+        let mut unknown = Vec::new();
+        for (handler, names) in references {
+            for name in names {
+                if !self.security_schemes.contains_key(security::component_name(&name)) {
+                    unknown.push(format!("{}: {}", handler, name));
+                }
+            }
+        }
+        unknown.sort();
+        unknown.dedup();
+        unknown
+    }
+
+    /// `#[link(..)]` targets that match no operation in this app by handler name or
+    /// `operationId`, as `handler: LinkName -> operation`.
+    ///
+    /// Checked when the spec is built, which panics on any.
+    pub fn unresolved_links(&self) -> Vec<String> {
+        let routes: Vec<&RouteInfo> = if self.has_explicit_routes() {
+            self.resolve_routes().iter().map(|r| r.route_info).collect()
+        } else {
+            inventory::iter::<&RouteInfo>.into_iter().copied().collect()
+        };
+        let ids = operation_id::OperationIds::assign(self.operation_id_style, self.number_duplicate_operation_ids, self.mounted_operations());
+        let resolves = |target: &str| match &ids {
+            Ok(ids) => ids.resolve(target).is_some(),
+            Err(_) => routes.iter().any(|r| r.handler_name == target),
+        };
+        let mut unresolved = Vec::new();
+        for route in &routes {
+            for link in route.links {
+                if !resolves(link.operation_id) {
+                    unresolved.push(format!("{}: {} -> {}", route.handler_name, link.name, link.operation_id));
+                }
+            }
+        }
+        unresolved.sort();
+        unresolved
+    }
+
+    /// `#[cache]` routes that inherit security from their routers but don't say
+    /// `allow_authenticated`, by handler name.
+    ///
+    /// Their responses could be served to other callers; `into_router` panics on any.
+    pub fn unsafely_cached_routes(&self) -> Vec<String> {
+        let mut routes: Vec<String> = self.resolve_routes().iter()
+            .filter(|r| r.route_info.cache.is_some_and(|c| !c.allow_authenticated) && !r.merged_security().is_empty())
+            .map(|r| r.route_info.handler_name.to_string())
+            .collect();
+        routes.sort();
+        routes.dedup();
+        routes
     }
 
-    pub fn include(mut self, router: HayaiRouter) -> Self {
-        self.routers.push(router);
-        self
+    /// Handlers that would share an `operationId`, as `id: handler (METHOD path) and
+    /// handler (METHOD path)`.
+    ///
+    /// Checked in [`into_router`](HayaiApp::into_router), which panics on any unless
+    /// [`number_duplicate_operation_ids`](HayaiApp::number_duplicate_operation_ids).
+    pub fn operation_id_collisions(&self) -> Vec<String> {
+        operation_id::OperationIds::assign(self.operation_id_style, false, self.mounted_operations())
+            .err()
+            .unwrap_or_default()
+    }
+
+    /// `(route, path, axum path)` of every route the router will mount
+    fn mounted_operations(&self) -> Vec<(&'static RouteInfo, String, String)> {
+        if self.has_explicit_routes() {
+            self.resolve_routes().iter().map(|r| (r.route_info, r.full_path(), r.full_axum_path())).collect()
+        } else {
+            inventory::iter::<&RouteInfo>.into_iter().map(|r| (*r, r.path.to_string(), r.axum_path.to_string())).collect()
+        }
     }
 
     /// Check if routers were explicitly included
@@ -483,6 +2614,18 @@ impl HayaiApp {
         !self.routers.is_empty()
     }
 
+    /// `(METHOD, axum path)` of every route the router will mount
+    fn mounted_paths(&self) -> Vec<(&'static str, String)> {
+        let mut paths: Vec<(&'static str, String)> = if self.has_explicit_routes() {
+            self.resolve_routes().iter().map(|r| (r.route_info.method, r.full_axum_path())).collect()
+        } else {
+            inventory::iter::<&RouteInfo>.into_iter().map(|r| (r.method, r.axum_path.to_string())).collect()
+        };
+        paths.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+        paths.dedup();
+        paths
+    }
+
     /// Resolve all routes from included routers
     pub fn resolve_routes(&self) -> Vec<ResolvedRoute> {
         let mut resolved = Vec::new();
@@ -492,13 +2635,128 @@ impl HayaiApp {
         resolved
     }
 
-    pub fn into_router(self) -> Router {
-        let spec = self.generate_openapi_spec();
-        let swagger_html = self.generate_swagger_html();
+    /// Every route the app mounts with its method, full path, security, tags and limits,
+    /// for generating gateway config; see [`manifest`]
+    pub fn route_manifest(&self) -> manifest::RouteManifest {
+        let ids = operation_id::OperationIds::assign(self.operation_id_style, self.number_duplicate_operation_ids, self.mounted_operations()).ok();
+        let mounted: Vec<(&'static RouteInfo, String, Vec<String>, Vec<String>)> = if self.has_explicit_routes() {
+            self.resolve_routes().iter()
+                .map(|r| (r.route_info, r.full_path(), r.merged_security().iter().map(|s| s.to_string()).collect(), r.merged_tags()))
+                .collect()
+        } else {
+            inventory::iter::<&RouteInfo>.into_iter()
+                .map(|r| (*r, r.path.to_string(), r.security.iter().map(|s| s.to_string()).collect(), r.tags.iter().map(|t| t.to_string()).collect()))
+                .collect()
+        };
+        let mut routes: Vec<manifest::ManifestRoute> = mounted.into_iter()
+            .map(|(route, path, security, tags)| manifest::ManifestRoute {
+                method: route.method.to_string(),
+                operation_id: match &ids {
+                    Some(ids) => ids.get(route, &path).to_string(),
+                    None => route.operation_id.map_or_else(|| self.operation_id_style.derive(route, &path), str::to_string),
+                },
+                handler: route.handler_name.to_string(),
+                path,
+                security,
+                tags,
+                version: route.version,
+                timeout_ms: route.timeout.or(self.default_timeout).map(|t| t.as_millis() as u64),
+                concurrency_limit: route.concurrency_limit.or(self.default_concurrency_limit).map(|l| l.max),
+                body_limit: self.body_limit.filter(|_| route.has_body),
+                cache_ttl_ms: route.cache.map(|c| c.ttl.as_millis() as u64),
+                feature_flag: route.feature_flag.map(str::to_string),
+            })
+            .collect();
+        routes.sort_by(|a, b| (&a.path, &a.method, a.version, &a.handler).cmp(&(&b.path, &b.method, b.version, &b.handler)));
+        routes.dedup();
+        manifest::RouteManifest { manifest_version: manifest::MANIFEST_VERSION, routes }
+    }
+
+    /// Write the [`route_manifest`](HayaiApp::route_manifest) to `path` as indented JSON
+    pub fn write_manifest(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.route_manifest().to_json())
+    }
+
+    pub fn into_router(mut self) -> Router {
+        if let Some(casing) = self.enforce_casing {
+            let violations = Self::casing_violations(casing);
+            if !violations.is_empty() {
+                panic!("Property names violate {:?} casing: {}", casing, violations.join(", "));
+            }
+        }
+
+        let repeated = self.repeated_segments();
+        if !repeated.is_empty() {
+            if self.strict_paths {
+                panic!("Route paths repeat a segment, check for a doubled router prefix: {}", repeated.join(", "));
+            }
+            for route in &repeated {
+                tracing::warn!(route = %route, "route path repeats a segment, check for a doubled router prefix");
+            }
+        }
+
+        // Linting builds every schema, which apps without docs otherwise never do
+        let model_issues = if self.serves_docs() || self.strict_models { lint::check_models() } else { Vec::new() };
+        if !model_issues.is_empty() {
+            let listed: Vec<String> = model_issues.iter().map(ToString::to_string).collect();
+            if self.strict_models {
+                panic!("Model attributes don't apply: {}", listed.join("; "));
+            }
+            for issue in &listed {
+                tracing::warn!(issue = %issue, "model attribute doesn't apply");
+            }
+        }
+
+        let unsafely_cached = self.unsafely_cached_routes();
+        if !unsafely_cached.is_empty() {
+            panic!("#[cache] on authenticated routes needs `allow_authenticated`: {}", unsafely_cached.join(", "));
+        }
+
+        let debug_manifest = self.debug_endpoints.then(|| {
+            if !debug::allowed(!cfg!(debug_assertions), self.allow_debug_in_release) {
+                panic!(
+                    "Debug endpoints are refused in release builds; set {}=1 or call allow_debug_in_release",
+                    debug::ALLOW_ENV_VAR,
+                );
+            }
+            self.route_manifest().to_json()
+        });
+
+        let operation_ids = operation_id::OperationIds::assign(self.operation_id_style, self.number_duplicate_operation_ids, self.mounted_operations())
+            .unwrap_or_else(|collisions| panic!("Handlers share an operationId: {}; rename one with #[operation_id(\"..\")]", collisions.join(", ")));
+
         let has_explicit = self.has_explicit_routes();
         let resolved = if has_explicit { self.resolve_routes() } else { Vec::new() };
-        let spec_json = serde_json::to_string_pretty(&spec.to_json_with_query_params(&self.routers))
-            .expect("Failed to serialize OpenAPI spec");
+        let docs = if !self.serves_docs() {
+            None
+        } else {
+            let spec = self.generate_openapi_spec(&operation_ids);
+            let mut spec_value = spec.to_json_with_query_params(&self.routers);
+            if let Some(header) = self.tenant_resolver.as_ref().and_then(|r| r.header()) {
+                openapi::add_global_header_parameter(&mut spec_value, "TenantId", header, "The tenant of the request");
+            }
+            if let Some(locales) = &self.locales {
+                locales.document(&mut spec_value);
+            }
+            if self.auto_head && self.document_auto_head {
+                openapi::add_head_operations(&mut spec_value, self.operation_id_style);
+            }
+            let issues = openapi::validate_spec(&spec_value);
+            if !issues.is_empty() {
+                let listed: Vec<String> = issues.iter().map(ToString::to_string).collect();
+                if self.strict_spec {
+                    panic!("OpenAPI spec is invalid: {}", listed.join("; "));
+                }
+                for issue in &listed {
+                    tracing::warn!(issue = %issue, "OpenAPI spec problem");
+                }
+            }
+            let spec_json = serde_json::to_string_pretty(&spec_value)
+                .expect("Failed to serialize OpenAPI spec");
+            Some((spec_json, self.generate_swagger_html()))
+        };
+
+        let strict_bodies = self.strict_body_properties();
 
         // Merge deps from routers
         let mut all_deps = self.deps;
@@ -506,54 +2764,481 @@ impl HayaiApp {
             all_deps.extend(router.collect_deps());
         }
 
+        let mounted: Vec<&'static RouteInfo> = if has_explicit {
+            resolved.iter().map(|r| r.route_info).collect()
+        } else {
+            inventory::iter::<&RouteInfo>.into_iter().copied().collect()
+        };
+
+        let flagged = feature_flags::flagged_routes(&mounted);
+        if self.feature_flags.is_none() && !flagged.is_empty() {
+            panic!("#[feature_flag] routes need `HayaiApp::feature_flags`: {}", flagged.join(", "));
+        }
+
+        // Owned by the state, so the watchers stop with the router, or if a later check fails
+        let reload_watchers = reload::Watchers::spawn(std::mem::take(&mut self.reload_tasks), &self.shutdown_signal);
+
         let state = AppState {
             deps: Arc::new(all_deps),
+            settings: Arc::new(AppSettings {
+                strict_bodies,
+                json: self.json_config,
+                validation_response: self.validation_response,
+                concurrency: concurrency::limiters(&mounted, self.default_concurrency_limit),
+                response_cache: Some(self.response_cache.unwrap_or_else(|| Arc::new(cache::MemoryCache::default()))),
+                in_flight: coalesce::InFlight::default(),
+                tenant_deps: self.tenant_deps,
+                parent_loaders: self.parent_loaders,
+                drain: Arc::new(drain::DrainState::default()),
+                deadline: self.default_deadline,
+                shutdown: self.shutdown_signal.clone(),
+                feature_flags: self.feature_flags,
+                disabled_route: self.disabled_route,
+                _reload_watchers: reload_watchers,
+            }),
         };
 
         let mut app = Router::new();
+        let operations = operation_ids.by_axum_path();
+        let mut transactional_routes = HashSet::new();
+        let mut event_routes = HashMap::new();
+        let mut audited_routes = HashMap::new();
+        let mut route_timeouts = HashMap::new();
+        let mut enveloped_routes = HashSet::new();
+        let mut route_methods: HashMap<String, BTreeSet<&'static str>> = HashMap::new();
+        let mut versioned: BTreeMap<(String, String), Vec<(u32, &'static RouteInfo)>> = BTreeMap::new();
+        let wraps = |route: &RouteInfo| self.transaction.is_some_and(|t| t.scope.includes(route.transactional));
 
         if has_explicit {
             for r in &resolved {
                 let axum_path = r.full_axum_path();
-                let method_router = (r.route_info.method_router_fn)();
-                app = app.route(&axum_path, method_router);
+                match r.route_info.version {
+                    Some(version) => versioned.entry((r.route_info.method.to_string(), axum_path.clone()))
+                        .or_default()
+                        .push((version, r.route_info)),
+                    None => app = app.route(&axum_path, (r.route_info.method_router_fn)()),
+                }
+                if wraps(r.route_info) {
+                    transactional_routes.insert((r.route_info.method.to_string(), axum_path.clone()));
+                }
+                if r.route_info.emit_event.is_some() {
+                    event_routes.insert((r.route_info.method.to_string(), axum_path.clone()), r.route_info);
+                }
+                if r.route_info.audited.is_some() {
+                    audited_routes.insert((r.route_info.method.to_string(), axum_path.clone()), r.route_info);
+                }
+                if let Some(limit) = r.route_info.timeout.or(self.default_timeout) {
+                    route_timeouts.insert((r.route_info.method.to_string(), axum_path.clone()), limit);
+                }
+                if !r.route_info.no_envelope {
+                    enveloped_routes.insert((r.route_info.method.to_string(), axum_path.clone()));
+                }
+                let methods = route_methods.entry(axum_path.clone()).or_default();
+                methods.insert(r.route_info.method);
+                // Seeking clients probe a file's size and `Accept-Ranges` first
+                if r.route_info.response_type_name == range::RANGED_FILE {
+                    methods.insert("HEAD");
+                }
             }
         } else {
             for route in inventory::iter::<&RouteInfo> {
-                app = (route.register_fn)(app);
+                match route.version {
+                    Some(version) => versioned.entry((route.method.to_string(), route.axum_path.to_string()))
+                        .or_default()
+                        .push((version, *route)),
+                    None => app = (route.register_fn)(app),
+                }
+                if wraps(route) {
+                    transactional_routes.insert((route.method.to_string(), route.axum_path.to_string()));
+                }
+                if route.emit_event.is_some() {
+                    event_routes.insert((route.method.to_string(), route.axum_path.to_string()), *route);
+                }
+                if route.audited.is_some() {
+                    audited_routes.insert((route.method.to_string(), route.axum_path.to_string()), *route);
+                }
+                if let Some(limit) = route.timeout.or(self.default_timeout) {
+                    route_timeouts.insert((route.method.to_string(), route.axum_path.to_string()), limit);
+                }
+                if !route.no_envelope {
+                    enveloped_routes.insert((route.method.to_string(), route.axum_path.to_string()));
+                }
+                let methods = route_methods.entry(route.axum_path.to_string()).or_default();
+                methods.insert(route.method);
+                if route.response_type_name == range::RANGED_FILE {
+                    methods.insert("HEAD");
+                }
+            }
+        }
+
+        for ((method, path), mut handlers) in versioned {
+            let Some(versioning) = &self.header_versioning else {
+                panic!("#[version] on {} {} needs HayaiApp::header_versioning", method, path);
+            };
+            handlers.sort_by_key(|(version, _)| *version);
+            if let Some(pair) = handlers.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+                panic!(
+                    "{} {} has two handlers for API version {}: {} and {}",
+                    method, path, pair[0].0, pair[0].1.handler_name, pair[1].1.handler_name,
+                );
             }
+            let route = versioning::VersionedRoute {
+                versioning: Arc::new(versioning.clone()),
+                handlers: Arc::new(handlers.iter()
+                    .map(|(version, info)| (*version, (info.method_router_fn)().with_state(state.clone())))
+                    .collect()),
+            };
+            let filter = axum::http::Method::from_bytes(method.as_bytes()).ok()
+                .and_then(|m| axum::routing::MethodFilter::try_from(m).ok())
+                .unwrap_or_else(|| panic!("unsupported method for a versioned route: {}", method));
+            app = app.route(&path, axum::routing::on(filter, move |req: axum::extract::Request| {
+                versioning::dispatch(route.clone(), req)
+            }));
         }
 
-        let spec_json_clone = spec_json.clone();
-        app = app.route("/openapi.json", axum::routing::get(move || {
-            let spec = spec_json_clone.clone();
-            async move {
-                (StatusCode::OK, [("content-type", "application/json")], spec)
+        if let Some(threshold) = self.detect_blocking {
+            let config = blocking::BlockingDetector { threshold, operations: Arc::new(operations.clone()) };
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                blocking::detect(config.clone(), req, next)
+            }));
+        }
+
+        // Inside the transaction, so a timed-out request rolls back
+        if !route_timeouts.is_empty() {
+            let timeouts: timeout::RouteTimeouts = Arc::new(route_timeouts);
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                timeout::enforce(timeouts.clone(), req, next)
+            }));
+        }
+
+        if let Some(setup) = self.transaction {
+            let begin = state.deps.get(&setup.dep_type)
+                .and_then(|dep| (setup.begin_fn)(dep.clone()))
+                .unwrap_or_else(|| panic!("transactional::<{}>() needs the dependency registered with .dep()", setup.dep_name));
+            let config = transaction::Transactional { begin, routes: Arc::new(transactional_routes) };
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                transaction::run_in_transaction(config.clone(), req, next)
+            }));
+        }
+
+        // Outside the transaction, so only committed responses emit events
+        if !self.response_hooks.is_empty() && !event_routes.is_empty() {
+            let config = events::EventHooks {
+                hooks: Arc::new(self.response_hooks),
+                routes: Arc::new(event_routes),
+                operations: Arc::new(operations.clone()),
+                shared: self.shared.clone(),
+            };
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                events::emit_events(config.clone(), req, next)
+            }));
+        }
+
+        // Outside the transaction too, recording rolled back requests with their status
+        if let Some(sink) = self.audit_sink.filter(|_| !audited_routes.is_empty()) {
+            let trail = audit::AuditTrail::new(sink, self.audit_buffer, audited_routes, operations.clone());
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                audit::audit(trail.clone(), req, next)
+            }));
+        }
+
+        // Outside the hooks and audit trail, which see the body the handler returned
+        if let Some(config) = self.response_envelope {
+            let enveloping = envelope::Enveloping { config: Arc::new(config), routes: Arc::new(enveloped_routes) };
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                envelope::wrap(enveloping.clone(), req, next)
+            }));
+        }
+
+        // Outermost route layer, so nothing runs for a request without a tenant
+        if let Some(resolver) = self.tenant_resolver {
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                tenant::resolve_tenant(resolver.clone(), req, next)
+            }));
+        }
+
+        if let Some((spec_json, swagger_html)) = docs {
+            let dynamic_servers = self.proxy.as_ref().is_some_and(|p| p.dynamic_servers);
+            app = app.route(&registry::join_path(&self.docs_prefix, "/openapi.json"), axum::routing::get(move |conn: proxy::ConnectionInfo| {
+                let spec = match conn.base_url().filter(|_| dynamic_servers) {
+                    Some(url) => openapi::with_server(&spec_json, &url),
+                    None => spec_json.clone(),
+                };
+                async move {
+                    (StatusCode::OK, [("content-type", "application/json")], spec)
+                }
+            }));
+
+            app = app.route(&registry::join_path(&self.docs_prefix, "/docs"), axum::routing::get(move || {
+                let html = swagger_html.clone();
+                async move {
+                    (StatusCode::OK, [("content-type", "text/html")], html)
+                }
+            }));
+
+            #[cfg(feature = "embedded-docs")]
+            if self.docs_assets == docs::DocsAssets::Embedded {
+                let assets = registry::join_path(&self.docs_prefix, docs::ASSETS_PATH);
+                app = app.route(&format!("{}/{{name}}", assets), axum::routing::get(docs::serve_asset));
             }
+        }
+
+        if let Some(limit) = self.body_limit {
+            app = app.layer(axum::extract::DefaultBodyLimit::max(limit));
+        }
+
+        if !self.accepted_encodings.is_empty() {
+            let accepted = self.accepted_encodings.clone();
+            let limit = self.body_limit.unwrap_or(decompression::DEFAULT_BODY_LIMIT);
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                decompression::decompress(accepted.clone(), limit, req, next)
+            }));
+        }
+
+        if self.health_endpoints {
+            let checks = Arc::new(self.health_checks);
+            let timeout = self.health_timeout;
+            let draining = state.settings.drain.clone();
+            app = app.route("/healthz", axum::routing::get(health::liveness));
+            app = app.route("/readyz", axum::routing::get(move || health::readiness(checks.clone(), timeout, draining.clone())));
+        }
+        let drain_path = self.drain_endpoint.as_ref().map(|(path, _)| path.clone());
+        if let Some((path, token)) = self.drain_endpoint {
+            let endpoint = drain::DrainEndpoint { token, timeout: self.drain_timeout };
+            let draining = state.settings.drain.clone();
+            app = app.route(&path, axum::routing::post(move |headers| drain::toggle(endpoint.clone(), draining.clone(), headers)));
+        }
+        let quiet_paths = self.well_known.as_ref().map(well_known::WellKnown::quiet_paths).unwrap_or_default();
+        if let Some(well_known) = self.well_known {
+            app = well_known.mount(app);
+        }
+        if let Some(manifest) = debug_manifest {
+            let api_key_headers = self.security_schemes.values()
+                .filter(|scheme| scheme.scheme_type == "apiKey" && scheme.location.as_deref() == Some("header"))
+                .filter_map(|scheme| scheme.name.clone());
+            let echo = debug::Echo::new(api_key_headers, self.body_limit.unwrap_or(decompression::DEFAULT_BODY_LIMIT));
+            app = app.route(debug::ECHO_PATH, axum::routing::any(move |req| debug::echo(echo.clone(), req)));
+            app = app.route(debug::ROUTES_PATH, axum::routing::get(move || {
+                let manifest = manifest.clone();
+                async move { ([("content-type", "application/json")], manifest) }
+            }));
+        }
+
+        let max_query_length = self.max_query_length;
+        app = app.layer(axum::middleware::from_fn(move |req, next| {
+            limit_query_length(max_query_length, req, next)
         }));
 
-        app = app.route("/docs", axum::routing::get(move || {
-            let html = swagger_html.clone();
-            async move {
-                (StatusCode::OK, [("content-type", "text/html")], html)
+        // Outside the routes and body handling, so a request in maintenance does no work
+        if let Some(mut config) = self.maintenance {
+            config.allow_paths.extend(drain_path);
+            let config = Arc::new(config);
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                maintenance::enforce(config.clone(), req, next)
+            }));
+        }
+
+        // Outside every layer that answers with an `ApiError`
+        if let Some(catalog) = self.message_catalog {
+            let localizer = i18n::Localizer { catalog, shaper: self.validation_response.map(|(_, shaper)| shaper) };
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                i18n::localize(localizer.clone(), req, next)
+            }));
+        }
+
+        // Outside the catalog, which renders errors in the resolved locale
+        if let Some(locales) = self.locales.map(Arc::new) {
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                locale::resolve_locale(locales.clone(), req, next)
+            }));
+        }
+
+        #[cfg(feature = "auth")]
+        {
+            app = app.layer(axum::middleware::from_fn(auth::propagate_subject));
+        }
+
+        #[cfg(feature = "http-client")]
+        {
+            app = app.layer(axum::middleware::from_fn(http_client::propagate_context));
+        }
+
+        if let Some(mut log) = self.access_log {
+            log.operations = Arc::new(operations);
+            log.excluded.extend(quiet_paths);
+            if log.bodies != access_log::LogBodies::Never {
+                log.sensitive = Arc::new(access_log::sensitive_fields());
             }
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                access_log::log_request(log.clone(), req, next)
+            }));
+        }
+
+        // Inside the proxy layer, whose `ConnectionInfo` tells whether the request came over HTTPS
+        if let Some(config) = self.security_headers {
+            let headers = Arc::new(config.resolve(&self.docs_assets));
+            app = app.layer(axum::middleware::from_fn(move |req, next| {
+                security_headers::add_headers(headers.clone(), req, next)
+            }));
+        }
+
+        let proxy_config = self.proxy.clone();
+        app = app.layer(axum::middleware::from_fn(move |req, next| {
+            proxy::connection_info(proxy_config.clone(), req, next)
         }));
 
+        if !self.auto_head {
+            let route_methods = Arc::new(route_methods);
+            app = app.layer(axum::middleware::from_fn(move |req, next| reject_head(route_methods.clone(), req, next)));
+        }
+
         app.with_state(state)
     }
 
+    /// [`serve`](HayaiApp::serve) on the address from the `BIND` config key
+    /// (`0.0.0.0:8000` when no config was loaded)
+    pub async fn serve_from_config(self) {
+        let addr = self.bind_addr.clone().unwrap_or_else(|| config::HayaiConfig::default().bind);
+        self.serve(&addr).await
+    }
+
+    /// Serve until Ctrl-C, running the lifespan callbacks around it.
+    ///
+    /// Panics if a lifespan callback, the warmup or binding `addr` fails.
     pub async fn serve(self, addr: &str) {
-        let app = self.into_router();
+        let ctrl_c = async {
+            let _ = tokio::signal::ctrl_c().await;
+        };
+        if let Err(e) = self.serve_with_shutdown(addr, ctrl_c).await {
+            panic!("{}", e);
+        }
+    }
+
+    /// [`serve`](HayaiApp::serve) on a runtime built from the [`runtime`](HayaiApp::runtime)
+    /// config, blocking until Ctrl-C; for `main` functions without `#[tokio::main]`.
+    ///
+    /// Panics if the runtime can't be built, or a lifespan callback, the warmup or binding
+    /// `addr` fails.
+    pub fn serve_blocking(self, addr: &str) {
+        let ctrl_c = async {
+            let _ = tokio::signal::ctrl_c().await;
+        };
+        if let Err(e) = self.serve_blocking_with_shutdown(addr, ctrl_c) {
+            panic!("{}", e);
+        }
+    }
+
+    /// [`serve_with_shutdown`](HayaiApp::serve_with_shutdown) on a runtime built from the
+    /// [`runtime`](HayaiApp::runtime) config, blocking until it returns.
+    ///
+    /// Panics if the runtime can't be built.
+    pub fn serve_blocking_with_shutdown<S>(self, addr: &str, signal: S) -> Result<(), lifespan::LifespanError>
+    where
+        S: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let runtime = self.runtime.build()
+            .unwrap_or_else(|e| panic!("Failed to build the Tokio runtime: {}", e));
+        runtime.block_on(self.serve_with_shutdown(addr, signal))
+    }
+
+    /// Run the startup callbacks, serve until `signal` completes, then run the shutdown
+    /// callbacks. Returns the first callback failure, or a failed warmup or bind reported as
+    /// a startup failure; once the startup callbacks have succeeded, the shutdown callbacks
+    /// run however serving ends.
+    pub fn serve_with_shutdown<S>(
+        mut self,
+        addr: &str,
+        signal: S,
+    ) -> impl std::future::Future<Output = Result<(), lifespan::LifespanError>> + Send + 'static
+    where
+        S: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let addr = addr.to_string();
+        async move {
+            let shared = self.shared.clone();
+            let shutdown = std::mem::take(&mut self.shutdown);
+            let shutdown_signal = self.shutdown_signal.clone();
+            let signal = async move {
+                signal.await;
+                shutdown_signal.begin();
+            };
+            lifespan::run_startup(std::mem::take(&mut self.startup), &shared).await?;
+            for (type_id, value) in shared.values() {
+                self.deps.entry(type_id).or_insert(value);
+            }
+
+            let docs = self.serves_docs().then(|| registry::join_path(&self.docs_prefix, "/docs"));
+            let http_config = self.http_config.clone();
+            let worker_start = std::mem::take(&mut self.worker_start);
+            let warmup = self.warmup.take().map(|plan| plan.requests(&self.mounted_paths()));
+            let app = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.into_router())) {
+                Ok(app) => app,
+                Err(panic) => {
+                    let _ = lifespan::run_shutdown(shutdown, &shared).await;
+                    std::panic::resume_unwind(panic);
+                }
+            };
+
+            let served = async {
+                if let Some(requests) = warmup {
+                    if let Err(e) = warmup::run(&app, requests).await {
+                        tracing::error!(error = %e, "warmup failed");
+                        return Err(lifespan::LifespanError { phase: "startup", callback: "warmup".to_string(), source: Box::new(e) });
+                    }
+                }
+
+                let handle = tokio::runtime::Handle::current();
+                for hook in worker_start {
+                    hook(&handle);
+                }
+
+                let listener = tokio::net::TcpListener::bind(&addr).await.map_err(|e| {
+                    tracing::error!(error = %e, addr = %addr, "failed to bind");
+                    lifespan::LifespanError { phase: "startup", callback: "bind".to_string(), source: Box::new(e) }
+                })?;
+                println!("🚀 Hayai server running at http://{}", addr);
+                if let Some(docs) = docs {
+                    println!("📖 Swagger UI available at http://{}{}", addr, docs);
+                }
+                let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+                server::serve(listener, app, http_config, signal).await;
+                Ok(())
+            }.await;
+
+            let stopped = lifespan::run_shutdown(shutdown, &shared).await;
+            served.and(stopped)
+        }
+    }
 
-        let listener = tokio::net::TcpListener::bind(addr).await
-            .expect("Failed to bind to address");
-        println!("🚀 Hayai server running at http://{}", addr);
-        println!("📖 Swagger UI available at http://{}/docs", addr);
-        axum::serve(listener, app).await
-            .expect("Server error");
+    /// Under [`strict_bodies`](HayaiApp::strict_bodies), the properties of each
+    /// `#[api_model(strict_bodies)]` model by component name, so requests don't rebuild them.
+    /// Unions are left out: their keys depend on the variant, and serde rejects what none of
+    /// them take.
+    fn strict_body_properties(&self) -> HashMap<String, HashSet<String>> {
+        if !self.strict_bodies {
+            return HashMap::new();
+        }
+        inventory::iter::<SchemaInfo>.into_iter()
+            .filter(|info| info.strict_bodies)
+            .filter_map(|info| {
+                let schema = (info.schema_fn)();
+                schema.one_of.is_empty().then(|| (info.component_name(), schema.properties.into_keys().collect()))
+            })
+            .collect()
+    }
+
+    /// The `<Model>BulkItem` component documenting the items of a `BulkResult<T>` route
+    fn bulk_item_component(route: &RouteInfo) -> Option<(String, openapi::Schema)> {
+        if !route.bulk || route.response_schema_fn.is_some() || openapi::inline_schema(route.vec_inner_type_name).is_some() {
+            return None;
+        }
+        let data = serde_json::json!({ "$ref": format!("#/components/schemas/{}", route.component_name(route.vec_inner_type_name)) });
+        bulk::schema(data).1
     }
 
-    fn build_operation(route: &RouteInfo, tags: Vec<String>, security_list: &[&str]) -> openapi::Operation {
+    /// `default_responses` are the app's and routers' documented errors, later entries
+    /// winning for the same status
+    fn build_operation(route: &RouteInfo, tags: Vec<String>, security_list: &[&str], default_responses: &[(u16, String)]) -> openapi::Operation {
         let description = if route.description.is_empty() {
             None
         } else {
@@ -563,29 +3248,33 @@ impl HayaiApp {
         let status_code = route.success_status.to_string();
 
         let security: Vec<HashMap<String, Vec<String>>> = security_list.iter().map(|s| {
-            let scheme_name = match *s {
-                "bearer" => "bearerAuth",
-                other => other,
-            };
+            let scheme_name = security::component_name(s);
             let mut map = HashMap::new();
             map.insert(scheme_name.to_string(), vec![]);
             map
         }).collect();
 
-        let schema_ref_value = if route.success_status == 204 {
+        // Untyped JSON (`serde_json::Value`) has no component and is documented as an open object
+        // Primitive responses are documented inline, they have no component
+        let model_schema = |name: &str| openapi::inline_schema(name)
+            .unwrap_or_else(|| serde_json::json!({ "$ref": format!("#/components/schemas/{}", route.component_name(name)) }));
+        let schema_ref_value = if !route.has_response_body() {
             None
+        } else if let Some(schema_fn) = route.response_schema_fn {
+            Some(schema_fn())
+        } else if let Some(kind) = route.pagination {
+            Some(kind.schema(model_schema(route.vec_inner_type_name)))
+        } else if route.bulk {
+            Some(bulk::schema(model_schema(route.vec_inner_type_name)).0)
         } else if route.is_vec_response {
-            Some(serde_json::json!({
-                "type": "array",
-                "items": { "$ref": format!("#/components/schemas/{}", route.vec_inner_type_name) }
-            }))
+            Some(serde_json::json!({ "type": "array", "items": model_schema(route.vec_inner_type_name) }))
         } else {
-            Some(serde_json::json!({ "$ref": format!("#/components/schemas/{}", route.response_type_name) }))
+            Some(model_schema(route.response_type_name))
         };
 
         let success_desc = openapi::status_description(route.success_status).to_string();
 
-        openapi::Operation {
+        let mut operation = openapi::Operation {
             summary: Some(route.handler_name.replace('_', " ")),
             description,
             operation_id: Some(route.handler_name.to_string()),
@@ -594,8 +3283,19 @@ impl HayaiApp {
             request_body: if route.has_body {
                 Some(openapi::RequestBody {
                     required: true,
-                    content_type: "application/json".to_string(),
-                    schema_ref: format!("#/components/schemas/{}", route.body_type_name),
+                    content_type: route.body_content_type.to_string(),
+                    schema: match route.body_type_name {
+                        "String" => serde_json::json!({ "type": "string" }),
+                        "Bytes" => serde_json::json!({ "type": "string", "format": "binary" }),
+                        "Multipart" => openapi::multipart_schema(route.multipart_fields),
+                        name => match route.body_collection {
+                            Some(collection) => collection.schema(model_schema(name)),
+                            None => serde_json::json!({ "$ref": format!("#/components/schemas/{}", route.component_name(name)) }),
+                        },
+                    },
+                    example: None,
+                    description: route.request_body_description.map(str::to_string),
+                    component: route.request_body_name.map(str::to_string),
                 })
             } else {
                 None
@@ -605,97 +3305,199 @@ impl HayaiApp {
                 map.insert(status_code, openapi::ResponseDef {
                     description: success_desc,
                     schema_ref: schema_ref_value,
+                    headers: {
+                        let mut headers: HashMap<String, openapi::ResponseHeader> = route.response_headers.iter()
+                            .map(|(name, kind, description)| (name.to_string(), openapi::ResponseHeader::new(kind, description)))
+                            .collect();
+                        if route.pagination.is_some() {
+                            headers.insert("Link".to_string(), openapi::ResponseHeader::new(
+                                "string",
+                                "RFC 5988 links to the `next` and `prev` pages, when they exist",
+                            ));
+                        }
+                        headers
+                    },
+                    links: route.links.to_vec(),
                 });
                 map.insert("400".to_string(), openapi::ResponseDef {
                     description: "Bad Request".to_string(),
                     schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                    headers: HashMap::new(),
+                    links: Vec::new(),
                 });
-                if route.is_result_return {
+                if route.none_status == Some(204) {
+                    map.insert("204".to_string(), openapi::ResponseDef {
+                        description: openapi::status_description(204).to_string(),
+                        schema_ref: None,
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                if route.is_result_return || route.none_status == Some(404) {
                     map.insert("404".to_string(), openapi::ResponseDef {
                         description: "Not Found".to_string(),
                         schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
                     });
                 }
-                if route.has_body {
+                let validated = matches!(route.body_content_type, "application/json" | "application/x-www-form-urlencoded" | "application/xml" | "text/xml");
+                if route.has_body && validated {
                     map.insert("422".to_string(), openapi::ResponseDef {
                         description: "Validation Failed".to_string(),
                         schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                if route.has_body && route.body_content_type != "application/json" {
+                    map.insert("415".to_string(), openapi::ResponseDef {
+                        description: "Unsupported Media Type".to_string(),
+                        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                if route.parameters.iter().any(|p| p.location == "header" && p.name == "If-Unmodified-Since") {
+                    map.insert("412".to_string(), openapi::ResponseDef {
+                        description: "Precondition Failed".to_string(),
+                        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                if route.version.is_some() {
+                    map.insert("406".to_string(), openapi::ResponseDef {
+                        description: openapi::status_description(406).to_string(),
+                        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                if !security_list.is_empty() {
+                    map.insert("401".to_string(), openapi::ResponseDef {
+                        description: "Unauthorized".to_string(),
+                        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                for (status, description) in default_responses {
+                    map.insert(status.to_string(), openapi::ResponseDef {
+                        description: description.clone(),
+                        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+                for (status, name) in route.error_responses {
+                    map.insert(status.to_string(), openapi::ResponseDef {
+                        description: openapi::status_description(*status).to_string(),
+                        schema_ref: Some(serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) })),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
                     });
                 }
-                map.insert("500".to_string(), openapi::ResponseDef {
-                    description: "Internal Server Error".to_string(),
-                    schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
-                });
                 map
             },
+            public: route.clears_security && security_list.is_empty(),
             security,
+            produces: (route.produces != "application/json").then(|| route.produces.to_string()),
+            extensions: {
+                let mut extensions = Vec::new();
+                if let Some(limit) = route.timeout {
+                    extensions.push((timeout::TIMEOUT_EXTENSION.to_string(), serde_json::json!(limit.as_millis() as u64)));
+                }
+                if let Some(version) = route.version {
+                    extensions.push((versioning::VERSION_EXTENSION.to_string(), serde_json::json!(version)));
+                }
+                for (key, value) in route.openapi_extensions {
+                    extensions.retain(|(k, _)| k != key);
+                    extensions.push((key.to_string(), value()));
+                }
+                extensions
+            },
+        };
+        if route.response_type_name == range::RANGED_FILE {
+            range::document(&mut operation);
         }
+        operation
+    }
+
+    /// Whether `/docs` and `/openapi.json` are mounted
+    fn serves_docs(&self) -> bool {
+        cfg!(feature = "openapi") && !self.minimal && self.docs
     }
 
     fn generate_swagger_html(&self) -> String {
-        match &self.swagger_mode {
-            SwaggerMode::Cdn(cdn_base) => {
-                format!(
-                    r#"<!DOCTYPE html>
-<html>
-<head>
-    <title>{title} - Swagger UI</title>
-    <link rel="stylesheet" type="text/css" href="{cdn}/swagger-ui.css" >
-</head>
-<body>
-    <div id="swagger-ui"></div>
-    <script src="{cdn}/swagger-ui-bundle.js"> </script>
-    <script>
-    SwaggerUIBundle({{
-        url: "/openapi.json",
-        dom_id: '#swagger-ui',
-        presets: [SwaggerUIBundle.presets.apis, SwaggerUIBundle.SwaggerUIStandalonePreset],
-        layout: "BaseLayout"
-    }})
-    </script>
-</body>
-</html>"#,
-                    title = self.title,
-                    cdn = cdn_base,
-                )
-            }
-            SwaggerMode::Embedded => {
-                format!(
-                    r#"<!DOCTYPE html>
-<html>
-<head>
-    <title>{title} - API Reference</title>
-    <meta charset="utf-8" />
-    <meta name="viewport" content="width=device-width, initial-scale=1" />
-    <style>{css}</style>
-</head>
-<body>
-    <script id="api-reference" data-url="/openapi.json"></script>
-    <script>{js}</script>
-</body>
-</html>"#,
-                    title = self.title,
-                    css = include_str!("../assets/scalar.min.css"),
-                    js = include_str!("../assets/scalar.min.js"),
-                )
-            }
-        }
-    }
-
-    fn generate_openapi_spec(&self) -> openapi::OpenApiSpec {
+        let spec_url = registry::join_path(&self.docs_prefix, "/openapi.json");
+        let assets_url = registry::join_path(&self.docs_prefix, "/docs/assets");
+        match &self.docs_html {
+            Some(template) => docs::render_template(template, &spec_url),
+            None => docs::render(&self.docs_assets, &self.title, &spec_url, &assets_url, &self.docs_customization),
+        }
+    }
+
+    fn generate_openapi_spec(&self, operation_ids: &operation_id::OperationIds) -> openapi::OpenApiSpec {
         let mut schemas = HashMap::new();
 
         // Add ApiError schema
         schemas.insert("ApiError".to_string(), openapi::api_error_schema());
 
+        let collisions = Self::schema_collisions();
+        if !collisions.is_empty() {
+            panic!("Schema component name collision: {}", collisions.join("; "));
+        }
+        let unknown_security = self.unknown_security_schemes();
+        if !unknown_security.is_empty() {
+            let mut known: Vec<&str> = self.security_schemes.keys().map(String::as_str).collect();
+            for short in [security::BEARER, security::API_KEY] {
+                if self.security_schemes.contains_key(security::component_name(short)) {
+                    known.push(short);
+                }
+            }
+            known.sort_unstable();
+            let known = if known.is_empty() { "none, register one with bearer_auth, api_key_auth or security_scheme".to_string() } else { known.join(", ") };
+            // Without `include`, every route in the binary is served, including ones
+            // written for other apps; those only warn unless the spec is strict
+            if self.has_explicit_routes() || self.strict_spec {
+                panic!("Unknown security scheme: {}; known schemes: {}", unknown_security.join(", "), known);
+            }
+            for reference in &unknown_security {
+                tracing::warn!(reference = %reference, known = %known, "unknown security scheme");
+            }
+        }
+        let unresolved_links = self.unresolved_links();
+        if !unresolved_links.is_empty() {
+            panic!("Link to an unknown operation: {}", unresolved_links.join(", "));
+        }
+        if self.encode_int64_as_string {
+            let raw = Self::raw_int64_properties();
+            if !raw.is_empty() {
+                panic!("64-bit integers not encoded as strings: {}; add #[schema(string_encoded)]", raw.join(", "));
+            }
+        }
         for info in inventory::iter::<SchemaInfo> {
-            schemas.insert(info.name.to_string(), (info.schema_fn)());
+            schemas.insert(info.component_name(), (info.schema_fn)());
             for (nested_name, nested_schema) in (info.nested_fn)() {
                 schemas.entry(nested_name).or_insert(nested_schema);
             }
         }
 
+        let taken: BTreeSet<&String> = self.error_components.keys().filter(|name| schemas.contains_key(*name)).collect();
+        if !taken.is_empty() {
+            panic!("Error component names are taken by models: {}", taken.into_iter().cloned().collect::<Vec<_>>().join(", "));
+        }
+        for (name, component) in &self.error_components {
+            schemas.insert(name.clone(), component.schema.clone());
+        }
+        for (nested_name, nested_schema) in self.error_components.values().flat_map(|component| &component.nested) {
+            schemas.entry(nested_name.clone()).or_insert_with(|| nested_schema.clone());
+        }
+
         let mut paths = HashMap::new();
+        let mut envelopes = Vec::new();
+        let mut bulk_items = Vec::new();
 
         if self.has_explicit_routes() {
             let resolved = self.resolve_routes();
@@ -704,20 +3506,165 @@ impl HayaiApp {
                 let full_path = r.full_path();
                 let tags = r.merged_tags();
                 let sec = r.merged_security();
-                let operation = Self::build_operation(route, tags, &sec);
+                let mut responses = self.global_responses.clone();
+                for (status, description) in &r.extra_responses {
+                    merge_response(&mut responses, *status, description);
+                }
+                let mut operation = Self::build_operation(route, tags, &sec, &responses);
+                operation.operation_id = Some(operation_ids.get(route, &full_path).to_string());
+                bulk_items.extend(Self::bulk_item_component(route));
+                if let Some(config) = self.response_envelope.as_ref().filter(|_| !route.no_envelope) {
+                    envelopes.extend(envelope::wrap_operation(config, &mut operation));
+                }
                 let path_item = paths.entry(full_path).or_insert_with(HashMap::new);
-                path_item.insert(route.method.to_lowercase(), operation);
+                path_item.insert(versioning::operation_key(route.method, route.version), operation);
             }
         } else {
             for route in inventory::iter::<&RouteInfo> {
                 let tags: Vec<String> = route.tags.iter().map(|s| s.to_string()).collect();
+                #[allow(clippy::iter_cloned_collect)]
                 let sec: Vec<&str> = route.security.iter().copied().collect();
-                let operation = Self::build_operation(route, tags, &sec);
+                let mut operation = Self::build_operation(route, tags, &sec, &self.global_responses);
+                operation.operation_id = Some(operation_ids.get(route, route.path).to_string());
+                bulk_items.extend(Self::bulk_item_component(route));
+                if let Some(config) = self.response_envelope.as_ref().filter(|_| !route.no_envelope) {
+                    envelopes.extend(envelope::wrap_operation(config, &mut operation));
+                }
                 let path_item = paths.entry(route.path.to_string()).or_insert_with(HashMap::new);
-                path_item.insert(route.method.to_lowercase(), operation);
+                path_item.insert(versioning::operation_key(route.method, route.version), operation);
+            }
+        }
+
+        let taken: BTreeSet<&String> = bulk_items.iter().map(|(name, _)| name).filter(|name| schemas.contains_key(*name)).collect();
+        if !taken.is_empty() {
+            panic!("Bulk item component names are taken by models: {}", taken.into_iter().cloned().collect::<Vec<_>>().join(", "));
+        }
+        schemas.extend(bulk_items);
+
+        if self.response_envelope.is_some() {
+            envelopes.push((envelope::META_COMPONENT.to_string(), envelope::meta_schema()));
+            let taken: BTreeSet<&String> = envelopes.iter().map(|(name, _)| name).filter(|name| schemas.contains_key(*name)).collect();
+            if !taken.is_empty() {
+                panic!("Envelope component names are taken by models: {}", taken.into_iter().cloned().collect::<Vec<_>>().join(", "));
+            }
+            schemas.extend(envelopes);
+        }
+
+        // Links may name their target by handler; the spec names it by operationId
+        for response in paths.values_mut().flat_map(|item| item.values_mut()).flat_map(|op| op.responses.values_mut()) {
+            for link in &mut response.links {
+                if let Some(id) = operation_ids.resolve(link.operation_id) {
+                    link.operation_id = id;
+                }
+            }
+        }
+
+        if let Some(limit) = self.default_timeout {
+            for operation in paths.values_mut().flat_map(|item| item.values_mut()) {
+                if !operation.extensions.iter().any(|(key, _)| key == timeout::TIMEOUT_EXTENSION) {
+                    operation.extensions.push((timeout::TIMEOUT_EXTENSION.to_string(), serde_json::json!(limit.as_millis() as u64)));
+                }
+            }
+        }
+
+        if let Some((status, _)) = self.validation_response {
+            let schema_ref = match self.error_schemas.iter().find(|(s, _)| *s == status.as_u16()) {
+                Some((_, name)) => serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) }),
+                None => serde_json::json!({ "type": "object" }),
+            };
+            for operation in paths.values_mut().flat_map(|item| item.values_mut()) {
+                if operation.responses.remove("422").is_some() {
+                    operation.responses.insert(status.as_u16().to_string(), openapi::ResponseDef {
+                        description: "Validation Failed".to_string(),
+                        schema_ref: Some(schema_ref.clone()),
+                        headers: HashMap::new(),
+                        links: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        // App-wide error components replace the default ApiError reference for their status
+        let api_error_ref = serde_json::json!({ "$ref": "#/components/schemas/ApiError" });
+        for (status, name) in &self.error_schemas {
+            let schema_ref = serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) });
+            for operation in paths.values_mut().flat_map(|item| item.values_mut()) {
+                if let Some(resp) = operation.responses.get_mut(&status.to_string()) {
+                    if resp.schema_ref.as_ref() == Some(&api_error_ref) {
+                        resp.schema_ref = Some(schema_ref.clone());
+                    }
+                }
+            }
+        }
+
+        if self.strict_bodies {
+            let body_types: Vec<String> = if self.has_explicit_routes() {
+                self.resolve_routes().iter().map(|r| r.route_info.component_name(r.route_info.body_type_name)).collect()
+            } else {
+                inventory::iter::<&RouteInfo>.into_iter().map(|r| r.component_name(r.body_type_name)).collect()
+            };
+            let strict = self.strict_body_properties();
+            for name in body_types.iter().filter(|name| strict.contains_key(*name)) {
+                if let Some(schema) = schemas.get_mut(name) {
+                    schema.additional_properties = Some(false);
+                }
             }
         }
 
+        for operation in paths.values_mut().flat_map(|item| item.values_mut()) {
+            let successes = operation.responses.iter_mut().filter(|(status, _)| status.starts_with('2'));
+            for (_, response) in successes {
+                for (name, header) in &self.global_response_headers {
+                    response.headers.entry(name.clone()).or_insert_with(|| header.clone());
+                }
+            }
+        }
+
+        // Complete request body examples, built once the component schemas are final
+        for operation in paths.values_mut().flat_map(|item| item.values_mut()) {
+            let Some(body) = &mut operation.request_body else { continue };
+            let component = body.schema.get("$ref")
+                .and_then(|r| r.as_str())
+                .and_then(|r| r.strip_prefix("#/components/schemas/"))
+                .and_then(|name| schemas.get(name));
+            if let Some(schema) = component {
+                body.example = Some(openapi::example_for_schema(schema, &schemas, self.example_placeholder));
+            }
+        }
+
+        versioning::nest_versions(&mut paths);
+
+        // Named bodies move to `components.requestBodies`, their operations keep a `$ref`
+        let mut operations: Vec<(&String, &String, &mut openapi::Operation)> = paths.iter_mut()
+            .flat_map(|(path, item)| item.iter_mut().map(move |(method, operation)| (path, method, operation)))
+            .collect();
+        operations.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        let mut request_bodies: HashMap<String, (String, openapi::RequestBody)> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (path, method, operation) in operations {
+            let Some(body) = &mut operation.request_body else { continue };
+            if body.component.is_none() && self.reuse_request_bodies {
+                body.component = body.schema.get("$ref")
+                    .and_then(|r| r.as_str())
+                    .and_then(|r| r.strip_prefix("#/components/schemas/"))
+                    .map(str::to_string);
+            }
+            let Some(name) = &body.component else { continue };
+            let location = format!("{} {}", method.to_uppercase(), path);
+            match request_bodies.get(name) {
+                Some((first, existing)) if existing.definition_json() != body.definition_json() => {
+                    conflicts.push(format!("{} ({} and {})", name, first, location));
+                }
+                Some(_) => {}
+                None => {
+                    request_bodies.insert(name.clone(), (location, body.clone()));
+                }
+            }
+        }
+        if !conflicts.is_empty() {
+            panic!("Request body component defined differently: {}; give one of them its own #[request_body(name = \"..\")]", conflicts.join(", "));
+        }
+
         openapi::OpenApiSpec {
             openapi: "3.1.0".to_string(),
             info: openapi::Info {
@@ -731,6 +3678,7 @@ impl HayaiApp {
             paths,
             schemas,
             security_schemes: self.security_schemes.clone(),
+            request_bodies: request_bodies.into_iter().map(|(name, (_, body))| (name, body)).collect(),
         }
     }
 }
@@ -746,6 +3694,7 @@ impl openapi::OpenApiSpec {
         struct RoutePathInfo {
             spec_path: String,
             method: String,
+            version: Option<u32>,
             query_params_fn: Option<fn() -> Vec<openapi::DynParameter>>,
         }
 
@@ -759,6 +3708,7 @@ impl openapi::OpenApiSpec {
                         route_paths.push(RoutePathInfo {
                             spec_path: r.full_path(),
                             method: r.route_info.method.to_lowercase(),
+                            version: r.route_info.version,
                             query_params_fn: Some(qfn),
                         });
                     }
@@ -770,6 +3720,7 @@ impl openapi::OpenApiSpec {
                     route_paths.push(RoutePathInfo {
                         spec_path: route.path.to_string(),
                         method: route.method.to_lowercase(),
+                        version: route.version,
                         query_params_fn: Some(qfn),
                     });
                 }
@@ -781,7 +3732,13 @@ impl openapi::OpenApiSpec {
                 let dyn_params = qfn();
                 if !dyn_params.is_empty() {
                     let escaped = rp.spec_path.replace('~', "~0").replace('/', "~1");
-                    let pointer = format!("/paths/{}/{}", escaped, rp.method);
+                    let mut pointer = format!("/paths/{}/{}", escaped, rp.method);
+                    // Older versions are nested in the latest version's operation
+                    if let Some(version) = rp.version {
+                        if val.pointer(&pointer).and_then(|op| op.get(versioning::VERSION_EXTENSION)) != Some(&serde_json::json!(version)) {
+                            pointer = format!("{}/{}/{}", pointer, versioning::VERSIONS_EXTENSION, version);
+                        }
+                    }
                     if let Some(op) = val.pointer_mut(&pointer) {
                         let params = op.get("parameters")
                             .and_then(|v| v.as_array())
@@ -789,22 +3746,7 @@ impl openapi::OpenApiSpec {
                             .unwrap_or_default();
                         let mut all_params = params;
                         for dp in &dyn_params {
-                            let mut schema = serde_json::json!({ "type": dp.schema_type });
-                            if let Some(v) = dp.minimum { schema["minimum"] = serde_json::json!(v); }
-                            if let Some(v) = dp.maximum { schema["maximum"] = serde_json::json!(v); }
-                            if let Some(v) = dp.min_length { schema["minLength"] = serde_json::json!(v); }
-                            if let Some(v) = dp.max_length { schema["maxLength"] = serde_json::json!(v); }
-                            if let Some(v) = &dp.pattern { schema["pattern"] = serde_json::json!(v); }
-                            let mut param = serde_json::json!({
-                                "name": dp.name,
-                                "in": dp.location,
-                                "required": dp.required,
-                                "schema": schema
-                            });
-                            if let Some(desc) = &dp.description {
-                                param["description"] = serde_json::Value::String(desc.clone());
-                            }
-                            all_params.push(param);
+                            all_params.push(serde_json::to_value(dp).unwrap());
                         }
                         op["parameters"] = serde_json::Value::Array(all_params);
                     }
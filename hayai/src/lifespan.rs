@@ -0,0 +1,136 @@
+//! Startup and shutdown callbacks, see [`HayaiApp::on_startup`](crate::HayaiApp::on_startup).
+//!
+//! Startup callbacks run one after another in registration order before the server
+//! accepts connections; shutdown callbacks run in reverse order once it has stopped.
+//! Every callback receives the same [`LifespanSharedState`], so a pool inserted by the
+//! first is visible to the second, and startup values become `Dep<T>`s for handlers.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Error type callbacks may fail with
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+type Callback = Box<dyn FnOnce(LifespanSharedState) -> Pin<Box<dyn Future<Output = Result<(), BoxError>> + Send>> + Send>;
+
+/// Values shared between lifespan callbacks, keyed by type
+#[derive(Clone, Default)]
+pub struct LifespanSharedState {
+    values: Arc<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl LifespanSharedState {
+    /// Store a value, replacing any earlier one of the same type
+    pub fn insert<T: 'static + Send + Sync>(&self, value: T) {
+        self.values.lock().unwrap().insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    pub fn get<T: 'static + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.values.lock().unwrap()
+            .get(&TypeId::of::<T>())
+            .and_then(|v| v.clone().downcast::<T>().ok())
+    }
+
+    pub(crate) fn values(&self) -> HashMap<TypeId, Arc<dyn Any + Send + Sync>> {
+        self.values.lock().unwrap().clone()
+    }
+}
+
+/// A startup or shutdown callback, optionally named for logs and errors.
+///
+/// Plain closures convert into unnamed callbacks, which are reported as `startup#1`,
+/// `shutdown#2`, ... by registration position.
+pub struct Lifespan {
+    name: Option<String>,
+    callback: Callback,
+}
+
+impl Lifespan {
+    /// A callback reported as `name` in logs and in a [`LifespanError`]
+    ///
+    /// ```ignore
+    /// app.on_startup(Lifespan::named("db", |state| async move {
+    ///     state.insert(Pool::connect(URL).await?);
+    ///     Ok(())
+    /// }))
+    /// ```
+    pub fn named<F, Fut>(name: impl Into<String>, callback: F) -> Self
+    where
+        F: FnOnce(LifespanSharedState) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), BoxError>> + Send + 'static,
+    {
+        Self { name: Some(name.into()), callback: Box::new(move |state| Box::pin(callback(state))) }
+    }
+}
+
+impl<F, Fut> From<F> for Lifespan
+where
+    F: FnOnce(LifespanSharedState) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), BoxError>> + Send + 'static,
+{
+    fn from(callback: F) -> Self {
+        Self { name: None, callback: Box::new(move |state| Box::pin(callback(state))) }
+    }
+}
+
+/// A failed lifespan callback
+#[derive(Debug)]
+pub struct LifespanError {
+    /// `"startup"` or `"shutdown"`
+    pub phase: &'static str,
+    /// The callback's [`Lifespan::named`] name, or `startup#N`/`shutdown#N`
+    pub callback: String,
+    pub source: BoxError,
+}
+
+impl std::fmt::Display for LifespanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} callback `{}` failed: {}", self.phase, self.callback, self.source)
+    }
+}
+
+impl std::error::Error for LifespanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Run startup callbacks in registration order, stopping at the first failure
+pub(crate) async fn run_startup(callbacks: Vec<Lifespan>, state: &LifespanSharedState) -> Result<(), LifespanError> {
+    for (i, lifespan) in callbacks.into_iter().enumerate() {
+        let name = lifespan.name.unwrap_or_else(|| format!("startup#{}", i + 1));
+        let started = Instant::now();
+        tracing::info!(callback = %name, "running startup callback");
+        if let Err(source) = (lifespan.callback)(state.clone()).await {
+            tracing::error!(callback = %name, error = %source, "startup callback failed");
+            return Err(LifespanError { phase: "startup", callback: name, source });
+        }
+        tracing::info!(callback = %name, elapsed_ms = started.elapsed().as_millis() as u64, "startup callback finished");
+    }
+    Ok(())
+}
+
+/// Run shutdown callbacks in reverse registration order. A failure is logged and the
+/// remaining callbacks still run; the first failure is returned.
+pub(crate) async fn run_shutdown(callbacks: Vec<Lifespan>, state: &LifespanSharedState) -> Result<(), LifespanError> {
+    let mut first_error = None;
+    for (i, lifespan) in callbacks.into_iter().enumerate().rev() {
+        let name = lifespan.name.unwrap_or_else(|| format!("shutdown#{}", i + 1));
+        let started = Instant::now();
+        tracing::info!(callback = %name, "running shutdown callback");
+        match (lifespan.callback)(state.clone()).await {
+            Ok(()) => {
+                tracing::info!(callback = %name, elapsed_ms = started.elapsed().as_millis() as u64, "shutdown callback finished");
+            }
+            Err(source) => {
+                tracing::error!(callback = %name, error = %source, "shutdown callback failed");
+                first_error.get_or_insert(LifespanError { phase: "shutdown", callback: name, source });
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
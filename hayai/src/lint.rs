@@ -0,0 +1,110 @@
+//! Checks that `#[validate]` and `#[schema]` attributes apply to the fields they're on.
+//!
+//! Some combinations compile but do nothing useful: `min_length` on a `Vec` documents a
+//! `minLength` no array has, `min_items` on a `String` one no string has, and attributes on
+//! a field whose serialized name the schema doesn't know (a `#[serde(flatten)]` field, or a
+//! rename the model macro didn't see) are dropped from the document altogether.
+//! [`check_models`] finds them in every registered model; [`HayaiApp::into_router`](crate::HayaiApp::into_router)
+//! logs them when it builds the spec anyway, or fails with
+//! [`HayaiApp::strict_models`](crate::HayaiApp::strict_models).
+
+use crate::openapi::{Property, PropertyPatch, Schema};
+use crate::SchemaInfo;
+use std::collections::HashMap;
+
+/// An attribute that doesn't apply to its field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintIssue {
+    /// A string constraint (`min_length`, `max_length`, `pattern`, `email`) or numeric one
+    /// (`minimum`, `maximum`) on a property of another JSON type
+    TypeMismatch {
+        model: String,
+        field: String,
+        constraint: &'static str,
+        json_type: String,
+    },
+    /// `min_items` on a property that isn't an array
+    ItemsOnNonArray {
+        model: String,
+        field: String,
+        json_type: String,
+    },
+    /// Attributes recorded for a property name the schema doesn't have
+    UnknownField {
+        model: String,
+        field: String,
+    },
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintIssue::TypeMismatch { model, field, constraint, json_type } => {
+                write!(f, "{}.{}: `{}` doesn't apply to {} values", model, field, constraint, json_type)
+            }
+            LintIssue::ItemsOnNonArray { model, field, json_type } => {
+                write!(f, "{}.{}: `min_items` applies to arrays, not {} values", model, field, json_type)
+            }
+            LintIssue::UnknownField { model, field } => {
+                write!(f, "{}.{}: no such property after serde renaming; its attributes are ignored", model, field)
+            }
+        }
+    }
+}
+
+/// Issues in every registered model, ordered by model and field
+pub fn check_models() -> Vec<LintIssue> {
+    let mut issues: Vec<LintIssue> = inventory::iter::<SchemaInfo>.into_iter()
+        .flat_map(|info| check_model(&info.component_name(), &info.schema(), &info.patches()))
+        .collect();
+    issues.sort_by_key(ToString::to_string);
+    issues.dedup();
+    issues
+}
+
+/// Issues in one model's `patches`, checked against its final `schema`
+pub fn check_model(model: &str, schema: &Schema, patches: &HashMap<String, PropertyPatch>) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    for (field, patch) in patches {
+        if *patch == PropertyPatch::default() {
+            continue;
+        }
+        let Some(prop) = schema.properties.get(field) else {
+            issues.push(LintIssue::UnknownField { model: model.to_string(), field: field.clone() });
+            continue;
+        };
+        let json_type = json_type(prop);
+        // Decimals are strings or numbers depending on `as_number`, and take either kind
+        let decimal = prop.format.as_deref() == Some("decimal");
+        let string = json_type == "string" || decimal;
+        let number = json_type == "integer" || json_type == "number" || decimal || patch.string_encoded;
+        let constraints = [
+            ("min_length", patch.min_length.is_some(), string),
+            ("max_length", patch.max_length.is_some(), string),
+            ("pattern", patch.pattern.is_some(), string),
+            ("email", patch.format.as_deref() == Some("email"), string),
+            ("minimum", patch.minimum.is_some(), number),
+            ("maximum", patch.maximum.is_some(), number),
+        ];
+        for (constraint, set, applies) in constraints {
+            if set && !applies {
+                issues.push(LintIssue::TypeMismatch {
+                    model: model.to_string(),
+                    field: field.clone(),
+                    constraint,
+                    json_type: json_type.to_string(),
+                });
+            }
+        }
+        if patch.min_items.is_some() && json_type != "array" {
+            issues.push(LintIssue::ItemsOnNonArray { model: model.to_string(), field: field.clone(), json_type: json_type.to_string() });
+        }
+    }
+    issues.sort_by_key(ToString::to_string);
+    issues
+}
+
+/// The property's JSON type, `object` for references to other models
+fn json_type(prop: &Property) -> &str {
+    if prop.ref_path.is_some() { "object" } else { &prop.type_name }
+}
@@ -0,0 +1,145 @@
+//! The locale a request is served in, see [`HayaiApp::locales`](crate::HayaiApp::locales).
+//!
+//! Each request is matched against the app's supported locales, first by the query
+//! parameter named with [`HayaiApp::locale_query`](crate::HayaiApp::locale_query), then the
+//! cookie named with [`HayaiApp::locale_cookie`](crate::HayaiApp::locale_cookie), then the
+//! `Accept-Language` ranges by quality, falling back to the default. A value naming no
+//! supported locale is skipped. Handlers read the result through a [`Locale`] argument,
+//! responses carry it as `Content-Language` unless they already have one, and a
+//! [`MessageCatalog`](crate::i18n::MessageCatalog) renders errors in it.
+//!
+//! ```ignore
+//! HayaiApp::new().locales(&["en", "fr", "ja"], "en").locale_query("lang").locale_cookie("lang")
+//!
+//! #[get("/invoices/{id}")]
+//! async fn get_invoice(id: i64, locale: Locale) -> Invoice { ... }
+//! ```
+
+use crate::ApiError;
+use axum::extract::{FromRequestParts, Request};
+use axum::http::header::{self, HeaderMap, HeaderValue};
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::sync::Arc;
+
+/// The locale a request is served in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    /// The supported locale chosen, as given to [`HayaiApp::locales`](crate::HayaiApp::locales)
+    pub tag: String,
+    /// The request's `Accept-Language` as sent
+    pub accept_language: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Locale {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts.extensions.get::<Locale>().cloned()
+            .ok_or_else(|| ApiError::internal("No locale for this request; list the supported ones with HayaiApp::locales".into()))
+    }
+}
+
+/// The supported locales and where a request may name one
+#[derive(Debug, Clone)]
+pub(crate) struct Locales {
+    pub(crate) supported: Vec<String>,
+    pub(crate) default: String,
+    pub(crate) query: Option<String>,
+    pub(crate) cookie: Option<String>,
+}
+
+impl Locales {
+    fn resolve(&self, query: Option<&str>, headers: &HeaderMap) -> Locale {
+        let supported = || self.supported.iter().map(String::as_str);
+        let from_query = self.query.as_deref().and_then(|name| {
+            form_urlencoded::parse(query.unwrap_or("").as_bytes()).find(|(k, _)| k == name).map(|(_, v)| v.into_owned())
+        });
+        let from_cookie = self.cookie.as_deref().and_then(|name| cookie(headers, name));
+        let accept_language = headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok());
+
+        let tag = from_query.iter().chain(&from_cookie)
+            .find_map(|tag| matching(tag, supported()))
+            .or_else(|| best_match(accept_language.unwrap_or(""), supported()))
+            .unwrap_or(self.default.as_str());
+        Locale { tag: tag.to_string(), accept_language: accept_language.map(str::to_string) }
+    }
+
+    /// Document the query parameter and cookie, as optional, next to the `Accept-Language`
+    /// header of the operations that take a [`Locale`]
+    pub(crate) fn document(&self, spec: &mut serde_json::Value) {
+        let Some(paths) = spec.get_mut("paths").and_then(|p| p.as_object_mut()) else { return };
+        let sources = [("query", &self.query), ("cookie", &self.cookie)];
+        for operation in paths.values_mut().filter_map(|p| p.as_object_mut()).flat_map(|p| p.values_mut()) {
+            let Some(parameters) = operation.get_mut("parameters").and_then(|p| p.as_array_mut()) else { continue };
+            let takes_locale = parameters.iter().any(|p| p["in"] == "header" && p["name"] == "Accept-Language");
+            if !takes_locale {
+                continue;
+            }
+            for (location, name) in sources {
+                let Some(name) = name else { continue };
+                parameters.push(serde_json::json!({
+                    "name": name,
+                    "in": location,
+                    "required": false,
+                    "description": format!("Locale to answer in, over `Accept-Language`: one of {}", self.supported.join(", ")),
+                    "schema": { "type": "string", "enum": self.supported },
+                }));
+            }
+        }
+    }
+}
+
+/// The value of the `name` cookie
+fn cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get_all(header::COOKIE).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            (k.trim() == name).then(|| v.trim().trim_matches('"').to_string())
+        })
+}
+
+/// The locale serving `tag`: the same tag, then its primary language (`fr` for `fr-CA`),
+/// then another region of it
+pub(crate) fn matching<'a>(tag: &str, mut locales: impl Iterator<Item = &'a str> + Clone) -> Option<&'a str> {
+    let primary = tag.split('-').next().unwrap_or_default();
+    let same_language = |locale: &str| locale.split('-').next().is_some_and(|p| p.eq_ignore_ascii_case(primary));
+    locales.clone().find(|locale| locale.eq_ignore_ascii_case(tag))
+        .or_else(|| locales.clone().find(|locale| locale.eq_ignore_ascii_case(primary)))
+        .or_else(|| locales.find(|locale| same_language(locale)))
+}
+
+/// The locale serving the first `Accept-Language` range, by quality, that one serves; `*`
+/// and what follows it leave the choice to the caller
+pub(crate) fn best_match<'a>(accept_language: &str, locales: impl Iterator<Item = &'a str> + Clone) -> Option<&'a str> {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts.find_map(|p| p.trim().strip_prefix("q=")).map_or(Some(1.0), |q| q.trim().parse().ok())?;
+            Some((tag, quality)).filter(|(tag, q)| !tag.is_empty() && *q > 0.0)
+        })
+        .collect();
+    // Stable, so equal qualities keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter()
+        .take_while(|(tag, _)| *tag != "*")
+        .find_map(|(tag, _)| matching(tag, locales.clone()))
+}
+
+/// Resolve the request's [`Locale`] and answer with it as `Content-Language`
+pub(crate) async fn resolve_locale(locales: Arc<Locales>, mut req: Request, next: Next) -> Response {
+    let locale = locales.resolve(req.uri().query(), req.headers());
+    let content_language = HeaderValue::from_str(&locale.tag).ok();
+    req.extensions_mut().insert(locale);
+    let mut response = next.run(req).await;
+    if let Some(value) = content_language {
+        response.headers_mut().entry(header::CONTENT_LANGUAGE).or_insert(value);
+    }
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept-language"));
+    response
+}
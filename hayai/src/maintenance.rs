@@ -0,0 +1,75 @@
+//! Maintenance mode, see [`HayaiApp::maintenance`](crate::HayaiApp::maintenance).
+//!
+//! While [`MaintenanceConfig::enabled_flag`] is set, every request is answered with 503, the
+//! configured message in the `ApiError` body, and `Retry-After`. `/healthz`, `/readyz`, the
+//! drain endpoint and the [`allow_paths`](MaintenanceConfig::allow_paths) prefixes keep
+//! being served. The flag is read on each request, so anything holding it can switch
+//! maintenance on and off without a restart: an admin route, a flag provider's poller, a
+//! signal handler.
+//!
+//! ```ignore
+//! let maintenance = Arc::new(AtomicBool::new(false));
+//! HayaiApp::new()
+//!     .dep(MaintenanceSwitch(maintenance.clone()))
+//!     .maintenance(MaintenanceConfig {
+//!         enabled_flag: maintenance,
+//!         retry_after: Duration::from_secs(600),
+//!         allow_paths: vec!["/admin".to_string()],
+//!         ..Default::default()
+//!     })
+//! ```
+
+use crate::ApiError;
+use axum::extract::Request;
+use axum::http::{header, HeaderValue};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Paths served during maintenance whatever the config says
+const ALWAYS_ALLOWED: [&str; 2] = ["/healthz", "/readyz"];
+
+/// When the app is in maintenance and what clients are told
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// Maintenance is on while this is `true`
+    pub enabled_flag: Arc<AtomicBool>,
+    /// The `error` of the 503 body
+    pub message: String,
+    /// Sent as `Retry-After`, in whole seconds rounded up
+    pub retry_after: Duration,
+    /// Path prefixes still served, e.g. `/admin` for `/admin` and everything under it
+    pub allow_paths: Vec<String>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled_flag: Arc::new(AtomicBool::new(false)),
+            message: "The API is down for maintenance".to_string(),
+            retry_after: Duration::from_secs(300),
+            allow_paths: Vec::new(),
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub(crate) fn allows(&self, path: &str) -> bool {
+        ALWAYS_ALLOWED.contains(&path) || self.allow_paths.iter().any(|prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            path.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+pub(crate) async fn enforce(config: Arc<MaintenanceConfig>, req: Request, next: Next) -> Response {
+    if !config.enabled_flag.load(Ordering::Relaxed) || config.allows(req.uri().path()) {
+        return next.run(req).await;
+    }
+    let mut response = ApiError::service_unavailable(config.message.clone()).into_response();
+    let seconds = config.retry_after.as_secs() + u64::from(config.retry_after.subsec_nanos() > 0);
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+    response
+}
@@ -0,0 +1,78 @@
+//! A machine-readable list of an app's routes for infrastructure tooling, see
+//! [`HayaiApp::route_manifest`](crate::HayaiApp::route_manifest).
+//!
+//! Gateway routes and WAF rules need each operation's method, full path, required auth
+//! and limits; the OpenAPI spec leaves the limits out and spreads the rest over
+//! components. The manifest lists them flat, one entry per mounted route, ordered by path,
+//! method and version so the same app always writes the same bytes. `manifest_version`
+//! changes whenever a field changes meaning or goes away.
+//!
+//! ```json
+//! {
+//!   "manifest_version": 1,
+//!   "routes": [
+//!     {
+//!       "method": "GET",
+//!       "path": "/api/items/{id}",
+//!       "operation_id": "get_item",
+//!       "handler": "get_item",
+//!       "security": ["bearer"],
+//!       "tags": ["items"],
+//!       "timeout_ms": 5000
+//!     }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// The manifest format written by this release
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Every route an app mounts, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteManifest {
+    pub manifest_version: u32,
+    pub routes: Vec<ManifestRoute>,
+}
+
+/// One mounted route; limits are left out when the route has none
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestRoute {
+    /// Upper-case HTTP method
+    pub method: String,
+    /// Path template under all router prefixes, e.g. `/api/items/{id}`
+    pub path: String,
+    pub operation_id: String,
+    pub handler: String,
+    /// Security schemes the caller must satisfy; empty for public routes
+    pub security: Vec<String>,
+    pub tags: Vec<String>,
+    /// `#[version(2)]` of versioned handlers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// `#[timeout(..)]`, or the app's default timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Requests handled at once, from `#[concurrency_limit(..)]` or the app's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_limit: Option<usize>,
+    /// Largest request body accepted, for routes taking one when the app sets a limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_limit: Option<usize>,
+    /// `#[cache(ttl = ..)]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_ms: Option<u64>,
+    /// `#[feature_flag(..)]`: the flag that switches the route on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature_flag: Option<String>,
+}
+
+impl RouteManifest {
+    /// The manifest as indented JSON ending in a newline, as [`HayaiApp::write_manifest`](crate::HayaiApp::write_manifest) writes it
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("a manifest serializes");
+        json.push('\n');
+        json
+    }
+}
@@ -12,6 +12,9 @@ pub struct OpenApiSpec {
     pub schemas: HashMap<String, Schema>,
     #[serde(skip)]
     pub security_schemes: HashMap<String, SecurityScheme>,
+    /// Request bodies shared by name, serialized under `components.requestBodies`
+    #[serde(skip)]
+    pub request_bodies: HashMap<String, RequestBody>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -70,6 +73,13 @@ impl OpenApiSpec {
             val["components"]["securitySchemes"] = serde_json::Value::Object(schemes);
         }
 
+        if !self.request_bodies.is_empty() {
+            let bodies: serde_json::Map<_, _> = self.request_bodies.iter()
+                .map(|(name, body)| (name.clone(), body.definition_json()))
+                .collect();
+            val["components"]["requestBodies"] = serde_json::Value::Object(bodies);
+        }
+
         if let Some(paths) = val["paths"].as_object_mut() {
             for (path, methods) in &self.paths {
                 let mut path_obj = serde_json::Map::new();
@@ -90,6 +100,147 @@ impl OpenApiSpec {
 
         val
     }
+
+    /// Structural problems in the generated document, see [`validate_spec`]
+    pub fn validate(&self) -> Vec<SpecIssue> {
+        validate_spec(&self.to_json())
+    }
+}
+
+/// Collection request bodies, documented around their element's schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyCollection {
+    /// `Vec<T>`
+    List,
+    /// `HashMap<String, T>` or `BTreeMap<String, T>`
+    Map,
+}
+
+impl BodyCollection {
+    pub fn schema(self, item: serde_json::Value) -> serde_json::Value {
+        match self {
+            BodyCollection::List => serde_json::json!({ "type": "array", "items": item }),
+            BodyCollection::Map => serde_json::json!({ "type": "object", "additionalProperties": item }),
+        }
+    }
+}
+
+/// Inline schema of a primitive or untyped JSON (`Value`) type; `None` for types documented
+/// as components
+pub fn inline_schema(type_name: &str) -> Option<serde_json::Value> {
+    let schema = match type_name {
+        "Value" => serde_json::json!({ "type": "object", "additionalProperties": true }),
+        "String" | "str" => serde_json::json!({ "type": "string" }),
+        "bool" => serde_json::json!({ "type": "boolean" }),
+        "f32" | "f64" => serde_json::json!({ "type": "number" }),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => {
+            serde_json::json!({ "type": "integer" })
+        }
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// Schema of a type inside a map, tuple or nullable response element: a `$ref` for models,
+/// inline for primitives, and open for other types, which have no component to point to
+pub fn type_schema(type_name: &str, model_component: Option<String>) -> serde_json::Value {
+    match model_component {
+        Some(component) => serde_json::json!({ "$ref": format!("#/components/schemas/{}", component) }),
+        None => inline_schema(type_name).unwrap_or_else(|| serde_json::json!({})),
+    }
+}
+
+/// `schema` or `null`, the form `Option<T>` fields are documented in
+pub fn nullable(schema: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+/// A problem found by [`validate_spec`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecIssue {
+    /// The operation (`GET /users/{id}`) or component (`components.schemas.User`)
+    pub location: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SpecIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Check a serialized OpenAPI document for what client generators trip over: `$ref`s that
+/// don't resolve, operations without responses, path templates and `in: path` parameters
+/// that disagree, and security requirements naming undeclared schemes
+pub fn validate_spec(spec: &serde_json::Value) -> Vec<SpecIssue> {
+    fn collect_refs<'a>(value: &'a serde_json::Value, refs: &mut Vec<&'a str>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(r) = map.get("$ref").and_then(|r| r.as_str()) {
+                    refs.push(r);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+    let dangling = |node: &serde_json::Value, location: &str, issues: &mut Vec<SpecIssue>| {
+        let mut refs = Vec::new();
+        collect_refs(node, &mut refs);
+        for r in refs {
+            let resolves = r.strip_prefix('#').is_some_and(|pointer| spec.pointer(pointer).is_some());
+            if !resolves {
+                issues.push(SpecIssue { location: location.to_string(), message: format!("$ref {} does not resolve", r) });
+            }
+        }
+    };
+
+    let mut issues = Vec::new();
+    let schemes = spec.pointer("/components/securitySchemes").and_then(|s| s.as_object());
+    for (path, item) in spec["paths"].as_object().into_iter().flatten() {
+        let placeholders: Vec<&str> = path.split('/')
+            .filter_map(|s| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+            .collect();
+        for (method, operation) in item.as_object().into_iter().flatten() {
+            let location = format!("{} {}", method.to_uppercase(), path);
+            dangling(operation, &location, &mut issues);
+            if operation["responses"].as_object().is_none_or(|r| r.is_empty()) {
+                issues.push(SpecIssue { location: location.clone(), message: "operation has no responses".to_string() });
+            }
+            let path_params: Vec<&str> = operation["parameters"].as_array().into_iter().flatten()
+                .filter(|p| p["in"] == "path")
+                .filter_map(|p| p["name"].as_str())
+                .collect();
+            for name in &placeholders {
+                if !path_params.contains(name) {
+                    issues.push(SpecIssue { location: location.clone(), message: format!("path parameter `{}` is not declared", name) });
+                }
+            }
+            for name in &path_params {
+                if !placeholders.contains(name) {
+                    issues.push(SpecIssue { location: location.clone(), message: format!("parameter `{}` is not in the path template", name) });
+                }
+            }
+            let required: Vec<&String> = operation["security"].as_array().into_iter().flatten()
+                .filter_map(|requirement| requirement.as_object())
+                .flat_map(|requirement| requirement.keys())
+                .collect();
+            for name in required {
+                if !schemes.is_some_and(|s| s.contains_key(name)) {
+                    issues.push(SpecIssue { location: location.clone(), message: format!("security scheme `{}` is not declared", name) });
+                }
+            }
+        }
+    }
+    for (name, schema) in spec.pointer("/components/schemas").and_then(|s| s.as_object()).into_iter().flatten() {
+        dangling(schema, &format!("components.schemas.{}", name), &mut issues);
+    }
+    for (name, body) in spec.pointer("/components/requestBodies").and_then(|s| s.as_object()).into_iter().flatten() {
+        dangling(body, &format!("components.requestBodies.{}", name), &mut issues);
+    }
+    issues.sort_by(|a, b| (&a.location, &a.message).cmp(&(&b.location, &b.message)));
+    issues
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -128,6 +279,12 @@ pub struct Operation {
     pub request_body: Option<RequestBody>,
     pub responses: HashMap<String, ResponseDef>,
     pub security: Vec<HashMap<String, Vec<String>>>,
+    /// Opted out of inherited security with `#[security(none)]`; serialized as `security: []`
+    pub public: bool,
+    /// Media type of the 2xx response bodies when it isn't `application/json`
+    pub produces: Option<String>,
+    /// `x-` vendor extensions, serialized as they are
+    pub extensions: Vec<(String, serde_json::Value)>,
 }
 
 impl Serialize for Operation {
@@ -142,7 +299,7 @@ impl Serialize for Operation {
         if let Some(rb) = &self.request_body {
             map.serialize_entry("requestBody", &rb.to_json_value())?;
         }
-        if !self.security.is_empty() {
+        if !self.security.is_empty() || self.public {
             map.serialize_entry("security", &self.security)?;
         }
         let mut resp = serde_json::Map::new();
@@ -150,20 +307,46 @@ impl Serialize for Operation {
             let mut obj = serde_json::Map::new();
             obj.insert("description".into(), serde_json::Value::String(r.description.clone()));
             if let Some(schema_ref) = &r.schema_ref {
+                let media_type = match &self.produces {
+                    Some(media_type) if code.starts_with('2') => media_type.as_str(),
+                    _ => "application/json",
+                };
                 let content = serde_json::json!({
-                    "application/json": {
+                    media_type: {
                         "schema": schema_ref
                     }
                 });
                 obj.insert("content".into(), content);
             }
+            if !r.headers.is_empty() {
+                let headers: serde_json::Map<_, _> = r.headers.iter()
+                    .map(|(name, header)| (name.clone(), serde_json::json!({
+                        "description": header.description,
+                        "schema": { "type": header.schema_type },
+                    })))
+                    .collect();
+                obj.insert("headers".into(), serde_json::Value::Object(headers));
+            }
+            if !r.links.is_empty() {
+                let links: serde_json::Map<_, _> = r.links.iter()
+                    .map(|link| (link.name.to_string(), link.to_json_value()))
+                    .collect();
+                obj.insert("links".into(), serde_json::Value::Object(links));
+            }
             resp.insert(code.clone(), serde_json::Value::Object(obj));
         }
         map.serialize_entry("responses", &resp)?;
+        for (key, value) in &self.extensions {
+            map.serialize_entry(key, value)?;
+        }
         map.end()
     }
 }
 
+/// A vendor extension from `#[openapi_extension(..)]`: its `x-` name and a function
+/// building its value
+pub type Extension = (&'static str, fn() -> serde_json::Value);
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Parameter {
     pub name: &'static str,
@@ -188,6 +371,29 @@ pub struct DynParameter {
     pub min_length: Option<u32>,
     pub max_length: Option<u32>,
     pub pattern: Option<String>,
+    /// Value used when the parameter is omitted (from `#[serde(default)]`)
+    pub default: Option<serde_json::Value>,
+    /// Item type of an array parameter
+    pub items_type: Option<String>,
+    /// For array parameters: `true` for repeated keys, `false` for comma-separated values
+    pub explode: Option<bool>,
+}
+
+impl DynParameter {
+    /// Fill in constraints and description the schemars schema doesn't carry,
+    /// e.g. from `#[validate(...)]` attributes on an `#[api_model]` query struct
+    pub fn merge_property(&mut self, prop: &Property) {
+        if self.description.is_none() {
+            self.description = prop.description.clone();
+        }
+        self.minimum = self.minimum.or(prop.minimum);
+        self.maximum = self.maximum.or(prop.maximum);
+        self.min_length = self.min_length.or(prop.min_length.map(|v| v as u32));
+        self.max_length = self.max_length.or(prop.max_length.map(|v| v as u32));
+        if self.pattern.is_none() {
+            self.pattern = prop.pattern.clone();
+        }
+    }
 }
 
 impl Serialize for DynParameter {
@@ -203,7 +409,13 @@ impl Serialize for DynParameter {
         if let Some(v) = self.min_length { schema["minLength"] = serde_json::json!(v); }
         if let Some(v) = self.max_length { schema["maxLength"] = serde_json::json!(v); }
         if let Some(v) = &self.pattern { schema["pattern"] = serde_json::json!(v); }
+        if let Some(v) = &self.default { schema["default"] = v.clone(); }
+        if let Some(t) = &self.items_type { schema["items"] = serde_json::json!({"type": t}); }
         map.serialize_entry("schema", &schema)?;
+        if let Some(explode) = self.explode {
+            map.serialize_entry("style", "form")?;
+            map.serialize_entry("explode", &explode)?;
+        }
         if let Some(desc) = &self.description {
             map.serialize_entry("description", desc)?;
         }
@@ -212,14 +424,25 @@ impl Serialize for DynParameter {
 }
 
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SchemaObject {
     #[serde(rename = "type")]
     pub type_name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<&'static str>,
 }
 
 impl SchemaObject {
     pub const fn new_type(t: &'static str) -> Self {
-        Self { type_name: t }
+        Self { type_name: t, minimum: None, maximum: None, min_length: None, max_length: None, pattern: None }
     }
 }
 
@@ -229,49 +452,263 @@ pub struct RequestBody {
     #[serde(skip)]
     pub content_type: String,
     #[serde(skip)]
-    pub schema_ref: String,
+    pub schema: serde_json::Value,
+    /// Complete example body, prefilled in Swagger's "Try it out" editor
+    #[serde(skip)]
+    pub example: Option<serde_json::Value>,
+    #[serde(skip)]
+    pub description: Option<String>,
+    /// Name under `components.requestBodies`; the operation then only holds a `$ref` to it
+    #[serde(skip)]
+    pub component: Option<String>,
 }
 
 impl RequestBody {
+    /// The body as the operation documents it: a `$ref` when it is a component, else inline
     pub fn to_json_value(&self) -> serde_json::Value {
-        serde_json::json!({
+        match &self.component {
+            Some(name) => serde_json::json!({ "$ref": format!("#/components/requestBodies/{}", name) }),
+            None => self.definition_json(),
+        }
+    }
+
+    /// The full Request Body Object, as inlined in the operation or kept in `components`
+    pub fn definition_json(&self) -> serde_json::Value {
+        let mut media = serde_json::json!({ "schema": &self.schema });
+        if let Some(example) = &self.example {
+            media["example"] = example.clone();
+        }
+        let mut body = serde_json::json!({
             "required": self.required,
             "content": {
-                &self.content_type: {
-                    "schema": {
-                        "$ref": &self.schema_ref
-                    }
+                &self.content_type: media
+            }
+        });
+        if let Some(description) = &self.description {
+            body["description"] = serde_json::Value::String(description.clone());
+        }
+        body
+    }
+}
+
+/// Value for a field without an example or default, see
+/// [`HayaiApp::example_placeholders`](crate::HayaiApp::example_placeholders)
+pub type ExamplePlaceholder = fn(&Property) -> serde_json::Value;
+
+/// Type-appropriate placeholder that satisfies the property's constraints
+pub fn default_placeholder(prop: &Property) -> serde_json::Value {
+    match prop.type_name.as_str() {
+        "string" => {
+            let text = match prop.format.as_deref() {
+                Some("email") => "user@example.com",
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("time") => "00:00:00",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("uri") | Some("url") => "https://example.com",
+                Some("ipv4") => "192.0.2.1",
+                Some("ipv6") => "2001:db8::1",
+                // `#[schema(string_encoded)]` integers
+                Some("int64" | "uint64" | "int32" | "uint32") => "0",
+                Some("decimal") => "0.00",
+                _ => "string",
+            };
+            let mut text = text.to_string();
+            if let Some(min) = prop.min_length {
+                while text.chars().count() < min {
+                    text.push('x');
                 }
             }
-        })
+            if let Some(max) = prop.max_length {
+                text = text.chars().take(max).collect();
+            }
+            serde_json::Value::String(text)
+        }
+        "integer" => {
+            let min = prop.minimum.map_or(0.0, |m| m.max(0.0).ceil());
+            serde_json::json!(prop.maximum.map_or(min, |max| min.min(max)) as i64)
+        }
+        "number" => serde_json::json!(prop.minimum.map_or(0.0, |m| m.max(0.0))),
+        "boolean" => serde_json::Value::Bool(false),
+        "array" => serde_json::json!([]),
+        _ => serde_json::json!({}),
     }
 }
 
+/// A complete example of `schema`: field examples first, then defaults, then placeholders.
+/// `$ref`s are resolved against `components`; a type already being expanded becomes `{}`.
+pub fn example_for_schema(schema: &Schema, components: &HashMap<String, Schema>, placeholder: ExamplePlaceholder) -> serde_json::Value {
+    ExampleBuilder { components, placeholder, expanding: Vec::new() }.schema(schema)
+}
+
+struct ExampleBuilder<'a> {
+    components: &'a HashMap<String, Schema>,
+    placeholder: ExamplePlaceholder,
+    expanding: Vec<&'a str>,
+}
+
+impl<'a> ExampleBuilder<'a> {
+    fn schema(&mut self, schema: &'a Schema) -> serde_json::Value {
+        if let Some(first) = schema.enum_values.as_ref().and_then(|values| values.first()) {
+            return serde_json::Value::String(first.clone());
+        }
+        // A union is exemplified by its first variant
+        if let Some((name, variant)) = schema.one_of.first().and_then(|(_, name)| self.components.get_key_value(name)) {
+            if !self.expanding.contains(&name.as_str()) {
+                self.expanding.push(name);
+                let example = self.schema(variant);
+                self.expanding.pop();
+                return example;
+            }
+        }
+        let mut names: Vec<&String> = schema.properties.keys().collect();
+        names.sort();
+        let object = names.into_iter()
+            .map(|name| (name.clone(), self.property(&schema.properties[name])))
+            .collect();
+        serde_json::Value::Object(object)
+    }
+
+    fn property(&mut self, prop: &'a Property) -> serde_json::Value {
+        if let Some(example) = &prop.example {
+            return match prop.type_name.as_str() {
+                "string" => serde_json::Value::String(example.clone()),
+                _ => serde_json::from_str(example).unwrap_or_else(|_| serde_json::Value::String(example.clone())),
+            };
+        }
+        if let Some(default) = &prop.default {
+            return default.clone();
+        }
+        if let Some(ref_path) = &prop.ref_path {
+            let name = ref_path.trim_start_matches("#/components/schemas/");
+            return match self.components.get_key_value(name) {
+                Some((name, schema)) if !self.expanding.contains(&name.as_str()) => {
+                    self.expanding.push(name);
+                    let example = self.schema(schema);
+                    self.expanding.pop();
+                    example
+                }
+                _ => serde_json::json!({}),
+            };
+        }
+        match (&prop.items, &prop.additional_properties) {
+            (Some(items), _) if prop.type_name == "array" => serde_json::json!([self.property(items)]),
+            (_, Some(values)) => serde_json::json!({ "key": self.property(values) }),
+            _ => (self.placeholder)(prop),
+        }
+    }
+}
+
+/// Request body schema for a `Multipart` handler from its `#[multipart_schema(..)]` fields;
+/// `binary` fields are file parts, anything else is used as the JSON schema type.
+pub fn multipart_schema(fields: &[(&str, &str)]) -> serde_json::Value {
+    let mut props = serde_json::Map::new();
+    for (name, kind) in fields {
+        let prop = match *kind {
+            "binary" => serde_json::json!({ "type": "string", "format": "binary" }),
+            other => serde_json::json!({ "type": other }),
+        };
+        props.insert(name.to_string(), prop);
+    }
+    serde_json::json!({ "type": "object", "properties": props })
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ResponseDef {
     pub description: String,
     #[serde(skip)]
     pub schema_ref: Option<serde_json::Value>,
+    /// Documented response headers by name
+    #[serde(skip)]
+    pub headers: HashMap<String, ResponseHeader>,
+    /// Operations this response feeds, declared with `#[link(..)]`
+    #[serde(skip)]
+    pub links: Vec<Link>,
 }
 
-#[derive(Debug, Clone)]
+/// An OpenAPI link from a response to another operation, declared on the handler with
+/// `#[link(name = "GetUserById", operation = "get_user", parameters(id = "$response.body#/id"))]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Link {
+    pub name: &'static str,
+    /// `operationId` of the target, checked against the spec's operations
+    pub operation_id: &'static str,
+    /// Target parameter name and the runtime expression that supplies it
+    pub parameters: &'static [(&'static str, &'static str)],
+}
+
+impl Link {
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let parameters: serde_json::Map<_, _> = self.parameters.iter()
+            .map(|(name, expr)| (name.to_string(), serde_json::Value::String(expr.to_string())))
+            .collect();
+        serde_json::json!({ "operationId": self.operation_id, "parameters": parameters })
+    }
+}
+
+/// A documented response header
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseHeader {
+    /// `string`, `integer`, `number` or `boolean`
+    pub schema_type: String,
+    pub description: String,
+}
+
+impl ResponseHeader {
+    pub fn new(schema_type: &str, description: &str) -> Self {
+        Self { schema_type: schema_type.to_string(), description: description.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Schema {
     pub type_name: String,
     pub properties: HashMap<String, Property>,
     pub required: Vec<String>,
+    pub title: Option<String>,
     pub description: Option<String>,
     pub enum_values: Option<Vec<String>>,
     pub example: Option<String>,
+    /// `Some(false)` documents `additionalProperties: false` (unknown fields rejected)
+    pub additional_properties: Option<bool>,
+    /// Variants of a `oneOf` union as `(discriminator value, component name)`, documented
+    /// instead of the properties
+    pub one_of: Vec<(String, String)>,
+    /// Property whose value names the `one_of` variant
+    pub discriminator: Option<String>,
 }
 
 impl Schema {
     pub fn to_json_value(&self) -> serde_json::Value {
+        if !self.one_of.is_empty() {
+            let refs: Vec<String> = self.one_of.iter().map(|(_, name)| format!("#/components/schemas/{}", name)).collect();
+            let mut obj = serde_json::json!({
+                "oneOf": refs.iter().map(|r| serde_json::json!({ "$ref": r })).collect::<Vec<_>>(),
+            });
+            if let Some(property) = &self.discriminator {
+                let mapping: serde_json::Map<String, serde_json::Value> = self.one_of.iter().zip(&refs)
+                    .map(|((value, _), r)| (value.clone(), serde_json::Value::String(r.clone())))
+                    .collect();
+                obj["discriminator"] = serde_json::json!({ "propertyName": property, "mapping": mapping });
+            }
+            if let Some(title) = &self.title {
+                obj["title"] = serde_json::Value::String(title.clone());
+            }
+            if let Some(desc) = &self.description {
+                obj["description"] = serde_json::Value::String(desc.clone());
+            }
+            return obj;
+        }
+
         // Enum schema
         if let Some(values) = &self.enum_values {
             let mut obj = serde_json::json!({
                 "type": self.type_name,
                 "enum": values,
             });
+            if let Some(title) = &self.title {
+                obj["title"] = serde_json::Value::String(title.clone());
+            }
             if let Some(desc) = &self.description {
                 obj["description"] = serde_json::Value::String(desc.clone());
             }
@@ -289,9 +726,15 @@ impl Schema {
         if !self.required.is_empty() {
             obj["required"] = serde_json::to_value(&self.required).unwrap();
         }
+        if let Some(title) = &self.title {
+            obj["title"] = serde_json::Value::String(title.clone());
+        }
         if let Some(desc) = &self.description {
             obj["description"] = serde_json::Value::String(desc.clone());
         }
+        if let Some(ap) = self.additional_properties {
+            obj["additionalProperties"] = serde_json::Value::Bool(ap);
+        }
         obj
     }
 }
@@ -302,7 +745,7 @@ impl Serialize for Schema {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Property {
     pub type_name: String,
     pub format: Option<String>,
@@ -312,12 +755,17 @@ pub struct Property {
     pub maximum: Option<f64>,
     pub pattern: Option<String>,
     pub min_items: Option<usize>,
+    pub max_items: Option<usize>,
     pub description: Option<String>,
     pub ref_path: Option<String>,
     pub items: Option<Box<Property>>,
     pub nullable: bool,
     pub example: Option<String>,
     pub additional_properties: Option<Box<Property>>,
+    /// Value used when the field is omitted (from `#[serde(default)]`)
+    pub default: Option<serde_json::Value>,
+    /// `#[schema(sensitive)]`: accepted in requests, never sent back
+    pub write_only: bool,
 }
 
 impl Property {
@@ -363,6 +811,14 @@ impl Property {
             obj.insert("example".into(), serde_json::Value::String(example.clone()));
         }
 
+        if let Some(default) = &self.default {
+            obj.insert("default".into(), default.clone());
+        }
+
+        if self.write_only {
+            obj.insert("writeOnly".into(), serde_json::Value::Bool(true));
+        }
+
         serde_json::Value::Object(obj)
     }
 
@@ -388,6 +844,9 @@ impl Property {
         if let Some(v) = self.min_items {
             obj.insert("minItems".into(), serde_json::Value::Number(v.into()));
         }
+        if let Some(v) = self.max_items {
+            obj.insert("maxItems".into(), serde_json::Value::Number(v.into()));
+        }
         if let Some(items) = &self.items {
             obj.insert("items".into(), items.to_json_value());
         }
@@ -403,7 +862,7 @@ impl Serialize for Property {
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PropertyPatch {
     pub min_length: Option<usize>,
     pub max_length: Option<usize>,
@@ -414,30 +873,105 @@ pub struct PropertyPatch {
     pub min_items: Option<usize>,
     pub description: Option<String>,
     pub example: Option<String>,
+    /// `#[schema(required)]`: list the field in `required` even if it is an Option.
+    /// Only the document changes — serde still accepts the field being absent.
+    pub required: bool,
+    /// `#[schema(non_nullable)]`: drop the anyOf-null wrapper on an Option field.
+    /// Only the document changes — serde still emits `null` for `None`.
+    pub non_nullable: bool,
+    /// `#[schema(string_encoded)]`: an integer sent as a JSON string, documented as
+    /// `type: string` with its integer format
+    pub string_encoded: bool,
+    /// `#[schema(sensitive)]`: documented as `writeOnly` and redacted from logged bodies
+    pub sensitive: bool,
+    /// `#[schema(as_number)]`: a `Decimal` sent as a JSON number, documented as
+    /// `type: number` with its `decimal` format
+    pub decimal_as_number: bool,
+}
+
+/// Apply the patches recorded by `HasSchemaPatches` to a generated schema
+pub fn apply_property_patches(schema: &mut Schema, patches: HashMap<String, PropertyPatch>) {
+    for (name, patch) in patches {
+        if let Some(prop) = schema.properties.get_mut(&name) {
+            if patch.min_length.is_some() { prop.min_length = patch.min_length; }
+            if patch.max_length.is_some() { prop.max_length = patch.max_length; }
+            if patch.format.is_some() { prop.format = patch.format; }
+            if patch.minimum.is_some() { prop.minimum = patch.minimum; }
+            if patch.maximum.is_some() { prop.maximum = patch.maximum; }
+            if patch.pattern.is_some() { prop.pattern = patch.pattern.clone(); }
+            if patch.min_items.is_some() { prop.min_items = patch.min_items; }
+            if patch.description.is_some() { prop.description = patch.description.clone(); }
+            if patch.example.is_some() { prop.example = patch.example.clone(); }
+            if patch.non_nullable { prop.nullable = false; }
+            if patch.sensitive { prop.write_only = true; }
+            if patch.decimal_as_number {
+                prop.type_name = "number".to_string();
+            }
+            if patch.string_encoded && prop.type_name == "integer" {
+                prop.type_name = "string".to_string();
+                if let Some(serde_json::Value::Number(n)) = &prop.default {
+                    prop.default = Some(serde_json::Value::String(n.to_string()));
+                }
+            }
+        }
+        // Names the schema doesn't have are reported by `lint::check_models`
+        if patch.required && schema.properties.contains_key(&name) && !schema.required.contains(&name) {
+            schema.required.push(name.clone());
+        }
+    }
 }
 
 /// Result of schema_from_schemars: the main schema + any nested definitions
+#[derive(Debug, Clone)]
 pub struct SchemaResult {
     pub schema: Schema,
     pub nested: HashMap<String, Schema>,
 }
 
+impl From<Schema> for SchemaResult {
+    fn from(schema: Schema) -> Self {
+        Self { schema, nested: HashMap::new() }
+    }
+}
+
+#[cfg(feature = "openapi")]
 pub fn schema_from_schemars(_name: &str, root: &schemars::schema::RootSchema) -> Schema {
     schema_from_schemars_full(_name, root).schema
 }
 
+/// Component name for `#[api_model(module_prefix)]`: the module path below the crate root,
+/// PascalCased, followed by the type name (`app::billing::Settings` → `BillingSettings`)
+pub fn module_schema_name(module_path: &str, name: &str) -> String {
+    let mut out = String::new();
+    for word in module_path.split("::").skip(1).flat_map(|segment| segment.split('_')) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    out.push_str(name);
+    out
+}
+
+/// Component schema for any `JsonSchema` type, e.g. an error body registered with
+/// `HayaiApp::error_schema`, with the types it references
+#[cfg(feature = "openapi")]
+pub fn schema_of<T: schemars::JsonSchema>() -> SchemaResult {
+    schema_from_schemars_full(&T::schema_name(), &schemars::schema_for!(T))
+}
+
+#[cfg(feature = "openapi")]
 pub fn schema_from_schemars_full(_name: &str, root: &schemars::schema::RootSchema) -> SchemaResult {
     let mut properties = HashMap::new();
     let mut required = Vec::new();
 
-    if let Some(obj) = &root.schema.object {
+    if let Some(obj) = root.schema.object.as_deref() {
         for (prop_name, prop_schema) in &obj.properties {
             let prop = property_from_schemars_schema(prop_schema, &root.definitions);
             properties.insert(prop_name.clone(), prop);
         }
-        for req in &obj.required {
-            required.push(req.clone());
-        }
+        required = required_properties(obj, &properties);
     }
 
     let mut nested = HashMap::new();
@@ -445,20 +979,28 @@ pub fn schema_from_schemars_full(_name: &str, root: &schemars::schema::RootSchem
         if let schemars::schema::Schema::Object(obj) = def_schema {
             if let Some(obj_val) = &obj.object {
                 let mut def_props = HashMap::new();
-                let mut def_required = Vec::new();
                 for (pname, pschema) in &obj_val.properties {
                     def_props.insert(pname.clone(), property_from_schemars_schema(pschema, &root.definitions));
                 }
-                for req in &obj_val.required {
-                    def_required.push(req.clone());
-                }
+                let def_required = required_properties(obj_val, &def_props);
                 nested.insert(def_name.clone(), Schema {
                     type_name: "object".to_string(),
                     properties: def_props,
                     required: def_required,
+                    title: None,
                     description: None,
                     enum_values: None,
                     example: None,
+                    additional_properties: denies_additional_properties(obj_val),
+                    one_of: Vec::new(),
+                    discriminator: None,
+                });
+            } else if let Some(values) = &obj.enum_values {
+                nested.insert(def_name.clone(), Schema {
+                    type_name: "string".to_string(),
+                    description: obj.metadata.as_ref().and_then(|m| m.description.clone()),
+                    enum_values: Some(values.iter().filter_map(|v| v.as_str().map(str::to_string)).collect()),
+                    ..Schema::default()
                 });
             }
         }
@@ -469,15 +1011,81 @@ pub fn schema_from_schemars_full(_name: &str, root: &schemars::schema::RootSchem
             type_name: "object".to_string(),
             properties,
             required,
+            title: None,
             description: None,
             enum_values: None,
             example: None,
+            additional_properties: root.schema.object.as_deref().and_then(denies_additional_properties),
+            one_of: Vec::new(),
+            discriminator: None,
         },
         nested,
     }
 }
 
+/// A serialized spec with `servers` replaced by the single `url`
+pub fn with_server(spec_json: &str, url: &str) -> String {
+    let mut spec: serde_json::Value = match serde_json::from_str(spec_json) {
+        Ok(spec) => spec,
+        Err(_) => return spec_json.to_string(),
+    };
+    spec["servers"] = serde_json::json!([{ "url": url }]);
+    serde_json::to_string_pretty(&spec).unwrap_or_else(|_| spec_json.to_string())
+}
+
+/// Document the implicit HEAD handler of every GET operation in a serialized spec:
+/// same parameters and status codes, no response bodies, and an `operationId` named
+/// after the GET's in `style`.
+pub fn add_head_operations(spec: &mut serde_json::Value, style: crate::operation_id::OperationIdStyle) {
+    let Some(paths) = spec.get_mut("paths").and_then(|p| p.as_object_mut()) else { return };
+    for path_item in paths.values_mut() {
+        let Some(get) = path_item.get("get") else { continue };
+        if path_item.get("head").is_some() {
+            continue;
+        }
+        let mut head = get.clone();
+        if let Some(id) = head.get("operationId").and_then(|v| v.as_str()) {
+            head["operationId"] = serde_json::Value::String(style.head_id(id));
+        }
+        if let Some(responses) = head.get_mut("responses").and_then(|r| r.as_object_mut()) {
+            for resp in responses.values_mut() {
+                if let Some(obj) = resp.as_object_mut() {
+                    obj.remove("content");
+                }
+            }
+        }
+        path_item["head"] = head;
+    }
+}
+
+/// Declare a required header as `components.parameters.<component>` and reference it from
+/// every operation
+pub fn add_global_header_parameter(spec: &mut serde_json::Value, component: &str, header: &str, description: &str) {
+    spec["components"]["parameters"][component] = serde_json::json!({
+        "name": header,
+        "in": "header",
+        "required": true,
+        "description": description,
+        "schema": { "type": "string" },
+    });
+    let reference = serde_json::json!({ "$ref": format!("#/components/parameters/{}", component) });
+    let Some(paths) = spec.get_mut("paths").and_then(|p| p.as_object_mut()) else { return };
+    for path_item in paths.values_mut() {
+        let Some(path_item) = path_item.as_object_mut() else { continue };
+        for (method, operation) in path_item.iter_mut() {
+            if !["get", "put", "post", "delete", "options", "head", "patch", "trace"].contains(&method.as_str()) {
+                continue;
+            }
+            match operation.get_mut("parameters").and_then(|p| p.as_array_mut()) {
+                Some(parameters) => parameters.push(reference.clone()),
+                None => operation["parameters"] = serde_json::Value::Array(vec![reference.clone()]),
+            }
+        }
+    }
+}
+
 /// Extract query parameters from a schemars RootSchema
+#[cfg(feature = "openapi")]
 pub fn query_params_from_schema(root: &schemars::schema::RootSchema) -> Vec<DynParameter> {
     let mut params = Vec::new();
     if let Some(obj) = &root.schema.object {
@@ -485,7 +1093,23 @@ pub fn query_params_from_schema(root: &schemars::schema::RootSchema) -> Vec<DynP
         for (name, prop_schema) in &obj.properties {
             let type_name = schema_type_string(prop_schema);
             let description = schema_description(prop_schema);
+            let default = schema_default(prop_schema);
             let constraints = extract_schema_constraints(prop_schema);
+            let (items_type, explode) = match non_null_variant(prop_schema) {
+                Some(obj) if type_name == "array" => {
+                    let items_type = obj.array.as_ref()
+                        .and_then(|a| match &a.items {
+                            Some(schemars::schema::SingleOrVec::Single(item)) => Some(schema_type_string(item)),
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| "string".to_string());
+                    let explode = obj.extensions.get(crate::query::EXPLODE_EXTENSION)
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true);
+                    (Some(items_type), Some(explode))
+                }
+                _ => (None, None),
+            };
             params.push(DynParameter {
                 name: name.clone(),
                 location: "query".to_string(),
@@ -497,13 +1121,51 @@ pub fn query_params_from_schema(root: &schemars::schema::RootSchema) -> Vec<DynP
                 min_length: constraints.2,
                 max_length: constraints.3,
                 pattern: constraints.4,
+                default,
+                items_type,
+                explode,
             });
         }
     }
     params
 }
 
+/// Names of the sequence-typed properties of a query struct, which are
+/// allowed to be absent from the query string (see [`crate::query::from_query_str`])
+#[cfg(feature = "openapi")]
+pub fn array_fields_from_schema(root: &schemars::schema::RootSchema) -> Vec<String> {
+    let Some(obj) = &root.schema.object else { return Vec::new() };
+    obj.properties.iter()
+        .filter(|(_, s)| schema_type_string(s) == "array")
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Property names of an object schema, empty for anything else
+#[cfg(feature = "openapi")]
+pub fn property_names_from_schema(root: &schemars::schema::RootSchema) -> Vec<String> {
+    root.schema.object.as_ref()
+        .map(|obj| obj.properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The schema object itself, or the non-null variant of an `anyOf` Option wrapper
+#[cfg(feature = "openapi")]
+fn non_null_variant(schema: &schemars::schema::Schema) -> Option<&schemars::schema::SchemaObject> {
+    let schemars::schema::Schema::Object(obj) = schema else { return None };
+    if let Some(any_of) = obj.subschemas.as_ref().and_then(|sub| sub.any_of.as_ref()) {
+        return any_of.iter().find_map(|s| match s {
+            schemars::schema::Schema::Object(o)
+                if o.instance_type != Some(schemars::schema::InstanceType::Null.into()) => Some(o),
+            _ => None,
+        });
+    }
+    Some(obj)
+}
+
 /// Extract numeric/string constraints from a schemars schema
+#[cfg(feature = "openapi")]
+#[allow(clippy::type_complexity)]
 fn extract_schema_constraints(schema: &schemars::schema::Schema) -> (Option<f64>, Option<f64>, Option<u32>, Option<u32>, Option<String>) {
     match schema {
         schemars::schema::Schema::Object(obj) => {
@@ -533,6 +1195,7 @@ fn extract_schema_constraints(schema: &schemars::schema::Schema) -> (Option<f64>
 }
 
 /// Extract description from a schemars schema
+#[cfg(feature = "openapi")]
 fn schema_description(schema: &schemars::schema::Schema) -> Option<String> {
     match schema {
         schemars::schema::Schema::Object(obj) => {
@@ -559,6 +1222,18 @@ fn schema_description(schema: &schemars::schema::Schema) -> Option<String> {
     }
 }
 
+#[cfg(feature = "openapi")]
+fn schema_default(schema: &schemars::schema::Schema) -> Option<serde_json::Value> {
+    match schema {
+        schemars::schema::Schema::Object(obj) => obj.metadata.as_ref()
+            .and_then(|meta| meta.default.clone())
+            .filter(|v| !v.is_null()),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "openapi")]
+#[allow(clippy::redundant_closure)]
 fn schema_type_string(schema: &schemars::schema::Schema) -> String {
     match schema {
         schemars::schema::Schema::Object(obj) => {
@@ -599,37 +1274,78 @@ pub fn api_error_schema() -> Schema {
     properties.insert("error".to_string(), Property {
         type_name: "string".to_string(),
         format: None, min_length: None, max_length: None,
-        minimum: None, maximum: None, pattern: None, min_items: None,
+        minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
         description: Some("Error message".to_string()),
-        ref_path: None, items: None, nullable: false, example: None, additional_properties: None,
+        ref_path: None, items: None, nullable: false, example: None, additional_properties: None, default: None, write_only: false,
     });
     properties.insert("details".to_string(), Property {
         type_name: "array".to_string(),
         format: None, min_length: None, max_length: None,
-        minimum: None, maximum: None, pattern: None, min_items: None,
+        minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
         description: Some("Validation error details".to_string()),
         ref_path: None,
         items: Some(Box::new(Property {
             type_name: "string".to_string(),
             format: None, min_length: None, max_length: None,
-            minimum: None, maximum: None, pattern: None, min_items: None,
-            description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None,
+            minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
+            description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None, default: None, write_only: false,
         })),
-        nullable: false, example: None, additional_properties: None,
+        nullable: false, example: None, additional_properties: None, default: None, write_only: false,
     });
     Schema {
         type_name: "object".to_string(),
         properties,
         required: vec!["error".to_string()],
+        title: None,
         description: Some("Standard API error response".to_string()),
         enum_values: None,
         example: None,
+        additional_properties: None,
+        one_of: Vec::new(),
+        discriminator: None,
+    }
+}
+
+/// The properties schemars lists as required, in its (sorted) order, without the nullable
+/// ones: an `Option<T>` field may be left out whatever serde attributes it carries, unless
+/// `#[schema(required)]` says otherwise
+#[cfg(feature = "openapi")]
+fn required_properties(obj: &schemars::schema::ObjectValidation, properties: &HashMap<String, Property>) -> Vec<String> {
+    obj.required.iter()
+        .filter(|name| properties.get(*name).is_none_or(|prop| !prop.nullable))
+        .cloned()
+        .collect()
+}
+
+/// `Some(false)` when schemars marked the object closed (serde `deny_unknown_fields`)
+#[cfg(feature = "openapi")]
+fn denies_additional_properties(obj: &schemars::schema::ObjectValidation) -> Option<bool> {
+    match obj.additional_properties.as_deref() {
+        Some(schemars::schema::Schema::Bool(false)) => Some(false),
+        _ => None,
     }
 }
 
+#[cfg(feature = "openapi")]
 fn property_from_schemars_schema(
     schema: &schemars::schema::Schema,
     definitions: &schemars::Map<String, schemars::schema::Schema>,
+) -> Property {
+    let mut prop = property_from_schemars_type(schema, definitions);
+    if let schemars::schema::Schema::Object(obj) = schema {
+        prop.default = obj.metadata.as_ref().and_then(|m| m.default.clone());
+        // Integer widths matter to clients: JavaScript loses precision beyond 2^53
+        if prop.type_name == "integer" && prop.ref_path.is_none() {
+            prop.format = obj.format.clone();
+        }
+    }
+    prop
+}
+
+#[cfg(feature = "openapi")]
+fn property_from_schemars_type(
+    schema: &schemars::schema::Schema,
+    definitions: &schemars::Map<String, schemars::schema::Schema>,
 ) -> Property {
     match schema {
         schemars::schema::Schema::Object(obj) => {
@@ -638,10 +1354,10 @@ fn property_from_schemars_schema(
                 return Property {
                     type_name: "object".to_string(),
                     format: None, min_length: None, max_length: None,
-                    minimum: None, maximum: None, pattern: None, min_items: None,
+                    minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
                     description: None,
                     ref_path: Some(format!("#/components/schemas/{}", ref_name)),
-                    items: None, nullable: false, example: None, additional_properties: None,
+                    items: None, nullable: false, example: None, additional_properties: None, default: None, write_only: false,
                 };
             }
 
@@ -666,27 +1382,14 @@ fn property_from_schemars_schema(
             }
 
             if let Some(ty) = &obj.instance_type {
-                let type_name = match ty {
-                    schemars::schema::SingleOrVec::Single(single) => format_instance_type(single),
+                // `Option<T>` of a type schemars describes inline: `"type": [T, "null"]`
+                let (type_name, nullable) = match ty {
+                    schemars::schema::SingleOrVec::Single(single) => (format_instance_type(single), false),
                     schemars::schema::SingleOrVec::Vec(vec) => {
-                        let non_null: Vec<_> = vec.iter()
-                            .filter(|t| **t != schemars::schema::InstanceType::Null)
-                            .collect();
+                        let non_null = vec.iter().find(|t| **t != schemars::schema::InstanceType::Null);
+                        #[allow(clippy::manual_contains)]
                         let has_null = vec.iter().any(|t| *t == schemars::schema::InstanceType::Null);
-                        let tn = if let Some(first) = non_null.first() {
-                            format_instance_type(first)
-                        } else {
-                            "string".to_string()
-                        };
-                        if has_null {
-                            return Property {
-                                type_name: tn,
-                                format: None, min_length: None, max_length: None,
-                                minimum: None, maximum: None, pattern: None, min_items: None,
-                                description: None, ref_path: None, items: None, nullable: true, example: None, additional_properties: None,
-                            };
-                        }
-                        tn
+                        (non_null.map_or_else(|| "string".to_string(), format_instance_type), has_null)
                     }
                 };
 
@@ -707,10 +1410,10 @@ fn property_from_schemars_schema(
                                     return Property {
                                         type_name: "string".to_string(),
                                         format: None, min_length: None, max_length: None,
-                                        minimum: None, maximum: None, pattern: None, min_items: None,
+                                        minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
                                         description: None,
-                                        ref_path: Some(format!("#/components/schemas/{}", info.name)),
-                                        items: None, nullable: false, example: None, additional_properties: None,
+                                        ref_path: Some(format!("#/components/schemas/{}", info.component_name())),
+                                        items: None, nullable, example: None, additional_properties: None, default: None, write_only: false,
                                     };
                                 }
                             }
@@ -718,7 +1421,8 @@ fn property_from_schemars_schema(
                     }
                 }
 
-                // HashMap<String, T> → object with additionalProperties
+                // HashMap<String, T> → object with additionalProperties, kept in the
+                // non-null branch when the map is nullable
                 if type_name == "object" {
                     if let Some(obj_validation) = &obj.object {
                         if let Some(ap_schema) = &obj_validation.additional_properties {
@@ -726,14 +1430,17 @@ fn property_from_schemars_schema(
                             return Property {
                                 type_name: "object".to_string(),
                                 format: None, min_length: None, max_length: None,
-                                minimum: None, maximum: None, pattern: None, min_items: None,
-                                description: None, ref_path: None, items: None, nullable: false, example: None,
+                                minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
+                                description: None, ref_path: None, items: None, nullable, example: None,
                                 additional_properties: Some(Box::new(ap_prop)),
+                                default: None,
+                                write_only: false,
                             };
                         }
                     }
                 }
 
+                // Vec<T> → array of T; `Vec<Option<T>>` items are nullable themselves
                 if type_name == "array" {
                     let items_prop = if let Some(arr) = &obj.array {
                         if let Some(schemars::schema::SingleOrVec::Single(item_schema)) = &arr.items {
@@ -747,34 +1454,35 @@ fn property_from_schemars_schema(
 
                     return Property {
                         type_name, format: None, min_length: None, max_length: None,
-                        minimum: None, maximum: None, pattern: None, min_items: None,
-                        description: None, ref_path: None, items: items_prop, nullable: false, example: None, additional_properties: None,
+                        minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
+                        description: None, ref_path: None, items: items_prop, nullable, example: None, additional_properties: None, default: None, write_only: false,
                     };
                 }
 
                 return Property {
                     type_name, format: None, min_length: None, max_length: None,
-                    minimum: None, maximum: None, pattern: None, min_items: None,
-                    description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None,
+                    minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
+                    description: None, ref_path: None, items: None, nullable, example: None, additional_properties: None, default: None, write_only: false,
                 };
             }
 
             Property {
                 type_name: "string".to_string(),
                 format: None, min_length: None, max_length: None,
-                minimum: None, maximum: None, pattern: None, min_items: None,
-                description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None,
+                minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
+                description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None, default: None, write_only: false,
             }
         }
         _ => Property {
             type_name: "string".to_string(),
             format: None, min_length: None, max_length: None,
-            minimum: None, maximum: None, pattern: None, min_items: None,
-            description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None,
+            minimum: None, maximum: None, pattern: None, min_items: None, max_items: None,
+            description: None, ref_path: None, items: None, nullable: false, example: None, additional_properties: None, default: None, write_only: false,
         },
     }
 }
 
+#[cfg(feature = "openapi")]
 fn format_instance_type(ty: &schemars::schema::InstanceType) -> String {
     match ty {
         schemars::schema::InstanceType::String => "string".to_string(),
@@ -792,11 +1500,17 @@ pub fn status_description(code: u16) -> &'static str {
     match code {
         200 => "OK",
         201 => "Created",
+        202 => "Accepted",
         204 => "No Content",
+        206 => "Partial Content",
+        207 => "Multi-Status",
         400 => "Bad Request",
         401 => "Unauthorized",
         403 => "Forbidden",
         404 => "Not Found",
+        406 => "Not Acceptable",
+        412 => "Precondition Failed",
+        416 => "Range Not Satisfiable",
         422 => "Validation Failed",
         500 => "Internal Server Error",
         _ => "Response",
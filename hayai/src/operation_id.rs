@@ -0,0 +1,211 @@
+//! How operations are named, see [`HayaiApp::operation_id_style`](crate::HayaiApp::operation_id_style).
+//!
+//! Generated clients name their methods after each operation's `operationId`. A route's
+//! `#[operation_id("fetchUser")]` is used as given; every other route gets one derived by
+//! the app's [`OperationIdStyle`]. The chosen id names the operation everywhere: in the
+//! spec and its links, in access log records, blocking warnings, audit events and
+//! response hooks, so they all correlate.
+//!
+//! Two handlers ending up with the same id fail [`into_router`](crate::HayaiApp::into_router);
+//! [`HayaiApp::number_duplicate_operation_ids`](crate::HayaiApp::number_duplicate_operation_ids)
+//! numbers them instead.
+
+use crate::RouteInfo;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+/// How an `operationId` is derived from a route without `#[operation_id]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OperationIdStyle {
+    /// The handler's name, `get_user_by_id`
+    #[default]
+    SnakeCase,
+    /// The handler's name in camelCase, `getUserById`
+    CamelCase,
+    /// The method and the full path, `getUsersById` for `GET /users/{id}`; versioned
+    /// handlers end with their version, `getUsersByIdV2`
+    MethodPath,
+}
+
+impl OperationIdStyle {
+    /// The id of `route` mounted at `path`, ignoring any `#[operation_id]`
+    pub fn derive(self, route: &RouteInfo, path: &str) -> String {
+        match self {
+            OperationIdStyle::SnakeCase => route.handler_name.to_string(),
+            OperationIdStyle::CamelCase => camel_case(route.handler_name),
+            OperationIdStyle::MethodPath => {
+                let mut id = route.method.to_ascii_lowercase();
+                let mut named = false;
+                for segment in path.split('/').filter(|s| !s.is_empty()) {
+                    named = true;
+                    match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                        Some(param) => {
+                            id.push_str("By");
+                            id.push_str(&pascal_case(param.trim_start_matches('*')));
+                        }
+                        None => id.push_str(&pascal_case(segment)),
+                    }
+                }
+                if !named {
+                    id.push_str("Root");
+                }
+                if let Some(version) = route.version {
+                    id.push_str(&format!("V{}", version));
+                }
+                id
+            }
+        }
+    }
+
+    /// The id documenting the implicit HEAD of a GET operation named `get_id`
+    pub fn head_id(self, get_id: &str) -> String {
+        match self {
+            OperationIdStyle::SnakeCase => format!("{}_head", get_id),
+            OperationIdStyle::CamelCase => format!("{}Head", get_id),
+            OperationIdStyle::MethodPath => match get_id.strip_prefix("get") {
+                Some(rest) => format!("head{}", rest),
+                None => format!("{}Head", get_id),
+            },
+        }
+    }
+
+    /// `n`th (from 2) of several operations that would share `id`
+    fn numbered(self, id: &str, n: usize) -> String {
+        match self {
+            OperationIdStyle::SnakeCase => format!("{}_{}", id, n),
+            _ => format!("{}{}", id, n),
+        }
+    }
+}
+
+/// Words split on anything but letters and digits, each capitalized
+fn pascal_case(text: &str) -> String {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect()
+}
+
+fn camel_case(text: &str) -> String {
+    let pascal = pascal_case(text);
+    let mut chars = pascal.chars();
+    chars.next().map(|first| first.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+/// Ids live as long as the routes they name; each distinct one is allocated once
+fn intern(id: String) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED.get_or_init(Mutex::default).lock().unwrap();
+    match interned.get(id.as_str()) {
+        Some(existing) => existing,
+        None => {
+            let leaked: &'static str = Box::leak(id.into_boxed_str());
+            interned.insert(leaked);
+            leaked
+        }
+    }
+}
+
+/// One mounted route and its id
+struct Assigned {
+    route: &'static RouteInfo,
+    path: String,
+    axum_path: String,
+    id: &'static str,
+}
+
+/// The id of every mounted operation
+pub(crate) struct OperationIds {
+    assigned: Vec<Assigned>,
+}
+
+impl OperationIds {
+    /// Name `mounted`, given as `(route, path, axum path)`. Handlers sharing an id are
+    /// listed as errors, or numbered in path order with `number_duplicates`.
+    pub(crate) fn assign(
+        style: OperationIdStyle,
+        number_duplicates: bool,
+        mut mounted: Vec<(&'static RouteInfo, String, String)>,
+    ) -> Result<Self, Vec<String>> {
+        mounted.sort_by(|a, b| {
+            (&a.1, a.0.method, a.0.version, a.0.handler_name).cmp(&(&b.1, b.0.method, b.0.version, b.0.handler_name))
+        });
+        mounted.dedup_by(|a, b| std::ptr::eq(a.0, b.0) && a.1 == b.1);
+
+        let mut named: Vec<(&'static RouteInfo, String, String, String)> = mounted.into_iter()
+            .map(|(route, path, axum_path)| {
+                let id = route.operation_id.map_or_else(|| style.derive(route, &path), str::to_string);
+                (route, path, axum_path, id)
+            })
+            .collect();
+
+        // The first handler with an id keeps it; one handler mounted twice is still one operation
+        let mut owners: HashMap<String, (&'static RouteInfo, &str)> = HashMap::new();
+        let mut collisions = Vec::new();
+        let mut renumber = Vec::new();
+        for (i, (route, path, _, id)) in named.iter().enumerate() {
+            match owners.get(id) {
+                Some((owner, owner_path)) if !std::ptr::eq(*owner, *route) => {
+                    collisions.push(format!(
+                        "{}: {} ({} {}) and {} ({} {})",
+                        id, owner.handler_name, owner.method, owner_path, route.handler_name, route.method, path,
+                    ));
+                    renumber.push(i);
+                }
+                Some(_) => {}
+                None => {
+                    owners.insert(id.clone(), (*route, path.as_str()));
+                }
+            }
+        }
+        if !collisions.is_empty() && !number_duplicates {
+            return Err(collisions);
+        }
+        // A handler numbered at one of its paths keeps that number at the others
+        let mut taken: HashSet<String> = owners.into_keys().collect();
+        let mut numbered: HashMap<(*const RouteInfo, String), String> = HashMap::new();
+        for i in renumber {
+            let (route, _, _, base) = &named[i];
+            let key = (*route as *const RouteInfo, base.clone());
+            let id = match numbered.get(&key) {
+                Some(id) => id.clone(),
+                None => {
+                    let id = (2..).map(|n| style.numbered(base, n)).find(|id| !taken.contains(id)).expect("a free number");
+                    taken.insert(id.clone());
+                    numbered.insert(key, id.clone());
+                    id
+                }
+            };
+            named[i].3 = id;
+        }
+
+        let assigned = named.into_iter()
+            .map(|(route, path, axum_path, id)| Assigned { route, path, axum_path, id: intern(id) })
+            .collect();
+        Ok(Self { assigned })
+    }
+
+    /// The id of `route` mounted at `path`
+    pub(crate) fn get(&self, route: &RouteInfo, path: &str) -> &'static str {
+        self.assigned.iter()
+            .find(|a| std::ptr::eq(a.route, route) && a.path == path)
+            .map_or(route.handler_name, |a| a.id)
+    }
+
+    /// Ids by `(METHOD, axum path)`, as the middleware looks them up
+    pub(crate) fn by_axum_path(&self) -> HashMap<(String, String), &'static str> {
+        self.assigned.iter()
+            .map(|a| ((a.route.method.to_string(), a.axum_path.clone()), a.id))
+            .collect()
+    }
+
+    /// The operation a `#[link]` targets, named by its handler or by its id
+    pub(crate) fn resolve(&self, target: &str) -> Option<&'static str> {
+        self.assigned.iter().find(|a| a.id == target)
+            .or_else(|| self.assigned.iter().find(|a| a.route.handler_name == target))
+            .map(|a| a.id)
+    }
+}
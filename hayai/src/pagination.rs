@@ -0,0 +1,155 @@
+//! Paginated response envelopes.
+//!
+//! A handler returning [`Page<T>`] or [`CursorPage<T>`] answers with the envelope as its
+//! body plus an RFC 5988 `Link` header pointing at the neighbouring pages. The links are
+//! the request URL with only the page (or cursor) query parameter replaced, built against
+//! the proxy-aware [`ConnectionInfo`].
+
+use crate::proxy::ConnectionInfo;
+use crate::{ApiError, AppState};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::Response;
+use serde::Serialize;
+
+/// Which envelope a route returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageKind {
+    Page,
+    Cursor,
+}
+
+impl PageKind {
+    /// Response schema for the envelope around `item` (a schema or `$ref`)
+    pub fn schema(self, item: serde_json::Value) -> serde_json::Value {
+        let items = serde_json::json!({ "type": "array", "items": item });
+        match self {
+            PageKind::Page => serde_json::json!({
+                "type": "object",
+                "required": ["items", "page", "per_page", "total"],
+                "properties": {
+                    "items": items,
+                    "page": { "type": "integer", "format": "uint32", "minimum": 1 },
+                    "per_page": { "type": "integer", "format": "uint32" },
+                    "total": { "type": "integer", "format": "uint64" },
+                },
+            }),
+            PageKind::Cursor => serde_json::json!({
+                "type": "object",
+                "required": ["items"],
+                "properties": {
+                    "items": items,
+                    "next_cursor": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                    "prev_cursor": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                },
+            }),
+        }
+    }
+}
+
+/// One page of a numbered listing; pages start at 1
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    /// Query parameter carrying the page number in links
+    #[serde(skip)]
+    pub page_param: &'static str,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, page: u32, per_page: u32, total: u64) -> Self {
+        Self { items, page, per_page, total, page_param: "page" }
+    }
+
+    /// Use `name` instead of `page` as the page number parameter in links
+    pub fn page_param(mut self, name: &'static str) -> Self {
+        self.page_param = name;
+        self
+    }
+
+    fn has_next(&self) -> bool {
+        u64::from(self.page) * u64::from(self.per_page) < self.total
+    }
+}
+
+/// One page of a cursor-based listing
+#[derive(Debug, Clone, Serialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+    /// Query parameter carrying the cursor in links
+    #[serde(skip)]
+    pub cursor_param: &'static str,
+}
+
+impl<T> CursorPage<T> {
+    pub fn new(items: Vec<T>, next_cursor: Option<String>, prev_cursor: Option<String>) -> Self {
+        Self { items, next_cursor, prev_cursor, cursor_param: "cursor" }
+    }
+
+    /// Use `name` instead of `cursor` as the cursor parameter in links
+    pub fn cursor_param(mut self, name: &'static str) -> Self {
+        self.cursor_param = name;
+        self
+    }
+}
+
+/// Envelopes that link to neighbouring pages
+pub trait Paginated: Serialize {
+    /// `(rel, query parameter, value)` for each neighbour
+    fn links(&self) -> Vec<(&'static str, &'static str, String)>;
+}
+
+impl<T: Serialize> Paginated for Page<T> {
+    fn links(&self) -> Vec<(&'static str, &'static str, String)> {
+        let mut links = Vec::new();
+        if self.has_next() {
+            links.push(("next", self.page_param, (self.page + 1).to_string()));
+        }
+        if self.page > 1 {
+            links.push(("prev", self.page_param, (self.page - 1).to_string()));
+        }
+        links
+    }
+}
+
+impl<T: Serialize> Paginated for CursorPage<T> {
+    fn links(&self) -> Vec<(&'static str, &'static str, String)> {
+        [("next", &self.next_cursor), ("prev", &self.prev_cursor)].into_iter()
+            .filter_map(|(rel, cursor)| cursor.clone().map(|c| (rel, self.cursor_param, c)))
+            .collect()
+    }
+}
+
+/// The request URL with `param` set to `value`, keeping every other query parameter
+fn link_url(parts: &Parts, param: &str, value: &str) -> String {
+    let mut query = form_urlencoded::Serializer::new(String::new());
+    let existing = form_urlencoded::parse(parts.uri.query().unwrap_or("").as_bytes());
+    for (k, v) in existing.filter(|(k, _)| k != param) {
+        query.append_pair(&k, &v);
+    }
+    query.append_pair(param, value);
+    let path = format!("{}?{}", parts.uri.path(), query.finish());
+    match parts.extensions.get::<ConnectionInfo>() {
+        Some(conn) => conn.absolute_url(&path),
+        None => path,
+    }
+}
+
+#[doc(hidden)]
+pub fn __page_response<P: Paginated>(state: &AppState, parts: &Parts, status: StatusCode, page: &P) -> Result<Response, ApiError> {
+    let mut response = crate::__json_response(state, status, page)?;
+    let links: Vec<String> = page.links().into_iter()
+        .map(|(rel, param, value)| format!("<{}>; rel=\"{}\"", link_url(parts, param, &value), rel))
+        .collect();
+    if !links.is_empty() {
+        let value = HeaderValue::from_str(&links.join(", "))
+            .map_err(|e| ApiError::internal(format!("Invalid Link header: {}", e)))?;
+        response.headers_mut().insert(header::LINK, value);
+    }
+    Ok(response)
+}
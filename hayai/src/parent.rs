@@ -0,0 +1,105 @@
+//! Parent resources of nested routes, loaded before the handler runs.
+//!
+//! A loader registered with [`HayaiApp::parent_loader`](crate::HayaiApp::parent_loader)
+//! turns one path parameter into a `T`; a handler taking `Parent<T>` gets the loaded value,
+//! and the loader's error, typically a 404, is the response when it fails. Within one
+//! request each parent is loaded once, however many arguments ask for it.
+//!
+//! ```ignore
+//! HayaiApp::new().parent_loader("user_id", |user_id: i64, deps: DepResolver| async move {
+//!     deps.get::<Database>()?.find_user(user_id).await?
+//!         .ok_or_else(|| ApiError::not_found(format!("User {} not found", user_id)))
+//! })
+//!
+//! #[get("/users/{user_id}/posts/{post_id}")]
+//! async fn get_post(user_id: i64, post_id: i64, user: Parent<User>) -> Result<Post, ApiError> { ... }
+//! ```
+
+use crate::{ApiError, AppState, DepResolver};
+use axum::extract::{FromRequestParts, RawPathParams};
+use axum::http::request::Parts;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+type Loaded = Arc<dyn Any + Send + Sync>;
+type LoadFn = Arc<dyn Fn(&str, AppState) -> Pin<Box<dyn Future<Output = Result<Loaded, ApiError>> + Send>> + Send + Sync>;
+
+/// The parent resource named by a path parameter, see the [module docs](self)
+pub struct Parent<T>(Arc<T>);
+
+impl<T> Parent<T> {
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for Parent<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// A registered loader and the path parameter it reads
+#[derive(Clone)]
+pub(crate) struct ParentLoader {
+    param: String,
+    load: LoadFn,
+}
+
+impl ParentLoader {
+    pub(crate) fn new<T, K, F, Fut>(param: &str, loader: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        K: FromStr + Send + 'static,
+        F: Fn(K, DepResolver<'static>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ApiError>> + Send + 'static,
+    {
+        let name = param.to_string();
+        let load: LoadFn = Arc::new(move |raw, state| {
+            let key = raw.parse::<K>()
+                .map_err(|_| ApiError::bad_request(format!("Invalid path parameter `{}`", name)));
+            let loaded = key.map(|key| loader(key, DepResolver::owned(state)));
+            Box::pin(async move { Ok(Arc::new(loaded?.await?) as Loaded) })
+        });
+        Self { param: param.to_string(), load }
+    }
+}
+
+/// Parents already loaded for the request, by type
+#[derive(Clone, Default)]
+struct LoadedParents(HashMap<TypeId, Loaded>);
+
+impl<T: Send + Sync + 'static> FromRequestParts<AppState> for Parent<T> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        let cached = parts.extensions.get::<LoadedParents>().and_then(|loaded| loaded.0.get(&TypeId::of::<T>()).cloned());
+        let loaded = match cached {
+            Some(loaded) => loaded,
+            None => {
+                let loader = state.settings.parent_loaders.get(&TypeId::of::<T>()).ok_or_else(|| {
+                    ApiError::internal(format!("No parent loader registered for {}", std::any::type_name::<T>()))
+                })?;
+                let params = RawPathParams::from_request_parts(parts, state).await
+                    .map_err(|e| ApiError::internal(format!("Failed to read path parameters: {}", e)))?;
+                let raw = params.iter().find(|(name, _)| *name == loader.param).map(|(_, value)| value.to_string())
+                    .ok_or_else(|| ApiError::internal(format!(
+                        "Parent<{}> needs a `{{{}}}` path parameter", std::any::type_name::<T>(), loader.param,
+                    )))?;
+                let loaded = (loader.load)(&raw, state.clone()).await?;
+                parts.extensions.get_or_insert_default::<LoadedParents>().0.insert(TypeId::of::<T>(), loaded.clone());
+                loaded
+            }
+        };
+        loaded.downcast::<T>()
+            .map(Parent)
+            .map_err(|_| ApiError::internal(format!("Parent loader returned the wrong type for {}", std::any::type_name::<T>())))
+    }
+}
@@ -0,0 +1,13 @@
+//! Helpers for the `{Name}Patch` models generated by `#[api_model(generate_patch)]`.
+
+use serde::{Deserialize, Deserializer};
+
+/// Deserialize a present key into `Some(..)` so that, combined with `#[serde(default)]`,
+/// an absent key is `None`, an explicit `null` is `Some(None)` and a value is `Some(Some(v))`.
+pub fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,159 @@
+//! Conditional request headers for optimistic concurrency.
+//!
+//! [`Precondition`] parses `If-Match`, `If-None-Match` and `If-Unmodified-Since`; a
+//! malformed date is rejected with 400 before the handler runs. Handlers taking it get the
+//! three headers documented as optional parameters.
+
+use crate::ApiError;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parsed conditional request headers
+///
+/// ```ignore
+/// #[put("/articles/{id}")]
+/// async fn update(id: i64, precondition: Precondition, body: Article, db: Dep<Db>) -> Result<Article, ApiError> {
+///     let current = db.get(id).await?;
+///     precondition.check_unmodified_since(current.updated_at)?;
+///     db.save(id, body).await
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Precondition {
+    /// Entity tags from `If-Match`, verbatim (`"*"` included)
+    pub if_match: Option<Vec<String>>,
+    /// Entity tags from `If-None-Match`, verbatim
+    pub if_none_match: Option<Vec<String>>,
+    pub if_unmodified_since: Option<SystemTime>,
+}
+
+impl Precondition {
+    fn from_headers(headers: &HeaderMap) -> Result<Self, ApiError> {
+        let if_unmodified_since = match header_str(headers, "if-unmodified-since")? {
+            Some(value) => Some(parse_http_date(value).ok_or_else(|| {
+                ApiError::bad_request(format!("Invalid If-Unmodified-Since date: {:?}", value))
+            })?),
+            None => None,
+        };
+        Ok(Self {
+            if_match: header_str(headers, "if-match")?.map(entity_tags),
+            if_none_match: header_str(headers, "if-none-match")?.map(entity_tags),
+            if_unmodified_since,
+        })
+    }
+
+    /// 412 if the resource changed after the client's `If-Unmodified-Since`; passes when
+    /// the header is absent. Compared at whole seconds, the resolution of HTTP dates.
+    pub fn check_unmodified_since(&self, resource_updated_at: SystemTime) -> Result<(), ApiError> {
+        let Some(since) = self.if_unmodified_since else { return Ok(()) };
+        if unix_secs(resource_updated_at) > unix_secs(since) {
+            let mut error = ApiError::precondition_failed("Precondition Failed");
+            error.details.push(format!("resource was modified after {}", format_http_date(since)));
+            return Err(error);
+        }
+        Ok(())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Precondition {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        Precondition::from_headers(&parts.headers)
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Result<Option<&'a str>, ApiError> {
+    headers.get(name)
+        .map(|v| v.to_str().map_err(|_| ApiError::bad_request(format!("Invalid {} header", name))))
+        .transpose()
+}
+
+fn entity_tags(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect()
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+pub(crate) const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+/// Parse an HTTP-date: IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`), or the obsolete
+/// RFC 850 (`Sunday, 06-Nov-94 08:49:37 GMT`) and asctime (`Sun Nov  6 08:49:37 1994`) forms
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    let (day, month, year, time) = match tokens.as_slice() {
+        [weekday, day, month, year, time, "GMT"] if weekday.ends_with(',') => (*day, *month, year.parse().ok()?, *time),
+        [weekday, date, time, "GMT"] if weekday.ends_with(',') => {
+            let mut parts = date.split('-');
+            let (day, month, yy) = (parts.next()?, parts.next()?, parts.next()?);
+            if parts.next().is_some() || yy.len() != 2 {
+                return None;
+            }
+            let yy: i64 = yy.parse().ok()?;
+            (day, month, if yy < 70 { 2000 + yy } else { 1900 + yy }, *time)
+        }
+        [_, month, day, time, year] => (*day, *month, year.parse().ok()?, *time),
+        _ => return None,
+    };
+
+    let day: u32 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    let mut clock = time.split(':').map(|t| if t.len() == 2 { t.parse::<u64>().ok() } else { None });
+    let (h, m, s) = (clock.next()??, clock.next()??, clock.next()??);
+    if clock.next().is_some() || h > 23 || m > 59 || s > 60 || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + h * 3600 + m * 60 + s))
+}
+
+/// IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn format_http_date(time: SystemTime) -> String {
+    let secs = unix_secs(time);
+    let days = (secs / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let of_day = secs % 86_400;
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize], day, MONTHS[month as usize - 1], year,
+        of_day / 3600, of_day / 60 % 60, of_day % 60,
+    )
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Howard Hinnant's date algorithms, days relative to 1970-01-01
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
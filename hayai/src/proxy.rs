@@ -0,0 +1,160 @@
+//! Effective scheme, host and client address of a request, see
+//! [`HayaiApp::behind_proxy`](crate::HayaiApp::behind_proxy).
+//!
+//! Every request gets a [`ConnectionInfo`] extension. Without a proxy configuration it
+//! comes from the request itself (`Host` header, socket peer); behind a proxy the
+//! `X-Forwarded-*` headers are used instead. `X-Forwarded-For` is appended to by every
+//! hop, so the entry written by the outermost trusted one is taken and those to its left,
+//! written by the client, are ignored. `X-Forwarded-Proto` and `X-Forwarded-Host` are
+//! usually overwritten by each proxy, so their last value is taken.
+
+use crate::ApiError;
+use axum::extract::{ConnectInfo, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::net::{IpAddr, SocketAddr};
+
+/// How many reverse proxies sit in front of the app, and which of their headers to honor
+#[derive(Debug, Clone)]
+pub struct ProxyConfig {
+    /// Proxies that append to `X-Forwarded-For` between the client and the app
+    pub trusted_hops: usize,
+    pub honor_x_forwarded_proto: bool,
+    pub honor_x_forwarded_host: bool,
+    /// Document the server URL the request was made against (`scheme://host`) in
+    /// `/openapi.json`, so Swagger's "Try it out" goes through the proxy
+    pub dynamic_servers: bool,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self { trusted_hops: 1, honor_x_forwarded_proto: true, honor_x_forwarded_host: true, dynamic_servers: false }
+    }
+}
+
+/// The effective connection details of a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// `http` or `https`
+    pub scheme: String,
+    /// Host (and port) the client addressed
+    pub host: Option<String>,
+    pub client_ip: Option<IpAddr>,
+}
+
+impl ConnectionInfo {
+    /// `scheme://host`, when the host is known
+    pub fn base_url(&self) -> Option<String> {
+        self.host.as_ref().map(|host| format!("{}://{}", self.scheme, host))
+    }
+
+    /// An absolute URL for `path`, e.g. for a `Location` header; `path` itself when the
+    /// host is unknown
+    pub fn absolute_url(&self, path: &str) -> String {
+        match self.base_url() {
+            Some(base) => format!("{}{}", base, path),
+            None => path.to_string(),
+        }
+    }
+
+    fn resolve(headers: &HeaderMap, peer: Option<IpAddr>, proxy: Option<&ProxyConfig>) -> Self {
+        let mut info = ConnectionInfo {
+            scheme: "http".to_string(),
+            host: header_str(headers, "host").map(str::to_string),
+            client_ip: peer,
+        };
+        let Some(proxy) = proxy else { return info };
+        if proxy.trusted_hops == 0 {
+            return info;
+        }
+        if let Some(ip) = trusted_entry(headers, "x-forwarded-for", proxy.trusted_hops).and_then(parse_forwarded_ip) {
+            info.client_ip = Some(ip);
+        }
+        if proxy.honor_x_forwarded_proto {
+            if let Some(proto) = last_entry(headers, "x-forwarded-proto") {
+                info.scheme = proto.to_ascii_lowercase();
+            }
+        }
+        if proxy.honor_x_forwarded_host {
+            if let Some(host) = last_entry(headers, "x-forwarded-host") {
+                info.host = Some(host.to_string());
+            }
+        }
+        info
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// The comma-separated entries across all occurrences of the header
+fn entries<'a>(headers: &'a HeaderMap, name: &str) -> Vec<&'a str> {
+    headers.get_all(name).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// The entry written by the outermost trusted hop: `trusted_hops` from the right. `None`
+/// if fewer entries than trusted hops are present, since the header then didn't come
+/// through the proxies.
+fn trusted_entry<'a>(headers: &'a HeaderMap, name: &str, trusted_hops: usize) -> Option<&'a str> {
+    let entries = entries(headers, name);
+    entries.len().checked_sub(trusted_hops).map(|i| entries[i])
+}
+
+/// The entry set by the proxy closest to the app
+fn last_entry<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    entries(headers, name).pop()
+}
+
+/// An `X-Forwarded-For` entry, which some proxies write with the client's port:
+/// `1.2.3.4`, `1.2.3.4:5678`, `::1`, `[::1]` or `[::1]:80`
+fn parse_forwarded_ip(entry: &str) -> Option<IpAddr> {
+    if let Ok(ip) = entry.parse() {
+        return Some(ip);
+    }
+    if let Ok(addr) = entry.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    entry.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
+
+/// Attach [`ConnectionInfo`] to every request
+pub(crate) async fn connection_info(proxy: Option<ProxyConfig>, mut req: Request, next: Next) -> Response {
+    let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+    let info = ConnectionInfo::resolve(req.headers(), peer, proxy.as_ref());
+    req.extensions_mut().insert(info);
+    next.run(req).await
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ConnectionInfo {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts.extensions.get::<ConnectionInfo>().cloned()
+            .ok_or_else(|| ApiError::internal("Connection info unavailable".into()))
+    }
+}
+
+/// The client's address: the socket peer, or the address reported by the trusted
+/// proxies. Serve with connect info (as [`HayaiApp::serve`](crate::HayaiApp::serve) does)
+/// for the peer address to be known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, ApiError> {
+        ConnectionInfo::from_request_parts(parts, state).await?
+            .client_ip
+            .map(ClientIp)
+            .ok_or_else(|| ApiError::internal("Client address unavailable".into()))
+    }
+}
@@ -0,0 +1,321 @@
+//! Query string deserialization for `Query<T>` parameters.
+//!
+//! Unlike `serde_urlencoded`, repeated keys (`?tag=a&tag=b`) collect into sequence fields,
+//! and sequence fields that are absent from the query deserialize to an empty list.
+
+use serde::de::value::{Error, SeqDeserializer};
+use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, Visitor};
+use std::str::FromStr;
+
+/// Deserialize `T` from a raw query string.
+///
+/// `array_fields` lists the sequence-typed fields of `T`; any that are missing from the
+/// query are supplied as empty lists instead of failing with "missing field".
+pub fn from_query_str<T: DeserializeOwned>(query: &str, array_fields: &[String]) -> Result<T, Error> {
+    let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, values)) => values.push(value.into_owned()),
+            None => entries.push((key.into_owned(), vec![value.into_owned()])),
+        }
+    }
+    for field in array_fields {
+        if !entries.iter().any(|(k, _)| k == field) {
+            entries.push((field.clone(), Vec::new()));
+        }
+    }
+    from_entries(entries)
+}
+
+/// Deserialize `T` from already grouped `key → values` pairs, parsing scalars from strings.
+///
+/// A value that fails to parse is reported as `key: reason`.
+pub(crate) fn from_entries<T: DeserializeOwned>(entries: Vec<(String, Vec<String>)>) -> Result<T, Error> {
+    T::deserialize(QueryDeserializer { entries })
+}
+
+/// A list that is also accepted comma-separated: `?tag=a,b` and `?tag=a&tag=b` both
+/// give `["a", "b"]`. Documented as `style: form, explode: false`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CommaSeparated<T>(pub Vec<T>);
+
+impl<T> CommaSeparated<T> {
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for CommaSeparated<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<'de, T> Deserialize<'de> for CommaSeparated<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CommaVisitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for CommaVisitor<T>
+        where
+            T: FromStr,
+            T::Err: std::fmt::Display,
+        {
+            type Value = Vec<T>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a comma-separated list")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Vec<T>, E> {
+                let mut out = Vec::new();
+                split_into(v, &mut out)?;
+                Ok(out)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+                let mut out = Vec::new();
+                while let Some(part) = seq.next_element::<String>()? {
+                    split_into(&part, &mut out)?;
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_any(CommaVisitor(std::marker::PhantomData)).map(CommaSeparated)
+    }
+}
+
+fn split_into<T, E>(value: &str, out: &mut Vec<T>) -> Result<(), E>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+    E: de::Error,
+{
+    for item in value.split(',').filter(|s| !s.is_empty()) {
+        out.push(item.parse().map_err(E::custom)?);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "openapi")]
+impl<T: schemars::JsonSchema> schemars::JsonSchema for CommaSeparated<T> {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        format!("CommaSeparated_{}", T::schema_name())
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        let mut schema = gen.subschema_for::<Vec<T>>().into_object();
+        schema.extensions.insert(EXPLODE_EXTENSION.to_string(), serde_json::Value::Bool(false));
+        schema.into()
+    }
+}
+
+/// Schema extension marking a list parameter as comma-separated (`explode: false`)
+#[cfg(feature = "openapi")]
+pub(crate) const EXPLODE_EXTENSION: &str = "x-hayai-explode";
+
+struct QueryDeserializer {
+    entries: Vec<(String, Vec<String>)>,
+}
+
+impl<'de> Deserializer<'de> for QueryDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(QueryMap { entries: self.entries.into_iter(), pending: None })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Map access over the entries; value errors are prefixed with their key
+struct QueryMap {
+    entries: std::vec::IntoIter<(String, Vec<String>)>,
+    pending: Option<(String, Vec<String>)>,
+}
+
+impl<'de> MapAccess<'de> for QueryMap {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some((key, values)) => {
+                let k = seed.deserialize(key.as_str().into_deserializer())?;
+                self.pending = Some((key, values));
+                Ok(Some(k))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, values) = self.pending.take().ok_or_else(|| de::Error::custom("value without key"))?;
+        seed.deserialize(Values(values)).map_err(|e| de::Error::custom(format!("{}: {}", key, e)))
+    }
+}
+
+/// All values given for one key
+struct Values(Vec<String>);
+
+impl Values {
+    /// The value of a scalar; a key repeated with different values is ambiguous
+    fn last(mut self) -> Result<Part, Error> {
+        let last = self.0.pop().ok_or_else(|| de::Error::custom("missing value"))?;
+        if let Some(other) = self.0.iter().find(|v| **v != last) {
+            return Err(de::Error::custom(format!("conflicting values `{}` and `{}`", other, last)));
+        }
+        Ok(Part(last))
+    }
+}
+
+macro_rules! forward_to_last_value {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            self.last()?.$method(visitor)
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for Values {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.0.len() == 1 {
+            self.last()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.into_iter().map(Part)))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.as_slice() {
+            [] => visitor.visit_none(),
+            [only] if only.is_empty() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.last()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_last_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_unit
+        deserialize_identifier
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit_struct tuple tuple_struct map struct ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Values {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// A single decoded value
+struct Part(String);
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0.parse() {
+                Ok(v) => visitor.$visit(v),
+                Err(_) => Err(de::Error::custom(format!("invalid value `{}`", self.0))),
+            }
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for Part {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Part {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
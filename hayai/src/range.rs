@@ -0,0 +1,168 @@
+//! Partial responses for large files, see [`RangedFile`].
+//!
+//! A handler returning `RangedFile` answers `Range: bytes=..` requests with
+//! `206 Partial Content` and streams only the requested slice, so browsers can seek in a
+//! video proxied from object storage. A range starting past the end answers `416`;
+//! requests without a range, or asking for several, get the whole file. Every answer
+//! says `Accept-Ranges: bytes`, and HEAD requests never open the file.
+//!
+//! ```ignore
+//! #[get("/artifacts/{key}")]
+//! async fn artifact(key: String, dep(store): Dep<ObjectStore>) -> Result<RangedFile, ApiError> {
+//!     let size = store.size(&key).await?;
+//!     Ok(RangedFile::new(size, move |range| store.reader(&key, range)).content_type("video/mp4"))
+//! }
+//! ```
+
+use crate::{openapi, ApiError};
+use axum::body::Body;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::ReaderStream;
+
+/// Type name the route macros recognize
+pub const RANGED_FILE: &str = "RangedFile";
+
+type Open = Box<dyn FnOnce(Range<u64>) -> Pin<Box<dyn AsyncRead + Send>> + Send>;
+
+/// A file of known size answered in whole or in part, as the `Range` header asks
+pub struct RangedFile {
+    size: u64,
+    content_type: String,
+    open: Open,
+}
+
+impl RangedFile {
+    /// A file of `size` bytes. `open` is called at most once, with the bytes the response
+    /// sends, and returns a reader starting at the first of them.
+    pub fn new<R>(size: u64, open: impl FnOnce(Range<u64>) -> R + Send + 'static) -> Self
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        Self {
+            size,
+            content_type: "application/octet-stream".to_string(),
+            open: Box::new(move |range| Box::pin(open(range))),
+        }
+    }
+
+    /// The `Content-Type` of the file (default `application/octet-stream`)
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = content_type.into();
+        self
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// What a `Range` header asks of a file
+#[derive(Debug, PartialEq)]
+enum Requested {
+    Whole,
+    Part(Range<u64>),
+    Unsatisfiable,
+}
+
+/// Parse a `Range` header for a file of `size` bytes. Headers this can't serve as one slice,
+/// malformed or in another unit, are ignored as the RFC allows.
+fn requested(range: Option<&HeaderValue>, size: u64) -> Requested {
+    let Some(spec) = range.and_then(|v| v.to_str().ok()).and_then(|v| v.trim().strip_prefix("bytes=")) else {
+        return Requested::Whole;
+    };
+    // Several ranges would need a multipart body
+    let Some((first, last)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {
+        return Requested::Whole;
+    };
+    let range = match (first.trim(), last.trim()) {
+        // The final `n` bytes
+        ("", n) => match n.parse::<u64>() {
+            Ok(0) => return Requested::Unsatisfiable,
+            Ok(n) => size.saturating_sub(n)..size,
+            Err(_) => return Requested::Whole,
+        },
+        (first, last) => {
+            let Ok(first) = first.parse::<u64>() else { return Requested::Whole };
+            match last {
+                "" => first..size,
+                last => match last.parse::<u64>() {
+                    Ok(last) if last >= first => first..last.saturating_add(1).min(size),
+                    _ => return Requested::Whole,
+                },
+            }
+        }
+    };
+    if range.start >= size {
+        Requested::Unsatisfiable
+    } else {
+        Requested::Part(range)
+    }
+}
+
+#[doc(hidden)]
+pub fn __ranged_response(parts: &Parts, file: RangedFile) -> Result<Response, ApiError> {
+    let size = file.size;
+    let (status, range) = match requested(parts.headers.get(header::RANGE), size) {
+        Requested::Whole => (StatusCode::OK, 0..size),
+        Requested::Part(range) => (StatusCode::PARTIAL_CONTENT, range),
+        Requested::Unsatisfiable => {
+            let mut response = ApiError::range_not_satisfiable(format!("Range starts beyond the {} bytes of the file", size)).into_response();
+            let headers = response.headers_mut();
+            headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+            headers.insert(header::CONTENT_RANGE, format!("bytes */{}", size).parse().expect("valid header"));
+            return Ok(response);
+        }
+    };
+    let length = range.end - range.start;
+    let body = if parts.method == Method::HEAD {
+        Body::empty()
+    } else {
+        let reader = (file.open)(range.clone()).take(length);
+        Body::from_stream(ReaderStream::new(reader))
+    };
+
+    let mut response = (status, body).into_response();
+    let headers = response.headers_mut();
+    let content_type = HeaderValue::from_str(&file.content_type)
+        .map_err(|_| ApiError::internal(format!("Invalid content type: {}", file.content_type)))?;
+    headers.insert(header::CONTENT_TYPE, content_type);
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    if status == StatusCode::PARTIAL_CONTENT {
+        let content_range = format!("bytes {}-{}/{}", range.start, range.end - 1, size);
+        headers.insert(header::CONTENT_RANGE, content_range.parse().expect("valid header"));
+    }
+    Ok(response)
+}
+
+/// Document a `RangedFile` operation: a binary body, the whole file or a `206` slice, and
+/// `416` for ranges past the end
+pub(crate) fn document(operation: &mut openapi::Operation) {
+    let binary = serde_json::json!({ "type": "string", "format": "binary" });
+    let accept_ranges = || ("Accept-Ranges".to_string(), openapi::ResponseHeader::new("string", "`bytes`: the file can be fetched in parts"));
+    let content_range = |description| ("Content-Range".to_string(), openapi::ResponseHeader::new("string", description));
+
+    for (_, whole) in operation.responses.iter_mut().filter(|(status, _)| status.starts_with('2')) {
+        whole.schema_ref = Some(binary.clone());
+        let (name, header) = accept_ranges();
+        whole.headers.insert(name, header);
+    }
+    operation.responses.insert("206".to_string(), openapi::ResponseDef {
+        description: openapi::status_description(206).to_string(),
+        schema_ref: Some(binary),
+        headers: HashMap::from([accept_ranges(), content_range("The slice sent and the file's size, `bytes 0-1023/4096`")]),
+        links: Vec::new(),
+    });
+    operation.responses.insert("416".to_string(), openapi::ResponseDef {
+        description: openapi::status_description(416).to_string(),
+        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/ApiError" })),
+        headers: HashMap::from([accept_ranges(), content_range("The file's size, `bytes */4096`")]),
+        links: Vec::new(),
+    });
+}
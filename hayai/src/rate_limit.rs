@@ -0,0 +1,102 @@
+//! Fixed-window request counters, checked by handlers or middleware.
+//!
+//! A [`RateLimiter`] counts the hits on a key (a client IP, an API key, a subject) within
+//! a window of time and says whether the latest one is still within the limit. Counters
+//! live in a [`MemoryRateLimiter`] per process; behind the `redis` feature,
+//! [`RedisRateLimiter`](crate::redis::RedisRateLimiter) shares them between replicas.
+//!
+//! ```ignore
+//! #[post("/login")]
+//! async fn login(limiter: Dep<MemoryRateLimiter>, ip: ClientIp, body: Login) -> Result<Session, ApiError> {
+//!     let decision = limiter.hit(&format!("login:{}", ip.0), RateLimit::new(5, Duration::from_secs(60))).await;
+//!     if !decision.allowed {
+//!         return Err(ApiError::new(StatusCode::TOO_MANY_REQUESTS, "Too many login attempts"));
+//!     }
+//!     ...
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Keys a [`MemoryRateLimiter`] holds before dropping the ones whose window has passed
+const PRUNE_AT: usize = 4096;
+
+/// At most `requests` hits per `window`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window: Duration,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, window: Duration) -> Self {
+        Self { requests, window }
+    }
+}
+
+/// Outcome of one [`RateLimiter::hit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateDecision {
+    pub allowed: bool,
+    /// Hits left in the current window
+    pub remaining: u32,
+    /// Time until the window starts over, e.g. for a `Retry-After` header
+    pub reset_after: Duration,
+}
+
+impl RateDecision {
+    /// What a limiter answers when it can't count, letting the request through
+    pub fn unlimited(limit: RateLimit) -> Self {
+        Self { allowed: true, remaining: limit.requests, reset_after: Duration::ZERO }
+    }
+
+    pub(crate) fn counted(count: u64, limit: RateLimit, reset_after: Duration) -> Self {
+        Self {
+            allowed: count <= u64::from(limit.requests),
+            remaining: u64::from(limit.requests).saturating_sub(count) as u32,
+            reset_after,
+        }
+    }
+}
+
+/// Counters of hits per key, e.g. [`MemoryRateLimiter`] or a shared store such as Redis
+pub trait RateLimiter: Send + Sync + 'static {
+    /// Count one hit on `key`, starting a window of `limit.window` on the first
+    fn hit(&self, key: &str, limit: RateLimit) -> impl Future<Output = RateDecision> + Send;
+}
+
+struct Window {
+    count: u64,
+    resets: Instant,
+}
+
+/// In-process [`RateLimiter`]
+#[derive(Default)]
+pub struct MemoryRateLimiter {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl MemoryRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RateLimiter for MemoryRateLimiter {
+    async fn hit(&self, key: &str, limit: RateLimit) -> RateDecision {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        if windows.len() >= PRUNE_AT {
+            windows.retain(|_, window| window.resets > now);
+        }
+        let window = windows.entry(key.to_string()).or_insert(Window { count: 0, resets: now + limit.window });
+        if window.resets <= now {
+            *window = Window { count: 0, resets: now + limit.window };
+        }
+        window.count += 1;
+        RateDecision::counted(window.count, limit, window.resets - now)
+    }
+}
@@ -0,0 +1,352 @@
+//! Stores shared by every replica through Redis, behind the `redis` feature:
+//! [`RedisResponseCache`] for [`#[cache]`](crate::cache) entries, [`RedisRateLimiter`] for
+//! [rate limit](crate::rate_limit) counters and [`RedisIdempotencyStore`] for
+//! [idempotency keys](crate::idempotency).
+//!
+//! Each store keeps its keys after a prefix (`hayai:cache:`, `hayai:rate:` and
+//! `hayai:idempotency:` by default) so several apps can share one database, and maps TTLs
+//! and windows to Redis expiries. Requests go over a [`RedisPool`] of multiplexed
+//! connections that reconnect on their own.
+//!
+//! When Redis can't be reached or returns something unreadable, a warning is logged and:
+//! - the cache fails open: the request is handled as a miss and nothing is stored;
+//! - the rate limiter fails open, letting the request through, unless built
+//!   [`fail_closed`](RedisRateLimiter::fail_closed);
+//! - the idempotency store fails closed with an [`IdempotencyError`], unless built
+//!   [`fail_open`](RedisIdempotencyStore::fail_open).
+//!
+//! ```ignore
+//! let pool = RedisPool::connect("redis://cache.internal:6379/0", 8).await?;
+//! let app = HayaiApp::new()
+//!     .response_cache(RedisResponseCache::new(pool.clone()).key_prefix("orders:cache:"))
+//!     .dep(RedisRateLimiter::new(pool.clone()))
+//!     .dep(RedisIdempotencyStore::new(pool));
+//! ```
+
+use crate::cache::{CachedResponse, ResponseCache};
+use crate::idempotency::{Claim, IdempotencyError, IdempotencyStore};
+use crate::rate_limit::{RateDecision, RateLimit, RateLimiter};
+use ::redis::aio::{ConnectionLike, ConnectionManager};
+use ::redis::{AsyncCommands, Cmd, ExistenceCheck, Pipeline, RedisFuture, SetExpiry, SetOptions, Value};
+use axum::body::Bytes;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use ::redis as client;
+pub use ::redis::{RedisError, RedisResult};
+
+/// Connections opened by each store's `connect`
+pub const DEFAULT_POOL_SIZE: usize = 4;
+
+/// Prefix of the keys written by [`RedisResponseCache`] unless set with
+/// [`key_prefix`](RedisResponseCache::key_prefix)
+pub const DEFAULT_KEY_PREFIX: &str = "hayai:cache:";
+
+/// Prefix of the keys written by [`RedisRateLimiter`]
+pub const DEFAULT_RATE_LIMIT_PREFIX: &str = "hayai:rate:";
+
+/// Prefix of the keys written by [`RedisIdempotencyStore`]
+pub const DEFAULT_IDEMPOTENCY_PREFIX: &str = "hayai:idempotency:";
+
+/// Connections to one Redis server, taken in turn
+#[derive(Clone)]
+pub struct RedisPool {
+    connections: Arc<[ConnectionManager]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl RedisPool {
+    /// Open `size` connections to `url`, e.g. `redis://:password@host:6379/0`
+    pub async fn connect(url: &str, size: usize) -> RedisResult<Self> {
+        let client = ::redis::Client::open(url)?;
+        let mut connections = Vec::with_capacity(size.max(1));
+        for _ in 0..size.max(1) {
+            connections.push(ConnectionManager::new(client.clone()).await?);
+        }
+        Ok(Self { connections: connections.into(), next: Arc::new(AtomicUsize::new(0)) })
+    }
+
+    fn take(&self) -> ConnectionManager {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        self.connections[index].clone()
+    }
+}
+
+impl ConnectionLike for RedisPool {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let mut connection = self.take();
+        Box::pin(async move { connection.req_packed_command(cmd).await })
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        let mut connection = self.take();
+        Box::pin(async move { connection.req_packed_commands(cmd, offset, count).await })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.connections[0].get_db()
+    }
+}
+
+/// [`ResponseCache`] in Redis, see the [module docs](self)
+///
+/// Any async Redis connection works, e.g. a cluster connection or a test double;
+/// [`connect`](RedisResponseCache::connect) opens a [`RedisPool`].
+#[derive(Clone)]
+pub struct RedisResponseCache<C = RedisPool> {
+    connection: C,
+    key_prefix: String,
+}
+
+impl RedisResponseCache<RedisPool> {
+    /// Cache in the Redis server at `url` over [`DEFAULT_POOL_SIZE`] connections
+    pub async fn connect(url: &str) -> RedisResult<Self> {
+        Ok(Self::new(RedisPool::connect(url, DEFAULT_POOL_SIZE).await?))
+    }
+}
+
+impl<C> RedisResponseCache<C> {
+    pub fn new(connection: C) -> Self {
+        Self { connection, key_prefix: DEFAULT_KEY_PREFIX.to_string() }
+    }
+
+    /// Put `prefix` before every key instead of [`DEFAULT_KEY_PREFIX`]
+    pub fn key_prefix(mut self, prefix: &str) -> Self {
+        self.key_prefix = prefix.to_string();
+        self
+    }
+}
+
+impl<C: ConnectionLike + Clone + Send + Sync + 'static> ResponseCache for RedisResponseCache<C> {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut connection = self.connection.clone();
+        let stored: Option<Vec<u8>> = match connection.get(format!("{}{}", self.key_prefix, key)).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!(error = %e, "Redis cache lookup failed, handling the request as a miss");
+                return None;
+            }
+        };
+        let decoded = decode(&stored?);
+        if decoded.is_none() {
+            tracing::warn!(key = %key, "unreadable Redis cache entry, handling the request as a miss");
+        }
+        decoded
+    }
+
+    async fn put(&self, key: &str, response: CachedResponse, ttl: Duration) {
+        let mut connection = self.connection.clone();
+        let expiry = SetOptions::default().with_expiration(SetExpiry::PX(millis(ttl)));
+        let stored: RedisResult<()> = connection.set_options(format!("{}{}", self.key_prefix, key), encode(&response), expiry).await;
+        if let Err(e) = stored {
+            tracing::warn!(error = %e, "Redis cache store failed, the response is not cached");
+        }
+    }
+}
+
+/// [`RateLimiter`] in Redis, see the [module docs](self)
+///
+/// Each window is a counter incremented by every hit and expiring with the window, so
+/// replicas count together and a window starts over once Redis drops the key.
+#[derive(Clone)]
+pub struct RedisRateLimiter<C = RedisPool> {
+    connection: C,
+    key_prefix: String,
+    fail_closed: bool,
+}
+
+impl RedisRateLimiter<RedisPool> {
+    /// Count in the Redis server at `url` over [`DEFAULT_POOL_SIZE`] connections
+    pub async fn connect(url: &str) -> RedisResult<Self> {
+        Ok(Self::new(RedisPool::connect(url, DEFAULT_POOL_SIZE).await?))
+    }
+}
+
+impl<C> RedisRateLimiter<C> {
+    pub fn new(connection: C) -> Self {
+        Self { connection, key_prefix: DEFAULT_RATE_LIMIT_PREFIX.to_string(), fail_closed: false }
+    }
+
+    /// Put `prefix` before every key instead of [`DEFAULT_RATE_LIMIT_PREFIX`]
+    pub fn key_prefix(mut self, prefix: &str) -> Self {
+        self.key_prefix = prefix.to_string();
+        self
+    }
+
+    /// Refuse requests while Redis can't count them, instead of letting them through
+    pub fn fail_closed(mut self) -> Self {
+        self.fail_closed = true;
+        self
+    }
+}
+
+impl<C: ConnectionLike + Clone + Send + Sync + 'static> RedisRateLimiter<C> {
+    async fn count(&self, key: &str, window: Duration) -> RedisResult<(u64, Duration)> {
+        let mut connection = self.connection.clone();
+        let key = format!("{}{}", self.key_prefix, key);
+        let count: u64 = connection.incr(&key, 1).await?;
+        if count == 1 {
+            let _: () = connection.pexpire(&key, millis(window) as i64).await?;
+        }
+        let mut ttl: i64 = connection.pttl(&key).await?;
+        // -1: the replica that started the window failed before setting its expiry
+        if ttl == -1 {
+            let _: () = connection.pexpire(&key, millis(window) as i64).await?;
+            ttl = millis(window) as i64;
+        }
+        Ok((count, Duration::from_millis(ttl.max(0) as u64)))
+    }
+}
+
+impl<C: ConnectionLike + Clone + Send + Sync + 'static> RateLimiter for RedisRateLimiter<C> {
+    async fn hit(&self, key: &str, limit: RateLimit) -> RateDecision {
+        match self.count(key, limit.window).await {
+            Ok((count, reset_after)) => RateDecision::counted(count, limit, reset_after),
+            Err(e) if self.fail_closed => {
+                tracing::warn!(error = %e, "Redis rate limit count failed, refusing the request");
+                RateDecision { allowed: false, remaining: 0, reset_after: limit.window }
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Redis rate limit count failed, letting the request through");
+                RateDecision::unlimited(limit)
+            }
+        }
+    }
+}
+
+/// [`IdempotencyStore`] in Redis, see the [module docs](self)
+///
+/// A claim sets the key to an empty marker only if it doesn't exist, so exactly one
+/// replica runs the request; completing replaces the marker with the encoded response.
+#[derive(Clone)]
+pub struct RedisIdempotencyStore<C = RedisPool> {
+    connection: C,
+    key_prefix: String,
+    fail_open: bool,
+}
+
+impl RedisIdempotencyStore<RedisPool> {
+    /// Keep keys in the Redis server at `url` over [`DEFAULT_POOL_SIZE`] connections
+    pub async fn connect(url: &str) -> RedisResult<Self> {
+        Ok(Self::new(RedisPool::connect(url, DEFAULT_POOL_SIZE).await?))
+    }
+}
+
+impl<C> RedisIdempotencyStore<C> {
+    pub fn new(connection: C) -> Self {
+        Self { connection, key_prefix: DEFAULT_IDEMPOTENCY_PREFIX.to_string(), fail_open: false }
+    }
+
+    /// Put `prefix` before every key instead of [`DEFAULT_IDEMPOTENCY_PREFIX`]
+    pub fn key_prefix(mut self, prefix: &str) -> Self {
+        self.key_prefix = prefix.to_string();
+        self
+    }
+
+    /// Run requests while Redis can't be reached, as if each key were new, instead of
+    /// failing them
+    pub fn fail_open(mut self) -> Self {
+        self.fail_open = true;
+        self
+    }
+
+    fn failed<T>(&self, e: impl std::fmt::Display, open: T) -> Result<T, IdempotencyError> {
+        if self.fail_open {
+            tracing::warn!(error = %e, "Redis idempotency store failed, handling the request as new");
+            return Ok(open);
+        }
+        tracing::warn!(error = %e, "Redis idempotency store failed, refusing the request");
+        Err(IdempotencyError { message: e.to_string() })
+    }
+}
+
+impl<C: ConnectionLike + Clone + Send + Sync + 'static> RedisIdempotencyStore<C> {
+    async fn try_claim(&self, key: &str, ttl: Duration) -> RedisResult<Option<Claim>> {
+        let mut connection = self.connection.clone();
+        let key = format!("{}{}", self.key_prefix, key);
+        let marker = SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::PX(millis(ttl)));
+        let set: Option<()> = connection.set_options(&key, b"", marker).await?;
+        if set.is_some() {
+            return Ok(Some(Claim::New));
+        }
+        let stored: Option<Vec<u8>> = connection.get(&key).await?;
+        Ok(match stored {
+            // Expired between the two commands
+            None => None,
+            Some(stored) if stored.is_empty() => Some(Claim::InProgress),
+            Some(stored) => match decode(&stored) {
+                Some(response) => Some(Claim::Completed(response)),
+                None => return Err(::redis::RedisError::from((::redis::ErrorKind::TypeError, "unreadable idempotency entry"))),
+            },
+        })
+    }
+}
+
+impl<C: ConnectionLike + Clone + Send + Sync + 'static> IdempotencyStore for RedisIdempotencyStore<C> {
+    async fn claim(&self, key: &str, ttl: Duration) -> Result<Claim, IdempotencyError> {
+        for _ in 0..2 {
+            match self.try_claim(key, ttl).await {
+                Ok(Some(claim)) => return Ok(claim),
+                Ok(None) => continue,
+                Err(e) => return self.failed(e, Claim::New),
+            }
+        }
+        // Claimed and dropped twice in a row: someone else is working on it
+        Ok(Claim::InProgress)
+    }
+
+    async fn complete(&self, key: &str, response: CachedResponse, ttl: Duration) -> Result<(), IdempotencyError> {
+        let mut connection = self.connection.clone();
+        let expiry = SetOptions::default().with_expiration(SetExpiry::PX(millis(ttl)));
+        let stored: RedisResult<()> = connection.set_options(format!("{}{}", self.key_prefix, key), encode(&response), expiry).await;
+        stored.or_else(|e| self.failed(e, ()))
+    }
+
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError> {
+        let mut connection = self.connection.clone();
+        let released: RedisResult<()> = connection.del(format!("{}{}", self.key_prefix, key)).await;
+        released.or_else(|e| self.failed(e, ()))
+    }
+}
+
+/// Redis rejects a zero expiry
+fn millis(ttl: Duration) -> u64 {
+    ttl.as_millis().max(1) as u64
+}
+
+/// Status, then each header as length-prefixed name and value, then the body
+fn encode(response: &CachedResponse) -> Vec<u8> {
+    let mut out = Vec::with_capacity(response.body.len() + 64);
+    out.extend_from_slice(&response.status.to_be_bytes());
+    out.extend_from_slice(&(response.headers.len() as u32).to_be_bytes());
+    for (name, value) in &response.headers {
+        for part in [name.as_bytes(), value.as_slice()] {
+            out.extend_from_slice(&(part.len() as u32).to_be_bytes());
+            out.extend_from_slice(part);
+        }
+    }
+    out.extend_from_slice(&response.body);
+    out
+}
+
+fn decode(mut bytes: &[u8]) -> Option<CachedResponse> {
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+        let (head, rest) = bytes.split_at_checked(n)?;
+        *bytes = rest;
+        Some(head)
+    }
+    fn length(bytes: &mut &[u8]) -> Option<usize> {
+        Some(u32::from_be_bytes(take(bytes, 4)?.try_into().ok()?) as usize)
+    }
+    let status = u16::from_be_bytes(take(&mut bytes, 2)?.try_into().ok()?);
+    let count = length(&mut bytes)?;
+    let mut headers = Vec::new();
+    for _ in 0..count {
+        let len = length(&mut bytes)?;
+        let name = String::from_utf8(take(&mut bytes, len)?.to_vec()).ok()?;
+        let len = length(&mut bytes)?;
+        headers.push((name, take(&mut bytes, len)?.to_vec()));
+    }
+    Some(CachedResponse { status, headers, body: Bytes::copy_from_slice(bytes) })
+}
@@ -0,0 +1,130 @@
+//! Introspection over the routes and models registered in this binary.
+//!
+//! Every `#[get]`/`#[post]`/... handler and `#[api_model]` type registers itself at link
+//! time; this module lists them without building an app or parsing the spec:
+//!
+//! ```ignore
+//! for route in hayai::registry::routes().into_iter().filter(|r| r.has_tag("users")) {
+//!     println!("{} {}", route.method(), route.full_path("/api"));
+//! }
+//! ```
+//!
+//! The public fields of [`RouteInfo`] and [`SchemaInfo`] are filled in by the macros and
+//! may change between releases; prefer the accessors here.
+
+use crate::{openapi, RouteInfo, SchemaInfo};
+
+/// Every registered route, ordered by path then method
+pub fn routes() -> Vec<&'static RouteInfo> {
+    let mut routes: Vec<&'static RouteInfo> = inventory::iter::<&'static RouteInfo>.into_iter().copied().collect();
+    routes.sort_by_key(|r| (r.path, r.method));
+    routes
+}
+
+/// Every registered model, ordered by component name
+pub fn schemas() -> Vec<&'static SchemaInfo> {
+    let mut schemas: Vec<&'static SchemaInfo> = inventory::iter::<SchemaInfo>.into_iter().collect();
+    schemas.sort_by_cached_key(|s| s.component_name());
+    schemas
+}
+
+/// Append `path` to `prefix` with exactly one slash between them; an empty `path`
+/// leaves the prefix without its trailing slash
+pub(crate) fn join_path(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if path.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{}/{}", prefix, path.trim_start_matches('/'))
+    }
+}
+
+impl RouteInfo {
+    /// The path as declared on the handler, e.g. `/users/{id}`
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The path mounted below `prefix` (a router prefix such as `/api/v1`)
+    pub fn full_path(&self, prefix: &str) -> String {
+        join_path(prefix, self.path)
+    }
+
+    /// Upper-case HTTP method
+    pub fn method(&self) -> &'static str {
+        self.method
+    }
+
+    /// Name of the handler function, also the `operationId` unless renamed, see [`crate::operation_id`]
+    pub fn handler_name(&self) -> &'static str {
+        self.handler_name
+    }
+
+    /// The handler's doc comment
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Tags declared with `#[tag(..)]` on the handler itself
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(&tag)
+    }
+
+    /// Security schemes declared with `#[security(..)]` on the handler itself
+    pub fn security(&self) -> &'static [&'static str] {
+        self.security
+    }
+
+    /// Status of a successful response
+    pub fn success_status(&self) -> u16 {
+        self.success_status
+    }
+
+    /// Whether a successful response has a body: not for 204s, nor for handlers returning
+    /// `()` or `NoContent` under another `#[status]`
+    pub fn has_response_body(&self) -> bool {
+        self.success_status != 204 && self.response_type_name != "()"
+    }
+
+    /// Component name of the request body, if the handler takes one
+    pub fn request_body(&self) -> Option<String> {
+        self.has_body.then(|| self.component_name(self.body_type_name))
+    }
+
+    /// Component name of the success response (the element type for `Vec<T>`, pages and
+    /// batches); `None` for empty responses, untyped JSON, maps and tuples
+    pub fn response(&self) -> Option<String> {
+        let name = match self.is_vec_response || self.pagination.is_some() || self.bulk {
+            _ if !self.has_response_body() || self.response_schema_fn.is_some() => return None,
+            true => self.vec_inner_type_name,
+            false => self.response_type_name,
+        };
+        (name != "Value").then(|| self.component_name(name))
+    }
+}
+
+impl SchemaInfo {
+    /// The component schema, including `#[validate]`/`#[schema]` constraints
+    pub fn schema(&self) -> openapi::Schema {
+        (self.schema_fn)()
+    }
+
+    /// The component schema as it appears in the spec
+    pub fn json(&self) -> serde_json::Value {
+        self.schema().to_json_value()
+    }
+
+    /// Schemas of non-model types this model references, keyed by component name
+    pub fn nested(&self) -> std::collections::HashMap<String, openapi::Schema> {
+        (self.nested_fn)()
+    }
+
+    /// The `#[validate]`/`#[schema]` attributes of each field, keyed by property name
+    pub fn patches(&self) -> std::collections::HashMap<String, openapi::PropertyPatch> {
+        (self.patches_fn)()
+    }
+}
@@ -0,0 +1,224 @@
+//! Configuration replaced while the server runs, see
+//! [`HayaiApp::reloadable_config`](crate::HayaiApp::reloadable_config).
+//!
+//! Handlers read the current value through `Dep<Reloadable<T>>`: [`Reloadable::load`]
+//! returns a snapshot, so a request keeps the configuration it started with while later
+//! requests see the new one. A [`ConfigWatcher`] produces the new values: a closure, a
+//! channel of JSON documents, or a [`FileWatcher`] rereading a [`ConfigSource`]. A value
+//! that fails to load, or that [`validated`](ConfigWatcher::validated) refuses, is logged
+//! and the current one kept. Watchers stop once shutdown begins or the router is dropped.
+//!
+//! ```ignore
+//! let source = ConfigSource::env("APP_").file("limits.toml");
+//! let watcher = FileWatcher::new(source.clone()).validated(|limits: &Limits| {
+//!     if limits.max_upload > 0 { Ok(()) } else { Err("max_upload: must be positive".to_string()) }
+//! });
+//! HayaiApp::new().reloadable_config(source.load::<Limits>()?, watcher)
+//!
+//! #[post("/uploads")]
+//! async fn upload(body: Upload, limits: Dep<Reloadable<Limits>>) -> Receipt {
+//!     let limits = limits.load();
+//!     ...
+//! }
+//! ```
+
+use crate::config::{ConfigError, ConfigSource};
+use crate::deadline::ShutdownSignal;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+/// How often a [`FileWatcher`] checks its file by default
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A value replaced as a whole by reloads; clones share it
+pub struct Reloadable<T> {
+    current: Arc<RwLock<Arc<T>>>,
+}
+
+impl<T> Reloadable<T> {
+    pub fn new(initial: T) -> Self {
+        Self { current: Arc::new(RwLock::new(Arc::new(initial))) }
+    }
+
+    /// The current value, unaffected by reloads after this call
+    pub fn load(&self) -> Arc<T> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Replace the value, taking effect for the next [`load`](Reloadable::load)
+    pub fn store(&self, value: T) {
+        *self.current.write().unwrap() = Arc::new(value);
+    }
+}
+
+impl<T> Clone for Reloadable<T> {
+    fn clone(&self) -> Self {
+        Self { current: self.current.clone() }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Reloadable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Reloadable").field(&self.load()).finish()
+    }
+}
+
+/// Produces new values of a [`Reloadable`] configuration.
+///
+/// Implemented by closures returning the next value, by channels of JSON documents
+/// deserialized into `T`, and by [`FileWatcher`].
+pub trait ConfigWatcher<T>: Send + 'static {
+    /// The next value, the error of one that failed to load, or `None` once there will be
+    /// no more
+    fn next(&mut self) -> impl Future<Output = Option<Result<T, ConfigError>>> + Send;
+
+    /// Reject values `check` refuses, e.g. limits that deserialize but are out of range;
+    /// the message is logged like a value that failed to load
+    fn validated<F>(self, check: F) -> Validated<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&T) -> Result<(), String> + Send + 'static,
+    {
+        Validated { watcher: self, check }
+    }
+}
+
+/// A [`ConfigWatcher`] whose values must pass a check, see [`ConfigWatcher::validated`]
+pub struct Validated<W, F> {
+    watcher: W,
+    check: F,
+}
+
+impl<T, W, F> ConfigWatcher<T> for Validated<W, F>
+where
+    T: Send,
+    W: ConfigWatcher<T>,
+    F: FnMut(&T) -> Result<(), String> + Send + 'static,
+{
+    async fn next(&mut self) -> Option<Result<T, ConfigError>> {
+        let value = self.watcher.next().await?;
+        Some(value.and_then(|value| match (self.check)(&value) {
+            Ok(()) => Ok(value),
+            Err(message) => Err(ConfigError { variable: None, message }),
+        }))
+    }
+}
+
+impl<T, F, Fut> ConfigWatcher<T> for F
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Option<Result<T, ConfigError>>> + Send,
+{
+    fn next(&mut self) -> impl Future<Output = Option<Result<T, ConfigError>>> + Send {
+        self()
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> ConfigWatcher<T> for mpsc::Receiver<serde_json::Value> {
+    async fn next(&mut self) -> Option<Result<T, ConfigError>> {
+        let document = self.recv().await?;
+        Some(serde_json::from_value(document).map_err(|e| ConfigError { variable: None, message: e.to_string() }))
+    }
+}
+
+/// Reloads a [`ConfigSource`] whenever the modification time of its file changes,
+/// checked every [`DEFAULT_POLL_INTERVAL`]
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    source: ConfigSource,
+    path: PathBuf,
+    interval: Duration,
+    modified: Option<SystemTime>,
+}
+
+impl FileWatcher {
+    /// Panics if `source` has no file
+    pub fn new(source: ConfigSource) -> Self {
+        let path = source.file.clone().expect("FileWatcher needs a ConfigSource with a file");
+        let modified = modified(&path);
+        Self { source, path, interval: DEFAULT_POLL_INTERVAL, modified }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl<T: DeserializeOwned + Send + 'static> ConfigWatcher<T> for FileWatcher {
+    async fn next(&mut self) -> Option<Result<T, ConfigError>> {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            let modified = modified(&self.path);
+            if modified != self.modified {
+                self.modified = modified;
+                return Some(self.source.load());
+            }
+        }
+    }
+}
+
+/// A watcher's loop, started by `into_router`
+pub(crate) type WatchTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The running watcher loops, aborted when dropped
+#[derive(Default)]
+pub(crate) struct Watchers(Vec<AbortHandle>);
+
+impl Watchers {
+    /// Run `tasks` until they end or shutdown begins; panics outside a Tokio runtime
+    pub(crate) fn spawn(tasks: Vec<WatchTask>, shutdown: &Arc<ShutdownSignal>) -> Self {
+        if tasks.is_empty() {
+            return Self::default();
+        }
+        let runtime = tokio::runtime::Handle::try_current()
+            .unwrap_or_else(|_| panic!("reloadable_config watchers need into_router to be called inside a Tokio runtime"));
+        Self(tasks.into_iter()
+            .map(|task| {
+                let shutdown = shutdown.clone();
+                runtime.spawn(async move {
+                    tokio::select! {
+                        _ = task => {}
+                        _ = shutdown.wait() => {}
+                    }
+                }).abort_handle()
+            })
+            .collect())
+    }
+}
+
+impl Drop for Watchers {
+    fn drop(&mut self) {
+        for task in &self.0 {
+            task.abort();
+        }
+    }
+}
+
+/// Store each value `watcher` produces until it has no more
+pub(crate) async fn watch<T, W>(config: Reloadable<T>, mut watcher: W)
+where
+    T: Send + Sync + 'static,
+    W: ConfigWatcher<T>,
+{
+    let name = std::any::type_name::<T>();
+    while let Some(update) = watcher.next().await {
+        match update {
+            Ok(value) => {
+                config.store(value);
+                tracing::info!(target: "hayai::reload", config = name, "configuration reloaded");
+            }
+            Err(e) => tracing::error!(target: "hayai::reload", config = name, error = %e, "new configuration rejected, keeping the current one"),
+        }
+    }
+}
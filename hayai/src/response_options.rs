@@ -0,0 +1,81 @@
+//! Response post-processing enabled per route with `#[response_options(..)]`.
+//!
+//! - `exclude_none` drops `null` members from the serialized response, at any depth
+//! - `sparse_fields` answers `?fields=name,email` with only those top-level members
+//!   (of each element, for list responses); unknown names are a 400
+//!
+//! ```ignore
+//! #[get("/users/{id}")]
+//! #[response_options(exclude_none, sparse_fields)]
+//! async fn get_user(id: i64) -> User { ... }
+//! ```
+
+use crate::{ApiError, AppState};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::Response;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Documentation of the `fields` query parameter added by `sparse_fields`
+pub const FIELDS_DESCRIPTION: &str = "Comma-separated top-level fields to include in the response";
+
+/// The `?fields=` selection, checked against the response model's property names.
+///
+/// `None` when the parameter is absent. An empty `known` list (untyped responses) accepts
+/// any name.
+#[doc(hidden)]
+pub fn __requested_fields(parts: &Parts, known: &[String]) -> Result<Option<Vec<String>>, ApiError> {
+    let query = parts.uri.query().unwrap_or("");
+    let Some(raw) = form_urlencoded::parse(query.as_bytes()).find(|(k, _)| k == "fields").map(|(_, v)| v) else {
+        return Ok(None);
+    };
+    let fields: Vec<String> = raw.split(',').map(str::trim).filter(|f| !f.is_empty()).map(String::from).collect();
+    if !known.is_empty() {
+        let unknown: Vec<&str> = fields.iter().map(String::as_str).filter(|f| !known.iter().any(|k| k == f)).collect();
+        if !unknown.is_empty() {
+            return Err(ApiError::bad_request(format!("Unknown fields requested: {}", unknown.join(", "))));
+        }
+    }
+    Ok(Some(fields))
+}
+
+/// Serialize like [`crate::__json_response`], then apply the route's options
+#[doc(hidden)]
+pub fn __json_response<T: Serialize>(
+    state: &AppState,
+    status: StatusCode,
+    value: &T,
+    exclude_none: bool,
+    fields: Option<&[String]>,
+) -> Result<Response, ApiError> {
+    let mut value = serde_json::to_value(value)
+        .map_err(|e| ApiError::internal(format!("Response serialization failed: {}", e)))?;
+    if let Some(fields) = fields {
+        match &mut value {
+            Value::Array(items) => items.iter_mut().for_each(|item| select(item, fields)),
+            other => select(other, fields),
+        }
+    }
+    if exclude_none {
+        strip_nulls(&mut value);
+    }
+    crate::__json_response(state, status, &value)
+}
+
+fn select(value: &mut Value, fields: &[String]) {
+    if let Value::Object(map) = value {
+        map.retain(|key, _| fields.iter().any(|f| f == key));
+    }
+}
+
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
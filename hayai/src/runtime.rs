@@ -0,0 +1,59 @@
+//! The Tokio runtime [`HayaiApp::serve_blocking`](crate::HayaiApp::serve_blocking) builds,
+//! see [`HayaiApp::runtime`](crate::HayaiApp::runtime).
+//!
+//! With `serve_blocking` the app owns its runtime, so `main` needs no `#[tokio::main]` and
+//! the worker count, blocking pool and thread names come from [`RuntimeConfig`]. Everything
+//! runs on that runtime, in this order: the startup callbacks, warmup, the
+//! [`on_worker_start`](crate::HayaiApp::on_worker_start) hooks, serving, and after the
+//! shutdown signal the shutdown callbacks.
+//!
+//! ```ignore
+//! fn main() {
+//!     HayaiApp::new()
+//!         .runtime(RuntimeConfig { worker_threads: Some(4), ..Default::default() })
+//!         .on_worker_start(|_| metrics::install_runtime_collector())
+//!         .serve_blocking("0.0.0.0:8000");
+//! }
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A hook run on the serving runtime before it accepts connections
+pub(crate) type WorkerStartHook = Box<dyn FnOnce(&tokio::runtime::Handle) + Send>;
+
+/// Shape of the multi-threaded runtime `serve_blocking` builds
+#[derive(Debug, Clone)]
+pub struct RuntimeConfig {
+    /// Threads polling tasks; `None` for one per CPU core
+    pub worker_threads: Option<usize>,
+    /// Most threads running `spawn_blocking` work at once (default 512)
+    pub max_blocking_threads: usize,
+    /// Threads are named `<thread_name>-<n>` (default `hayai-worker`)
+    pub thread_name: String,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            worker_threads: None,
+            max_blocking_threads: 512,
+            thread_name: "hayai-worker".to_string(),
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub(crate) fn build(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        if let Some(workers) = self.worker_threads {
+            builder.worker_threads(workers);
+        }
+        let prefix = self.thread_name.clone();
+        let next = AtomicUsize::new(1);
+        builder
+            .max_blocking_threads(self.max_blocking_threads)
+            .thread_name_fn(move || format!("{}-{}", prefix, next.fetch_add(1, Ordering::Relaxed)))
+            .enable_all()
+            .build()
+    }
+}
@@ -0,0 +1,82 @@
+//! Per-request values computed by middleware and read by handlers.
+//!
+//! Middleware stores values in the request through [`RequestScope`]; handlers declare a
+//! [`Scoped<T>`] argument to receive them. Values are keyed by type, so each type holds at
+//! most one value per request.
+//!
+//! ```ignore
+//! async fn resolve_tenant(mut req: Request, next: Next) -> Response {
+//!     let tenant = Tenant::from_host(req.headers());
+//!     RequestScope::of(&mut req).insert(tenant);
+//!     next.run(req).await
+//! }
+//!
+//! #[get("/projects")]
+//! async fn list_projects(tenant: Scoped<Tenant>, flags: Scoped<Option<Flags>>) -> Vec<Project> { ... }
+//! ```
+
+use crate::ApiError;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{Extensions, Request};
+use std::ops::Deref;
+
+/// Typed values attached to one request
+pub struct RequestScope<'a> {
+    extensions: &'a mut Extensions,
+}
+
+impl<'a> RequestScope<'a> {
+    pub fn of<B>(req: &'a mut Request<B>) -> Self {
+        Self { extensions: req.extensions_mut() }
+    }
+
+    pub fn from_parts(parts: &'a mut Parts) -> Self {
+        Self { extensions: &mut parts.extensions }
+    }
+
+    /// Store `value`, returning the previous value of the same type
+    pub fn insert<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.extensions.insert(Entry(value)).map(|e| e.0)
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get::<Entry<T>>().map(|e| &e.0)
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.extensions.remove::<Entry<T>>().map(|e| e.0)
+    }
+}
+
+/// Keeps scoped values apart from other request extensions of the same type
+#[derive(Clone)]
+struct Entry<T>(T);
+
+/// A value from the [`RequestScope`]; rejects with 500 naming the type when no middleware
+/// stored one. Route handlers may take `Scoped<Option<T>>` to accept its absence.
+#[derive(Debug, Clone)]
+pub struct Scoped<T>(pub T);
+
+impl<T> Deref for Scoped<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static, S: Send + Sync> FromRequestParts<S> for Scoped<T> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts.extensions.get::<Entry<T>>()
+            .map(|e| Scoped(e.0.clone()))
+            .ok_or_else(|| ApiError::internal(format!("No {} in request scope", std::any::type_name::<T>())))
+    }
+}
+
+#[doc(hidden)]
+pub fn __scoped_optional<T: Clone + Send + Sync + 'static>(parts: &Parts) -> Scoped<Option<T>> {
+    Scoped(parts.extensions.get::<Entry<T>>().map(|e| e.0.clone()))
+}
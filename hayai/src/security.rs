@@ -0,0 +1,26 @@
+//! Names of the built-in security schemes, for `#[security(..)]` and
+//! [`HayaiRouter::security`](crate::HayaiRouter::security).
+//!
+//! ```ignore
+//! #[get("/me")]
+//! #[security(hayai::security::BEARER)]
+//! async fn me() -> User { ... }
+//! ```
+//!
+//! Every referenced name must match a scheme registered on the app, either directly by its
+//! component name or through one of these short names; the spec build panics otherwise.
+
+/// HTTP bearer tokens, registered by [`HayaiApp::bearer_auth`](crate::HayaiApp::bearer_auth)
+pub const BEARER: &str = "bearer";
+
+/// API key header, registered by [`HayaiApp::api_key_auth`](crate::HayaiApp::api_key_auth)
+pub const API_KEY: &str = "api_key";
+
+/// The component a security reference is documented with
+pub fn component_name(name: &str) -> &str {
+    match name {
+        BEARER => "bearerAuth",
+        API_KEY => "apiKeyAuth",
+        other => other,
+    }
+}
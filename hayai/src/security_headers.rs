@@ -0,0 +1,170 @@
+//! Security response headers, see [`HayaiApp::security_headers`](crate::HayaiApp::security_headers).
+//!
+//! [`SecurityHeaders::default`] sends `X-Content-Type-Options: nosniff`,
+//! `X-Frame-Options: DENY` and `Referrer-Policy: no-referrer` on every response,
+//! `Strict-Transport-Security` on requests that came over HTTPS (as reported by
+//! [`ConnectionInfo`], so behind a TLS-terminating proxy through its `X-Forwarded-Proto`),
+//! and a `Content-Security-Policy` on HTML responses, i.e. the docs page. The default
+//! policy allows what the configured docs assets load; a page pulling in scripts or styles
+//! from elsewhere (`DocsCustomization::custom_js`, a custom template) needs its own
+//! [`content_security_policy`](SecurityHeaders::content_security_policy).
+//!
+//! Headers a handler sets itself are left alone.
+//!
+//! ```ignore
+//! app.security_headers(SecurityHeaders::default()
+//!     .hsts(Duration::from_secs(2 * 365 * 24 * 3600), true)
+//!     .frame_options(FrameOptions::SameOrigin)
+//!     .without("Referrer-Policy"))
+//! ```
+
+use crate::docs::{self, DocsAssets};
+use crate::proxy::ConnectionInfo;
+use axum::extract::Request;
+use axum::http::{header, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `X-Frame-Options` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameOptions {
+    /// The response is never shown in a frame
+    Deny,
+    /// Only pages of the same origin may frame the response
+    SameOrigin,
+}
+
+impl FrameOptions {
+    fn as_str(self) -> &'static str {
+        match self {
+            FrameOptions::Deny => "DENY",
+            FrameOptions::SameOrigin => "SAMEORIGIN",
+        }
+    }
+}
+
+/// The security headers to send, a preset adjusted header by header
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    frame_options: FrameOptions,
+    referrer_policy: String,
+    hsts_max_age: Duration,
+    hsts_include_subdomains: bool,
+    force_hsts: bool,
+    /// `None` for the policy of the docs assets
+    content_security_policy: Option<String>,
+    removed: Vec<HeaderName>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            frame_options: FrameOptions::Deny,
+            referrer_policy: "no-referrer".to_string(),
+            hsts_max_age: Duration::from_secs(365 * 24 * 3600),
+            hsts_include_subdomains: true,
+            force_hsts: false,
+            content_security_policy: None,
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// `X-Frame-Options` (default `DENY`)
+    pub fn frame_options(mut self, options: FrameOptions) -> Self {
+        self.frame_options = options;
+        self
+    }
+
+    /// `Referrer-Policy` (default `no-referrer`)
+    pub fn referrer_policy(mut self, policy: &str) -> Self {
+        self.referrer_policy = policy.to_string();
+        self
+    }
+
+    /// `Strict-Transport-Security: max-age=..` (default a year, with `includeSubDomains`)
+    pub fn hsts(mut self, max_age: Duration, include_subdomains: bool) -> Self {
+        self.hsts_max_age = max_age;
+        self.hsts_include_subdomains = include_subdomains;
+        self
+    }
+
+    /// Send `Strict-Transport-Security` on plain HTTP requests too, for deployments where
+    /// TLS ends before the app without an `X-Forwarded-Proto` saying so
+    pub fn force_hsts(mut self) -> Self {
+        self.force_hsts = true;
+        self
+    }
+
+    /// `Content-Security-Policy` of HTML responses, replacing the policy derived from the
+    /// docs assets
+    pub fn content_security_policy(mut self, policy: &str) -> Self {
+        self.content_security_policy = Some(policy.to_string());
+        self
+    }
+
+    /// Don't send `name`, e.g. `X-Frame-Options` for an API meant to be framed
+    pub fn without(mut self, name: &str) -> Self {
+        let name = HeaderName::try_from(name).unwrap_or_else(|_| panic!("Invalid header name: {}", name));
+        self.removed.push(name);
+        self
+    }
+
+    /// The headers sent on every response, and the HSTS and HTML-only ones, for an app
+    /// serving `docs`; panics on values that aren't valid header values
+    pub(crate) fn resolve(self, docs: &DocsAssets) -> ResolvedSecurityHeaders {
+        let value = |name: &HeaderName, value: String| {
+            let parsed = HeaderValue::try_from(value.as_str())
+                .unwrap_or_else(|_| panic!("Invalid {} value: {}", name, value));
+            (!self.removed.contains(name)).then(|| (name.clone(), parsed))
+        };
+        let always = [
+            value(&header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            value(&header::X_FRAME_OPTIONS, self.frame_options.as_str().to_string()),
+            value(&header::REFERRER_POLICY, self.referrer_policy.clone()),
+        ];
+        let mut hsts = format!("max-age={}", self.hsts_max_age.as_secs());
+        if self.hsts_include_subdomains {
+            hsts.push_str("; includeSubDomains");
+        }
+        let csp = self.content_security_policy.clone().unwrap_or_else(|| docs::content_security_policy(docs));
+        ResolvedSecurityHeaders {
+            always: always.into_iter().flatten().collect(),
+            hsts: value(&header::STRICT_TRANSPORT_SECURITY, hsts).map(|(_, v)| v),
+            force_hsts: self.force_hsts,
+            html: value(&header::CONTENT_SECURITY_POLICY, csp).map(|(_, v)| v),
+        }
+    }
+}
+
+/// [`SecurityHeaders`] as header values
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedSecurityHeaders {
+    always: Vec<(HeaderName, HeaderValue)>,
+    hsts: Option<HeaderValue>,
+    force_hsts: bool,
+    /// `Content-Security-Policy` of HTML responses
+    html: Option<HeaderValue>,
+}
+
+pub(crate) async fn add_headers(headers: Arc<ResolvedSecurityHeaders>, req: Request, next: Next) -> Response {
+    let https = req.extensions().get::<ConnectionInfo>().is_some_and(|info| info.scheme == "https");
+    let mut response = next.run(req).await;
+    let html = response.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/html"));
+    let response_headers = response.headers_mut();
+    for (name, value) in &headers.always {
+        response_headers.entry(name).or_insert_with(|| value.clone());
+    }
+    if let Some(hsts) = headers.hsts.as_ref().filter(|_| https || headers.force_hsts) {
+        response_headers.entry(header::STRICT_TRANSPORT_SECURITY).or_insert_with(|| hsts.clone());
+    }
+    if let Some(csp) = headers.html.as_ref().filter(|_| html) {
+        response_headers.entry(header::CONTENT_SECURITY_POLICY).or_insert_with(|| csp.clone());
+    }
+    response
+}
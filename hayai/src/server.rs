@@ -0,0 +1,181 @@
+//! Connection handling of [`HayaiApp::serve`](crate::HayaiApp::serve), see
+//! [`HayaiApp::http_config`](crate::HayaiApp::http_config).
+//!
+//! The timeouts bound how long a client can hold a connection without making progress: one
+//! that trickles its request headers, or sits idle between requests, is cut off after
+//! `header_read_timeout`; one that stops reading its response is cut off once a write has
+//! been stuck for `write_timeout`. Connections beyond `max_concurrent_connections` are
+//! closed as soon as they are accepted, before any request is read, so an overloaded
+//! instance sheds load without routing.
+//!
+//! ```ignore
+//! HayaiApp::new()
+//!     .http_config(HttpConfig {
+//!         write_timeout: Duration::from_secs(10),
+//!         max_concurrent_connections: 2_000,
+//!         ..Default::default()
+//!     })
+//! ```
+
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+use tokio::time::Sleep;
+use tower_service::Service;
+
+/// Connection-level limits of the server
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// How long a client has to send a request's headers, counted from when the server
+    /// starts waiting for them, so it also ends idle kept-alive connections (default 30
+    /// seconds)
+    pub header_read_timeout: Duration,
+    /// How long writing a response may stall on a client that isn't reading before the
+    /// connection is closed (default 60 seconds)
+    pub write_timeout: Duration,
+    /// Connections served at once; further ones are closed on accept (default 10 000)
+    pub max_concurrent_connections: usize,
+    /// Whether HTTP/1.1 connections stay open for further requests (default `true`)
+    pub keep_alive: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            header_read_timeout: Duration::from_secs(30),
+            write_timeout: Duration::from_secs(60),
+            max_concurrent_connections: 10_000,
+            keep_alive: true,
+        }
+    }
+}
+
+/// Serve `app` on `listener` under `config` until `signal` completes, then wait for the open
+/// connections to finish their requests
+pub(crate) async fn serve<S>(
+    listener: TcpListener,
+    app: IntoMakeServiceWithConnectInfo<Router, SocketAddr>,
+    config: HttpConfig,
+    signal: S,
+) where
+    S: Future<Output = ()> + Send + 'static,
+{
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    builder.http1()
+        .timer(TokioTimer::new())
+        .header_read_timeout(config.header_read_timeout)
+        .keep_alive(config.keep_alive);
+    builder.http2().timer(TokioTimer::new());
+    let connections = Arc::new(Semaphore::new(config.max_concurrent_connections));
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(signal);
+
+    loop {
+        let (stream, remote_addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Out of file descriptors and the like; accepting again right away would spin
+                    tracing::error!(error = %e, "failed to accept connection");
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    continue;
+                }
+            },
+            _ = &mut signal => break,
+        };
+        let Ok(permit) = connections.clone().try_acquire_owned() else {
+            tracing::warn!(%remote_addr, limit = config.max_concurrent_connections, "connection limit reached, closing connection");
+            continue;
+        };
+        let _ = stream.set_nodelay(true);
+
+        let mut make_service = app.clone();
+        let service = match make_service.call(remote_addr).await {
+            Ok(service) => service,
+            Err(never) => match never {},
+        };
+        let io = TokioIo::new(WriteTimeout::new(stream, config.write_timeout));
+        let connection = builder.serve_connection_with_upgrades(io, TowerToHyperService::new(service)).into_owned();
+        let connection = graceful.watch(connection);
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::debug!(%remote_addr, error = %e, "connection closed with an error");
+            }
+            drop(permit);
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+}
+
+/// A stream whose writes fail with `TimedOut` once they have made no progress for `timeout`
+struct WriteTimeout<IO> {
+    io: IO,
+    timeout: Duration,
+    /// Running while a write is pending
+    stalled: Option<Pin<Box<Sleep>>>,
+}
+
+impl<IO> WriteTimeout<IO> {
+    fn new(io: IO, timeout: Duration) -> Self {
+        Self { io, timeout, stalled: None }
+    }
+
+    fn check<T>(&mut self, cx: &mut Context<'_>, poll: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+        if poll.is_ready() {
+            self.stalled = None;
+            return poll;
+        }
+        let timeout = self.timeout;
+        let stalled = self.stalled.get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match stalled.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "response write timed out"))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<IO: AsyncRead + Unpin> AsyncRead for WriteTimeout<IO> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for WriteTimeout<IO> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.io).poll_write(cx, buf);
+        self.check(cx, poll)
+    }
+
+    fn poll_write_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.io).poll_write_vectored(cx, bufs);
+        self.check(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.io).poll_flush(cx);
+        self.check(cx, poll)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,102 @@
+//! Integers carried as JSON strings, for `#[schema(string_encoded)]`.
+//!
+//! JavaScript numbers lose precision above 2^53, so IDs beyond that are safer as strings.
+//! These serde helpers write the integer as a string and accept either a string or a
+//! number when reading. [`option`] does the same for `Option` fields.
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::Serializer;
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    Encoded::deserialize(deserializer).map(|e| e.0)
+}
+
+pub mod option {
+    use super::Encoded;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T: Display, S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Option::<Encoded<T>>::deserialize(deserializer).map(|e| e.map(|e| e.0))
+    }
+}
+
+/// An integer read from a string or a number, parsed with `T::from_str` either way so
+/// range errors read the same
+struct Encoded<T>(T);
+
+impl<'de, T: FromStr> Deserialize<'de> for Encoded<T>
+where
+    T::Err: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(EncodedVisitor(PhantomData))
+    }
+}
+
+struct EncodedVisitor<T>(PhantomData<T>);
+
+impl<T: FromStr> EncodedVisitor<T>
+where
+    T::Err: Display,
+{
+    fn parse<E: de::Error>(text: &str) -> Result<Encoded<T>, E> {
+        text.parse().map(Encoded).map_err(|e| E::custom(format!("invalid integer {:?}: {}", text, e)))
+    }
+}
+
+impl<T: FromStr> Visitor<'_> for EncodedVisitor<T>
+where
+    T::Err: Display,
+{
+    type Value = Encoded<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an integer or a string containing one")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Self::parse(&v.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Self::parse(&v.to_string())
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Self::Value, E> {
+        Self::parse(&v.to_string())
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        Self::parse(&v.to_string())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Self::parse(v)
+    }
+}
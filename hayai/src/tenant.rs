@@ -0,0 +1,219 @@
+//! Multi-tenancy: a tenant resolved for every request, and dependencies kept per tenant.
+//!
+//! [`HayaiApp::tenant_resolver`](crate::HayaiApp::tenant_resolver) installs a
+//! [`TenantResolver`] that runs before each route; its error is the response when no
+//! tenant can be resolved. Handlers read the result through a [`Tenant`] argument.
+//! [`HayaiApp::tenant_scoped`](crate::HayaiApp::tenant_scoped) registers a factory for
+//! `Dep<TenantScoped<T>>`, called once per tenant and cached after that.
+//!
+//! ```ignore
+//! HayaiApp::new()
+//!     .tenant_resolver(TenantSource::header("X-Tenant-Id").known(["acme", "globex"]))
+//!     .tenant_scoped(|tenant: Tenant| async move { Database::connect(&format!("postgres:///{}", tenant.id)).await })
+//!
+//! #[get("/projects")]
+//! async fn list_projects(tenant: Tenant, db: Dep<TenantScoped<Database>>) -> Vec<Project> { ... }
+//! ```
+
+use crate::{ApiError, AppState, Dep};
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The tenant a request is for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tenant {
+    pub id: String,
+}
+
+impl Tenant {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Tenant {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts.extensions.get::<Tenant>().cloned()
+            .ok_or_else(|| ApiError::internal("No tenant for this request; register a resolver with HayaiApp::tenant_resolver".into()))
+    }
+}
+
+/// Finds the tenant of a request; the error is sent as the response, e.g. 404 for an
+/// unknown tenant or 401 when it comes from a missing credential
+pub trait TenantResolver: Send + Sync + 'static {
+    fn resolve(&self, parts: &Parts) -> impl Future<Output = Result<Tenant, ApiError>> + Send;
+
+    /// The request header naming the tenant, documented as a parameter of every operation
+    fn header(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// [`TenantResolver`] behind a pointer, as kept by the middleware
+pub(crate) trait DynTenantResolver: Send + Sync {
+    fn resolve<'a>(&'a self, parts: &'a Parts) -> BoxFuture<'a, Result<Tenant, ApiError>>;
+    fn header(&self) -> Option<&str>;
+}
+
+impl<R: TenantResolver> DynTenantResolver for R {
+    fn resolve<'a>(&'a self, parts: &'a Parts) -> BoxFuture<'a, Result<Tenant, ApiError>> {
+        Box::pin(TenantResolver::resolve(self, parts))
+    }
+
+    fn header(&self) -> Option<&str> {
+        TenantResolver::header(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Header(String),
+    Subdomain(String),
+}
+
+/// Tenant ids read from a header or from the `Host` subdomain
+#[derive(Debug, Clone)]
+pub struct TenantSource {
+    source: Source,
+    known: Option<HashSet<String>>,
+}
+
+impl TenantSource {
+    /// The value of `name`; a request without it gets 400
+    pub fn header(name: &str) -> Self {
+        Self { source: Source::Header(name.to_string()), known: None }
+    }
+
+    /// The first label of a `Host` under `base_domain`, `acme` for `acme.example.com`;
+    /// other hosts get 400
+    pub fn subdomain(base_domain: &str) -> Self {
+        Self { source: Source::Subdomain(base_domain.trim_start_matches('.').to_ascii_lowercase()), known: None }
+    }
+
+    /// Only accept these tenants; others get 404
+    pub fn known<'a>(mut self, ids: impl IntoIterator<Item = &'a str>) -> Self {
+        self.known = Some(ids.into_iter().map(str::to_string).collect());
+        self
+    }
+
+    fn id(&self, parts: &Parts) -> Result<String, ApiError> {
+        match &self.source {
+            Source::Header(name) => parts.headers.get(name.as_str())
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| ApiError::bad_request(format!("Missing {} header", name))),
+            Source::Subdomain(base) => {
+                let host = parts.headers.get(header::HOST).and_then(|v| v.to_str().ok()).unwrap_or("");
+                let host = host.split(':').next().unwrap_or("").to_ascii_lowercase();
+                host.strip_suffix(base.as_str())
+                    .and_then(|rest| rest.strip_suffix('.'))
+                    .filter(|label| !label.is_empty() && !label.contains('.'))
+                    .map(str::to_string)
+                    .ok_or_else(|| ApiError::bad_request(format!("Host is not a subdomain of {}", base)))
+            }
+        }
+    }
+}
+
+impl TenantResolver for TenantSource {
+    async fn resolve(&self, parts: &Parts) -> Result<Tenant, ApiError> {
+        let id = self.id(parts)?;
+        if self.known.as_ref().is_some_and(|known| !known.contains(&id)) {
+            return Err(ApiError::not_found(format!("Unknown tenant `{}`", id)));
+        }
+        Ok(Tenant { id })
+    }
+
+    fn header(&self) -> Option<&str> {
+        match &self.source {
+            Source::Header(name) => Some(name),
+            Source::Subdomain(_) => None,
+        }
+    }
+}
+
+/// The request tenant's instance of `T`, resolved as `Dep<TenantScoped<T>>`
+pub struct TenantScoped<T>(Arc<T>);
+
+impl<T> Deref for TenantScoped<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+type Factory = Arc<dyn Fn(Tenant) -> BoxFuture<'static, Result<Arc<dyn Any + Send + Sync>, ApiError>> + Send + Sync>;
+
+/// The factory of one tenant-scoped type and the instances it built, by tenant id
+#[derive(Clone)]
+pub(crate) struct TenantDep {
+    factory: Factory,
+    instances: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl TenantDep {
+    pub(crate) fn new<T, F, Fut>(factory: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(Tenant) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ApiError>> + Send + 'static,
+    {
+        let factory: Factory = Arc::new(move |tenant| {
+            let built = factory(tenant);
+            Box::pin(async move { Ok(Arc::new(built.await?) as Arc<dyn Any + Send + Sync>) })
+        });
+        Self { factory, instances: Arc::default() }
+    }
+}
+
+/// `Dep<TenantScoped<T>>` for the request's tenant, built on first use
+#[doc(hidden)]
+pub async fn __tenant_dep<T: Send + Sync + 'static>(state: &AppState, parts: &Parts) -> Result<Dep<TenantScoped<T>>, ApiError> {
+    let dep = state.settings.tenant_deps.get(&TypeId::of::<T>()).ok_or_else(|| {
+        ApiError::internal(format!("Tenant-scoped dependency not registered: {}", std::any::type_name::<T>()))
+    })?;
+    let tenant = parts.extensions.get::<Tenant>().cloned()
+        .ok_or_else(|| ApiError::internal("No tenant for this request; register a resolver with HayaiApp::tenant_resolver".into()))?;
+
+    let cached = dep.instances.lock().unwrap().get(&tenant.id).cloned();
+    let instance = match cached {
+        Some(instance) => instance,
+        None => {
+            let id = tenant.id.clone();
+            let built = (dep.factory)(tenant).await?;
+            // A concurrent first request may have built one too; keep the first stored
+            dep.instances.lock().unwrap().entry(id).or_insert(built).clone()
+        }
+    };
+    let instance = instance.downcast::<T>()
+        .map_err(|_| ApiError::internal(format!("Tenant-scoped dependency has the wrong type: {}", std::any::type_name::<T>())))?;
+    Ok(Dep::new(TenantScoped(instance)))
+}
+
+/// Middleware resolving the tenant of every routed request
+pub(crate) async fn resolve_tenant(resolver: Arc<dyn DynTenantResolver>, req: Request, next: Next) -> Response {
+    let (mut parts, body) = req.into_parts();
+    match resolver.resolve(&parts).await {
+        Ok(tenant) => {
+            parts.extensions.insert(tenant);
+            next.run(Request::from_parts(parts, body)).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
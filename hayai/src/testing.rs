@@ -0,0 +1,494 @@
+//! Property-based tests against the registered schemas, and an in-process client for
+//! handler tests, behind the `testing` feature.
+//!
+//! [`arbitrary_for_schema`] is a [proptest](https://docs.rs/proptest) strategy for JSON
+//! values that satisfy a component schema: string lengths, patterns and formats, numeric
+//! ranges at and between their bounds, enums, nullable and omitted optional fields, array
+//! sizes, and `$ref`s to other registered models. [`fuzz_route`] sends such bodies to a
+//! route and fails on any 5xx or panic.
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn create_item_never_fails() {
+//!     let router = HayaiApp::new().into_router();
+//!     hayai::testing::fuzz_route(&router, __HAYAI_ROUTE_CREATE_ITEM, 500).await;
+//! }
+//! ```
+//!
+//! [`TestClient`] sends requests to an app without binding a port. Tests of secured routes
+//! either hand `Auth<V>` its claims with [`authenticated_as`](TestClient::authenticated_as),
+//! or send real credentials through the validator with
+//! [`with_bearer`](TestClient::with_bearer) and [`with_api_key`](TestClient::with_api_key).
+//!
+//! ```ignore
+//! let client = TestClient::new(app).authenticated_as(Claims { user_id: 42 });
+//! let res = client.get("/me").send().await;
+//! assert_eq!(res.status(), 200);
+//! ```
+
+use crate::openapi::{BodyCollection, Property, Schema};
+use crate::{HayaiApp, RouteInfo, SchemaInfo};
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode};
+use axum::Router;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, Union, ValueTree};
+use proptest::test_runner::TestRunner;
+use serde_json::Value;
+use std::collections::HashMap;
+use tower_service::Service;
+
+pub use proptest;
+
+/// Characters of a response body quoted in a failure
+const BODY_EXCERPT: usize = 512;
+
+/// JSON values satisfying `schema`, resolving `$ref`s against the registered models
+pub fn arbitrary_for_schema(schema: &Schema) -> impl Strategy<Value = Value> {
+    Generator::registered().schema(schema)
+}
+
+/// Send `iterations` requests with generated bodies and path parameters to `route`, mounted
+/// at its own path in `router`, panicking with the request that got a 5xx or made the
+/// handler panic
+pub async fn fuzz_route(router: &Router, route: &RouteInfo, iterations: u32) {
+    let mut generator = Generator::registered();
+    let body = route.request_body()
+        .filter(|_| route.body_content_type == "application/json")
+        .map(|name| {
+            let item = generator.component(&name);
+            match route.body_collection {
+                Some(BodyCollection::List) => proptest::collection::vec(item, 0..=4).prop_map(Value::Array).sboxed(),
+                Some(BodyCollection::Map) => proptest::collection::btree_map(key(), item, 0..=4)
+                    .prop_map(|map| Value::Object(map.into_iter().collect()))
+                    .sboxed(),
+                None => item,
+            }
+        });
+    let params: Vec<(&str, SBoxedStrategy<Value>)> = route.parameters.iter()
+        .filter(|p| p.location == "path")
+        .map(|p| {
+            let prop = Property {
+                type_name: p.schema.type_name.to_string(),
+                minimum: p.schema.minimum,
+                maximum: p.schema.maximum,
+                min_length: p.schema.min_length,
+                max_length: p.schema.max_length,
+                pattern: p.schema.pattern.map(str::to_string),
+                ..Default::default()
+            };
+            (p.name, generator.property(&prop))
+        })
+        .collect();
+
+    let mut runner = TestRunner::default();
+    for _ in 0..iterations {
+        let mut path = route.path.to_string();
+        for (name, strategy) in &params {
+            let value = strategy.new_tree(&mut runner).expect("path parameter strategy").current();
+            let text = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            let encoded = percent_encode(&text);
+            path = path.replace(&format!("{{{}}}", name), &encoded).replace(&format!("{{*{}}}", name), &encoded);
+        }
+        let body = body.as_ref().map(|s| s.new_tree(&mut runner).expect("body strategy").current());
+
+        let mut request = Request::builder().method(route.method).uri(&path);
+        if body.is_some() {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+        }
+        let request = request
+            .body(body.as_ref().map_or_else(Body::empty, |json| Body::from(json.to_string())))
+            .unwrap_or_else(|e| panic!("fuzzed request for {} {} is invalid: {}", route.method, path, e));
+        let call = tokio::spawn(router.clone().call(request));
+        let failure = match call.await {
+            Ok(Ok(response)) if response.status().is_server_error() => {
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap_or_default();
+                let excerpt: String = String::from_utf8_lossy(&bytes).chars().take(BODY_EXCERPT).collect();
+                format!("answered {}: {}", status, excerpt)
+            }
+            Ok(Ok(_)) => continue,
+            Ok(Err(never)) => match never {},
+            Err(e) => format!("panicked: {}", panic_message(e)),
+        };
+        let body = body.map_or_else(|| "no body".to_string(), |json| json.to_string());
+        panic!("{} {} {}\nrequest body: {}", route.method, path, failure, body);
+    }
+}
+
+fn panic_message(error: tokio::task::JoinError) -> String {
+    match error.try_into_panic() {
+        Ok(payload) => payload.downcast_ref::<String>().cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "non-string panic payload".to_string()),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// Everything but unreserved characters as `%XX`
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Sends requests to an app in-process, see the [module docs](self)
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+    headers: HeaderMap,
+    /// The header of the app's `api_key_auth` scheme
+    api_key_header: Option<String>,
+    #[cfg(feature = "auth")]
+    claims: Option<std::sync::Arc<dyn Fn() -> crate::auth::InjectedClaims + Send + Sync>>,
+}
+
+impl TestClient {
+    pub fn new(app: HayaiApp) -> Self {
+        let api_key_header = app.security_schemes.get(crate::security::component_name(crate::security::API_KEY))
+            .and_then(|scheme| scheme.name.clone());
+        Self {
+            router: app.into_router(),
+            headers: HeaderMap::new(),
+            api_key_header,
+            #[cfg(feature = "auth")]
+            claims: None,
+        }
+    }
+
+    /// Hand every `Auth<V>` extractor these claims instead of calling `V::validate`; the
+    /// handler sees them as they are, and the subject comes from their
+    /// [`subject`](crate::auth::SecurityValidator::subject). A route authenticating with another
+    /// validator answers 500.
+    #[cfg(feature = "auth")]
+    pub fn authenticated_as<V: crate::auth::SecurityValidator + Clone>(mut self, claims: V) -> Self {
+        self.claims = Some(std::sync::Arc::new(move || crate::auth::InjectedClaims::new(claims.clone())));
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>` with every request, checked by the real validator
+    pub fn with_bearer(self, token: &str) -> Self {
+        self.header(header::AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+
+    /// Send `key` in the app's [`api_key_auth`](HayaiApp::api_key_auth) header with every
+    /// request; panics when the app has no such scheme
+    pub fn with_api_key(self, key: &str) -> Self {
+        let name = self.api_key_header.clone()
+            .unwrap_or_else(|| panic!("with_api_key needs an app with `api_key_auth`"));
+        self.header(&name, key)
+    }
+
+    /// Send this header with every request
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name).unwrap_or_else(|e| panic!("invalid header name {:?}: {}", name, e));
+        let value = HeaderValue::try_from(value).unwrap_or_else(|e| panic!("invalid value for header {}: {}", name, e));
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> TestRequest {
+        let mut request = Request::builder().method(method).uri(path);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        #[cfg(feature = "auth")]
+        if let Some(claims) = &self.claims {
+            request = request.extension(claims());
+        }
+        TestRequest { router: self.router.clone(), request, body: Body::empty() }
+    }
+
+    pub fn get(&self, path: &str) -> TestRequest {
+        self.request(Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> TestRequest {
+        self.request(Method::POST, path)
+    }
+
+    pub fn put(&self, path: &str) -> TestRequest {
+        self.request(Method::PUT, path)
+    }
+
+    pub fn patch(&self, path: &str) -> TestRequest {
+        self.request(Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> TestRequest {
+        self.request(Method::DELETE, path)
+    }
+}
+
+/// A request built by [`TestClient`]
+pub struct TestRequest {
+    router: Router,
+    request: axum::http::request::Builder,
+    body: Body,
+}
+
+impl TestRequest {
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.request = self.request.header(name, value);
+        self
+    }
+
+    /// Send `value` as a JSON body
+    pub fn json<T: serde::Serialize + ?Sized>(mut self, value: &T) -> Self {
+        let json = serde_json::to_vec(value).expect("JSON request body");
+        self.request = self.request.header(header::CONTENT_TYPE, "application/json");
+        self.body = Body::from(json);
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let request = self.request.body(self.body).unwrap_or_else(|e| panic!("invalid test request: {}", e));
+        let Ok(response) = self.router.clone().call(request).await;
+        let (head, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await.expect("response body");
+        TestResponse { status: head.status, headers: head.headers, body }
+    }
+}
+
+/// A response read in full by [`TestRequest::send`]
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// The body as `T`, panicking with an excerpt of it when it doesn't parse
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| {
+            let excerpt: String = self.text().chars().take(BODY_EXCERPT).collect();
+            panic!("response body is not the expected JSON ({}): {}", e, excerpt)
+        })
+    }
+}
+
+fn key() -> SBoxedStrategy<String> {
+    proptest::string::string_regex("[a-z][a-z0-9_]{0,15}").expect("valid key pattern").sboxed()
+}
+
+/// Builds strategies from schemas, expanding each referenced component once per path
+struct Generator {
+    components: HashMap<String, Schema>,
+    expanding: Vec<String>,
+}
+
+impl Generator {
+    fn registered() -> Self {
+        let mut components = HashMap::new();
+        for info in inventory::iter::<SchemaInfo> {
+            components.insert(info.component_name(), info.schema());
+            for (name, schema) in info.nested() {
+                components.entry(name).or_insert(schema);
+            }
+        }
+        Self { components, expanding: Vec::new() }
+    }
+
+    fn component(&mut self, name: &str) -> SBoxedStrategy<Value> {
+        match self.components.get(name).cloned() {
+            Some(schema) if !self.expanding.iter().any(|n| n == name) => {
+                self.expanding.push(name.to_string());
+                let strategy = self.schema(&schema);
+                self.expanding.pop();
+                strategy
+            }
+            // A model containing itself ends the recursion with an empty object
+            _ => Just(serde_json::json!({})).sboxed(),
+        }
+    }
+
+    fn schema(&mut self, schema: &Schema) -> SBoxedStrategy<Value> {
+        if let Some(values) = schema.enum_values.clone().filter(|values| !values.is_empty()) {
+            return proptest::sample::select(values).prop_map(Value::String).sboxed();
+        }
+        if !schema.one_of.is_empty() {
+            let variants: Vec<SBoxedStrategy<Value>> = schema.one_of.iter()
+                .map(|(tag, name)| {
+                    let variant = self.component(name);
+                    match schema.discriminator.clone() {
+                        Some(property) => {
+                            let tag = tag.clone();
+                            variant.prop_map(move |mut value| {
+                                value[&property] = Value::String(tag.clone());
+                                value
+                            }).sboxed()
+                        }
+                        None => variant,
+                    }
+                })
+                .collect();
+            return Union::new(variants).sboxed();
+        }
+
+        let mut names: Vec<&String> = schema.properties.keys().collect();
+        names.sort();
+        let fields: Vec<SBoxedStrategy<Option<(String, Value)>>> = names.into_iter()
+            .map(|name| {
+                let value = self.property(&schema.properties[name]);
+                let name = name.clone();
+                if schema.required.contains(&name) {
+                    value.prop_map(move |v| Some((name.clone(), v))).sboxed()
+                } else {
+                    proptest::option::of(value).prop_map(move |v| v.map(|v| (name.clone(), v))).sboxed()
+                }
+            })
+            .collect();
+        fields.prop_map(|fields| Value::Object(fields.into_iter().flatten().collect())).sboxed()
+    }
+
+    fn property(&mut self, prop: &Property) -> SBoxedStrategy<Value> {
+        let value = self.value(prop);
+        if prop.nullable {
+            prop_oneof![1 => Just(Value::Null), 4 => value].sboxed()
+        } else {
+            value
+        }
+    }
+
+    fn value(&mut self, prop: &Property) -> SBoxedStrategy<Value> {
+        if let Some(ref_path) = &prop.ref_path {
+            return self.component(ref_path.trim_start_matches("#/components/schemas/"));
+        }
+        match prop.type_name.as_str() {
+            "string" => string(prop).prop_map(Value::String).sboxed(),
+            "integer" => integer(prop).prop_map(integer_value).sboxed(),
+            "number" => number(prop).prop_map(number_value).sboxed(),
+            "boolean" => any::<bool>().prop_map(Value::Bool).sboxed(),
+            "array" => {
+                let items = match &prop.items {
+                    Some(items) => self.property(items),
+                    None => Just(Value::Null).sboxed(),
+                };
+                let min = prop.min_items.unwrap_or(0);
+                let max = prop.max_items.unwrap_or(min + 8).max(min);
+                proptest::collection::vec(items, min..=max).prop_map(Value::Array).sboxed()
+            }
+            "object" => match &prop.additional_properties {
+                Some(values) => proptest::collection::btree_map(key(), self.property(values), 0..=4)
+                    .prop_map(|map| Value::Object(map.into_iter().collect()))
+                    .sboxed(),
+                None => Just(serde_json::json!({})).sboxed(),
+            },
+            _ => Just(Value::Null).sboxed(),
+        }
+    }
+}
+
+fn string(prop: &Property) -> SBoxedStrategy<String> {
+    let min = prop.min_length.unwrap_or(0);
+    let max = prop.max_length.unwrap_or(min + 64).max(min);
+    if let Some(pattern) = &prop.pattern {
+        let unanchored = pattern.strip_prefix('^').unwrap_or(pattern);
+        let unanchored = unanchored.strip_suffix('$').filter(|p| !p.ends_with('\\')).unwrap_or(unanchored);
+        if let Ok(strategy) = proptest::string::string_regex(unanchored) {
+            return strategy.prop_filter("length within bounds", move |s| (min..=max).contains(&s.chars().count())).sboxed();
+        }
+    }
+    let text = match prop.format.as_deref() {
+        Some("email") => Some("[a-z0-9]{1,16}(\\.[a-z0-9]{1,8})?@[a-z]{1,12}\\.(com|org|dev)"),
+        Some("uuid") => Some("[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}"),
+        Some("uri" | "url") => Some("https://[a-z]{1,12}\\.example(/[a-z0-9_-]{1,12}){0,3}"),
+        _ => None,
+    };
+    if let Some(pattern) = text {
+        return proptest::string::string_regex(pattern).expect("valid format pattern").sboxed();
+    }
+    match prop.format.as_deref() {
+        Some("date-time") => (date(), 0..24u32, 0..60u32, 0..60u32)
+            .prop_map(|(date, h, m, s)| format!("{}T{:02}:{:02}:{:02}Z", date, h, m, s))
+            .sboxed(),
+        Some("date") => date().sboxed(),
+        Some("time") => (0..24u32, 0..60u32, 0..60u32).prop_map(|(h, m, s)| format!("{:02}:{:02}:{:02}", h, m, s)).sboxed(),
+        Some("ipv4") => any::<[u8; 4]>().prop_map(|octets| std::net::Ipv4Addr::from(octets).to_string()).sboxed(),
+        Some("ipv6") => any::<[u16; 8]>().prop_map(|segments| std::net::Ipv6Addr::from(segments).to_string()).sboxed(),
+        // `#[schema(string_encoded)]` integers
+        Some("int64" | "uint64" | "int32" | "uint32") => integer(prop).prop_map(|n| n.to_string()).sboxed(),
+        Some("decimal") => number(prop).prop_map(|n| format!("{:.2}", n)).sboxed(),
+        // Bounded strings stay ASCII, so their byte length is their character count
+        _ if prop.max_length.is_some() => proptest::collection::vec(proptest::char::range(' ', '~'), min..=max)
+            .prop_map(String::from_iter)
+            .sboxed(),
+        _ => proptest::collection::vec(any::<char>(), min..=max).prop_map(String::from_iter).sboxed(),
+    }
+}
+
+fn date() -> impl Strategy<Value = String> {
+    (1970..=2100i32, 1..=12u32, 1..=28u32).prop_map(|(y, m, d)| format!("{:04}-{:02}-{:02}", y, m, d))
+}
+
+/// The bounds, always included, and values between them
+fn integer(prop: &Property) -> SBoxedStrategy<i128> {
+    let (low, high): (i128, i128) = match prop.format.as_deref() {
+        Some("int8") => (i8::MIN.into(), i8::MAX.into()),
+        Some("int16") => (i16::MIN.into(), i16::MAX.into()),
+        Some("int32") => (i32::MIN.into(), i32::MAX.into()),
+        Some("uint8") => (0, u8::MAX.into()),
+        Some("uint16") => (0, u16::MAX.into()),
+        Some("uint32") => (0, u32::MAX.into()),
+        Some("uint64") => (0, u64::MAX.into()),
+        _ => (i64::MIN.into(), i64::MAX.into()),
+    };
+    let low = prop.minimum.map_or(low, |m| low.max(m.ceil() as i128));
+    let high = prop.maximum.map_or(high, |m| high.min(m.floor() as i128)).max(low);
+    prop_oneof![1 => Just(low), 1 => Just(high), 6 => low..=high].sboxed()
+}
+
+fn integer_value(n: i128) -> Value {
+    match i64::try_from(n) {
+        Ok(n) => Value::from(n),
+        Err(_) => Value::from(n as u64),
+    }
+}
+
+/// Finite numbers, the bounds included; an open side reaches 1e12 past the other
+fn number(prop: &Property) -> SBoxedStrategy<f64> {
+    match (prop.minimum, prop.maximum) {
+        (None, None) => prop_oneof![
+            1 => Just(0.0),
+            4 => proptest::num::f64::NORMAL | proptest::num::f64::ZERO,
+            4 => -1e6..1e6f64,
+        ].sboxed(),
+        (min, max) => {
+            let low = min.unwrap_or_else(|| max.unwrap_or(0.0) - 1e12);
+            let high = max.unwrap_or(low + 1e12).max(low);
+            prop_oneof![1 => Just(low), 1 => Just(high), 6 => low..=high].sboxed()
+        }
+    }
+}
+
+fn number_value(n: f64) -> Value {
+    serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,33 @@
+//! Time limits on requests: `#[timeout("5s")]` on a route, or
+//! [`HayaiApp::default_timeout`](crate::HayaiApp::default_timeout) for the routes without one.
+//!
+//! A request still running when its limit passes is dropped and answered with 504. The
+//! limit is documented on the operation as `x-timeout-ms`, so clients can set theirs to match.
+
+use crate::deadline::RouteExpiry;
+use crate::ApiError;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Operation extension carrying the limit in milliseconds
+pub const TIMEOUT_EXTENSION: &str = "x-timeout-ms";
+
+/// Middleware state: the limit of each route by `(METHOD, axum path)`
+pub(crate) type RouteTimeouts = Arc<HashMap<(String, String), Duration>>;
+
+pub(crate) async fn enforce(timeouts: RouteTimeouts, mut req: Request, next: Next) -> Response {
+    let limit = req.extensions().get::<MatchedPath>()
+        .and_then(|path| timeouts.get(&(req.method().to_string(), path.as_str().to_string())).copied());
+    let Some(limit) = limit else {
+        return next.run(req).await;
+    };
+    req.extensions_mut().insert(RouteExpiry(tokio::time::Instant::now() + limit));
+    match tokio::time::timeout(limit, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => ApiError::gateway_timeout(format!("Request timed out after {} ms", limit.as_millis())).into_response(),
+    }
+}
@@ -0,0 +1,158 @@
+//! A transaction per request, see [`HayaiApp::transactional`](crate::HayaiApp::transactional).
+//!
+//! The dependency begins a transaction before the handler runs; the handler reaches it
+//! through a [`Tx`] argument. A response below 400 commits it, anything else (validation
+//! failures and `ApiError`s) rolls it back. Routes opt out with
+//! `#[transactional(skip)]`, or opt in with `#[transactional]` when the app uses
+//! [`transactional_annotated`](crate::HayaiApp::transactional_annotated).
+//!
+//! ```ignore
+//! impl RequestTransaction for Database {
+//!     async fn begin(&self) -> Result<Box<dyn TxHandle>, ApiError> {
+//!         Ok(Box::new(PgTx(self.pool.begin().await?)))
+//!     }
+//! }
+//!
+//! #[post("/orders")]
+//! async fn create_order(body: NewOrder, tx: Tx) -> Result<Order, ApiError> {
+//!     let mut tx = tx.lock().await?;
+//!     let pg = tx.downcast_mut::<PgTx>().unwrap();
+//!     ...
+//! }
+//! ```
+
+use crate::ApiError;
+use axum::extract::{FromRequestParts, MatchedPath, Request};
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::any::{Any, TypeId};
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// A dependency that can open a transaction, e.g. a database pool
+pub trait RequestTransaction: Send + Sync + 'static {
+    fn begin(&self) -> impl Future<Output = Result<Box<dyn TxHandle>, ApiError>> + Send;
+}
+
+/// An open transaction, finished by the framework once the response is known
+pub trait TxHandle: Any + Send + Sync {
+    fn commit(self: Box<Self>) -> BoxFuture<Result<(), ApiError>>;
+    fn rollback(self: Box<Self>) -> BoxFuture<Result<(), ApiError>>;
+}
+
+impl dyn TxHandle {
+    /// The concrete transaction type returned by [`RequestTransaction::begin`]
+    pub fn downcast_mut<T: TxHandle>(&mut self) -> Option<&mut T> {
+        (self as &mut dyn Any).downcast_mut()
+    }
+
+    pub fn downcast_ref<T: TxHandle>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+}
+
+/// The request's transaction; rejects with 500 on routes that aren't transactional
+#[derive(Clone)]
+pub struct Tx(Arc<Mutex<Option<Box<dyn TxHandle>>>>);
+
+impl Tx {
+    /// Exclusive access to the transaction until the guard is dropped
+    pub async fn lock(&self) -> Result<MappedMutexGuard<'_, dyn TxHandle>, ApiError> {
+        MutexGuard::try_map(self.0.lock().await, |slot| slot.as_deref_mut())
+            .map_err(|_| ApiError::internal("Transaction already finished".into()))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Tx {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts.extensions.get::<Tx>().cloned().ok_or_else(|| {
+            ApiError::internal("No transaction for this route; enable HayaiApp::transactional or add #[transactional]".into())
+        })
+    }
+}
+
+/// Which routes run in a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransactionScope {
+    /// Every route without `#[transactional(skip)]`
+    AllRoutes,
+    /// Only routes with `#[transactional]`
+    Annotated,
+}
+
+impl TransactionScope {
+    /// Whether a route with the given `#[transactional]` marker is wrapped
+    pub(crate) fn includes(self, marker: Option<bool>) -> bool {
+        match self {
+            TransactionScope::AllRoutes => marker != Some(false),
+            TransactionScope::Annotated => marker == Some(true),
+        }
+    }
+}
+
+pub(crate) type BeginFn = Arc<dyn Fn() -> BoxFuture<Result<Box<dyn TxHandle>, ApiError>> + Send + Sync>;
+
+/// `transactional::<D>()` as configured on the app, before the dependency is resolved
+#[derive(Clone, Copy)]
+pub(crate) struct TransactionSetup {
+    pub(crate) scope: TransactionScope,
+    pub(crate) dep_type: TypeId,
+    pub(crate) dep_name: &'static str,
+    pub(crate) begin_fn: fn(Arc<dyn Any + Send + Sync>) -> Option<BeginFn>,
+}
+
+impl TransactionSetup {
+    pub(crate) fn new<D: RequestTransaction>(scope: TransactionScope) -> Self {
+        Self { scope, dep_type: TypeId::of::<D>(), dep_name: std::any::type_name::<D>(), begin_fn: begin_fn::<D> }
+    }
+}
+
+fn begin_fn<D: RequestTransaction>(dep: Arc<dyn Any + Send + Sync>) -> Option<BeginFn> {
+    let dep = dep.downcast::<D>().ok()?;
+    Some(Arc::new(move || {
+        let dep = dep.clone();
+        Box::pin(async move { dep.begin().await })
+    }))
+}
+
+/// Middleware state: how to begin, and the `(METHOD, axum path)` of the wrapped routes
+#[derive(Clone)]
+pub(crate) struct Transactional {
+    pub(crate) begin: BeginFn,
+    pub(crate) routes: Arc<HashSet<(String, String)>>,
+}
+
+pub(crate) async fn run_in_transaction(config: Transactional, mut req: Request, next: Next) -> Response {
+    let wrapped = req.extensions().get::<MatchedPath>()
+        .is_some_and(|path| config.routes.contains(&(req.method().to_string(), path.as_str().to_string())));
+    if !wrapped {
+        return next.run(req).await;
+    }
+
+    let handle = match (config.begin)().await {
+        Ok(handle) => handle,
+        Err(e) => return e.into_response(),
+    };
+    let tx = Tx(Arc::new(Mutex::new(Some(handle))));
+    req.extensions_mut().insert(tx.clone());
+
+    let response = next.run(req).await;
+
+    let Some(handle) = tx.0.lock().await.take() else { return response };
+    if response.status().as_u16() < 400 {
+        if let Err(e) = handle.commit().await {
+            return ApiError::internal(format!("Transaction commit failed: {}", e.error)).into_response();
+        }
+    } else if let Err(e) = handle.rollback().await {
+        tracing::warn!(error = %e.error, "transaction rollback failed");
+    }
+    response
+}
@@ -0,0 +1,206 @@
+//! Geographic types with proper component schemas, behind the `geo` feature.
+//!
+//! [`LatLng`] is a `{lat, lng}` pair and [`GeoJsonGeometry`] a GeoJSON (RFC 7946) Point,
+//! LineString or Polygon. Both work as whole bodies and as `#[api_model]` fields; either
+//! way they are validated before the handler runs, answering 422 for coordinates out of
+//! range and polygon rings that don't close. The spec documents `LatLng` with its ranges
+//! and `GeoJsonGeometry` as a `oneOf` discriminated by `type`, each variant with the
+//! nesting of its `coordinates` arrays.
+//!
+//! ```ignore
+//! #[api_model]
+//! struct Store {
+//!     name: String,
+//!     location: LatLng,
+//!     delivery_area: GeoJsonGeometry,
+//! }
+//! ```
+
+use crate::openapi::{Property, Schema};
+use crate::{ComponentName, SchemaInfo, Validate, ValidationError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A GeoJSON position: longitude, latitude and an optional altitude
+pub type Position = Vec<f64>;
+
+/// A point in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct LatLng {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl LatLng {
+    pub fn new(lat: f64, lng: f64) -> Self {
+        Self { lat, lng }
+    }
+}
+
+impl Validate for LatLng {
+    fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if !(-90.0..=90.0).contains(&self.lat) {
+            errors.push(ValidationError::new("lat", "range", "must be between -90 and 90"));
+        }
+        if !(-180.0..=180.0).contains(&self.lng) {
+            errors.push(ValidationError::new("lng", "range", "must be between -180 and 180"));
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl ComponentName for LatLng {
+    fn component_name() -> String {
+        "LatLng".to_string()
+    }
+}
+
+/// A GeoJSON geometry, tagged by its `type` member
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(tag = "type")]
+pub enum GeoJsonGeometry {
+    Point { coordinates: Position },
+    LineString { coordinates: Vec<Position> },
+    /// The first ring is the outer boundary, any others are holes
+    Polygon { coordinates: Vec<Vec<Position>> },
+}
+
+impl Validate for GeoJsonGeometry {
+    fn validate_fields(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        match self {
+            Self::Point { coordinates } => check_position("coordinates", coordinates, &mut errors),
+            Self::LineString { coordinates } => {
+                if coordinates.len() < 2 {
+                    errors.push(ValidationError::new("coordinates", "min_items", "must have at least 2 positions"));
+                }
+                for (i, position) in coordinates.iter().enumerate() {
+                    check_position(&format!("coordinates[{}]", i), position, &mut errors);
+                }
+            }
+            Self::Polygon { coordinates } => {
+                if coordinates.is_empty() {
+                    errors.push(ValidationError::new("coordinates", "min_items", "must have at least 1 ring"));
+                }
+                for (i, ring) in coordinates.iter().enumerate() {
+                    let field = format!("coordinates[{}]", i);
+                    if ring.len() < 4 {
+                        errors.push(ValidationError::new(&field, "min_items", "must have at least 4 positions"));
+                    } else if ring.first() != ring.last() {
+                        errors.push(ValidationError::new(&field, "ring_closure", "must end with its first position"));
+                    }
+                    for (j, position) in ring.iter().enumerate() {
+                        check_position(&format!("{}[{}]", field, j), position, &mut errors);
+                    }
+                }
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl ComponentName for GeoJsonGeometry {
+    fn component_name() -> String {
+        "GeoJsonGeometry".to_string()
+    }
+}
+
+fn check_position(field: &str, position: &[f64], errors: &mut Vec<ValidationError>) {
+    if !(2..=3).contains(&position.len()) {
+        errors.push(ValidationError::new(field, "position", "must be [longitude, latitude] with an optional altitude"));
+        return;
+    }
+    if !(-180.0..=180.0).contains(&position[0]) {
+        errors.push(ValidationError::new(format!("{}[0]", field), "range", "longitude must be between -180 and 180"));
+    }
+    if !(-90.0..=90.0).contains(&position[1]) {
+        errors.push(ValidationError::new(format!("{}[1]", field), "range", "latitude must be between -90 and 90"));
+    }
+}
+
+fn degrees(limit: f64) -> Property {
+    Property { type_name: "number".to_string(), format: Some("double".to_string()), minimum: Some(-limit), maximum: Some(limit), ..Default::default() }
+}
+
+fn array(items: Property, min_items: usize, max_items: Option<usize>) -> Property {
+    Property { type_name: "array".to_string(), items: Some(Box::new(items)), min_items: Some(min_items), max_items, ..Default::default() }
+}
+
+fn position() -> Property {
+    Property {
+        description: Some("Longitude, latitude and an optional altitude".to_string()),
+        ..array(Property { type_name: "number".to_string(), format: Some("double".to_string()), ..Default::default() }, 2, Some(3))
+    }
+}
+
+/// The component of one geometry variant: its `type` and `coordinates`
+fn geometry_variant(kind: &str, coordinates: Property) -> Schema {
+    let kind_property = Property { type_name: "string".to_string(), example: Some(kind.to_string()), ..Default::default() };
+    Schema {
+        type_name: "object".to_string(),
+        properties: HashMap::from([("type".to_string(), kind_property), ("coordinates".to_string(), coordinates)]),
+        required: vec!["type".to_string(), "coordinates".to_string()],
+        title: Some(format!("GeoJSON {}", kind)),
+        ..Default::default()
+    }
+}
+
+fn lat_lng_schema() -> Schema {
+    Schema {
+        type_name: "object".to_string(),
+        properties: HashMap::from([("lat".to_string(), degrees(90.0)), ("lng".to_string(), degrees(180.0))]),
+        required: vec!["lat".to_string(), "lng".to_string()],
+        description: Some("A point in decimal degrees".to_string()),
+        ..Default::default()
+    }
+}
+
+fn geometry_schema() -> Schema {
+    Schema {
+        description: Some("A GeoJSON geometry".to_string()),
+        one_of: vec![
+            ("Point".to_string(), "GeoJsonPoint".to_string()),
+            ("LineString".to_string(), "GeoJsonLineString".to_string()),
+            ("Polygon".to_string(), "GeoJsonPolygon".to_string()),
+        ],
+        discriminator: Some("type".to_string()),
+        ..Default::default()
+    }
+}
+
+fn point_schema() -> Schema {
+    geometry_variant("Point", position())
+}
+
+fn line_string_schema() -> Schema {
+    geometry_variant("LineString", array(position(), 2, None))
+}
+
+fn polygon_schema() -> Schema {
+    let ring = Property {
+        description: Some("A closed ring: the last position repeats the first".to_string()),
+        ..array(position(), 4, None)
+    };
+    geometry_variant("Polygon", array(ring, 1, None))
+}
+
+inventory::submit! {
+    SchemaInfo { name: "LatLng", module_path: module_path!(), module_prefix: false, strict_bodies: false, schema_fn: lat_lng_schema, nested_fn: HashMap::new, patches_fn: HashMap::new }
+}
+
+inventory::submit! {
+    SchemaInfo { name: "GeoJsonGeometry", module_path: module_path!(), module_prefix: false, strict_bodies: false, schema_fn: geometry_schema, nested_fn: HashMap::new, patches_fn: HashMap::new }
+}
+
+inventory::submit! {
+    SchemaInfo { name: "GeoJsonPoint", module_path: module_path!(), module_prefix: false, strict_bodies: false, schema_fn: point_schema, nested_fn: HashMap::new, patches_fn: HashMap::new }
+}
+
+inventory::submit! {
+    SchemaInfo { name: "GeoJsonLineString", module_path: module_path!(), module_prefix: false, strict_bodies: false, schema_fn: line_string_schema, nested_fn: HashMap::new, patches_fn: HashMap::new }
+}
+
+inventory::submit! {
+    SchemaInfo { name: "GeoJsonPolygon", module_path: module_path!(), module_prefix: false, strict_bodies: false, schema_fn: polygon_schema, nested_fn: HashMap::new, patches_fn: HashMap::new }
+}
@@ -0,0 +1,144 @@
+//! API versions negotiated through the `Accept` header instead of the URL.
+//!
+//! With [`HayaiApp::header_versioning`](crate::HayaiApp::header_versioning) installed,
+//! several handlers may serve the same method and path, each marked `#[version(N)]`. A
+//! request asking for `Accept: application/vnd.{vendor}.v{N}+json` reaches version `N`;
+//! one without a vendor media type reaches the highest version, and one asking for a
+//! version nothing serves is answered 406 with the supported versions. Versioned
+//! responses carry `Vary: Accept`.
+//!
+//! In the spec, the path item holds the highest version's operation, marked with an
+//! `x-api-version` extension. The older versions' operations, complete and marked the
+//! same way, are nested under its `x-api-versions` extension, keyed by version number.
+//!
+//! ```ignore
+//! HayaiApp::new().header_versioning(HeaderVersioning::vnd("myapp"))
+//!
+//! #[get("/users")]
+//! #[version(1)]
+//! async fn list_users_v1() -> Vec<UserV1> { ... }
+//!
+//! #[get("/users")]
+//! #[version(2)]
+//! async fn list_users_v2() -> Vec<User> { ... }
+//! ```
+
+use crate::openapi::Operation;
+use crate::ApiError;
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use axum::routing::MethodRouter;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tower_service::Service;
+
+/// Operation extension naming the API version an operation serves
+pub const VERSION_EXTENSION: &str = "x-api-version";
+
+/// Extension of the highest version's operation holding the other versions' operations
+pub const VERSIONS_EXTENSION: &str = "x-api-versions";
+
+/// How a request names the API version it wants
+#[derive(Debug, Clone)]
+pub struct HeaderVersioning {
+    vendor: String,
+}
+
+impl HeaderVersioning {
+    /// Versions asked for as `Accept: application/vnd.{vendor}.v{N}+json`
+    pub fn vnd(vendor: &str) -> Self {
+        Self { vendor: vendor.to_ascii_lowercase() }
+    }
+
+    /// The vendor media type of `version`
+    pub fn media_type(&self, version: u32) -> String {
+        format!("application/vnd.{}.v{}+json", self.vendor, version)
+    }
+
+    /// The version `headers` ask for: `None` without a vendor media type, `Err` with the
+    /// requested text when it isn't a version number
+    fn requested(&self, headers: &HeaderMap) -> Option<Result<u32, String>> {
+        let prefix = format!("application/vnd.{}.", self.vendor);
+        headers.get_all(header::ACCEPT).iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|range| range.split(';').next().unwrap_or("").trim().to_ascii_lowercase())
+            .find_map(|essence| {
+                let requested = essence.strip_prefix(&prefix)?;
+                let requested = requested.strip_suffix("+json").unwrap_or(requested).to_string();
+                Some(requested.strip_prefix('v').and_then(|n| n.parse().ok()).ok_or(requested))
+            })
+    }
+}
+
+/// The handlers of one method and path, by version
+#[derive(Clone)]
+pub(crate) struct VersionedRoute {
+    pub(crate) versioning: Arc<HeaderVersioning>,
+    /// Sorted by version, ascending
+    pub(crate) handlers: Arc<Vec<(u32, MethodRouter)>>,
+}
+
+/// Send the request to the handler of the version it asks for
+pub(crate) async fn dispatch(route: VersionedRoute, req: Request) -> Response {
+    let requested = route.versioning.requested(req.headers());
+    let chosen = match &requested {
+        None => route.handlers.last(),
+        Some(Ok(version)) => route.handlers.iter().find(|(v, _)| v == version),
+        Some(Err(_)) => None,
+    };
+    let Some((_, handler)) = chosen else {
+        let requested = match requested {
+            Some(Ok(version)) => version.to_string(),
+            Some(Err(text)) => text,
+            None => String::new(),
+        };
+        let supported: Vec<String> = route.handlers.iter().map(|(v, _)| v.to_string()).collect();
+        let mut response = ApiError::not_acceptable(format!(
+            "Unsupported API version `{}`; supported: {}", requested, supported.join(", "),
+        )).into_response();
+        response.headers_mut().insert(header::VARY, HeaderValue::from_static("accept"));
+        return response;
+    };
+    let mut response = match handler.clone().call(req).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    };
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+/// Key of an operation in its path item while the spec is built; versioned operations
+/// stay apart until [`nest_versions`]
+pub(crate) fn operation_key(method: &str, version: Option<u32>) -> String {
+    match version {
+        Some(version) => format!("{} v{}", method.to_lowercase(), version),
+        None => method.to_lowercase(),
+    }
+}
+
+/// Put each method's highest version under the method, with the others nested under its
+/// [`VERSIONS_EXTENSION`]
+pub(crate) fn nest_versions(paths: &mut HashMap<String, HashMap<String, Operation>>) {
+    for item in paths.values_mut() {
+        let keys: Vec<String> = item.keys().filter(|key| key.contains(' ')).cloned().collect();
+        let mut by_method: BTreeMap<String, BTreeMap<u32, Operation>> = BTreeMap::new();
+        for key in keys {
+            let operation = item.remove(&key).expect("key was just listed");
+            let (method, version) = key.split_once(" v").expect("versioned operation key");
+            let version = version.parse().expect("versioned operation key");
+            by_method.entry(method.to_string()).or_default().insert(version, operation);
+        }
+        for (method, mut versions) in by_method {
+            let (_, mut latest) = versions.pop_last().expect("at least one version");
+            if !versions.is_empty() {
+                let older: serde_json::Map<String, serde_json::Value> = versions.into_iter()
+                    .map(|(version, operation)| (version.to_string(), serde_json::to_value(&operation).unwrap_or_default()))
+                    .collect();
+                latest.extensions.push((VERSIONS_EXTENSION.to_string(), serde_json::Value::Object(older)));
+            }
+            item.insert(method, latest);
+        }
+    }
+}
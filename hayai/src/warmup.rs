@@ -0,0 +1,133 @@
+//! Requests sent through the app before it starts listening, see
+//! [`HayaiApp::warmup`](crate::HayaiApp::warmup).
+//!
+//! They run after the startup callbacks, against the same router the server will use, so
+//! a handler whose dependencies are broken fails the deploy instead of the first real
+//! request. The first response outside its expected status range aborts
+//! [`serve_with_shutdown`](crate::HayaiApp::serve_with_shutdown).
+//!
+//! ```ignore
+//! HayaiApp::new()
+//!     .warmup(WarmupPlan::from_routes_get_only()
+//!         .post("/search", json!({ "query": "warmup" }), 200..=299))
+//! ```
+
+use axum::body::Body;
+use axum::http::{header, Method, Request};
+use axum::Router;
+use std::fmt;
+use std::ops::RangeInclusive;
+use tower_service::Service;
+
+/// Value for every path parameter of a generated request
+pub const PATH_PLACEHOLDER: &str = "1";
+
+/// Longest part of a response body quoted in a [`WarmupError`]
+const BODY_EXCERPT: usize = 200;
+
+/// One synthetic request and the statuses that count as healthy
+#[derive(Debug, Clone)]
+pub struct WarmupRequest {
+    pub method: Method,
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+    pub expect: RangeInclusive<u16>,
+}
+
+/// The requests to send before serving
+#[derive(Debug, Clone, Default)]
+pub struct WarmupPlan {
+    requests: Vec<WarmupRequest>,
+    routes_get_only: bool,
+}
+
+impl WarmupPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A GET of every GET route, with path parameters set to [`PATH_PLACEHOLDER`]. Any
+    /// status below 500 passes, since a placeholder id may well be missing or invalid.
+    pub fn from_routes_get_only() -> Self {
+        Self { requests: Vec::new(), routes_get_only: true }
+    }
+
+    pub fn get(self, path: &str, expect: RangeInclusive<u16>) -> Self {
+        self.request(Method::GET, path, None, expect)
+    }
+
+    pub fn post(self, path: &str, body: serde_json::Value, expect: RangeInclusive<u16>) -> Self {
+        self.request(Method::POST, path, Some(body), expect)
+    }
+
+    pub fn request(mut self, method: Method, path: &str, body: Option<serde_json::Value>, expect: RangeInclusive<u16>) -> Self {
+        self.requests.push(WarmupRequest { method, path: path.to_string(), body, expect });
+        self
+    }
+
+    /// The plan's requests, after one generated for each of `routes`' GET routes
+    pub(crate) fn requests(&self, routes: &[(&'static str, String)]) -> Vec<WarmupRequest> {
+        let generated = routes.iter()
+            .filter(|(method, _)| self.routes_get_only && *method == "GET")
+            .map(|(_, path)| WarmupRequest { method: Method::GET, path: fill_placeholders(path), body: None, expect: 100..=499 });
+        generated.chain(self.requests.iter().cloned()).collect()
+    }
+}
+
+/// `/users/{id}/posts/{*rest}` → `/users/1/posts/1`
+fn fill_placeholders(path: &str) -> String {
+    path.split('/')
+        .map(|segment| if segment.starts_with('{') && segment.ends_with('}') { PATH_PLACEHOLDER } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A warm-up request answered outside its expected range
+#[derive(Debug)]
+pub struct WarmupError {
+    pub method: Method,
+    pub path: String,
+    pub status: u16,
+    pub expect: RangeInclusive<u16>,
+    /// The start of the response body
+    pub body: String,
+}
+
+impl fmt::Display for WarmupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "warmup {} {} answered {}, expected {}-{}: {}",
+            self.method, self.path, self.status, self.expect.start(), self.expect.end(), self.body,
+        )
+    }
+}
+
+impl std::error::Error for WarmupError {}
+
+/// Send each request through `router` in order, stopping at the first unexpected status
+pub(crate) async fn run(router: &Router, requests: Vec<WarmupRequest>) -> Result<(), WarmupError> {
+    for warmup in requests {
+        let mut builder = Request::builder().method(warmup.method.clone()).uri(&warmup.path);
+        let body = match &warmup.body {
+            Some(json) => {
+                builder = builder.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        let request = builder.body(body).expect("warmup request path is not a valid URI");
+        let response = match router.clone().call(request).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        };
+        let status = response.status().as_u16();
+        if !warmup.expect.contains(&status) {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap_or_default();
+            let body: String = String::from_utf8_lossy(&bytes).chars().take(BODY_EXCERPT).collect();
+            return Err(WarmupError { method: warmup.method, path: warmup.path, status, expect: warmup.expect, body });
+        }
+        tracing::info!(method = %warmup.method, path = %warmup.path, status, "warmup request passed");
+    }
+    Ok(())
+}
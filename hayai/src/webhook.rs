@@ -0,0 +1,145 @@
+//! Signed webhook bodies, behind the `webhooks` feature, see [`SignedPayload`].
+//!
+//! Payment providers and code hosts sign each delivery with a secret shared with the
+//! receiver. A handler taking `SignedPayload<T, S>` checks the signature scheme `S` against
+//! the raw body before anything parses it, then reads and validates `T` like any JSON body.
+//! Every failed check answers the same `401`, so a caller can't learn which part was wrong.
+//! The spec documents the signature header as a required parameter.
+//!
+//! ```ignore
+//! struct StripeSecret(String);
+//!
+//! impl WebhookSecret for StripeSecret {
+//!     const HEADER: &'static str = "X-Stripe-Signature";
+//!     fn secret(&self) -> &[u8] { self.0.as_bytes() }
+//! }
+//!
+//! #[post("/webhooks/payments")]
+//! async fn payment_event(event: SignedPayload<PaymentEvent, HmacSha256<StripeSecret>>) -> StatusCode {
+//!     ...
+//! }
+//!
+//! HayaiApp::new().dep(StripeSecret(std::env::var("STRIPE_SECRET")?))
+//! ```
+
+use crate::{ApiError, AppState, DepResolver};
+use axum::body::Bytes;
+use axum::http::{HeaderMap, Request};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::marker::PhantomData;
+
+/// Type name the route macros recognize
+pub const SIGNED_PAYLOAD: &str = "SignedPayload";
+
+/// The one message every failed check answers with
+pub const INVALID_SIGNATURE: &str = "Invalid webhook signature";
+
+/// How a delivery proves it came from the sender
+pub trait SignatureScheme: Send + Sync + 'static {
+    /// The request header carrying the signature
+    const HEADER: &'static str;
+
+    /// Whether `body` is signed as `headers` claim. Secrets come from the app's
+    /// dependencies; a missing one is an error rather than a failed check.
+    fn verify(headers: &HeaderMap, body: &[u8], deps: &DepResolver<'_>) -> Result<bool, ApiError>;
+}
+
+/// A shared signing secret, registered with [`HayaiApp::dep`](crate::HayaiApp::dep)
+pub trait WebhookSecret: Send + Sync + 'static {
+    /// The header [`HmacSha256`] reads the signature from
+    const HEADER: &'static str = "X-Signature-256";
+
+    fn secret(&self) -> &[u8];
+}
+
+/// The hex HMAC-SHA256 of the body, keyed by the `K` dependency, in `K::HEADER`
+pub struct HmacSha256<K>(PhantomData<fn() -> K>);
+
+impl<K: WebhookSecret> SignatureScheme for HmacSha256<K> {
+    const HEADER: &'static str = K::HEADER;
+
+    fn verify(headers: &HeaderMap, body: &[u8], deps: &DepResolver<'_>) -> Result<bool, ApiError> {
+        let Some(signature) = header_str(headers, Self::HEADER) else { return Ok(false) };
+        Ok(constant_time_eq(hmac_hex(deps.get::<K>()?.secret(), body).as_bytes(), signature.to_ascii_lowercase().as_bytes()))
+    }
+}
+
+/// GitHub's `X-Hub-Signature-256: sha256=<hex>`, keyed by the `K` dependency; `K::HEADER`
+/// is not used
+pub struct GitHubSha256<K>(PhantomData<fn() -> K>);
+
+impl<K: WebhookSecret> SignatureScheme for GitHubSha256<K> {
+    const HEADER: &'static str = "X-Hub-Signature-256";
+
+    fn verify(headers: &HeaderMap, body: &[u8], deps: &DepResolver<'_>) -> Result<bool, ApiError> {
+        let Some(signature) = header_str(headers, Self::HEADER).and_then(|v| v.strip_prefix("sha256=")) else {
+            return Ok(false);
+        };
+        Ok(constant_time_eq(hmac_hex(deps.get::<K>()?.secret(), body).as_bytes(), signature.to_ascii_lowercase().as_bytes()))
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+/// Lowercase hex HMAC-SHA256 of `body`
+pub fn hmac_hex(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Compare two signatures in time that depends only on their lengths, so a caller timing
+/// answers can't find the expected one byte by byte
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+/// A JSON body of type `T` whose signature `S` was checked before it was parsed
+pub struct SignedPayload<T, S> {
+    payload: T,
+    raw: Bytes,
+    _scheme: PhantomData<fn() -> S>,
+}
+
+impl<T, S> SignedPayload<T, S> {
+    pub fn into_inner(self) -> T {
+        self.payload
+    }
+
+    /// The body exactly as signed, for schemes that sign more than the payload's fields
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+}
+
+impl<T, S> std::ops::Deref for SignedPayload<T, S> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.payload
+    }
+}
+
+/// Read a `SignedPayload` body for a generated handler wrapper: verify, then parse
+#[doc(hidden)]
+pub async fn __extract_signed<T: DeserializeOwned, S: SignatureScheme>(
+    req: Request<axum::body::Body>,
+    state: &AppState,
+    type_name: &str,
+) -> Result<SignedPayload<T, S>, ApiError> {
+    let headers = req.headers().clone();
+    let raw = crate::__read_json_bytes(req, state).await?;
+    let deps = DepResolver { state: std::borrow::Cow::Borrowed(state) };
+    if !S::verify(&headers, &raw, &deps)? {
+        return Err(ApiError::unauthorized(INVALID_SIGNATURE));
+    }
+    let payload = crate::__parse_json_body(&raw, state, type_name)?;
+    Ok(SignedPayload { payload, raw, _scheme: PhantomData })
+}
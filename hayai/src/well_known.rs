@@ -0,0 +1,132 @@
+//! Standard discovery endpoints for public deployments, see [`HayaiApp::well_known`](crate::HayaiApp::well_known).
+//!
+//! `/robots.txt`, `/.well-known/security.txt` (RFC 9116) and JSON discovery documents
+//! under any path. None of them appear in the OpenAPI spec, and requests to them are
+//! left out of the access log unless [`WellKnown::log_requests`] is set: scanners fetch
+//! them constantly.
+//!
+//! ```ignore
+//! HayaiApp::new().well_known(WellKnown::new()
+//!     .robots_txt(RobotsTxt::DenyAll)
+//!     .security_txt("mailto:security@example.com", expires)
+//!     .well_known_json("/.well-known/api-descriptor", || json!({ "version": env!("CARGO_PKG_VERSION") })))
+//! ```
+
+use crate::AppState;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::Router;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const TEXT_PLAIN: &str = "text/plain; charset=utf-8";
+
+/// What `/robots.txt` answers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RobotsTxt {
+    /// `Disallow: /` for every user agent
+    DenyAll,
+    /// The file as given
+    Custom(String),
+}
+
+impl RobotsTxt {
+    fn body(&self) -> String {
+        match self {
+            RobotsTxt::DenyAll => "User-agent: *\nDisallow: /\n".to_string(),
+            RobotsTxt::Custom(body) => body.clone(),
+        }
+    }
+}
+
+impl From<&str> for RobotsTxt {
+    fn from(body: &str) -> Self {
+        RobotsTxt::Custom(body.to_string())
+    }
+}
+
+impl From<String> for RobotsTxt {
+    fn from(body: String) -> Self {
+        RobotsTxt::Custom(body)
+    }
+}
+
+/// A JSON discovery document: a fixed value, or a function called on each request
+pub trait WellKnownDocument: Send + Sync + 'static {
+    fn document(&self) -> serde_json::Value;
+}
+
+impl WellKnownDocument for serde_json::Value {
+    fn document(&self) -> serde_json::Value {
+        self.clone()
+    }
+}
+
+impl<F: Fn() -> serde_json::Value + Send + Sync + 'static> WellKnownDocument for F {
+    fn document(&self) -> serde_json::Value {
+        self()
+    }
+}
+
+/// The discovery endpoints an app serves
+#[derive(Clone, Default)]
+pub struct WellKnown {
+    robots: Option<String>,
+    security: Option<String>,
+    documents: Vec<(String, Arc<dyn WellKnownDocument>)>,
+    log_requests: bool,
+}
+
+impl WellKnown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `/robots.txt`: [`RobotsTxt::DenyAll`], or the file's contents
+    pub fn robots_txt(mut self, robots: impl Into<RobotsTxt>) -> Self {
+        self.robots = Some(robots.into().body());
+        self
+    }
+
+    /// Serve `/.well-known/security.txt` naming `contact` (a `mailto:` or `https:` URI)
+    /// and valid until `expires`
+    pub fn security_txt(mut self, contact: impl Into<String>, expires: SystemTime) -> Self {
+        self.security = Some(format!("Contact: {}\nExpires: {}\n", contact.into(), crate::access_log::rfc3339(expires)));
+        self
+    }
+
+    /// Serve `document` as JSON at `path`, e.g. `/.well-known/api-descriptor`
+    pub fn well_known_json(mut self, path: &str, document: impl WellKnownDocument) -> Self {
+        self.documents.push((path.to_string(), Arc::new(document)));
+        self
+    }
+
+    /// Log requests to these endpoints like any other
+    pub fn log_requests(mut self) -> Self {
+        self.log_requests = true;
+        self
+    }
+
+    /// The paths served, left out of the access log unless [`Self::log_requests`]
+    pub(crate) fn quiet_paths(&self) -> Vec<String> {
+        if self.log_requests {
+            return Vec::new();
+        }
+        let robots = self.robots.as_ref().map(|_| "/robots.txt".to_string());
+        let security = self.security.as_ref().map(|_| "/.well-known/security.txt".to_string());
+        robots.into_iter().chain(security).chain(self.documents.iter().map(|(path, _)| path.clone())).collect()
+    }
+
+    pub(crate) fn mount(self, mut app: Router<AppState>) -> Router<AppState> {
+        if let Some(robots) = self.robots {
+            app = app.route("/robots.txt", axum::routing::get(move || async move { ([(header::CONTENT_TYPE, TEXT_PLAIN)], robots) }));
+        }
+        if let Some(security) = self.security {
+            app = app.route("/.well-known/security.txt", axum::routing::get(move || async move { ([(header::CONTENT_TYPE, TEXT_PLAIN)], security) }));
+        }
+        for (path, document) in self.documents {
+            app = app.route(&path, axum::routing::get(move || async move { axum::Json(document.document()).into_response() }));
+        }
+        app
+    }
+}
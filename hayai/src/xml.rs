@@ -0,0 +1,57 @@
+//! XML bodies for partners that don't speak JSON, behind the `xml` feature.
+//!
+//! `#[consumes("application/xml")]` on a route whose body is a model reads that model
+//! from XML; validation runs as it does for JSON. `#[produces("application/xml")]`
+//! serializes the response as XML under a root element named after the response type, or
+//! `root = ".."`. The spec documents both media types with the model's schema.
+//!
+//! ```ignore
+//! #[post("/partner/users")]
+//! #[consumes("application/xml")]
+//! #[produces("application/xml", root = "user")]
+//! async fn import_user(body: CreateUser) -> User { ... }
+//! ```
+
+use crate::{ApiError, AppState};
+use axum::body::Bytes;
+use axum::extract::FromRequest;
+use axum::http::{header, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Whether a `Content-Type` essence is an XML media type
+pub fn is_xml_media_type(essence: &str) -> bool {
+    let essence = essence.trim().to_ascii_lowercase();
+    essence == "application/xml" || essence == "text/xml" || (essence.starts_with("application/") && essence.ends_with("+xml"))
+}
+
+/// Read and deserialize an XML request body for a generated handler wrapper
+#[doc(hidden)]
+pub async fn __extract_xml_body<T: DeserializeOwned>(req: Request<axum::body::Body>, state: &AppState) -> Result<T, ApiError> {
+    let essence = req.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or("");
+    if !is_xml_media_type(essence) {
+        return Err(ApiError::unsupported_media_type("Expected Content-Type: application/xml".to_string()));
+    }
+    let bytes = Bytes::from_request(req, state).await.map_err(|rejection| {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            ApiError::payload_too_large("Request body too large")
+        } else {
+            ApiError::bad_request(format!("Failed to read body: {}", rejection.body_text()))
+        }
+    })?;
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| ApiError::bad_request("Invalid XML body: not valid UTF-8".to_string()))?;
+    quick_xml::de::from_str(text).map_err(|e| ApiError::bad_request(format!("Invalid XML body: {}", e)))
+}
+
+/// Serialize a handler's return value as XML under a `root` element
+#[doc(hidden)]
+pub fn __xml_response<T: Serialize>(status: StatusCode, value: &T, root: &str) -> Result<Response, ApiError> {
+    let body = quick_xml::se::to_string_with_root(root, value)
+        .map_err(|e| ApiError::internal(format!("Response serialization failed: {}", e)))?;
+    Ok((status, [(header::CONTENT_TYPE, "application/xml")], body).into_response())
+}
@@ -0,0 +1,170 @@
+#![cfg(feature = "auth")]
+
+use hayai::audit::{AuditEvent, AuditSink, FileAuditSink, MemoryAuditSink};
+use hayai::lifespan::BoxError;
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+struct Claims {
+    user: String,
+}
+
+impl SecurityValidator for Claims {
+    async fn validate(parts: &http::request::Parts) -> Result<Self, ApiError> {
+        parts.headers.get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(|user| Claims { user: user.to_string() })
+            .ok_or_else(|| ApiError::unauthorized("Missing token"))
+    }
+
+    fn subject(&self) -> String {
+        self.user.clone()
+    }
+}
+
+#[api_model]
+struct UserUpdate {
+    name: String,
+    #[schema(sensitive)]
+    password: String,
+}
+
+#[api_model]
+struct User {
+    id: i64,
+    name: String,
+}
+
+#[put("/users/{id}")]
+#[audited(resource = "user")]
+async fn update_user(id: i64, _auth: Auth<Claims>, body: UserUpdate) -> User {
+    User { id, name: body.name }
+}
+
+#[get("/users/{id}")]
+async fn get_user(id: i64) -> User {
+    User { id, name: "Ada".to_string() }
+}
+
+#[delete("/users/{id}")]
+#[audited(resource = "user")]
+async fn delete_user(id: i64) -> Result<(), ApiError> {
+    Err(ApiError::not_found(format!("User {} not found", id)))
+}
+
+fn app() -> HayaiApp {
+    HayaiApp::new()
+        .bearer_auth()
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_UPDATE_USER)
+            .route(__HAYAI_ROUTE_GET_USER)
+            .route(__HAYAI_ROUTE_DELETE_USER))
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn update(base: &str, id: i64) -> reqwest::Response {
+    reqwest::Client::new().put(format!("{base}/users/{id}"))
+        .bearer_auth("alice")
+        .json(&json!({ "name": "Ada", "password": "hunter2" }))
+        .send().await.unwrap()
+}
+
+/// The sink's events once `count` have arrived
+async fn recorded(sink: &MemoryAuditSink, count: usize) -> Vec<AuditEvent> {
+    for _ in 0..100 {
+        if sink.events().len() >= count {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    sink.events()
+}
+
+#[tokio::test]
+async fn test_put_records_one_redacted_event() {
+    let sink = MemoryAuditSink::new();
+    let base = spawn(app().audit_sink(sink.clone())).await;
+
+    assert_eq!(reqwest::get(format!("{base}/users/7")).await.unwrap().status(), 200);
+    assert_eq!(update(&base, 7).await.status(), 200);
+
+    let events = recorded(&sink, 1).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(sink.events().len(), 1, "only the PUT is audited");
+    let event = &events[0];
+    assert_eq!(event.resource, "user");
+    assert_eq!(event.operation_id, "update_user");
+    assert_eq!((event.method.as_str(), event.route.as_str()), ("PUT", "/users/{id}"));
+    assert_eq!(event.path_params.get("id").map(String::as_str), Some("7"));
+    assert_eq!(event.subject.as_deref(), Some("alice"));
+    assert_eq!(event.body, Some(json!({ "name": "Ada", "password": "***" })));
+    assert_eq!(event.status, 200);
+    assert!(event.received_at <= event.completed_at);
+}
+
+#[tokio::test]
+async fn test_failed_mutations_are_recorded_with_their_status() {
+    let sink = MemoryAuditSink::new();
+    let base = spawn(app().audit_sink(sink.clone())).await;
+    let resp = reqwest::Client::new().delete(format!("{base}/users/3")).send().await.unwrap();
+    assert_eq!(resp.status(), 404);
+
+    let events = recorded(&sink, 1).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].operation_id, events[0].status), ("delete_user", 404));
+    assert_eq!(events[0].subject, None);
+    assert_eq!(events[0].body, None);
+}
+
+/// Never finishes recording, so the queue fills up
+struct StuckSink;
+
+impl AuditSink for StuckSink {
+    async fn record(&self, _event: AuditEvent) -> Result<(), BoxError> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn test_slow_sink_does_not_block_responses() {
+    let base = spawn(app().audit_sink(StuckSink).audit_buffer(2)).await;
+    let started = std::time::Instant::now();
+    for id in 0..10 {
+        assert_eq!(update(&base, id).await.status(), 200);
+    }
+    assert!(started.elapsed() < Duration::from_secs(5), "took {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn test_file_sink_appends_json_lines() {
+    let path = std::env::temp_dir().join(format!("hayai-audit-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let base = spawn(app().audit_sink(FileAuditSink::open(&path).unwrap())).await;
+    update(&base, 1).await;
+    update(&base, 2).await;
+
+    let mut lines = Vec::new();
+    for _ in 0..100 {
+        lines = std::fs::read_to_string(&path).unwrap_or_default().lines().map(str::to_string).collect();
+        if lines.len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(lines.len(), 2);
+    let first: Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(first["path_params"], json!({ "id": "1" }));
+    assert_eq!(first["body"]["password"], "***");
+    assert!(first["received_at"].as_str().unwrap().ends_with('Z'));
+}
@@ -1,3 +1,5 @@
+#![cfg(all(feature = "openapi", feature = "validation", feature = "auth"))]
+
 use hayai::prelude::*;
 use hayai::axum;
 use serde_json::Value;
@@ -25,6 +27,10 @@ impl SecurityValidator for TestClaims {
             _ => Err(ApiError::unauthorized("Invalid token")),
         }
     }
+
+    fn subject(&self) -> String {
+        format!("user-{}", self.user_id)
+    }
 }
 
 // ===== Models =====
@@ -47,6 +53,12 @@ async fn get_me(auth: Auth<TestClaims>) -> UserProfile {
     }
 }
 
+/// Who am I, without the full claims
+#[get("/whoami")]
+async fn whoami(_auth: Auth<TestClaims>, subject: Subject) -> String {
+    subject.0
+}
+
 /// A public endpoint (no authentication required)
 #[get("/health")]
 async fn health_check() -> UserProfile {
@@ -57,8 +69,8 @@ async fn health_check() -> UserProfile {
 
 async fn spawn_auth_app() -> String {
     let router = hayai::HayaiRouter::new("/api")
-        .route(get_me)
-        .route(health_check);
+        .route(__HAYAI_ROUTE_GET_ME)
+        .route(__HAYAI_ROUTE_HEALTH_CHECK);
 
     let app = hayai::HayaiApp::new()
         .title("Auth Test API")
@@ -145,6 +157,96 @@ async fn test_unsecured_route_works_without_token() {
     assert_eq!(body["name"], "healthy");
 }
 
+// ===== E2E Tests: Subject propagation =====
+
+#[derive(Clone, Default)]
+struct CapturedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn spawn_subject_app(log: CapturedLog) -> String {
+    let router = hayai::HayaiRouter::new("/api")
+        .route(__HAYAI_ROUTE_GET_ME)
+        .route(__HAYAI_ROUTE_WHOAMI)
+        .route(__HAYAI_ROUTE_HEALTH_CHECK);
+
+    let app = hayai::HayaiApp::new()
+        .bearer_auth()
+        .access_log(hayai::access_log::AccessLogFormat::Json)
+        .access_log_writer(log)
+        .include(router)
+        .into_router()
+        .layer(axum::middleware::from_fn(|req: axum::extract::Request, next: axum::middleware::Next| async move {
+            let mut response = next.run(req).await;
+            let subject = response.extensions().get::<AuthSubject>().cloned();
+            if let Some(subject) = subject {
+                let value = format!("{}:{}", subject.scheme, subject.subject);
+                response.headers_mut().insert("x-subject", value.parse().unwrap());
+            }
+            response
+        }));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_subject_in_access_log() {
+    let log = CapturedLog::default();
+    let base = spawn_subject_app(log.clone()).await;
+    let client = reqwest::Client::new();
+    client.get(format!("{base}/api/me"))
+        .header("Authorization", "Bearer valid-token-42")
+        .send().await.unwrap();
+    reqwest::get(format!("{base}/api/health")).await.unwrap();
+
+    let output = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["path"], "/api/me");
+    assert_eq!(lines[0]["subject"], "user-42");
+    assert_eq!(lines[1]["path"], "/api/health");
+    assert!(lines[1]["subject"].is_null());
+}
+
+#[tokio::test]
+async fn test_downstream_middleware_reads_subject() {
+    let base = spawn_subject_app(CapturedLog::default()).await;
+    let client = reqwest::Client::new();
+    let resp = client.get(format!("{base}/api/me"))
+        .header("Authorization", "Bearer valid-token-99")
+        .send().await.unwrap();
+    assert_eq!(resp.headers()["x-subject"], "bearer:user-99");
+
+    let resp = client.get(format!("{base}/api/me")).send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+    assert!(resp.headers().get("x-subject").is_none());
+}
+
+#[tokio::test]
+async fn test_subject_extractor() {
+    let base = spawn_subject_app(CapturedLog::default()).await;
+    let client = reqwest::Client::new();
+    let resp = client.get(format!("{base}/api/whoami"))
+        .header("Authorization", "Bearer valid-token-42")
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<String>().await.unwrap(), "user-42");
+}
+
 // ===== E2E Tests: OpenAPI Spec =====
 
 #[tokio::test]
@@ -218,5 +320,175 @@ async fn test_swagger_ui_available() {
     let resp = reqwest::get(format!("{base}/docs")).await.unwrap();
     assert_eq!(resp.status(), 200);
     let body = resp.text().await.unwrap();
-    assert!(body.contains("swagger"), "Should serve Swagger UI HTML");
+    assert!(body.contains(r#"id="api-reference""#), "Should serve the docs UI HTML");
+}
+
+// ===== Route-level overrides of router security and tags =====
+
+/// Anyone can list users
+#[get("/users")]
+#[security(none)]
+async fn list_members() -> Vec<UserProfile> {
+    vec![UserProfile { id: 1, name: "Alice".into() }]
+}
+
+/// Only signed-in users can add one
+#[post("/users")]
+async fn add_member(auth: Auth<TestClaims>) -> UserProfile {
+    UserProfile { id: 2, name: format!("Added by {}", auth.user_id) }
+}
+
+#[get("/users/audit")]
+#[tag(replace = "admin")]
+async fn audit_members(_auth: Auth<TestClaims>) -> Vec<UserProfile> {
+    Vec::new()
+}
+
+#[tokio::test]
+async fn test_route_overrides_router_security_and_tags() {
+    let router = hayai::HayaiRouter::new("/members")
+        .tag("members")
+        .security(hayai::security::BEARER)
+        .route(__HAYAI_ROUTE_LIST_MEMBERS)
+        .route(__HAYAI_ROUTE_ADD_MEMBER)
+        .route(__HAYAI_ROUTE_AUDIT_MEMBERS);
+    let app = hayai::HayaiApp::new().bearer_auth().include(router).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    let base = format!("http://{}", addr);
+    let client = reqwest::Client::new();
+
+    let resp = client.get(format!("{base}/members/users")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let resp = client.post(format!("{base}/members/users")).send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+    let resp = client.post(format!("{base}/members/users"))
+        .header("Authorization", "Bearer valid-token-42")
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+
+    let spec: Value = client.get(format!("{base}/openapi.json")).send().await.unwrap().json().await.unwrap();
+    let users = &spec["paths"]["/members/users"];
+    assert_eq!(users["get"]["security"], serde_json::json!([]));
+    assert_eq!(users["get"]["tags"], serde_json::json!(["members"]));
+    assert_eq!(users["post"]["security"], serde_json::json!([{ "bearerAuth": [] }]));
+    let audit = &spec["paths"]["/members/users/audit"]["get"];
+    assert_eq!(audit["tags"], serde_json::json!(["admin"]));
+    assert_eq!(audit["security"], serde_json::json!([{ "bearerAuth": [] }]));
+}
+
+// ===== Cached secured routes =====
+
+static INBOX_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+#[get("/inbox")]
+#[cache(ttl = "30s", allow_authenticated)]
+async fn cached_inbox(auth: Auth<TestClaims>) -> UserProfile {
+    INBOX_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    UserProfile { id: auth.user_id, name: format!("Inbox of {}", auth.user_id) }
+}
+
+#[tokio::test]
+async fn test_cache_on_secured_route_is_per_subject() {
+    let router = hayai::HayaiRouter::new("/api").route(__HAYAI_ROUTE_CACHED_INBOX);
+    let app = hayai::HayaiApp::new().bearer_auth().include(router).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    let base = format!("http://{}", addr);
+    let client = reqwest::Client::new();
+    let inbox = |token: &'static str| client.get(format!("{base}/api/inbox")).header("Authorization", format!("Bearer {token}")).send();
+
+    assert_eq!(inbox("valid-token-42").await.unwrap().json::<Value>().await.unwrap()["id"], 42);
+    let hit = inbox("valid-token-42").await.unwrap();
+    assert_eq!(hit.headers()["x-cache"], "HIT");
+    assert_eq!(INBOX_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // Another subject gets its own entry, and credentials are still checked first
+    assert_eq!(inbox("valid-token-99").await.unwrap().json::<Value>().await.unwrap()["id"], 99);
+    assert_eq!(INBOX_CALLS.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert_eq!(inbox("forged").await.unwrap().status(), 401);
+    assert_eq!(client.get(format!("{base}/api/inbox")).send().await.unwrap().status(), 401);
+}
+
+/// A validator keeping the default subject, which every caller shares
+struct OpaqueToken(String);
+
+impl SecurityValidator for OpaqueToken {
+    async fn validate(parts: &http::request::Parts) -> Result<Self, ApiError> {
+        parts.headers.get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(|token| OpaqueToken(token.to_string()))
+            .ok_or_else(|| ApiError::unauthorized("Missing Authorization header"))
+    }
+}
+
+#[get("/drafts")]
+#[cache(ttl = "30s", allow_authenticated)]
+async fn cached_drafts(auth: Auth<OpaqueToken>) -> Value {
+    serde_json::json!({ "owner": auth.0.0 })
+}
+
+#[tokio::test]
+async fn test_cache_without_subject_is_per_authorization() {
+    let router = hayai::HayaiRouter::new("/api").route(__HAYAI_ROUTE_CACHED_DRAFTS);
+    let app = hayai::HayaiApp::new().bearer_auth().include(router).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    let base = format!("http://{}", addr);
+    let client = reqwest::Client::new();
+    let drafts = |token: &'static str| client.get(format!("{base}/api/drafts")).header("Authorization", format!("Bearer {token}")).send();
+
+    assert_eq!(drafts("alice").await.unwrap().json::<Value>().await.unwrap()["owner"], "alice");
+    let res = drafts("bob").await.unwrap();
+    assert_eq!(res.headers()["x-cache"], "MISS");
+    assert_eq!(res.json::<Value>().await.unwrap()["owner"], "bob");
+    let res = drafts("alice").await.unwrap();
+    assert_eq!(res.headers()["x-cache"], "HIT");
+    assert_eq!(res.json::<Value>().await.unwrap()["owner"], "alice");
+}
+
+static DRAFT_RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+#[get("/drafts/live")]
+#[coalesce]
+async fn live_drafts(auth: Auth<OpaqueToken>) -> Value {
+    DRAFT_RUNS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    serde_json::json!({ "owner": auth.0.0 })
+}
+
+#[tokio::test]
+async fn test_coalesce_without_subject_is_per_authorization() {
+    let router = hayai::HayaiRouter::new("/api").route(__HAYAI_ROUTE_LIVE_DRAFTS);
+    let app = hayai::HayaiApp::new().bearer_auth().include(router).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    let base = format!("http://{}", addr);
+    let client = reqwest::Client::new();
+
+    let handles: Vec<_> = ["alice", "bob", "alice"].into_iter()
+        .map(|token| {
+            let request = client.get(format!("{base}/api/drafts/live")).header("Authorization", format!("Bearer {token}"));
+            tokio::spawn(async move { request.send().await.unwrap().json::<Value>().await.unwrap() })
+        })
+        .collect();
+    let mut owners = Vec::new();
+    for handle in handles {
+        owners.push(handle.await.unwrap()["owner"].clone());
+    }
+    assert_eq!(owners, ["alice", "bob", "alice"]);
+    assert_eq!(DRAFT_RUNS.load(std::sync::atomic::Ordering::SeqCst), 2);
 }
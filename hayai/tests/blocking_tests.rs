@@ -0,0 +1,113 @@
+use hayai::prelude::*;
+use hayai::axum;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata};
+
+/// Collects `hayai::blocking` warnings as `handler: message`
+#[derive(Clone, Default)]
+struct Warnings(Arc<Mutex<Vec<String>>>);
+
+#[derive(Default)]
+struct Fields {
+    handler: String,
+    message: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "handler" {
+            self.handler = value.to_string();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+impl tracing::Subscriber for Warnings {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "hayai::blocking"
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(format!("{}: {}", fields.handler, fields.message));
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[get("/blocking/sleep")]
+async fn sleeps_synchronously() -> String {
+    std::thread::sleep(Duration::from_millis(50));
+    "done".into()
+}
+
+#[get("/blocking/async-sleep")]
+async fn sleeps_asynchronously() -> String {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    "done".into()
+}
+
+#[get("/blocking/offloaded")]
+async fn offloads_sleep() -> Result<String, ApiError> {
+    hayai::blocking(|| {
+        std::thread::sleep(Duration::from_millis(50));
+        "done".to_string()
+    }).await
+}
+
+/// Serve the routes on this test's thread, where `warnings` is the subscriber
+async fn spawn(warnings: &Warnings) -> (String, tracing::subscriber::DefaultGuard) {
+    let guard = tracing::subscriber::set_default(warnings.clone());
+    let app = HayaiApp::new()
+        .detect_blocking(Duration::from_millis(20))
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_SLEEPS_SYNCHRONOUSLY)
+            .route(__HAYAI_ROUTE_SLEEPS_ASYNCHRONOUSLY)
+            .route(__HAYAI_ROUTE_OFFLOADS_SLEEP))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}"), guard)
+}
+
+#[tokio::test]
+async fn test_synchronous_sleep_is_reported() {
+    let warnings = Warnings::default();
+    let (base, _guard) = spawn(&warnings).await;
+
+    assert_eq!(reqwest::get(format!("{base}/blocking/sleep")).await.unwrap().status(), 200);
+    let logged = warnings.0.lock().unwrap().clone();
+    assert_eq!(logged.len(), 1, "{logged:?}");
+    assert!(logged[0].starts_with("sleeps_synchronously: handler blocked the runtime"), "{logged:?}");
+}
+
+#[tokio::test]
+async fn test_async_and_offloaded_sleeps_are_not_reported() {
+    let warnings = Warnings::default();
+    let (base, _guard) = spawn(&warnings).await;
+
+    assert_eq!(reqwest::get(format!("{base}/blocking/async-sleep")).await.unwrap().status(), 200);
+    let resp = reqwest::get(format!("{base}/blocking/offloaded")).await.unwrap();
+    assert_eq!(resp.text().await.unwrap(), "\"done\"");
+    assert!(warnings.0.lock().unwrap().is_empty());
+}
@@ -0,0 +1,92 @@
+#![cfg(all(feature = "openapi", feature = "validation"))]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+struct CreateUser {
+    #[validate(email)]
+    email: String,
+}
+
+#[api_model]
+struct User {
+    id: i64,
+    email: String,
+}
+
+/// Create users; addresses on `taken.example` already have an account
+#[post("/users/batch")]
+#[status(207)]
+async fn create_users(users: Vec<CreateUser>) -> BulkResult<User> {
+    users.into_iter().enumerate()
+        .map(|(i, user)| match user.email.ends_with("@taken.example") {
+            true => Err(ApiError::conflict(format!("{} is taken", user.email))),
+            false => Ok(User { id: i as i64 + 1, email: user.email }),
+        })
+        .collect::<BulkResult<User>>()
+        .success_status(http::StatusCode::CREATED)
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_USERS))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn post(base: &str, body: Value) -> (u16, Value) {
+    let res = reqwest::Client::new().post(format!("{base}/users/batch"))
+        .header("content-type", "application/json")
+        .body(body.to_string())
+        .send().await.unwrap();
+    (res.status().as_u16(), res.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_invalid_element_reported_at_its_index() {
+    let base = spawn().await;
+    let (status, body) = post(&base, json!([
+        { "email": "ada@example.com" },
+        { "email": "not-an-email" },
+        { "email": "grace@taken.example" },
+        { "email": "linus@example.com" },
+    ])).await;
+
+    assert_eq!(status, 207);
+    let items = body.as_array().unwrap();
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0], json!({ "index": 0, "status": 201, "data": { "id": 1, "email": "ada@example.com" } }));
+
+    // Rejected before the handler, with the error a single invalid body would get
+    assert_eq!(items[1]["index"], 1);
+    assert_eq!(items[1]["status"], 422);
+    assert!(items[1].get("data").is_none());
+    assert_eq!(items[1]["error"]["error"], "Validation failed");
+    assert!(items[1]["error"]["details"][0].as_str().unwrap().starts_with("email:"), "{body}");
+
+    // The handler saw three elements, the items keep their body positions
+    assert_eq!(items[2], json!({ "index": 2, "status": 409, "error": { "error": "grace@taken.example is taken" } }));
+    assert_eq!(items[3], json!({ "index": 3, "status": 201, "data": { "id": 3, "email": "linus@example.com" } }));
+}
+
+#[tokio::test]
+async fn test_spec_documents_bulk_items() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+
+    let response = &spec["paths"]["/users/batch"]["post"]["responses"]["207"];
+    assert_eq!(response["description"], "Multi-Status");
+    assert_eq!(
+        response["content"]["application/json"]["schema"],
+        json!({ "type": "array", "items": { "$ref": "#/components/schemas/UserBulkItem" } }),
+    );
+    let item = &spec["components"]["schemas"]["UserBulkItem"];
+    assert_eq!(item["properties"]["data"], json!({ "$ref": "#/components/schemas/User" }));
+    assert_eq!(item["properties"]["error"], json!({ "$ref": "#/components/schemas/ApiError" }));
+    assert_eq!(item["required"], json!(["index", "status"]));
+}
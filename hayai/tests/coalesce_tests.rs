@@ -0,0 +1,119 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[derive(Default)]
+struct Calls(AtomicUsize);
+
+impl Calls {
+    /// Count a handler run, staying in flight long enough for the others to arrive
+    async fn run(&self) -> usize {
+        let call = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        call
+    }
+}
+
+#[api_model]
+struct ReportQuery {
+    region: Option<String>,
+}
+
+#[get("/report")]
+#[coalesce]
+async fn report(query: Query<ReportQuery>, calls: Dep<Calls>) -> Value {
+    let call = calls.run().await;
+    json!({ "region": query.region, "call": call })
+}
+
+#[get("/flaky")]
+#[coalesce]
+async fn flaky(calls: Dep<Calls>) -> Result<Value, ApiError> {
+    calls.run().await;
+    Err(ApiError::service_unavailable("Upstream is down"))
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .dep(Calls::default())
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_REPORT).route(__HAYAI_ROUTE_FLAKY))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// Send `requests` at once, answering (status, x-coalesced, body) for each
+async fn concurrently(requests: Vec<reqwest::RequestBuilder>) -> Vec<(u16, bool, Value)> {
+    let handles: Vec<_> = requests.into_iter()
+        .map(|request| tokio::spawn(async move {
+            let res = request.send().await.unwrap();
+            let coalesced = res.headers().get("x-coalesced").is_some_and(|v| v == "true");
+            (res.status().as_u16(), coalesced, res.json::<Value>().await.unwrap())
+        }))
+        .collect();
+    let mut responses = Vec::new();
+    for handle in handles {
+        responses.push(handle.await.unwrap());
+    }
+    responses
+}
+
+#[tokio::test]
+async fn test_identical_requests_share_one_run() {
+    let base = spawn().await;
+    let client = reqwest::Client::new();
+    let responses = concurrently((0..8).map(|_| client.get(format!("{base}/report?region=eu"))).collect()).await;
+
+    for (status, _, body) in &responses {
+        assert_eq!(*status, 200);
+        assert_eq!(body, &json!({ "region": "eu", "call": 1 }));
+    }
+    assert_eq!(responses.iter().filter(|(_, coalesced, _)| *coalesced).count(), 7);
+
+    // Nothing is kept once the run finishes
+    let res: Value = client.get(format!("{base}/report?region=eu")).send().await.unwrap().json().await.unwrap();
+    assert_eq!(res["call"], 2);
+}
+
+#[tokio::test]
+async fn test_differing_queries_run_separately() {
+    let base = spawn().await;
+    let client = reqwest::Client::new();
+    let responses = concurrently(["eu", "us", "ap"].iter().map(|region| client.get(format!("{base}/report?region={region}"))).collect()).await;
+
+    let mut calls: Vec<i64> = responses.iter().map(|(_, _, body)| body["call"].as_i64().unwrap()).collect();
+    calls.sort();
+    assert_eq!(calls, [1, 2, 3]);
+    assert!(responses.iter().all(|(_, coalesced, _)| !coalesced));
+}
+
+#[tokio::test]
+async fn test_error_delivered_to_every_waiter() {
+    let base = spawn().await;
+    let client = reqwest::Client::new();
+    let responses = concurrently((0..5).map(|_| client.get(format!("{base}/flaky"))).collect()).await;
+
+    for (status, _, body) in &responses {
+        assert_eq!(*status, 503);
+        assert_eq!(body, &json!({ "error": "Upstream is down" }));
+    }
+    assert_eq!(responses.iter().filter(|(_, coalesced, _)| *coalesced).count(), 4);
+}
+
+#[tokio::test]
+async fn test_callers_with_different_credentials_run_separately() {
+    let base = spawn().await;
+    let client = reqwest::Client::new();
+    let requests = ["Bearer alice", "Bearer bob", "Bearer alice"].iter()
+        .map(|token| client.get(format!("{base}/report")).header("authorization", *token))
+        .collect();
+    let responses = concurrently(requests).await;
+
+    assert_eq!(responses.iter().filter(|(_, coalesced, _)| *coalesced).count(), 1);
+    assert_eq!(responses[0].2["call"], responses[2].2["call"]);
+    assert_ne!(responses[0].2["call"], responses[1].2["call"]);
+}
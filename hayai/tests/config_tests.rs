@@ -0,0 +1,103 @@
+use hayai::prelude::*;
+use hayai::axum;
+use hayai::config::{ConfigError, ConfigSource};
+use serde::Deserialize;
+use serde_json::Value;
+
+// Environment variables are process-wide: each test uses its own prefix, except the one
+// exercising the default `HAYAI_` prefix.
+
+#[derive(Debug, Deserialize)]
+struct AppConfig {
+    greeting: String,
+    max_items: u32,
+    #[serde(default)]
+    beta: bool,
+}
+
+#[api_model]
+struct GreetingResponse {
+    greeting: String,
+    max_items: u32,
+}
+
+#[get("/config/greeting")]
+async fn configured_greeting(config: Dep<AppConfig>) -> GreetingResponse {
+    GreetingResponse { greeting: config.greeting.clone(), max_items: config.max_items }
+}
+
+async fn spawn_on(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_typed_config_resolves_in_handler() {
+    std::env::set_var("TYPED_GREETING", "hello");
+    std::env::set_var("TYPED_MAX_ITEMS", "25");
+    let app = HayaiApp::new().config::<AppConfig>(ConfigSource::env("TYPED_")).unwrap();
+    let base = spawn_on(app.into_router()).await;
+
+    let body: Value = reqwest::get(format!("{base}/config/greeting")).await.unwrap().json().await.unwrap();
+    assert_eq!(body["greeting"], "hello");
+    assert_eq!(body["max_items"], 25);
+}
+
+#[tokio::test]
+async fn test_hayai_docs_false_disables_docs() {
+    std::env::set_var("HAYAI_GREETING", "hi");
+    std::env::set_var("HAYAI_MAX_ITEMS", "1");
+    std::env::set_var("HAYAI_DOCS", "false");
+    let app = HayaiApp::new().config_from_env::<AppConfig>().unwrap();
+    let base = spawn_on(app.into_router()).await;
+
+    assert_eq!(reqwest::get(format!("{base}/docs")).await.unwrap().status(), 404);
+    assert_eq!(reqwest::get(format!("{base}/openapi.json")).await.unwrap().status(), 404);
+    assert_eq!(reqwest::get(format!("{base}/config/greeting")).await.unwrap().status(), 200);
+}
+
+#[test]
+fn test_parse_failure_names_variable() {
+    std::env::set_var("BADNUM_GREETING", "hi");
+    std::env::set_var("BADNUM_MAX_ITEMS", "lots");
+    let err = ConfigSource::env("BADNUM_").load::<AppConfig>().unwrap_err();
+    assert_eq!(err.variable.as_deref(), Some("BADNUM_MAX_ITEMS"));
+    assert_eq!(err.to_string(), "invalid configuration: BADNUM_MAX_ITEMS: invalid value `lots`");
+
+    let err: ConfigError = ConfigSource::env("MISSING_").load::<AppConfig>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid configuration: MISSING_GREETING: is required but not set");
+}
+
+#[test]
+fn test_file_values_overridden_by_env() {
+    let path = std::env::temp_dir().join(format!("hayai-config-{}.toml", std::process::id()));
+    std::fs::write(&path, "greeting = \"from file\"\nmax_items = 3\nbeta = true\n").unwrap();
+    std::env::set_var("FILECFG_MAX_ITEMS", "7");
+
+    let config: AppConfig = ConfigSource::env("FILECFG_").file(&path).load().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.greeting, "from file");
+    assert_eq!(config.max_items, 7);
+    assert!(config.beta);
+}
+
+#[tokio::test]
+async fn test_body_limit_from_config() {
+    std::env::set_var("LIMITED_GREETING", "hi");
+    std::env::set_var("LIMITED_MAX_ITEMS", "1");
+    std::env::set_var("LIMITED_BODY_LIMIT", "16");
+    let app = HayaiApp::new().config::<AppConfig>(ConfigSource::env("LIMITED_")).unwrap();
+    let base = spawn_on(app.into_router()).await;
+
+    let resp = reqwest::Client::new().post(format!("{base}/config/echo"))
+        .json(&serde_json::json!({ "greeting": "a much longer greeting than sixteen bytes", "max_items": 1 }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 413);
+}
+
+#[post("/config/echo")]
+async fn echo_greeting(body: GreetingResponse) -> GreetingResponse {
+    body
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "contract")]
+
+use hayai::contract::{self, Expectations, Violation};
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+struct Claims;
+
+impl SecurityValidator for Claims {
+    async fn validate(parts: &http::request::Parts) -> Result<Self, ApiError> {
+        match parts.headers.get("authorization").and_then(|v| v.to_str().ok()) {
+            Some("Bearer contract-token") => Ok(Claims),
+            _ => Err(ApiError::unauthorized("Missing token")),
+        }
+    }
+}
+
+#[api_model]
+struct Widget {
+    id: i64,
+    name: String,
+    tags: Vec<String>,
+}
+
+#[api_model]
+struct NewWidget {
+    #[validate(min_length = 3)]
+    name: String,
+    tags: Vec<String>,
+}
+
+#[get("/widgets/{id}")]
+async fn get_widget(id: i64, _auth: Auth<Claims>) -> Widget {
+    Widget { id, name: "sprocket".to_string(), tags: vec![] }
+}
+
+#[post("/widgets")]
+async fn create_widget(body: NewWidget) -> Widget {
+    Widget { id: 1, name: body.name, tags: body.tags }
+}
+
+/// Forwarded from the old service, which sends ids as strings
+#[get("/legacy/widgets/{id}")]
+#[response(schema = "Widget")]
+async fn get_legacy_widget(id: i64) -> Value {
+    json!({ "id": id.to_string(), "name": "sprocket", "tags": ["old"] })
+}
+
+#[delete("/widgets/{id}")]
+async fn delete_widget(id: i64) -> Result<(), ApiError> {
+    Err(ApiError::not_found(format!("Widget {} not found", id)))
+}
+
+async fn spawn() -> (String, Value) {
+    let app = HayaiApp::new()
+        .bearer_auth()
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_GET_WIDGET)
+            .route(__HAYAI_ROUTE_CREATE_WIDGET)
+            .route(__HAYAI_ROUTE_GET_LEGACY_WIDGET)
+            .route(__HAYAI_ROUTE_DELETE_WIDGET))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let base = format!("http://{addr}");
+    let spec = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    (base, spec)
+}
+
+#[tokio::test]
+async fn test_reports_only_the_misdocumented_route() {
+    let (base, spec) = spawn().await;
+    let report = contract::verify(spec, &base, Expectations::new().bearer("contract-token")).await;
+
+    assert_eq!(report.checked, [
+        "GET /legacy/widgets/{id}",
+        "POST /widgets",
+        "GET /widgets/{id}",
+        "DELETE /widgets/{id}",
+    ]);
+    assert_eq!(report.violations, [Violation {
+        operation: "GET /legacy/widgets/{id}".to_string(),
+        status: Some(200),
+        pointer: Some("#/id".to_string()),
+        message: "expected integer, got string".to_string(),
+    }]);
+    assert!(!report.is_ok());
+    assert!(report.to_string().contains("GET /legacy/widgets/{id} (200) at #/id: expected integer, got string"));
+}
+
+#[tokio::test]
+async fn test_skips_destructive_methods_and_unselected_operations() {
+    let (base, spec) = spawn().await;
+
+    let report = contract::verify(spec.clone(), &base, Expectations::new()
+        .bearer("contract-token")
+        .skip_destructive()).await;
+    assert_eq!(report.skipped, ["DELETE /widgets/{id}"]);
+    assert_eq!(report.checked.len(), 3);
+
+    let get_widget = spec["paths"]["/widgets/{id}"]["get"]["operationId"].as_str().unwrap().to_string();
+    let report = contract::verify(spec, &base, Expectations::new()
+        .operations([get_widget])
+        .path_param("id", "42")).await;
+    assert_eq!(report.checked, ["GET /widgets/{id}"]);
+    // Without the token the documented 401 comes back
+    report.assert_ok();
+}
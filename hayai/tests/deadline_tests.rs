@@ -0,0 +1,133 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[api_model]
+struct Notifications {
+    items: Vec<String>,
+    waited_ms: u64,
+}
+
+/// Notified each time a poll parks
+#[derive(Clone, Default)]
+struct Parked(Arc<tokio::sync::Notify>);
+
+/// Never delivers anything, so only the deadline or shutdown ends the poll
+#[get("/notifications")]
+async fn notifications(deadline: Deadline, parked: Dep<Parked>) -> Notifications {
+    let started = tokio::time::Instant::now();
+    parked.0.notify_one();
+    let items = deadline.sleep_until_or_event(std::future::pending::<Vec<String>>()).await.unwrap_or_default();
+    Notifications { items, waited_ms: started.elapsed().as_millis() as u64 }
+}
+
+#[api_model]
+struct Budget {
+    remaining_ms: u64,
+}
+
+#[get("/budget")]
+#[timeout("2s")]
+async fn budget(deadline: Deadline) -> Budget {
+    Budget { remaining_ms: deadline.remaining().as_millis() as u64 }
+}
+
+fn app() -> HayaiApp {
+    app_with(Parked::default())
+}
+
+fn app_with(parked: Parked) -> HayaiApp {
+    HayaiApp::new()
+        .dep(parked)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_NOTIFICATIONS)
+            .route(__HAYAI_ROUTE_BUDGET))
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn get(base: &str, path: &str, request_timeout: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{base}{path}"));
+    if let Some(value) = request_timeout {
+        request = request.header("X-Request-Timeout", value);
+    }
+    request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_client_budget_ends_long_poll_early() {
+    let base = spawn(app().default_deadline(Duration::from_secs(20))).await;
+    let body: Value = get(&base, "/notifications", Some("200ms")).await.json().await.unwrap();
+    assert_eq!(body["items"], json!([]));
+    let waited = body["waited_ms"].as_u64().unwrap();
+    assert!((150..2000).contains(&waited), "waited {waited}ms");
+
+    let body: Value = get(&base, "/notifications", Some("150")).await.json().await.unwrap();
+    assert!(body["waited_ms"].as_u64().unwrap() < 2000);
+
+    let resp = get(&base, "/notifications", Some("soon")).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_budget_is_the_shortest_limit() {
+    let base = spawn(app().default_deadline(Duration::from_secs(20))).await;
+    // The route's 2s timeout wins over the app's 20s and a client's 10s
+    let body: Value = get(&base, "/budget", Some("10s")).await.json().await.unwrap();
+    let remaining = body["remaining_ms"].as_u64().unwrap();
+    assert!((1000..=2000).contains(&remaining), "remaining {remaining}ms");
+
+    let body: Value = get(&base, "/budget", Some("300ms")).await.json().await.unwrap();
+    assert!(body["remaining_ms"].as_u64().unwrap() <= 300);
+
+    let base = spawn(app().default_deadline(Duration::from_millis(500))).await;
+    let body: Value = get(&base, "/budget", None).await.json().await.unwrap();
+    assert!(body["remaining_ms"].as_u64().unwrap() <= 500);
+}
+
+#[tokio::test]
+async fn test_shutdown_wakes_parked_long_poll() {
+    let addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let parked = Parked::default();
+    let server = tokio::spawn(app_with(parked.clone()).serve_with_shutdown(&addr, async { let _ = stop_rx.await; }));
+
+    let base = format!("http://{addr}");
+    while reqwest::get(format!("{base}/budget")).await.is_err() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let poll = tokio::spawn(async move { get(&base, "/notifications", None).await.json::<Value>().await.unwrap() });
+    tokio::time::timeout(Duration::from_secs(5), parked.0.notified()).await
+        .expect("long poll never reached the handler");
+
+    let stopping = tokio::time::Instant::now();
+    stop_tx.send(()).unwrap();
+    let body = tokio::time::timeout(Duration::from_secs(2), poll).await
+        .expect("long poll still parked after shutdown began")
+        .unwrap();
+    assert_eq!(body["items"], json!([]));
+    tokio::time::timeout(Duration::from_secs(2), server).await.unwrap().unwrap().unwrap();
+    assert!(stopping.elapsed() < Duration::from_secs(2));
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_request_timeout_header_is_documented() {
+    let base = spawn(app()).await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let parameters = spec["paths"]["/notifications"]["get"]["parameters"].as_array().unwrap();
+    let header = parameters.iter().find(|p| p["name"] == "X-Request-Timeout").unwrap();
+    assert_eq!(header["in"], "header");
+    assert_eq!(header["required"], false);
+}
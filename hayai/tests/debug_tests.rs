@@ -0,0 +1,102 @@
+#![cfg(feature = "openapi")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+struct Login {
+    username: String,
+    #[schema(sensitive)]
+    password: String,
+}
+
+#[post("/login")]
+async fn login(body: Login) -> Value {
+    json!({ "user": body.username })
+}
+
+#[get("/widgets")]
+async fn list_widgets() -> Value {
+    json!([])
+}
+
+fn app() -> HayaiApp {
+    HayaiApp::new()
+        .api_key_auth("X-Api-Key")
+        .debug_endpoints(true)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_LOGIN).route(__HAYAI_ROUTE_LIST_WIDGETS))
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_echo_reflects_request_with_credentials_redacted() {
+    let base = spawn(app().allow_debug_in_release()).await;
+    let res = reqwest::Client::new().put(format!("{base}/_debug/echo?tag=a&tag=b&page=2"))
+        .header("authorization", "Bearer secret-token")
+        .header("x-api-key", "key-123")
+        .header("x-trace", "abc")
+        .header("content-type", "application/json")
+        .body(json!({ "username": "ada", "password": "hunter2", "nested": [{ "password": "x" }] }).to_string())
+        .send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let echo: Value = res.json().await.unwrap();
+
+    assert_eq!(echo["method"], "PUT");
+    assert_eq!(echo["path"], "/_debug/echo");
+    assert_eq!(echo["query"], json!({ "tag": ["a", "b"], "page": ["2"] }));
+    assert_eq!(echo["headers"]["authorization"], "***");
+    assert_eq!(echo["headers"]["x-api-key"], "***");
+    assert_eq!(echo["headers"]["x-trace"], "abc");
+    assert_eq!(echo["body"], json!({ "username": "ada", "password": "***", "nested": [{ "password": "***" }] }));
+
+    let res = reqwest::Client::new().post(format!("{base}/_debug/echo")).body("plain text").send().await.unwrap();
+    let echo: Value = res.json().await.unwrap();
+    assert_eq!(echo["body"], "plain text");
+    assert_eq!(echo["body_bytes"], 10);
+}
+
+#[tokio::test]
+async fn test_routes_lists_handlers_outside_the_spec() {
+    let base = spawn(app().allow_debug_in_release()).await;
+    let manifest: Value = reqwest::get(format!("{base}/_debug/routes")).await.unwrap().json().await.unwrap();
+    let handlers: Vec<(&str, &str, &str)> = manifest["routes"].as_array().unwrap().iter()
+        .map(|r| (r["method"].as_str().unwrap(), r["path"].as_str().unwrap(), r["handler"].as_str().unwrap()))
+        .collect();
+    assert_eq!(handlers, [("POST", "/login", "login"), ("GET", "/widgets", "list_widgets")]);
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let paths: Vec<&String> = spec["paths"].as_object().unwrap().keys().collect();
+    assert_eq!(paths, ["/login", "/widgets"]);
+}
+
+#[tokio::test]
+async fn test_disabled_by_default() {
+    let base = spawn(HayaiApp::new().include(HayaiRouter::new("").route(__HAYAI_ROUTE_LIST_WIDGETS))).await;
+    assert_eq!(reqwest::get(format!("{base}/_debug/routes")).await.unwrap().status(), 404);
+}
+
+#[test]
+fn test_release_builds_need_an_override() {
+    assert!(hayai::debug::allowed(false, false));
+    assert!(hayai::debug::allowed(false, true));
+    assert!(hayai::debug::allowed(true, true));
+    // Nothing in the tests sets the environment override
+    assert!(std::env::var(hayai::debug::ALLOW_ENV_VAR).is_err());
+    assert!(!hayai::debug::allowed(true, false));
+}
+
+// Run with `cargo test --release`
+#[cfg(not(debug_assertions))]
+#[test]
+#[should_panic(expected = "Debug endpoints are refused in release builds")]
+fn test_release_build_refuses_without_override() {
+    let _ = app().into_router();
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "decimal")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+struct Product {
+    name: String,
+    #[validate(minimum = "0", max_scale = 2)]
+    price: Decimal,
+    /// Share of the price taken off
+    #[schema(as_number)]
+    #[validate(maximum = "1")]
+    discount: Option<Decimal>,
+}
+
+#[post("/products")]
+async fn create_product(body: Product) -> Product {
+    body
+}
+
+#[post("/products/total")]
+#[status(200)]
+async fn total(body: Vec<Product>) -> Value {
+    let total: Decimal = body.iter().map(|p| p.price * (Decimal::ONE - p.discount.unwrap_or_default())).sum();
+    json!({ "total": total.round_dp(2).to_string() })
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_CREATE_PRODUCT)
+            .route(__HAYAI_ROUTE_TOTAL))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn post(base: &str, path: &str, body: &Value) -> reqwest::Response {
+    reqwest::Client::new().post(format!("{base}{path}")).json(body).send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_decimals_round_trip_exactly() {
+    let base = spawn().await;
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": "19.99", "discount": 0.15 })).await;
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.json::<Value>().await.unwrap(), json!({ "name": "Tea", "price": "19.99", "discount": 0.15 }));
+
+    // Numbers are read as their decimal text, strings are read as numbers
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": 19.99, "discount": "0.1" })).await;
+    assert_eq!(resp.json::<Value>().await.unwrap(), json!({ "name": "Tea", "price": "19.99", "discount": 0.1 }));
+
+    let products = json!([
+        { "name": "Tea", "price": "0.10" },
+        { "name": "Cup", "price": "0.20" },
+        { "name": "Pot", "price": "30.00", "discount": "0.5" },
+    ]);
+    let resp = post(&base, "/products/total", &products).await;
+    assert_eq!(resp.json::<Value>().await.unwrap(), json!({ "total": "15.30" }));
+}
+
+async fn details(resp: reqwest::Response) -> Vec<String> {
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    body["details"].as_array().unwrap().iter().map(|d| d.as_str().unwrap().to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_decimal_constraints_are_enforced() {
+    let base = spawn().await;
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": "19.999" })).await;
+    assert_eq!(details(resp).await, ["price: must have at most 2 decimal places"]);
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": "19.990" })).await;
+    assert_eq!(resp.status(), 201);
+
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": "-0.01" })).await;
+    assert_eq!(details(resp).await, ["price: must be at least 0"]);
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": "1", "discount": 1.5 })).await;
+    assert_eq!(details(resp).await, ["discount: must be at most 1"]);
+
+    let resp = post(&base, "/products", &json!({ "name": "Tea", "price": "cheap" })).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_decimal_schema() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let props = &spec["components"]["schemas"]["Product"]["properties"];
+    assert_eq!(props["price"]["type"], "string");
+    assert_eq!(props["price"]["format"], "decimal");
+    assert_eq!(props["price"]["minimum"], 0.0);
+    assert_eq!(props["price"]["pattern"], "^-?[0-9]+(\\.[0-9]{0,2}0*)?$");
+    let discount = &props["discount"]["anyOf"][0];
+    assert_eq!((discount["type"].clone(), discount["format"].clone()), (json!("number"), json!("decimal")));
+    assert_eq!(discount["maximum"], 1.0);
+}
@@ -0,0 +1,101 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::borrow::Borrow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct Counter {
+    hits: AtomicU64,
+}
+
+impl Counter {
+    fn bump(&self) -> u64 {
+        self.hits.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+fn read(counter: &Counter) -> u64 {
+    counter.hits.load(Ordering::SeqCst)
+}
+
+fn read_arc(counter: Arc<Counter>) -> u64 {
+    read(&counter)
+}
+
+fn read_as_ref(counter: impl AsRef<Counter>) -> u64 {
+    read(counter.as_ref())
+}
+
+fn read_borrowed(counter: impl Borrow<Counter>) -> u64 {
+    read(counter.borrow())
+}
+
+#[get("/hits")]
+async fn hits(counter: Dep<Counter>) -> Value {
+    // Deref: methods of `Counter`, and `&Dep<Counter>` where `&Counter` is expected
+    let bumped = counter.bump();
+    let by_ref = read(&counter);
+    let by_as_ref = read_as_ref(counter.clone());
+    let by_borrow = read_borrowed(counter.clone());
+
+    // A clone, or the `Arc`, moves into a spawned task
+    let handle = counter.clone();
+    let from_clone = tokio::spawn(async move { handle.bump() }).await.unwrap();
+    let shared = counter.inner_arc();
+    let from_arc = tokio::spawn(async move { read_arc(shared) }).await.unwrap();
+
+    json!({
+        "bumped": bumped,
+        "by_ref": by_ref,
+        "by_as_ref": by_as_ref,
+        "by_borrow": by_borrow,
+        "from_clone": from_clone,
+        "from_arc": from_arc,
+        "debug": format!("{:?}", counter),
+    })
+}
+
+#[get("/qualified")]
+async fn qualified(counter: hayai::Dep<Counter>) -> Value {
+    json!({ "hits": counter.bump() })
+}
+
+#[tokio::test]
+async fn test_dep_impls_in_handler_and_task() {
+    let app = HayaiApp::new()
+        .dep(Counter::default())
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_HITS).route(__HAYAI_ROUTE_QUALIFIED))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let body: Value = reqwest::get(format!("http://{addr}/hits")).await.unwrap().json().await.unwrap();
+    assert_eq!(body, json!({
+        "bumped": 1,
+        "by_ref": 1,
+        "by_as_ref": 1,
+        "by_borrow": 1,
+        "from_clone": 2,
+        "from_arc": 2,
+        "debug": "Dep(Counter { hits: 2 })",
+    }));
+
+    // Every clone is the registered instance
+    let body: Value = reqwest::get(format!("http://{addr}/qualified")).await.unwrap().json().await.unwrap();
+    assert_eq!(body, json!({ "hits": 3 }));
+}
+
+#[tokio::test]
+async fn test_handler_called_with_constructed_dep() {
+    let counter = Dep::new(Counter::default());
+    let body = hits(counter.clone()).await;
+    assert_eq!(body["from_arc"], 2);
+    assert_eq!(read(&counter), 2);
+
+    let shared = Arc::new(Counter::default());
+    qualified(Dep::from(shared.clone())).await;
+    assert_eq!(read(&shared), 1);
+}
@@ -0,0 +1,145 @@
+#![cfg(all(feature = "openapi", feature = "validation"))]
+
+// The same models written with `#[api_model]` and with `#[derive(ApiModel)]`: validation,
+// schemas and request handling must come out identical.
+use hayai::prelude::*;
+use hayai::axum;
+use hayai::schemars::JsonSchema;
+use hayai::serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// A new account
+///
+/// Created on sign-up.
+#[api_model(rename_all = "camelCase", generate_patch)]
+#[derive(Debug, Clone)]
+struct CreateAccount {
+    #[validate(min_length = 1, max_length = 50)]
+    display_name: String,
+    /// Where receipts go
+    #[validate(email)]
+    contact_email: String,
+    #[validate(minimum = 13, maximum = 130)]
+    age: i64,
+    #[validate(pattern = "^[a-z]{2}$")]
+    #[schema(example = "de")]
+    country: String,
+    #[schema(string_encoded)]
+    external_id: u64,
+}
+
+/// A new account
+///
+/// Created on sign-up.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, ApiModel)]
+#[serde(crate = "hayai::serde", rename_all = "camelCase")]
+#[schemars(crate = "hayai::schemars", rename = "CreateAccountByDerive")]
+#[api_model_options(generate_patch)]
+struct DerivedCreateAccount {
+    #[validate(min_length = 1, max_length = 50)]
+    display_name: String,
+    /// Where receipts go
+    #[validate(email)]
+    contact_email: String,
+    #[validate(minimum = 13, maximum = 130)]
+    age: i64,
+    #[validate(pattern = "^[a-z]{2}$")]
+    #[schema(example = "de")]
+    country: String,
+    #[schema(string_encoded)]
+    #[serde(with = "hayai::string_encoded")]
+    #[schemars(with = "u64")]
+    external_id: u64,
+}
+
+fn check_dates(booking: &DerivedBooking) -> Result<(), Vec<String>> {
+    if booking.nights > 0 { Ok(()) } else { Err(vec!["nights: must be positive".to_string()]) }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, ApiModel)]
+#[serde(crate = "hayai::serde")]
+#[schemars(crate = "hayai::schemars")]
+#[api_model_options(validate(custom = "check_dates"), title = "Booking")]
+struct DerivedBooking {
+    nights: i64,
+}
+
+fn schema(name: &str) -> Value {
+    let info = hayai::registry::schemas().into_iter().find(|s| s.component_name() == name).unwrap();
+    (info.schema_fn)().to_json_value()
+}
+
+fn account(display_name: &str, contact_email: &str, age: i64, country: &str) -> Value {
+    json!({ "displayName": display_name, "contactEmail": contact_email, "age": age, "country": country, "externalId": "9007199254740993" })
+}
+
+#[test]
+fn test_same_schema() {
+    assert_eq!(schema("CreateAccountByDerive"), schema("CreateAccount"));
+    // Patch models only differ in the model their description links to
+    let mut patch = schema("CreateAccountByDerivePatch");
+    let mut attribute_patch = schema("CreateAccountPatch");
+    assert_eq!(patch["description"], "Partial update for [`DerivedCreateAccount`]: only the fields present are applied.");
+    patch["description"].take();
+    attribute_patch["description"].take();
+    assert_eq!(patch, attribute_patch);
+    assert_eq!(<DerivedCreateAccount as hayai::ComponentName>::component_name(), "CreateAccountByDerive");
+    assert_eq!(schema("DerivedBooking")["title"], "Booking");
+}
+
+#[test]
+fn test_same_validation() {
+    let cases = [
+        account("Ada", "ada@example.com", 36, "gb"),
+        account("", "ada", 12, "GB"),
+        account(&"x".repeat(51), "ada@example", 131, ""),
+    ];
+    for case in cases {
+        let by_attribute: CreateAccount = serde_json::from_value(case.clone()).unwrap();
+        let by_derive: DerivedCreateAccount = serde_json::from_value(case.clone()).unwrap();
+        assert_eq!(by_derive.validate_fields(), by_attribute.validate_fields(), "{case}");
+        assert_eq!(serde_json::to_value(&by_derive).unwrap(), serde_json::to_value(&by_attribute).unwrap());
+
+        let patch: DerivedCreateAccountPatch = serde_json::from_value(case.clone()).unwrap();
+        let attribute_patch: CreateAccountPatch = serde_json::from_value(case.clone()).unwrap();
+        assert_eq!(patch.validate_fields(), attribute_patch.validate_fields());
+    }
+
+    let errors = DerivedBooking { nights: 0 }.validate().unwrap_err();
+    assert_eq!(errors, ["nights: must be positive"]);
+}
+
+#[post("/accounts")]
+async fn create_account(body: CreateAccount) -> CreateAccount {
+    body
+}
+
+#[post("/derived-accounts")]
+async fn create_derived_account(body: DerivedCreateAccount) -> DerivedCreateAccount {
+    body
+}
+
+#[tokio::test]
+async fn test_same_responses() {
+    let app = HayaiApp::new()
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_ACCOUNT).route(__HAYAI_ROUTE_CREATE_DERIVED_ACCOUNT))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = reqwest::Client::new();
+
+    for body in [account("Ada", "ada@example.com", 36, "de"), account("", "ada", 12, "GB")] {
+        let mut responses = Vec::new();
+        for path in ["/accounts", "/derived-accounts"] {
+            let res = client.post(format!("http://{addr}{path}")).json(&body).send().await.unwrap();
+            responses.push((res.status(), res.json::<Value>().await.unwrap()));
+        }
+        assert_eq!(responses[0], responses[1]);
+    }
+
+    let spec: Value = reqwest::get(format!("http://{addr}/openapi.json")).await.unwrap().json().await.unwrap();
+    let schema_ref = |path: &str| spec["paths"][path]["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"].clone();
+    assert_eq!(schema_ref("/accounts"), "#/components/schemas/CreateAccount");
+    assert_eq!(schema_ref("/derived-accounts"), "#/components/schemas/CreateAccountByDerive");
+}
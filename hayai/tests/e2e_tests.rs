@@ -1,9 +1,14 @@
+#![cfg(all(feature = "openapi", feature = "validation", feature = "auth"))]
+
 use hayai::prelude::*;
 use hayai::axum;
 use serde_json::Value;
 
 // --- App setup ---
 
+/// A registered user
+///
+/// Returned by every user endpoint.
 #[api_model]
 #[derive(Debug, Clone)]
 struct User {
@@ -12,12 +17,13 @@ struct User {
     email: String,
 }
 
-#[api_model]
+#[api_model(strict_bodies)]
 #[derive(Debug, Clone)]
 struct CreateUser {
     #[validate(min_length = 1, max_length = 100)]
     name: String,
     #[validate(email)]
+    #[schema(example = "jane@example.com")]
     email: String,
 }
 
@@ -37,12 +43,21 @@ struct UserWithAddress {
     nickname: Option<String>,
 }
 
-#[derive(hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+/// Query parameters for listing users
+#[api_model]
 struct Pagination {
-    page: Option<i64>,
-    limit: Option<i64>,
+    /// Page number (1-based)
+    #[serde(default = "default_page")]
+    page: i64,
+    /// Number of items per page
+    #[serde(default = "default_limit")]
+    #[validate(minimum = 1, maximum = 100)]
+    limit: i64,
 }
 
+fn default_page() -> i64 { 1 }
+fn default_limit() -> i64 { 20 }
+
 struct Database;
 impl Database {
     async fn get_user(&self, id: i64) -> Option<User> {
@@ -55,6 +70,9 @@ impl Database {
     async fn list_users(&self, _page: Option<i64>, _limit: Option<i64>) -> Vec<User> {
         vec![User { id: 1, name: "Alice".into(), email: "alice@example.com".into() }]
     }
+    async fn email_taken(&self, email: &str) -> bool {
+        email == "alice@example.com"
+    }
 }
 
 /// Get a user by ID
@@ -68,6 +86,8 @@ async fn get_user(id: i64, db: Dep<Database>) -> Result<User, ApiError> {
 /// Create a new user
 #[post("/users")]
 #[tag("users")]
+#[link(name = "GetUserById", operation = get_user, parameters(id = "$response.body#/id"))]
+#[link(name = "ListUsers", operation = "list_users")]
 async fn create_user(body: CreateUser, db: Dep<Database>) -> User {
     db.create_user(&body).await
 }
@@ -76,7 +96,7 @@ async fn create_user(body: CreateUser, db: Dep<Database>) -> User {
 #[get("/users")]
 #[tag("users")]
 async fn list_users(query: Query<Pagination>, db: Dep<Database>) -> Vec<User> {
-    db.list_users(query.page, query.limit).await
+    db.list_users(Some(query.page), Some(query.limit)).await
 }
 
 // --- Helper ---
@@ -85,6 +105,7 @@ async fn spawn_app() -> String {
     let app = HayaiApp::new()
         .title("Test API")
         .version("0.1.0")
+        .strict_spec(true)
         .dep(Database)
         .into_router();
 
@@ -193,6 +214,10 @@ async fn test_openapi_nested_schemas() {
     
     // nickname should be nullable (anyOf)
     assert!(schemas["UserWithAddress"]["properties"]["nickname"].get("anyOf").is_some());
+
+    // Struct doc comments become the component title and description
+    assert_eq!(schemas["User"]["title"], "A registered user");
+    assert_eq!(schemas["User"]["description"], "A registered user\n\nReturned by every user endpoint.");
 }
 
 #[tokio::test]
@@ -201,7 +226,97 @@ async fn test_docs_returns_swagger_html() {
     let resp = reqwest::get(format!("{base}/docs")).await.unwrap();
     assert_eq!(resp.status(), 200);
     let body = resp.text().await.unwrap();
-    assert!(body.to_lowercase().contains("swagger"));
+    assert!(body.contains(r#"<script id="api-reference" data-url="/openapi.json"></script>"#));
+}
+
+#[tokio::test]
+async fn test_docs_customization() {
+    let custom = hayai::docs::DocsCustomization {
+        title: Some("Acme <API>".into()),
+        favicon_url: Some("/static/favicon.ico".into()),
+        logo_url: Some("/static/logo.svg?v=1&dark=\"1\"".into()),
+        custom_css: Some("/static/docs.css".into()),
+        custom_js: Some("/static/consent.js".into()),
+        head_html: Some("<script>window.consent = true;</script>".into()),
+    };
+    for mode in [hayai::SwaggerMode::Embedded, hayai::SwaggerMode::Cdn("https://cdn.example.com".into())] {
+        let base = spawn_on(HayaiApp::new().dep(Database).swagger_mode(mode).docs_customization(custom.clone()).into_router()).await;
+        let body = reqwest::get(format!("{base}/docs")).await.unwrap().text().await.unwrap();
+        assert!(body.contains("<title>Acme &lt;API&gt;</title>"), "{body}");
+        assert!(body.contains(r#"<link rel="stylesheet" type="text/css" href="/static/docs.css" />"#));
+        assert!(body.contains(r#"<link rel="icon" href="/static/favicon.ico" />"#));
+        assert!(body.contains(r#"<img src="/static/logo.svg?v=1&amp;dark=&quot;1&quot;" alt="Acme &lt;API&gt;" />"#));
+        assert!(body.contains(r#"<script src="/static/consent.js"></script>"#));
+        // head_html is trusted and inserted unescaped
+        assert!(body.contains("<script>window.consent = true;</script>\n</head>"));
+    }
+}
+
+#[tokio::test]
+async fn test_docs_html_replacement_template() {
+    let template = r#"<html><body><redoc spec-url="{{SPEC_URL}}"></redoc></body></html>"#;
+    let base = spawn_on(HayaiApp::new().dep(Database).docs_html(template).into_router()).await;
+    let body = reqwest::get(format!("{base}/docs")).await.unwrap().text().await.unwrap();
+    assert_eq!(body, r#"<html><body><redoc spec-url="/openapi.json"></redoc></body></html>"#);
+
+    let base = spawn_on(HayaiApp::new().dep(Database).docs_prefix("/api/").docs_html(template).into_router()).await;
+    let body = reqwest::get(format!("{base}/api/docs")).await.unwrap().text().await.unwrap();
+    assert_eq!(body, r#"<html><body><redoc spec-url="/api/openapi.json"></redoc></body></html>"#);
+    assert_eq!(reqwest::get(format!("{base}/api/openapi.json")).await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/docs")).await.unwrap().status(), 404);
+
+    // The generated page follows the prefix too
+    let base = spawn_on(HayaiApp::new().dep(Database).docs_prefix("api").into_router()).await;
+    let body = reqwest::get(format!("{base}/api/docs")).await.unwrap().text().await.unwrap();
+    assert!(body.contains(r#"data-url="/api/openapi.json""#));
+}
+
+#[tokio::test]
+async fn test_embedded_docs_assets_served_locally() {
+    let base = spawn_on(HayaiApp::new().dep(Database).docs_assets(hayai::docs::DocsAssets::Embedded).into_router()).await;
+    let body = reqwest::get(format!("{base}/docs")).await.unwrap().text().await.unwrap();
+    assert!(!body.contains("https://"), "{body}");
+    let version = env!("CARGO_PKG_VERSION");
+    assert!(body.contains(&format!(r#"<script src="/docs/assets/scalar.min.js?v={version}"></script>"#)), "{body}");
+    assert!(body.contains(&format!(r#"href="/docs/assets/scalar.min.css?v={version}""#)), "{body}");
+
+    let client = reqwest::Client::new();
+    let resp = client.get(format!("{base}/docs/assets/scalar.min.css")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/css; charset=utf-8");
+    assert_eq!(resp.headers()["cache-control"], "public, max-age=31536000, immutable");
+
+    // Sent compressed to clients accepting brotli, decompressed to the rest
+    let resp = client.get(format!("{base}/docs/assets/scalar.min.js")).header("accept-encoding", "gzip, br").send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/javascript; charset=utf-8");
+    assert_eq!(resp.headers()["content-encoding"], "br");
+    assert_eq!(resp.headers()["vary"], "accept-encoding");
+    let compressed = resp.bytes().await.unwrap();
+
+    let resp = client.get(format!("{base}/docs/assets/scalar.min.js")).header("accept-encoding", "gzip, br;q=0").send().await.unwrap();
+    assert!(resp.headers().get("content-encoding").is_none());
+    assert_eq!(resp.headers()["cache-control"], "public, max-age=31536000, immutable");
+    let script = resp.bytes().await.unwrap();
+    assert_eq!(&script[..], include_bytes!("../assets/scalar.min.js"));
+    assert!(compressed.len() < script.len() / 3);
+
+    assert_eq!(client.get(format!("{base}/docs/assets/missing.js")).send().await.unwrap().status(), 404);
+
+    // Under the docs prefix
+    let base = spawn_on(HayaiApp::new().dep(Database).docs_prefix("/api").into_router()).await;
+    let body = reqwest::get(format!("{base}/api/docs")).await.unwrap().text().await.unwrap();
+    assert!(body.contains("/api/docs/assets/scalar.min.js"));
+    assert_eq!(reqwest::get(format!("{base}/api/docs/assets/scalar.min.css")).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_cdn_docs_assets() {
+    let app = HayaiApp::new().dep(Database).docs_assets(hayai::docs::DocsAssets::Cdn("https://cdn.example.com/swagger-ui".into()));
+    let base = spawn_on(app.into_router()).await;
+    let body = reqwest::get(format!("{base}/docs")).await.unwrap().text().await.unwrap();
+    assert!(body.contains(r#"<script src="https://cdn.example.com/swagger-ui/swagger-ui-bundle.js">"#), "{body}");
+    assert_eq!(reqwest::get(format!("{base}/docs/assets/scalar.min.js")).await.unwrap().status(), 404);
 }
 
 #[tokio::test]
@@ -223,6 +338,7 @@ async fn test_dep_injection_works() {
 // ---- Query Parameter E2E Tests ----
 
 #[tokio::test]
+#[allow(clippy::len_zero)]
 async fn test_list_users_with_query_params() {
     let base = spawn_app().await;
     let resp = reqwest::get(format!("{base}/users?page=1&limit=10")).await.unwrap();
@@ -279,6 +395,52 @@ async fn test_openapi_status_codes() {
     assert!(get_user["responses"]["200"].is_object(), "GET should default to 200");
 }
 
+#[api_model]
+struct ExportJob {
+    id: String,
+    state: String,
+}
+
+/// Start an export that finishes in the background
+#[post("/exports")]
+#[status(202)]
+async fn start_export() -> ExportJob {
+    ExportJob { id: "exp-1".to_string(), state: "queued".to_string() }
+}
+
+#[tokio::test]
+async fn test_custom_status_code() {
+    let base = spawn_app().await;
+    let resp = reqwest::Client::new().post(format!("{base}/exports")).send().await.unwrap();
+    assert_eq!(resp.status(), 202);
+    let job: Value = resp.json().await.unwrap();
+    assert_eq!(job["state"], "queued");
+
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let responses = &body["paths"]["/exports"]["post"]["responses"];
+    assert_eq!(responses["202"]["description"], "Accepted");
+    assert_eq!(
+        responses["202"]["content"]["application/json"]["schema"],
+        serde_json::json!({ "$ref": "#/components/schemas/ExportJob" }),
+    );
+    assert!(responses.get("201").is_none(), "#[status] replaces the default");
+}
+
+#[tokio::test]
+async fn test_openapi_response_links() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+
+    let links = &body["paths"]["/users"]["post"]["responses"]["201"]["links"];
+    assert_eq!(links["GetUserById"], serde_json::json!({
+        "operationId": "get_user",
+        "parameters": { "id": "$response.body#/id" },
+    }));
+    assert_eq!(links["ListUsers"], serde_json::json!({ "operationId": "list_users", "parameters": {} }));
+    assert!(body["paths"]["/users"]["post"]["responses"]["400"].get("links").is_none());
+    assert!(body["paths"]["/users/{id}"]["get"]["responses"]["200"].get("links").is_none());
+}
+
 // ---- OpenAPI Spec: Tags ----
 
 #[tokio::test]
@@ -326,6 +488,24 @@ async fn test_openapi_query_params() {
     }
 }
 
+#[tokio::test]
+async fn test_openapi_query_param_docs_and_defaults() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/openapi.json")).await.unwrap();
+    let body: Value = resp.json().await.unwrap();
+
+    let params = body["paths"]["/users"]["get"]["parameters"].as_array().unwrap();
+    let page = params.iter().find(|p| p["name"] == "page").unwrap();
+    assert_eq!(page["description"], "Page number (1-based)");
+    assert_eq!(page["schema"]["default"], 1);
+
+    let limit = params.iter().find(|p| p["name"] == "limit").unwrap();
+    assert_eq!(limit["description"], "Number of items per page");
+    assert_eq!(limit["schema"]["default"], 20);
+    assert_eq!(limit["schema"]["minimum"], 1.0);
+    assert_eq!(limit["schema"]["maximum"], 100.0);
+}
+
 // ---- Vec<T> Response E2E ----
 
 #[tokio::test]
@@ -386,7 +566,7 @@ async fn e2e_delete_item(id: i64) -> () {
     let _ = id;
 }
 
-async fn spawn_router_app() -> String {
+fn router_app() -> HayaiApp {
     let items = hayai::HayaiRouter::new("/api/items")
         .tag("items")
         .security("bearer")
@@ -394,12 +574,15 @@ async fn spawn_router_app() -> String {
         .route(__HAYAI_ROUTE_E2E_GET_ITEM)
         .route(__HAYAI_ROUTE_E2E_DELETE_ITEM);
 
-    let app = HayaiApp::new()
+    HayaiApp::new()
         .title("Router Test API")
         .version("0.1.0")
         .bearer_auth()
         .include(items)
-        .into_router();
+}
+
+async fn spawn_router_app() -> String {
+    let app = router_app().into_router();
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
@@ -420,6 +603,7 @@ async fn test_router_e2e_get_prefixed_path() {
 }
 
 #[tokio::test]
+#[allow(clippy::len_zero)]
 async fn test_router_e2e_list_prefixed_path() {
     let base = spawn_router_app().await;
     let resp = reqwest::get(format!("{base}/api/items/e2e-rt-list")).await.unwrap();
@@ -487,6 +671,41 @@ async fn test_router_e2e_nested_routers() {
     assert_eq!(resp.status(), 200);
 }
 
+#[test]
+fn test_route_manifest_lists_prefixed_secured_routes() {
+    let manifest = router_app().default_timeout(std::time::Duration::from_secs(5)).route_manifest();
+    assert_eq!(manifest.manifest_version, hayai::manifest::MANIFEST_VERSION);
+    let routes: Vec<(&str, &str)> = manifest.routes.iter().map(|r| (r.method.as_str(), r.path.as_str())).collect();
+    assert_eq!(routes, [
+        ("DELETE", "/api/items/e2e-rt-del/{id}"),
+        ("GET", "/api/items/e2e-rt-item/{id}"),
+        ("GET", "/api/items/e2e-rt-list"),
+    ]);
+    assert_eq!(serde_json::to_value(&manifest.routes[1]).unwrap(), serde_json::json!({
+        "method": "GET",
+        "path": "/api/items/e2e-rt-item/{id}",
+        "operation_id": "e2e_get_item",
+        "handler": "e2e_get_item",
+        "security": ["bearer"],
+        "tags": ["items"],
+        "timeout_ms": 5000,
+    }));
+}
+
+#[test]
+fn test_route_manifest_round_trips_and_is_byte_stable() {
+    let manifest = router_app().route_manifest();
+    let json = manifest.to_json();
+    assert_eq!(serde_json::from_str::<hayai::manifest::RouteManifest>(&json).unwrap(), manifest);
+    assert_eq!(router_app().route_manifest().to_json(), json);
+
+    let path = std::env::temp_dir().join(format!("hayai-manifest-{}.json", std::process::id()));
+    router_app().write_manifest(&path).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written, json);
+}
+
 // ===== Result<T, ApiError> E2E tests =====
 
 #[tokio::test]
@@ -779,3 +998,2582 @@ async fn test_cross_field_validation_same_date_returns_422() {
     let details = body["details"].as_array().unwrap();
     assert!(details.iter().any(|d| d.as_str().unwrap().contains("end_date must be after start_date")));
 }
+
+// ===== Unknown field rejection =====
+
+#[api_model(deny_unknown_fields)]
+#[derive(Debug, Clone)]
+struct StrictSignup {
+    name: String,
+    email: String,
+}
+
+#[post("/strict-signup")]
+async fn strict_signup(body: StrictSignup) -> StrictSignup {
+    body
+}
+
+#[tokio::test]
+async fn test_deny_unknown_fields_returns_422_naming_field() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/strict-signup"))
+        .json(&serde_json::json!({"name": "Bob", "email": "bob@example.com", "emial": "typo"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    let details = body["details"].as_array().unwrap();
+    assert!(details.iter().any(|d| d.as_str().unwrap().contains("emial")));
+}
+
+#[tokio::test]
+async fn test_deny_unknown_fields_schema_closed() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert_eq!(body["components"]["schemas"]["StrictSignup"]["additionalProperties"], false);
+    assert!(body["components"]["schemas"]["CreateUser"].get("additionalProperties").is_none());
+}
+
+#[tokio::test]
+async fn test_strict_bodies_app_wide() {
+    let app = HayaiApp::new()
+        .dep(Database)
+        .strict_bodies(true)
+        // create_user links to these two
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_CREATE_USER)
+            .route(__HAYAI_ROUTE_GET_USER)
+            .route(__HAYAI_ROUTE_LIST_USERS)
+            .route(__HAYAI_ROUTE_REGISTER))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+    let base = format!("http://{}", addr);
+
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/users"))
+        .json(&serde_json::json!({"name": "Bob", "email": "bob@example.com", "emial": "typo"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"][0], "emial: unknown field");
+
+    let resp = client.post(format!("{base}/users"))
+        .json(&serde_json::json!({"name": "Bob", "email": "bob@example.com"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+
+    // Models that didn't opt in keep ignoring unknown keys
+    let resp = client.post(format!("{base}/registrations"))
+        .json(&serde_json::json!({"credentials": {"username": "bob", "password": "hunter22"}, "team": "core", "tema": "typo"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert_eq!(spec["components"]["schemas"]["CreateUser"]["additionalProperties"], false);
+    assert!(spec["components"]["schemas"]["Registration"].get("additionalProperties").is_none());
+}
+
+// ===== Generated Patch models =====
+
+#[api_model(generate_patch)]
+#[derive(Debug, Clone)]
+struct Profile {
+    #[validate(min_length = 1)]
+    display_name: String,
+    #[validate(email)]
+    email: String,
+    bio: Option<String>,
+}
+
+#[put("/profiles/{id}")]
+async fn update_profile(id: i64, body: ProfilePatch) -> Profile {
+    let mut profile = Profile {
+        display_name: format!("user{}", id),
+        email: "old@example.com".into(),
+        bio: Some("old bio".into()),
+    };
+    body.apply_to(&mut profile);
+    profile
+}
+
+#[test]
+fn test_patch_distinguishes_null_from_absent() {
+    let absent: ProfilePatch = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(absent.bio.is_none());
+    let cleared: ProfilePatch = serde_json::from_value(serde_json::json!({"bio": null})).unwrap();
+    assert_eq!(cleared.bio, Some(None));
+    let set: ProfilePatch = serde_json::from_value(serde_json::json!({"bio": "hi"})).unwrap();
+    assert_eq!(set.bio, Some(Some("hi".to_string())));
+}
+
+#[tokio::test]
+async fn test_patch_applies_only_provided_fields() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.put(format!("{base}/profiles/7"))
+        .json(&serde_json::json!({"email": "new@example.com", "bio": null}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["display_name"], "user7");
+    assert_eq!(body["email"], "new@example.com");
+    assert!(body["bio"].is_null());
+}
+
+#[tokio::test]
+async fn test_patch_validates_present_fields() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.put(format!("{base}/profiles/7"))
+        .json(&serde_json::json!({"email": "not-an-email"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert!(body["details"][0].as_str().unwrap().contains("email"));
+}
+
+#[tokio::test]
+async fn test_patch_schema_registered() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let patch = &body["components"]["schemas"]["ProfilePatch"];
+    assert!(patch.is_object(), "ProfilePatch component should exist");
+    assert!(patch.get("required").is_none(), "nothing is required in a patch");
+    assert_eq!(patch["properties"]["display_name"]["minLength"], 1);
+}
+
+#[patch("/users/{id}/profile")]
+async fn patch_user_profile(id: i64, body: ProfilePatch, db: Dep<Database>) -> Result<Profile, ApiError> {
+    let user = db.get_user(id).await.ok_or_else(|| ApiError::not_found(format!("User {} not found", id)))?;
+    let mut profile = Profile { display_name: user.name, email: user.email, bio: None };
+    body.apply_to(&mut profile);
+    Ok(profile)
+}
+
+#[tokio::test]
+async fn test_patch_route() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.patch(format!("{base}/users/7/profile"))
+        .json(&serde_json::json!({"bio": "hi"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({"display_name": "Alice", "email": "alice@example.com", "bio": "hi"}));
+
+    let resp = client.patch(format!("{base}/users/9999/profile")).json(&serde_json::json!({})).send().await.unwrap();
+    assert_eq!(resp.status(), 404);
+    let resp = client.get(format!("{base}/users/7/profile")).send().await.unwrap();
+    assert_eq!(resp.status(), 405);
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let op = &spec["paths"]["/users/{id}/profile"]["patch"];
+    assert_eq!(op["operationId"], "patch_user_profile");
+    assert_eq!(op["requestBody"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ProfilePatch");
+    assert_eq!(op["responses"]["200"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/Profile");
+}
+
+// ---- Repeated Query Parameters ----
+
+#[derive(hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+struct TagFilter {
+    /// Repeat the key to filter by several tags
+    tag: Vec<String>,
+    /// Comma-separated item ids
+    ids: hayai::query::CommaSeparated<i64>,
+}
+
+#[api_model]
+struct TagFilterEcho {
+    tags: Vec<String>,
+    ids: Vec<i64>,
+}
+
+#[get("/tagged")]
+async fn tagged(filter: Query<TagFilter>) -> TagFilterEcho {
+    let filter = filter.0;
+    TagFilterEcho { tags: filter.tag, ids: filter.ids.into_inner() }
+}
+
+#[tokio::test]
+async fn test_query_repeated_keys_collect_into_vec() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/tagged?tag=a&tag=b&tag=c"))
+        .await.unwrap().json().await.unwrap();
+    assert_eq!(body["tags"], serde_json::json!(["a", "b", "c"]));
+}
+
+#[tokio::test]
+async fn test_query_absent_vec_is_empty() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/tagged")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["tags"], serde_json::json!([]));
+    assert_eq!(body["ids"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_query_comma_separated() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/tagged?ids=1,2&ids=3&tag=x"))
+        .await.unwrap().json().await.unwrap();
+    assert_eq!(body["ids"], serde_json::json!([1, 2, 3]));
+    assert_eq!(body["tags"], serde_json::json!(["x"]));
+
+    let resp = reqwest::get(format!("{base}/tagged?ids=1,nope")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_openapi_array_query_params_style() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let params = body["paths"]["/tagged"]["get"]["parameters"].as_array().unwrap();
+
+    let tag = params.iter().find(|p| p["name"] == "tag").unwrap();
+    assert_eq!(tag["schema"]["type"], "array");
+    assert_eq!(tag["schema"]["items"]["type"], "string");
+    assert_eq!(tag["style"], "form");
+    assert_eq!(tag["explode"], true);
+
+    let ids = params.iter().find(|p| p["name"] == "ids").unwrap();
+    assert_eq!(ids["schema"]["items"]["type"], "integer");
+    assert_eq!(ids["style"], "form");
+    assert_eq!(ids["explode"], false);
+}
+
+// ---- Bodies on DELETE and GET ----
+
+#[api_model]
+struct BulkDelete {
+    #[validate(min_items = 1)]
+    ids: Vec<i64>,
+}
+
+#[api_model]
+struct DeletedCount {
+    deleted: usize,
+}
+
+/// Delete several items at once
+#[delete("/items")]
+async fn bulk_delete_items(body: BulkDelete) -> DeletedCount {
+    DeletedCount { deleted: body.ids.len() }
+}
+
+/// Search with a JSON query document
+#[get("/items/search")]
+#[allow_body]
+async fn search_items(body: BulkDelete) -> DeletedCount {
+    DeletedCount { deleted: body.ids.len() }
+}
+
+#[tokio::test]
+async fn test_delete_with_body() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.delete(format!("{base}/items"))
+        .json(&serde_json::json!({"ids": [1, 2, 3]}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["deleted"], 3);
+
+    let resp = client.delete(format!("{base}/items"))
+        .json(&serde_json::json!({"ids": []}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+}
+
+#[tokio::test]
+async fn test_get_with_allowed_body() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.get(format!("{base}/items/search"))
+        .json(&serde_json::json!({"ids": [4, 5]}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["deleted"], 2);
+}
+
+#[tokio::test]
+async fn test_openapi_delete_request_body() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let delete = &body["paths"]["/items"]["delete"];
+    assert_eq!(
+        delete["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/BulkDelete"
+    );
+    assert!(delete["responses"]["200"].is_object(), "DELETE returning a value defaults to 200");
+    assert!(delete["responses"]["422"].is_object());
+
+    let search = &body["paths"]["/items/search"]["get"];
+    assert!(search["requestBody"].is_object());
+}
+
+// ---- HEAD for GET routes ----
+
+#[tokio::test]
+async fn test_head_on_get_route() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let get = client.get(format!("{base}/users/42")).send().await.unwrap();
+    let get_len = get.bytes().await.unwrap().len();
+
+    let resp = client.head(format!("{base}/users/42")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-length"], get_len.to_string().as_str());
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert!(resp.bytes().await.unwrap().is_empty());
+}
+
+#[get("/e2e-files/{name}")]
+async fn download_file(name: String) -> String {
+    format!("contents of {name}")
+}
+
+#[head("/e2e-files/{name}")]
+async fn probe_file(name: String) -> Result<NoContent, ApiError> {
+    match name.as_str() {
+        "missing" => Err(ApiError::not_found("No such file".to_string())),
+        _ => Ok(NoContent),
+    }
+}
+
+#[tokio::test]
+async fn test_explicit_head_handler_takes_precedence() {
+    for auto_head in [false, true] {
+        let app = HayaiApp::new()
+            .auto_head(auto_head)
+            .include(HayaiRouter::new("").route(__HAYAI_ROUTE_DOWNLOAD_FILE).route(__HAYAI_ROUTE_PROBE_FILE))
+            .into_router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let resp = client.head(format!("http://{addr}/e2e-files/report")).send().await.unwrap();
+        assert_eq!(resp.status(), 204, "auto_head({auto_head})");
+        let resp = client.head(format!("http://{addr}/e2e-files/missing")).send().await.unwrap();
+        assert_eq!(resp.status(), 404, "auto_head({auto_head})");
+        let resp = client.get(format!("http://{addr}/e2e-files/report")).send().await.unwrap();
+        assert_eq!(resp.json::<String>().await.unwrap(), "contents of report");
+    }
+}
+
+#[tokio::test]
+async fn test_head_disabled() {
+    let app = HayaiApp::new().dep(Database).auto_head(false).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = reqwest::Client::new();
+    let resp = client.head(format!("http://{addr}/users/42")).send().await.unwrap();
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "GET");
+    let resp = client.get(format!("http://{addr}/users/42")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let resp = client.head(format!("http://{addr}/users")).send().await.unwrap();
+    assert_eq!(resp.headers()["allow"], "GET, POST");
+    let resp = client.head(format!("http://{addr}/no-such-route")).send().await.unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_openapi_auto_head_documented_only_on_request() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert!(body["paths"]["/users/{id}"].get("head").is_none());
+
+    let app = HayaiApp::new().dep(Database).document_auto_head(true).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let body: Value = reqwest::get(format!("http://{addr}/openapi.json")).await.unwrap().json().await.unwrap();
+    let head = &body["paths"]["/users/{id}"]["head"];
+    assert_eq!(head["operationId"], "get_user_head");
+    assert!(head["responses"]["200"].get("content").is_none());
+    assert!(body["paths"]["/users"].get("head").is_some(), "query params are kept on list routes");
+    assert_eq!(body["paths"]["/users"]["head"]["parameters"], body["paths"]["/users"]["get"]["parameters"]);
+    assert!(body["paths"]["/users"]["post"].is_object());
+}
+
+// ---- Raw String Bodies ----
+
+#[api_model]
+struct WebhookReceipt {
+    length: usize,
+    first_line: String,
+}
+
+/// Receive a plain-text webhook payload
+#[post("/webhooks/text")]
+#[consumes("text/plain")]
+#[status(200)]
+async fn receive_text_webhook(payload: String) -> WebhookReceipt {
+    WebhookReceipt {
+        length: payload.len(),
+        first_line: payload.lines().next().unwrap_or("").to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_plain_text_body() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/webhooks/text"))
+        .header("content-type", "text/plain; charset=utf-8")
+        .body("hello\nworld")
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["length"], 11);
+    assert_eq!(body["first_line"], "hello");
+}
+
+#[tokio::test]
+async fn test_plain_text_body_wrong_content_type() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/webhooks/text"))
+        .json(&serde_json::json!({"text": "hello"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 415);
+}
+
+#[tokio::test]
+async fn test_openapi_plain_text_request_body() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let op = &body["paths"]["/webhooks/text"]["post"];
+    assert_eq!(op["requestBody"]["content"]["text/plain"]["schema"]["type"], "string");
+    assert!(op["requestBody"]["content"].get("application/json").is_none());
+    assert!(!op["responses"]["422"].is_object(), "raw bodies aren't validated");
+    assert!(op["responses"]["415"].is_object());
+}
+
+#[tokio::test]
+async fn test_plain_text_body_over_limit() {
+    let base = spawn_on(HayaiApp::new().dep(Database).body_limit(64).into_router()).await;
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/webhooks/text"))
+        .header("content-type", "text/plain")
+        .body("x".repeat(65))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 413);
+
+    let resp = client.post(format!("{base}/webhooks/text"))
+        .header("content-type", "text/plain")
+        .body("x".repeat(64))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+// ---- Form and Multipart Bodies ----
+
+#[api_model]
+struct ContactForm {
+    #[validate(min_length = 1)]
+    name: String,
+    #[validate(email)]
+    email: String,
+    message: String,
+}
+
+#[api_model]
+struct ContactReceipt {
+    name: String,
+    chars: usize,
+}
+
+/// Submit the contact form
+#[post("/contact")]
+async fn submit_contact(form: Form<ContactForm>) -> ContactReceipt {
+    ContactReceipt { name: form.0.name, chars: form.0.message.len() }
+}
+
+#[api_model]
+struct UploadReceipt {
+    bytes: usize,
+    caption: String,
+}
+
+/// Upload an image with a caption
+#[post("/images")]
+#[multipart_schema(file: binary, caption: string)]
+async fn upload_image(mut form: Multipart) -> Result<UploadReceipt, ApiError> {
+    let mut receipt = UploadReceipt { bytes: 0, caption: String::new() };
+    while let Some(field) = form.next_field().await.map_err(|e| ApiError::bad_request(e.to_string()))? {
+        match field.name() {
+            Some("file") => receipt.bytes = field.bytes().await.map_err(|e| ApiError::bad_request(e.to_string()))?.len(),
+            Some("caption") => receipt.caption = field.text().await.map_err(|e| ApiError::bad_request(e.to_string()))?,
+            _ => {}
+        }
+    }
+    Ok(receipt)
+}
+
+#[tokio::test]
+async fn test_submit_contact_form() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/contact"))
+        .form(&[("name", "Ann"), ("email", "ann@example.com"), ("message", "hello")])
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["name"], "Ann");
+    assert_eq!(body["chars"], 5);
+
+    let resp = client.post(format!("{base}/contact"))
+        .form(&[("name", "Ann"), ("email", "nope"), ("message", "hello")])
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+}
+
+#[tokio::test]
+async fn test_upload_image_multipart() {
+    let base = spawn_app().await;
+    let form = reqwest::multipart::Form::new()
+        .part("file", reqwest::multipart::Part::bytes(vec![0u8; 64]).file_name("a.png"))
+        .text("caption", "a cat");
+    let resp = reqwest::Client::new().post(format!("{base}/images"))
+        .multipart(form)
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["bytes"], 64);
+    assert_eq!(body["caption"], "a cat");
+}
+
+#[tokio::test]
+async fn test_openapi_form_and_multipart_bodies() {
+    let base = spawn_app().await;
+    let body: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+
+    let contact = &body["paths"]["/contact"]["post"]["requestBody"]["content"];
+    assert_eq!(
+        contact["application/x-www-form-urlencoded"]["schema"]["$ref"],
+        "#/components/schemas/ContactForm"
+    );
+
+    let upload = &body["paths"]["/images"]["post"]["requestBody"]["content"]["multipart/form-data"]["schema"];
+    assert_eq!(upload["type"], "object");
+    assert_eq!(upload["properties"]["file"]["format"], "binary");
+    assert_eq!(upload["properties"]["caption"]["type"], "string");
+}
+
+// ---- JSON Response Formatting ----
+
+#[tokio::test]
+async fn test_pretty_json_responses() {
+    let app = HayaiApp::new()
+        .dep(Database)
+        .json_config(hayai::JsonConfig { pretty: true })
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let text = reqwest::get(format!("http://{addr}/users/1")).await.unwrap().text().await.unwrap();
+    assert!(text.contains('\n'), "pretty output spans lines: {text}");
+
+    let base = spawn_app().await;
+    let compact = reqwest::get(format!("{base}/users/1")).await.unwrap().text().await.unwrap();
+    assert!(!compact.contains('\n'));
+}
+
+// ---- Validation Response Shape ----
+
+fn errors_by_field(errors: Vec<hayai::ValidationError>) -> Value {
+    let mut by_field = serde_json::Map::new();
+    for e in errors {
+        by_field.entry(e.field).or_insert_with(|| serde_json::json!([]))
+            .as_array_mut().unwrap()
+            .push(e.code.into());
+    }
+    serde_json::json!({ "errors": by_field })
+}
+
+#[tokio::test]
+async fn test_custom_validation_response_shape() {
+    let app = HayaiApp::new()
+        .dep(Database)
+        .validation_response(axum::http::StatusCode::BAD_REQUEST, errors_by_field)
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("http://{addr}/users"))
+        .json(&serde_json::json!({"name": "", "email": "bob@example.com"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "errors": { "name": ["min_length"] } }));
+
+    let spec: Value = reqwest::get(format!("http://{addr}/openapi.json")).await.unwrap().json().await.unwrap();
+    let responses = &spec["paths"]["/users"]["post"]["responses"];
+    assert!(responses.get("422").is_none());
+    assert_eq!(responses["400"]["content"]["application/json"]["schema"]["type"], "object");
+
+    // The default app keeps the `{error, details}` shape
+    let base = spawn_app().await;
+    let resp = client.post(format!("{base}/users"))
+        .json(&serde_json::json!({"name": "", "email": "bob@example.com"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Validation failed");
+    assert_eq!(body["details"][0], "name: must be at least 1 characters");
+}
+
+#[tokio::test]
+async fn test_custom_validation_response_shapes_unknown_fields() {
+    let app = HayaiApp::new()
+        .dep(Database)
+        .validation_response(axum::http::StatusCode::BAD_REQUEST, errors_by_field)
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let resp = reqwest::Client::new().post(format!("http://{addr}/strict-signup"))
+        .json(&serde_json::json!({"name": "Bob", "email": "bob@example.com", "emial": "typo"}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "errors": { "emial": ["unknown_field"] } }));
+}
+
+// ---- Error Schemas Per Status ----
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct AuthError {
+    error: String,
+    realm: String,
+}
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct ConflictError {
+    error: String,
+    existing_id: i64,
+}
+
+#[post("/e2e-rt-claim/{id}")]
+#[responses(409 = "ConflictError")]
+async fn e2e_claim_item(id: i64) -> Item {
+    Item { id, name: "Widget".into() }
+}
+
+#[tokio::test]
+async fn test_error_schema_per_status() {
+    let items = hayai::HayaiRouter::new("/api/items")
+        .security("bearer")
+        .route(__HAYAI_ROUTE_E2E_GET_ITEM)
+        .route(__HAYAI_ROUTE_E2E_CLAIM_ITEM);
+    let app = HayaiApp::new()
+        .bearer_auth()
+        .error_schema(401, "UnauthorizedError", hayai::openapi::schema_of::<AuthError>())
+        .include(items)
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let spec: Value = reqwest::get(format!("http://{addr}/openapi.json")).await.unwrap().json().await.unwrap();
+    let schema_of = |path: &str, method: &str, status: &str| {
+        spec["paths"][path][method]["responses"][status]["content"]["application/json"]["schema"]["$ref"].clone()
+    };
+    assert_eq!(schema_of("/api/items/e2e-rt-item/{id}", "get", "401"), "#/components/schemas/UnauthorizedError");
+    assert_eq!(schema_of("/api/items/e2e-rt-item/{id}", "get", "500"), "#/components/schemas/ApiError");
+    assert_eq!(schema_of("/api/items/e2e-rt-claim/{id}", "post", "409"), "#/components/schemas/ConflictError");
+    assert_eq!(schema_of("/api/items/e2e-rt-claim/{id}", "post", "401"), "#/components/schemas/UnauthorizedError");
+
+    let components = &spec["components"]["schemas"];
+    assert_eq!(components["UnauthorizedError"]["properties"]["realm"]["type"], "string");
+    assert!(components["ApiError"].is_object());
+    assert!(components["ConflictError"].is_object());
+}
+
+/// Plain schemars types, reachable only through the error component's definitions
+#[derive(hayai::serde::Serialize, hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+#[schemars(crate = "hayai::schemars")]
+struct ThrottledError {
+    error: String,
+    retry: RetryHint,
+    scope: ThrottleScope,
+}
+
+#[derive(hayai::serde::Serialize, hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+#[schemars(crate = "hayai::schemars")]
+struct RetryHint {
+    after_seconds: u32,
+}
+
+#[derive(hayai::serde::Serialize, hayai::serde::Deserialize, hayai::schemars::JsonSchema)]
+#[schemars(crate = "hayai::schemars")]
+enum ThrottleScope {
+    User,
+    Tenant,
+}
+
+fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.push(reference);
+            }
+            map.values().for_each(|v| collect_refs(v, refs));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+        _ => {}
+    }
+}
+
+#[tokio::test]
+async fn test_error_schema_nested_types_resolve() {
+    let app = HayaiApp::new()
+        .error_schema(429, "TooManyRequests", hayai::openapi::schema_of::<ThrottledError>())
+        .include(HayaiRouter::new("/api/items").route(__HAYAI_ROUTE_E2E_CLAIM_ITEM))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let spec: Value = reqwest::get(format!("http://{addr}/openapi.json")).await.unwrap().json().await.unwrap();
+    let components = &spec["components"]["schemas"];
+    assert_eq!(components["TooManyRequests"]["properties"]["retry"]["$ref"], "#/components/schemas/RetryHint");
+    assert_eq!(components["RetryHint"]["properties"]["after_seconds"]["type"], "integer");
+    assert_eq!(components["ThrottleScope"]["enum"], serde_json::json!(["User", "Tenant"]));
+
+    let mut refs = Vec::new();
+    collect_refs(&spec, &mut refs);
+    assert!(!refs.is_empty());
+    for reference in refs {
+        let name = reference.strip_prefix("#/components/schemas/").unwrap_or_else(|| panic!("unexpected $ref {reference}"));
+        assert!(components.get(name).is_some(), "dangling $ref {reference}");
+    }
+}
+
+#[test]
+#[should_panic(expected = "Error component names are taken by models: ConflictError")]
+fn test_error_schema_name_taken_by_model() {
+    let _ = HayaiApp::new()
+        .error_schema(409, "ConflictError", hayai::openapi::schema_of::<AuthError>())
+        .include(HayaiRouter::new("/api/items").route(__HAYAI_ROUTE_E2E_CLAIM_ITEM))
+        .into_router();
+}
+
+#[get("/accounts")]
+async fn admin_list_accounts() -> Vec<Item> {
+    Vec::new()
+}
+
+#[delete("/accounts/{id}")]
+#[responses(401 = "ApiError")]
+async fn admin_delete_account(id: i64) {
+    let _ = id;
+}
+
+#[get("/status")]
+async fn public_status() -> Value {
+    serde_json::json!({ "ok": true })
+}
+
+#[tokio::test]
+async fn test_router_default_responses() {
+    let admin = HayaiRouter::new("/admin")
+        .default_responses([(401, "Token missing or expired"), (403, "Forbidden"), (429, "Too Many Requests")])
+        .route(__HAYAI_ROUTE_ADMIN_LIST_ACCOUNTS)
+        .route(__HAYAI_ROUTE_ADMIN_DELETE_ACCOUNT);
+    let app = HayaiApp::new()
+        .global_responses([(500, "Internal Server Error"), (503, "Down for maintenance")])
+        .include(admin)
+        .include(HayaiRouter::new("/public").route(__HAYAI_ROUTE_PUBLIC_STATUS));
+    let base = spawn_on(app.into_router()).await;
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let responses = |path: &str, method: &str| spec["paths"][path][method]["responses"].clone();
+
+    let list = responses("/admin/accounts", "get");
+    assert_eq!(list["403"]["description"], "Forbidden");
+    assert_eq!(list["403"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ApiError");
+    assert_eq!(list["401"]["description"], "Token missing or expired");
+    assert_eq!(list["429"]["description"], "Too Many Requests");
+    assert_eq!(list["503"]["description"], "Down for maintenance");
+
+    // The route's own declaration wins
+    let delete = responses("/admin/accounts/{id}", "delete");
+    assert_eq!(delete["401"]["description"], "Unauthorized");
+    assert_eq!(delete["403"]["description"], "Forbidden");
+
+    let public = responses("/public/status", "get");
+    assert!(public.get("401").is_none());
+    assert!(public.get("403").is_none());
+    assert_eq!(public["500"]["description"], "Internal Server Error");
+    assert_eq!(public["503"]["description"], "Down for maintenance");
+}
+
+// ---- Health Checks ----
+
+struct HealthyCache;
+
+impl hayai::health::HealthCheck for HealthyCache {
+    async fn health(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+struct FailingQueue {
+    checks_run: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl hayai::health::HealthCheck for FailingQueue {
+    async fn health(&self) -> Result<(), String> {
+        self.checks_run.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Err("broker unreachable".into())
+    }
+}
+
+struct SlowSearch;
+
+impl hayai::health::HealthCheck for SlowSearch {
+    async fn health(&self) -> Result<(), String> {
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        Ok(())
+    }
+}
+
+async fn spawn_on(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_readiness_reports_failing_dependency() {
+    let checks_run = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .dep_with_health(HealthyCache)
+        .dep_with_health(FailingQueue { checks_run: checks_run.clone() })
+        .health_endpoints()
+        .into_router()).await;
+
+    // Liveness never runs the dependency checks
+    let resp = reqwest::get(format!("{base}/healthz")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(checks_run.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    let resp = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["failures"], serde_json::json!(["FailingQueue"]));
+    assert_eq!(body["checks"]["HealthyCache"], "ok");
+    assert_eq!(body["checks"]["FailingQueue"], "broker unreachable");
+    assert_eq!(checks_run.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // Without the failing dependency the app is ready
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .dep_with_health(HealthyCache)
+        .health_endpoints()
+        .into_router()).await;
+    let resp = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "status": "ready", "checks": { "HealthyCache": "ok" } }));
+}
+
+#[tokio::test]
+async fn test_readiness_check_timeout() {
+    let base = spawn_on(HayaiApp::new()
+        .dep_with_health(SlowSearch)
+        .health_endpoints()
+        .health_timeout(std::time::Duration::from_millis(50))
+        .into_router()).await;
+    let resp = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["checks"]["SlowSearch"], "timed out after 50ms");
+}
+
+// ---- Draining ----
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct EventBatch {
+    events: Vec<String>,
+    draining: bool,
+}
+
+#[get("/events/poll")]
+async fn poll_events(draining: Draining) -> EventBatch {
+    tokio::select! {
+        _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => EventBatch { events: vec!["tick".into()], draining: false },
+        _ = draining.started() => EventBatch { events: Vec::new(), draining: draining.is_draining() },
+    }
+}
+
+async fn toggle_drain(base: &str, token: &str) -> reqwest::Response {
+    reqwest::Client::new().post(format!("{base}/_drain")).bearer_auth(token).send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_drain_endpoint_toggles_readiness() {
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .dep_with_health(HealthyCache)
+        .health_endpoints()
+        .drain_endpoint("/_drain", "deploy-secret")
+        .into_router()).await;
+    assert_eq!(reqwest::get(format!("{base}/readyz")).await.unwrap().status(), 200);
+
+    // A long poll in flight returns as soon as draining starts
+    let poll = tokio::spawn(reqwest::get(format!("{base}/events/poll")));
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let resp = toggle_drain(&base, "deploy-secret").await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<Value>().await.unwrap(), serde_json::json!({ "draining": true }));
+    let resp = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    assert_eq!(resp.json::<Value>().await.unwrap()["status"], "draining");
+
+    let batch: Value = poll.await.unwrap().unwrap().json().await.unwrap();
+    assert_eq!(batch, serde_json::json!({ "events": [], "draining": true }));
+
+    // Requests are still served while draining
+    assert_eq!(reqwest::get(format!("{base}/healthz")).await.unwrap().status(), 200);
+    let resp = reqwest::get(format!("{base}/users/1")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = toggle_drain(&base, "deploy-secret").await;
+    assert_eq!(resp.json::<Value>().await.unwrap(), serde_json::json!({ "draining": false }));
+    assert_eq!(reqwest::get(format!("{base}/readyz")).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_drain_requires_token() {
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .health_endpoints()
+        .drain_endpoint("/_drain", "deploy-secret")
+        .into_router()).await;
+
+    assert_eq!(toggle_drain(&base, "deploy-secreT").await.status(), 401);
+    assert_eq!(toggle_drain(&base, "deploy").await.status(), 401);
+    let resp = reqwest::Client::new().post(format!("{base}/_drain")).send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+    assert_eq!(reqwest::get(format!("{base}/readyz")).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_drain_times_out() {
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .health_endpoints()
+        .drain_endpoint("/_drain", "deploy-secret")
+        .drain_timeout(std::time::Duration::from_secs(1))
+        .into_router()).await;
+    assert_eq!(toggle_drain(&base, "deploy-secret").await.status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/readyz")).await.unwrap().status(), 503);
+    tokio::time::sleep(std::time::Duration::from_millis(1200)).await;
+    assert_eq!(reqwest::get(format!("{base}/readyz")).await.unwrap().status(), 200);
+}
+
+// ---- Trusted Proxies ----
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct WhoAmI {
+    ip: String,
+    location: String,
+}
+
+#[get("/whoami")]
+async fn whoami(ip: ClientIp, conn: ConnectionInfo) -> WhoAmI {
+    WhoAmI { ip: ip.0.to_string(), location: conn.absolute_url("/whoami") }
+}
+
+async fn spawn_with_peer(app: HayaiApp) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let service = app.dep(Database).into_router().into_make_service_with_connect_info::<std::net::SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, service).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn whoami_via_proxy(base: &str, forwarded_for: &str) -> Value {
+    reqwest::Client::new().get(format!("{base}/whoami"))
+        .header("x-forwarded-for", forwarded_for)
+        .header("x-forwarded-proto", "https")
+        .header("x-forwarded-host", "api.example.com")
+        .send().await.unwrap()
+        .json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_forwarded_headers_ignored_without_proxy_trust() {
+    let base = spawn_with_peer(HayaiApp::new()).await;
+    let body = whoami_via_proxy(&base, "203.0.113.9").await;
+    assert_eq!(body["ip"], "127.0.0.1");
+    assert_eq!(body["location"], format!("{base}/whoami"));
+}
+
+#[tokio::test]
+async fn test_trusted_proxy_headers_applied() {
+    let base = spawn_with_peer(HayaiApp::new().behind_proxy(hayai::proxy::ProxyConfig::default())).await;
+    // The client spoofed the first entry; the proxy appended the real address
+    let body = whoami_via_proxy(&base, "6.6.6.6, 203.0.113.9").await;
+    assert_eq!(body["ip"], "203.0.113.9");
+    assert_eq!(body["location"], "https://api.example.com/whoami");
+}
+
+#[tokio::test]
+async fn test_headers_missing_trusted_hops_ignored() {
+    let config = hayai::proxy::ProxyConfig { trusted_hops: 2, ..Default::default() };
+    let base = spawn_with_peer(HayaiApp::new().behind_proxy(config)).await;
+    let body = whoami_via_proxy(&base, "203.0.113.9").await;
+    assert_eq!(body["ip"], "127.0.0.1");
+    // Proxies overwrite X-Forwarded-Proto and -Host, so a single value is the real one
+    assert_eq!(body["location"], "https://api.example.com/whoami");
+}
+
+#[tokio::test]
+async fn test_forwarded_proto_and_host_take_the_last_value() {
+    let config = hayai::proxy::ProxyConfig { trusted_hops: 2, ..Default::default() };
+    let base = spawn_with_peer(HayaiApp::new().behind_proxy(config)).await;
+    let body: Value = reqwest::Client::new().get(format!("{base}/whoami"))
+        .header("x-forwarded-for", "6.6.6.6, 203.0.113.9, 10.0.0.2")
+        .header("x-forwarded-proto", "http, https")
+        .header("x-forwarded-host", "evil.example.com, api.example.com")
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(body["ip"], "203.0.113.9");
+    assert_eq!(body["location"], "https://api.example.com/whoami");
+}
+
+#[tokio::test]
+async fn test_forwarded_for_entries_with_ports() {
+    let base = spawn_with_peer(HayaiApp::new().behind_proxy(hayai::proxy::ProxyConfig::default())).await;
+    for (entry, ip) in [("203.0.113.9:5678", "203.0.113.9"), ("[2001:db8::1]:80", "2001:db8::1"), ("[2001:db8::1]", "2001:db8::1"), ("2001:db8::1", "2001:db8::1")] {
+        let body = whoami_via_proxy(&base, &format!("6.6.6.6, {entry}")).await;
+        assert_eq!(body["ip"], ip, "{entry}");
+    }
+}
+
+#[tokio::test]
+async fn test_dynamic_servers_entry_behind_proxy() {
+    let config = hayai::proxy::ProxyConfig { dynamic_servers: true, ..Default::default() };
+    let base = spawn_with_peer(HayaiApp::new().server("http://internal:8000").behind_proxy(config)).await;
+    let spec: Value = reqwest::Client::new().get(format!("{base}/openapi.json"))
+        .header("x-forwarded-proto", "https")
+        .header("x-forwarded-host", "api.example.com")
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(spec["servers"], serde_json::json!([{ "url": "https://api.example.com" }]));
+
+    let base = spawn_with_peer(HayaiApp::new().server("http://internal:8000")).await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert_eq!(spec["servers"], serde_json::json!([{ "url": "http://internal:8000" }]));
+}
+
+// --- Compressed request bodies ---
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+async fn spawn_accepting_gzip(limit: usize) -> String {
+    spawn_on(HayaiApp::new()
+        .dep(Database)
+        .body_limit(limit)
+        .accept_compressed_bodies(&[hayai::decompression::Encoding::Gzip])
+        .into_router()).await
+}
+
+#[tokio::test]
+async fn test_gzip_body_is_decompressed() {
+    let base = spawn_accepting_gzip(64 * 1024).await;
+    let payload = serde_json::json!({ "name": "Zipped", "email": "zipped@example.com" });
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .body(gzip(payload.to_string().as_bytes()))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["name"], "Zipped");
+
+    // Validation still applies to the inflated payload
+    let payload = serde_json::json!({ "name": "", "email": "zipped@example.com" });
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .body(gzip(payload.to_string().as_bytes()))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+}
+
+#[cfg(feature = "brotli")]
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::new();
+    brotli::BrotliCompress(&mut &data[..], &mut compressed, &Default::default()).unwrap();
+    compressed
+}
+
+#[cfg(feature = "brotli")]
+#[tokio::test]
+async fn test_brotli_body_is_decompressed() {
+    use hayai::decompression::Encoding;
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .body_limit(64 * 1024)
+        .accept_compressed_bodies(&[Encoding::Gzip, Encoding::Br])
+        .into_router()).await;
+    let payload = serde_json::json!({ "name": "Squeezed", "email": "squeezed@example.com" });
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "br")
+        .body(brotli(payload.to_string().as_bytes()))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["name"], "Squeezed");
+
+    let bomb = brotli(&vec![b' '; 8 * 1024 * 1024]);
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "br")
+        .body(bomb)
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 413);
+
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "br")
+        .body("not brotli")
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_gzip_bomb_rejected_with_413() {
+    let base = spawn_accepting_gzip(64 * 1024).await;
+    let bomb = gzip(&vec![b' '; 8 * 1024 * 1024]);
+    assert!(bomb.len() < 64 * 1024);
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .body(bomb)
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 413);
+}
+
+#[tokio::test]
+async fn test_unaccepted_encoding_rejected_with_415() {
+    let base = spawn_accepting_gzip(64 * 1024).await;
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .header("content-encoding", "br")
+        .body("irrelevant")
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 415);
+
+    // Without opting in, bodies pass through untouched
+    let base = spawn_app().await;
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .header("content-type", "application/json")
+        .body(r#"{"name":"Plain","email":"plain@example.com"}"#)
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+}
+
+// --- Access log ---
+
+#[derive(Clone, Default)]
+struct CapturedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedLog {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap().lines().map(str::to_string).collect()
+    }
+}
+
+#[tokio::test]
+async fn test_access_log_json_line() {
+    let log = CapturedLog::default();
+    let base = spawn_with_peer(HayaiApp::new()
+        .access_log(hayai::access_log::AccessLogFormat::Json)
+        .access_log_writer(log.clone())).await;
+
+    let resp = reqwest::Client::new().get(format!("{base}/users/7"))
+        .header("x-request-id", "req-123")
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let lines = log.lines();
+    assert_eq!(lines.len(), 1);
+    let record: Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(record["method"], "GET");
+    assert_eq!(record["path"], "/users/7");
+    assert_eq!(record["route"], "/users/{id}");
+    assert_eq!(record["operation_id"], "get_user");
+    assert_eq!(record["status"], 200);
+    assert_eq!(record["request_id"], "req-123");
+    assert_eq!(record["client_ip"], "127.0.0.1");
+    assert!(record["subject"].is_null());
+    assert!(record["latency_ms"].as_f64().unwrap() >= 0.0);
+    assert!(record["timestamp"].as_str().unwrap().ends_with('Z'));
+
+    // Unmatched paths are still logged, without a template; request ids are generated
+    reqwest::get(format!("{base}/nowhere")).await.unwrap();
+    let record: Value = serde_json::from_str(&log.lines()[1]).unwrap();
+    assert_eq!(record["status"], 404);
+    assert!(record["route"].is_null());
+    assert!(!record["request_id"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_access_log_common_format_and_exclusions() {
+    let log = CapturedLog::default();
+    let base = spawn_with_peer(HayaiApp::new()
+        .health_endpoints()
+        .access_log(hayai::access_log::AccessLogFormat::Common)
+        .access_log_exclude(&["/healthz", "/readyz"])
+        .access_log_writer(log.clone())).await;
+
+    assert_eq!(reqwest::get(format!("{base}/healthz")).await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/readyz")).await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/users/1")).await.unwrap().status(), 200);
+
+    let lines = log.lines();
+    assert_eq!(lines.len(), 1, "health checks should not be logged: {lines:?}");
+    assert!(lines[0].starts_with("127.0.0.1 - - ["), "{}", lines[0]);
+    assert!(lines[0].contains("] \"GET /users/1 HTTP/1.1\" 200 "), "{}", lines[0]);
+}
+
+#[tokio::test]
+async fn test_access_log_custom_format() {
+    let log = CapturedLog::default();
+    let base = spawn_with_peer(HayaiApp::new()
+        .access_log(hayai::access_log::AccessLogFormat::Custom(|r| {
+            format!("{} {} -> {}", r.method, r.route.as_deref().unwrap_or("?"), r.status)
+        }))
+        .access_log_writer(log.clone())).await;
+
+    reqwest::get(format!("{base}/users/3")).await.unwrap();
+    assert_eq!(log.lines(), vec!["GET /users/{id} -> 200".to_string()]);
+}
+
+#[api_model]
+struct Credentials {
+    username: String,
+    #[schema(sensitive)]
+    password: String,
+}
+
+#[api_model]
+struct Registration {
+    credentials: Credentials,
+    #[validate(min_length = 2)]
+    team: String,
+}
+
+#[post("/registrations")]
+async fn register(body: Registration) -> String {
+    body.credentials.username
+}
+
+#[tokio::test]
+async fn test_access_log_bodies_on_error_are_redacted() {
+    let log = CapturedLog::default();
+    let base = spawn_with_peer(HayaiApp::new()
+        .log_bodies(hayai::access_log::LogBodies::OnError)
+        .access_log_writer(log.clone())).await;
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("{base}/registrations"))
+        .json(&serde_json::json!({ "credentials": { "username": "al", "password": "hunter2" }, "team": "x" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let resp = client.post(format!("{base}/registrations"))
+        .json(&serde_json::json!({ "credentials": { "username": "alice", "password": "correct horse" }, "team": "ops" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+
+    let lines = log.lines();
+    assert_eq!(lines.len(), 2);
+    assert!(!lines[0].contains("hunter2"), "{}", lines[0]);
+    let record: Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(record["status"], 422);
+    assert_eq!(record["body"], serde_json::json!({
+        "credentials": { "username": "al", "password": "***" },
+        "team": "x",
+    }));
+    let record: Value = serde_json::from_str(&lines[1]).unwrap();
+    assert_eq!(record["status"], 201);
+    assert!(record.get("body").is_none(), "{}", lines[1]);
+}
+
+#[tokio::test]
+async fn test_sensitive_fields_are_write_only() {
+    let base = spawn_app().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let properties = &spec["components"]["schemas"]["Credentials"]["properties"];
+    assert_eq!(properties["password"]["writeOnly"], true);
+    assert!(properties["username"].get("writeOnly").is_none());
+}
+
+// --- Pagination links ---
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct Tag {
+    name: String,
+}
+
+#[api_model]
+struct TagQuery {
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_limit")]
+    limit: i64,
+    sort: Option<String>,
+}
+
+/// List tags, numbered pages
+#[get("/tags")]
+async fn list_tags(query: Query<TagQuery>) -> Page<Tag> {
+    let total = 25;
+    let start = (query.page - 1) * query.limit;
+    let items = (start..(start + query.limit).min(total)).map(|i| Tag { name: format!("tag-{i}") }).collect();
+    Page::new(items, query.page as u32, query.limit as u32, total as u64)
+}
+
+/// List tags by cursor
+#[get("/tag-feed")]
+async fn tag_feed() -> CursorPage<Tag> {
+    CursorPage::new(vec![Tag { name: "a".into() }], Some("b64/next+1=".into()), None)
+}
+
+/// Parse an RFC 5988 `Link` header into `rel` → URL
+fn parse_link_header(header: &str) -> std::collections::HashMap<String, String> {
+    header.split(',')
+        .map(|link| {
+            let mut segments = link.split(';').map(str::trim);
+            let url = segments.next().unwrap().strip_prefix('<').unwrap().strip_suffix('>').unwrap();
+            let rel = segments
+                .find_map(|p| p.strip_prefix("rel="))
+                .expect("link without rel")
+                .trim_matches('"');
+            (rel.to_string(), url.to_string())
+        })
+        .collect()
+}
+
+async fn links_of(resp: reqwest::Response) -> std::collections::HashMap<String, String> {
+    match resp.headers().get("link") {
+        Some(link) => parse_link_header(link.to_str().unwrap()),
+        None => Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_page_link_headers() {
+    let base = spawn_app().await;
+
+    let resp = reqwest::get(format!("{base}/tags?sort=name&page=2&limit=10")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let links = links_of(resp).await;
+    assert_eq!(links.len(), 2);
+    let host = base.strip_prefix("http://").unwrap();
+    assert_eq!(links["next"], format!("http://{host}/tags?sort=name&limit=10&page=3"));
+    assert_eq!(links["prev"], format!("http://{host}/tags?sort=name&limit=10&page=1"));
+
+    let resp = reqwest::get(format!("{base}/tags?limit=10")).await.unwrap();
+    let links = links_of(resp).await;
+    assert_eq!(links.keys().collect::<Vec<_>>(), vec!["next"]);
+
+    let resp = reqwest::get(format!("{base}/tags?page=3&limit=10")).await.unwrap();
+    let links = links_of(resp).await;
+    assert_eq!(links.keys().collect::<Vec<_>>(), vec!["prev"]);
+    let body: Value = reqwest::get(format!("{base}/tags?page=3&limit=10")).await.unwrap().json().await.unwrap();
+    assert_eq!(body["items"].as_array().unwrap().len(), 5);
+    assert_eq!(body["total"], 25);
+}
+
+#[tokio::test]
+async fn test_cursor_page_link_behind_proxy() {
+    let base = spawn_with_peer(HayaiApp::new().behind_proxy(hayai::proxy::ProxyConfig::default())).await;
+    let resp = reqwest::Client::new().get(format!("{base}/tag-feed?q=x"))
+        .header("x-forwarded-proto", "https")
+        .header("x-forwarded-host", "api.example.com")
+        .send().await.unwrap();
+    let links = links_of(resp).await;
+    assert_eq!(links.len(), 1);
+    assert_eq!(links["next"], "https://api.example.com/tag-feed?q=x&cursor=b64%2Fnext%2B1%3D");
+}
+
+#[tokio::test]
+async fn test_page_link_header_documented() {
+    let base = spawn_app().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let ok = &spec["paths"]["/tags"]["get"]["responses"]["200"];
+    assert_eq!(ok["headers"]["Link"]["schema"]["type"], "string");
+    let schema = &ok["content"]["application/json"]["schema"];
+    assert_eq!(schema["properties"]["items"]["items"]["$ref"], "#/components/schemas/Tag");
+    assert_eq!(schema["properties"]["total"]["type"], "integer");
+    assert!(spec["components"]["schemas"]["Tag"].is_object());
+
+    let feed = &spec["paths"]["/tag-feed"]["get"]["responses"]["200"];
+    assert!(feed["headers"]["Link"].is_object());
+    assert!(feed["content"]["application/json"]["schema"]["properties"]["next_cursor"].is_object());
+    assert!(spec["paths"]["/users"]["get"]["responses"]["200"].get("headers").is_none());
+}
+
+// --- Conditional requests ---
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct Document {
+    id: i64,
+    title: String,
+}
+
+/// Every document was last modified at this instant
+const DOCUMENT_UPDATED_AT: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+/// Update a document unless it changed since the client read it
+#[put("/documents/{id}")]
+async fn update_document(id: i64, precondition: Precondition, body: Document) -> Result<Document, ApiError> {
+    let updated_at = hayai::precondition::parse_http_date(DOCUMENT_UPDATED_AT).unwrap();
+    precondition.check_unmodified_since(updated_at)?;
+    Ok(Document { id, title: body.title })
+}
+
+async fn put_document(base: &str, if_unmodified_since: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().put(format!("{base}/documents/1"))
+        .json(&serde_json::json!({ "id": 1, "title": "Renamed" }));
+    if let Some(date) = if_unmodified_since {
+        request = request.header("if-unmodified-since", date);
+    }
+    request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_if_unmodified_since_passes() {
+    let base = spawn_app().await;
+    assert_eq!(put_document(&base, None).await.status(), 200);
+    assert_eq!(put_document(&base, Some(DOCUMENT_UPDATED_AT)).await.status(), 200);
+    assert_eq!(put_document(&base, Some("Thu, 22 Oct 2015 00:00:00 GMT")).await.status(), 200);
+}
+
+#[tokio::test]
+async fn test_if_unmodified_since_before_update_is_412() {
+    let base = spawn_app().await;
+    let resp = put_document(&base, Some("Tue, 20 Oct 2015 07:28:00 GMT")).await;
+    assert_eq!(resp.status(), 412);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Precondition Failed");
+    assert_eq!(body["details"][0], "resource was modified after Tue, 20 Oct 2015 07:28:00 GMT");
+}
+
+#[tokio::test]
+async fn test_malformed_if_unmodified_since_is_400() {
+    let base = spawn_app().await;
+    for garbage in ["yesterday", "Wed, 32 Oct 2015 07:28:00 GMT", "2015-10-21T07:28:00Z"] {
+        let resp = put_document(&base, Some(garbage)).await;
+        assert_eq!(resp.status(), 400, "{garbage}");
+        let body: Value = resp.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("If-Unmodified-Since"));
+    }
+}
+
+#[tokio::test]
+async fn test_precondition_headers_documented() {
+    let base = spawn_app().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let op = &spec["paths"]["/documents/{id}"]["put"];
+    let headers: Vec<&str> = op["parameters"].as_array().unwrap().iter()
+        .filter(|p| p["in"] == "header")
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(headers, vec!["If-Match", "If-None-Match", "If-Unmodified-Since"]);
+    assert!(op["parameters"].as_array().unwrap().iter().all(|p| p["in"] != "header" || p["required"] == false));
+    assert!(op["responses"]["412"].is_object());
+    assert!(spec["paths"]["/users"]["post"]["responses"].get("412").is_none());
+}
+
+// --- Request body examples ---
+
+fn request_example(spec: &Value, path: &str, method: &str) -> Value {
+    spec["paths"][path][method]["requestBody"]["content"]["application/json"]["example"].clone()
+}
+
+#[tokio::test]
+async fn test_request_body_example_synthesized() {
+    let base = spawn_app().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert_eq!(
+        request_example(&spec, "/users", "post"),
+        serde_json::json!({ "name": "string", "email": "jane@example.com" }),
+    );
+    let document = request_example(&spec, "/documents/{id}", "put");
+    assert_eq!(document, serde_json::json!({ "id": 0, "title": "string" }));
+}
+
+#[tokio::test]
+async fn test_custom_example_placeholders() {
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .example_placeholders(|prop| match prop.type_name.as_str() {
+            "string" => serde_json::json!("lorem"),
+            _ => hayai::openapi::default_placeholder(prop),
+        })
+        .into_router()).await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert_eq!(
+        request_example(&spec, "/users", "post"),
+        serde_json::json!({ "name": "lorem", "email": "jane@example.com" }),
+    );
+}
+
+// --- Response header documentation ---
+
+/// Fetch a document with its version tag
+#[get("/documents/{id}")]
+#[response_header("ETag", "string", "Version of the returned document")]
+#[response_header("Last-Modified", "string", "HTTP-date of the last change")]
+async fn get_document(id: i64) -> Document {
+    Document { id, title: "Draft".into() }
+}
+
+#[tokio::test]
+async fn test_response_headers_documented() {
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .global_response_header("X-RateLimit-Remaining", "integer", "Requests left in the window")
+        .into_router()).await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+
+    for (path, method, status) in [("/users", "post", "201"), ("/users/{id}", "get", "200")] {
+        let response = &spec["paths"][path][method]["responses"][status];
+        assert_eq!(response["headers"]["X-RateLimit-Remaining"], serde_json::json!({
+            "description": "Requests left in the window",
+            "schema": { "type": "integer" },
+        }), "{method} {path}");
+        assert!(response["headers"].get("ETag").is_none());
+        assert!(spec["paths"][path][method]["responses"]["400"].get("headers").is_none());
+    }
+
+    let headers = &spec["paths"]["/documents/{id}"]["get"]["responses"]["200"]["headers"];
+    assert_eq!(headers["ETag"]["schema"]["type"], "string");
+    assert_eq!(headers["ETag"]["description"], "Version of the returned document");
+    assert!(headers["Last-Modified"].is_object());
+    assert!(headers["X-RateLimit-Remaining"].is_object());
+    assert!(spec["paths"]["/documents/{id}"]["put"]["responses"]["200"]["headers"].get("ETag").is_none());
+}
+
+// --- Request scope ---
+
+#[derive(Clone)]
+struct Tenant {
+    name: String,
+}
+
+#[derive(Clone)]
+struct BetaFlag;
+
+#[get("/projects")]
+async fn list_projects(tenant: Scoped<Tenant>, beta: Scoped<Option<BetaFlag>>) -> Vec<String> {
+    let mut projects = vec![format!("{}-main", tenant.name)];
+    if beta.is_some() {
+        projects.push(format!("{}-beta", tenant.name));
+    }
+    projects
+}
+
+async fn resolve_tenant(mut req: axum::extract::Request, next: axum::middleware::Next) -> axum::response::Response {
+    let tenant = req.headers().get("x-tenant").and_then(|v| v.to_str().ok()).unwrap_or("acme").to_string();
+    let beta = req.headers().contains_key("x-beta");
+    let mut scope = RequestScope::of(&mut req);
+    scope.insert(Tenant { name: tenant });
+    if beta {
+        scope.insert(BetaFlag);
+    }
+    next.run(req).await
+}
+
+#[tokio::test]
+async fn test_scoped_value_from_middleware() {
+    let base = spawn_on(HayaiApp::new()
+        .dep(Database)
+        .into_router()
+        .layer(axum::middleware::from_fn(resolve_tenant))).await;
+    let client = reqwest::Client::new();
+
+    let projects: Vec<String> = client.get(format!("{base}/projects"))
+        .header("x-tenant", "globex")
+        .send().await.unwrap().json().await.unwrap();
+    assert_eq!(projects, ["globex-main"]);
+
+    let projects: Vec<String> = client.get(format!("{base}/projects"))
+        .header("x-beta", "1")
+        .send().await.unwrap().json().await.unwrap();
+    assert_eq!(projects, ["acme-main", "acme-beta"]);
+
+    let spec: Value = client.get(format!("{base}/openapi.json")).send().await.unwrap().json().await.unwrap();
+    assert!(spec["paths"]["/projects"]["get"].get("parameters").is_none());
+}
+
+#[tokio::test]
+async fn test_missing_scoped_value_is_500() {
+    let base = spawn_on(HayaiApp::new().dep(Database).into_router()).await;
+    let resp = reqwest::get(format!("{base}/projects")).await.unwrap();
+    assert_eq!(resp.status(), 500);
+    let body: Value = resp.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("Tenant"), "{body}");
+}
+
+// --- Untyped JSON responses ---
+
+/// Forward an upstream document as-is
+#[get("/upstream/raw")]
+async fn upstream_raw() -> Value {
+    serde_json::json!({ "source": "upstream", "nested": { "ok": true } })
+}
+
+#[get("/upstream/labels")]
+async fn upstream_labels() -> Result<std::collections::HashMap<String, Value>, ApiError> {
+    Ok([("env".to_string(), Value::from("prod"))].into_iter().collect())
+}
+
+#[get("/upstream/user")]
+#[response(schema = "User")]
+async fn upstream_user() -> Value {
+    serde_json::json!({ "id": 7, "name": "Ada", "email": "ada@example.com" })
+}
+
+#[tokio::test]
+async fn test_untyped_json_responses() {
+    let base = spawn_app().await;
+    let raw: Value = reqwest::get(format!("{base}/upstream/raw")).await.unwrap().json().await.unwrap();
+    assert_eq!(raw["nested"]["ok"], true);
+    let labels: Value = reqwest::get(format!("{base}/upstream/labels")).await.unwrap().json().await.unwrap();
+    assert_eq!(labels, serde_json::json!({ "env": "prod" }));
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert!(!spec.to_string().contains("#/components/schemas/Value"));
+    assert!(spec["components"]["schemas"].get("Value").is_none());
+    let free_form = serde_json::json!({ "type": "object", "additionalProperties": true });
+    for path in ["/upstream/raw", "/upstream/labels"] {
+        assert_eq!(spec["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]["schema"], free_form, "{path}");
+    }
+    assert_eq!(
+        spec["paths"]["/upstream/user"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/User",
+    );
+}
+
+// --- Zero-copy bodies ---
+
+/// An event whose strings borrow from the request body
+#[api_model]
+struct IngestEvent<'a> {
+    #[validate(min_length = 1)]
+    source: &'a str,
+    #[serde(borrow)]
+    message: std::borrow::Cow<'a, str>,
+}
+
+#[api_model]
+struct IngestReceipt {
+    source: String,
+    message: String,
+    borrowed: bool,
+}
+
+#[post("/ingest")]
+#[zero_copy]
+async fn ingest(event: IngestEvent<'_>) -> IngestReceipt {
+    IngestReceipt {
+        source: event.source.to_string(),
+        borrowed: matches!(event.message, std::borrow::Cow::Borrowed(_)),
+        message: event.message.into_owned(),
+    }
+}
+
+#[api_model(deny_unknown_fields)]
+struct StrictIngestEvent<'a> {
+    source: &'a str,
+}
+
+#[post("/ingest-strict")]
+#[zero_copy]
+async fn ingest_strict(event: StrictIngestEvent<'_>) -> IngestReceipt {
+    IngestReceipt { source: event.source.to_string(), message: String::new(), borrowed: true }
+}
+
+#[tokio::test]
+async fn test_zero_copy_unknown_fields_use_custom_validation_response() {
+    let app = HayaiApp::new()
+        .dep(Database)
+        .validation_response(axum::http::StatusCode::BAD_REQUEST, errors_by_field)
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let resp = reqwest::Client::new().post(format!("http://{addr}/ingest-strict"))
+        .json(&serde_json::json!({ "source": "sensor-7", "sorce": "typo" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "errors": { "sorce": ["unknown_field"] } }));
+}
+
+#[tokio::test]
+async fn test_zero_copy_body() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("{base}/ingest"))
+        .json(&serde_json::json!({ "source": "sensor-7", "message": "plain text" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let receipt: Value = resp.json().await.unwrap();
+    assert_eq!(receipt, serde_json::json!({ "source": "sensor-7", "message": "plain text", "borrowed": true }));
+
+    // Escapes can't be borrowed; the Cow field falls back to an owned copy
+    let resp = client.post(format!("{base}/ingest"))
+        .json(&serde_json::json!({ "source": "sensor-7", "message": "line\nbreak \"quoted\" \u{e9}" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let receipt: Value = resp.json().await.unwrap();
+    assert_eq!(receipt["message"], "line\nbreak \"quoted\" \u{e9}");
+    assert_eq!(receipt["borrowed"], false);
+
+    // A plain &str field can't hold an unescaped copy
+    let resp = client.post(format!("{base}/ingest"))
+        .json(&serde_json::json!({ "source": "sensor\t7", "message": "x" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let resp = client.post(format!("{base}/ingest"))
+        .json(&serde_json::json!({ "source": "", "message": "x" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+
+    let resp = client.post(format!("{base}/ingest"))
+        .header("content-type", "text/plain")
+        .body(r#"{"source":"a","message":"b"}"#)
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let spec: Value = client.get(format!("{base}/openapi.json")).send().await.unwrap().json().await.unwrap();
+    assert_eq!(
+        spec["paths"]["/ingest"]["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/IngestEvent",
+    );
+    let schema = &spec["components"]["schemas"]["IngestEvent"];
+    assert_eq!(schema["properties"]["source"]["type"], "string");
+    assert_eq!(schema["properties"]["source"]["minLength"], 1);
+    assert_eq!(schema["properties"]["message"]["type"], "string");
+}
+
+// ── Path parameter constraints ──
+
+#[get("/accounts/{id}")]
+#[param(id, minimum = 1)]
+async fn get_account(id: i64) -> String {
+    format!("account {}", id)
+}
+
+#[get("/orgs/{org}/members/{member}")]
+#[param(org, pattern = "^[a-z-]+$", max_length = 12)]
+#[param(member, minimum = 1, maximum = 1000)]
+async fn get_org_member(org: String, member: u32) -> String {
+    format!("{} #{}", org, member)
+}
+
+#[tokio::test]
+async fn test_path_param_constraints() {
+    let base = spawn_app().await;
+
+    let resp = reqwest::get(format!("{base}/accounts/5")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let resp = reqwest::get(format!("{base}/accounts/-5")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"], serde_json::json!(["id: must be at least 1"]));
+
+    let resp = reqwest::get(format!("{base}/orgs/acme/members/7")).await.unwrap();
+    assert_eq!(resp.json::<String>().await.unwrap(), "acme #7");
+    // Every failing constraint is reported
+    let resp = reqwest::get(format!("{base}/orgs/Acme-Corporation/members/1001")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"], serde_json::json!([
+        "org: must be at most 12 characters",
+        "org: must match pattern ^[a-z-]+$",
+        "member: must be at most 1000",
+    ]));
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let id = &spec["paths"]["/accounts/{id}"]["get"]["parameters"][0];
+    assert_eq!(id["schema"]["type"], "integer");
+    assert_eq!(id["schema"]["minimum"], 1.0);
+    assert!(id["schema"].get("maximum").is_none());
+    let params = &spec["paths"]["/orgs/{org}/members/{member}"]["get"]["parameters"];
+    assert_eq!(params[0]["schema"], serde_json::json!({ "type": "string", "maxLength": 12, "pattern": "^[a-z-]+$" }));
+    assert_eq!(params[1]["schema"], serde_json::json!({ "type": "integer", "minimum": 1.0, "maximum": 1000.0 }));
+}
+
+// ── Async validators ──
+
+#[api_model(async_validate = "email_available")]
+struct Signup {
+    #[validate(email)]
+    email: String,
+}
+
+impl Signup {
+    async fn email_available(&self, deps: &DepResolver<'_>) -> Result<(), Vec<String>> {
+        let db = deps.get::<Database>().map_err(|e| vec![e.error])?;
+        if db.email_taken(&self.email).await {
+            return Err(vec!["email: already registered".to_string()]);
+        }
+        Ok(())
+    }
+}
+
+#[api_model(async_validate = "handle_free")]
+struct ClaimHandle {
+    handle: String,
+}
+
+impl ClaimHandle {
+    async fn handle_free(&self, _deps: &DepResolver<'_>) -> Result<(), ApiError> {
+        if self.handle == "admin" {
+            return Err(ApiError::conflict("Handle admin is taken"));
+        }
+        Ok(())
+    }
+}
+
+#[post("/signups")]
+async fn signup(body: Signup) -> String {
+    body.email
+}
+
+#[post("/handles")]
+async fn claim_handle(body: ClaimHandle) -> String {
+    body.handle
+}
+
+#[tokio::test]
+async fn test_async_validation() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("{base}/signups"))
+        .json(&serde_json::json!({ "email": "bob@example.com" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+
+    let resp = client.post(format!("{base}/signups"))
+        .json(&serde_json::json!({ "email": "alice@example.com" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"], serde_json::json!(["email: already registered"]));
+
+    // Synchronous rules fail first, without reaching the database
+    let resp = client.post(format!("{base}/signups"))
+        .json(&serde_json::json!({ "email": "not-an-email" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"], serde_json::json!(["email: must be a valid email address"]));
+
+    let resp = client.post(format!("{base}/handles"))
+        .json(&serde_json::json!({ "handle": "admin" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 409);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Handle admin is taken");
+}
+
+// ── Response options ──
+
+#[api_model]
+struct Contact {
+    name: String,
+    email: String,
+    phone: Option<String>,
+    address: Option<Address>,
+}
+
+fn sample_contact(id: i64) -> Contact {
+    Contact {
+        name: format!("Contact {}", id),
+        email: "contact@example.com".into(),
+        phone: None,
+        address: Some(Address { city: "Osaka".into(), country: "JP".into() }),
+    }
+}
+
+#[get("/contacts/{id}")]
+#[response_options(exclude_none, sparse_fields)]
+async fn get_contact(id: i64) -> Contact {
+    sample_contact(id)
+}
+
+#[get("/contacts")]
+#[response_options(sparse_fields)]
+async fn list_contacts() -> Vec<Contact> {
+    vec![sample_contact(1), sample_contact(2)]
+}
+
+#[tokio::test]
+async fn test_response_options() {
+    let base = spawn_app().await;
+
+    let body: Value = reqwest::get(format!("{base}/contacts/1")).await.unwrap().json().await.unwrap();
+    assert!(body.get("phone").is_none());
+    assert_eq!(body["address"]["city"], "Osaka");
+
+    let body: Value = reqwest::get(format!("{base}/contacts/1?fields=name,email")).await.unwrap().json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "name": "Contact 1", "email": "contact@example.com" }));
+
+    // Without exclude_none, selected nulls stay
+    let body: Value = reqwest::get(format!("{base}/contacts?fields=name,phone")).await.unwrap().json().await.unwrap();
+    assert_eq!(body, serde_json::json!([
+        { "name": "Contact 1", "phone": null },
+        { "name": "Contact 2", "phone": null },
+    ]));
+
+    let resp = reqwest::get(format!("{base}/contacts/1?fields=name,password")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Unknown fields requested: password");
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let params = spec["paths"]["/contacts"]["get"]["parameters"].as_array().unwrap();
+    assert!(params.iter().any(|p| p["name"] == "fields" && p["in"] == "query" && p["required"] == false));
+}
+
+// ── Collection bodies ──
+
+#[post("/users/bulk")]
+async fn create_users(body: Vec<CreateUser>, db: Dep<Database>) -> Vec<User> {
+    let mut users = Vec::new();
+    for input in &body {
+        users.push(db.create_user(input).await);
+    }
+    users
+}
+
+#[put("/users/by-handle")]
+async fn upsert_users(body: std::collections::HashMap<String, CreateUser>) -> Vec<String> {
+    let mut handles: Vec<String> = body.into_keys().collect();
+    handles.sort();
+    handles
+}
+
+#[tokio::test]
+async fn test_collection_bodies() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("{base}/users/bulk"))
+        .json(&serde_json::json!([
+            { "name": "Ann", "email": "ann@example.com" },
+            { "name": "Ben", "email": "ben@example.com" },
+        ]))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.json::<Value>().await.unwrap().as_array().unwrap().len(), 2);
+
+    let resp = client.post(format!("{base}/users/bulk"))
+        .json(&serde_json::json!([
+            { "name": "Ann", "email": "ann@example.com" },
+            { "name": "Ben", "email": "not-an-email" },
+        ]))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"], serde_json::json!(["[1].email: must be a valid email address"]));
+
+    let resp = client.put(format!("{base}/users/by-handle"))
+        .json(&serde_json::json!({ "ann": { "name": "", "email": "ann@example.com" } }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["details"], serde_json::json!(["[ann].name: must be at least 1 characters"]));
+
+    let spec: Value = client.get(format!("{base}/openapi.json")).send().await.unwrap().json().await.unwrap();
+    assert_eq!(
+        spec["paths"]["/users/bulk"]["post"]["requestBody"]["content"]["application/json"]["schema"],
+        serde_json::json!({ "type": "array", "items": { "$ref": "#/components/schemas/CreateUser" } }),
+    );
+    assert_eq!(
+        spec["paths"]["/users/by-handle"]["put"]["requestBody"]["content"]["application/json"]["schema"],
+        serde_json::json!({ "type": "object", "additionalProperties": { "$ref": "#/components/schemas/CreateUser" } }),
+    );
+}
+
+// ── Raw request parts ──
+
+#[api_model]
+struct SignedDelivery {
+    signature: String,
+    path: String,
+    name: String,
+}
+
+#[post("/deliveries/{source}")]
+async fn receive_delivery(source: String, parts: RequestParts, body: CreateUser) -> Result<SignedDelivery, ApiError> {
+    let signature = parts.headers.get("x-signature")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| ApiError::unauthorized("Missing signature"))?;
+    Ok(SignedDelivery { signature: format!("{source}:{signature}"), path: parts.uri.path().to_string(), name: body.name })
+}
+
+#[tokio::test]
+async fn test_request_parts_with_body() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("{base}/deliveries/github?delivery=1"))
+        .header("X-Signature", "sha256=abc")
+        .json(&serde_json::json!({ "name": "Ann", "email": "ann@example.com" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "signature": "github:sha256=abc", "path": "/deliveries/github", "name": "Ann" }));
+
+    let resp = client.post(format!("{base}/deliveries/github"))
+        .json(&serde_json::json!({ "name": "Ann", "email": "ann@example.com" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+
+    // The body is still validated
+    let resp = client.post(format!("{base}/deliveries/github"))
+        .header("X-Signature", "sha256=abc")
+        .json(&serde_json::json!({ "name": "Ann", "email": "nope" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+
+    let spec: Value = client.get(format!("{base}/openapi.json")).send().await.unwrap().json().await.unwrap();
+    let params = spec["paths"]["/deliveries/{source}"]["post"]["parameters"].as_array().unwrap();
+    assert_eq!(params.len(), 1);
+    assert_eq!(params[0]["name"], "source");
+}
+
+// ── Concurrency limits ──
+
+const REPORT_TIME: std::time::Duration = std::time::Duration::from_millis(300);
+
+#[get("/reports/queued")]
+#[concurrency_limit(1)]
+async fn queued_report() -> Value {
+    tokio::time::sleep(REPORT_TIME).await;
+    serde_json::json!({ "ok": true })
+}
+
+#[get("/reports/fail-fast")]
+#[concurrency_limit(1, fail_fast)]
+async fn fail_fast_report() -> Value {
+    tokio::time::sleep(REPORT_TIME).await;
+    serde_json::json!({ "ok": true })
+}
+
+#[get("/reports/impatient")]
+#[concurrency_limit(1, queue_timeout_ms = 50)]
+async fn impatient_report() -> Value {
+    tokio::time::sleep(REPORT_TIME).await;
+    serde_json::json!({ "ok": true })
+}
+
+#[get("/reports/unlimited")]
+async fn unlimited_report() -> Value {
+    tokio::time::sleep(REPORT_TIME).await;
+    serde_json::json!({ "ok": true })
+}
+
+/// Start a request to `url` and give it time to reach the handler
+async fn start_request(url: String) -> tokio::task::JoinHandle<reqwest::Response> {
+    let first = tokio::spawn(async move { reqwest::get(url).await.unwrap() });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    first
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_queues() {
+    let base = spawn_app().await;
+    let first = start_request(format!("{base}/reports/queued")).await;
+
+    // Other routes are unaffected while the slot is taken
+    let started = std::time::Instant::now();
+    assert_eq!(reqwest::get(format!("{base}/users/1")).await.unwrap().status(), 200);
+    assert!(started.elapsed() < REPORT_TIME / 2);
+
+    let started = std::time::Instant::now();
+    let second = reqwest::get(format!("{base}/reports/queued")).await.unwrap();
+    assert_eq!(second.status(), 200);
+    assert!(started.elapsed() >= REPORT_TIME, "the second request waited for the first");
+    assert_eq!(first.await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_fail_fast() {
+    let base = spawn_app().await;
+    let first = start_request(format!("{base}/reports/fail-fast")).await;
+
+    let resp = reqwest::get(format!("{base}/reports/fail-fast")).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    assert_eq!(resp.headers()["retry-after"], "1");
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Too many concurrent requests to fail_fast_report");
+
+    assert_eq!(first.await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/reports/fail-fast")).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_queue_timeout() {
+    let base = spawn_app().await;
+    let first = start_request(format!("{base}/reports/impatient")).await;
+
+    let started = std::time::Instant::now();
+    let resp = reqwest::get(format!("{base}/reports/impatient")).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    assert!(started.elapsed() < REPORT_TIME);
+    assert_eq!(first.await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_default_concurrency_limit() {
+    let app = HayaiApp::new()
+        .default_concurrency_limit(hayai::concurrency::ConcurrencyLimit::fail_fast(1))
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_UNLIMITED_REPORT)
+            .route(__HAYAI_ROUTE_QUEUED_REPORT));
+    let base = spawn_on(app.into_router()).await;
+
+    let first = start_request(format!("{base}/reports/unlimited")).await;
+    assert_eq!(reqwest::get(format!("{base}/reports/unlimited")).await.unwrap().status(), 503);
+    // Each route counts separately, and its own attribute wins over the default
+    let queued = start_request(format!("{base}/reports/queued")).await;
+    assert_eq!(reqwest::get(format!("{base}/reports/queued")).await.unwrap().status(), 200);
+    assert_eq!(first.await.unwrap().status(), 200);
+    assert_eq!(queued.await.unwrap().status(), 200);
+}
+
+// ── Response caching ──
+
+static CATALOG_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+static RATES_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct CatalogQuery {
+    page: Option<i64>,
+}
+
+#[get("/catalog")]
+#[cache(ttl = "30s", vary = ["query:page", "header:Accept-Language"])]
+async fn cached_catalog(query: Query<CatalogQuery>, request: RequestParts) -> Value {
+    let call = CATALOG_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+    let language = request.headers.get("accept-language").and_then(|v| v.to_str().ok()).unwrap_or("en").to_string();
+    serde_json::json!({ "page": query.page.unwrap_or(1), "language": language, "call": call })
+}
+
+#[get("/rates")]
+#[cache(ttl = "200ms")]
+async fn cached_rates() -> Value {
+    let call = RATES_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+    serde_json::json!({ "call": call })
+}
+
+#[tokio::test]
+async fn test_cache_hit_and_vary() {
+    let base = spawn_on(HayaiApp::new().include(HayaiRouter::new("").route(__HAYAI_ROUTE_CACHED_CATALOG)).into_router()).await;
+    let client = reqwest::Client::new();
+
+    let first = client.get(format!("{base}/catalog?page=2")).send().await.unwrap();
+    assert_eq!(first.headers()["x-cache"], "MISS");
+    let first: Value = first.json().await.unwrap();
+    let second = client.get(format!("{base}/catalog?page=2")).send().await.unwrap();
+    assert_eq!(second.status(), 200);
+    assert_eq!(second.headers()["x-cache"], "HIT");
+    assert_eq!(second.headers()["content-type"], "application/json");
+    assert_eq!(second.json::<Value>().await.unwrap(), first);
+    assert_eq!(CATALOG_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // A different page or language is another entry; unlisted parameters are ignored
+    let other_page = client.get(format!("{base}/catalog?page=3")).send().await.unwrap();
+    assert_eq!(other_page.headers()["x-cache"], "MISS");
+    let german = client.get(format!("{base}/catalog?page=2")).header("Accept-Language", "de").send().await.unwrap();
+    assert_eq!(german.json::<Value>().await.unwrap()["language"], "de");
+    let tracked = client.get(format!("{base}/catalog?page=2&utm_source=mail")).send().await.unwrap();
+    assert_eq!(tracked.headers()["x-cache"], "HIT");
+    assert_eq!(CATALOG_CALLS.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    // Failed requests are neither served from nor stored in the cache
+    let invalid = client.get(format!("{base}/catalog?page=x")).send().await.unwrap();
+    assert_eq!(invalid.status(), 400);
+    assert!(invalid.headers().get("x-cache").is_none());
+}
+
+#[tokio::test]
+async fn test_cache_ttl_expiry() {
+    let base = spawn_on(HayaiApp::new().include(HayaiRouter::new("").route(__HAYAI_ROUTE_CACHED_RATES)).into_router()).await;
+
+    let call = |n: &str| {
+        let url = format!("{base}/rates?n={n}");
+        async move { reqwest::get(url).await.unwrap().json::<Value>().await.unwrap()["call"].clone() }
+    };
+    // Without `vary` the whole query string selects the entry
+    let first = call("1").await;
+    assert_eq!(call("1").await, first);
+    assert_ne!(call("2").await, first);
+
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_ne!(call("1").await, first, "the entry expired");
+}
+
+#[tokio::test]
+async fn test_custom_response_cache() {
+    let cache = std::sync::Arc::new(hayai::cache::MemoryCache::new(1));
+    struct Shared(std::sync::Arc<hayai::cache::MemoryCache>);
+    impl hayai::cache::ResponseCache for Shared {
+        async fn get(&self, key: &str) -> Option<hayai::cache::CachedResponse> {
+            hayai::cache::ResponseCache::get(&*self.0, key).await
+        }
+        async fn put(&self, key: &str, response: hayai::cache::CachedResponse, ttl: std::time::Duration) {
+            hayai::cache::ResponseCache::put(&*self.0, key, response, ttl).await
+        }
+    }
+    let app = HayaiApp::new()
+        .response_cache(Shared(cache.clone()))
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CACHED_RATES));
+    let base = spawn_on(app.into_router()).await;
+
+    reqwest::get(format!("{base}/rates?n=10")).await.unwrap();
+    reqwest::get(format!("{base}/rates?n=11")).await.unwrap();
+    assert_eq!(cache.len(), 1, "the least recently used entry was dropped");
+    let resp = reqwest::get(format!("{base}/rates?n=10")).await.unwrap();
+    assert_eq!(resp.headers()["x-cache"], "MISS");
+}
+
+#[tokio::test]
+async fn test_memory_cache_drops_least_recently_used() {
+    use hayai::cache::{CachedResponse, MemoryCache, ResponseCache};
+    let cache = MemoryCache::new(2);
+    let ttl = std::time::Duration::from_secs(60);
+    let response = |body: &'static str| CachedResponse { status: 200, headers: Vec::new(), body: body.into() };
+    cache.put("a", response("a"), ttl).await;
+    cache.put("b", response("b"), ttl).await;
+    assert!(cache.get("a").await.is_some());
+    cache.put("c", response("c"), ttl).await;
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("b").await.is_none(), "b was used least recently");
+    assert_eq!(cache.get("a").await.unwrap().body, "a");
+
+    // Replacing an entry refreshes it without growing the cache
+    cache.put("c", response("c2"), ttl).await;
+    cache.put("d", response("d"), ttl).await;
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("a").await.is_none());
+    assert_eq!(cache.get("c").await.unwrap().body, "c2");
+}
+
+#[tokio::test]
+#[should_panic(expected = "#[cache] on authenticated routes needs `allow_authenticated`: cached_rates")]
+async fn test_cache_refused_under_secured_router() {
+    let _ = HayaiApp::new()
+        .bearer_auth()
+        .include(HayaiRouter::new("/secure").security("bearer").route(__HAYAI_ROUTE_CACHED_RATES))
+        .into_router();
+}
+
+// ── Response events ──
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct Shipment {
+    #[validate(minimum = 1)]
+    parcels: i64,
+}
+
+#[post("/shipments")]
+#[emit_event("shipment.created", include_body)]
+async fn create_shipment(body: Shipment) -> Result<Value, ApiError> {
+    if body.parcels > 100 {
+        return Err(ApiError::bad_request("Too many parcels".into()));
+    }
+    Ok(serde_json::json!({ "id": 7, "parcels": body.parcels }))
+}
+
+#[delete("/shipments/{id}")]
+#[emit_event("shipment.cancelled")]
+async fn cancel_shipment(id: i64) {
+    let _ = id;
+}
+
+#[get("/shipments/{id}")]
+async fn get_shipment(id: i64) -> Value {
+    serde_json::json!({ "id": id })
+}
+
+#[post("/shipments/{id}/explode")]
+#[emit_event("shipment.exploded")]
+async fn explode_shipment(id: i64) -> Value {
+    serde_json::json!({ "id": id })
+}
+
+#[tokio::test]
+async fn test_on_response_hook() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<hayai::events::ResponseMeta>();
+    let app = HayaiApp::new()
+        .on_response(move |meta, _state| {
+            let (tx, meta) = (tx.clone(), meta.clone());
+            async move {
+                if meta.event == "shipment.exploded" {
+                    panic!("broker exploded");
+                }
+                tx.send(meta).map_err(|e| e.to_string().into())
+            }
+        })
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_CREATE_SHIPMENT)
+            .route(__HAYAI_ROUTE_CANCEL_SHIPMENT)
+            .route(__HAYAI_ROUTE_GET_SHIPMENT)
+            .route(__HAYAI_ROUTE_EXPLODE_SHIPMENT));
+    let base = spawn_on(app.into_router()).await;
+    let client = reqwest::Client::new();
+
+    // A panicking hook doesn't affect the response, nor the hooks of later requests
+    let resp = client.post(format!("{base}/shipments/3/explode")).send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.json::<Value>().await.unwrap()["id"], 3);
+
+    // Failures and unannotated routes emit nothing
+    assert_eq!(client.post(format!("{base}/shipments")).json(&serde_json::json!({ "parcels": 0 })).send().await.unwrap().status(), 422);
+    assert_eq!(client.post(format!("{base}/shipments")).json(&serde_json::json!({ "parcels": 500 })).send().await.unwrap().status(), 400);
+    assert_eq!(client.get(format!("{base}/shipments/7")).send().await.unwrap().status(), 200);
+
+    let resp = client.post(format!("{base}/shipments")).json(&serde_json::json!({ "parcels": 2 })).send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    let sent: Value = resp.json().await.unwrap();
+    let created = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(created.event, "shipment.created");
+    assert_eq!(created.status, 201);
+    assert_eq!(created.operation_id, "create_shipment");
+    assert_eq!(serde_json::from_slice::<Value>(&created.body.unwrap()).unwrap(), sent);
+
+    assert_eq!(client.delete(format!("{base}/shipments/7")).send().await.unwrap().status(), 204);
+    let cancelled = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(cancelled.event, "shipment.cancelled");
+    assert_eq!(cancelled.status, 204);
+    assert!(cancelled.body.is_none(), "the body is only passed with include_body");
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(rx.try_recv().is_err());
+}
+
+// ── Timeouts and operation extensions ──
+
+#[get("/ledgers/{id}")]
+#[timeout("250ms")]
+#[openapi_extension("x-internal-owner", "payments-team")]
+#[openapi_extension("x-retries", 3)]
+#[openapi_extension("x-sla", { "p99_ms": 120, "tier": "gold" })]
+async fn get_ledger(id: i64) -> Value {
+    if id == 0 {
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    serde_json::json!({ "id": id })
+}
+
+#[get("/ledgers")]
+async fn list_ledgers() -> Value {
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    serde_json::json!([])
+}
+
+#[tokio::test]
+async fn test_timeout_and_openapi_extensions() {
+    let app = HayaiApp::new()
+        .default_timeout(std::time::Duration::from_secs(5))
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_GET_LEDGER).route(__HAYAI_ROUTE_LIST_LEDGERS));
+    let base = spawn_on(app.into_router()).await;
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let get = &spec["paths"]["/ledgers/{id}"]["get"];
+    assert_eq!(get["x-timeout-ms"], 250);
+    assert_eq!(get["x-internal-owner"], "payments-team");
+    assert_eq!(get["x-retries"], 3);
+    assert_eq!(get["x-sla"], serde_json::json!({ "p99_ms": 120, "tier": "gold" }));
+    assert_eq!(spec["paths"]["/ledgers"]["get"]["x-timeout-ms"], 5000);
+
+    assert_eq!(reqwest::get(format!("{base}/ledgers/1")).await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/ledgers")).await.unwrap().status(), 200);
+    let started = std::time::Instant::now();
+    let resp = reqwest::get(format!("{base}/ledgers/0")).await.unwrap();
+    assert_eq!(resp.status(), 504);
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    assert_eq!(resp.json::<Value>().await.unwrap()["error"], "Request timed out after 250 ms");
+}
+
+// ── Option<T> responses ──
+
+#[get("/parcels/{id}")]
+async fn find_parcel(id: i64) -> Option<Item> {
+    (id == 1).then(|| Item { id, name: "Parcel".into() })
+}
+
+#[get("/parcels/{id}/label")]
+async fn find_parcel_label(id: i64) -> Result<Option<Value>, ApiError> {
+    if id < 0 {
+        return Err(ApiError::bad_request("Negative parcel id".into()));
+    }
+    Ok((id == 1).then(|| serde_json::json!({ "text": "FRAGILE" })))
+}
+
+#[get("/parcels/{id}/note")]
+#[none_as(204)]
+async fn find_parcel_note(id: i64) -> Option<String> {
+    (id == 1).then(|| "Leave at the door".to_string())
+}
+
+#[tokio::test]
+async fn test_option_responses() {
+    let base = spawn_app().await;
+
+    let resp = reqwest::get(format!("{base}/parcels/1")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<Value>().await.unwrap(), serde_json::json!({ "id": 1, "name": "Parcel" }));
+    let resp = reqwest::get(format!("{base}/parcels/2")).await.unwrap();
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.json::<Value>().await.unwrap()["error"], "Item not found");
+
+    assert_eq!(reqwest::get(format!("{base}/parcels/1/label")).await.unwrap().json::<Value>().await.unwrap()["text"], "FRAGILE");
+    let resp = reqwest::get(format!("{base}/parcels/2/label")).await.unwrap();
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.json::<Value>().await.unwrap()["error"], "Resource not found");
+    assert_eq!(reqwest::get(format!("{base}/parcels/-1/label")).await.unwrap().status(), 400);
+
+    assert_eq!(reqwest::get(format!("{base}/parcels/1/note")).await.unwrap().text().await.unwrap(), "\"Leave at the door\"");
+    let resp = reqwest::get(format!("{base}/parcels/2/note")).await.unwrap();
+    assert_eq!(resp.status(), 204);
+    assert!(resp.text().await.unwrap().is_empty());
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let parcel = &spec["paths"]["/parcels/{id}"]["get"]["responses"];
+    assert!(parcel["200"]["content"]["application/json"]["schema"]["$ref"].as_str().unwrap().ends_with("/Item"));
+    assert_eq!(parcel["404"]["description"], "Not Found");
+    let note = &spec["paths"]["/parcels/{id}/note"]["get"]["responses"];
+    assert_eq!(note["200"]["content"]["application/json"]["schema"]["type"], "string");
+    assert_eq!(note["204"]["description"], "No Content");
+    assert!(note.get("404").is_none());
+}
+
+// ── Parent resources ──
+
+#[derive(Default)]
+struct Kennel {
+    owner_loads: std::sync::atomic::AtomicUsize,
+}
+
+#[api_model]
+struct Owner {
+    id: i64,
+    name: String,
+}
+
+#[api_model]
+struct Pet {
+    id: i64,
+    name: String,
+    owner: String,
+}
+
+#[get("/owners/{owner_id}/pets/{pet_id}")]
+async fn get_pet(owner_id: i64, pet_id: i64, owner: Parent<Owner>, also_owner: Parent<Owner>) -> Pet {
+    assert_eq!(owner.id, owner_id);
+    assert_eq!(also_owner.name, owner.name);
+    Pet { id: pet_id, name: "Rex".to_string(), owner: owner.name.clone() }
+}
+
+async fn spawn_parent_app() -> (String, std::sync::Arc<Kennel>) {
+    let kennel = std::sync::Arc::new(Kennel::default());
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .dep(kennel.clone())
+        .parent_loader("owner_id", |owner_id: i64, deps: DepResolver| async move {
+            let kennel = deps.get::<std::sync::Arc<Kennel>>()?;
+            kennel.owner_loads.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            match owner_id {
+                1 => Ok(Owner { id: 1, name: "Ada".to_string() }),
+                _ => Err(ApiError::not_found(format!("Owner {} not found", owner_id))),
+            }
+        })
+        .include(hayai::HayaiRouter::new("").route(__HAYAI_ROUTE_GET_PET))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), kennel)
+}
+
+#[tokio::test]
+async fn test_parent_loaded_once_per_request() {
+    let (base, kennel) = spawn_parent_app().await;
+    let loads = || kennel.owner_loads.load(std::sync::atomic::Ordering::SeqCst);
+
+    let resp = reqwest::get(format!("{base}/owners/1/pets/7")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<Value>().await.unwrap(), serde_json::json!({ "id": 7, "name": "Rex", "owner": "Ada" }));
+    assert_eq!(loads(), 1);
+
+    let resp = reqwest::get(format!("{base}/owners/2/pets/7")).await.unwrap();
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.json::<Value>().await.unwrap()["error"], "Owner 2 not found");
+    assert_eq!(loads(), 2);
+
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let params: Vec<&str> = spec["paths"]["/owners/{owner_id}/pets/{pet_id}"]["get"]["parameters"].as_array().unwrap()
+        .iter().map(|p| p["name"].as_str().unwrap()).collect();
+    assert_eq!(params, ["owner_id", "pet_id"]);
+}
@@ -0,0 +1,92 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+
+#[put("/users/{id}/avatar")]
+async fn replace_avatar(id: i64) {
+    let _ = id;
+}
+
+#[post("/users/{id}/verify")]
+async fn verify_user(id: i64) -> Result<(), ApiError> {
+    match id {
+        0 => Err(ApiError::not_found("User not found".to_string())),
+        _ => Ok(()),
+    }
+}
+
+#[get("/health/ready")]
+async fn ready() -> NoContent {
+    NoContent
+}
+
+#[post("/jobs")]
+#[status(202)]
+async fn enqueue_job() -> Result<NoContent, ApiError> {
+    Ok(NoContent)
+}
+
+#[delete("/sessions/current")]
+#[status(200)]
+async fn end_session() {}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_REPLACE_AVATAR)
+            .route(__HAYAI_ROUTE_VERIFY_USER)
+            .route(__HAYAI_ROUTE_READY)
+            .route(__HAYAI_ROUTE_ENQUEUE_JOB)
+            .route(__HAYAI_ROUTE_END_SESSION))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_unit_returns_answer_without_a_body() {
+    let base = spawn().await;
+    let client = reqwest::Client::new();
+    let cases = [
+        (client.put(format!("{base}/users/1/avatar")), 204),
+        (client.post(format!("{base}/users/1/verify")), 204),
+        (client.get(format!("{base}/health/ready")), 204),
+        (client.post(format!("{base}/jobs")), 202),
+        (client.delete(format!("{base}/sessions/current")), 200),
+    ];
+    for (request, status) in cases {
+        let res = request.send().await.unwrap();
+        let url = res.url().clone();
+        assert_eq!(res.status(), status, "{url}");
+        assert!(res.headers().get("content-type").is_none(), "{url}");
+        assert_eq!(res.text().await.unwrap(), "", "{url}");
+    }
+
+    // Errors keep their body
+    let res = client.post(format!("{base}/users/0/verify")).send().await.unwrap();
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.json::<Value>().await.unwrap()["error"], "User not found");
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_spec_documents_no_content() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let cases = [
+        ("/users/{id}/avatar", "put", "204"),
+        ("/users/{id}/verify", "post", "204"),
+        ("/health/ready", "get", "204"),
+        ("/jobs", "post", "202"),
+        ("/sessions/current", "delete", "200"),
+    ];
+    for (path, method, status) in cases {
+        let responses = spec["paths"][path][method]["responses"].as_object().unwrap();
+        let success: Vec<&String> = responses.keys().filter(|code| code.starts_with('2')).collect();
+        assert_eq!(success, [status], "{method} {path}");
+        assert!(responses[status].get("content").is_none(), "{method} {path}: {}", responses[status]);
+    }
+    assert!(spec["components"]["schemas"].get("NoContent").is_none());
+}
@@ -0,0 +1,110 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+enum Status {
+    Active,
+    Inactive,
+}
+
+#[api_model(enum_case_insensitive, rename_all = "snake_case")]
+enum Plan {
+    Free,
+    TeamPlus,
+}
+
+#[api_model]
+struct Address {
+    kind: Status,
+}
+
+#[api_model(strict_bodies)]
+struct Account {
+    status: Status,
+    plan: Plan,
+    address: Option<Address>,
+    members: Vec<Member>,
+}
+
+#[api_model]
+struct Member {
+    status: Status,
+}
+
+#[post("/accounts")]
+async fn create_account(body: Account) -> Account {
+    body
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_ACCOUNT)).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn post(base: &str, body: Value) -> (u16, Value) {
+    let res = reqwest::Client::new().post(format!("{base}/accounts")).json(&body).send().await.unwrap();
+    (res.status().as_u16(), res.json().await.unwrap())
+}
+
+fn account(status: &str, plan: &str) -> Value {
+    json!({ "status": status, "plan": plan, "members": [] })
+}
+
+#[tokio::test]
+async fn test_wrong_case_rejected_with_allowed_values() {
+    let base = spawn(HayaiApp::new()).await;
+    let (status, body) = post(&base, account("ACTIVE", "free")).await;
+    assert_eq!(status, 422);
+    assert_eq!(body, json!({ "error": "Validation failed", "details": ["status: must be one of Active, Inactive"] }));
+}
+
+#[tokio::test]
+async fn test_structured_error_carries_code_and_allowed() {
+    // Strict bodies parse from a JSON value first; the error is the same
+    for strict in [false, true] {
+        let app = HayaiApp::new().strict_bodies(strict).validation_response(http::StatusCode::UNPROCESSABLE_ENTITY, |errors| {
+            json!(errors.iter().map(|e| json!({ "field": e.field, "code": e.code, "params": e.params })).collect::<Vec<_>>())
+        });
+        let base = spawn(app).await;
+        let (status, body) = post(&base, account("active", "free")).await;
+        assert_eq!(status, 422);
+        assert_eq!(body, json!([{
+            "field": "status",
+            "code": "invalid_enum_value",
+            "params": [["value", "active"], ["allowed", "Active, Inactive"]],
+        }]));
+    }
+}
+
+#[tokio::test]
+async fn test_case_insensitive_enum_accepts_any_case() {
+    let base = spawn(HayaiApp::new()).await;
+    for plan in ["team_plus", "TEAM_PLUS", "Team_Plus"] {
+        let (status, body) = post(&base, account("Active", plan)).await;
+        assert_eq!(status, 201, "{plan}");
+        // Answered in the canonical spelling
+        assert_eq!(body["plan"], "team_plus");
+    }
+    let (status, body) = post(&base, account("Active", "enterprise")).await;
+    assert_eq!(status, 422);
+    assert_eq!(body["details"], json!(["plan: must be one of free, team_plus"]));
+}
+
+#[tokio::test]
+async fn test_nested_enum_fields_report_their_path() {
+    let base = spawn(HayaiApp::new()).await;
+    let mut body = account("Active", "free");
+    body["address"] = json!({ "kind": "home" });
+    let (status, res) = post(&base, body).await;
+    assert_eq!(status, 422);
+    assert_eq!(res["details"], json!(["address.kind: must be one of Active, Inactive"]));
+
+    let mut body = account("Active", "free");
+    body["members"] = json!([{ "status": "Active" }, { "status": "gone" }]);
+    let (_, res) = post(&base, body).await;
+    assert_eq!(res["details"], json!(["members[1].status: must be one of Active, Inactive"]));
+}
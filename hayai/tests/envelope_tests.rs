@@ -0,0 +1,109 @@
+use hayai::prelude::*;
+use hayai::axum;
+use hayai::envelope::EnvelopeContext;
+use serde_json::{json, Value};
+
+#[api_model]
+struct Order {
+    id: i64,
+    total: f64,
+}
+
+#[get("/orders/{id}")]
+async fn get_order(id: i64) -> Result<Order, ApiError> {
+    if id == 0 {
+        return Err(ApiError::not_found("Order 0 not found".into()));
+    }
+    Ok(Order { id, total: 9.5 })
+}
+
+#[get("/orders")]
+async fn list_orders() -> Vec<Order> {
+    vec![Order { id: 1, total: 9.5 }, Order { id: 2, total: 3.0 }]
+}
+
+#[get("/status")]
+#[no_envelope]
+async fn status() -> Value {
+    json!({ "ok": true })
+}
+
+fn took(ctx: &EnvelopeContext) -> Value {
+    json!({ "route": ctx.route, "status": ctx.status, "took_ms": ctx.elapsed.as_millis() as u64 })
+}
+
+async fn spawn(config: EnvelopeConfig) -> String {
+    let app = HayaiApp::new()
+        .response_envelope(config)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_GET_ORDER).route(__HAYAI_ROUTE_LIST_ORDERS).route(__HAYAI_ROUTE_STATUS))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn get(url: String) -> (u16, Value) {
+    let res = reqwest::get(url).await.unwrap();
+    (res.status().as_u16(), res.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_body_nested_under_data() {
+    let base = spawn(EnvelopeConfig::default()).await;
+
+    let (status, body) = get(format!("{base}/orders/7")).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["data"], json!({ "id": 7, "total": 9.5 }));
+    assert!(!body["meta"]["request_id"].as_str().unwrap().is_empty());
+
+    let (_, body) = get(format!("{base}/orders")).await;
+    assert_eq!(body["data"][1]["id"], 2);
+
+    // The caller's request id is echoed
+    let res = reqwest::Client::new().get(format!("{base}/orders/7")).header("x-request-id", "req-42").send().await.unwrap();
+    assert_eq!(res.json::<Value>().await.unwrap()["meta"], json!({ "request_id": "req-42" }));
+}
+
+#[tokio::test]
+async fn test_meta_fn_and_data_key() {
+    let base = spawn(EnvelopeConfig { data_key: "result".to_string(), meta_fn: took }).await;
+    let res = reqwest::Client::new().get(format!("{base}/orders/7")).header("x-request-id", "req-7").send().await.unwrap();
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["result"]["id"], 7);
+    assert_eq!(body["meta"]["request_id"], "req-7");
+    assert_eq!(body["meta"]["route"], "/orders/{id}");
+    assert_eq!(body["meta"]["status"], 200);
+    assert!(body["meta"]["took_ms"].is_u64());
+}
+
+#[tokio::test]
+async fn test_errors_and_no_envelope_routes_stay_flat() {
+    let base = spawn(EnvelopeConfig::default()).await;
+    assert_eq!(get(format!("{base}/orders/0")).await, (404, json!({ "error": "Order 0 not found" })));
+    assert_eq!(get(format!("{base}/status")).await, (200, json!({ "ok": true })));
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_spec_documents_wrapper() {
+    let base = spawn(EnvelopeConfig::default()).await;
+    let (_, spec) = get(format!("{base}/openapi.json")).await;
+    let response_schema = |path: &str| spec["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]["schema"].clone();
+
+    assert_eq!(response_schema("/orders/{id}"), json!({ "$ref": "#/components/schemas/OrderEnvelope" }));
+    assert_eq!(response_schema("/orders"), json!({ "$ref": "#/components/schemas/OrderListEnvelope" }));
+    assert_eq!(response_schema("/status")["type"], "object");
+    assert!(response_schema("/status").get("properties").is_none());
+
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["OrderEnvelope"]["properties"]["data"], json!({ "$ref": "#/components/schemas/Order" }));
+    assert_eq!(schemas["OrderEnvelope"]["properties"]["meta"], json!({ "$ref": "#/components/schemas/EnvelopeMeta" }));
+    assert_eq!(schemas["OrderListEnvelope"]["properties"]["data"]["items"], json!({ "$ref": "#/components/schemas/Order" }));
+    assert_eq!(schemas["EnvelopeMeta"]["required"], json!(["request_id"]));
+    // Errors keep the plain error schema
+    assert_eq!(
+        spec["paths"]["/orders/{id}"]["get"]["responses"]["404"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/ApiError" }),
+    );
+}
@@ -0,0 +1,108 @@
+use hayai::prelude::*;
+use hayai::axum;
+use hayai::feature_flags::DisabledRoute;
+use serde_json::{json, Value};
+
+#[api_model]
+struct NewInvoice {
+    amount_cents: i64,
+}
+
+#[post("/invoices")]
+#[feature_flag("new_billing")]
+async fn create_invoice(body: NewInvoice, flags: Dep<FeatureFlags>) -> Value {
+    json!({ "amount_cents": body.amount_cents, "pdf": flags.is_enabled("invoice_pdf") })
+}
+
+#[get("/status")]
+async fn status() -> Value {
+    json!({ "ok": true })
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_INVOICE).route(__HAYAI_ROUTE_STATUS))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn create(base: &str, body: &str) -> reqwest::Response {
+    reqwest::Client::new().post(format!("{base}/invoices"))
+        .header("content-type", "application/json")
+        .body(body.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_toggling_flips_route_without_restart() {
+    let flags = InMemoryFlags::new().with("new_billing", true);
+    let base = spawn(HayaiApp::new().feature_flags(flags.clone())).await;
+
+    let res = create(&base, r#"{"amount_cents":100}"#).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "amount_cents": 100, "pdf": false }));
+
+    flags.set("new_billing", false);
+    let res = create(&base, r#"{"amount_cents":100}"#).await;
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.text().await.unwrap(), "");
+    // Other routes are untouched
+    assert_eq!(reqwest::get(format!("{base}/status")).await.unwrap().status(), 200);
+
+    flags.set("new_billing", true);
+    flags.set("invoice_pdf", true);
+    let res = create(&base, r#"{"amount_cents":100}"#).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.json::<Value>().await.unwrap()["pdf"], true);
+}
+
+#[tokio::test]
+async fn test_disabled_route_answers_configured_status() {
+    let flags = InMemoryFlags::new();
+    let app = HayaiApp::new()
+        .feature_flags(flags.clone())
+        .disabled_route(DisabledRoute::Unavailable("Billing is paused".to_string()));
+    let base = spawn(app).await;
+
+    let res = create(&base, r#"{"amount_cents":100}"#).await;
+    assert_eq!(res.status(), 503);
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "error": "Billing is paused" }));
+
+    flags.set("new_billing", true);
+    assert_eq!(create(&base, r#"{"amount_cents":100}"#).await.status(), 201);
+}
+
+#[tokio::test]
+async fn test_rejected_before_extraction() {
+    let base = spawn(HayaiApp::new().feature_flags(InMemoryFlags::new())).await;
+    // A malformed body would be a 400 if the route read it
+    assert_eq!(create(&base, "not json").await.status(), 404);
+}
+
+struct KillSwitches;
+
+impl FlagProvider for KillSwitches {
+    fn is_enabled(&self, name: &str) -> bool {
+        name != "new_billing"
+    }
+}
+
+#[tokio::test]
+async fn test_custom_provider_and_default() {
+    let base = spawn(HayaiApp::new().feature_flags(KillSwitches)).await;
+    assert_eq!(create(&base, r#"{"amount_cents":1}"#).await.status(), 404);
+
+    let flags = InMemoryFlags::new().enabled_by_default();
+    assert!(flags.is_enabled("anything"));
+    flags.set("anything", false);
+    assert!(!flags.is_enabled("anything"));
+}
+
+#[test]
+#[should_panic(expected = "#[feature_flag] routes need `HayaiApp::feature_flags`: new_billing (create_invoice)")]
+fn test_flagged_routes_need_provider() {
+    let _ = HayaiApp::new().include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_INVOICE)).into_router();
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "geo")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+struct Store {
+    name: String,
+    location: LatLng,
+    delivery_area: GeoJsonGeometry,
+    pickup_points: Option<Vec<LatLng>>,
+}
+
+#[post("/stores")]
+async fn create_store(body: Store) -> Store {
+    body
+}
+
+#[post("/geometries/simplify")]
+#[status(200)]
+async fn simplify(body: GeoJsonGeometry) -> GeoJsonGeometry {
+    body
+}
+
+#[post("/locations/snap")]
+#[status(200)]
+async fn snap(body: LatLng) -> LatLng {
+    LatLng::new((body.lat * 100.0).round() / 100.0, (body.lng * 100.0).round() / 100.0)
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_CREATE_STORE)
+            .route(__HAYAI_ROUTE_SIMPLIFY)
+            .route(__HAYAI_ROUTE_SNAP))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn post(base: &str, path: &str, body: &Value) -> reqwest::Response {
+    reqwest::Client::new().post(format!("{base}{path}")).json(body).send().await.unwrap()
+}
+
+fn square() -> Value {
+    json!({
+        "type": "Polygon",
+        "coordinates": [[[13.38, 52.51], [13.42, 52.51], [13.42, 52.53], [13.38, 52.53], [13.38, 52.51]]],
+    })
+}
+
+#[tokio::test]
+async fn test_geo_types_round_trip() {
+    let base = spawn().await;
+    let store = json!({
+        "name": "Mitte",
+        "location": { "lat": 52.52, "lng": 13.405 },
+        "delivery_area": square(),
+        "pickup_points": [{ "lat": 52.5, "lng": 13.4 }],
+    });
+    let resp = post(&base, "/stores", &store).await;
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.json::<Value>().await.unwrap(), store);
+
+    for geometry in [
+        json!({ "type": "Point", "coordinates": [13.405, 52.52, 34.0] }),
+        json!({ "type": "LineString", "coordinates": [[13.38, 52.51], [13.42, 52.53]] }),
+        square(),
+    ] {
+        let resp = post(&base, "/geometries/simplify", &geometry).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.json::<Value>().await.unwrap(), geometry);
+    }
+
+    let snapped: Value = post(&base, "/locations/snap", &json!({ "lat": 52.5212, "lng": 13.4049 })).await.json().await.unwrap();
+    assert_eq!(snapped, json!({ "lat": 52.52, "lng": 13.4 }));
+}
+
+async fn details(resp: reqwest::Response) -> Vec<String> {
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.unwrap();
+    body["details"].as_array().unwrap().iter().map(|d| d.as_str().unwrap().to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_out_of_range_coordinates_are_rejected() {
+    let base = spawn().await;
+    let store = json!({
+        "name": "Nowhere",
+        "location": { "lat": 91.0, "lng": 13.405 },
+        "delivery_area": { "type": "Point", "coordinates": [181.0, 52.52] },
+        "pickup_points": [{ "lat": 52.5, "lng": 13.4 }, { "lat": 52.5, "lng": -200.0 }],
+    });
+    assert_eq!(details(post(&base, "/stores", &store).await).await, [
+        "location.lat: must be between -90 and 90",
+        "delivery_area.coordinates[0]: longitude must be between -180 and 180",
+        "pickup_points[1].lng: must be between -180 and 180",
+    ]);
+
+    let line = json!({ "type": "LineString", "coordinates": [[13.38, -95.0]] });
+    assert_eq!(details(post(&base, "/geometries/simplify", &line).await).await, [
+        "coordinates: must have at least 2 positions",
+        "coordinates[0][1]: latitude must be between -90 and 90",
+    ]);
+    let point = json!({ "type": "Point", "coordinates": [13.38] });
+    assert_eq!(details(post(&base, "/geometries/simplify", &point).await).await, [
+        "coordinates: must be [longitude, latitude] with an optional altitude",
+    ]);
+    assert_eq!(details(post(&base, "/locations/snap", &json!({ "lat": -90.5, "lng": 0.0 })).await).await, [
+        "lat: must be between -90 and 90",
+    ]);
+}
+
+#[tokio::test]
+async fn test_polygon_rings_must_close() {
+    let base = spawn().await;
+    let open = json!({
+        "type": "Polygon",
+        "coordinates": [[[13.38, 52.51], [13.42, 52.51], [13.42, 52.53], [13.38, 52.53]]],
+    });
+    assert_eq!(details(post(&base, "/geometries/simplify", &open).await).await, [
+        "coordinates[0]: must end with its first position",
+    ]);
+    let triangle = json!({ "type": "Polygon", "coordinates": [[[13.38, 52.51], [13.42, 52.51], [13.38, 52.51]]] });
+    assert_eq!(details(post(&base, "/geometries/simplify", &triangle).await).await, [
+        "coordinates[0]: must have at least 4 positions",
+    ]);
+}
+
+#[tokio::test]
+async fn test_geo_schemas_are_documented() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let schemas = &spec["components"]["schemas"];
+
+    assert_eq!(schemas["Store"]["properties"]["location"]["$ref"], "#/components/schemas/LatLng");
+    assert_eq!(schemas["Store"]["properties"]["delivery_area"]["$ref"], "#/components/schemas/GeoJsonGeometry");
+    assert_eq!(schemas["LatLng"]["properties"]["lat"]["minimum"], -90.0);
+    assert_eq!(schemas["LatLng"]["properties"]["lng"]["maximum"], 180.0);
+    assert_eq!(schemas["LatLng"]["required"], json!(["lat", "lng"]));
+
+    let geometry = &schemas["GeoJsonGeometry"];
+    assert_eq!(geometry["oneOf"], json!([
+        { "$ref": "#/components/schemas/GeoJsonPoint" },
+        { "$ref": "#/components/schemas/GeoJsonLineString" },
+        { "$ref": "#/components/schemas/GeoJsonPolygon" },
+    ]));
+    assert_eq!(geometry["discriminator"]["propertyName"], "type");
+    assert_eq!(geometry["discriminator"]["mapping"]["Polygon"], "#/components/schemas/GeoJsonPolygon");
+
+    let point = &schemas["GeoJsonPoint"]["properties"]["coordinates"];
+    assert_eq!((point["type"].clone(), point["minItems"].clone(), point["maxItems"].clone()), (json!("array"), json!(2), json!(3)));
+    assert_eq!(point["items"]["type"], "number");
+    let line = &schemas["GeoJsonLineString"]["properties"]["coordinates"];
+    assert_eq!(line["minItems"], 2);
+    assert_eq!(line["items"]["maxItems"], 3);
+    let polygon = &schemas["GeoJsonPolygon"]["properties"]["coordinates"];
+    assert_eq!(polygon["items"]["minItems"], 4);
+    assert_eq!(polygon["items"]["items"]["items"]["type"], "number");
+
+    let body = &spec["paths"]["/geometries/simplify"]["post"]["requestBody"]["content"]["application/json"];
+    assert_eq!(body["schema"]["$ref"], "#/components/schemas/GeoJsonGeometry");
+    assert_eq!(body["example"]["type"], "Point");
+}
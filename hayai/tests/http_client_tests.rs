@@ -0,0 +1,94 @@
+#![cfg(feature = "http-client")]
+
+use hayai::prelude::*;
+use hayai::{axum, HttpClient, HttpClientConfig, HttpClients};
+use serde_json::Value;
+
+/// Answers every request with its path and the headers a client is expected to send
+async fn spawn_upstream() -> String {
+    let echo = |req: axum::extract::Request| async move {
+        let header = |name: &str| req.headers().get(name).map(|v| v.to_str().unwrap().to_string());
+        axum::Json(serde_json::json!({
+            "path": req.uri().path(),
+            "request_id": header("x-request-id"),
+            "traceparent": header("traceparent"),
+            "api_key": header("x-api-key"),
+        }))
+    };
+    let upstream = axum::Router::new().fallback(echo);
+    serve(upstream).await
+}
+
+async fn serve(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[get("/profile")]
+async fn profile(users: Dep<HttpClient>) -> Result<Value, ApiError> {
+    let resp = users.get("/users/1").send().await.map_err(|e| ApiError::internal(e.to_string()))?;
+    resp.json().await.map_err(|e| ApiError::internal(e.to_string()))
+}
+
+#[get("/invoices")]
+async fn invoices(clients: Dep<HttpClients>) -> Result<Value, ApiError> {
+    let billing = clients.get("billing").ok_or_else(|| ApiError::internal("no billing client".into()))?;
+    let resp = billing.get("invoices").send().await.map_err(|e| ApiError::internal(e.to_string()))?;
+    resp.json().await.map_err(|e| ApiError::internal(e.to_string()))
+}
+
+#[tokio::test]
+async fn test_propagates_request_context() {
+    let upstream = spawn_upstream().await;
+    let mut default_headers = axum::http::HeaderMap::new();
+    default_headers.insert("x-api-key", "secret".parse().unwrap());
+    let app = HayaiApp::new()
+        .http_client(HttpClientConfig { base_url: Some(format!("{upstream}/api/")), default_headers, ..Default::default() })
+        .http_client_named("billing", HttpClientConfig { base_url: Some(format!("{upstream}/billing")), ..Default::default() });
+    let base = serve(app.into_router()).await;
+    let client = reqwest::Client::new();
+
+    let body: Value = client.get(format!("{base}/profile"))
+        .header("X-Request-Id", "req-123")
+        .header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        .send().await.unwrap().json().await.unwrap();
+    assert_eq!(body, serde_json::json!({
+        "path": "/api/users/1",
+        "request_id": "req-123",
+        "traceparent": "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        "api_key": "secret",
+    }));
+
+    // Nothing to propagate when the incoming request has no ids
+    let body: Value = client.get(format!("{base}/invoices")).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "path": "/billing/invoices", "request_id": null, "traceparent": null, "api_key": null }));
+
+    let body: Value = client.get(format!("{base}/invoices"))
+        .header("X-Request-Id", "req-456")
+        .send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["request_id"], "req-456");
+}
+
+#[tokio::test]
+async fn test_outside_a_request_sends_no_context() {
+    let upstream = spawn_upstream().await;
+    let client = HttpClient::new(HttpClientConfig { base_url: Some(upstream), ..Default::default() }).unwrap();
+    let body: Value = client.get("/ping").send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["path"], "/ping");
+    assert_eq!(body["request_id"], Value::Null);
+}
+
+#[test]
+fn test_base_url_joining() {
+    let client = |base: &str| HttpClient::new(HttpClientConfig { base_url: Some(base.into()), ..Default::default() }).unwrap();
+    assert_eq!(client("http://svc/api").url("/users"), "http://svc/api/users");
+    assert_eq!(client("http://svc/api/").url("/users"), "http://svc/api/users");
+    assert_eq!(client("http://svc/api/").url("users?page=2"), "http://svc/api/users?page=2");
+    assert_eq!(client("http://svc/api").url(""), "http://svc/api");
+    assert_eq!(client("http://svc").url("https://other.example/x"), "https://other.example/x");
+
+    let bare = HttpClient::new(HttpClientConfig::default()).unwrap();
+    assert_eq!(bare.url("http://svc/users"), "http://svc/users");
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "validation")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+struct CreateUser {
+    #[validate(min_length = 1, max_length = 8)]
+    name: String,
+}
+
+#[post("/users")]
+async fn create_user(body: CreateUser) -> CreateUser {
+    body
+}
+
+#[get("/users/{id}")]
+async fn get_user(id: i64) -> Result<CreateUser, ApiError> {
+    Err(ApiError::not_found(format!("User {} not found", id)).with_code("user_not_found").with_param("id", id))
+}
+
+fn catalog() -> MessageCatalog {
+    MessageCatalog::new()
+        .message("fr", "validation_failed", "La validation a échoué")
+        .message("fr", "min_length", "doit contenir au moins {min} caractère(s)")
+        .message("ja", "validation_failed", "検証に失敗しました")
+        .message("ja", "min_length", "{field}は{min}文字以上で入力してください")
+        .message("en", "user_not_found", "No user with id {id}")
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_USER).route(__HAYAI_ROUTE_GET_USER))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn create(base: &str, name: &str, accept_language: Option<&str>) -> (u16, Value) {
+    let mut req = reqwest::Client::new().post(format!("{base}/users")).json(&json!({ "name": name }));
+    if let Some(language) = accept_language {
+        req = req.header("accept-language", language);
+    }
+    let res = req.send().await.unwrap();
+    (res.status().as_u16(), res.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_validation_error_follows_accept_language() {
+    let base = spawn(HayaiApp::new().message_catalog(catalog())).await;
+
+    let (status, body) = create(&base, "", Some("fr-CH, fr;q=0.9, en;q=0.8")).await;
+    assert_eq!(status, 422);
+    assert_eq!(body, json!({ "error": "La validation a échoué", "details": ["name: doit contenir au moins 1 caractère(s)"] }));
+
+    let (_, body) = create(&base, "", Some("ja")).await;
+    assert_eq!(body["details"], json!(["name: nameは1文字以上で入力してください"]));
+
+    for accept_language in [None, Some("en-US"), Some("de, *;q=0.1")] {
+        let (status, body) = create(&base, "", accept_language).await;
+        assert_eq!(status, 422);
+        assert_eq!(body, json!({ "error": "Validation failed", "details": ["name: must be at least 1 characters"] }), "{accept_language:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_missing_keys_fall_back_to_english() {
+    let base = spawn(HayaiApp::new().message_catalog(catalog())).await;
+
+    // No French `max_length`: the built-in English message
+    let (_, body) = create(&base, "Bartholomew", Some("fr")).await;
+    assert_eq!(body, json!({ "error": "La validation a échoué", "details": ["name: must be at most 8 characters"] }));
+
+    // No French `user_not_found`: the catalog's English template
+    let res = reqwest::Client::new().get(format!("{base}/users/7")).header("accept-language", "fr").send().await.unwrap();
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "error": "No user with id 7" }));
+}
+
+#[tokio::test]
+async fn test_default_locale_and_shaped_bodies() {
+    let app = HayaiApp::new()
+        .message_catalog(catalog().default_locale("fr"))
+        .validation_response(http::StatusCode::BAD_REQUEST, |errors| {
+            json!({ "problems": errors.iter().map(|e| json!({ "field": e.field, "message": e.message })).collect::<Vec<_>>() })
+        });
+    let base = spawn(app).await;
+
+    let (status, body) = create(&base, "", None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body, json!({ "problems": [{ "field": "name", "message": "doit contenir au moins 1 caractère(s)" }] }));
+
+    let (_, body) = create(&base, "", Some("ja;q=0.5, fr;q=0.2")).await;
+    assert_eq!(body["problems"][0]["message"], "nameは1文字以上で入力してください");
+}
+
+#[tokio::test]
+async fn test_errors_unchanged_without_catalog() {
+    let base = spawn(HayaiApp::new()).await;
+    let (_, body) = create(&base, "", Some("fr")).await;
+    assert_eq!(body, json!({ "error": "Validation failed", "details": ["name: must be at least 1 characters"] }));
+}
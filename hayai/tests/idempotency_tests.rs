@@ -0,0 +1,38 @@
+use hayai::cache::CachedResponse;
+use hayai::idempotency::{Claim, IdempotencyError, IdempotencyStore, MemoryIdempotencyStore};
+use hayai::axum::http::StatusCode;
+use hayai::prelude::*;
+use std::time::Duration;
+
+fn response(body: &'static str) -> CachedResponse {
+    CachedResponse { status: 201, headers: vec![], body: body.into() }
+}
+
+#[tokio::test]
+async fn test_memory_store_claims_completes_and_releases() {
+    let store = MemoryIdempotencyStore::new();
+    let ttl = Duration::from_secs(60);
+
+    assert!(matches!(store.claim("k", ttl).await, Ok(Claim::New)));
+    assert!(matches!(store.claim("k", ttl).await, Ok(Claim::InProgress)));
+    store.release("k").await.unwrap();
+    assert!(matches!(store.claim("k", ttl).await, Ok(Claim::New)));
+
+    store.complete("k", response("order 1"), ttl).await.unwrap();
+    let Ok(Claim::Completed(replayed)) = store.claim("k", ttl).await else { panic!("expected the stored response") };
+    assert_eq!((replayed.status, replayed.body.as_ref()), (201, b"order 1".as_slice()));
+}
+
+#[tokio::test]
+async fn test_memory_store_keys_expire() {
+    let store = MemoryIdempotencyStore::new();
+    store.complete("k", response("order 1"), Duration::from_millis(50)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert!(matches!(store.claim("k", Duration::from_secs(60)).await, Ok(Claim::New)));
+}
+
+#[test]
+fn test_store_error_is_service_unavailable() {
+    let error: ApiError = IdempotencyError { message: "connection refused".to_string() }.into();
+    assert_eq!(error.status, StatusCode::SERVICE_UNAVAILABLE);
+}
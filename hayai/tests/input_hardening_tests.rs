@@ -0,0 +1,123 @@
+//! Malformed and oversized path and query input must be rejected with a 4xx JSON error,
+//! never a panic or a dropped connection.
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+
+static PANICS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_panics() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            // Failed assertions in this file are not server panics
+            if info.location().is_some_and(|l| !l.file().ends_with("input_hardening_tests.rs")) {
+                PANICS.fetch_add(1, Ordering::SeqCst);
+            }
+            default(info);
+        }));
+    });
+}
+
+#[api_model]
+struct Search {
+    q: Option<String>,
+    page: Option<u32>,
+    tags: Vec<String>,
+}
+
+#[get("/items/{id}")]
+async fn get_item(id: u64) -> String {
+    id.to_string()
+}
+
+#[get("/files/{name}")]
+async fn get_file(name: String) -> String {
+    name
+}
+
+#[get("/search")]
+async fn search(query: Query<Search>) -> String {
+    format!("{:?} {:?} {}", query.q, query.page, query.tags.len())
+}
+
+async fn spawn_app(app: HayaiApp) -> String {
+    count_panics();
+    let app = app.into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// Status and JSON error message, asserting the response is a 4xx JSON body
+async fn rejected(url: String) -> (u16, String) {
+    let resp = reqwest::get(&url).await.unwrap_or_else(|e| panic!("{url}: {e}"));
+    let status = resp.status().as_u16();
+    assert!((400..500).contains(&status), "{url}: status {status}");
+    let body: Value = resp.json().await.unwrap_or_else(|e| panic!("{url}: not JSON: {e}"));
+    (status, body["error"].as_str().unwrap_or_default().to_string())
+}
+
+#[tokio::test]
+async fn test_invalid_utf8_in_path() {
+    let base = spawn_app(HayaiApp::new()).await;
+    for segment in ["%FF", "%C3%28", "%E2%82", "abc%F0%9F%98", "%ED%A0%80"] {
+        let (status, error) = rejected(format!("{base}/files/{segment}")).await;
+        assert_eq!(status, 400);
+        assert!(error.contains("`name`"), "{segment}: {error}");
+    }
+    let (status, error) = rejected(format!("{base}/items/%FF")).await;
+    assert_eq!(status, 400);
+    assert!(error.contains("`id`"), "{error}");
+    assert_eq!(PANICS.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_unparseable_path_values() {
+    let base = spawn_app(HayaiApp::new()).await;
+    let long = "9".repeat(50 * 1024);
+    for value in ["-1", "abc", "18446744073709551616", long.as_str()] {
+        let (status, error) = rejected(format!("{base}/items/{value}")).await;
+        assert_eq!(status, 400);
+        assert!(error.contains("`id`"), "{value}: {error}");
+    }
+    assert_eq!(PANICS.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_malformed_query_values() {
+    let base = spawn_app(HayaiApp::new()).await;
+    for query in ["page=%FF", "page=abc", "page=1&page=2", "page=abc&page=1", "page=-1", "page=99999999999"] {
+        let (status, error) = rejected(format!("{base}/search?{query}")).await;
+        assert_eq!(status, 400);
+        assert!(error.contains("page"), "{query}: {error}");
+    }
+    // Lossy but valid: invalid UTF-8 in a string field is replaced, not a failure
+    let resp = reqwest::get(format!("{base}/search?q=%FF&tags=a&tags=%C3%28&page=2&page=2")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(PANICS.load(Ordering::SeqCst), 0);
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_query_length_limit() {
+    let base = spawn_app(HayaiApp::new().max_query_length(1024)).await;
+    let resp = reqwest::get(format!("{base}/search?q={}", "a".repeat(1000))).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let (status, error) = rejected(format!("{base}/search?q={}", "a".repeat(2000))).await;
+    assert_eq!(status, 414);
+    assert_eq!(error, "Query string longer than 1024 bytes");
+
+    // The default limit stops 50KB query strings
+    let base = spawn_app(HayaiApp::new()).await;
+    let (status, _) = rejected(format!("{base}/search?q={}", "a".repeat(50 * 1024))).await;
+    assert_eq!(status, 414);
+    assert_eq!(PANICS.load(Ordering::SeqCst), 0);
+}
@@ -1,3 +1,5 @@
+#![cfg(all(feature = "openapi", feature = "validation", feature = "auth"))]
+
 use hayai::prelude::*;
 use hayai::openapi;
 use std::collections::HashMap;
@@ -77,6 +79,91 @@ fn test_validation_multiple_errors() {
     assert_eq!(err.len(), 2);
 }
 
+#[api_model]
+struct IntranetContact {
+    #[validate(email(allow_no_tld))]
+    email: String,
+}
+
+#[cfg(feature = "strict-email")]
+#[api_model]
+struct StrictContact {
+    #[validate(email(strict))]
+    email: String,
+}
+
+/// Accepted by every mode
+const VALID_EMAILS: &[&str] = &[
+    "alice@example.com",
+    "first.last@example.com",
+    "user+tag@example.co.uk",
+    "o'brien@example.ie",
+    "\"john doe\"@example.com",
+    "\"a@b\"@example.com",
+    "user@xn--bcher-kva.example",
+    "user@bücher.example",
+    "user@sub-domain.example.com",
+];
+
+/// Rejected by every mode
+const INVALID_EMAILS: &[&str] = &[
+    "",
+    "notanemail",
+    "@example.com",
+    "user@",
+    "user@@example.com",
+    "a@b..com",
+    ".user@example.com",
+    "user.@example.com",
+    "us..er@example.com",
+    "user@-example.com",
+    "user@example-.com",
+    "user@.example.com",
+    "user@example.com.",
+    "user name@example.com",
+    "\"unterminated@example.com",
+    "Alice <alice@example.com>",
+];
+
+#[test]
+fn test_email_default_mode() {
+    let options = hayai::email::EmailOptions::default();
+    for email in VALID_EMAILS {
+        assert!(hayai::email::is_valid(email, options), "should accept {email}");
+        let user = CreateTestUser { name: "Alice".into(), email: email.to_string() };
+        assert!(user.validate().is_ok(), "should accept {email}");
+    }
+    for email in INVALID_EMAILS.iter().chain(&["user@localhost"]) {
+        assert!(!hayai::email::is_valid(email, options), "should reject {email}");
+    }
+}
+
+#[test]
+fn test_email_allow_no_tld_mode() {
+    for email in VALID_EMAILS.iter().chain(&["user@localhost", "ops@intranet"]) {
+        let contact = IntranetContact { email: email.to_string() };
+        assert!(contact.validate().is_ok(), "should accept {email}");
+    }
+    for email in INVALID_EMAILS {
+        let contact = IntranetContact { email: email.to_string() };
+        let errors = contact.validate_fields().unwrap_err();
+        assert_eq!(errors, vec![hayai::ValidationError::new("email", "email", "must be a valid email address")], "{email}");
+    }
+}
+
+#[cfg(feature = "strict-email")]
+#[test]
+fn test_email_strict_mode() {
+    for email in VALID_EMAILS.iter().chain(&["user@[192.168.0.1]"]) {
+        let contact = StrictContact { email: email.to_string() };
+        assert!(contact.validate().is_ok(), "should accept {email}");
+    }
+    for email in INVALID_EMAILS.iter().chain(&["user@localhost"]) {
+        let contact = StrictContact { email: email.to_string() };
+        assert!(contact.validate().is_err(), "should reject {email}");
+    }
+}
+
 // ---- Schema Tests ----
 
 #[test]
@@ -156,6 +243,102 @@ fn test_option_not_required() {
     assert!(schema.required.contains(&"tags".to_string()), "Non-Option field should be required");
 }
 
+#[api_model]
+#[derive(Debug, Clone)]
+struct ContractOverrides {
+    #[schema(required)]
+    deleted_at: Option<String>,
+    #[schema(non_nullable)]
+    created_by: Option<String>,
+    note: Option<String>,
+}
+
+#[test]
+fn test_schema_required_keeps_option_nullable() {
+    let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
+    let info = schemas.iter().find(|s| s.name == "ContractOverrides").unwrap();
+    let schema = (info.schema_fn)();
+    assert!(schema.required.contains(&"deleted_at".to_string()));
+    assert!(schema.properties["deleted_at"].nullable);
+    let json = schema.to_json_value();
+    assert_eq!(json["properties"]["deleted_at"], serde_json::json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] }));
+}
+
+#[test]
+fn test_schema_non_nullable_drops_null_branch() {
+    let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
+    let info = schemas.iter().find(|s| s.name == "ContractOverrides").unwrap();
+    let schema = (info.schema_fn)();
+    assert!(!schema.required.contains(&"created_by".to_string()));
+    let json = schema.to_json_value();
+    assert!(json["properties"]["created_by"].get("anyOf").is_none());
+    assert_eq!(json["properties"]["created_by"]["type"], "string");
+}
+
+#[test]
+fn test_schema_option_defaults_untouched() {
+    let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
+    let info = schemas.iter().find(|s| s.name == "ContractOverrides").unwrap();
+    let schema = (info.schema_fn)();
+    assert!(!schema.required.contains(&"note".to_string()));
+    assert!(schema.properties["note"].nullable);
+}
+
+#[api_model(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+struct CamelCaseModel {
+    #[validate(min_length = 1)]
+    first_name: String,
+    #[serde(rename = "ID")]
+    user_id: i64,
+}
+
+#[api_model(rename_all = "snake_case")]
+#[derive(Debug, Clone)]
+enum CamelCaseLevel {
+    VeryHigh,
+    Low,
+}
+
+#[test]
+fn test_rename_all_camel_case_wire_format() {
+    let model = CamelCaseModel { first_name: "Ann".into(), user_id: 7 };
+    let json = serde_json::to_value(&model).unwrap();
+    assert_eq!(json, serde_json::json!({"firstName": "Ann", "ID": 7}));
+    let err = CamelCaseModel { first_name: "".into(), user_id: 1 }.validate().unwrap_err();
+    assert!(err[0].starts_with("firstName:"), "validation errors use the wire name: {:?}", err);
+}
+
+#[test]
+fn test_rename_all_schema_keeps_patches() {
+    let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
+    let info = schemas.iter().find(|s| s.name == "CamelCaseModel").unwrap();
+    let schema = (info.schema_fn)();
+    assert_eq!(schema.properties["firstName"].min_length, Some(1));
+    assert!(schema.properties.contains_key("ID"));
+    assert!(!schema.properties.contains_key("first_name"));
+
+    let level = schemas.iter().find(|s| s.name == "CamelCaseLevel").unwrap();
+    let values = (level.schema_fn)().enum_values.unwrap();
+    assert_eq!(values, vec!["very_high".to_string(), "low".to_string()]);
+}
+
+#[test]
+fn test_casing_violations_name_struct_and_field() {
+    let violations = hayai::HayaiApp::casing_violations(hayai::Casing::Camel);
+    assert!(violations.contains(&"ContractOverrides.deleted_at".to_string()));
+    assert!(!violations.iter().any(|v| v.starts_with("CamelCaseModel.firstName")));
+    assert!(hayai::Casing::Snake.matches("deleted_at"));
+    assert!(hayai::Casing::Kebab.matches("deleted-at"));
+    assert!(!hayai::Casing::Camel.matches("FirstName"));
+}
+
+#[test]
+#[should_panic(expected = "ContractOverrides.deleted_at")]
+fn test_enforce_casing_fails_into_router() {
+    let _ = hayai::HayaiApp::new().enforce_casing(hayai::Casing::Camel).into_router();
+}
+
 #[test]
 fn test_nested_definitions_collected() {
     let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
@@ -179,7 +362,188 @@ fn test_nested_json_serialization() {
     assert_eq!(tags["type"], "array");
     assert_eq!(tags["items"]["type"], "string");
     let nick = &json["properties"]["nickname"];
-    assert!(nick.get("anyOf").is_some());
+    assert_eq!(*nick, serde_json::json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] }));
+}
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct NullableContainers {
+    labels: Option<HashMap<String, String>>,
+    addresses: Option<HashMap<String, Address>>,
+    aliases: Option<Vec<String>>,
+    scores: Vec<Option<i32>>,
+    stops: Vec<Option<Address>>,
+    #[serde(default)]
+    notes: Option<Vec<String>>,
+    name: String,
+}
+
+fn nullable_containers_json() -> serde_json::Value {
+    let info = inventory::iter::<hayai::SchemaInfo>().find(|s| s.name == "NullableContainers").unwrap();
+    (info.schema_fn)().to_json_value()
+}
+
+#[test]
+fn test_nullable_map_keeps_additional_properties() {
+    let json = nullable_containers_json();
+    assert_eq!(json["properties"]["labels"], serde_json::json!({
+        "anyOf": [{ "type": "object", "additionalProperties": { "type": "string" } }, { "type": "null" }],
+    }));
+    assert_eq!(json["properties"]["addresses"], serde_json::json!({
+        "anyOf": [
+            { "type": "object", "additionalProperties": { "$ref": "#/components/schemas/Address" } },
+            { "type": "null" },
+        ],
+    }));
+}
+
+#[test]
+fn test_nullable_array_keeps_items() {
+    let json = nullable_containers_json();
+    assert_eq!(json["properties"]["aliases"], serde_json::json!({
+        "anyOf": [{ "type": "array", "items": { "type": "string" } }, { "type": "null" }],
+    }));
+}
+
+#[test]
+fn test_array_of_options_has_nullable_items() {
+    let json = nullable_containers_json();
+    assert_eq!(json["properties"]["scores"], serde_json::json!({
+        "type": "array",
+        "items": { "anyOf": [{ "type": "integer", "format": "int32" }, { "type": "null" }] },
+    }));
+    assert_eq!(json["properties"]["stops"], serde_json::json!({
+        "type": "array",
+        "items": { "anyOf": [{ "$ref": "#/components/schemas/Address" }, { "type": "null" }] },
+    }));
+}
+
+#[test]
+fn test_option_fields_never_required() {
+    let json = nullable_containers_json();
+    assert_eq!(json["required"], serde_json::json!(["name", "scores", "stops"]));
+}
+
+// ---- Schema Component Names ----
+
+mod billing {
+    use hayai::prelude::*;
+
+    #[api_model(schema_name = "BillingSettings")]
+    #[derive(Debug, Clone)]
+    pub struct Settings {
+        pub plan: String,
+    }
+
+    #[api_model(module_prefix)]
+    #[derive(Debug, Clone)]
+    pub struct Invoice {
+        pub amount: i64,
+    }
+}
+
+#[api_model]
+#[derive(Debug, Clone)]
+struct Account {
+    settings: billing::Settings,
+    invoices: Vec<billing::Invoice>,
+}
+
+#[get("/billing/settings")]
+async fn get_billing_settings() -> billing::Settings {
+    billing::Settings { plan: "pro".into() }
+}
+
+#[post("/billing/invoices")]
+async fn create_invoice(body: billing::Invoice) -> billing::Invoice {
+    body
+}
+
+fn component(name: &str) -> &'static hayai::SchemaInfo {
+    inventory::iter::<hayai::SchemaInfo>().find(|s| s.component_name() == name).unwrap()
+}
+
+#[test]
+fn test_schema_name_renames_component_and_refs() {
+    assert_eq!(component("BillingSettings").name, "BillingSettings");
+    assert!(inventory::iter::<hayai::SchemaInfo>().all(|s| s.component_name() != "Settings"));
+
+    let account = (component("Account").schema_fn)();
+    assert_eq!(account.properties["settings"].ref_path.as_deref(), Some("#/components/schemas/BillingSettings"));
+
+    let route = inventory::iter::<&hayai::RouteInfo>().find(|r| r.handler_name == "get_billing_settings").unwrap();
+    assert_eq!(route.component_name(route.response_type_name), "BillingSettings");
+}
+
+#[test]
+fn test_module_prefix_component_name() {
+    assert_eq!(openapi::module_schema_name("app::billing_v2", "Invoice"), "BillingV2Invoice");
+    assert_eq!(openapi::module_schema_name("app", "Invoice"), "Invoice");
+
+    let invoice = component("BillingInvoice");
+    assert_eq!(invoice.name, "Invoice");
+    assert_eq!(invoice.module_path, "integration_tests::billing");
+    assert!((invoice.schema_fn)().properties.contains_key("amount"));
+
+    let account = (component("Account").schema_fn)();
+    let items = account.properties["invoices"].items.as_ref().unwrap();
+    assert_eq!(items.ref_path.as_deref(), Some("#/components/schemas/BillingInvoice"));
+
+    let route = inventory::iter::<&hayai::RouteInfo>().find(|r| r.handler_name == "create_invoice").unwrap();
+    assert_eq!(route.component_name(route.body_type_name), "BillingInvoice");
+    assert!(HayaiApp::schema_collisions().is_empty());
+}
+
+// ---- Registry Introspection ----
+
+#[test]
+fn test_registry_routes_sorted_with_accessors() {
+    let routes = hayai::registry::routes();
+    assert!(routes.windows(2).all(|w| (w[0].path(), w[0].method()) <= (w[1].path(), w[1].method())));
+
+    let create = routes.iter().find(|r| r.handler_name() == "create_item_route").unwrap();
+    assert_eq!(create.method(), "POST");
+    assert_eq!(create.path(), "/items");
+    assert_eq!(create.full_path("/api/"), "/api/items");
+    assert_eq!(create.success_status(), 201);
+    assert_eq!(create.description(), "A tagged and status-coded route");
+    assert_eq!(create.request_body().as_deref(), Some("NumericModel"));
+    assert_eq!(create.response().as_deref(), Some("TestUser"));
+}
+
+#[test]
+fn test_registry_filter_by_tag() {
+    let tagged: Vec<&str> = hayai::registry::routes().into_iter()
+        .filter(|r| r.has_tag("custom"))
+        .map(|r| r.handler_name())
+        .collect();
+    assert_eq!(tagged, ["rt_tagged_route"]);
+}
+
+#[test]
+fn test_registry_markdown_route_table() {
+    let mut table = String::from("| Method | Path | Handler |\n|---|---|---|\n");
+    for route in hayai::registry::routes().into_iter().filter(|r| r.path().starts_with("/billing")) {
+        table.push_str(&format!("| {} | `{}` | {} |\n", route.method(), route.full_path("/v1"), route.handler_name()));
+    }
+    assert_eq!(
+        table,
+        "| Method | Path | Handler |\n|---|---|---|\n\
+         | POST | `/v1/billing/invoices` | create_invoice |\n\
+         | GET | `/v1/billing/settings` | get_billing_settings |\n"
+    );
+}
+
+#[test]
+fn test_registry_schemas_json() {
+    let schemas = hayai::registry::schemas();
+    let names: Vec<String> = schemas.iter().map(|s| s.component_name()).collect();
+    assert!(names.windows(2).all(|w| w[0] <= w[1]));
+
+    let settings = schemas.iter().find(|s| s.component_name() == "BillingSettings").unwrap();
+    let json = settings.json();
+    assert_eq!(json["type"], "object");
+    assert_eq!(json["properties"]["plan"]["type"], "string");
 }
 
 // ---- Route Registration Tests ----
@@ -315,6 +679,57 @@ fn test_field_description() {
     assert_eq!(schema.properties["name"].description.as_deref(), Some("Human-readable name"));
 }
 
+/// Doc text that gets overridden
+#[api_model(title = "Overridden", description = "Explicit description")]
+#[derive(Debug, Clone)]
+struct TitledModel {
+    id: i64,
+}
+
+#[test]
+fn test_struct_title_from_doc_comment() {
+    let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
+    let info = schemas.iter().find(|s| s.name == "DocumentedModel").unwrap();
+    let schema = (info.schema_fn)();
+    assert_eq!(schema.title.as_deref(), Some("A documented struct"));
+    let json = schema.to_json_value();
+    assert_eq!(json["title"], "A documented struct");
+    assert_eq!(json["description"], "A documented struct");
+}
+
+#[test]
+fn test_struct_title_and_description_override() {
+    let schemas: Vec<_> = inventory::iter::<hayai::SchemaInfo>().collect();
+    let info = schemas.iter().find(|s| s.name == "TitledModel").unwrap();
+    let schema = (info.schema_fn)();
+    assert_eq!(schema.title.as_deref(), Some("Overridden"));
+    assert_eq!(schema.description.as_deref(), Some("Explicit description"));
+}
+
+// ---- Model-level custom validation ----
+
+fn check_window(w: &WindowModel) -> Result<(), Vec<String>> {
+    if w.close > w.open { Ok(()) } else { Err(vec!["close: must be after open".to_string(), "window is empty".to_string()]) }
+}
+
+#[api_model(validate(custom = "check_window"))]
+#[derive(Debug, Clone)]
+struct WindowModel {
+    #[validate(minimum = 0)]
+    open: i64,
+    close: i64,
+}
+
+#[test]
+fn test_custom_validation_runs_after_field_checks() {
+    assert!(WindowModel { open: 1, close: 2 }.validate().is_ok());
+
+    let errors = WindowModel { open: -1, close: -2 }.validate_fields().unwrap_err();
+    let found: Vec<_> = errors.iter().map(|e| (e.field.as_str(), e.code.as_str(), e.message.as_str())).collect();
+    assert_eq!(found[1..], [("close", "custom", "must be after open"), ("", "custom", "window is empty")]);
+    assert_eq!(found[0].0, "open");
+}
+
 // ---- Issue #7: Numeric Validation ----
 
 #[api_model]
@@ -421,7 +836,7 @@ async fn default_get_route() -> TestUser {
 }
 
 #[delete("/default-delete/{id}")]
-#[allow(unused_variables)]
+#[allow(unused_variables, clippy::unused_unit)]
 async fn default_delete_route(id: i64) -> () {
     ()
 }
@@ -650,6 +1065,7 @@ fn test_openapi_spec_description_in_json() {
         paths: HashMap::new(),
         schemas: HashMap::new(),
         security_schemes: HashMap::new(),
+        request_bodies: HashMap::new(),
     };
     let json = spec.to_json();
     assert_eq!(json["info"]["description"], "My description");
@@ -688,6 +1104,7 @@ fn test_openapi_spec_contact_license_in_json() {
         paths: HashMap::new(),
         schemas: HashMap::new(),
         security_schemes: HashMap::new(),
+        request_bodies: HashMap::new(),
     };
     let json = spec.to_json();
     assert_eq!(json["info"]["contact"]["name"], "Author");
@@ -787,6 +1204,12 @@ async fn rt_get_item(id: i64) -> RouterTestItem {
     RouterTestItem { id, name: "item".into() }
 }
 
+#[post("/rt-linked")]
+#[link(name = "GetItem", operation = "rt_get_item", parameters(id = "$response.body#/id"))]
+async fn rt_linked_item() -> RouterTestItem {
+    RouterTestItem { id: 1, name: "item".into() }
+}
+
 #[post("/rt-create")]
 async fn rt_create_item(body: CreateTestUser) -> RouterTestItem {
     RouterTestItem { id: 1, name: body.name }
@@ -826,6 +1249,58 @@ fn test_nested_router_prefix_concatenation() {
     assert_eq!(resolved[0].full_path(), "/api/v1/items/rt-list");
 }
 
+#[test]
+fn test_router_trailing_slash_prefix_normalized() {
+    let inner = hayai::HayaiRouter::new("items/")
+        .route(__HAYAI_ROUTE_RT_GET_ITEM);
+    let outer = hayai::HayaiRouter::new("/api/")
+        .include(inner);
+    let resolved = outer.resolve("", &[], &[]);
+    assert_eq!(resolved[0].full_path(), "/api/items/rt-item/{id}");
+    assert_eq!(resolved[0].full_axum_path(), resolved[0].full_path());
+}
+
+#[test]
+fn test_router_root_prefix() {
+    for prefix in ["", "/"] {
+        let router = hayai::HayaiRouter::new(prefix)
+            .route(__HAYAI_ROUTE_RT_LIST_ITEMS);
+        let resolved = router.resolve("", &[], &[]);
+        assert_eq!(resolved[0].full_path(), "/rt-list");
+        assert_eq!(resolved[0].full_axum_path(), "/rt-list");
+    }
+}
+
+#[test]
+#[should_panic(expected = "empty segment")]
+fn test_router_prefix_empty_segment_rejected() {
+    hayai::HayaiRouter::new("/api//v1");
+}
+
+#[test]
+fn test_doubled_prefix_reported() {
+    let inner = hayai::HayaiRouter::new("/api/items")
+        .route(__HAYAI_ROUTE_RT_LIST_ITEMS);
+    let app = hayai::HayaiApp::new()
+        .include(hayai::HayaiRouter::new("/api").include(inner));
+    assert_eq!(app.repeated_segments(), vec!["GET /api/api/items/rt-list".to_string()]);
+
+    let app = hayai::HayaiApp::new()
+        .include(hayai::HayaiRouter::new("/api/items").route(__HAYAI_ROUTE_RT_LIST_ITEMS));
+    assert!(app.repeated_segments().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "GET /api/api/items/rt-list")]
+fn test_strict_paths_rejects_doubled_prefix() {
+    let inner = hayai::HayaiRouter::new("/api/items")
+        .route(__HAYAI_ROUTE_RT_LIST_ITEMS);
+    let _ = hayai::HayaiApp::new()
+        .strict_paths(true)
+        .include(hayai::HayaiRouter::new("/api").include(inner))
+        .into_router();
+}
+
 #[test]
 fn test_router_tags_merged_with_route_tags() {
     let router = hayai::HayaiRouter::new("/tagged")
@@ -849,6 +1324,59 @@ fn test_router_security_applied() {
     assert!(sec.contains(&"bearer"));
 }
 
+#[test]
+fn test_security_references_checked_against_registered_schemes() {
+    let router = hayai::HayaiRouter::new("/secure")
+        .security(hayai::security::API_KEY)
+        .route(__HAYAI_ROUTE_RT_SECURED_ROUTE)
+        .route(__HAYAI_ROUTE_RT_LIST_ITEMS);
+    let app = hayai::HayaiApp::new().bearer_auth().api_key_auth("X-Api-Key").include(router);
+    assert!(app.unknown_security_schemes().is_empty());
+    let _ = app.into_router();
+
+    let router = hayai::HayaiRouter::new("/secure")
+        .security("bearar")
+        .route(__HAYAI_ROUTE_RT_LIST_ITEMS);
+    let app = hayai::HayaiApp::new().bearer_auth().include(router);
+    assert_eq!(app.unknown_security_schemes(), vec!["rt_list_items: bearar".to_string()]);
+}
+
+#[test]
+#[should_panic(expected = "Unknown security scheme: rt_list_items: bearar; known schemes: bearer, bearerAuth")]
+fn test_misspelled_security_scheme_fails_spec_build() {
+    let router = hayai::HayaiRouter::new("/secure")
+        .security("bearar")
+        .route(__HAYAI_ROUTE_RT_LIST_ITEMS);
+    let _ = hayai::HayaiApp::new().bearer_auth().include(router).into_router();
+}
+
+#[test]
+#[should_panic(expected = "Unknown security scheme: ")]
+fn test_unknown_security_scheme_fails_strict_spec_without_include() {
+    // Serving every registered route only warns, unless the spec is strict
+    let _ = hayai::HayaiApp::new().title("Test").into_router();
+    let _ = hayai::HayaiApp::new().strict_spec(true).into_router();
+}
+
+#[test]
+fn test_links_resolve_against_mounted_operations() {
+    let router = hayai::HayaiRouter::new("/items")
+        .route(__HAYAI_ROUTE_RT_LINKED_ITEM)
+        .route(__HAYAI_ROUTE_RT_GET_ITEM);
+    assert!(hayai::HayaiApp::new().include(router).unresolved_links().is_empty());
+
+    let router = hayai::HayaiRouter::new("/items").route(__HAYAI_ROUTE_RT_LINKED_ITEM);
+    let app = hayai::HayaiApp::new().include(router);
+    assert_eq!(app.unresolved_links(), vec!["rt_linked_item: GetItem -> rt_get_item".to_string()]);
+}
+
+#[test]
+#[should_panic(expected = "Link to an unknown operation: rt_linked_item: GetItem -> rt_get_item")]
+fn test_link_to_unmounted_operation_fails_spec_build() {
+    let router = hayai::HayaiRouter::new("/items").route(__HAYAI_ROUTE_RT_LINKED_ITEM);
+    let _ = hayai::HayaiApp::new().include(router).into_router();
+}
+
 #[test]
 fn test_router_no_include_backward_compat() {
     // When no .include() is used, auto-discovery should work
@@ -1048,3 +1576,229 @@ fn test_integration_override_with_dep() {
     
     // The app builds successfully with override applied
 }
+
+#[test]
+fn test_http_date_formats() {
+    use hayai::precondition::{format_http_date, parse_http_date};
+    let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(784_111_777);
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(expected));
+    assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), Some(expected));
+    assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), Some(expected));
+    assert_eq!(format_http_date(expected), "Sun, 06 Nov 1994 08:49:37 GMT");
+
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 PST"), None);
+    assert_eq!(parse_http_date("Sun, 29 Feb 1995 08:49:37 GMT"), None);
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 8:49:37 GMT"), None);
+    assert_eq!(parse_http_date(""), None);
+}
+
+fn default_retries() -> i64 {
+    3
+}
+
+#[api_model]
+struct RetryPolicy {
+    #[serde(default = "default_retries")]
+    retries: i64,
+    status: TaskStatus,
+    #[schema(example = "[\"eu\", \"us\"]")]
+    regions: Vec<String>,
+}
+
+#[test]
+fn test_example_for_schema() {
+    let components: std::collections::HashMap<String, hayai::openapi::Schema> = hayai::registry::schemas()
+        .into_iter()
+        .map(|info| (info.component_name(), info.schema()))
+        .collect();
+    let example = |name: &str| {
+        hayai::openapi::example_for_schema(&components[name], &components, hayai::openapi::default_placeholder)
+    };
+
+    // Nested models are expanded and arrays get one element
+    assert_eq!(example("UserWithAddress"), serde_json::json!({
+        "name": "string",
+        "address": { "city": "string", "country": "string" },
+        "tags": ["string"],
+        "nickname": "string",
+    }));
+    // Defaults, the first enum value and parsed non-string examples
+    assert_eq!(example("RetryPolicy"), serde_json::json!({
+        "retries": 3,
+        "status": "Active",
+        "regions": ["eu", "us"],
+    }));
+}
+
+// ===== String-encoded integers =====
+
+#[api_model]
+#[derive(Debug, PartialEq)]
+struct Snowflake {
+    #[schema(string_encoded)]
+    id: i64,
+    #[schema(string_encoded)]
+    parent_id: Option<u64>,
+    #[validate(maximum = 100)]
+    #[schema(string_encoded)]
+    shard: i32,
+}
+
+#[test]
+fn test_string_encoded_round_trip() {
+    let big = (1_i64 << 53) + 1;
+    let value = Snowflake { id: big, parent_id: Some(u64::MAX), shard: 7 };
+    let json = serde_json::to_value(&value).unwrap();
+    assert_eq!(json, serde_json::json!({ "id": "9007199254740993", "parent_id": "18446744073709551615", "shard": "7" }));
+    assert_eq!(serde_json::from_value::<Snowflake>(json).unwrap(), value);
+
+    // Numbers are still accepted, and an absent or null Option is None
+    let from_numbers: Snowflake = serde_json::from_str(r#"{"id": 9007199254740993, "shard": 7}"#).unwrap();
+    assert_eq!(from_numbers, Snowflake { id: big, parent_id: None, shard: 7 });
+    let with_null: Snowflake = serde_json::from_str(r#"{"id": "1", "parent_id": null, "shard": "2"}"#).unwrap();
+    assert_eq!(with_null.parent_id, None);
+    assert_eq!(serde_json::to_value(&with_null).unwrap()["parent_id"], serde_json::Value::Null);
+
+    let err = serde_json::from_str::<Snowflake>(r#"{"id": "12ab", "shard": "1"}"#).unwrap_err();
+    assert!(err.to_string().contains("invalid integer \"12ab\""), "{err}");
+    assert!(serde_json::from_str::<Snowflake>(r#"{"id": "1", "shard": "4294967296"}"#).is_err());
+
+    // Validation sees the integer
+    let over: Snowflake = serde_json::from_str(r#"{"id": "1", "shard": "101"}"#).unwrap();
+    assert!(over.validate().is_err());
+}
+
+#[test]
+fn test_string_encoded_schema() {
+    let schema = hayai::registry::schemas().into_iter()
+        .find(|s| s.component_name() == "Snowflake")
+        .unwrap()
+        .json();
+    assert_eq!(schema["properties"]["id"], serde_json::json!({ "type": "string", "format": "int64" }));
+    assert_eq!(schema["properties"]["parent_id"]["anyOf"][0], serde_json::json!({ "type": "string", "format": "uint64" }));
+    assert_eq!(schema["properties"]["shard"]["type"], "string");
+    assert_eq!(schema["properties"]["shard"]["maximum"], 100.0);
+
+    let raw = hayai::HayaiApp::raw_int64_properties();
+    assert!(!raw.iter().any(|p| p.starts_with("Snowflake.")), "{raw:?}");
+    assert!(raw.contains(&"RetryPolicy.retries".to_string()), "{raw:?}");
+}
+
+#[test]
+#[should_panic(expected = "64-bit integers not encoded as strings")]
+fn test_encode_int64_as_string_audit() {
+    let _ = hayai::HayaiApp::new().encode_int64_as_string(true).into_router();
+}
+
+// ---- Spec self-validation ----
+
+fn bare_operation(responses: HashMap<String, openapi::ResponseDef>) -> openapi::Operation {
+    openapi::Operation {
+        summary: None,
+        description: None,
+        operation_id: None,
+        tags: vec![],
+        parameters: vec![],
+        request_body: None,
+        responses,
+        security: vec![],
+        public: false,
+        produces: None,
+        extensions: vec![],
+    }
+}
+
+#[test]
+fn test_spec_validation_reports_issues() {
+    let ghost = openapi::ResponseDef {
+        description: "OK".to_string(),
+        schema_ref: Some(serde_json::json!({ "$ref": "#/components/schemas/Ghost" })),
+        headers: HashMap::new(),
+        links: Vec::new(),
+    };
+    let get_user = bare_operation(HashMap::from([("200".to_string(), ghost)]));
+    let mut health = bare_operation(HashMap::new());
+    health.security = vec![HashMap::from([("bearar".to_string(), vec![])])];
+    health.parameters = vec![openapi::Parameter {
+        name: "id",
+        location: "path",
+        required: true,
+        schema: openapi::SchemaObject::new_type("integer"),
+        description: None,
+    }];
+
+    let spec = openapi::OpenApiSpec {
+        openapi: "3.1.0".to_string(),
+        info: openapi::Info {
+            title: "Broken".to_string(),
+            version: "1.0".to_string(),
+            description: None,
+            contact: None,
+            license: None,
+        },
+        servers: vec![],
+        paths: HashMap::from([
+            ("/users/{id}".to_string(), HashMap::from([("get".to_string(), get_user)])),
+            ("/health".to_string(), HashMap::from([("get".to_string(), health)])),
+        ]),
+        schemas: HashMap::new(),
+        security_schemes: HashMap::new(),
+        request_bodies: HashMap::new(),
+    };
+    let issues: Vec<String> = spec.validate().iter().map(ToString::to_string).collect();
+    assert_eq!(issues, vec![
+        "GET /health: operation has no responses",
+        "GET /health: parameter `id` is not in the path template",
+        "GET /health: security scheme `bearar` is not declared",
+        "GET /users/{id}: $ref #/components/schemas/Ghost does not resolve",
+        "GET /users/{id}: path parameter `id` is not declared",
+    ]);
+}
+
+#[test]
+fn test_spec_validation_resolves_request_body_refs() {
+    let body = |component: Option<&str>, schema: serde_json::Value| openapi::RequestBody {
+        required: true,
+        content_type: "application/json".to_string(),
+        schema,
+        example: None,
+        description: None,
+        component: component.map(str::to_string),
+    };
+    let ok = openapi::ResponseDef { description: "OK".to_string(), schema_ref: None, headers: HashMap::new(), links: Vec::new() };
+    let mut create = bare_operation(HashMap::from([("200".to_string(), ok.clone())]));
+    create.request_body = Some(body(Some("CreateUser"), serde_json::Value::Null));
+    let mut invite = bare_operation(HashMap::from([("200".to_string(), ok)]));
+    invite.request_body = Some(body(Some("Invite"), serde_json::Value::Null));
+
+    let spec = openapi::OpenApiSpec {
+        openapi: "3.1.0".to_string(),
+        info: openapi::Info {
+            title: "Broken".to_string(),
+            version: "1.0".to_string(),
+            description: None,
+            contact: None,
+            license: None,
+        },
+        servers: vec![],
+        paths: HashMap::from([
+            ("/users".to_string(), HashMap::from([("post".to_string(), create)])),
+            ("/invites".to_string(), HashMap::from([("post".to_string(), invite)])),
+        ]),
+        schemas: HashMap::new(),
+        security_schemes: HashMap::new(),
+        request_bodies: HashMap::from([
+            ("Invite".to_string(), body(None, serde_json::json!({ "$ref": "#/components/schemas/Ghost" }))),
+        ]),
+    };
+    let issues: Vec<String> = spec.validate().iter().map(ToString::to_string).collect();
+    assert_eq!(issues, vec![
+        "POST /users: $ref #/components/requestBodies/CreateUser does not resolve",
+        "components.requestBodies.Invite: $ref #/components/schemas/Ghost does not resolve",
+    ]);
+}
+
+#[test]
+fn test_strict_spec_accepts_generated_spec() {
+    let _ = HayaiApp::new().bearer_auth().strict_spec(true).into_router();
+}
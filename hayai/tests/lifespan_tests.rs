@@ -0,0 +1,216 @@
+use hayai::prelude::*;
+use hayai::lifespan::LifespanError;
+use hayai::warmup::WarmupPlan;
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+fn logger(log: &Log, entry: &'static str) -> impl FnOnce(LifespanSharedState) -> std::future::Ready<Result<(), hayai::lifespan::BoxError>> {
+    let log = log.clone();
+    move |_| {
+        log.lock().unwrap().push(entry);
+        std::future::ready(Ok(()))
+    }
+}
+
+/// A free local address for `serve_with_shutdown`
+fn free_addr() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+struct Pool {
+    size: i64,
+}
+
+#[api_model]
+struct PoolStatus {
+    size: i64,
+}
+
+#[get("/pool")]
+async fn pool_status(pool: Dep<Pool>) -> PoolStatus {
+    PoolStatus { size: pool.size }
+}
+
+#[tokio::test]
+async fn test_callbacks_run_in_order_and_reverse_on_shutdown() {
+    let log = Log::default();
+    HayaiApp::minimal()
+        .on_startup(logger(&log, "start db"))
+        .on_startup(logger(&log, "start cache"))
+        .on_shutdown(logger(&log, "stop db"))
+        .on_shutdown(logger(&log, "stop cache"))
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap();
+    assert_eq!(*log.lock().unwrap(), ["start db", "start cache", "stop cache", "stop db"]);
+}
+
+#[tokio::test]
+async fn test_shared_state_visible_to_later_callbacks_and_handlers() {
+    let log = Log::default();
+    let seen = log.clone();
+    let addr = free_addr();
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let server = tokio::spawn({
+        let addr = addr.clone();
+        HayaiApp::minimal()
+            .on_startup(|state: LifespanSharedState| async move {
+                state.insert(Pool { size: 4 });
+                Ok(())
+            })
+            .on_startup(move |state: LifespanSharedState| async move {
+                let pool = state.get::<Pool>().ok_or("pool missing")?;
+                assert_eq!(pool.size, 4);
+                seen.lock().unwrap().push("pool seen");
+                Ok(())
+            })
+            .serve_with_shutdown(&addr, async { let _ = stop_rx.await; })
+    });
+
+    let body = loop {
+        match reqwest::get(format!("http://{addr}/pool")).await {
+            Ok(resp) => break resp.json::<serde_json::Value>().await.unwrap(),
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+        }
+    };
+    assert_eq!(body["size"], 4);
+    stop_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert_eq!(*log.lock().unwrap(), ["pool seen"]);
+}
+
+#[tokio::test]
+async fn test_named_startup_failure_identifies_callback() {
+    let log = Log::default();
+    let err: LifespanError = HayaiApp::minimal()
+        .on_startup(logger(&log, "start cache"))
+        .on_startup(Lifespan::named("db", |_| async { Err("connection refused".into()) }))
+        .on_startup(logger(&log, "start worker"))
+        .on_shutdown(logger(&log, "stop cache"))
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.phase, "startup");
+    assert_eq!(err.callback, "db");
+    assert_eq!(err.to_string(), "startup callback `db` failed: connection refused");
+    assert_eq!(*log.lock().unwrap(), ["start cache"]);
+}
+
+#[tokio::test]
+async fn test_shutdown_failure_still_runs_remaining_callbacks() {
+    let log = Log::default();
+    let err = HayaiApp::minimal()
+        .on_shutdown(logger(&log, "stop db"))
+        .on_shutdown(|_| async { Err("flush failed".into()) })
+        .on_shutdown(logger(&log, "stop cache"))
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.phase, "shutdown");
+    assert_eq!(err.callback, "shutdown#2");
+    assert_eq!(*log.lock().unwrap(), ["stop cache", "stop db"]);
+}
+
+/// Stands in for a client whose upstream may be down
+struct Catalog {
+    reachable: bool,
+}
+
+impl Catalog {
+    async fn count(&self) -> Result<i64, ApiError> {
+        if self.reachable { Ok(3) } else { Err(ApiError::internal("catalog unreachable".into())) }
+    }
+}
+
+#[api_model]
+struct CatalogStatus {
+    items: i64,
+}
+
+#[get("/catalog/status")]
+async fn catalog_status(catalog: Dep<Catalog>) -> Result<CatalogStatus, ApiError> {
+    Ok(CatalogStatus { items: catalog.count().await? })
+}
+
+#[tokio::test]
+async fn test_failed_warmup_aborts_startup() {
+    let log = Log::default();
+    let err = HayaiApp::minimal()
+        .dep(Pool { size: 4 })
+        .dep(Catalog { reachable: false })
+        .on_shutdown(logger(&log, "stop db"))
+        .warmup(WarmupPlan::new().get("/pool", 200..=299).get("/catalog/status", 200..=299))
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.phase, "startup");
+    assert_eq!(err.callback, "warmup");
+    assert_eq!(
+        err.to_string(),
+        r#"startup callback `warmup` failed: warmup GET /catalog/status answered 500, expected 200-299: {"error":"catalog unreachable"}"#,
+    );
+    assert_eq!(*log.lock().unwrap(), ["stop db"]);
+
+    // Generated requests catch the broken dependency too
+    let err = HayaiApp::minimal()
+        .dep(Pool { size: 4 })
+        .dep(Catalog { reachable: false })
+        .warmup(WarmupPlan::from_routes_get_only())
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("GET /catalog/status answered 500"), "{err}");
+}
+
+#[tokio::test]
+async fn test_passing_warmup_serves() {
+    HayaiApp::minimal()
+        .dep(Pool { size: 4 })
+        .dep(Catalog { reachable: false })
+        .warmup(WarmupPlan::new().get("/pool", 200..=299))
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap();
+
+    HayaiApp::minimal()
+        .dep(Pool { size: 4 })
+        .dep(Catalog { reachable: true })
+        .warmup(WarmupPlan::from_routes_get_only())
+        .serve_with_shutdown(&free_addr(), async {})
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_bind_failure_is_returned_after_shutdown() {
+    let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap().to_string();
+    let log = Log::default();
+    let err = HayaiApp::minimal()
+        .on_startup(logger(&log, "start db"))
+        .on_shutdown(logger(&log, "stop db"))
+        .serve_with_shutdown(&addr, async {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.phase, "startup");
+    assert_eq!(err.callback, "bind");
+    assert!(err.to_string().starts_with("startup callback `bind` failed: "), "{err}");
+    assert_eq!(*log.lock().unwrap(), ["start db", "stop db"]);
+}
+
+#[tokio::test]
+async fn test_router_panic_runs_shutdown() {
+    let log = Log::default();
+    let server = tokio::spawn(HayaiApp::minimal()
+        .dep(Pool { size: 4 })
+        .strict_paths(true)
+        .include(HayaiRouter::new("/pool").route(__HAYAI_ROUTE_POOL_STATUS))
+        .on_shutdown(logger(&log, "stop db"))
+        .serve_with_shutdown(&free_addr(), async {}));
+    let panic = server.await.unwrap_err().into_panic();
+    assert!(panic.downcast_ref::<String>().unwrap().contains("Route paths repeat a segment"));
+    assert_eq!(*log.lock().unwrap(), ["stop db"]);
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "openapi")]
+
+use hayai::lint::{check_model, check_models, LintIssue};
+use hayai::openapi::{Property, PropertyPatch, Schema};
+use hayai::prelude::*;
+use hayai::schemars::JsonSchema;
+use hayai::serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn schema(properties: &[(&str, Property)]) -> Schema {
+    Schema {
+        type_name: "object".to_string(),
+        properties: properties.iter().map(|(name, prop)| (name.to_string(), prop.clone())).collect(),
+        ..Default::default()
+    }
+}
+
+fn typed(type_name: &str) -> Property {
+    Property { type_name: type_name.to_string(), ..Default::default() }
+}
+
+fn lint(prop: Property, patch: PropertyPatch) -> Vec<LintIssue> {
+    check_model("Account", &schema(&[("field", prop)]), &HashMap::from([("field".to_string(), patch)]))
+}
+
+fn mismatch(constraint: &'static str, json_type: &str) -> LintIssue {
+    LintIssue::TypeMismatch { model: "Account".to_string(), field: "field".to_string(), constraint, json_type: json_type.to_string() }
+}
+
+#[test]
+fn test_string_constraints_on_other_types() {
+    assert_eq!(lint(typed("integer"), PropertyPatch { min_length: Some(1), ..Default::default() }), [mismatch("min_length", "integer")]);
+    assert_eq!(lint(typed("boolean"), PropertyPatch { pattern: Some("^y$".to_string()), ..Default::default() }), [mismatch("pattern", "boolean")]);
+    assert_eq!(
+        lint(typed("integer"), PropertyPatch { format: Some("email".to_string()), ..Default::default() }),
+        [mismatch("email", "integer")],
+    );
+    // `Vec` has a length too, but `minLength` means nothing on an array
+    assert_eq!(lint(typed("array"), PropertyPatch { max_length: Some(3), ..Default::default() }), [mismatch("max_length", "array")]);
+}
+
+#[test]
+fn test_numeric_constraints_on_other_types() {
+    assert_eq!(
+        lint(typed("string"), PropertyPatch { minimum: Some(0.0), maximum: Some(9.0), ..Default::default() }),
+        [mismatch("maximum", "string"), mismatch("minimum", "string")],
+    );
+    let nested = Property { ref_path: Some("#/components/schemas/Address".to_string()), ..typed("object") };
+    assert_eq!(lint(nested, PropertyPatch { minimum: Some(1.0), ..Default::default() }), [mismatch("minimum", "object")]);
+}
+
+#[test]
+fn test_items_constraint_on_non_array() {
+    assert_eq!(
+        lint(typed("string"), PropertyPatch { min_items: Some(1), ..Default::default() }),
+        [LintIssue::ItemsOnNonArray { model: "Account".to_string(), field: "field".to_string(), json_type: "string".to_string() }],
+    );
+}
+
+#[test]
+fn test_patch_for_unknown_field() {
+    let patches = HashMap::from([("emailAddress".to_string(), PropertyPatch { format: Some("email".to_string()), ..Default::default() })]);
+    assert_eq!(
+        check_model("Account", &schema(&[("email_address", typed("string"))]), &patches),
+        [LintIssue::UnknownField { model: "Account".to_string(), field: "emailAddress".to_string() }],
+    );
+}
+
+#[test]
+fn test_fitting_constraints_pass() {
+    let optional_email = Property { nullable: true, ..typed("string") };
+    assert!(lint(optional_email, PropertyPatch { format: Some("email".to_string()), min_length: Some(3), ..Default::default() }).is_empty());
+    assert!(lint(typed("array"), PropertyPatch { min_items: Some(1), ..Default::default() }).is_empty());
+    // Decimals and string-encoded integers are strings that take numeric bounds
+    let decimal = Property { format: Some("decimal".to_string()), ..typed("string") };
+    assert!(lint(decimal, PropertyPatch { minimum: Some(0.0), pattern: Some("^[0-9.]+$".to_string()), ..Default::default() }).is_empty());
+    let encoded = Property { format: Some("int64".to_string()), ..typed("string") };
+    assert!(lint(encoded, PropertyPatch { maximum: Some(10.0), string_encoded: true, ..Default::default() }).is_empty());
+}
+
+#[api_model]
+struct Tagging {
+    #[validate(min_length = 1)]
+    tags: Vec<String>,
+    #[validate(min_items = 1)]
+    code: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "hayai::serde")]
+#[schemars(crate = "hayai::schemars")]
+struct Address {
+    city: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, ApiModel)]
+#[serde(crate = "hayai::serde")]
+#[schemars(crate = "hayai::schemars")]
+struct Shipment {
+    /// Where it goes
+    #[serde(flatten)]
+    address: Address,
+}
+
+#[test]
+fn test_registered_models_checked() {
+    let issues: Vec<String> = check_models().iter().map(ToString::to_string).collect();
+    assert_eq!(issues, [
+        "Shipment.address: no such property after serde renaming; its attributes are ignored",
+        "Tagging.code: `min_items` applies to arrays, not string values",
+        "Tagging.tags: `min_length` doesn't apply to array values",
+    ]);
+
+    // Only logged unless strict
+    let _ = HayaiApp::new().into_router();
+}
+
+#[test]
+#[should_panic(expected = "Model attributes don't apply: Shipment.address")]
+fn test_strict_models_fail_at_startup() {
+    let _ = HayaiApp::new().strict_models(true).into_router();
+}
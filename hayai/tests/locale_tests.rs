@@ -0,0 +1,123 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[get("/greeting")]
+async fn greeting(locale: Locale) -> Value {
+    let text = match locale.tag.as_str() {
+        "fr" => "Bonjour",
+        "ja" => "こんにちは",
+        _ => "Hello",
+    };
+    json!({ "locale": locale.tag, "text": text, "accept_language": locale.accept_language })
+}
+
+#[get("/orders/{id}")]
+async fn get_order(id: i64, _locale: Locale) -> Result<Value, ApiError> {
+    Err(ApiError::not_found(format!("Order {} not found", id)).with_code("order_not_found").with_param("id", id))
+}
+
+fn app() -> HayaiApp {
+    HayaiApp::new()
+        .locales(&["en", "fr", "ja"], "en")
+        .locale_query("lang")
+        .locale_cookie("lang")
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_GREETING).route(__HAYAI_ROUTE_GET_ORDER))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// (locale, Content-Language) for `/greeting{query}` with the given headers
+async fn greet(base: &str, query: &str, headers: &[(&str, &str)]) -> (String, String) {
+    let mut req = reqwest::Client::new().get(format!("{base}/greeting{query}"));
+    for (name, value) in headers {
+        req = req.header(*name, *value);
+    }
+    let res = req.send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let content_language = res.headers()["content-language"].to_str().unwrap().to_string();
+    let body: Value = res.json().await.unwrap();
+    (body["locale"].as_str().unwrap().to_string(), content_language)
+}
+
+#[tokio::test]
+async fn test_accept_language_by_quality() {
+    let base = spawn(app()).await;
+    let cases = [
+        ("ja;q=0.5, fr;q=0.9, en;q=0.1", "fr"),
+        ("de, ja;q=0.8, fr;q=0.8", "ja"),
+        ("fr-CA", "fr"),
+        ("EN-gb;q=0.4, ja-JP;q=0.7", "ja"),
+        ("fr;q=0, ja;q=0.2", "ja"),
+    ];
+    for (accept_language, expected) in cases {
+        let (locale, content_language) = greet(&base, "", &[("accept-language", accept_language)]).await;
+        assert_eq!(locale, expected, "{accept_language}");
+        assert_eq!(content_language, expected, "{accept_language}");
+    }
+
+    let res = reqwest::Client::new().get(format!("{base}/greeting")).header("accept-language", "fr-CH, fr;q=0.9").send().await.unwrap();
+    assert_eq!(res.headers()["vary"], "accept-language");
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "locale": "fr", "text": "Bonjour", "accept_language": "fr-CH, fr;q=0.9" }));
+}
+
+#[tokio::test]
+async fn test_unsupported_language_falls_back_to_default() {
+    let base = spawn(HayaiApp::new().locales(&["fr", "ja"], "ja")).await;
+    for headers in [&[][..], &[("accept-language", "de, pt-BR;q=0.5")][..], &[("accept-language", "*, fr;q=0.1")][..]] {
+        assert_eq!(greet(&base, "", headers).await, ("ja".to_string(), "ja".to_string()), "{headers:?}");
+    }
+    // Not configured: the query parameter is an ordinary one
+    assert_eq!(greet(&base, "?lang=fr", &[]).await.0, "ja");
+}
+
+#[tokio::test]
+async fn test_query_and_cookie_override_header() {
+    let base = spawn(app()).await;
+    let header = ("accept-language", "ja");
+    assert_eq!(greet(&base, "?lang=fr", &[header]).await, ("fr".to_string(), "fr".to_string()));
+    assert_eq!(greet(&base, "", &[header, ("cookie", "theme=dark; lang=fr")]).await.0, "fr");
+    // The query parameter wins over the cookie
+    assert_eq!(greet(&base, "?lang=en", &[header, ("cookie", "lang=fr")]).await.0, "en");
+    // Unsupported values are skipped
+    assert_eq!(greet(&base, "?lang=de", &[header, ("cookie", "lang=xx")]).await.0, "ja");
+}
+
+#[tokio::test]
+async fn test_errors_rendered_in_resolved_locale() {
+    let catalog = MessageCatalog::new().message("fr", "order_not_found", "Commande {id} introuvable");
+    let base = spawn(app().message_catalog(catalog)).await;
+    let res = reqwest::Client::new().get(format!("{base}/orders/4?lang=fr")).header("accept-language", "en").send().await.unwrap();
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["content-language"], "fr");
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "error": "Commande 4 introuvable" }));
+}
+
+#[tokio::test]
+async fn test_locale_without_locales_fails() {
+    let base = spawn(HayaiApp::new()).await;
+    assert_eq!(reqwest::get(format!("{base}/greeting")).await.unwrap().status(), 500);
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_sources_documented_as_optional() {
+    let base = spawn(app()).await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let sources: Vec<(Value, Value, Value)> = spec["paths"]["/greeting"]["get"]["parameters"].as_array().unwrap().iter()
+        .map(|p| (p["name"].clone(), p["in"].clone(), p["required"].clone()))
+        .collect();
+    assert_eq!(sources, [
+        (json!("Accept-Language"), json!("header"), json!(false)),
+        (json!("lang"), json!("query"), json!(false)),
+        (json!("lang"), json!("cookie"), json!(false)),
+    ]);
+    assert_eq!(spec["paths"]["/greeting"]["get"]["parameters"][1]["schema"]["enum"], json!(["en", "fr", "ja"]));
+}
@@ -0,0 +1,83 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct MaintenanceSwitch(Arc<AtomicBool>);
+
+#[get("/widgets")]
+async fn list_widgets() -> Value {
+    json!(["sprocket"])
+}
+
+#[post("/admin/maintenance")]
+async fn toggle_maintenance(switch: Dep<MaintenanceSwitch>) -> Value {
+    let on = !switch.0.fetch_xor(true, Ordering::SeqCst);
+    json!({ "maintenance": on })
+}
+
+#[get("/administrators")]
+async fn list_administrators() -> Value {
+    json!([])
+}
+
+async fn spawn(flag: Arc<AtomicBool>) -> String {
+    let app = HayaiApp::new()
+        .dep(MaintenanceSwitch(flag.clone()))
+        .health_endpoints()
+        .drain_endpoint("/internal/drain", "drain-token")
+        .maintenance(MaintenanceConfig {
+            enabled_flag: flag,
+            message: "Migrating the database, back soon".to_string(),
+            retry_after: Duration::from_millis(120_500),
+            allow_paths: vec!["/admin/".to_string()],
+        })
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_LIST_WIDGETS)
+            .route(__HAYAI_ROUTE_TOGGLE_MAINTENANCE)
+            .route(__HAYAI_ROUTE_LIST_ADMINISTRATORS))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_flag_flips_responses_at_runtime() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let base = spawn(flag.clone()).await;
+    assert_eq!(reqwest::get(format!("{base}/widgets")).await.unwrap().status(), 200);
+
+    flag.store(true, Ordering::SeqCst);
+    let res = reqwest::get(format!("{base}/widgets")).await.unwrap();
+    assert_eq!(res.status(), 503);
+    assert_eq!(res.headers()["retry-after"], "121");
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "error": "Migrating the database, back soon" }));
+
+    flag.store(false, Ordering::SeqCst);
+    assert_eq!(reqwest::get(format!("{base}/widgets")).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_allowlisted_paths_keep_working() {
+    let flag = Arc::new(AtomicBool::new(true));
+    let base = spawn(flag.clone()).await;
+    let client = reqwest::Client::new();
+
+    for path in ["/healthz", "/readyz"] {
+        assert_eq!(reqwest::get(format!("{base}{path}")).await.unwrap().status(), 200, "{path}");
+    }
+    let res = client.post(format!("{base}/internal/drain")).bearer_auth("drain-token").send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    // Prefixes match whole segments
+    assert_eq!(reqwest::get(format!("{base}/administrators")).await.unwrap().status(), 503);
+
+    // The allowlisted admin route turns maintenance off
+    let res = client.post(format!("{base}/admin/maintenance")).send().await.unwrap();
+    assert_eq!(res.json::<Value>().await.unwrap(), json!({ "maintenance": false }));
+    assert!(!flag.load(Ordering::SeqCst));
+    assert_eq!(reqwest::get(format!("{base}/widgets")).await.unwrap().status(), 200);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "validation")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// ===== Schema generation counter =====
+
+static SCHEMA_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_schema() -> hayai::openapi::Schema {
+    SCHEMA_CALLS.fetch_add(1, Ordering::SeqCst);
+    hayai::openapi::api_error_schema()
+}
+
+hayai::inventory::submit! {
+    hayai::SchemaInfo {
+        name: "Counted",
+        module_path: module_path!(),
+        module_prefix: false,
+        strict_bodies: false,
+        schema_fn: counted_schema,
+        nested_fn: std::collections::HashMap::new,
+        patches_fn: std::collections::HashMap::new,
+    }
+}
+
+// ===== Routes =====
+
+#[api_model]
+struct Greeting {
+    #[validate(min_length = 1)]
+    name: String,
+}
+
+#[get("/hello/{name}")]
+async fn hello(name: String) -> Greeting {
+    Greeting { name }
+}
+
+#[post("/greetings")]
+async fn create_greeting(body: Greeting) -> Greeting {
+    body
+}
+
+async fn spawn(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+// ===== Tests =====
+
+#[tokio::test]
+async fn test_minimal_app_serves_handlers_without_docs() {
+    let app = HayaiApp::minimal()
+        .route_explicit(__HAYAI_ROUTE_HELLO)
+        .route_explicit(__HAYAI_ROUTE_CREATE_GREETING)
+        .into_router();
+    assert_eq!(SCHEMA_CALLS.load(Ordering::SeqCst), 0, "minimal apps never build schemas");
+    let base = spawn(app).await;
+
+    let body: Value = reqwest::get(format!("{base}/hello/ann")).await.unwrap().json().await.unwrap();
+    assert_eq!(body["name"], "ann");
+
+    let client = reqwest::Client::new();
+    let resp = client.post(format!("{base}/greetings"))
+        .json(&serde_json::json!({"name": ""}))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422, "validation still runs");
+
+    assert_eq!(reqwest::get(format!("{base}/docs")).await.unwrap().status(), 404);
+    assert_eq!(reqwest::get(format!("{base}/openapi.json")).await.unwrap().status(), 404);
+    assert_eq!(SCHEMA_CALLS.load(Ordering::SeqCst), 0);
+
+    // A regular app assembles the spec up front
+    let _ = HayaiApp::new().into_router();
+    assert!(SCHEMA_CALLS.load(Ordering::SeqCst) > 0);
+}
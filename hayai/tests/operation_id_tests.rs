@@ -0,0 +1,153 @@
+use hayai::operation_id::OperationIdStyle;
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+
+#[api_model]
+struct User {
+    id: i64,
+    name: String,
+}
+
+#[get("/users/{id}")]
+#[link(name = "Rename", operation = rename_user, parameters(id = "$response.body#/id"))]
+async fn get_user_by_id(id: i64) -> User {
+    User { id, name: "Ada".to_string() }
+}
+
+#[put("/users/{id}")]
+#[operation_id("renameUser")]
+async fn rename_user(id: i64) -> User {
+    User { id, name: "Grace".to_string() }
+}
+
+#[get("/user-list")]
+async fn list_users() -> Vec<User> {
+    Vec::new()
+}
+
+#[get("/user_list")]
+async fn list_users_legacy() -> Vec<User> {
+    Vec::new()
+}
+
+/// Handlers of two merged crates, named alike
+mod billing {
+    use hayai::prelude::*;
+
+    #[get("/invoices/{id}")]
+    pub async fn get_by_id(id: i64) -> String {
+        format!("invoice {id}")
+    }
+}
+
+mod accounts {
+    use hayai::prelude::*;
+
+    #[get("/accounts/{id}")]
+    #[operation_id("accounts_get_by_id")]
+    pub async fn get_by_id(id: i64) -> String {
+        format!("account {id}")
+    }
+}
+
+#[derive(Clone, Default)]
+struct CapturedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn users_app(style: OperationIdStyle) -> HayaiApp {
+    HayaiApp::new()
+        .operation_id_style(style)
+        .document_auto_head(true)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_GET_USER_BY_ID)
+            .route(__HAYAI_ROUTE_RENAME_USER))
+}
+
+fn list_app() -> HayaiApp {
+    HayaiApp::new()
+        .operation_id_style(OperationIdStyle::MethodPath)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_LIST_USERS)
+            .route(__HAYAI_ROUTE_LIST_USERS_LEGACY))
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[cfg(feature = "openapi")]
+async fn spec(app: HayaiApp) -> Value {
+    let base = spawn(app).await;
+    reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap()
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_each_style_names_get_users_by_id() {
+    for (style, get, head) in [
+        (OperationIdStyle::SnakeCase, "get_user_by_id", "get_user_by_id_head"),
+        (OperationIdStyle::CamelCase, "getUserById", "getUserByIdHead"),
+        (OperationIdStyle::MethodPath, "getUsersById", "headUsersById"),
+    ] {
+        let spec = spec(users_app(style)).await;
+        let item = &spec["paths"]["/users/{id}"];
+        assert_eq!(item["get"]["operationId"], get, "{style:?}");
+        assert_eq!(item["head"]["operationId"], head, "{style:?}");
+        // `#[operation_id]` wins over every style, and links follow it
+        assert_eq!(item["put"]["operationId"], "renameUser", "{style:?}");
+        assert_eq!(item["get"]["responses"]["200"]["links"]["Rename"]["operationId"], "renameUser");
+    }
+}
+
+#[tokio::test]
+async fn test_access_log_uses_chosen_id() {
+    let log = CapturedLog::default();
+    let base = spawn(users_app(OperationIdStyle::MethodPath)
+        .access_log(hayai::access_log::AccessLogFormat::Json)
+        .access_log_writer(log.clone())).await;
+    assert_eq!(reqwest::get(format!("{base}/users/7")).await.unwrap().status(), 200);
+
+    let line = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    let record: Value = serde_json::from_str(line.trim()).unwrap();
+    assert_eq!(record["operation_id"], "getUsersById");
+}
+
+#[test]
+#[should_panic(expected = "Handlers share an operationId: getUserList: list_users (GET /user-list) and list_users_legacy (GET /user_list)")]
+fn test_colliding_derived_ids_fail() {
+    let _ = list_app().into_router();
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_duplicates_numbered_on_request() {
+    assert_eq!(list_app().operation_id_collisions().len(), 1);
+    let spec = spec(list_app().number_duplicate_operation_ids(true)).await;
+    assert_eq!(spec["paths"]["/user-list"]["get"]["operationId"], "getUserList");
+    assert_eq!(spec["paths"]["/user_list"]["get"]["operationId"], "getUserList2");
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_override_separates_same_named_handlers() {
+    let spec = spec(HayaiApp::new().include(HayaiRouter::new("")
+        .route(billing::__HAYAI_ROUTE_GET_BY_ID)
+        .route(accounts::__HAYAI_ROUTE_GET_BY_ID))).await;
+    assert_eq!(spec["paths"]["/invoices/{id}"]["get"]["operationId"], "get_by_id");
+    assert_eq!(spec["paths"]["/accounts/{id}"]["get"]["operationId"], "accounts_get_by_id");
+}
@@ -0,0 +1,122 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::LazyLock;
+
+static VIDEO: LazyLock<Vec<u8>> = LazyLock::new(|| (0..10_000u32).map(|i| (i % 251) as u8).collect());
+static OPENED: AtomicUsize = AtomicUsize::new(0);
+
+/// Serves the slice it is asked for, as an object store's ranged GET would
+#[get("/videos/intro")]
+async fn intro_video() -> RangedFile {
+    RangedFile::new(VIDEO.len() as u64, |range| {
+        OPENED.fetch_add(1, Ordering::SeqCst);
+        Cursor::new(&VIDEO[range.start as usize..range.end as usize])
+    })
+    .content_type("video/mp4")
+}
+
+async fn spawn() -> String {
+    spawn_app(HayaiApp::new()).await
+}
+
+async fn spawn_app(app: HayaiApp) -> String {
+    let app = app
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_INTRO_VIDEO))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn fetch(base: &str, range: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{base}/videos/intro"));
+    if let Some(range) = range {
+        request = request.header("Range", range);
+    }
+    request.send().await.unwrap()
+}
+
+fn header<'a>(resp: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    resp.headers().get(name).map(|v| v.to_str().unwrap())
+}
+
+#[tokio::test]
+async fn test_full_fetch() {
+    let base = spawn().await;
+    let resp = fetch(&base, None).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(header(&resp, "accept-ranges"), Some("bytes"));
+    assert_eq!(header(&resp, "content-type"), Some("video/mp4"));
+    assert_eq!(header(&resp, "content-length"), Some("10000"));
+    assert_eq!(resp.bytes().await.unwrap(), VIDEO.as_slice());
+
+    // Several ranges get the whole file
+    let resp = fetch(&base, Some("bytes=0-9,20-29")).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn test_middle_range_is_streamed_alone() {
+    let base = spawn().await;
+    let resp = fetch(&base, Some("bytes=4000-4999")).await;
+    assert_eq!(resp.status(), 206);
+    assert_eq!(header(&resp, "content-range"), Some("bytes 4000-4999/10000"));
+    assert_eq!(header(&resp, "content-length"), Some("1000"));
+    assert_eq!(resp.bytes().await.unwrap(), &VIDEO[4000..5000]);
+
+    // Open-ended and suffix ranges, clamped to the end
+    let resp = fetch(&base, Some("bytes=9990-20000")).await;
+    assert_eq!(header(&resp, "content-range"), Some("bytes 9990-9999/10000"));
+    let resp = fetch(&base, Some("bytes=-5")).await;
+    assert_eq!(header(&resp, "content-range"), Some("bytes 9995-9999/10000"));
+    assert_eq!(resp.bytes().await.unwrap(), &VIDEO[9995..]);
+}
+
+#[tokio::test]
+async fn test_range_past_the_end_is_416() {
+    let base = spawn().await;
+    let resp = fetch(&base, Some("bytes=10000-10100")).await;
+    assert_eq!(resp.status(), 416);
+    assert_eq!(header(&resp, "content-range"), Some("bytes */10000"));
+    let body: Value = resp.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("10000 bytes"));
+}
+
+#[tokio::test]
+async fn test_head_reports_ranges_without_opening() {
+    let base = spawn().await;
+    let opened = OPENED.load(Ordering::SeqCst);
+    let resp = reqwest::Client::new().head(format!("{base}/videos/intro")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(header(&resp, "accept-ranges"), Some("bytes"));
+    assert_eq!(header(&resp, "content-length"), Some("10000"));
+    assert_eq!(OPENED.load(Ordering::SeqCst), opened);
+}
+
+#[tokio::test]
+async fn test_head_answered_without_auto_head() {
+    let base = spawn_app(HayaiApp::new().auto_head(false)).await;
+    let resp = reqwest::Client::new().head(format!("{base}/videos/intro")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(header(&resp, "accept-ranges"), Some("bytes"));
+    assert_eq!(header(&resp, "content-length"), Some("10000"));
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_ranges_are_documented() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let get = &spec["paths"]["/videos/intro"]["get"];
+    let binary = serde_json::json!({ "type": "string", "format": "binary" });
+    assert_eq!(get["responses"]["200"]["content"]["application/octet-stream"]["schema"], binary);
+    assert!(get["responses"]["200"]["headers"]["Accept-Ranges"].is_object());
+    assert_eq!(get["responses"]["206"]["content"]["application/octet-stream"]["schema"], binary);
+    assert!(get["responses"]["206"]["headers"]["Content-Range"].is_object());
+    assert_eq!(get["responses"]["416"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ApiError");
+    assert_eq!(get["parameters"][0]["name"], "Range");
+}
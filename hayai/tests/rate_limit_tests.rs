@@ -0,0 +1,28 @@
+use hayai::rate_limit::{MemoryRateLimiter, RateLimit, RateLimiter};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_memory_limiter_counts_per_key() {
+    let limiter = MemoryRateLimiter::new();
+    let limit = RateLimit::new(2, Duration::from_secs(60));
+
+    let mut seen = Vec::new();
+    for _ in 0..3 {
+        let decision = limiter.hit("ip:10.0.0.1", limit).await;
+        seen.push((decision.allowed, decision.remaining));
+    }
+    assert_eq!(seen, [(true, 1), (true, 0), (false, 0)]);
+    assert!(limiter.hit("ip:10.0.0.2", limit).await.allowed);
+}
+
+#[tokio::test]
+async fn test_memory_limiter_window_rolls_over() {
+    let limiter = MemoryRateLimiter::new();
+    let limit = RateLimit::new(1, Duration::from_millis(100));
+
+    let first = limiter.hit("key", limit).await;
+    assert!(first.allowed && first.reset_after <= Duration::from_millis(100));
+    assert!(!limiter.hit("key", limit).await.allowed);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert!(limiter.hit("key", limit).await.allowed);
+}
@@ -0,0 +1,358 @@
+#![cfg(feature = "redis")]
+
+use hayai::axum;
+use hayai::cache::CachedResponse;
+use hayai::idempotency::{Claim, IdempotencyStore};
+use hayai::prelude::*;
+use hayai::rate_limit::{RateLimit, RateLimiter};
+use hayai::redis::client::aio::ConnectionLike;
+use hayai::redis::client::{Arg, Cmd, ErrorKind, Pipeline, RedisError, RedisFuture, Value};
+use hayai::redis::{RedisIdempotencyStore, RedisRateLimiter, RedisResponseCache};
+use serde_json::{json, Value as Json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Set to a Redis URL, e.g. `redis://127.0.0.1:6379/15`, to also run against a real server
+const REDIS_URL_VAR: &str = "HAYAI_TEST_REDIS_URL";
+
+/// In-memory stand-in for Redis answering the commands the stores send, recording each
+/// expiry set and dropping keys once it passes
+#[derive(Clone, Default)]
+struct FakeRedis {
+    entries: Arc<Mutex<HashMap<Vec<u8>, Vec<u8>>>>,
+    expiries: Arc<Mutex<HashMap<Vec<u8>, u64>>>,
+    deadlines: Arc<Mutex<HashMap<Vec<u8>, Instant>>>,
+    down: Arc<AtomicBool>,
+}
+
+impl FakeRedis {
+    fn expire(&self, key: &[u8], ms: &[u8]) {
+        let ms: u64 = std::str::from_utf8(ms).unwrap().parse().unwrap();
+        self.expiries.lock().unwrap().insert(key.to_vec(), ms);
+        self.deadlines.lock().unwrap().insert(key.to_vec(), Instant::now() + Duration::from_millis(ms));
+    }
+
+    fn answer(&self, cmd: &Cmd) -> Result<Value, RedisError> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(RedisError::from((ErrorKind::IoError, "connection refused")));
+        }
+        let args: Vec<&[u8]> = cmd.args_iter()
+            .map(|arg| match arg {
+                Arg::Simple(bytes) => bytes,
+                Arg::Cursor => b"",
+            })
+            .collect();
+        if let Some(key) = args.get(1) {
+            if self.deadlines.lock().unwrap().get(*key).is_some_and(|deadline| *deadline <= Instant::now()) {
+                self.entries.lock().unwrap().remove(*key);
+                self.deadlines.lock().unwrap().remove(*key);
+            }
+        }
+        match args.as_slice() {
+            [b"GET", key] => Ok(self.entries.lock().unwrap().get(*key).cloned().map_or(Value::Nil, Value::BulkString)),
+            [b"SET", key, value, b"PX", ms] => {
+                self.entries.lock().unwrap().insert(key.to_vec(), value.to_vec());
+                self.expire(key, ms);
+                Ok(Value::Okay)
+            }
+            [b"SET", key, value, b"NX", b"PX", ms] => {
+                if self.entries.lock().unwrap().contains_key(*key) {
+                    return Ok(Value::Nil);
+                }
+                self.entries.lock().unwrap().insert(key.to_vec(), value.to_vec());
+                self.expire(key, ms);
+                Ok(Value::Okay)
+            }
+            [b"INCRBY", key, by] => {
+                let mut entries = self.entries.lock().unwrap();
+                let count = entries.get(*key).map_or(0, |v| std::str::from_utf8(v).unwrap().parse::<i64>().unwrap())
+                    + std::str::from_utf8(by).unwrap().parse::<i64>().unwrap();
+                entries.insert(key.to_vec(), count.to_string().into_bytes());
+                Ok(Value::Int(count))
+            }
+            [b"PEXPIRE", key, ms] => {
+                let exists = self.entries.lock().unwrap().contains_key(*key);
+                if exists {
+                    self.expire(key, ms);
+                }
+                Ok(Value::Int(exists as i64))
+            }
+            [b"PTTL", key] => Ok(Value::Int(match (self.entries.lock().unwrap().contains_key(*key), self.deadlines.lock().unwrap().get(*key)) {
+                (false, _) => -2,
+                (true, None) => -1,
+                (true, Some(deadline)) => deadline.saturating_duration_since(Instant::now()).as_millis() as i64,
+            })),
+            [b"DEL", key] => {
+                self.deadlines.lock().unwrap().remove(*key);
+                Ok(Value::Int(self.entries.lock().unwrap().remove(*key).is_some() as i64))
+            }
+            other => panic!("unexpected command {:?}", other.iter().map(|a| String::from_utf8_lossy(a)).collect::<Vec<_>>()),
+        }
+    }
+}
+
+impl ConnectionLike for FakeRedis {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let answer = self.answer(cmd);
+        Box::pin(async move { answer })
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, _cmd: &'a Pipeline, _offset: usize, _count: usize) -> RedisFuture<'a, Vec<Value>> {
+        unimplemented!("the stores send no pipelines")
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
+
+#[derive(Default)]
+struct Calls(AtomicUsize);
+
+#[get("/quotes")]
+#[cache(ttl = "30s")]
+async fn quotes(calls: Dep<Calls>) -> Json {
+    json!({ "call": calls.0.fetch_add(1, Ordering::SeqCst) + 1 })
+}
+
+#[get("/flash")]
+#[cache(ttl = "1s")]
+async fn flash(calls: Dep<Calls>) -> Json {
+    json!({ "call": calls.0.fetch_add(1, Ordering::SeqCst) + 1 })
+}
+
+#[post("/orders/{key}")]
+async fn place_order(key: String, calls: Dep<Calls>, store: Dep<RedisIdempotencyStore<FakeRedis>>) -> Result<Json, ApiError> {
+    match store.claim(&key, Duration::from_secs(60)).await? {
+        Claim::New => {}
+        Claim::InProgress => return Err(ApiError::conflict("An order with this key is in progress")),
+        Claim::Completed(response) => return Ok(serde_json::from_slice(&response.body).unwrap()),
+    }
+    let order = json!({ "order": calls.0.fetch_add(1, Ordering::SeqCst) + 1 });
+    let response = CachedResponse { status: 201, headers: vec![], body: order.to_string().into() };
+    store.complete(&key, response, Duration::from_secs(60)).await?;
+    Ok(order)
+}
+
+/// One replica: its own handler state, sharing `cache` with the others
+async fn replica(cache: impl hayai::cache::ResponseCache) -> String {
+    let app = HayaiApp::new()
+        .dep(Calls::default())
+        .response_cache(cache)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_QUOTES).route(__HAYAI_ROUTE_FLASH))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// One replica taking orders, sharing `store` with the others
+async fn order_replica(store: RedisIdempotencyStore<FakeRedis>) -> String {
+    let app = HayaiApp::new()
+        .dep(Calls::default())
+        .dep(store)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_PLACE_ORDER))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// (status, body)
+async fn order(url: String) -> (u16, Json) {
+    let res = reqwest::Client::new().post(url).send().await.unwrap();
+    (res.status().as_u16(), res.json().await.unwrap())
+}
+
+/// (X-Cache, body)
+async fn fetch(url: String) -> (String, Json) {
+    let res = reqwest::get(url).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let x_cache = res.headers()["x-cache"].to_str().unwrap().to_string();
+    (x_cache, res.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_replicas_share_entries() {
+    let redis = FakeRedis::default();
+    let first = replica(RedisResponseCache::new(redis.clone())).await;
+    let second = replica(RedisResponseCache::new(redis.clone())).await;
+
+    assert_eq!(fetch(format!("{first}/quotes")).await, ("MISS".to_string(), json!({ "call": 1 })));
+    // The second replica never ran the handler, yet answers with the first one's response
+    assert_eq!(fetch(format!("{second}/quotes")).await, ("HIT".to_string(), json!({ "call": 1 })));
+}
+
+#[tokio::test]
+async fn test_entries_prefixed_and_expire_with_route_ttl() {
+    let redis = FakeRedis::default();
+    let base = replica(RedisResponseCache::new(redis.clone()).key_prefix("shop:")).await;
+    fetch(format!("{base}/quotes")).await;
+    fetch(format!("{base}/flash")).await;
+
+    let expiries = redis.expiries.lock().unwrap();
+    let mut stored: Vec<(String, u64)> = expiries.iter().map(|(k, ms)| (String::from_utf8_lossy(k).into_owned(), *ms)).collect();
+    stored.sort();
+    assert_eq!(stored.len(), 2);
+    assert!(stored.iter().all(|(key, _)| key.starts_with("shop:")));
+    assert_eq!(stored.iter().map(|(_, ms)| *ms).collect::<Vec<_>>(), [1000, 30000]);
+}
+
+#[tokio::test]
+async fn test_unreachable_redis_fails_open() {
+    let redis = FakeRedis::default();
+    redis.down.store(true, Ordering::SeqCst);
+    let base = replica(RedisResponseCache::new(redis.clone())).await;
+
+    assert_eq!(fetch(format!("{base}/quotes")).await.1, json!({ "call": 1 }));
+    assert_eq!(fetch(format!("{base}/quotes")).await.1, json!({ "call": 2 }));
+
+    // Back up: entries are stored again
+    redis.down.store(false, Ordering::SeqCst);
+    fetch(format!("{base}/quotes")).await;
+    assert_eq!(fetch(format!("{base}/quotes")).await, ("HIT".to_string(), json!({ "call": 3 })));
+}
+
+#[tokio::test]
+async fn test_real_redis_shared_across_replicas() {
+    let Ok(url) = std::env::var(REDIS_URL_VAR) else {
+        eprintln!("{REDIS_URL_VAR} is not set, skipping");
+        return;
+    };
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    let prefix = format!("hayai-test:{}:{}:", std::process::id(), nanos);
+    let connect = || async { RedisResponseCache::connect(&url).await.unwrap().key_prefix(&prefix) };
+    let first = replica(connect().await).await;
+    let second = replica(connect().await).await;
+
+    assert_eq!(fetch(format!("{first}/quotes")).await.0, "MISS");
+    assert_eq!(fetch(format!("{second}/quotes")).await, ("HIT".to_string(), json!({ "call": 1 })));
+
+    assert_eq!(fetch(format!("{first}/flash")).await.0, "MISS");
+    assert_eq!(fetch(format!("{second}/flash")).await.0, "HIT");
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    assert_eq!(fetch(format!("{second}/flash")).await, ("MISS".to_string(), json!({ "call": 1 })));
+}
+
+#[tokio::test]
+async fn test_rate_limit_shared_across_replicas() {
+    let redis = FakeRedis::default();
+    let first = RedisRateLimiter::new(redis.clone());
+    let second = RedisRateLimiter::new(redis.clone());
+    let limit = RateLimit::new(3, Duration::from_secs(60));
+
+    let mut seen = Vec::new();
+    for limiter in [&first, &second, &first, &second] {
+        let decision = limiter.hit("ip:10.0.0.1", limit).await;
+        seen.push((decision.allowed, decision.remaining));
+    }
+    assert_eq!(seen, [(true, 2), (true, 1), (true, 0), (false, 0)]);
+    // Other keys count on their own
+    assert!(first.hit("ip:10.0.0.2", limit).await.allowed);
+
+    let decision = second.hit("ip:10.0.0.1", limit).await;
+    assert!(decision.reset_after > Duration::from_secs(59) && decision.reset_after <= Duration::from_secs(60));
+    assert_eq!(redis.expiries.lock().unwrap().get(b"hayai:rate:ip:10.0.0.1".as_slice()), Some(&60000));
+}
+
+#[tokio::test]
+async fn test_rate_limit_window_rolls_over() {
+    let limiter = RedisRateLimiter::new(FakeRedis::default()).key_prefix("shop:rate:");
+    let limit = RateLimit::new(1, Duration::from_millis(200));
+
+    assert!(limiter.hit("key", limit).await.allowed);
+    assert!(!limiter.hit("key", limit).await.allowed);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let decision = limiter.hit("key", limit).await;
+    assert!(decision.allowed);
+    assert_eq!(decision.remaining, 0);
+}
+
+#[tokio::test]
+async fn test_rate_limit_unreachable_redis_fails_open_unless_closed() {
+    let redis = FakeRedis::default();
+    redis.down.store(true, Ordering::SeqCst);
+    let limit = RateLimit::new(1, Duration::from_secs(60));
+
+    let open = RedisRateLimiter::new(redis.clone());
+    for _ in 0..3 {
+        assert!(open.hit("key", limit).await.allowed);
+    }
+    let closed = RedisRateLimiter::new(redis.clone()).fail_closed();
+    let decision = closed.hit("key", limit).await;
+    assert!(!decision.allowed);
+    assert_eq!(decision.reset_after, Duration::from_secs(60));
+}
+
+#[tokio::test]
+async fn test_idempotent_request_replayed_by_another_replica() {
+    let redis = FakeRedis::default();
+    let first = order_replica(RedisIdempotencyStore::new(redis.clone())).await;
+    let second = order_replica(RedisIdempotencyStore::new(redis.clone())).await;
+
+    assert_eq!(order(format!("{first}/orders/k1")).await, (201, json!({ "order": 1 })));
+    // The second replica never ran the handler, yet answers with the first one's order
+    assert_eq!(order(format!("{second}/orders/k1")).await, (201, json!({ "order": 1 })));
+    // A new key runs on the replica it lands on
+    assert_eq!(order(format!("{second}/orders/k2")).await, (201, json!({ "order": 1 })));
+    assert_eq!(redis.expiries.lock().unwrap().get(b"hayai:idempotency:k1".as_slice()), Some(&60000));
+}
+
+#[tokio::test]
+async fn test_idempotency_claims_exclusive_until_completed_or_released() {
+    let store = RedisIdempotencyStore::new(FakeRedis::default()).key_prefix("shop:idem:");
+    let ttl = Duration::from_secs(60);
+
+    assert!(matches!(store.claim("k", ttl).await, Ok(Claim::New)));
+    assert!(matches!(store.claim("k", ttl).await, Ok(Claim::InProgress)));
+    store.release("k").await.unwrap();
+    assert!(matches!(store.claim("k", ttl).await, Ok(Claim::New)));
+
+    let response = CachedResponse { status: 201, headers: vec![("location".to_string(), b"/orders/7".to_vec())], body: "{}".into() };
+    store.complete("k", response, ttl).await.unwrap();
+    let Ok(Claim::Completed(replayed)) = store.claim("k", ttl).await else { panic!("expected the stored response") };
+    assert_eq!((replayed.status, replayed.headers), (201, vec![("location".to_string(), b"/orders/7".to_vec())]));
+}
+
+#[tokio::test]
+async fn test_idempotency_unreachable_redis_fails_closed_unless_open() {
+    let redis = FakeRedis::default();
+    redis.down.store(true, Ordering::SeqCst);
+    let base = order_replica(RedisIdempotencyStore::new(redis.clone())).await;
+    assert_eq!(order(format!("{base}/orders/k1")).await.0, 503);
+
+    let store = RedisIdempotencyStore::new(redis.clone()).fail_open();
+    assert!(matches!(store.claim("k1", Duration::from_secs(60)).await, Ok(Claim::New)));
+    assert!(store.release("k1").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_real_redis_rate_limit_and_idempotency() {
+    let Ok(url) = std::env::var(REDIS_URL_VAR) else {
+        eprintln!("{REDIS_URL_VAR} is not set, skipping");
+        return;
+    };
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    let prefix = format!("hayai-test:{}:{}:", std::process::id(), nanos);
+
+    let first = RedisRateLimiter::connect(&url).await.unwrap().key_prefix(&prefix);
+    let second = RedisRateLimiter::connect(&url).await.unwrap().key_prefix(&prefix);
+    let limit = RateLimit::new(1, Duration::from_millis(500));
+    assert!(first.hit("rate", limit).await.allowed);
+    assert!(!second.hit("rate", limit).await.allowed);
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert!(second.hit("rate", limit).await.allowed);
+
+    let first = RedisIdempotencyStore::connect(&url).await.unwrap().key_prefix(&prefix);
+    let second = RedisIdempotencyStore::connect(&url).await.unwrap().key_prefix(&prefix);
+    let ttl = Duration::from_secs(5);
+    assert!(matches!(first.claim("idem", ttl).await, Ok(Claim::New)));
+    assert!(matches!(second.claim("idem", ttl).await, Ok(Claim::InProgress)));
+    first.complete("idem", CachedResponse { status: 200, headers: vec![], body: "done".into() }, ttl).await.unwrap();
+    let Ok(Claim::Completed(replayed)) = second.claim("idem", ttl).await else { panic!("expected the stored response") };
+    assert_eq!(replayed.body, "done");
+}
@@ -0,0 +1,201 @@
+use hayai::prelude::*;
+use hayai::axum;
+use hayai::config::ConfigSource;
+use hayai::reload::{ConfigWatcher, FileWatcher};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+
+#[derive(Debug, Deserialize)]
+struct Settings {
+    greeting: String,
+}
+
+/// Holds `/greeting/slow` between loading its snapshot and answering
+#[derive(Default)]
+struct Gate {
+    entered: Notify,
+    release: Notify,
+}
+
+#[get("/greeting")]
+async fn greeting(settings: Dep<Reloadable<Settings>>) -> Value {
+    json!(settings.load().greeting)
+}
+
+#[get("/greeting/slow")]
+async fn slow_greeting(settings: Dep<Reloadable<Settings>>, gate: Dep<Arc<Gate>>) -> Value {
+    let settings = settings.load();
+    gate.entered.notify_one();
+    gate.release.notified().await;
+    json!(settings.greeting)
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_GREETING).route(__HAYAI_ROUTE_SLOW_GREETING))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn current(base: &str) -> String {
+    reqwest::get(format!("{base}/greeting")).await.unwrap().json().await.unwrap()
+}
+
+/// Poll until the watcher task has stored `expected`
+async fn wait_for(base: &str, expected: &str) {
+    for _ in 0..200 {
+        if current(base).await == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("greeting never became {expected:?}, still {:?}", current(base).await);
+}
+
+#[tokio::test]
+async fn test_channel_updates_apply_to_next_request() {
+    let (updates, receiver) = mpsc::channel::<Value>(4);
+    let gate = Arc::new(Gate::default());
+    let base = spawn(HayaiApp::new()
+        .dep(gate.clone())
+        .reloadable_config(Settings { greeting: "hello".to_string() }, receiver)).await;
+    assert_eq!(current(&base).await, "hello");
+
+    let in_flight = tokio::spawn(reqwest::get(format!("{base}/greeting/slow")));
+    gate.entered.notified().await;
+
+    updates.send(json!({ "greeting": "hi" })).await.unwrap();
+    wait_for(&base, "hi").await;
+
+    // The request that started before the reload finishes with its snapshot
+    gate.release.notify_one();
+    let res = in_flight.await.unwrap().unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.json::<Value>().await.unwrap(), "hello");
+
+    // A document that doesn't deserialize is rejected and the current value kept
+    updates.send(json!({ "greeting": 5 })).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(current(&base).await, "hi");
+    updates.send(json!({ "greeting": "hey" })).await.unwrap();
+    wait_for(&base, "hey").await;
+}
+
+#[tokio::test]
+async fn test_file_watcher_reloads_on_change() {
+    let path = std::env::temp_dir().join(format!("hayai-reload-{}.toml", std::process::id()));
+    std::fs::write(&path, "greeting = \"hello\"\n").unwrap();
+    let source = ConfigSource::env("HAYAI_RELOAD_TEST_").file(&path);
+    let initial: Settings = source.load().unwrap();
+    let base = spawn(HayaiApp::new()
+        .dep(Arc::new(Gate::default()))
+        .reloadable_config(initial, FileWatcher::new(source).interval(Duration::from_millis(10)))).await;
+    assert_eq!(current(&base).await, "hello");
+
+    std::fs::write(&path, "greeting = \"bonjour\"\n").unwrap();
+    wait_for(&base, "bonjour").await;
+
+    std::fs::write(&path, "greeting = [unclosed\n").unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(current(&base).await, "bonjour");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_validated_watcher_rejects_invalid_values() {
+    let (updates, receiver) = mpsc::channel::<Value>(4);
+    let watcher = receiver.validated(|settings: &Settings| {
+        if settings.greeting.is_empty() { Err("greeting: must not be empty".to_string()) } else { Ok(()) }
+    });
+    let base = spawn(HayaiApp::new()
+        .dep(Arc::new(Gate::default()))
+        .reloadable_config(Settings { greeting: "hello".to_string() }, watcher)).await;
+
+    // Deserializes, but the check refuses it
+    updates.send(json!({ "greeting": "" })).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(current(&base).await, "hello");
+    updates.send(json!({ "greeting": "hi" })).await.unwrap();
+    wait_for(&base, "hi").await;
+}
+
+/// Set once the watcher holding it is dropped
+struct Stopped(Arc<AtomicBool>);
+
+impl Drop for Stopped {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A watcher that never produces a value, and whether it has been stopped
+fn idle_watcher() -> (impl ConfigWatcher<Settings>, Arc<AtomicBool>) {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let guard = Stopped(stopped.clone());
+    let watcher = move || {
+        let _guard = &guard;
+        std::future::pending::<Option<Result<Settings, hayai::config::ConfigError>>>()
+    };
+    (watcher, stopped)
+}
+
+async fn wait_stopped(stopped: &AtomicBool) {
+    for _ in 0..200 {
+        if stopped.load(Ordering::SeqCst) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("watcher still running");
+}
+
+#[get("/versioned")]
+#[version(1)]
+async fn versioned() -> Value {
+    json!(1)
+}
+
+#[tokio::test]
+async fn test_watchers_stop_with_the_router() {
+    let (watcher, stopped) = idle_watcher();
+    let router = HayaiApp::new()
+        .reloadable_config(Settings { greeting: "hello".to_string() }, watcher)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_GREETING))
+        .into_router();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!stopped.load(Ordering::SeqCst));
+    drop(router);
+    wait_stopped(&stopped).await;
+
+    // A check failing after the watchers started stops them too
+    let (watcher, stopped) = idle_watcher();
+    let app = HayaiApp::new()
+        .reloadable_config(Settings { greeting: "hello".to_string() }, watcher)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_VERSIONED));
+    let failed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| app.into_router()));
+    assert!(failed.is_err());
+    wait_stopped(&stopped).await;
+}
+
+#[tokio::test]
+async fn test_watchers_stop_at_shutdown() {
+    let (watcher, stopped) = idle_watcher();
+    let shutdown = Arc::new(Notify::new());
+    let signal = shutdown.clone();
+    let server = tokio::spawn(HayaiApp::new()
+        .reloadable_config(Settings { greeting: "hello".to_string() }, watcher)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_GREETING))
+        .serve_with_shutdown("127.0.0.1:0", async move { signal.notified().await }));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!stopped.load(Ordering::SeqCst));
+    shutdown.notify_one();
+    wait_stopped(&stopped).await;
+    server.await.unwrap().unwrap();
+}
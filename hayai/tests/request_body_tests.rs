@@ -0,0 +1,107 @@
+#![cfg(feature = "openapi")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+
+#[api_model]
+struct CreateUser {
+    #[validate(min_length = 1)]
+    name: String,
+}
+
+#[api_model]
+struct Invite {
+    email: String,
+}
+
+#[post("/users")]
+async fn create_user(body: CreateUser) -> Value {
+    json!({ "name": body.name })
+}
+
+#[put("/users/{id}")]
+async fn replace_user(id: i64, body: CreateUser) -> Value {
+    json!({ "id": id, "name": body.name })
+}
+
+#[post("/invites")]
+#[request_body(name = "InviteBody", description = "Who to invite")]
+async fn invite(body: Invite) -> Value {
+    json!({ "email": body.email })
+}
+
+#[post("/notes")]
+#[consumes("text/plain")]
+async fn add_note(body: String) -> Value {
+    json!({ "note": body })
+}
+
+#[post("/guests")]
+#[request_body(name = "InviteBody")]
+async fn add_guest(body: CreateUser) -> Value {
+    json!({ "name": body.name })
+}
+
+async fn spec(reuse: bool) -> Value {
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .reuse_request_bodies(reuse)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_CREATE_USER)
+            .route(__HAYAI_ROUTE_REPLACE_USER)
+            .route(__HAYAI_ROUTE_INVITE)
+            .route(__HAYAI_ROUTE_ADD_NOTE))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    reqwest::get(format!("http://{addr}/openapi.json")).await.unwrap().json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_reused_request_bodies_are_components() {
+    let spec = spec(true).await;
+    let body = &spec["components"]["requestBodies"]["CreateUser"];
+    assert_eq!(body["required"], true);
+    assert_eq!(body["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/CreateUser");
+    assert_eq!(body["content"]["application/json"]["example"], json!({ "name": "string" }));
+
+    let reference = json!({ "$ref": "#/components/requestBodies/CreateUser" });
+    assert_eq!(spec["paths"]["/users"]["post"]["requestBody"], reference);
+    assert_eq!(spec["paths"]["/users/{id}"]["put"]["requestBody"], reference);
+
+    // Bodies without a schema component stay inline
+    assert_eq!(spec["paths"]["/notes"]["post"]["requestBody"]["content"]["text/plain"]["schema"]["type"], "string");
+    assert_eq!(spec["components"]["requestBodies"].as_object().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_named_request_body() {
+    for reuse in [true, false] {
+        let spec = spec(reuse).await;
+        let body = &spec["components"]["requestBodies"]["InviteBody"];
+        assert_eq!(body["description"], "Who to invite");
+        assert_eq!(body["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/Invite");
+        assert_eq!(spec["paths"]["/invites"]["post"]["requestBody"], json!({ "$ref": "#/components/requestBodies/InviteBody" }));
+    }
+}
+
+#[tokio::test]
+async fn test_request_bodies_inline_without_reuse() {
+    let spec = spec(false).await;
+    assert!(spec["components"]["requestBodies"].get("CreateUser").is_none());
+    for (path, method) in [("/users", "post"), ("/users/{id}", "put")] {
+        let body = &spec["paths"][path][method]["requestBody"];
+        assert!(body.get("$ref").is_none());
+        assert_eq!(body["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/CreateUser");
+    }
+}
+
+#[test]
+#[should_panic(expected = "Request body component defined differently: InviteBody (POST /guests and POST /invites)")]
+fn test_conflicting_request_body_names_rejected() {
+    let _ = HayaiApp::new()
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_INVITE).route(__HAYAI_ROUTE_ADD_GUEST))
+        .into_router();
+}
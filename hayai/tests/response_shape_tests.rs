@@ -0,0 +1,198 @@
+#![cfg(feature = "openapi")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+#[api_model]
+struct UserStats {
+    posts: i64,
+    followers: i64,
+}
+
+#[api_model]
+struct User {
+    id: i64,
+    name: String,
+}
+
+/// Serializable, but not a model: there is no component for it
+#[derive(serde::Serialize)]
+struct Unregistered {
+    label: String,
+}
+
+#[get("/stats")]
+async fn stats_by_user() -> HashMap<String, UserStats> {
+    [("ada".to_string(), UserStats { posts: 3, followers: 10 })].into_iter().collect()
+}
+
+#[get("/stats/counts")]
+async fn counts_by_user() -> Result<BTreeMap<String, i64>, ApiError> {
+    Ok([("ada".to_string(), 3)].into_iter().collect())
+}
+
+#[get("/users/by-team")]
+async fn users_by_team() -> HashMap<String, Vec<User>> {
+    HashMap::new()
+}
+
+#[get("/users/slots")]
+async fn user_slots() -> Vec<Option<User>> {
+    vec![Some(User { id: 1, name: "Ada".to_string() }), None]
+}
+
+#[get("/users/last-seen")]
+async fn last_seen() -> HashMap<String, Option<String>> {
+    [("ada".to_string(), None)].into_iter().collect()
+}
+
+#[get("/labels")]
+async fn labels() -> HashMap<String, Unregistered> {
+    HashMap::new()
+}
+
+/// A third-party builder's output, which can't carry `#[api_model]`
+mod vendor {
+    #[derive(serde::Serialize)]
+    pub struct Quote {
+        pub symbol: String,
+        pub price: f64,
+    }
+
+    pub struct QuoteBuilder(pub Vec<(String, f64)>);
+
+    impl QuoteBuilder {
+        pub fn build(self) -> Vec<Quote> {
+            self.0.into_iter().map(|(symbol, price)| Quote { symbol, price }).collect()
+        }
+    }
+}
+
+#[get("/quotes/latest")]
+#[response(schema = inline({
+    "type": "object",
+    "required": ["symbol", "price"],
+    "properties": { "symbol": { "type": "string" }, "price": { "type": "number" } },
+}))]
+async fn latest_quote() -> impl SerializeResponse {
+    vendor::QuoteBuilder(vec![("ACME".to_string(), 12.5)]).build().remove(0)
+}
+
+/// A registered model with the same shape documents the vendor type
+#[api_model]
+struct Quote {
+    symbol: String,
+    price: f64,
+}
+
+#[get("/quotes")]
+#[response(schema = "Quote")]
+async fn list_quotes() -> Result<Vec<impl SerializeResponse>, ApiError> {
+    Ok(vendor::QuoteBuilder(vec![("ACME".to_string(), 12.5), ("INIT".to_string(), 3.0)]).build())
+}
+
+#[get("/users/{id}/profile")]
+#[response(tuple)]
+async fn get_profile(id: i64) -> (User, Vec<UserStats>) {
+    (User { id, name: "Ada".to_string() }, Vec::new())
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_STATS_BY_USER)
+            .route(__HAYAI_ROUTE_COUNTS_BY_USER)
+            .route(__HAYAI_ROUTE_USERS_BY_TEAM)
+            .route(__HAYAI_ROUTE_USER_SLOTS)
+            .route(__HAYAI_ROUTE_LAST_SEEN)
+            .route(__HAYAI_ROUTE_LABELS)
+            .route(__HAYAI_ROUTE_GET_PROFILE)
+            .route(__HAYAI_ROUTE_LATEST_QUOTE)
+            .route(__HAYAI_ROUTE_LIST_QUOTES))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn get(url: String) -> Value {
+    reqwest::get(url).await.unwrap().json().await.unwrap()
+}
+
+async fn spec() -> Value {
+    get(format!("{}/openapi.json", spawn().await)).await
+}
+
+fn response_schema(spec: &Value, path: &str) -> Value {
+    spec["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]["schema"].clone()
+}
+
+#[tokio::test]
+async fn test_map_responses_document_their_values() {
+    let spec = spec().await;
+    assert_eq!(response_schema(&spec, "/stats"), json!({
+        "type": "object",
+        "additionalProperties": { "$ref": "#/components/schemas/UserStats" },
+    }));
+    assert_eq!(response_schema(&spec, "/stats/counts"), json!({
+        "type": "object",
+        "additionalProperties": { "type": "integer" },
+    }));
+    assert_eq!(response_schema(&spec, "/users/by-team"), json!({
+        "type": "object",
+        "additionalProperties": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+    }));
+    // No component to point to, so no `$ref`
+    assert_eq!(response_schema(&spec, "/labels"), json!({ "type": "object", "additionalProperties": {} }));
+    assert!(spec["components"]["schemas"].get("HashMap").is_none());
+    assert!(!spec.to_string().contains("#/components/schemas/Unregistered"));
+}
+
+#[tokio::test]
+async fn test_option_elements_are_nullable() {
+    let spec = spec().await;
+    assert_eq!(response_schema(&spec, "/users/slots"), json!({
+        "type": "array",
+        "items": { "anyOf": [{ "$ref": "#/components/schemas/User" }, { "type": "null" }] },
+    }));
+    assert_eq!(response_schema(&spec, "/users/last-seen"), json!({
+        "type": "object",
+        "additionalProperties": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+    }));
+}
+
+#[tokio::test]
+async fn test_tuple_response_is_a_positional_array() {
+    let spec = spec().await;
+    assert_eq!(response_schema(&spec, "/users/{id}/profile"), json!({
+        "type": "array",
+        "prefixItems": [
+            { "$ref": "#/components/schemas/User" },
+            { "type": "array", "items": { "$ref": "#/components/schemas/UserStats" } },
+        ],
+        "minItems": 2,
+        "maxItems": 2,
+    }));
+}
+
+#[tokio::test]
+async fn test_opaque_response_served_with_declared_schema() {
+    let base = spawn().await;
+    assert_eq!(get(format!("{base}/quotes/latest")).await, json!({ "symbol": "ACME", "price": 12.5 }));
+    assert_eq!(get(format!("{base}/quotes")).await[1], json!({ "symbol": "INIT", "price": 3.0 }));
+
+    let spec = get(format!("{base}/openapi.json")).await;
+    assert_eq!(response_schema(&spec, "/quotes/latest"), json!({
+        "type": "object",
+        "required": ["symbol", "price"],
+        "properties": { "symbol": { "type": "string" }, "price": { "type": "number" } },
+    }));
+    assert_eq!(response_schema(&spec, "/quotes"), json!({
+        "type": "array",
+        "items": { "$ref": "#/components/schemas/Quote" },
+    }));
+}
@@ -0,0 +1,99 @@
+use hayai::prelude::*;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct Events(Log);
+
+fn record(log: &Log, entry: String) {
+    log.lock().unwrap().push(entry);
+}
+
+fn workers() -> usize {
+    tokio::runtime::Handle::current().metrics().num_workers()
+}
+
+#[get("/runtime")]
+async fn runtime_info(events: Dep<Events>) -> Value {
+    record(&events.0, "request".to_string());
+    let blocking_thread = tokio::task::spawn_blocking(|| std::thread::current().name().map(str::to_string)).await.unwrap();
+    json!({
+        "thread": std::thread::current().name(),
+        "blocking_thread": blocking_thread,
+        "workers": workers(),
+    })
+}
+
+/// Serve on a runtime of the app's own from a plain thread, as a `main` without
+/// `#[tokio::main]` would, and GET `/runtime` once it listens
+fn serve_and_get(config: RuntimeConfig, log: &Log) -> Value {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let app = HayaiApp::new()
+        .runtime(config)
+        .dep(Events(log.clone()))
+        .on_startup({
+            let log = log.clone();
+            move |_| async move {
+                record(&log, format!("startup on {} workers", workers()));
+                Ok(())
+            }
+        })
+        .on_worker_start({
+            let log = log.clone();
+            move |handle| record(&log, format!("worker_start on {} workers", handle.metrics().num_workers()))
+        })
+        .on_shutdown({
+            let log = log.clone();
+            move |_| async move {
+                record(&log, "shutdown".to_string());
+                Ok(())
+            }
+        })
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_RUNTIME_INFO));
+
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = {
+        let addr = addr.clone();
+        std::thread::spawn(move || app.serve_blocking_with_shutdown(&addr, async { let _ = stop_rx.await; }))
+    };
+
+    let client = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let body = client.block_on(async {
+        while tokio::net::TcpStream::connect(&addr).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        reqwest::get(format!("http://{addr}/runtime")).await.unwrap().json().await.unwrap()
+    });
+    stop_tx.send(()).unwrap();
+    server.join().unwrap().unwrap();
+    body
+}
+
+#[test]
+fn test_runtime_config_shapes_the_serving_runtime() {
+    let log = Log::default();
+    let body = serve_and_get(RuntimeConfig {
+        worker_threads: Some(3),
+        max_blocking_threads: 2,
+        thread_name: "orders-api".to_string(),
+    }, &log);
+
+    assert_eq!(body["workers"], 3);
+    assert!(body["thread"].as_str().unwrap().starts_with("orders-api-"), "{body}");
+    assert!(body["blocking_thread"].as_str().unwrap().starts_with("orders-api-"), "{body}");
+}
+
+#[test]
+fn test_hooks_run_in_order_on_the_built_runtime() {
+    let log = Log::default();
+    serve_and_get(RuntimeConfig { worker_threads: Some(2), ..Default::default() }, &log);
+
+    assert_eq!(*log.lock().unwrap(), [
+        "startup on 2 workers",
+        "worker_start on 2 workers",
+        "request",
+        "shutdown",
+    ]);
+}
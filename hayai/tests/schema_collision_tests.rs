@@ -0,0 +1,58 @@
+#![cfg(feature = "openapi")]
+
+use hayai::prelude::*;
+
+// Two models registered under the same component name with different shapes
+
+mod billing {
+    use hayai::prelude::*;
+
+    #[api_model]
+    #[derive(Debug, Clone)]
+    pub struct Settings {
+        pub plan: String,
+        pub seats: i64,
+    }
+}
+
+mod profile {
+    use hayai::prelude::*;
+
+    #[api_model]
+    #[derive(Debug, Clone)]
+    pub struct Settings {
+        pub theme: String,
+    }
+}
+
+#[get("/billing/settings")]
+async fn billing_settings() -> billing::Settings {
+    billing::Settings { plan: "pro".into(), seats: 3 }
+}
+
+#[get("/profile/settings")]
+async fn profile_settings() -> profile::Settings {
+    profile::Settings { theme: "dark".into() }
+}
+
+#[cfg(feature = "openapi")]
+#[test]
+fn test_schema_collisions_list_both_definitions() {
+    let collisions = HayaiApp::schema_collisions();
+    assert_eq!(collisions.len(), 1);
+    let msg = &collisions[0];
+    assert!(msg.starts_with("`Settings` is defined in"), "{msg}");
+    assert!(msg.contains("schema_collision_tests::billing") && msg.contains("{plan, seats}"), "{msg}");
+    assert!(msg.contains("schema_collision_tests::profile") && msg.contains("{theme}"), "{msg}");
+}
+
+#[test]
+#[should_panic(expected = "Schema component name collision")]
+fn test_collision_fails_spec_build() {
+    let _ = HayaiApp::new().into_router();
+}
+
+#[test]
+fn test_minimal_app_skips_spec_build() {
+    let _ = HayaiApp::minimal().route_explicit(__HAYAI_ROUTE_BILLING_SETTINGS).into_router();
+}
@@ -0,0 +1,102 @@
+use hayai::prelude::*;
+use hayai::axum;
+use hayai::proxy::ProxyConfig;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+#[get("/widgets")]
+async fn list_widgets() -> Value {
+    json!(["sprocket"])
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_LIST_WIDGETS))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await.unwrap()
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_default_headers_on_api_responses() {
+    let base = spawn(HayaiApp::new().security_headers(SecurityHeaders::default())).await;
+
+    let res = reqwest::get(format!("{base}/widgets")).await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+    assert_eq!(res.headers()["x-frame-options"], "DENY");
+    assert_eq!(res.headers()["referrer-policy"], "no-referrer");
+    // Plain HTTP: HSTS would be ignored, and JSON needs no CSP
+    assert!(res.headers().get("strict-transport-security").is_none());
+    assert!(res.headers().get("content-security-policy").is_none());
+
+    // Errors the framework answers get them too
+    let res = reqwest::get(format!("{base}/missing")).await.unwrap();
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+}
+
+#[tokio::test]
+async fn test_hsts_only_over_https() {
+    let base = spawn(HayaiApp::new()
+        .behind_proxy(ProxyConfig::default())
+        .security_headers(SecurityHeaders::default())).await;
+    let client = reqwest::Client::new();
+
+    let res = client.get(format!("{base}/widgets")).header("x-forwarded-proto", "https").send().await.unwrap();
+    assert_eq!(res.headers()["strict-transport-security"], "max-age=31536000; includeSubDomains");
+    let res = client.get(format!("{base}/widgets")).header("x-forwarded-proto", "http").send().await.unwrap();
+    assert!(res.headers().get("strict-transport-security").is_none());
+
+    let base = spawn(HayaiApp::new().security_headers(SecurityHeaders::default()
+        .hsts(Duration::from_secs(600), false)
+        .force_hsts())).await;
+    let res = reqwest::get(format!("{base}/widgets")).await.unwrap();
+    assert_eq!(res.headers()["strict-transport-security"], "max-age=600");
+}
+
+#[tokio::test]
+async fn test_overridden_and_removed_headers() {
+    let base = spawn(HayaiApp::new().security_headers(SecurityHeaders::default()
+        .frame_options(FrameOptions::SameOrigin)
+        .referrer_policy("strict-origin-when-cross-origin")
+        .without("X-Content-Type-Options"))).await;
+
+    let res = reqwest::get(format!("{base}/widgets")).await.unwrap();
+    assert_eq!(res.headers()["x-frame-options"], "SAMEORIGIN");
+    assert_eq!(res.headers()["referrer-policy"], "strict-origin-when-cross-origin");
+    assert!(res.headers().get("x-content-type-options").is_none());
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_docs_render_under_their_csp() {
+    let base = spawn(HayaiApp::new().security_headers(SecurityHeaders::default())).await;
+
+    let res = reqwest::get(format!("{base}/docs")).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let csp = res.headers()["content-security-policy"].to_str().unwrap().to_string();
+    assert!(csp.contains("style-src 'self' 'unsafe-inline'"), "{csp}");
+    assert!(csp.contains("connect-src 'self'"), "{csp}");
+    let html = res.text().await.unwrap();
+    assert!(html.contains("<script src=\"/docs/assets/scalar.min.js"), "{html}");
+
+    // The Swagger page runs an inline script and loads its bundle from the CDN
+    let base = spawn(HayaiApp::new()
+        .docs_assets(hayai::docs::DocsAssets::Cdn("https://cdn.example.com/swagger-ui/5".to_string()))
+        .security_headers(SecurityHeaders::default())).await;
+    let res = reqwest::get(format!("{base}/docs")).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let csp = res.headers()["content-security-policy"].to_str().unwrap().to_string();
+    assert!(csp.contains("script-src 'self' 'unsafe-inline' https://cdn.example.com;"), "{csp}");
+    assert!(res.text().await.unwrap().contains("SwaggerUIBundle("));
+
+    let base = spawn(HayaiApp::new()
+        .security_headers(SecurityHeaders::default().content_security_policy("default-src 'self'"))).await;
+    let res = reqwest::get(format!("{base}/docs")).await.unwrap();
+    assert_eq!(res.headers()["content-security-policy"], "default-src 'self'");
+}
@@ -0,0 +1,120 @@
+use hayai::prelude::*;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Larger than the loopback socket buffers, so writing it blocks on a client that doesn't read
+const EXPORT_SIZE: usize = 64 << 20;
+
+#[get("/ping")]
+async fn ping() -> String {
+    "pong".to_string()
+}
+
+#[get("/export")]
+async fn export() -> String {
+    "x".repeat(EXPORT_SIZE)
+}
+
+async fn spawn(config: HttpConfig) -> String {
+    let addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+    let app = HayaiApp::new()
+        .http_config(config)
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_PING).route(__HAYAI_ROUTE_EXPORT));
+    tokio::spawn(app.serve_with_shutdown(&addr, std::future::pending()));
+    while TcpStream::connect(&addr).await.is_err() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    addr
+}
+
+/// A GET on a connection of its own, so no pooled connection holds a slot
+async fn ping_once(addr: &str) -> Result<String, reqwest::Error> {
+    let client = reqwest::Client::builder().pool_max_idle_per_host(0).build().unwrap();
+    client.get(format!("http://{addr}/ping")).send().await?.error_for_status()?.text().await
+}
+
+/// Send a request on a raw connection without reading the response
+async fn send_request(addr: &str, path: &str) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\n\r\n").as_bytes()).await.unwrap();
+    stream
+}
+
+/// A connection the server is answering `path` on: it holds a slot
+async fn open_response(addr: &str, path: &str) -> TcpStream {
+    loop {
+        let mut stream = send_request(addr, path).await;
+        let mut status = [0; 12];
+        // Refused while a probe's connection still holds the slot
+        match stream.read_exact(&mut status).await {
+            Ok(_) => {
+                assert_eq!(&status, b"HTTP/1.1 200", "{path}");
+                return stream;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+}
+
+/// Read until the server closes the connection; how much arrived
+async fn read_until_closed(stream: &mut TcpStream) -> usize {
+    let mut buf = vec![0; 1 << 16];
+    let mut total = 0;
+    loop {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return total,
+            Ok(n) => total += n,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_stalled_reader_hits_write_timeout() {
+    let addr = spawn(HttpConfig {
+        write_timeout: Duration::from_millis(200),
+        max_concurrent_connections: 1,
+        ..Default::default()
+    }).await;
+
+    let mut stalled = open_response(&addr, "/export").await;
+    let started = Instant::now();
+    // The stalled connection holds the only slot until the write timeout closes it
+    while ping_once(&addr).await.is_err() {
+        assert!(started.elapsed() < Duration::from_secs(3), "stalled connection still open");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(started.elapsed() >= Duration::from_millis(200));
+
+    let received = tokio::time::timeout(Duration::from_secs(5), read_until_closed(&mut stalled)).await.unwrap();
+    assert!(received < EXPORT_SIZE, "the whole export arrived");
+}
+
+#[tokio::test]
+async fn test_connections_over_the_cap_are_closed() {
+    let addr = spawn(HttpConfig { max_concurrent_connections: 2, ..Default::default() }).await;
+
+    let mut held = Vec::new();
+    for _ in 0..2 {
+        held.push(open_response(&addr, "/ping").await);
+    }
+
+    let mut rejected = send_request(&addr, "/ping").await;
+    let received = tokio::time::timeout(Duration::from_secs(2), read_until_closed(&mut rejected)).await.unwrap();
+    assert_eq!(received, 0);
+    assert!(ping_once(&addr).await.is_err());
+
+    // A closed connection frees its slot
+    drop(held.pop());
+    let started = Instant::now();
+    loop {
+        match ping_once(&addr).await {
+            Ok(body) => break assert_eq!(body, "\"pong\""),
+            Err(_) => assert!(started.elapsed() < Duration::from_secs(2), "slot not freed"),
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
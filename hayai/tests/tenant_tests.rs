@@ -0,0 +1,83 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Stands in for a per-tenant connection pool
+struct Database {
+    name: String,
+}
+
+#[api_model]
+struct Workspace {
+    tenant: String,
+    database: String,
+}
+
+#[get("/workspace")]
+async fn show_workspace(tenant: Tenant, db: Dep<TenantScoped<Database>>) -> Workspace {
+    Workspace { tenant: tenant.id, database: db.name.clone() }
+}
+
+/// The app's address, and how often the database factory ran
+async fn spawn() -> (String, Arc<AtomicUsize>) {
+    let built = Arc::new(AtomicUsize::new(0));
+    let counter = built.clone();
+    let app = HayaiApp::new()
+        .tenant_resolver(TenantSource::header("X-Tenant-Id").known(["acme", "globex"]))
+        .tenant_scoped(move |tenant: Tenant| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(Database { name: format!("db_{}", tenant.id) }) }
+        })
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_SHOW_WORKSPACE))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}"), built)
+}
+
+async fn workspace(base: &str, tenant: &str) -> reqwest::Response {
+    reqwest::Client::new().get(format!("{base}/workspace"))
+        .header("X-Tenant-Id", tenant)
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_tenants_resolve_their_own_database() {
+    let (base, built) = spawn().await;
+    for _ in 0..2 {
+        let acme: Value = workspace(&base, "acme").await.json().await.unwrap();
+        assert_eq!(acme, serde_json::json!({ "tenant": "acme", "database": "db_acme" }));
+        let globex: Value = workspace(&base, "globex").await.json().await.unwrap();
+        assert_eq!(globex, serde_json::json!({ "tenant": "globex", "database": "db_globex" }));
+    }
+    assert_eq!(built.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_missing_and_unknown_tenants_are_rejected() {
+    let (base, built) = spawn().await;
+    let resp = reqwest::get(format!("{base}/workspace")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Missing X-Tenant-Id header");
+
+    let resp = workspace(&base, "initech").await;
+    assert_eq!(resp.status(), 404);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Unknown tenant `initech`");
+    assert_eq!(built.load(Ordering::SeqCst), 0);
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_tenant_header_is_documented() {
+    let (base, _) = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    assert_eq!(spec["components"]["parameters"]["TenantId"]["name"], "X-Tenant-Id");
+    assert_eq!(spec["components"]["parameters"]["TenantId"]["in"], "header");
+    let parameters = spec["paths"]["/workspace"]["get"]["parameters"].as_array().unwrap();
+    assert!(parameters.contains(&serde_json::json!({ "$ref": "#/components/parameters/TenantId" })));
+}
@@ -0,0 +1,99 @@
+#![cfg(all(feature = "testing", feature = "auth"))]
+
+use hayai::prelude::*;
+use hayai::testing::TestClient;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone)]
+struct Claims {
+    user_id: i64,
+    roles: Vec<String>,
+}
+
+impl SecurityValidator for Claims {
+    async fn validate(parts: &http::request::Parts) -> Result<Self, ApiError> {
+        match parts.headers.get("authorization").and_then(|v| v.to_str().ok()) {
+            Some("Bearer good-token") => Ok(Claims { user_id: 1, roles: vec!["reader".to_string()] }),
+            Some(_) => Err(ApiError::unauthorized("Invalid token")),
+            None => Err(ApiError::unauthorized("Missing Authorization header")),
+        }
+    }
+
+    fn subject(&self) -> String {
+        format!("user-{}", self.user_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ApiKeyClaims;
+
+impl SecurityValidator for ApiKeyClaims {
+    const SCHEME: &'static str = "api_key";
+
+    async fn validate(parts: &http::request::Parts) -> Result<Self, ApiError> {
+        match parts.headers.get("x-api-key") {
+            Some(key) if key == "secret" => Ok(ApiKeyClaims),
+            _ => Err(ApiError::unauthorized("Invalid API key")),
+        }
+    }
+}
+
+#[get("/me")]
+async fn me(auth: Auth<Claims>, subject: Subject) -> Value {
+    json!({ "user_id": auth.user_id, "roles": auth.roles, "subject": subject.0 })
+}
+
+#[get("/reports")]
+async fn reports(_auth: Auth<ApiKeyClaims>) -> Value {
+    json!({ "reports": [] })
+}
+
+fn client() -> TestClient {
+    TestClient::new(HayaiApp::new()
+        .bearer_auth()
+        .api_key_auth("X-Api-Key")
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_ME).route(__HAYAI_ROUTE_REPORTS)))
+}
+
+#[tokio::test]
+async fn test_injected_claims_reach_handler() {
+    let claims = Claims { user_id: 7, roles: vec!["admin".to_string(), "billing".to_string()] };
+    let res = client().authenticated_as(claims).get("/me").send().await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.json::<Value>(), json!({ "user_id": 7, "roles": ["admin", "billing"], "subject": "user-7" }));
+
+    // Injected claims win over whatever credentials the request carries
+    let res = client().with_bearer("bad-token").authenticated_as(Claims { user_id: 8, roles: vec![] }).get("/me").send().await;
+    assert_eq!(res.json::<Value>()["user_id"], 8);
+}
+
+#[tokio::test]
+async fn test_injected_claims_of_another_validator_fail() {
+    let res = client().authenticated_as(Claims { user_id: 7, roles: vec![] }).get("/reports").send().await;
+    assert_eq!(res.status(), 500);
+}
+
+#[tokio::test]
+async fn test_bearer_goes_through_validator() {
+    let res = client().with_bearer("good-token").get("/me").send().await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.json::<Value>(), json!({ "user_id": 1, "roles": ["reader"], "subject": "user-1" }));
+
+    let res = client().with_bearer("bad-token").get("/me").send().await;
+    assert_eq!(res.status(), 401);
+    assert_eq!(res.json::<Value>()["error"], "Invalid token");
+
+    assert_eq!(client().get("/me").send().await.status(), 401);
+}
+
+#[tokio::test]
+async fn test_api_key_goes_through_validator() {
+    assert_eq!(client().with_api_key("secret").get("/reports").send().await.status(), 200);
+    assert_eq!(client().with_api_key("wrong").get("/reports").send().await.status(), 401);
+}
+
+#[test]
+#[should_panic(expected = "with_api_key needs an app with `api_key_auth`")]
+fn test_api_key_needs_scheme() {
+    let _ = TestClient::new(HayaiApp::new().bearer_auth().include(HayaiRouter::new("").route(__HAYAI_ROUTE_ME))).with_api_key("secret");
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "testing")]
+
+use hayai::prelude::*;
+use hayai::testing::proptest::strategy::{Strategy, ValueTree};
+use hayai::testing::proptest::test_runner::TestRunner;
+use hayai::testing::{arbitrary_for_schema, fuzz_route};
+use serde_json::Value;
+
+#[api_model]
+#[derive(Debug, Clone, PartialEq)]
+enum Category {
+    Tools,
+    Garden,
+}
+
+#[api_model]
+struct Dimensions {
+    #[validate(minimum = 1, maximum = 500)]
+    width_cm: f64,
+    #[validate(minimum = 1, maximum = 500)]
+    height_cm: f64,
+}
+
+#[api_model]
+struct CreateItem {
+    #[validate(min_length = 1, max_length = 40)]
+    name: String,
+    #[validate(pattern = "^[A-Z]{3}-[0-9]{4}$")]
+    sku: String,
+    #[validate(minimum = 1)]
+    quantity: i64,
+    #[validate(minimum = 0)]
+    unit_price_cents: i64,
+    category: Category,
+    #[validate(min_items = 1)]
+    tags: Vec<String>,
+    dimensions: Option<Dimensions>,
+    contact: Option<String>,
+}
+
+#[api_model]
+struct Item {
+    name: String,
+    total_cents: i64,
+}
+
+/// Multiplies without checking: large quantities overflow
+#[post("/items")]
+async fn create_item(body: CreateItem) -> Item {
+    Item { name: body.name, total_cents: body.quantity * body.unit_price_cents }
+}
+
+#[post("/items/checked")]
+async fn create_item_checked(body: CreateItem) -> Result<Item, ApiError> {
+    let total_cents = body.quantity.checked_mul(body.unit_price_cents)
+        .ok_or_else(|| ApiError::validation_error(vec!["quantity: order total is too large".to_string()]))?;
+    Ok(Item { name: body.name, total_cents })
+}
+
+fn component(name: &str) -> hayai::openapi::Schema {
+    hayai::registry::schemas().into_iter().find(|s| s.component_name() == name).unwrap().schema()
+}
+
+#[test]
+fn test_generated_values_satisfy_the_schema() {
+    let strategy = arbitrary_for_schema(&component("CreateItem"));
+    let mut runner = TestRunner::default();
+    let (mut with_dimensions, mut without_contact) = (false, false);
+    for _ in 0..300 {
+        let value: Value = strategy.new_tree(&mut runner).unwrap().current();
+        let item: CreateItem = serde_json::from_value(value.clone()).unwrap_or_else(|e| panic!("{e}: {value}"));
+        assert_eq!(item.validate(), Ok(()), "{value}");
+        assert!(matches!(value["category"].as_str(), Some("Tools" | "Garden")), "{value}");
+        if let Some(dimensions) = &item.dimensions {
+            with_dimensions = true;
+            assert!((1.0..=500.0).contains(&dimensions.width_cm), "{value}");
+        }
+        without_contact |= value.get("contact").is_none();
+    }
+    assert!(with_dimensions && without_contact);
+}
+
+#[tokio::test]
+async fn test_fuzzing_finds_overflow() {
+    let router = HayaiApp::new()
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_ITEM))
+        .into_router();
+    let fuzzed = tokio::spawn(async move { fuzz_route(&router, __HAYAI_ROUTE_CREATE_ITEM, 500).await }).await;
+    let payload = fuzzed.expect_err("the overflow was not found").into_panic();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("POST /items panicked: attempt to multiply with overflow"), "{message}");
+    assert!(message.contains("request body: {"), "{message}");
+}
+
+#[tokio::test]
+async fn test_fuzzing_passes_checked_handler() {
+    let router = HayaiApp::new()
+        .include(HayaiRouter::new("").route(__HAYAI_ROUTE_CREATE_ITEM_CHECKED))
+        .into_router();
+    fuzz_route(&router, __HAYAI_ROUTE_CREATE_ITEM_CHECKED, 300).await;
+}
@@ -0,0 +1,163 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<String>>>;
+type Finish = Pin<Box<dyn std::future::Future<Output = Result<(), ApiError>> + Send>>;
+
+/// Records every transaction step instead of talking to a database
+#[derive(Default)]
+struct Database {
+    log: Log,
+}
+
+struct FakeTx {
+    log: Log,
+}
+
+impl FakeTx {
+    fn execute(&mut self, statement: &str) {
+        self.log.lock().unwrap().push(statement.to_string());
+    }
+
+    fn finish(&self, step: &str) -> Finish {
+        self.log.lock().unwrap().push(step.to_string());
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+impl TxHandle for FakeTx {
+    fn commit(self: Box<Self>) -> Finish {
+        self.finish("commit")
+    }
+
+    fn rollback(self: Box<Self>) -> Finish {
+        self.finish("rollback")
+    }
+}
+
+impl RequestTransaction for Database {
+    async fn begin(&self) -> Result<Box<dyn TxHandle>, ApiError> {
+        self.log.lock().unwrap().push("begin".to_string());
+        Ok(Box::new(FakeTx { log: self.log.clone() }))
+    }
+}
+
+#[api_model]
+struct NewOrder {
+    #[validate(min_length = 1)]
+    item: String,
+}
+
+#[api_model]
+struct Order {
+    item: String,
+}
+
+#[post("/orders")]
+async fn create_order(body: NewOrder, tx: Tx) -> Result<Order, ApiError> {
+    let mut tx = tx.lock().await?;
+    tx.downcast_mut::<FakeTx>().unwrap().execute(&format!("insert {}", body.item));
+    if body.item == "sold-out" {
+        return Err(ApiError::conflict("Item sold out"));
+    }
+    Ok(Order { item: body.item })
+}
+
+#[get("/orders/count")]
+#[transactional(skip)]
+async fn count_orders() -> Value {
+    serde_json::json!({ "count": 0 })
+}
+
+#[post("/orders/archive")]
+#[transactional]
+async fn archive_orders(tx: Tx) -> Result<Value, ApiError> {
+    tx.lock().await?.downcast_mut::<FakeTx>().unwrap().execute("archive");
+    Ok(serde_json::json!({ "archived": true }))
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// The app's database and its address
+async fn spawn_transactional(annotated: bool) -> (Log, String) {
+    let db = Database::default();
+    let log = db.log.clone();
+    let app = HayaiApp::new().dep(db);
+    let app = if annotated { app.transactional_annotated::<Database>() } else { app.transactional::<Database>() };
+    (log, spawn(app).await)
+}
+
+fn take(log: &Log) -> Vec<String> {
+    std::mem::take(&mut *log.lock().unwrap())
+}
+
+#[tokio::test]
+async fn test_commit_on_success() {
+    let (log, base) = spawn_transactional(false).await;
+    let resp = reqwest::Client::new().post(format!("{base}/orders"))
+        .json(&serde_json::json!({ "item": "widget" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    assert_eq!(take(&log), ["begin", "insert widget", "commit"]);
+}
+
+#[cfg(feature = "validation")]
+#[tokio::test]
+async fn test_rollback_on_validation_failure() {
+    let (log, base) = spawn_transactional(false).await;
+    let resp = reqwest::Client::new().post(format!("{base}/orders"))
+        .json(&serde_json::json!({ "item": "" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 422);
+    assert_eq!(take(&log), ["begin", "rollback"]);
+}
+
+#[tokio::test]
+async fn test_rollback_on_handler_error() {
+    let (log, base) = spawn_transactional(false).await;
+    let resp = reqwest::Client::new().post(format!("{base}/orders"))
+        .json(&serde_json::json!({ "item": "sold-out" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 409);
+    assert_eq!(take(&log), ["begin", "insert sold-out", "rollback"]);
+}
+
+#[tokio::test]
+async fn test_skipped_and_unmatched_routes_run_without_transaction() {
+    let (log, base) = spawn_transactional(false).await;
+    assert_eq!(reqwest::get(format!("{base}/orders/count")).await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(format!("{base}/nowhere")).await.unwrap().status(), 404);
+    assert!(take(&log).is_empty());
+}
+
+#[tokio::test]
+async fn test_annotated_routes_only() {
+    let (log, base) = spawn_transactional(true).await;
+    let client = reqwest::Client::new();
+
+    let resp = client.post(format!("{base}/orders/archive")).send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    assert_eq!(take(&log), ["begin", "archive", "commit"]);
+
+    // Not annotated: no transaction, so asking for one is a server error
+    let resp = client.post(format!("{base}/orders"))
+        .json(&serde_json::json!({ "item": "widget" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 500);
+    assert!(take(&log).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "needs the dependency registered with .dep()")]
+fn test_transactional_requires_the_dependency() {
+    let _ = HayaiApp::new().transactional::<Database>().into_router();
+}
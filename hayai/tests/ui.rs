@@ -0,0 +1,9 @@
+// Compile-fail cases for the route and api_model macros; run with `TRYBUILD=overwrite`
+// to regenerate the expected messages after changing them.
+// The expected messages are those of the default features
+#[cfg(all(feature = "openapi", feature = "validation"))]
+#[test]
+fn macro_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
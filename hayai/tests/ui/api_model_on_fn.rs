@@ -0,0 +1,6 @@
+use hayai::prelude::*;
+
+#[api_model]
+fn not_a_model() {}
+
+fn main() {}
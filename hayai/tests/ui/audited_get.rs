@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/users/{id}")]
+#[audited(resource = "user")]
+async fn get_user(id: i64) -> String {
+    id.to_string()
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[post("/items")]
+#[status(999)]
+async fn create_item() -> String {
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/items")]
+async fn list_items(db: Dep) -> String {
+    let _ = db;
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use hayai::prelude::*;
+
+#[api_model]
+struct Ingest<'a> {
+    source: &'a str,
+}
+
+#[post("/ingest")]
+async fn ingest(event: Ingest<'_>) -> String {
+    event.source.to_string()
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use hayai::prelude::*;
+
+#[get("/account")]
+#[security("bearer")]
+#[cache(ttl = "30s")]
+async fn account() -> String {
+    "account".into()
+}
+
+fn main() {}
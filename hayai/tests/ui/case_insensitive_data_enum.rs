@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[api_model(enum_case_insensitive)]
+enum Shape {
+    Circle,
+    Square(u32),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use hayai::prelude::*;
+
+struct Database;
+
+#[get("/items")]
+async fn list_items(db: Dep<&Database>) -> String {
+    let _ = db;
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use hayai::prelude::*;
+
+#[derive(hayai::serde::Serialize, hayai::serde::Deserialize, hayai::schemars::JsonSchema, ApiModel)]
+#[serde(crate = "hayai::serde")]
+#[schemars(crate = "hayai::schemars")]
+struct Order {
+    #[schema(string_encoded)]
+    id: u64,
+}
+
+fn main() {}
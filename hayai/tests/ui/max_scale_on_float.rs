@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[api_model]
+struct Price {
+    #[validate(max_scale = 2)]
+    amount: f64,
+}
+
+fn main() {}
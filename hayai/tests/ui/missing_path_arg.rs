@@ -0,0 +1,8 @@
+use hayai::prelude::*;
+
+#[get("/items/{id}")]
+async fn get_item() -> String {
+    String::new()
+}
+
+fn main() {}
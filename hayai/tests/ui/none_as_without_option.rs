@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/notes/{id}")]
+#[none_as(204)]
+async fn get_note(id: i64) -> String {
+    id.to_string()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use hayai::prelude::*;
+
+#[get("/items")]
+fn list_items() -> String {
+    String::new()
+}
+
+fn main() {}
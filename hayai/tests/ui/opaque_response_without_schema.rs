@@ -0,0 +1,13 @@
+use hayai::prelude::*;
+
+#[derive(serde::Serialize)]
+struct Quote {
+    symbol: String,
+}
+
+#[get("/quotes/latest")]
+async fn latest_quote() -> impl SerializeResponse {
+    Quote { symbol: "ACME".to_string() }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/payments")]
+#[openapi_extension("internal-owner", "payments-team")]
+async fn list_payments() -> String {
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use hayai::prelude::*;
+
+#[delete("/sessions/{id}")]
+async fn end_session(id: i64) -> Option<()> {
+    (id > 0).then_some(())
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use hayai::prelude::*;
+
+#[get("/users/{id}")]
+#[param(id, max_length = 10)]
+#[param(name, minimum = 1)]
+async fn get_user(id: i64) -> String {
+    id.to_string()
+}
+
+fn main() {}
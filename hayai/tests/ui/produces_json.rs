@@ -0,0 +1,14 @@
+use hayai::prelude::*;
+
+#[api_model]
+struct Note {
+    text: String,
+}
+
+#[get("/notes")]
+#[produces("application/json")]
+async fn get_note() -> Note {
+    Note { text: "hi".to_string() }
+}
+
+fn main() {}
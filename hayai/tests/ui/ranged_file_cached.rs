@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/videos/{id}")]
+#[cache(ttl = "30s")]
+async fn video(id: i64) -> RangedFile {
+    RangedFile::new(0, move |_| std::io::Cursor::new(id.to_string().into_bytes()))
+}
+
+fn main() {}
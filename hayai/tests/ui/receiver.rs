@@ -0,0 +1,12 @@
+use hayai::prelude::*;
+
+struct Handlers;
+
+impl Handlers {
+    #[get("/items")]
+    async fn list_items(&self) -> String {
+        String::new()
+    }
+}
+
+fn main() {}
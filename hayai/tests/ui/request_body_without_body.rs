@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/users")]
+#[request_body(name = "UserBody")]
+async fn list_users() -> Vec<String> {
+    Vec::new()
+}
+
+fn main() {}
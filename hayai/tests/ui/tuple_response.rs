@@ -0,0 +1,18 @@
+use hayai::prelude::*;
+
+#[api_model]
+struct User {
+    id: i64,
+}
+
+#[api_model]
+struct Post {
+    title: String,
+}
+
+#[get("/users/{id}/profile")]
+async fn get_profile(id: i64) -> (User, Vec<Post>) {
+    (User { id }, Vec::new())
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use hayai::prelude::*;
+
+#[api_model]
+struct Filters {
+    name: String,
+}
+
+#[api_model]
+struct NewItem {
+    name: String,
+}
+
+#[post("/items")]
+async fn create_item(filters: Filters, body: NewItem) -> NewItem {
+    let _ = filters;
+    body
+}
+
+fn main() {}
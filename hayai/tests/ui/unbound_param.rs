@@ -0,0 +1,8 @@
+use hayai::prelude::*;
+
+#[get("/items")]
+async fn list_items(limit: i64) -> String {
+    limit.to_string()
+}
+
+fn main() {}
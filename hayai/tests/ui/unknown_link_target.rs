@@ -0,0 +1,14 @@
+use hayai::prelude::*;
+
+#[get("/items/{id}")]
+async fn get_item(id: i64) -> String {
+    id.to_string()
+}
+
+#[post("/items")]
+#[link(name = "GetItem", operation = get_itme, parameters(id = "$response.body#/id"))]
+async fn create_item() -> String {
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[api_model]
+struct Signup {
+    #[validate(min_lenght = 3)]
+    name: String,
+}
+
+fn main() {}
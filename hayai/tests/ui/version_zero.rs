@@ -0,0 +1,9 @@
+use hayai::prelude::*;
+
+#[get("/users")]
+#[version(0)]
+async fn list_users() -> Vec<String> {
+    Vec::new()
+}
+
+fn main() {}
@@ -0,0 +1,174 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+
+#[api_model]
+struct UserV1 {
+    id: i64,
+    name: String,
+}
+
+#[api_model]
+struct User {
+    id: i64,
+    first_name: String,
+    last_name: String,
+}
+
+#[get("/users")]
+#[version(1)]
+async fn list_users_v1() -> Vec<UserV1> {
+    vec![UserV1 { id: 1, name: "Ada Lovelace".into() }]
+}
+
+#[get("/users")]
+#[version(2)]
+async fn list_users_v2() -> Vec<User> {
+    vec![User { id: 1, first_name: "Ada".into(), last_name: "Lovelace".into() }]
+}
+
+#[get("/users/{id}")]
+#[version(1)]
+async fn get_user_v1(id: i64) -> UserV1 {
+    UserV1 { id, name: "Ada Lovelace".into() }
+}
+
+#[get("/users/{id}")]
+#[version(2)]
+async fn get_user_v2(id: i64) -> User {
+    User { id, first_name: "Ada".into(), last_name: "Lovelace".into() }
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .header_versioning(HeaderVersioning::vnd("myapp"))
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_LIST_USERS_V1)
+            .route(__HAYAI_ROUTE_LIST_USERS_V2)
+            .route(__HAYAI_ROUTE_GET_USER_V1)
+            .route(__HAYAI_ROUTE_GET_USER_V2))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn get(base: &str, path: &str, accept: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{base}{path}"));
+    if let Some(accept) = accept {
+        request = request.header("accept", accept);
+    }
+    request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_accept_header_picks_the_version() {
+    let base = spawn().await;
+    let resp = get(&base, "/users", Some("application/vnd.myapp.v1+json")).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["vary"], "accept");
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, serde_json::json!([{ "id": 1, "name": "Ada Lovelace" }]));
+
+    let body: Value = get(&base, "/users", Some("application/vnd.myapp.v2+json")).await.json().await.unwrap();
+    assert_eq!(body, serde_json::json!([{ "id": 1, "first_name": "Ada", "last_name": "Lovelace" }]));
+
+    // Path parameters reach the chosen handler
+    let body: Value = get(&base, "/users/7", Some("text/html, application/vnd.myapp.v1+json;q=0.9")).await.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "id": 7, "name": "Ada Lovelace" }));
+}
+
+#[tokio::test]
+async fn test_missing_version_gets_the_latest() {
+    let base = spawn().await;
+    for accept in [None, Some("application/json"), Some("*/*")] {
+        let body: Value = get(&base, "/users", accept).await.json().await.unwrap();
+        assert_eq!(body[0]["first_name"], "Ada", "{accept:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_version_is_not_acceptable() {
+    let base = spawn().await;
+    for (accept, requested) in [("application/vnd.myapp.v9+json", "9"), ("application/vnd.myapp.latest+json", "latest")] {
+        let resp = get(&base, "/users", Some(accept)).await;
+        assert_eq!(resp.status(), 406);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["error"], format!("Unsupported API version `{requested}`; supported: 1, 2"));
+    }
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_versions_are_documented() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let latest = &spec["paths"]["/users"]["get"];
+    assert_eq!(latest["operationId"], "list_users_v2");
+    assert_eq!(latest["x-api-version"], 2);
+    assert!(latest["responses"]["406"].is_object());
+    let v1 = &latest["x-api-versions"]["1"];
+    assert_eq!(v1["operationId"], "list_users_v1");
+    assert_eq!(v1["x-api-version"], 1);
+    assert_eq!(v1["responses"]["200"]["content"]["application/json"]["schema"]["items"]["$ref"], "#/components/schemas/UserV1");
+    assert_eq!(spec["paths"]["/users/{id}"]["get"]["x-api-versions"]["1"]["parameters"][0]["name"], "id");
+}
+
+mod v1 {
+    use hayai::prelude::*;
+
+    #[get("/stats")]
+    #[version(1)]
+    #[operation_id("stats_v1")]
+    #[cache(ttl = "30s")]
+    #[coalesce]
+    pub async fn stats() -> serde_json::Value {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        serde_json::json!({ "version": 1 })
+    }
+}
+
+mod v2 {
+    use hayai::prelude::*;
+
+    #[get("/stats")]
+    #[version(2)]
+    #[operation_id("stats_v2")]
+    #[cache(ttl = "30s")]
+    #[coalesce]
+    pub async fn stats() -> serde_json::Value {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        serde_json::json!({ "version": 2 })
+    }
+}
+
+#[tokio::test]
+async fn test_versions_keep_their_own_cache_and_coalesced_runs() {
+    let app = HayaiApp::new()
+        .header_versioning(HeaderVersioning::vnd("myapp"))
+        .include(HayaiRouter::new("").route(v1::__HAYAI_ROUTE_STATS).route(v2::__HAYAI_ROUTE_STATS))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let base = format!("http://{addr}");
+
+    // In flight together, each version runs its own handler
+    let (first, second) = tokio::join!(
+        get(&base, "/stats", Some("application/vnd.myapp.v1+json")),
+        get(&base, "/stats", Some("application/vnd.myapp.v2+json")),
+    );
+    assert!(first.headers().get("x-coalesced").is_none());
+    assert!(second.headers().get("x-coalesced").is_none());
+    assert_eq!(first.json::<Value>().await.unwrap()["version"], 1);
+    assert_eq!(second.json::<Value>().await.unwrap()["version"], 2);
+
+    // And hits come from the requested version's entry
+    for version in [1, 2] {
+        let resp = get(&base, "/stats", Some(&format!("application/vnd.myapp.v{version}+json"))).await;
+        assert_eq!(resp.headers()["x-cache"], "HIT");
+        assert_eq!(resp.json::<Value>().await.unwrap()["version"], version);
+    }
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "webhooks")]
+
+use hayai::prelude::*;
+use hayai::webhook::{constant_time_eq, hmac_hex, GitHubSha256, HmacSha256, WebhookSecret};
+use hayai::axum;
+use serde_json::Value;
+
+struct PaymentsSecret(&'static str);
+
+impl WebhookSecret for PaymentsSecret {
+    const HEADER: &'static str = "X-Payments-Signature";
+
+    fn secret(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+struct GitHubSecret(&'static str);
+
+impl WebhookSecret for GitHubSecret {
+    fn secret(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+#[api_model]
+struct PaymentEvent {
+    #[validate(min_length = 1)]
+    id: String,
+    #[validate(minimum = 0)]
+    amount_cents: i64,
+}
+
+#[api_model]
+struct PushEvent {
+    #[serde(rename = "ref")]
+    git_ref: String,
+}
+
+#[post("/webhooks/payments")]
+async fn payment_event(event: SignedPayload<PaymentEvent, HmacSha256<PaymentsSecret>>) -> Value {
+    serde_json::json!({ "id": event.id, "amount_cents": event.amount_cents, "raw_len": event.raw().len() })
+}
+
+#[post("/webhooks/github")]
+async fn github_push(event: SignedPayload<PushEvent, GitHubSha256<GitHubSecret>>) -> Value {
+    serde_json::json!({ "ref": event.into_inner().git_ref })
+}
+
+const SECRET: &str = "whsec_test";
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .dep(PaymentsSecret(SECRET))
+        .dep(GitHubSecret(SECRET))
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_PAYMENT_EVENT)
+            .route(__HAYAI_ROUTE_GITHUB_PUSH))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn deliver(base: &str, path: &str, header: &str, signature: String, body: &str) -> reqwest::Response {
+    reqwest::Client::new().post(format!("{base}{path}"))
+        .header("content-type", "application/json")
+        .header(header, signature)
+        .body(body.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_valid_signature_parses_and_validates() {
+    let base = spawn().await;
+    let body = r#"{"id":"evt_1","amount_cents":1250}"#;
+    let res = deliver(&base, "/webhooks/payments", "X-Payments-Signature", hmac_hex(SECRET.as_bytes(), body.as_bytes()), body).await;
+    assert_eq!(res.status(), 201);
+    let json: Value = res.json().await.unwrap();
+    assert_eq!(json, serde_json::json!({ "id": "evt_1", "amount_cents": 1250, "raw_len": body.len() }));
+
+    // Signed but invalid payloads still fail validation
+    let body = r#"{"id":"","amount_cents":-1}"#;
+    let res = deliver(&base, "/webhooks/payments", "X-Payments-Signature", hmac_hex(SECRET.as_bytes(), body.as_bytes()), body).await;
+    assert_eq!(res.status(), 422);
+}
+
+#[tokio::test]
+async fn test_tampered_body_rejected() {
+    let base = spawn().await;
+    let signed = r#"{"id":"evt_1","amount_cents":1250}"#;
+    let signature = hmac_hex(SECRET.as_bytes(), signed.as_bytes());
+    let tampered = r#"{"id":"evt_1","amount_cents":9999}"#;
+
+    let mut messages = Vec::new();
+    for (header, signature, body) in [
+        ("X-Payments-Signature", signature.clone(), tampered),
+        ("X-Payments-Signature", hmac_hex(b"wrong secret", signed.as_bytes()), signed),
+        ("X-Payments-Signature", "not hex".to_string(), signed),
+        ("X-Other", signature, signed),
+    ] {
+        let res = deliver(&base, "/webhooks/payments", header, signature, body).await;
+        assert_eq!(res.status(), 401);
+        let json: Value = res.json().await.unwrap();
+        messages.push(json["error"].clone());
+    }
+    // The same answer whatever was wrong
+    assert!(messages.iter().all(|m| m == "Invalid webhook signature"), "{messages:?}");
+}
+
+#[tokio::test]
+async fn test_github_prefixed_signature() {
+    let base = spawn().await;
+    let body = r#"{"ref":"refs/heads/main"}"#;
+    let signature = hmac_hex(SECRET.as_bytes(), body.as_bytes());
+
+    let res = deliver(&base, "/webhooks/github", "X-Hub-Signature-256", format!("sha256={signature}"), body).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.json::<Value>().await.unwrap()["ref"], "refs/heads/main");
+
+    let res = deliver(&base, "/webhooks/github", "X-Hub-Signature-256", signature, body).await;
+    assert_eq!(res.status(), 401);
+}
+
+#[test]
+fn test_compare_helper() {
+    let signature = hmac_hex(SECRET.as_bytes(), b"{}");
+    assert!(constant_time_eq(signature.as_bytes(), hmac_hex(SECRET.as_bytes(), b"{}").as_bytes()));
+    let mut flipped = signature.clone().into_bytes();
+    flipped[63] ^= 1;
+    assert!(!constant_time_eq(signature.as_bytes(), &flipped));
+    assert!(!constant_time_eq(signature.as_bytes(), &signature.as_bytes()[..32]));
+    assert!(constant_time_eq(b"", b""));
+}
+
+#[tokio::test]
+async fn test_spec_documents_signature_header() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let operation = &spec["paths"]["/webhooks/payments"]["post"];
+    let header = operation["parameters"].as_array().unwrap().iter()
+        .find(|p| p["in"] == "header")
+        .unwrap();
+    assert_eq!(header["name"], "X-Payments-Signature");
+    assert_eq!(header["required"], true);
+    assert_eq!(operation["requestBody"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/PaymentEvent");
+    assert!(operation["responses"]["401"].is_object());
+    let github = &spec["paths"]["/webhooks/github"]["post"]["parameters"][0];
+    assert_eq!(github["name"], "X-Hub-Signature-256");
+}
@@ -0,0 +1,121 @@
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::{json, Value};
+use std::time::{Duration, UNIX_EPOCH};
+
+#[get("/items")]
+async fn list_items() -> Vec<String> {
+    vec!["a".to_string()]
+}
+
+#[derive(Clone, Default)]
+struct CapturedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedLog {
+    fn lines(&self) -> Vec<Value> {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+fn well_known(robots: RobotsTxt) -> WellKnown {
+    // 2030-01-01T00:00:00Z
+    let expires = UNIX_EPOCH + Duration::from_secs(1_893_456_000);
+    WellKnown::new()
+        .robots_txt(robots)
+        .security_txt("mailto:security@example.com", expires)
+        .well_known_json("/.well-known/api-descriptor", json!({ "name": "items", "version": 1 }))
+        .well_known_json("/.well-known/build", || json!({ "built": "now" }))
+}
+
+async fn spawn(app: HayaiApp) -> String {
+    let app = app.include(HayaiRouter::new("").route(__HAYAI_ROUTE_LIST_ITEMS)).into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn fetch(base: &str, path: &str) -> (u16, String, String) {
+    let res = reqwest::get(format!("{base}{path}")).await.unwrap();
+    let content_type = res.headers()["content-type"].to_str().unwrap().to_string();
+    (res.status().as_u16(), content_type, res.text().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_text_documents() {
+    let base = spawn(HayaiApp::new().well_known(well_known(RobotsTxt::DenyAll))).await;
+
+    let (status, content_type, body) = fetch(&base, "/robots.txt").await;
+    assert_eq!(status, 200);
+    assert_eq!(content_type, "text/plain; charset=utf-8");
+    assert_eq!(body, "User-agent: *\nDisallow: /\n");
+
+    let (status, content_type, body) = fetch(&base, "/.well-known/security.txt").await;
+    assert_eq!(status, 200);
+    assert_eq!(content_type, "text/plain; charset=utf-8");
+    assert_eq!(body, "Contact: mailto:security@example.com\nExpires: 2030-01-01T00:00:00.000Z\n");
+
+    let custom = "User-agent: *\nDisallow: /admin\n";
+    let base = spawn(HayaiApp::new().well_known(well_known(custom.into()))).await;
+    assert_eq!(fetch(&base, "/robots.txt").await.2, custom);
+}
+
+#[tokio::test]
+async fn test_json_documents() {
+    let base = spawn(HayaiApp::new().well_known(well_known(RobotsTxt::DenyAll))).await;
+
+    let (status, content_type, body) = fetch(&base, "/.well-known/api-descriptor").await;
+    assert_eq!(status, 200);
+    assert_eq!(content_type, "application/json");
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), json!({ "name": "items", "version": 1 }));
+
+    let (_, _, body) = fetch(&base, "/.well-known/build").await;
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), json!({ "built": "now" }));
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn test_not_in_spec() {
+    let base = spawn(HayaiApp::new().well_known(well_known(RobotsTxt::DenyAll))).await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let paths: Vec<&String> = spec["paths"].as_object().unwrap().keys().collect();
+    assert_eq!(paths, ["/items"]);
+}
+
+#[tokio::test]
+async fn test_left_out_of_access_log() {
+    let log = CapturedLog::default();
+    let app = HayaiApp::new()
+        .access_log(hayai::access_log::AccessLogFormat::Json)
+        .access_log_writer(log.clone())
+        .well_known(well_known(RobotsTxt::DenyAll));
+    let base = spawn(app).await;
+    for path in ["/robots.txt", "/.well-known/security.txt", "/.well-known/api-descriptor", "/items"] {
+        fetch(&base, path).await;
+    }
+    let paths: Vec<Value> = log.lines().iter().map(|line| line["path"].clone()).collect();
+    assert_eq!(paths, [json!("/items")]);
+
+    let log = CapturedLog::default();
+    let app = HayaiApp::new()
+        .access_log(hayai::access_log::AccessLogFormat::Json)
+        .access_log_writer(log.clone())
+        .well_known(well_known(RobotsTxt::DenyAll).log_requests());
+    let base = spawn(app).await;
+    fetch(&base, "/robots.txt").await;
+    assert_eq!(log.lines()[0]["path"], "/robots.txt");
+}
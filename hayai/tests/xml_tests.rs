@@ -0,0 +1,102 @@
+#![cfg(feature = "xml")]
+
+use hayai::prelude::*;
+use hayai::axum;
+use serde_json::Value;
+
+#[api_model]
+struct CreateUser {
+    #[validate(min_length = 2)]
+    name: String,
+    age: u32,
+}
+
+#[api_model]
+struct User {
+    id: i64,
+    name: String,
+    age: u32,
+}
+
+#[post("/partner/users")]
+#[consumes("application/xml")]
+#[produces("application/xml", root = "user")]
+async fn import_user(body: CreateUser) -> User {
+    User { id: 1, name: body.name, age: body.age }
+}
+
+#[post("/users")]
+async fn create_user(body: CreateUser) -> User {
+    User { id: 2, name: body.name, age: body.age }
+}
+
+async fn spawn() -> String {
+    let app = HayaiApp::new()
+        .strict_spec(true)
+        .include(HayaiRouter::new("")
+            .route(__HAYAI_ROUTE_IMPORT_USER)
+            .route(__HAYAI_ROUTE_CREATE_USER))
+        .into_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn post_xml(base: &str, body: &str) -> reqwest::Response {
+    reqwest::Client::new().post(format!("{base}/partner/users"))
+        .header("content-type", "application/xml")
+        .body(body.to_string())
+        .send().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_xml_body_and_response() {
+    let base = spawn().await;
+    let resp = post_xml(&base, "<CreateUser><name>Ada</name><age>36</age></CreateUser>").await;
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.headers()["content-type"], "application/xml");
+    assert_eq!(resp.text().await.unwrap(), "<user><id>1</id><name>Ada</name><age>36</age></user>");
+
+    // The JSON route on the same models is unaffected
+    let resp = reqwest::Client::new().post(format!("{base}/users"))
+        .json(&serde_json::json!({ "name": "Grace", "age": 45 }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.json::<Value>().await.unwrap(), serde_json::json!({ "id": 2, "name": "Grace", "age": 45 }));
+}
+
+#[tokio::test]
+async fn test_invalid_xml_bodies_are_rejected() {
+    let base = spawn().await;
+    let resp = post_xml(&base, "<CreateUser><name>Ada</name><age>36</CreateUser>").await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().starts_with("Invalid XML body: "), "{body}");
+
+    let resp = post_xml(&base, "<CreateUser><name>A</name><age>36</age></CreateUser>").await;
+    assert_eq!(resp.status(), 422);
+
+    let resp = reqwest::Client::new().post(format!("{base}/partner/users"))
+        .json(&serde_json::json!({ "name": "Ada", "age": 36 }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 415);
+}
+
+#[tokio::test]
+async fn test_xml_media_types_are_documented() {
+    let base = spawn().await;
+    let spec: Value = reqwest::get(format!("{base}/openapi.json")).await.unwrap().json().await.unwrap();
+    let operation = &spec["paths"]["/partner/users"]["post"];
+    assert_eq!(
+        operation["requestBody"]["content"]["application/xml"]["schema"]["$ref"],
+        "#/components/schemas/CreateUser",
+    );
+    assert_eq!(
+        operation["responses"]["201"]["content"]["application/xml"]["schema"]["$ref"],
+        "#/components/schemas/User",
+    );
+    assert!(operation["responses"]["422"]["content"]["application/json"].is_object());
+    let json_route = &spec["paths"]["/users"]["post"];
+    assert!(json_route["responses"]["201"]["content"]["application/json"].is_object());
+}